@@ -12,7 +12,6 @@ use ratatui::{
     Frame, Terminal,
 };
 
-use crate::ui::events::Events;
 use crate::ui::{
     AnimatedText, AnimatedTextState, ScannerText, ScannerTextState, SparklineBraille,
     BrailleChart, Dataset, Axis, AnimationType
@@ -53,8 +52,7 @@ impl Default for App {
         let sparkline_data = (0..100)
             .map(|i| {
                 let x = i as f64 * 0.1;
-                let value = ((x * 0.8).sin() * 50.0 + 50.0) as u64;
-                value
+                ((x * 0.8).sin() * 50.0 + 50.0) as u64
             })
             .collect();
 
@@ -104,7 +102,7 @@ impl App {
         // Actualizar datos de ejemplo - Simular datos en tiempo real
         let last_value = self.sparkline_data.last().unwrap_or(&50);
         let new_value = (*last_value as f64 + (rand::random::<f64>() * 10.0 - 5.0)) as u64;
-        self.sparkline_data.push(new_value.max(1).min(100));
+        self.sparkline_data.push(new_value.clamp(1, 100));
         if self.sparkline_data.len() > 100 {
             self.sparkline_data.remove(0);
         }
@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Organización propietaria de un sistema autónomo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsnOwner {
+    /// Número de sistema autónomo
+    pub asn: u32,
+    /// Código de país ISO del registro
+    pub country: String,
+    /// Nombre de la organización (p. ej. "AMAZON-02")
+    pub organization: String,
+}
+
+/// Base de datos de rangos IP → ASN en el formato TSV de iptoasn.com
+/// (`inicio  fin  ASN  país  descripción`, IPv4 e IPv6)
+#[derive(Debug, Default)]
+pub struct AsnDatabase {
+    /// Rangos IPv4 ordenados por inicio
+    v4: Vec<(u32, u32, u32)>,
+    /// Rangos IPv6 ordenados por inicio
+    v6: Vec<(u128, u128, u32)>,
+    /// Propietario de cada ASN
+    owners: HashMap<u32, AsnOwner>,
+}
+
+impl AsnDatabase {
+    /// Cargar la base de datos desde un archivo TSV descomprimido
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| AppError::ConfigurationError(format!(
+            "No se pudo leer la base de datos de ASN {}: {} (descárgala de https://iptoasn.com y descomprímela)",
+            path.display(), e
+        )))?;
+        let database = Self::parse(&content);
+        if database.v4.is_empty() && database.v6.is_empty() {
+            return Err(AppError::ConfigurationError(format!(
+                "La base de datos de ASN {} no contiene rangos válidos (se espera el TSV de iptoasn.com)",
+                path.display()
+            )).into());
+        }
+        Ok(database)
+    }
+
+    /// Interpretar el TSV; las líneas mal formadas y los rangos sin enrutar (ASN 0) se omiten
+    pub fn parse(content: &str) -> Self {
+        let mut database = Self::default();
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [start, end, asn, country, organization] = fields[..] else {
+                continue;
+            };
+            let Ok(asn) = asn.trim().parse::<u32>() else {
+                continue;
+            };
+            if asn == 0 {
+                continue;
+            }
+            match (start.trim().parse::<IpAddr>(), end.trim().parse::<IpAddr>()) {
+                (Ok(IpAddr::V4(start)), Ok(IpAddr::V4(end))) => database.v4.push((start.into(), end.into(), asn)),
+                (Ok(IpAddr::V6(start)), Ok(IpAddr::V6(end))) => database.v6.push((start.into(), end.into(), asn)),
+                _ => continue,
+            }
+            database.owners.entry(asn).or_insert_with(|| AsnOwner {
+                asn,
+                country: country.trim().to_string(),
+                organization: organization.trim().to_string(),
+            });
+        }
+        database.v4.sort_unstable();
+        database.v6.sort_unstable();
+        database
+    }
+
+    /// Propietario de la dirección, si pertenece a algún rango enrutado
+    pub fn lookup(&self, ip: IpAddr) -> Option<&AsnOwner> {
+        let asn = match ip {
+            IpAddr::V4(ip) => find_range(&self.v4, u32::from(ip)),
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => find_range(&self.v4, u32::from(ip)),
+                None => find_range(&self.v6, u128::from(ip)),
+            },
+        }?;
+        self.owners.get(&asn)
+    }
+
+    /// Agrupar los destinos remotos por organización, de más a menos conexiones.
+    /// Las direcciones locales, privadas y de loopback no salen de la red y se omiten.
+    pub fn group_egress(&self, remotes: impl IntoIterator<Item = SocketAddr>) -> Vec<AsnEgress> {
+        let mut groups: BTreeMap<Option<u32>, (HashSet<SocketAddr>, HashSet<IpAddr>)> = BTreeMap::new();
+        for remote in remotes {
+            // ::ffff:a.b.c.d cuenta como el mismo host que a.b.c.d
+            let ip = remote.ip().to_canonical();
+            if !is_public(ip) {
+                continue;
+            }
+            let asn = self.lookup(ip).map(|owner| owner.asn);
+            let (connections, hosts) = groups.entry(asn).or_default();
+            connections.insert(SocketAddr::new(ip, remote.port()));
+            hosts.insert(ip);
+        }
+
+        let mut egress: Vec<AsnEgress> = groups
+            .into_iter()
+            .map(|(asn, (connections, hosts))| {
+                let owner = asn.and_then(|asn| self.owners.get(&asn));
+                AsnEgress {
+                    asn,
+                    organization: owner.map(|owner| owner.organization.clone()),
+                    country: owner.map(|owner| owner.country.clone()),
+                    connections: connections.len(),
+                    hosts: hosts.len(),
+                    unseen: false,
+                }
+            })
+            .collect();
+        egress.sort_by(|a, b| b.connections.cmp(&a.connections).then(a.asn.cmp(&b.asn)));
+        egress
+    }
+}
+
+/// Buscar el rango que contiene la dirección (los rangos no se solapan)
+fn find_range<T: Ord + Copy>(ranges: &[(T, T, u32)], ip: T) -> Option<u32> {
+    let index = ranges.partition_point(|&(start, _, _)| start <= ip).checked_sub(1)?;
+    let (_, end, asn) = ranges[index];
+    (ip <= end).then_some(asn)
+}
+
+/// Indica si la dirección es enrutable en Internet (no local, privada ni de loopback)
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+            || ip.is_broadcast() || ip.is_multicast() || ip.is_documentation()),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            // fc00::/7 (locales únicas) y fe80::/10 (enlace local)
+            None => !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast()
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80),
+        },
+    }
+}
+
+/// Conexiones salientes hacia una misma organización
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsnEgress {
+    /// ASN de destino (None si la dirección no aparece en la base de datos)
+    pub asn: Option<u32>,
+    /// Organización propietaria del ASN
+    pub organization: Option<String>,
+    /// País del registro del ASN
+    pub country: Option<String>,
+    /// Conexiones distintas (dirección y puerto remotos)
+    pub connections: usize,
+    /// Direcciones IP distintas
+    pub hosts: usize,
+    /// El ASN no aparece en la línea base del proceso
+    #[serde(default)]
+    pub unseen: bool,
+}
+
+impl AsnEgress {
+    /// Texto legible ("AS16509 AMAZON-02 (US)" o "ASN desconocido")
+    pub fn label(&self) -> String {
+        match (self.asn, &self.organization) {
+            (Some(asn), Some(organization)) => match self.country.as_deref().filter(|c| !c.is_empty() && *c != "None") {
+                Some(country) => format!("AS{} {} ({})", asn, organization, country),
+                None => format!("AS{} {}", asn, organization),
+            },
+            (Some(asn), None) => format!("AS{}", asn),
+            (None, _) => "ASN desconocido".to_string(),
+        }
+    }
+}
+
+/// ASNs a los que se ha conectado cada proceso en ejecuciones anteriores
+/// (~/.shadowtrace/asn_baseline.json)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AsnBaseline {
+    /// ASNs conocidos por nombre de proceso
+    processes: BTreeMap<String, BTreeSet<u32>>,
+    /// Archivo de la línea base
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl AsnBaseline {
+    /// Archivo de la línea base por defecto
+    pub fn default_path() -> Option<PathBuf> {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("asn_baseline.json"))
+    }
+
+    /// Cargar la línea base de un archivo, o empezar una vacía si no existe
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut baseline = if path.exists() {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("No se pudo leer la línea base de ASN {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Línea base de ASN inválida {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        baseline.path = Some(path.to_path_buf());
+        Ok(baseline)
+    }
+
+    /// Cargar la línea base por defecto
+    pub fn load_default() -> Result<Self> {
+        let path = Self::default_path()
+            .ok_or_else(|| anyhow::anyhow!("No se pudo determinar el directorio home"))?;
+        Self::load(&path)
+    }
+
+    /// Marcar los grupos cuyo ASN el proceso no había usado antes e incorporarlos a la línea base.
+    /// La primera vez que se ve un proceso solo se registra su línea base, sin marcar nada.
+    /// Devuelve false si el proceso no tenía línea base.
+    pub fn mark_unseen(&mut self, process_name: &str, egress: &mut [AsnEgress]) -> bool {
+        let known = self.processes.contains_key(process_name);
+        // Sin destinos identificados no hay nada que aprender
+        if egress.iter().all(|group| group.asn.is_none()) {
+            return known;
+        }
+        let asns = self.processes.entry(process_name.to_string()).or_default();
+        for group in egress.iter_mut() {
+            let Some(asn) = group.asn else {
+                continue;
+            };
+            group.unseen = known && !asns.contains(&asn);
+            asns.insert(asn);
+        }
+        known
+    }
+
+    /// Guardar la línea base
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
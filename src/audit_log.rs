@@ -0,0 +1,115 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Tamaño a partir del cual el registro se rota (audit.log → audit.log.1)
+pub const AUDIT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Archivos rotados que se conservan (audit.log.1 ... audit.log.N)
+const AUDIT_LOG_ROTATIONS: usize = 5;
+
+/// Ruta del registro fijada al arrancar (`--audit-log`); None = ruta por defecto
+static AUDIT_LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializa las escrituras de los distintos hilos (CLI y TUI)
+static AUDIT_LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Acción registrada en el registro de auditoría
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Momento de la acción
+    pub timestamp: DateTime<Utc>,
+    /// Acción realizada (monitor, audit, kill, export...)
+    pub action: String,
+    /// PID, nombre de proceso, binario o archivo sobre el que se actuó
+    pub target: Option<String>,
+    /// Usuario del sistema que ejecuta ShadowTrace
+    pub user: Option<String>,
+    /// Resultado ("ok", "iniciado" o el mensaje de error)
+    pub outcome: String,
+}
+
+impl AuditEvent {
+    /// Crear un evento con la hora y el usuario actuales
+    pub fn new(action: &str, target: Option<String>, outcome: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            target,
+            user: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok(),
+            outcome: outcome.into(),
+        }
+    }
+
+    /// Evento con el resultado de una operación
+    pub fn from_result<T, E: std::fmt::Display>(action: &str, target: Option<String>, result: &std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Self::new(action, target, "ok"),
+            Err(e) => Self::new(action, target, format!("error: {}", e)),
+        }
+    }
+}
+
+/// Fijar la ruta del registro de auditoría (solo tiene efecto la primera vez)
+pub fn set_audit_log_path(path: PathBuf) {
+    let _ = AUDIT_LOG_PATH.set(path);
+}
+
+/// Ruta del registro de auditoría (~/.shadowtrace/audit.log salvo `--audit-log`)
+pub fn audit_log_path() -> Option<PathBuf> {
+    AUDIT_LOG_PATH
+        .get()
+        .cloned()
+        .or_else(|| BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("audit.log")))
+}
+
+/// Añadir una acción al registro de auditoría (una línea JSON por acción).
+/// Un fallo al escribir se avisa en el log pero nunca interrumpe la acción registrada.
+pub fn audit_log(event: AuditEvent) {
+    let Some(path) = audit_log_path() else {
+        tracing::warn!("No se pudo determinar la ruta del registro de auditoría");
+        return;
+    };
+    let _guard = AUDIT_LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Err(e) = append_event(&path, &event) {
+        tracing::warn!("No se pudo escribir en el registro de auditoría {}: {}", path.display(), e);
+    }
+}
+
+fn append_event(path: &Path, event: &AuditEvent) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() >= AUDIT_LOG_MAX_BYTES) {
+        rotate(path)?;
+    }
+
+    // Solo legible por el usuario: registra qué procesos y binarios se inspeccionaron
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Desplazar audit.log.N-1 → audit.log.N ... audit.log → audit.log.1 (se descarta el más antiguo)
+fn rotate(path: &Path) -> Result<()> {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    for n in (1..AUDIT_LOG_ROTATIONS).rev() {
+        if rotated(n).exists() {
+            fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))?;
+    Ok(())
+}
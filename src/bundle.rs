@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::reports::Report;
+
+/// Nombre del manifiesto dentro del paquete
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Nombre del archivo de sumas de comprobación (formato de `sha256sum -c`)
+pub const CHECKSUMS_NAME: &str = "SHA256SUMS";
+
+/// Artefacto incluido en el paquete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Nombre dentro del zip
+    pub name: String,
+    /// Tamaño en bytes
+    pub size: u64,
+    /// SHA-256 en hexadecimal
+    pub sha256: String,
+}
+
+/// Descripción del caso y de los artefactos del paquete (`manifest.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Versión de ShadowTrace que generó el paquete
+    pub shadowtrace_version: String,
+    /// ID del reporte
+    pub report_id: String,
+    /// Título del reporte
+    pub title: String,
+    /// Momento en que se creó el paquete
+    pub created_at: DateTime<Utc>,
+    /// Artefactos incluidos
+    pub files: Vec<BundleFile>,
+}
+
+/// Empaquetar en un zip los artefactos generados para un reporte, con un manifiesto
+/// y un archivo `SHA256SUMS` que cubre los artefactos y el manifiesto.
+/// Los artefactos se guardan con su nombre de archivo, sin directorios.
+pub fn write_bundle(path: &Path, report: &Report, artifacts: &[PathBuf]) -> Result<BundleManifest> {
+    let mut contents: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut names = HashSet::new();
+    for artifact in artifacts {
+        let name = artifact
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .with_context(|| format!("Artefacto sin nombre de archivo: {}", artifact.display()))?;
+        // El mismo archivo no se incluye dos veces
+        if !names.insert(name.clone()) {
+            continue;
+        }
+        let data = fs::read(artifact).with_context(|| format!("No se pudo leer {}", artifact.display()))?;
+        contents.push((name, data));
+    }
+
+    let manifest = BundleManifest {
+        shadowtrace_version: env!("CARGO_PKG_VERSION").to_string(),
+        report_id: report.id.clone(),
+        title: report.title.clone(),
+        created_at: Utc::now(),
+        files: contents
+            .iter()
+            .map(|(name, data)| BundleFile { name: name.clone(), size: data.len() as u64, sha256: sha256_hex(data) })
+            .collect(),
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+
+    let mut checksums = String::new();
+    for file in &manifest.files {
+        checksums.push_str(&format!("{}  {}\n", file.sha256, file.name));
+    }
+    checksums.push_str(&format!("{}  {}\n", sha256_hex(&manifest_json), MANIFEST_NAME));
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    // Se escribe en un temporal y se renombra para no dejar un zip a medias
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let file = File::create(&tmp).with_context(|| format!("No se pudo crear {}", tmp.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in &contents {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(&manifest_json)?;
+    zip.start_file(CHECKSUMS_NAME, options)?;
+    zip.write_all(checksums.as_bytes())?;
+    zip.finish()?;
+    fs::rename(&tmp, path)?;
+
+    Ok(manifest)
+}
+
+/// SHA-256 de unos datos en hexadecimal
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
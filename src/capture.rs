@@ -0,0 +1,452 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::file_monitor::{self, FileEvent, FileMonitor, FileOperation};
+use crate::network::{self, NetworkMonitor};
+
+/// Orden en que `auto` prueba los métodos de captura, del más fiel al más básico
+const AUTO_ORDER: [CaptureBackend; 3] = [CaptureBackend::Ebpf, CaptureBackend::Fanotify, CaptureBackend::Procfs];
+
+/// Método con el que se capturan los archivos y conexiones reales de los procesos
+/// monitoreados (`--capture-backend`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureBackend {
+    /// El más fiel de los disponibles en este equipo
+    #[default]
+    Auto,
+    /// Recorrer /proc cada segundo: archivos abiertos y conexiones (sin privilegios)
+    Procfs,
+    /// Eventos de apertura y escritura del kernel (Linux, requiere CAP_SYS_ADMIN);
+    /// las conexiones se siguen leyendo de /proc
+    Fanotify,
+    /// Sondas eBPF del kernel (Linux, requiere CAP_BPF); esta compilación no incluye
+    /// el cargador, así que nunca está disponible
+    Ebpf,
+}
+
+impl CaptureBackend {
+    /// Nombre del método
+    pub fn label(self) -> &'static str {
+        match self {
+            CaptureBackend::Auto => "auto",
+            CaptureBackend::Procfs => "procfs",
+            CaptureBackend::Fanotify => "fanotify",
+            CaptureBackend::Ebpf => "ebpf",
+        }
+    }
+
+    /// Comprobar si el método se puede usar en este equipo; el error explica por qué no
+    pub fn probe(self) -> std::result::Result<(), String> {
+        match self {
+            CaptureBackend::Auto => Ok(()),
+            CaptureBackend::Procfs => probe_procfs(),
+            CaptureBackend::Fanotify => probe_fanotify(),
+            CaptureBackend::Ebpf => Err("esta compilación de ShadowTrace no incluye el cargador eBPF".to_string()),
+        }
+    }
+
+    /// Resolver `auto` al primer método disponible, o comprobar el que se eligió.
+    /// Si no hay ninguno, `auto` se queda con procfs, que en esta plataforma no registra
+    /// actividad; un método elegido explícitamente que no está disponible es un error
+    pub fn resolve(self) -> Result<Self> {
+        self.resolve_with(CaptureBackend::probe)
+    }
+
+    /// `resolve` con la comprobación de disponibilidad indicada
+    fn resolve_with(self, probe: impl Fn(Self) -> std::result::Result<(), String>) -> Result<Self> {
+        if self != CaptureBackend::Auto {
+            probe(self).map_err(|reason| AppError::ConfigurationError(
+                format!("La captura {} no está disponible: {}", self.label(), reason)
+            ))?;
+            return Ok(self);
+        }
+
+        for backend in AUTO_ORDER {
+            match probe(backend) {
+                Ok(()) => {
+                    tracing::info!("Captura de eventos: {}", backend.label());
+                    return Ok(backend);
+                }
+                Err(reason) => tracing::debug!("Captura {} no disponible: {}", backend.label(), reason),
+            }
+        }
+        tracing::warn!("Ningún método de captura disponible: no se registrarán archivos ni conexiones");
+        Ok(CaptureBackend::Procfs)
+    }
+
+    /// Abrir la fuente de eventos del método
+    pub fn open(self) -> Result<Box<dyn CaptureSource>> {
+        match self {
+            CaptureBackend::Auto => self.resolve()?.open(),
+            CaptureBackend::Procfs => Ok(Box::new(ProcfsCapture)),
+            #[cfg(target_os = "linux")]
+            CaptureBackend::Fanotify => Ok(Box::new(FanotifyCapture::new().map_err(|reason| {
+                AppError::ConfigurationError(format!("No se pudo iniciar la captura fanotify: {}", reason))
+            })?)),
+            backend => Err(AppError::ConfigurationError(format!(
+                "La captura {} no está disponible: {}",
+                backend.label(),
+                backend.probe().err().unwrap_or_default()
+            )).into()),
+        }
+    }
+}
+
+/// Fuente de captura compartida entre los hilos de monitoreo
+pub type SharedCapture = Arc<Mutex<Box<dyn CaptureSource>>>;
+
+/// Fuente de los eventos reales de archivos y red de los procesos monitoreados.
+/// La comparten el modo CLI, la auditoría y los hilos de la TUI
+pub trait CaptureSource: Send {
+    /// Método que implementa la fuente
+    fn backend(&self) -> CaptureBackend;
+
+    /// Registrar la actividad de archivos del proceso desde la última llamada
+    fn record_files(&mut self, file_monitor: &mut FileMonitor, pid: u32);
+
+    /// Registrar las conexiones nuevas del proceso, sus cambios de estado y las que se cerraron
+    fn record_connections(&mut self, network_monitor: &mut NetworkMonitor, pid: u32) {
+        network_monitor.sync_connections(pid, network::enumerate_connections(pid));
+    }
+}
+
+/// Captura recorriendo /proc/<pid>/fd y /proc/<pid>/net: solo ve lo que está abierto
+/// en el momento de cada recorrido
+pub struct ProcfsCapture;
+
+impl CaptureSource for ProcfsCapture {
+    fn backend(&self) -> CaptureBackend {
+        CaptureBackend::Procfs
+    }
+
+    fn record_files(&mut self, file_monitor: &mut FileMonitor, pid: u32) {
+        record_open_files(file_monitor, pid);
+    }
+}
+
+/// Registrar los archivos que el proceso tiene abiertos y que aún no se conocían
+fn record_open_files(file_monitor: &mut FileMonitor, pid: u32) {
+    let already_open: Vec<String> = file_monitor
+        .get_open_files_for_pid(pid)
+        .into_iter()
+        .cloned()
+        .collect();
+    for path in file_monitor::enumerate_open_files(pid) {
+        if already_open.contains(&path) {
+            continue;
+        }
+        file_monitor.record_event(FileEvent {
+            pid,
+            path,
+            operation: FileOperation::Open,
+            timestamp: chrono::Utc::now(),
+            size: None,
+            success: true,
+            entropy: None,
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn probe_procfs() -> std::result::Result<(), String> {
+    if std::path::Path::new("/proc/self/fd").is_dir() {
+        Ok(())
+    } else {
+        Err("/proc no está montado".to_string())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_procfs() -> std::result::Result<(), String> {
+    Err("solo existe en Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn probe_fanotify() -> std::result::Result<(), String> {
+    FanotifyCapture::new().map(|_| ())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_fanotify() -> std::result::Result<(), String> {
+    Err("solo existe en Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+pub use self::fanotify::FanotifyCapture;
+
+#[cfg(target_os = "linux")]
+mod fanotify {
+    use std::fs::File;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    use chrono::Utc;
+
+    use super::{record_open_files, CaptureBackend, CaptureSource};
+    use crate::file_monitor::{FileEvent, FileMonitor, FileOperation};
+
+    /// Eventos que se piden al kernel
+    const EVENT_MASK: u64 = libc::FAN_OPEN | libc::FAN_CLOSE_WRITE | libc::FAN_CLOSE_NOWRITE;
+
+    /// Sistemas de archivos virtuales que no se vigilan: no guardan archivos del usuario
+    /// y el propio ShadowTrace los lee sin parar
+    const PSEUDO_FILESYSTEMS: &[&str] = &[
+        "proc", "sysfs", "cgroup", "cgroup2", "devpts", "mqueue", "debugfs", "tracefs",
+        "securityfs", "pstore", "bpf", "configfs", "fusectl", "hugetlbfs", "autofs",
+        "binfmt_misc", "nsfs", "efivarfs", "rpc_pipefs",
+    ];
+
+    /// Tiempo que se guardan los eventos de procesos por los que aún no se ha preguntado;
+    /// así no se pierde lo que hace un proceso recién lanzado antes del primer recorrido
+    const PENDING_TTL_SECS: i64 = 5;
+
+    /// Eventos pendientes como máximo; se descartan los más antiguos
+    const MAX_PENDING_EVENTS: usize = 50_000;
+
+    /// Captura con fanotify: el kernel avisa de cada apertura y cierre en los sistemas
+    /// de archivos montados, así que se ven también los archivos que el proceso abre y
+    /// cierra entre dos recorridos de /proc
+    pub struct FanotifyCapture {
+        fd: OwnedFd,
+        pending: Vec<FileEvent>,
+        overflowed: bool,
+    }
+
+    impl FanotifyCapture {
+        /// Abrir el grupo de fanotify y vigilar todos los puntos de montaje reales
+        pub fn new() -> Result<Self, String> {
+            let raw = unsafe {
+                libc::fanotify_init(
+                    libc::FAN_CLASS_NOTIF | libc::FAN_CLOEXEC | libc::FAN_NONBLOCK,
+                    (libc::O_RDONLY | libc::O_LARGEFILE) as libc::c_uint,
+                )
+            };
+            if raw < 0 {
+                let error = std::io::Error::last_os_error();
+                return Err(match error.raw_os_error() {
+                    Some(libc::EPERM) => "requiere CAP_SYS_ADMIN (ejecutar como root)".to_string(),
+                    Some(libc::ENOSYS) => "el kernel no soporta fanotify".to_string(),
+                    _ => error.to_string(),
+                });
+            }
+            let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+            let mut marked = 0;
+            let mut last_error = None;
+            for mount_point in mount_points() {
+                let Ok(path) = std::ffi::CString::new(mount_point) else {
+                    continue;
+                };
+                let result = unsafe {
+                    libc::fanotify_mark(
+                        fd.as_raw_fd(),
+                        libc::FAN_MARK_ADD | libc::FAN_MARK_MOUNT,
+                        EVENT_MASK,
+                        libc::AT_FDCWD,
+                        path.as_ptr(),
+                    )
+                };
+                if result == 0 {
+                    marked += 1;
+                } else {
+                    last_error = Some(std::io::Error::last_os_error());
+                }
+            }
+            if marked == 0 {
+                return Err(match last_error {
+                    Some(error) => format!("no se pudo vigilar ningún punto de montaje: {}", error),
+                    None => "no se encontraron puntos de montaje".to_string(),
+                });
+            }
+            tracing::debug!("fanotify vigila {} puntos de montaje", marked);
+
+            Ok(Self { fd, pending: Vec::new(), overflowed: false })
+        }
+
+        /// Leer todos los eventos encolados por el kernel
+        fn read_events(&mut self) {
+            let metadata_size = std::mem::size_of::<libc::fanotify_event_metadata>();
+            let mut buffer = [0u8; 16 * 1024];
+            loop {
+                let read = unsafe {
+                    libc::read(self.fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len())
+                };
+                // Sin más eventos (EAGAIN) o error de lectura
+                if read <= 0 {
+                    break;
+                }
+
+                let mut offset = 0;
+                while offset + metadata_size <= read as usize {
+                    let metadata: libc::fanotify_event_metadata =
+                        unsafe { std::ptr::read_unaligned(buffer.as_ptr().add(offset).cast()) };
+                    if (metadata.event_len as usize) < metadata_size {
+                        break;
+                    }
+                    offset += metadata.event_len as usize;
+                    self.handle_event(&metadata);
+                }
+            }
+        }
+
+        /// Convertir un evento del kernel en eventos de archivo pendientes
+        fn handle_event(&mut self, metadata: &libc::fanotify_event_metadata) {
+            if metadata.mask & libc::FAN_Q_OVERFLOW != 0 {
+                if !self.overflowed {
+                    tracing::warn!("La cola de fanotify se desbordó: se perdieron eventos de archivo");
+                    self.overflowed = true;
+                }
+                return;
+            }
+            if metadata.fd < 0 {
+                return;
+            }
+            // El descriptor lo abrió el kernel para nosotros: se cierra al soltarlo
+            let file = File::from(unsafe { OwnedFd::from_raw_fd(metadata.fd) });
+            let Ok(path) = std::fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd())) else {
+                return;
+            };
+
+            let pid = metadata.pid as u32;
+            let path = path.to_string_lossy().to_string();
+            let timestamp = Utc::now();
+            let event = |operation, size| FileEvent {
+                pid,
+                path: path.clone(),
+                operation,
+                timestamp,
+                size,
+                success: true,
+                entropy: None,
+            };
+            if metadata.mask & libc::FAN_OPEN != 0 {
+                self.pending.push(event(FileOperation::Open, None));
+            }
+            if metadata.mask & libc::FAN_CLOSE_WRITE != 0 {
+                let size = file.metadata().ok().map(|metadata| metadata.len());
+                self.pending.push(event(FileOperation::Write, size));
+            }
+            if metadata.mask & (libc::FAN_CLOSE_WRITE | libc::FAN_CLOSE_NOWRITE) != 0 {
+                self.pending.push(event(FileOperation::Close, None));
+            }
+        }
+    }
+
+    impl CaptureSource for FanotifyCapture {
+        fn backend(&self) -> CaptureBackend {
+            CaptureBackend::Fanotify
+        }
+
+        fn record_files(&mut self, file_monitor: &mut FileMonitor, pid: u32) {
+            self.read_events();
+
+            let cutoff = Utc::now() - chrono::Duration::seconds(PENDING_TTL_SECS);
+            let (events, pending): (Vec<FileEvent>, Vec<FileEvent>) = std::mem::take(&mut self.pending)
+                .into_iter()
+                .filter(|event| event.timestamp >= cutoff)
+                .partition(|event| event.pid == pid);
+            self.pending = pending;
+            if self.pending.len() > MAX_PENDING_EVENTS {
+                let excess = self.pending.len() - MAX_PENDING_EVENTS;
+                self.pending.drain(..excess);
+            }
+
+            for event in events {
+                file_monitor.record_event(event);
+            }
+            // Lo que ya estaba abierto antes de empezar, o en sistemas de archivos sin vigilar
+            record_open_files(file_monitor, pid);
+        }
+    }
+
+    /// Puntos de montaje con archivos reales, según /proc/self/mountinfo
+    fn mount_points() -> Vec<String> {
+        let Ok(mountinfo) = std::fs::read_to_string("/proc/self/mountinfo") else {
+            return vec!["/".to_string()];
+        };
+        mountinfo
+            .lines()
+            .filter_map(|line| {
+                // "36 35 98:0 / /mnt rw,noatime master:1 - ext3 /dev/root rw"
+                let (mount, filesystem) = line.split_once(" - ")?;
+                let mount_point = mount.split_whitespace().nth(4)?;
+                let filesystem = filesystem.split_whitespace().next()?;
+                (!PSEUDO_FILESYSTEMS.contains(&filesystem)).then(|| unescape_mount_point(mount_point))
+            })
+            .collect()
+    }
+
+    /// Deshacer los escapes octales de mountinfo ("\040" es un espacio)
+    fn unescape_mount_point(field: &str) -> String {
+        let bytes = field.as_bytes();
+        let mut unescaped = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let octal = bytes.get(i + 1..i + 4).and_then(|digits| {
+                std::str::from_utf8(digits).ok().and_then(|digits| u8::from_str_radix(digits, 8).ok())
+            });
+            match (bytes[i], octal) {
+                (b'\\', Some(byte)) => {
+                    unescaped.push(byte);
+                    i += 4;
+                }
+                (byte, _) => {
+                    unescaped.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8_lossy(&unescaped).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Comprobación que solo acepta los métodos indicados
+    fn available(backends: &'static [CaptureBackend]) -> impl Fn(CaptureBackend) -> std::result::Result<(), String> {
+        move |backend| {
+            if backends.contains(&backend) {
+                Ok(())
+            } else {
+                Err(format!("{} no disponible", backend.label()))
+            }
+        }
+    }
+
+    #[test]
+    fn auto_prefers_fanotify_and_falls_back_to_procfs() {
+        let auto = CaptureBackend::Auto;
+        let all = &[CaptureBackend::Fanotify, CaptureBackend::Procfs];
+        assert_eq!(auto.resolve_with(available(all)).unwrap(), CaptureBackend::Fanotify);
+        assert_eq!(auto.resolve_with(available(&[CaptureBackend::Procfs])).unwrap(), CaptureBackend::Procfs);
+        // Sin ninguno disponible se queda con procfs en lugar de fallar
+        assert_eq!(auto.resolve_with(available(&[])).unwrap(), CaptureBackend::Procfs);
+    }
+
+    #[test]
+    fn explicit_backend_must_be_available() {
+        let fanotify = CaptureBackend::Fanotify;
+        assert_eq!(fanotify.resolve_with(available(&[CaptureBackend::Fanotify])).unwrap(), fanotify);
+        let error = fanotify.resolve_with(available(&[CaptureBackend::Procfs])).unwrap_err();
+        assert_eq!(error.to_string(), "Error de configuración: La captura fanotify no está disponible: fanotify no disponible");
+    }
+
+    #[test]
+    fn ebpf_is_accepted_but_unavailable() {
+        let ebpf = <CaptureBackend as clap::ValueEnum>::from_str("ebpf", true).unwrap();
+        assert_eq!(ebpf, CaptureBackend::Ebpf);
+        assert_eq!(serde_json::from_str::<CaptureBackend>("\"ebpf\"").unwrap(), ebpf);
+
+        // auto lo prueba primero y pasa al siguiente
+        assert_ne!(CaptureBackend::Auto.resolve().unwrap(), ebpf);
+        // Elegido explícitamente, falla explicando el motivo, también al abrirlo directamente
+        let expected = "Error de configuración: La captura ebpf no está disponible: esta compilación de ShadowTrace no incluye el cargador eBPF";
+        assert_eq!(ebpf.resolve().unwrap_err().to_string(), expected);
+        assert_eq!(ebpf.open().err().unwrap().to_string(), expected);
+    }
+}
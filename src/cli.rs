@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use clap::{Parser, Subcommand};
 
 /// Argumentos de línea de comandos para ShadowTrace
@@ -96,25 +96,25 @@ impl Default for Config {
 impl Config {
     /// Crear una nueva configuración a partir de los argumentos CLI
     pub fn from_cli(cli: &Cli) -> Self {
-        let mut config = Config::default();
+        let config_path = match &cli.command {
+            Some(Commands::Interactive { config }) => config.clone(),
+            _ => None,
+        };
         
-        config.update_interval = cli.interval;
-        config.verbose = cli.verbose;
-        config.theme = cli.theme.clone();
-        config.files = cli.files.clone();
-        
-        if let Some(Commands::Interactive { config: config_path }) = &cli.command {
-            config.config_path = config_path.clone();
+        Config {
+            config_path,
+            update_interval: cli.interval,
+            verbose: cli.verbose,
+            theme: cli.theme.clone(),
+            files: cli.files.clone(),
         }
-        
-        config
     }
     
     /// Cargar configuración desde un archivo
-    pub fn load_from_file(&mut self, path: &PathBuf) -> Result<(), std::io::Error> {
+    pub fn load_from_file(&mut self, path: &Path) -> Result<(), std::io::Error> {
         // Esta función cargaría la configuración desde un archivo externo
         // Por ahora simplemente actualizamos la ruta
-        self.config_path = Some(path.clone());
+        self.config_path = Some(path.to_path_buf());
         Ok(())
     }
 } 
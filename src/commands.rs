@@ -1,45 +1,81 @@
-use chrono::{DateTime, Utc};
-use std::path::PathBuf;
-use std::time::{Duration, SystemTime};
+use chrono::{Local, NaiveTime, Utc};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use anyhow::Result;
 use serde_json;
 use tokio::time;
-use tracing::{info, error};
+use std::future::Future;
+use tracing::{info, error, warn};
 
-use crate::process::ProcessMonitor;
-use crate::file_monitor::{FileEvent, FileMonitor, FileOperation, FileActivity};
-use crate::network::{NetworkEvent, NetworkMonitor, Protocol, Direction, ConnectionState};
-use crate::reports::Report;
-use crate::config::AppConfig;
+use crate::process::{format_memory, io_bytes, memory_map_summary, own_pid, probe_pid, spawn_rules, PidState, ProcessInfo, ProcessMonitor, ProcessStatus, SpawnRule, SuspiciousSpawn, SustainedCpu};
+use crate::file_monitor::{aggregate_file_events, enumerate_open_files, FileMonitor, FileOperation, FileActivity, HIGH_ENTROPY_THRESHOLD};
+use crate::network::{enumerate_connections, NetworkEvent, NetworkMonitor, Direction};
+use crate::reports::{AuditedProcess, ExportFormat, Finding, Report, SeverityLevel};
+use crate::config::{AppConfig, EffectiveConfig};
 use crate::error::AppError;
+use crate::integrations::live::{LiveMode, LiveServer};
+use crate::integrations::syslog::FindingSink;
+use crate::integrations::webhook::AlertDispatcher;
+use crate::static_analysis;
+use crate::offline_analysis;
+use crate::syscalls;
+use crate::profile::{Detector, MonitorSettings};
+use crate::llm::{self, AnalysisDepth, LlmClient};
+use crate::redaction::{IpPseudonymizer, REDACTED};
+use crate::asn::{AsnBaseline, AsnDatabase};
+use crate::dns::DnsLog;
+use crate::audit_log::{audit_log, AuditEvent};
+use crate::bundle::write_bundle;
+use crate::system_logs::LogContext;
+use crate::sample_events::SampleCapture;
+use crate::sampler::{AdaptiveSampler, ResourceReading, ResourceSample};
+use crate::schedule::Schedule;
 
-/// Monitorear un proceso específico
+/// Monitorear un proceso específico, o analizar eventos grabados con `sample_file`
 pub async fn monitor_process(
     pid: &Option<u32>,
     name: &Option<String>,
-    duration: u64,
-    interval: u64,
+    sample_file: &Option<PathBuf>,
+    settings: &MonitorSettings,
     config: &AppConfig,
 ) -> Result<()> {
-    if let Some(pid) = pid {
+    let duration = settings.duration;
+    // Eventos grabados en lugar de un proceso en vivo (--sample-file); se validan antes de empezar
+    let sample = sample_file.as_deref().map(SampleCapture::load).transpose()?;
+    if let Some(path) = sample_file {
+        info!("Analizando los eventos grabados en {}", path.display());
+    } else if let Some(pid) = pid {
         info!("Monitoreando proceso con PID {} durante {} segundos", pid, duration);
     } else if let Some(name) = name {
         info!("Monitoreando proceso '{}' durante {} segundos", name, duration);
-    } else {
+    } else if !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+        // Sin terminal no se puede preguntar (scripts, CI)
         error!("Debe especificar un PID o nombre de proceso");
         return Err(anyhow::anyhow!("Debe especificar un PID o nombre de proceso"));
     }
 
     // Inicializar monitores
     let mut process_monitor = ProcessMonitor::new();
+    process_monitor.set_max_cmd_args(config.max_cmd_args);
+    process_monitor.set_refresh_throttle(Duration::from_millis(config.refresh_throttle_ms));
+    process_monitor.set_include_self(config.include_self);
     let mut file_monitor = FileMonitor::new();
     let mut network_monitor = NetworkMonitor::new();
+    network_monitor.set_closed_history_len(config.closed_connections_history);
+    file_monitor.add_persistence_locations(&config.persistence_paths);
+    network_monitor.add_sensitive_ports(&config.sensitive_ports);
+    network_monitor.add_expected_ports(&config.expected_ports);
+    network_monitor.add_sensitive_endpoints(&config.sensitive_endpoints);
 
     // Identificar el proceso
-    let target_pid = match (pid, name) {
-        (Some(p), _) => *p,
-        (_, Some(n)) => {
-            // Buscar proceso por nombre
+    let target_pid = match (&sample, pid, name) {
+        (Some(sample), _, _) => sample.pid(),
+        (_, Some(p), _) => *p,
+        (_, _, Some(n)) => {
+            // Buscar proceso por nombre (sin contar a ShadowTrace salvo que se pida)
             let processes = process_monitor.find_process_by_name(n);
             if processes.is_empty() {
                 return Err(AppError::ProcessAccessError(format!("No se encontró ningún proceso con el nombre: {}", n)).into());
@@ -51,18 +87,67 @@ pub async fn monitor_process(
             }
             processes[0].pid
         },
-        _ => return Err(AppError::ConfigurationError("Debe especificar un PID o nombre de proceso".to_string()).into())
+        // Sin PID ni nombre en una terminal: elegir el proceso de una lista
+        _ => match pick_process(&mut process_monitor)? {
+            Some(pid) => pid,
+            None => {
+                println!("No se seleccionó ningún proceso");
+                return Ok(());
+            }
+        },
     };
 
+    if sample.is_none() && target_pid == own_pid() {
+        if !config.include_self {
+            return Err(AppError::ConfigurationError(
+                "El PID indicado es el propio ShadowTrace; usa --include-self para monitorearlo".to_string()
+            ).into());
+        }
+        println!("ℹ️ Monitoreando el propio proceso de ShadowTrace (--include-self)");
+    }
+    
+    // El tráfico hacia el LLM lo genera ShadowTrace, no el proceso monitoreado
+    if !config.include_self {
+        for addr in llm::endpoint_addrs(&config.llm.api_url).await {
+            network_monitor.ignore_remote(addr);
+        }
+    }
+
     // Obtener información del proceso
-    let process_info = process_monitor.get_process_by_pid(target_pid)
-        .ok_or_else(|| AppError::ProcessAccessError(format!("No se encontró el proceso con PID: {}", target_pid)))?;
+    let process_info = match &sample {
+        Some(sample) => sample.process_info(),
+        None => process_monitor.get_process_by_pid(target_pid)?
+            .ok_or_else(|| AppError::ProcessAccessError(format!("No se encontró el proceso con PID: {}", target_pid)))?,
+    };
+
+    // Alertas externas para hallazgos críticos
+    let mut alerts = config.alert_webhook.clone().map(AlertDispatcher::new).transpose()?;
+    // Registro de los hallazgos en syslog o en el journal (--syslog); se conecta antes de empezar
+    let mut syslog = config.syslog.map(FindingSink::connect).transpose()?;
+    // Estado en vivo para otras herramientas (--serve)
+    let live_server = config.serve
+        .map(|addr| LiveServer::start(addr, LiveMode::Monitor, config.redactor.clone()))
+        .transpose()?;
+    
+    // Seudónimos de IP compartidos por el prompt y el reporte (--redact-network)
+    let mut ip_pseudonymizer = config.redact_network.then(IpPseudonymizer::load_default).transpose()?;
+    // Base de datos de ASN para agrupar la salida de red (--asn-db); se carga antes de empezar
+    let asn_database = config.asn_db.as_deref().map(AsnDatabase::load).transpose()?;
+    // Resoluciones DNS (--dns-log); se vuelve a leer al terminar
+    let mut dns_log = config.dns_log.as_deref().map(DnsLog::load).transpose()?;
 
     // Iniciar reporte
     let mut report = Report::new_for_process(target_pid, process_info.name.clone());
+    report.analyst_notes = config.llm.analyst_notes.clone();
+    report.set_max_entries(config.max_report_entries);
+    report.set_encoding(config.report_encoding);
+    report.set_verbosity(config.verbose);
     report.set_process_info(process_info.clone());
     
     // Mensaje de inicio
+    if let Some(path) = sample_file {
+        println!("Analizando eventos grabados: {}", path.display());
+    }
     println!("Monitoreando proceso: {} (PID: {})", process_info.name, target_pid);
     if let Some(path) = &process_info.path {
         println!("Ruta del ejecutable: {}", path);
@@ -78,100 +163,320 @@ pub async fn monitor_process(
         None
     );
 
-    // Configurar loop de monitoreo
-    let interval_duration = Duration::from_secs(interval);
-    let mut tick_interval = time::interval(interval_duration);
+    // La ruta del ejecutable no cambia durante el monitoreo: basta con comprobarla una vez.
+    // El binario sí puede borrarse después, así que eso se vuelve a mirar en cada muestra.
+    let mut deleted_exe_reported = false;
+    if settings.detects(Detector::Masquerade) {
+        detect_masquerade(&mut report, &process_info);
+        detect_deleted_exe(&mut report, &process_info, &mut deleted_exe_reported);
+    }
+    // Relaciones padre → hijo sospechosas ya reportadas (también van al prompt del LLM)
+    let spawn_rules = spawn_rules(&config.spawn_rules);
+    let mut suspicious_spawns = Vec::new();
+    if settings.detects(Detector::Spawn) && sample.is_none() {
+        detect_suspicious_spawns(&mut process_monitor, &mut report, target_pid, &spawn_rules, &mut suspicious_spawns);
+    }
+
+    // Configurar loop de monitoreo (un intervalo de 0 se eleva al mínimo permitido)
+    let sample_interval = settings.sample_interval();
+    if settings.interval == 0 {
+        println!("ℹ️ Intervalo 0: muestreando cada {} ms", sample_interval.as_millis());
+    }
+    let mut tick_interval = time::interval(sample_interval);
+    // Muestreo adaptativo: el intervalo se recalcula tras cada muestra (--interval-adaptive)
+    let mut sampler = settings.interval_adaptive.then(|| {
+        let (min, max) = settings.adaptive_bounds();
+        println!("ℹ️ Muestreo adaptativo: entre {} y {} ms", min.as_millis(), max.as_millis());
+        AdaptiveSampler::new(min, max, sample_interval)
+    });
     
-    // Tiempo máximo si no es infinito
-    let max_iterations = if duration > 0 { 
-        if interval > 0 {
-            Some(duration / interval)
-        } else {
-            Some(duration) // Si el intervalo es 0, usamos la duración como número máximo de iteraciones
-        }
-    } else { 
-        None // Si la duración es 0, monitoreamos indefinidamente
-    };
+    // Fin del monitoreo si no es indefinido (la duración con 0 lo es)
+    let started = time::Instant::now();
+    let deadline = settings.time_limit().map(|limit| started + limit);
     let mut iterations = 0;
+    let mut idle_reported = false;
+    // Tramo de CPU por encima del umbral y severidad ya reportada para ese tramo
+    let mut sustained_cpu = SustainedCpu::new(settings.cpu_threshold);
+    // Muestras de CPU para el análisis heurístico (--offline)
+    let mut cpu_history: Vec<f32> = Vec::new();
+    let mut sustained_cpu_reported = None;
 
-    // Loop de monitoreo
-    loop {
-        tick_interval.tick().await;
+    // Eventos grabados: se cargan de una vez y pasan por los detectores en lugar del loop
+    let sample_span = sample.as_ref().map(SampleCapture::span);
+    if let Some(sample) = sample {
+        let loaded_files = file_monitor.load_events(sample.file_events);
+        let loaded_network = network_monitor.load_events(sample.network_events);
+        add_recorded_events(&mut report, &file_monitor, &network_monitor);
+        let description = format!(
+            "Eventos grabados cargados desde {}: {} de archivo y {} de red (no se observó un proceso en vivo)",
+            sample_file.as_deref().unwrap_or(Path::new("-")).display(), loaded_files, loaded_network
+        );
+        println!("{}", description);
+        report.add_info("sample_file", &description, None);
+        
+        detect_event_patterns(settings, &file_monitor, &network_monitor, &mut report, target_pid, &process_info.name);
+        if let Some(live_server) = &live_server {
+            live_server.publish(
+                report.processes.clone(),
+                report.findings.clone(),
+                file_monitor.get_events().len(),
+                network_monitor.get_events().len(),
+            );
+        }
+    } else {
+        // Archivos y conexiones reales del proceso (--capture-backend)
+        let mut capture = config.capture_backend.open()?;
+        info!("Captura de eventos: {}", capture.backend().label());
         
-        // Incrementar contador de iteraciones
-        iterations += 1;
+        // Loop de monitoreo
+        loop {
+            tick_interval.tick().await;
         
-        // Verificar si debemos terminar
-        if let Some(max) = max_iterations {
-            if iterations >= max {
+            // Incrementar contador de iteraciones
+            iterations += 1;
+        
+            // Verificar si debemos terminar
+            if deadline.is_some_and(|deadline| time::Instant::now() >= deadline) {
                 break;
             }
-        }
-        
-        // Actualizar información del proceso
-        if let Some(updated_info) = process_monitor.get_process_by_pid(target_pid) {
-            // Verificar si todavía está en ejecución
-            if updated_info.cpu_usage == 0.0 && iterations > 2 {
-                report.add_warning(
-                    "process", 
-                    &format!("El proceso {} (PID: {}) parece haber terminado", 
-                        updated_info.name, target_pid), 
-                    None
-                );
-                println!("⚠️ El proceso parece haber terminado (uso de CPU: 0%)");
+            if config.runtime_exceeded() {
+                stop_for_max_runtime(&mut report);
                 break;
             }
+        
+            // Actualizar información del proceso
+            // Refrescar solo el proceso monitoreado, no todo el sistema
+            process_monitor.refresh_specific(&[target_pid]);
+            match process_monitor.lookup_process(target_pid) {
+                Ok(Some(updated_info)) => {
+                    // Un proceso zombi ya terminó aunque siga en la tabla de procesos
+                    if updated_info.status.is_terminated() {
+                        report.add_warning(
+                            "process", 
+                            &format!("El proceso {} (PID: {}) ha terminado (estado: {})", 
+                                updated_info.name, target_pid, updated_info.status), 
+                            None
+                        );
+                        println!("⚠️ El proceso ha terminado (estado: {})", updated_info.status);
+                        break;
+                    }
             
-            // Registrar uso de recursos
-            let cpu_usage = updated_info.cpu_usage;
-            let memory_usage = updated_info.memory_usage;
+                    // Sin uso de CPU tras unos segundos: se comprueba con el sistema si el PID sigue vivo
+                    // en lugar de darlo por terminado (un proceso inactivo o detenido tampoco usa CPU)
+                    let halted = matches!(updated_info.status, ProcessStatus::Stopped | ProcessStatus::Tracing);
+                    if updated_info.cpu_usage == 0.0 && !halted && started.elapsed() > Duration::from_secs(2) {
+                        if probe_pid(target_pid) == PidState::Gone {
+                            report.add_warning(
+                                "process", 
+                                &format!("El proceso {} (PID: {}) ha terminado", 
+                                    updated_info.name, target_pid), 
+                                None
+                            );
+                            println!("⚠️ El proceso ha terminado");
+                            break;
+                        }
+                        if !idle_reported {
+                            println!("ℹ️ El proceso sigue en ejecución pero está inactivo (uso de CPU: 0%)");
+                            idle_reported = true;
+                        }
+                    }
             
-            if cpu_usage > 80.0 {
-                report.add_warning(
-                    "resource", 
-                    &format!("Alto uso de CPU: {:.2}%", cpu_usage), 
-                    None
-                );
-            }
+                    // Registrar uso de recursos
+                    let cpu_usage = updated_info.cpu_usage;
+                    let memory_usage = updated_info.memory_usage;
+            
+                    sustained_cpu.record(std::time::Instant::now(), cpu_usage);
+                    detect_sustained_cpu(&mut report, &sustained_cpu, settings.cpu_sustained_secs, &mut sustained_cpu_reported);
+                    if config.offline {
+                        cpu_history.push(cpu_usage);
+                    }
+                    if let Some(sampler) = &mut sampler {
+                        let io_bytes = io_bytes(target_pid);
+                        let previous = sampler.current();
+                        let next = sampler.observe(ResourceReading { cpu_usage, memory_usage, io_bytes });
+                        report.add_resource_sample(ResourceSample {
+                            timestamp: Utc::now(),
+                            cpu_usage,
+                            memory_usage,
+                            io_bytes,
+                            interval_ms: next.as_millis() as u64,
+                        });
+                        if next != previous {
+                            tick_interval = time::interval_at(time::Instant::now() + next, next);
+                        }
+                    }
+            
+                    if iterations % 5 == 0 {
+                        println!("Uso CPU: {:.2}%, Memoria: {}", cpu_usage, format_memory(memory_usage));
+                    }
+            
+                    // Estado detenido e hijos zombis (la tabla completa se recorre cada 5 iteraciones)
+                    detect_status_patterns(&mut process_monitor, &mut report, &updated_info, iterations % 5 == 0);
+                    if settings.detects(Detector::Masquerade) {
+                        detect_deleted_exe(&mut report, &updated_info, &mut deleted_exe_reported);
+                    }
             
-            if iterations % 5 == 0 {
-                println!("Uso CPU: {:.2}%, Memoria: {} KB", cpu_usage, memory_usage);
+                    // El reporte conserva la última lectura (CPU/memoria exportadas como métricas)
+                    report.set_process_info(updated_info);
+                }
+                Ok(None) => {
+                    report.add_warning(
+                        "process",
+                        &format!("El proceso {} (PID: {}) ha terminado", process_info.name, target_pid),
+                        None
+                    );
+                    println!("⚠️ El proceso ha terminado");
+                    break;
+                }
+                Err(e) => {
+                    // Sigue existiendo, pero ya no se puede leer (p. ej. cambió de usuario con setuid)
+                    report.add_warning("process", &format!("No se puede seguir leyendo el proceso: {}", e), None);
+                    println!("⚠️ No se puede seguir leyendo el proceso: {}", e);
+                    break;
+                }
             }
-        } else {
-            report.add_warning("process", "Proceso terminado o no accesible", None);
-            println!("⚠️ El proceso ya no está accesible");
-            break;
-        }
         
-        // Simular eventos de archivo y red (aquí iría la implementación real)
-        simulate_file_events(&mut file_monitor, &mut report, target_pid, iterations);
-        simulate_network_events(&mut network_monitor, &mut report, target_pid, iterations);
+            // Registrar la actividad de archivos y red desde la muestra anterior
+            capture.record_files(&mut file_monitor, target_pid);
+            capture.record_connections(&mut network_monitor, target_pid);
+        
+            // Detectar patrones sospechosos
+            detect_event_patterns(settings, &file_monitor, &network_monitor, &mut report, target_pid, &process_info.name);
+            if settings.detects(Detector::Memory) {
+                detect_memory_patterns(&mut report, target_pid);
+            }
+            // Los hijos nuevos se buscan con la tabla completa, cada 5 iteraciones
+            if settings.detects(Detector::Spawn) && iterations % 5 == 0 {
+                detect_suspicious_spawns(&mut process_monitor, &mut report, target_pid, &spawn_rules, &mut suspicious_spawns);
+            }
         
-        // Detectar patrones sospechosos
-        detect_file_patterns(&file_monitor, &mut report, target_pid);
-        detect_network_patterns(&network_monitor, &mut report, target_pid);
+            if let Some(alerts) = &mut alerts {
+                alerts.notify_critical(&report.findings);
+            }
+            if let Some(syslog) = &mut syslog {
+                syslog.notify(&report.findings, target_pid);
+            }
+            if let Some(live_server) = &live_server {
+                live_server.publish(
+                    report.processes.clone(),
+                    report.findings.clone(),
+                    file_monitor.get_events().len(),
+                    network_monitor.get_events().len(),
+                );
+            }
+        }
+        add_recorded_events(&mut report, &file_monitor, &network_monitor);
+    }
+    
+    // Detectar escritura sostenida de datos cifrados o comprimidos
+    if settings.detects(Detector::Entropy) {
+        detect_entropy_patterns(&file_monitor, &mut report, target_pid, settings.entropy_write_count);
+    }
+    if let Some(alerts) = &mut alerts {
+        alerts.notify_critical(&report.findings);
+        alerts.flush().await;
+    }
+    
+    // Conservar las conexiones breves que ya se cerraron
+    for closed in network_monitor.get_closed_connections_for_pid(target_pid) {
+        report.add_closed_connection(closed.clone());
+    }
+    if let Some(database) = &asn_database {
+        let remotes = network_monitor.get_events_for_pid(target_pid).into_iter().cloned();
+        summarize_egress(&mut report, database, &process_info.name, remotes);
+    }
+    if let Some(dns_log) = &mut dns_log {
+        dns_log.reload();
+        detect_raw_ip_connections(&mut report, dns_log, config.raw_ip_severity, &network_monitor, network_monitor.get_events_for_pid(target_pid));
+    }
+    // Incluye los hallazgos de entropía y de ASN nuevos
+    if let Some(syslog) = &mut syslog {
+        syslog.notify(&report.findings, target_pid);
+    }
+    // Después de notificar a syslog, para no reenviarle sus propias líneas; los
+    // registros de este equipo no corresponden a una captura grabada
+    if let Some(max_lines) = config.with_logs.filter(|_| sample_file.is_none()) {
+        add_system_logs(&mut report, target_pid, &process_info.name, process_info.path.as_deref(), max_lines);
     }
     
     // Finalizar monitoreo
     report.update_end_time();
+    if let Some(span) = sample_span {
+        report.duration = span;
+    }
     println!("Monitoreo finalizado para {} (PID: {})", process_info.name, target_pid);
     
-    // Analizar con LLM si está disponible
-    if let Some(client) = &config.llm_client {
+    // Analizar con LLM si está disponible y el perfil lo pide
+    if let Some(client) = config.llm_client.as_ref().filter(|_| settings.llm_analysis) {
         println!("Analizando comportamiento con IA...");
         
         // Convertir a JSON para el LLM
-        let process_json = serde_json::to_value(&process_info)?;
-        let file_events_json = serde_json::to_value(&file_monitor.get_events_for_pid(target_pid))?;
-        let network_events_json = serde_json::to_value(&network_monitor.get_events_for_pid(target_pid))?;
+        let mut process_json = serde_json::to_value(&process_info)?;
+        // El ejecutable pudo borrarse después de la primera lectura
+        if deleted_exe_reported {
+            process_json["deleted_exe"] = serde_json::json!(true);
+        }
+        // Bibliotecas cargadas y regiones ejecutables/escribibles (solo de un proceso en vivo)
+        if sample_file.is_none() {
+            if let Ok(regions) = ProcessMonitor::memory_maps(target_pid) {
+                process_json["memory_map"] = memory_map_summary(&regions);
+            }
+        }
+        if !report.egress_by_asn.is_empty() {
+            process_json["egress_by_asn"] = serde_json::to_value(&report.egress_by_asn)?;
+        }
+        if !suspicious_spawns.is_empty() {
+            process_json["suspicious_spawns"] = serde_json::to_value(&suspicious_spawns)?;
+        }
+        if let Some(logs) = &report.system_logs {
+            process_json["system_logs"] = system_logs_json(logs, config.analysis_depth);
+        }
+        // Con menos profundidad se envían estadísticas en lugar de los eventos en bruto
+        let depth = config.analysis_depth;
+        // Los eventos repetidos van agrupados por ruta y operación
+        let mut file_events_json = depth.shape_events(
+            serde_json::to_value(file_monitor.aggregate_events(target_pid))?,
+            "operation",
+        );
+        let mut network_events_json = depth.shape_events(
+            serde_json::to_value(network_monitor.get_events_for_pid(target_pid))?,
+            "remote_addr",
+        );
+        
+        // Redactar secretos antes de enviarlos al LLM
+        if let Some(redactor) = &config.redactor {
+            redactor.redact_value(&mut process_json);
+            redactor.redact_value(&mut file_events_json);
+        }
+        if let Some(pseudonymizer) = &mut ip_pseudonymizer {
+            pseudonymizer.pseudonymize_value(&mut process_json);
+            pseudonymizer.pseudonymize_value(&mut file_events_json);
+            pseudonymizer.pseudonymize_value(&mut network_events_json);
+        }
+        
+        if let Some(estimated) = client.comprehensive_context_overflow(&process_json, &file_events_json, &network_events_json)? {
+            println!(
+                "⚠️ El prompt estimado (~{} tokens) supera la ventana de contexto del modelo (num_ctx = {}); el modelo podría truncarlo.{}",
+                estimated,
+                config.llm.num_ctx.unwrap_or_default(),
+                match depth {
+                    AnalysisDepth::Deep => " Considera usar --depth normal o shallow.",
+                    AnalysisDepth::Normal => " Considera usar --depth shallow.",
+                    AnalysisDepth::Shallow => "",
+                },
+            );
+        }
         
         // Realizar análisis completo
-        match client.comprehensive_analysis(
+        match llm_within_max_runtime(config, client.comprehensive_analysis(
             process_json,
             file_events_json,
             network_events_json,
-        ).await {
+        )).await {
             Ok(analysis) => {
+                report.set_llm_model(client.answered_by());
+                note_reduced_context(&mut report, client);
+                
                 // Almacenar el análisis como hallazgo
                 report.add_info(
                     "llm_analysis", 
@@ -185,82 +490,375 @@ pub async fn monitor_process(
             }
         }
     }
+    if config.offline {
+        add_offline_analysis(&mut report, &cpu_history);
+    }
+    
+    // Redactar secretos antes de guardar
+    if let Some(redactor) = &config.redactor {
+        report.redact(redactor);
+    }
+    if let Some(pseudonymizer) = &mut ip_pseudonymizer {
+        pseudonymize_report(&mut report, pseudonymizer);
+    }
     
     // Guardar reportes
+    save_artifacts(&report, config);
+    
+    Ok(())
+}
+
+/// Agrupar las conexiones salientes por organización y marcar los ASN que el proceso
+/// no había usado en ejecuciones anteriores (la línea base se actualiza con cada ejecución)
+fn summarize_egress(report: &mut Report, database: &AsnDatabase, process_name: &str, events: impl Iterator<Item = NetworkEvent>) {
+    let remotes = events
+        .filter(|event| event.direction == Direction::Outbound)
+        .filter_map(|event| event.remote_addr);
+    let mut egress = database.group_egress(remotes);
+    match AsnBaseline::load_default() {
+        Ok(mut baseline) => {
+            let known = baseline.mark_unseen(process_name, &mut egress);
+            if !known && egress.iter().any(|group| group.asn.is_some()) {
+                println!("ℹ️ Primera ejecución de {}: se guarda su línea base de ASN", process_name);
+            }
+            if let Err(e) = baseline.save() {
+                println!("⚠️ No se pudo guardar la línea base de ASN: {}", e);
+            }
+        }
+        Err(e) => println!("⚠️ No se pudo cargar la línea base de ASN: {}", e),
+    }
+    if !egress.is_empty() {
+        println!("Salida de red por organización:");
+    }
+    for group in &egress {
+        println!("  {} - {} conexiones{}", group.label(), group.connections, if group.unseen { " (ASN no visto antes)" } else { "" });
+    }
+    report.set_egress_by_asn(egress);
+}
+
+/// Reportar las conexiones salientes a IP públicas que no resolvió ningún nombre del
+/// registro DNS (`--dns-log`). El tráfico propio de ShadowTrace no cuenta.
+fn detect_raw_ip_connections<'a>(
+    report: &mut Report,
+    dns_log: &DnsLog,
+    severity: SeverityLevel,
+    network_monitor: &NetworkMonitor,
+    events: impl IntoIterator<Item = &'a NetworkEvent>,
+) {
+    let events = events
+        .into_iter()
+        .filter(|event| event.remote_addr.filter(|addr| network_monitor.ignored_remotes().contains(addr)).is_none());
+    for detection in dns_log.detect_raw_ip_connections(events) {
+        println!("{} {}", severity.marker(), detection.message);
+        report.add_detection("raw_ip_connection", severity, &detection);
+    }
+}
+
+/// Añadir al reporte las líneas recientes de los registros del sistema que mencionan
+/// el proceso (`--with-logs`); las fuentes ausentes o ilegibles solo se avisan
+fn add_system_logs(report: &mut Report, pid: u32, name: &str, path: Option<&str>, max_lines: usize) {
+    let context = LogContext::collect(pid, name, path, max_lines);
+    println!("Registros del sistema: {}", context.summary());
+    for reason in &context.unavailable {
+        println!("⚠️ Registro no disponible: {}", reason);
+    }
+    report.set_system_logs(context);
+}
+
+/// Contexto de los registros del sistema para el LLM: el recuento siempre y las líneas
+/// según la profundidad (ninguna en shallow, las más recientes en normal, todas en deep)
+fn system_logs_json(logs: &LogContext, depth: AnalysisDepth) -> serde_json::Value {
+    let lines: Vec<&str> = logs.lines.iter().map(|line| line.text.as_str()).collect();
+    let lines = match depth {
+        AnalysisDepth::Shallow => &lines[..0],
+        AnalysisDepth::Normal => &lines[lines.len().saturating_sub(AnalysisDepth::NORMAL_SAMPLE)..],
+        AnalysisDepth::Deep => &lines[..],
+    };
+    serde_json::json!({
+        "sources": logs.sources,
+        "auth_failures": logs.auth_failures,
+        "sudo_events": logs.sudo_events,
+        "lines": lines,
+    })
+}
+
+/// Generar el análisis heurístico sin IA (`--offline`), mostrarlo y guardarlo en el reporte
+fn add_offline_analysis(report: &mut Report, cpu_history: &[f32]) {
+    let analysis = offline_analysis::heuristic_analysis(report, cpu_history);
+    println!("\n--- Análisis heurístico (sin IA) ---\n{}\n", analysis);
+    report.set_offline_analysis(analysis);
+}
+
+/// Avisar, en pantalla y en el reporte, de que el análisis se repitió solo con
+/// estadísticas porque el prompt no cabía en la ventana de contexto del modelo
+fn note_reduced_context(report: &mut Report, client: &LlmClient) {
+    if !client.used_reduced_context() {
+        return;
+    }
+    println!("ℹ️ El prompt no cabía en la ventana de contexto del modelo: el análisis se repitió solo con estadísticas (como --depth shallow)");
+    report.add_info(
+        "llm_reduced_context",
+        "El análisis de IA se hizo solo con estadísticas porque el prompt completo excedía la ventana de contexto del modelo",
+        None,
+    );
+}
+
+/// Margen tras `--max-runtime` para que la sesión se cierre por sí misma y guarde lo recopilado
+const MAX_RUNTIME_GRACE: Duration = Duration::from_secs(15);
+
+/// Ejecutar un comando acotado por `--max-runtime`. Los bucles de muestreo y las llamadas
+/// al LLM se cortan al vencer el límite y el comando guarda lo recopilado; si aun así
+/// sigue bloqueado pasado un margen, se abandona.
+pub async fn with_max_runtime<T>(config: &AppConfig, command: impl Future<Output = Result<T>>) -> Result<T> {
+    let Some(remaining) = config.remaining_runtime() else {
+        return command.await;
+    };
+    match time::timeout(remaining + MAX_RUNTIME_GRACE, command).await {
+        Ok(result) => result,
+        Err(_) => {
+            error!("Se alcanzó --max-runtime y la sesión no terminó en {} s; se abandona sin guardar", MAX_RUNTIME_GRACE.as_secs());
+            Err(anyhow::anyhow!("Se superó el tiempo máximo de ejecución (--max-runtime)"))
+        }
+    }
+}
+
+/// Limitar una llamada al LLM al tiempo que queda de `--max-runtime`
+async fn llm_within_max_runtime(config: &AppConfig, analysis: impl Future<Output = Result<String>>) -> Result<String> {
+    match config.remaining_runtime() {
+        Some(remaining) => time::timeout(remaining, analysis).await.unwrap_or_else(|_| {
+            warn!("Se alcanzó --max-runtime durante el análisis LLM");
+            Err(anyhow::anyhow!("se alcanzó el tiempo máximo de ejecución (--max-runtime) antes de recibir la respuesta"))
+        }),
+        None => analysis.await,
+    }
+}
+
+/// Registrar en el log y en el reporte que la sesión se cortó por `--max-runtime`
+fn stop_for_max_runtime(report: &mut Report) {
+    warn!("Se alcanzó --max-runtime: se detiene la sesión y se guarda lo recopilado");
+    println!("⏱️ Se alcanzó el tiempo máximo de ejecución (--max-runtime); se guarda lo recopilado");
+    report.add_warning("max_runtime", "La sesión se detuvo al alcanzar el tiempo máximo de ejecución (--max-runtime)", None);
+}
+
+/// Guardar el reporte, exportarlo en los formatos de --export y, con --export-bundle,
+/// empaquetar todo lo generado en un zip
+fn save_artifacts(report: &Report, config: &AppConfig) {
+    let mut artifacts = save_report(report);
+    artifacts.extend(export_report(report, config));
+    if let Some(path) = &config.export_bundle {
+        bundle_report(report, &artifacts, path);
+    }
+}
+
+/// Guardar el reporte JSON y Markdown en el directorio por defecto.
+/// Devuelve los archivos escritos.
+fn save_report(report: &Report) -> Vec<PathBuf> {
     match report.save_to_default_dir() {
         Ok((json_path, md_path)) => {
             println!("Reporte JSON guardado en: {}", json_path.display());
             println!("Reporte Markdown guardado en: {}", md_path.display());
+            audit_log(AuditEvent::new("export", Some(json_path.display().to_string()), "ok"));
+            audit_log(AuditEvent::new("export", Some(md_path.display().to_string()), "ok"));
+            vec![json_path, md_path]
         }
         Err(e) => {
             println!("⚠️ Error al guardar reportes: {}. Continuando sin guardar reportes.", e);
+            audit_log(AuditEvent::new("export", Some(report.id.clone()), format!("error: {}", e)));
+            Vec::new()
         }
     }
-    
-    Ok(())
 }
 
-/// Simular eventos de archivo
-fn simulate_file_events(
-    file_monitor: &mut FileMonitor, 
-    report: &mut Report, 
-    target_pid: u32, 
-    iterations: u64
-) {
-    if iterations % 3 == 0 {
-        // Usar rutas compatibles con el sistema operativo
-        #[cfg(target_os = "linux")]
-        let file_path = format!("/tmp/test_file_{}.txt", iterations);
+/// Empaquetar los artefactos generados en un zip con manifiesto y sumas SHA-256
+fn bundle_report(report: &Report, artifacts: &[PathBuf], path: &Path) {
+    if artifacts.is_empty() {
+        println!("⚠️ No se generó ningún artefacto; no se crea el paquete {}", path.display());
+        return;
+    }
+    let result = write_bundle(path, report, artifacts);
+    audit_log(AuditEvent::from_result("export", Some(path.display().to_string()), &result));
+    match result {
+        Ok(manifest) => println!("Paquete con {} artefactos guardado en: {}", manifest.files.len(), path.display()),
+        Err(e) => println!("⚠️ Error al crear el paquete {}: {}", path.display(), e),
+    }
+}
+
+/// Exportar el reporte en los formatos adicionales pedidos con --export.
+/// Devuelve los archivos escritos.
+fn export_report(report: &Report, config: &AppConfig) -> Vec<PathBuf> {
+    let mut artifacts = Vec::new();
+    for format in &config.exports {
+        match format {
+            ExportFormat::Prometheus => {
+                let Some(path) = config.prometheus_textfile.clone().or_else(Report::default_prometheus_path) else {
+                    println!("⚠️ No se pudo determinar el archivo de métricas; usa --prometheus-textfile");
+                    continue;
+                };
+                let result = report.export_prometheus(&path);
+                audit_log(AuditEvent::from_result("export", Some(path.display().to_string()), &result));
+                match result {
+                    Ok(()) => {
+                        println!("Métricas de Prometheus guardadas en: {}", path.display());
+                        artifacts.push(path);
+                    }
+                    Err(e) => println!("⚠️ Error al exportar métricas de Prometheus: {}", e),
+                }
+            }
+            ExportFormat::Summary => match report.save_summary_to_default_dir() {
+                Ok(path) => {
+                    println!("Resumen guardado en: {}", path.display());
+                    audit_log(AuditEvent::new("export", Some(path.display().to_string()), "ok"));
+                    artifacts.push(path);
+                }
+                Err(e) => {
+                    println!("⚠️ Error al guardar el resumen: {}", e);
+                    audit_log(AuditEvent::new("export", Some(report.id.clone()), format!("error: {}", e)));
+                }
+            },
+            ExportFormat::Csv => match report.save_file_activity_csv_to_default_dir() {
+                Ok(path) => {
+                    println!("Actividad de archivos (CSV) guardada en: {}", path.display());
+                    audit_log(AuditEvent::new("export", Some(path.display().to_string()), "ok"));
+                    artifacts.push(path);
+                }
+                Err(e) => {
+                    println!("⚠️ Error al guardar el CSV de actividad de archivos: {}", e);
+                    audit_log(AuditEvent::new("export", Some(report.id.clone()), format!("error: {}", e)));
+                }
+            },
+        }
+    }
+    artifacts
+}
+
+/// Intervalo con el que se muestrea el árbol de procesos de un binario auditado
+const AUDIT_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Máximo de procesos que muestra el selector interactivo
+const PICKER_MAX_ROWS: usize = 20;
+
+/// Elegir interactivamente el proceso a monitorear, filtrando por nombre o PID.
+/// Devuelve None si el usuario cancela ("q" o fin de la entrada).
+fn pick_process(process_monitor: &mut ProcessMonitor) -> Result<Option<u32>> {
+    loop {
+        let Some(query) = prompt_line("Proceso a monitorear (nombre o PID; vacío para listar todos, q para salir): ")? else {
+            return Ok(None);
+        };
+        if query == "q" {
+            return Ok(None);
+        }
         
-        #[cfg(target_os = "macos")]
-        let file_path = format!("/tmp/test_file_{}.txt", iterations);
+        let mut matches = process_monitor.find_process_by_name(&query);
+        if let Ok(pid) = query.parse::<u32>() {
+            let listed = process_monitor.lists(pid) && !matches.iter().any(|p| p.pid == pid);
+            if let Some(process) = process_monitor.process_info(pid).filter(|_| listed) {
+                matches.push(process);
+            }
+        }
+        matches.sort_by_key(|p| Reverse(p.memory_usage));
         
-        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-        let file_path = format!("C:/temp/test_file_{}.txt", iterations);
+        match matches.len() {
+            0 => {
+                println!("Ningún proceso coincide con '{}'", query);
+                continue;
+            }
+            1 => {
+                println!("Seleccionado: {} (PID: {})", matches[0].name, matches[0].pid);
+                return Ok(Some(matches[0].pid));
+            }
+            _ => {}
+        }
         
-        let event = FileEvent {
-            pid: target_pid,
-            path: file_path,
-            operation: FileOperation::Write,
-            timestamp: Utc::now(),
-            size: Some(1024),
-            success: true,
-        };
-        file_monitor.record_event(event.clone());
+        for (index, process) in matches.iter().take(PICKER_MAX_ROWS).enumerate() {
+            println!(
+                "  {:>2}) {:<24} PID {:<8} {:>10}  {}",
+                index + 1,
+                process.name,
+                process.pid,
+                format_memory(process.memory_usage),
+                process.path.as_deref().unwrap_or(""),
+            );
+        }
+        if matches.len() > PICKER_MAX_ROWS {
+            println!("  ... y {} más; afina el filtro", matches.len() - PICKER_MAX_ROWS);
+        }
         
-        // Agregar como actividad de archivo
-        let file_activity = FileActivity {
-            path: PathBuf::from(event.path.clone()),
-            operation: event.operation,
+        // Repetir la pregunta hasta recibir un número válido; vacío vuelve a la búsqueda
+        loop {
+            let Some(choice) = prompt_line("Número del proceso (vacío para buscar de nuevo): ")? else {
+                return Ok(None);
+            };
+            if choice.is_empty() {
+                break;
+            }
+            match choice.parse::<usize>() {
+                Ok(n) if (1..=matches.len().min(PICKER_MAX_ROWS)).contains(&n) => {
+                    return Ok(Some(matches[n - 1].pid));
+                }
+                _ => println!("Escribe un número entre 1 y {}", matches.len().min(PICKER_MAX_ROWS)),
+            }
+        }
+    }
+}
+
+/// Mostrar un mensaje y leer una línea de la entrada estándar (None al llegar al final)
+fn prompt_line(message: &str) -> Result<Option<String>> {
+    print!("{}", message);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Seudonimizar las IPs del reporte y guardar el mapeo para poder deshacerlo
+fn pseudonymize_report(report: &mut Report, pseudonymizer: &mut IpPseudonymizer) {
+    report.pseudonymize_ips(pseudonymizer);
+    match pseudonymizer.save() {
+        Ok(()) => {
+            if let Some(path) = pseudonymizer.path() {
+                println!("Mapeo de IPs seudonimizadas guardado en: {}", path.display());
+            }
+        }
+        Err(e) => println!("⚠️ Error al guardar el mapeo de IPs: {}", e),
+    }
+}
+
+/// Añadir al reporte los eventos de archivo y red registrados por los monitores
+fn add_recorded_events(report: &mut Report, file_monitor: &FileMonitor, network_monitor: &NetworkMonitor) {
+    for event in file_monitor.get_events() {
+        report.add_file_activity(FileActivity {
+            path: PathBuf::from(&event.path),
+            operation: event.operation.clone(),
             process_id: Some(event.pid),
-            timestamp: SystemTime::now(),
+            timestamp: event.timestamp,
             size: event.size,
-        };
-        report.add_file_activity(file_activity);
+            entropy: event.entropy,
+        });
+    }
+    for event in network_monitor.get_events() {
+        report.add_network_event(event.clone());
     }
 }
 
-/// Simular eventos de red
-fn simulate_network_events(
-    network_monitor: &mut NetworkMonitor, 
-    report: &mut Report, 
-    target_pid: u32, 
-    iterations: u64
+/// Detectores sobre los eventos de archivo y red registrados hasta el momento
+fn detect_event_patterns(
+    settings: &MonitorSettings,
+    file_monitor: &FileMonitor,
+    network_monitor: &NetworkMonitor,
+    report: &mut Report,
+    target_pid: u32,
+    process_name: &str,
 ) {
-    if iterations % 4 == 0 {
-        let event = NetworkEvent {
-            pid: target_pid,
-            local_addr: "127.0.0.1:12345".parse().unwrap(),
-            remote_addr: Some("8.8.8.8:443".parse().unwrap()),
-            protocol: Protocol::TCP,
-            direction: Direction::Outbound,
-            state: ConnectionState::Established,
-            timestamp: Utc::now(),
-            bytes_sent: Some(512),
-            bytes_received: Some(1024),
-        };
-        network_monitor.record_event(event.clone());
-        report.add_network_event(event);
+    if settings.detects(Detector::File) {
+        detect_file_patterns(file_monitor, report, target_pid);
+    }
+    if settings.detects(Detector::Network) {
+        detect_network_patterns(network_monitor, report, target_pid, process_name);
+    }
+    if settings.detects(Detector::Persistence) {
+        detect_persistence_patterns(file_monitor, report, target_pid);
     }
 }
 
@@ -272,21 +870,359 @@ fn detect_file_patterns(
 ) {
     let suspicious_files = file_monitor.detect_suspicious_patterns(target_pid);
     for pattern in suspicious_files {
-        report.add_alert("file_access", &pattern, None);
-        println!("⚠️ {}", pattern);
+        report.add_detection("file_access", SeverityLevel::Critical, &pattern);
+        println!("⚠️ {}", pattern.message);
+    }
+}
+
+/// Detectar ejecutables dejados en ubicaciones de arranque/persistencia
+fn detect_persistence_patterns(
+    file_monitor: &FileMonitor, 
+    report: &mut Report, 
+    target_pid: u32
+) {
+    for drop in file_monitor.detect_persistence_drops(target_pid) {
+        // Los detectores se ejecutan en cada iteración: reportar cada archivo una sola vez
+        let already_reported = report.findings.iter().any(|f| {
+            f.title == "persistence" && f.description == drop.reason && f.affected_resources.contains(&drop.path)
+        });
+        if already_reported {
+            continue;
+        }
+        
+        println!("⚠️ {}: {}", drop.reason, drop.path);
+        report.add_finding(Finding {
+            title: "persistence".to_string(),
+            description: drop.reason,
+            severity: SeverityLevel::Critical,
+            recommendation: Some("Revisar el archivo y eliminarlo si no corresponde a una instalación legítima".to_string()),
+            affected_resources: vec![drop.path],
+            timestamp: drop.timestamp,
+        });
+    }
+}
+
+/// Detectar regiones de memoria escribibles y ejecutables (señal de inyección de código)
+fn detect_memory_patterns(report: &mut Report, target_pid: u32) {
+    let Ok(regions) = ProcessMonitor::memory_maps(target_pid) else {
+        return;
+    };
+    
+    // Los detectores se ejecutan en cada iteración: reportar solo las regiones nuevas
+    let reported: Vec<&String> = report
+        .findings
+        .iter()
+        .filter(|f| f.title == "rwx_memory")
+        .flat_map(|f| f.affected_resources.iter())
+        .collect();
+    let new_regions: Vec<String> = regions
+        .iter()
+        .filter(|region| region.is_rwx())
+        .map(|region| format!(
+            "{} {} ({})",
+            region.range(),
+            region.path.as_deref().unwrap_or("[anónima]"),
+            format_memory(region.size())
+        ))
+        .filter(|resource| !reported.contains(&resource))
+        .collect();
+    if new_regions.is_empty() {
+        return;
+    }
+    
+    let anonymous = regions.iter().filter(|region| region.is_rwx() && region.is_anonymous()).count();
+    let description = format!(
+        "{} regiones de memoria escribibles y ejecutables ({} anónimas)",
+        new_regions.len(), anonymous
+    );
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "rwx_memory".to_string(),
+        description,
+        severity: SeverityLevel::Warning,
+        recommendation: Some(
+            "Las regiones RWX son habituales en motores JIT (navegadores, Java, Node.js); \
+            en otros procesos pueden indicar código inyectado".to_string()
+        ),
+        affected_resources: new_regions,
+        timestamp: Utc::now(),
+    });
+}
+
+/// Detectar un proceso que se hace pasar por otro (nombre o ruta del ejecutable)
+fn detect_masquerade(report: &mut Report, process: &ProcessInfo) {
+    let Some(description) = process.masquerade() else {
+        return;
+    };
+    
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "masquerade".to_string(),
+        description,
+        severity: SeverityLevel::Critical,
+        recommendation: Some(
+            "Verificar el origen del ejecutable (paquete instalado, hash, firma) y \
+            detener el proceso si no es legítimo".to_string()
+        ),
+        affected_resources: process.path.iter().cloned().collect(),
+        timestamp: Utc::now(),
+    });
+}
+
+/// Detectar un proceso que sigue en marcha aunque su ejecutable se borró del disco,
+/// técnica habitual del malware para no dejar el binario. Se reporta una sola vez.
+fn detect_deleted_exe(report: &mut Report, process: &ProcessInfo, reported: &mut bool) {
+    if *reported || process.deleted_exe != Some(true) {
+        return;
+    }
+    *reported = true;
+    
+    let description = format!(
+        "El ejecutable de {} (PID: {}) se borró del disco mientras el proceso sigue en marcha",
+        process.name, process.pid
+    );
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "deleted_exe".to_string(),
+        description,
+        severity: SeverityLevel::Critical,
+        recommendation: Some(format!(
+            "Recuperar el binario antes de detener el proceso (cp /proc/{}/exe ./evidencia.bin) \
+            y analizarlo; un binario legítimo borrado suele deberse a una actualización pendiente de reinicio",
+            process.pid
+        )),
+        affected_resources: process.path.iter().cloned().collect(),
+        timestamp: Utc::now(),
+    });
+}
+
+/// Detectar relaciones padre → hijo sospechosas del proceso (con su padre) y de sus
+/// descendientes. Las ya reportadas en `reported` se omiten.
+fn detect_suspicious_spawns(
+    process_monitor: &mut ProcessMonitor,
+    report: &mut Report,
+    pid: u32,
+    rules: &[SpawnRule],
+    reported: &mut Vec<SuspiciousSpawn>,
+) {
+    let tree = process_monitor.build_process_tree();
+    let pids: Vec<u32> = std::iter::once(pid)
+        .chain(tree.descendants(pid).into_iter().map(|(child, _)| child))
+        .collect();
+    for spawn in tree.suspicious_spawns(&pids, rules) {
+        if reported.iter().any(|r| r.pid == spawn.pid && r.parent_pid == spawn.parent_pid) {
+            continue;
+        }
+        add_spawn_finding(report, &spawn);
+        reported.push(spawn);
+    }
+}
+
+/// Registrar una relación padre → hijo sospechosa como hallazgo crítico
+fn add_spawn_finding(report: &mut Report, spawn: &SuspiciousSpawn) {
+    let description = spawn.description();
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "suspicious_spawn".to_string(),
+        description,
+        severity: SeverityLevel::Critical,
+        recommendation: Some(
+            "Revisar la línea de comandos del proceso hijo y la actividad del padre; \
+            si no es legítimo, aislar el equipo y detener la cadena de procesos".to_string()
+        ),
+        affected_resources: vec![spawn.chain.clone()],
+        timestamp: Utc::now(),
+    });
+}
+
+/// Severidad de un uso de CPU sostenido según su duración: advertencia a partir de
+/// `min_secs`, error a partir del triple y crítico a partir de diez veces
+fn sustained_cpu_severity(duration: Duration, min_secs: u64) -> Option<SeverityLevel> {
+    let min = Duration::from_secs(min_secs.max(1));
+    if duration >= min * 10 {
+        Some(SeverityLevel::Critical)
+    } else if duration >= min * 3 {
+        Some(SeverityLevel::Error)
+    } else if duration >= min {
+        Some(SeverityLevel::Warning)
+    } else {
+        None
+    }
+}
+
+/// Detectar un uso de CPU alto y sostenido (p. ej. un minero), no un pico aislado.
+/// Se reporta una vez por cada severidad que alcanza el tramo actual.
+fn detect_sustained_cpu(
+    report: &mut Report,
+    cpu: &SustainedCpu,
+    min_secs: u64,
+    reported: &mut Option<SeverityLevel>,
+) {
+    let duration = cpu.duration();
+    let Some(severity) = sustained_cpu_severity(duration, min_secs) else {
+        // Por debajo de la duración mínima (o fuera del tramo): el siguiente tramo se reporta de nuevo
+        *reported = None;
+        return;
+    };
+    if *reported == Some(severity) {
+        return;
+    }
+    *reported = Some(severity);
+    
+    let description = format!(
+        "CPU sostenido > {}% durante {} s (media {:.1}%)",
+        cpu.threshold(), duration.as_secs(), cpu.average()
+    );
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "sustained_cpu".to_string(),
+        description,
+        severity,
+        recommendation: Some(
+            "Comprobar si la carga es esperada; un uso alto y continuo sin motivo \
+            puede indicar minería de criptomonedas".to_string()
+        ),
+        affected_resources: Vec::new(),
+        timestamp: Utc::now(),
+    });
+}
+
+/// Hijos zombis a partir de los cuales se reporta el patrón
+const ZOMBIE_FLOOD_THRESHOLD: usize = 5;
+
+/// Detectar estados anómalos del proceso: detenido, en depuración o con hijos zombis
+fn detect_status_patterns(
+    process_monitor: &mut ProcessMonitor,
+    report: &mut Report,
+    process: &ProcessInfo,
+    scan_children: bool,
+) {
+    let halted = matches!(process.status, ProcessStatus::Stopped | ProcessStatus::Tracing);
+    if halted && !report.findings.iter().any(|f| f.title == "process_stopped") {
+        let description = format!("El proceso {} (PID: {}) está {}", process.name, process.pid, process.status);
+        println!("⚠️ {}", description);
+        report.add_finding(Finding {
+            title: "process_stopped".to_string(),
+            description,
+            severity: SeverityLevel::Warning,
+            recommendation: Some(
+                "Un proceso detenido o bajo un depurador puede estar siendo inspeccionado \
+                o manipulado por otro proceso; comprobar quién lo detuvo".to_string()
+            ),
+            affected_resources: vec![format!("PID {}", process.pid)],
+            timestamp: Utc::now(),
+        });
+    }
+    
+    if !scan_children {
+        return;
+    }
+    let zombies = process_monitor.zombie_children(process.pid);
+    if zombies.len() < ZOMBIE_FLOOD_THRESHOLD {
+        return;
+    }
+    // Reportar solo los zombis que no se conocían
+    let reported: Vec<&String> = report
+        .findings
+        .iter()
+        .filter(|f| f.title == "zombie_children")
+        .flat_map(|f| f.affected_resources.iter())
+        .collect();
+    let new_zombies: Vec<String> = zombies
+        .iter()
+        .map(|pid| format!("PID {}", pid))
+        .filter(|resource| !reported.contains(&resource))
+        .collect();
+    if new_zombies.is_empty() {
+        return;
+    }
+    
+    let description = format!(
+        "{} procesos hijos zombis sin recoger ({} nuevos)",
+        zombies.len(), new_zombies.len()
+    );
+    println!("⚠️ {}", description);
+    report.add_finding(Finding {
+        title: "zombie_children".to_string(),
+        description,
+        severity: SeverityLevel::Warning,
+        recommendation: Some(
+            "Muchos hijos zombis indican que el proceso lanza subprocesos sin esperarlos; \
+            revisar qué ejecuta y si el patrón se repite".to_string()
+        ),
+        affected_resources: new_zombies,
+        timestamp: Utc::now(),
+    });
+}
+
+/// Detectar escrituras sostenidas de alta entropía (cifrado, empaquetado o exfiltración)
+fn detect_entropy_patterns(
+    file_monitor: &FileMonitor, 
+    report: &mut Report, 
+    target_pid: u32,
+    min_writes: usize,
+) {
+    let writes = file_monitor.high_entropy_writes(target_pid);
+    if writes.len() < min_writes {
+        return;
+    }
+    
+    // Escribir datos cifrados y renombrar archivos es el patrón típico de ransomware
+    let renames = file_monitor
+        .get_events_for_pid(target_pid)
+        .iter()
+        .filter(|e| matches!(e.operation, FileOperation::Rename { .. }))
+        .count();
+    let severity = if renames > 0 { SeverityLevel::Critical } else { SeverityLevel::Warning };
+    
+    let mut description = format!(
+        "{} escrituras con entropía superior a {:.1} bits/byte",
+        writes.len(), HIGH_ENTROPY_THRESHOLD
+    );
+    if renames > 0 {
+        description.push_str(&format!(" junto con {} renombrados de archivos", renames));
     }
+    println!("⚠️ {}", description);
+    
+    // El hallazgo data de la última escritura, que es la que alcanzó el umbral
+    let timestamp = writes.last().map_or_else(Utc::now, |e| e.timestamp);
+    report.add_finding(Finding {
+        title: "high_entropy_writes".to_string(),
+        description,
+        severity,
+        recommendation: Some("Verificar si el proceso está cifrando o comprimiendo archivos del usuario".to_string()),
+        affected_resources: writes
+            .iter()
+            .map(|e| format!("{} (entropía {:.2})", e.path, e.entropy.unwrap_or(0.0)))
+            .collect(),
+        timestamp,
+    });
 }
 
 /// Detectar patrones sospechosos de red
 fn detect_network_patterns(
     network_monitor: &NetworkMonitor, 
     report: &mut Report, 
-    target_pid: u32
+    target_pid: u32,
+    process_name: &str,
 ) {
     let suspicious_network = network_monitor.detect_suspicious_patterns(target_pid);
     for pattern in suspicious_network {
-        report.add_alert("network", &pattern, None);
-        println!("⚠️ {}", pattern);
+        // Los detectores se ejecutan en cada iteración: reportar cada patrón una sola vez
+        if report.findings.iter().any(|f| f.title == "network" && f.description == pattern.message) {
+            continue;
+        }
+        report.add_detection("network", SeverityLevel::Critical, &pattern);
+        println!("⚠️ {}", pattern.message);
+    }
+    
+    // Los detectores se ejecutan en cada iteración: reportar cada conexión inusual una sola vez
+    for pattern in network_monitor.detect_unusual_connections(target_pid, process_name) {
+        if report.findings.iter().any(|f| f.title == "unusual_connection" && f.description == pattern.message) {
+            continue;
+        }
+        report.add_detection("unusual_connection", SeverityLevel::Warning, &pattern);
+        println!("⚠️ {}", pattern.message);
     }
 }
 
@@ -295,25 +1231,549 @@ pub async fn audit_binary(
     binary: &PathBuf,
     args: &Option<Vec<String>>,
     timeout: u64,
+    static_mode: bool,
+    trace_syscalls: bool,
+    follow_children: bool,
     config: &AppConfig,
 ) -> Result<()> {
     info!("Auditando binario: {:?}", binary);
     
+    if static_mode {
+        return audit_binary_static(binary, config).await;
+    }
+    
     if let Some(args_vec) = args {
         info!("Argumentos: {:?}", args_vec);
     }
     
     info!("Tiempo máximo de ejecución: {} segundos", timeout);
-    info!("Esta función está parcialmente implementada");
-
-    Ok(())
+    
+    if trace_syscalls {
+        return audit_binary_traced(binary, args.as_deref().unwrap_or_default(), timeout, config).await;
+    }
+    
+    audit_binary_monitored(binary, args.as_deref().unwrap_or_default(), timeout, follow_children, config).await
 }
 
-/// Monitorear actividad del sistema
-pub async fn monitor_system(
+/// Auditar un binario ejecutándolo y monitoreando sus archivos abiertos y conexiones.
+/// Con `follow_children` se monitorean también los procesos que crea, a medida que
+/// aparecen, hasta que termina todo el árbol o se agota el tiempo.
+async fn audit_binary_monitored(
+    binary: &PathBuf,
+    args: &[String],
+    timeout: u64,
+    follow_children: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    // Se escucha antes de ejecutar el binario para no dejarlo huérfano si el puerto está ocupado
+    let live_server = config.serve
+        .map(|addr| LiveServer::start(addr, LiveMode::Audit, config.redactor.clone()))
+        .transpose()?;
+    // La captura se abre antes de ejecutar el binario para no perder sus primeros archivos
+    let mut capture = config.capture_backend.open()?;
+    info!("Captura de eventos: {}", capture.backend().label());
+    let mut child = tokio::process::Command::new(binary)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::ProcessAccessError(format!("No se pudo ejecutar {}: {}", binary.display(), e)))?;
+    let root_pid = child
+        .id()
+        .ok_or_else(|| AppError::ProcessAccessError(format!("{} terminó antes de poder monitorearlo", binary.display())))?;
+    
+    let mut process_monitor = ProcessMonitor::new();
+    process_monitor.set_max_cmd_args(config.max_cmd_args);
+    process_monitor.set_refresh_throttle(Duration::from_millis(config.refresh_throttle_ms));
+    process_monitor.set_include_self(config.include_self);
+    let mut file_monitor = FileMonitor::new();
+    let mut network_monitor = NetworkMonitor::new();
+    network_monitor.set_closed_history_len(config.closed_connections_history);
+    file_monitor.add_persistence_locations(&config.persistence_paths);
+    network_monitor.add_sensitive_ports(&config.sensitive_ports);
+    network_monitor.add_expected_ports(&config.expected_ports);
+    network_monitor.add_sensitive_endpoints(&config.sensitive_endpoints);
+    let spawn_rules = spawn_rules(&config.spawn_rules);
+    let mut suspicious_spawns = Vec::new();
+    let mut ip_pseudonymizer = config.redact_network.then(IpPseudonymizer::load_default).transpose()?;
+    // Base de datos de ASN para agrupar la salida de red (--asn-db); se carga antes de empezar
+    let asn_database = config.asn_db.as_deref().map(AsnDatabase::load).transpose()?;
+    // Resoluciones DNS (--dns-log); se vuelve a leer al terminar
+    let mut dns_log = config.dns_log.as_deref().map(DnsLog::load).transpose()?;
+    if !config.include_self {
+        for addr in llm::endpoint_addrs(&config.llm.api_url).await {
+            network_monitor.ignore_remote(addr);
+        }
+    }
+    
+    let name = binary
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| binary.display().to_string());
+    let mut report = Report::new_for_process(root_pid, name.clone());
+    report.title = format!("Auditoría de {}", name);
+    report.analyst_notes = config.llm.analyst_notes.clone();
+    report.set_max_entries(config.max_report_entries);
+    report.set_encoding(config.report_encoding);
+    report.set_verbosity(config.verbose);
+    match process_monitor.get_process_by_pid(root_pid) {
+        Ok(Some(process_info)) => report.set_process_info(process_info),
+        // Si ya terminó, el muestreo lo registra como proceso finalizado
+        Ok(None) => {}
+        Err(e) => report.add_warning("process", &e.to_string(), None),
+    }
+    report.audited_processes.push(AuditedProcess {
+        pid: root_pid,
+        parent_pid: None,
+        name: name.clone(),
+        cmd_line: Some(std::iter::once(binary.display().to_string()).chain(args.iter().cloned()).collect()),
+        first_seen: Utc::now(),
+        exited_at: None,
+    });
+    
+    println!(
+        "Ejecutando {} (PID: {}) durante un máximo de {} s{}",
+        binary.display(), root_pid, timeout,
+        if follow_children { ", siguiendo a sus procesos hijos" } else { "" }
+    );
+    
+    let mut tick_interval = time::interval(AUDIT_SAMPLE_INTERVAL);
+    // --max-runtime acorta el tiempo máximo si vence antes
+    let runtime_capped = config.remaining_runtime().is_some_and(|remaining| remaining < Duration::from_secs(timeout));
+    let deadline = time::Instant::now() + config.remaining_runtime().unwrap_or(Duration::MAX).min(Duration::from_secs(timeout));
+    let mut root_running = true;
+    let mut timed_out = false;
+    loop {
+        tick_interval.tick().await;
+        
+        // El binario es hijo de ShadowTrace: recogerlo para que no quede como zombi
+        if root_running && child.try_wait()?.is_some() {
+            root_running = false;
+        }
+        
+        // Incorporar los descendientes de cualquier proceso del árbol (también los
+        // de hijos huérfanos cuyo padre ya terminó)
+        if follow_children {
+            let tree = process_monitor.build_process_tree();
+            let live: Vec<u32> = report.audited_processes
+                .iter()
+                .filter(|p| p.exited_at.is_none())
+                .map(|p| p.pid)
+                .collect();
+            for pid in live {
+                for (descendant, parent) in tree.descendants(pid) {
+                    if report.audited_processes.iter().any(|p| p.pid == descendant) {
+                        continue;
+                    }
+                    let Some(info) = process_monitor.process_info(descendant) else {
+                        continue;
+                    };
+                    println!("↳ Nuevo proceso hijo: {} (PID: {}, padre: {})", info.name, descendant, parent);
+                    for spawn in tree.suspicious_spawns(&[descendant], &spawn_rules) {
+                        add_spawn_finding(&mut report, &spawn);
+                        suspicious_spawns.push(spawn);
+                    }
+                    report.audited_processes.push(AuditedProcess {
+                        pid: descendant,
+                        parent_pid: Some(parent),
+                        name: info.name.clone(),
+                        cmd_line: info.cmd_line.clone(),
+                        first_seen: Utc::now(),
+                        exited_at: None,
+                    });
+                    report.add_process(info);
+                }
+            }
+        }
+        
+        // Registrar la actividad de los procesos vivos y marcar los que terminaron
+        let live: Vec<u32> = report.audited_processes
+            .iter()
+            .filter(|p| p.exited_at.is_none())
+            .map(|p| p.pid)
+            .collect();
+        process_monitor.refresh_specific(&live);
+        for pid in live {
+            let running = if pid == root_pid {
+                root_running
+            } else {
+                process_monitor.process_info(pid).is_some_and(|info| !info.status.is_terminated())
+            };
+            if running {
+                capture.record_files(&mut file_monitor, pid);
+                capture.record_connections(&mut network_monitor, pid);
+            } else if let Some(process) = report.audited_processes.iter_mut().find(|p| p.pid == pid) {
+                process.exited_at = Some(Utc::now());
+                if pid != root_pid {
+                    println!("↳ Terminó el proceso hijo {} (PID: {})", process.name, pid);
+                }
+            }
+        }
+        if let Some(live_server) = &live_server {
+            live_server.publish(
+                report.processes.clone(),
+                report.findings.clone(),
+                file_monitor.get_events().len(),
+                network_monitor.get_events().len(),
+            );
+        }
+        
+        if report.audited_processes.iter().all(|p| p.exited_at.is_some()) {
+            println!("{}", if follow_children { "El binario y todos sus procesos hijos terminaron" } else { "El binario terminó" });
+            break;
+        }
+        if time::Instant::now() >= deadline {
+            timed_out = true;
+            break;
+        }
+    }
+    
+    // Al agotarse el tiempo se detiene lo que siga en ejecución
+    if timed_out && runtime_capped {
+        stop_for_max_runtime(&mut report);
+    }
+    if timed_out {
+        println!("⚠️ Se alcanzó el tiempo máximo; deteniendo los procesos que siguen en ejecución");
+        let killed = child.start_kill();
+        audit_log(AuditEvent::from_result("kill", Some(format!("PID {} ({})", root_pid, binary.display())), &killed));
+        for process in report.audited_processes.iter().filter(|p| p.exited_at.is_none() && p.pid != root_pid) {
+            let outcome = if process_monitor.kill_process(process.pid) { "ok" } else { "error: no se pudo detener el proceso" };
+            audit_log(AuditEvent::new("kill", Some(format!("PID {} ({})", process.pid, process.name)), outcome));
+        }
+        let _ = child.wait().await;
+        report.add_warning(
+            "audit",
+            &format!("Se alcanzó el tiempo máximo de {} s y se detuvieron los procesos en ejecución", timeout),
+            None,
+        );
+    }
+    
+    // Atribuir la actividad de cada proceso del árbol a la auditoría
+    add_recorded_events(&mut report, &file_monitor, &network_monitor);
+    let audited: Vec<(u32, String)> = report.audited_processes.iter().map(|p| (p.pid, p.name.clone())).collect();
+    for (pid, process_name) in &audited {
+        detect_file_patterns(&file_monitor, &mut report, *pid);
+        detect_network_patterns(&network_monitor, &mut report, *pid, process_name);
+        detect_persistence_patterns(&file_monitor, &mut report, *pid);
+        for closed in network_monitor.get_closed_connections_for_pid(*pid) {
+            report.add_closed_connection(closed.clone());
+        }
+    }
+    if let Some(database) = &asn_database {
+        summarize_egress(&mut report, database, &name, network_monitor.get_events().iter().cloned());
+    }
+    if let Some(dns_log) = &mut dns_log {
+        dns_log.reload();
+        detect_raw_ip_connections(&mut report, dns_log, config.raw_ip_severity, &network_monitor, network_monitor.get_events());
+    }
+    if let Some(max_lines) = config.with_logs {
+        add_system_logs(&mut report, root_pid, &name, Some(&binary.display().to_string()), max_lines);
+    }
+    if audited.len() > 1 {
+        report.add_info(
+            "process_tree",
+            &format!("El binario creó {} procesos durante la auditoría", audited.len() - 1),
+            None,
+        );
+    }
+    println!(
+        "Auditoría finalizada: {} procesos, {} eventos de archivo, {} eventos de red",
+        audited.len(), file_monitor.get_events().len(), network_monitor.get_events().len()
+    );
+    
+    // Analizar con LLM si está disponible
+    if let Some(client) = &config.llm_client {
+        println!("Analizando comportamiento con IA...");
+        
+        let depth = config.analysis_depth;
+        let mut process_json = serde_json::json!({
+            "binary": binary.display().to_string(),
+            "process_tree": report.audited_processes,
+        });
+        if !report.egress_by_asn.is_empty() {
+            process_json["egress_by_asn"] = serde_json::to_value(&report.egress_by_asn)?;
+        }
+        if !suspicious_spawns.is_empty() {
+            process_json["suspicious_spawns"] = serde_json::to_value(&suspicious_spawns)?;
+        }
+        if let Some(logs) = &report.system_logs {
+            process_json["system_logs"] = system_logs_json(logs, depth);
+        }
+        let mut file_events_json = depth.shape_events(serde_json::to_value(aggregate_file_events(file_monitor.get_events()))?, "operation");
+        let mut network_events_json = depth.shape_events(serde_json::to_value(network_monitor.get_events())?, "remote_addr");
+        if let Some(redactor) = &config.redactor {
+            redactor.redact_value(&mut process_json);
+            redactor.redact_value(&mut file_events_json);
+        }
+        if let Some(pseudonymizer) = &mut ip_pseudonymizer {
+            pseudonymizer.pseudonymize_value(&mut process_json);
+            pseudonymizer.pseudonymize_value(&mut network_events_json);
+        }
+        
+        match llm_within_max_runtime(config, client.comprehensive_analysis(process_json, file_events_json, network_events_json)).await {
+            Ok(analysis) => {
+                report.set_llm_model(client.answered_by());
+                note_reduced_context(&mut report, client);
+                report.add_info(
+                    "llm_analysis",
+                    "Análisis de comportamiento realizado por IA",
+                    Some(serde_json::from_str(&analysis)?),
+                );
+                println!("\n--- Análisis de IA ---\n{}\n", analysis);
+            }
+            Err(e) => {
+                println!("⚠️ Error al realizar análisis con LLM: {}. Continuando sin análisis.", e);
+            }
+        }
+    }
+    if config.offline {
+        add_offline_analysis(&mut report, &[]);
+    }
+    
+    save_audit_report(report, ip_pseudonymizer.as_mut(), config)
+}
+
+/// Auditar un binario ejecutándolo bajo strace y resumiendo sus llamadas al sistema
+async fn audit_binary_traced(binary: &Path, args: &[String], timeout: u64, config: &AppConfig) -> Result<()> {
+    println!("Ejecutando {} bajo strace (máximo {} s)...", binary.display(), timeout);
+    let limit = config.remaining_runtime().unwrap_or(Duration::MAX).min(Duration::from_secs(timeout));
+    let summary = syscalls::trace_binary(binary, args, limit).await?;
+    let mut ip_pseudonymizer = config.redact_network.then(IpPseudonymizer::load_default).transpose()?;
+    
+    println!("Llamadas al sistema: {}", summary.total_calls());
+    for (category, calls) in summary.category_totals() {
+        println!("  {:<10} {:>10}", category.label(), calls);
+    }
+    if summary.timed_out {
+        println!("⚠️ Se alcanzó el tiempo máximo; el binario fue detenido");
+    }
+    
+    let name = binary
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| binary.display().to_string());
+    let mut report = Report::new(&format!("Auditoría de {}", name));
+    report.analyst_notes = config.llm.analyst_notes.clone();
+    report.set_encoding(config.report_encoding);
+    report.set_verbosity(config.verbose);
+    report.set_syscalls(summary.clone());
+    for finding in report.findings.iter().filter(|f| f.severity != SeverityLevel::Info) {
+        println!("[{}] {}", finding.severity.marker(), finding.description);
+    }
+    
+    // Analizar con LLM si está disponible
+    if let Some(client) = &config.llm_client {
+        println!("Analizando llamadas al sistema con IA...");
+        
+        let categories: serde_json::Map<String, serde_json::Value> = summary
+            .category_totals()
+            .into_iter()
+            .map(|(category, calls)| (category.label().to_string(), calls.into()))
+            .collect();
+        let mut syscalls_json = serde_json::json!({
+            "binary": name,
+            "args": args,
+            "timed_out": summary.timed_out,
+            "exit_code": summary.exit_code,
+            "total_calls": summary.total_calls(),
+            "categories": categories,
+            "syscalls": summary.syscalls,
+        });
+        if let Some(redactor) = &config.redactor {
+            redactor.redact_value(&mut syscalls_json);
+        }
+        
+        match llm_within_max_runtime(config, client.analyze_syscalls(syscalls_json)).await {
+            Ok(llm_analysis) => {
+                report.set_llm_model(client.answered_by());
+                report.add_info("llm_analysis", &llm_analysis, None);
+                println!("\n--- Análisis de IA ---\n{}\n", llm_analysis);
+            }
+            Err(e) => {
+                println!("⚠️ Error al realizar análisis con LLM: {}. Continuando sin análisis.", e);
+            }
+        }
+    }
+    
+    save_audit_report(report, ip_pseudonymizer.as_mut(), config)
+}
+
+/// Auditar un binario sin ejecutarlo (análisis de cabeceras, secciones y cadenas)
+async fn audit_binary_static(binary: &Path, config: &AppConfig) -> Result<()> {
+    let analysis = static_analysis::analyze_binary(binary)?;
+    let mut ip_pseudonymizer = config.redact_network.then(IpPseudonymizer::load_default).transpose()?;
+    
+    println!("Análisis estático de {}", analysis.path.display());
+    println!("  Formato: {}", analysis.format);
+    println!("  Tamaño: {} bytes (entropía {:.2})", analysis.size, analysis.entropy);
+    println!("  Bibliotecas: {}", analysis.libraries.len());
+    println!("  Importaciones: {}", analysis.imports.len());
+    println!("  Secciones: {}", analysis.sections.len());
+    for section in &analysis.sections {
+        let marker = if section.entropy >= static_analysis::HIGH_ENTROPY_THRESHOLD { " ⚠️" } else { "" };
+        println!("    {:<20} {:>10} bytes  entropía {:.2}{}", section.name, section.size, section.entropy, marker);
+    }
+    
+    let name = binary
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| binary.display().to_string());
+    let mut report = Report::new(&format!("Auditoría estática de {}", name));
+    report.analyst_notes = config.llm.analyst_notes.clone();
+    report.set_encoding(config.report_encoding);
+    report.set_verbosity(config.verbose);
+    
+    for finding in analysis.findings() {
+        if finding.severity != SeverityLevel::Info {
+            println!("[{}] {}", finding.severity.marker(), finding.description);
+        }
+        report.add_finding(finding);
+    }
+    
+    // Analizar con LLM si está disponible
+    if let Some(client) = &config.llm_client {
+        println!("Analizando binario con IA...");
+        
+        let mut analysis_json = serde_json::to_value(&analysis)?;
+        if let Some(redactor) = &config.redactor {
+            redactor.redact_value(&mut analysis_json);
+        }
+        
+        match llm_within_max_runtime(config, client.analyze_static_binary(analysis_json)).await {
+            Ok(llm_analysis) => {
+                report.set_llm_model(client.answered_by());
+                report.add_info("llm_analysis", &llm_analysis, None);
+                println!("\n--- Análisis de IA ---\n{}\n", llm_analysis);
+            }
+            Err(e) => {
+                println!("⚠️ Error al realizar análisis con LLM: {}. Continuando sin análisis.", e);
+            }
+        }
+    }
+    
+    save_audit_report(report, ip_pseudonymizer.as_mut(), config)
+}
+
+/// Cerrar, redactar, guardar y exportar el reporte de una auditoría
+fn save_audit_report(mut report: Report, ip_pseudonymizer: Option<&mut IpPseudonymizer>, config: &AppConfig) -> Result<()> {
+    report.update_end_time();
+    
+    // Redactar secretos antes de guardar
+    if let Some(redactor) = &config.redactor {
+        report.redact(redactor);
+    }
+    if let Some(pseudonymizer) = ip_pseudonymizer {
+        pseudonymize_report(&mut report, pseudonymizer);
+    }
+    
+    save_artifacts(&report, config);
+    
+    Ok(())
+}
+
+/// Formato de salida de comandos informativos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Tabla legible (por defecto)
+    Table,
+    /// JSON para scripts
+    Json,
+}
+
+/// Comparar dos reportes JSON guardados: añadidos en verde, eliminados en rojo y
+/// cambios de recursos en amarillo (sin colores con `plain` o fuera de una terminal)
+pub fn diff_reports(before: &Path, after: &Path, plain: bool) -> Result<()> {
+    use crossterm::style::Stylize;
+    
+    let load = |path: &Path| {
+        Report::load_json(path).map_err(|e| AppError::ReportGenerationError(format!("No se pudo leer {}: {}", path.display(), e)))
+    };
+    let diff = load(before)?.diff(&load(after)?);
+    let color = !plain && std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    
+    println!("Comparando: {} → {}", diff.before, diff.after);
+    if diff.is_empty() {
+        println!("Sin diferencias entre los reportes");
+        return Ok(());
+    }
+    
+    for section in &diff.sections {
+        println!();
+        if color {
+            println!("{}", format!("== {} ==", section.category).bold());
+        } else {
+            println!("== {} ==", section.category);
+        }
+        for (marker, items) in [('+', &section.added), ('-', &section.removed), ('~', &section.changed)] {
+            for item in items {
+                let line = format!("{} {}", marker, item);
+                match (color, marker) {
+                    (false, _) => println!("{}", line),
+                    (true, '+') => println!("{}", line.green()),
+                    (true, '-') => println!("{}", line.red()),
+                    (true, _) => println!("{}", line.yellow()),
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Listar los modelos disponibles en el endpoint LLM configurado
+pub async fn list_models(format: OutputFormat, effective: &EffectiveConfig) -> Result<()> {
+    let llm_config = effective.llm_config()?;
+    let api_url = llm_config.api_url.clone();
+    let current_model = llm_config.model.clone();
+    let client = LlmClient::new(llm_config)?;
+    let models = client.list_models().await?;
+    
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&models)?),
+        OutputFormat::Table => {
+            if models.is_empty() {
+                println!("No hay modelos disponibles en {}", api_url);
+                return Ok(());
+            }
+            println!("{:<32} {:>10} {:<12} {:<10} CUANTIZACIÓN", "MODELO", "TAMAÑO", "FAMILIA", "PARÁMETROS");
+            for model in &models {
+                let size = model.size
+                    .map(|bytes| format!("{:.1} GB", bytes as f64 / 1_000_000_000.0))
+                    .unwrap_or_else(|| "-".to_string());
+                // Ollama añade la etiqueta ":latest" cuando el modelo se indica sin versión
+                let is_current = model.name == current_model || model.name == format!("{}:latest", current_model);
+                let marker = if is_current { " *" } else { "" };
+                println!(
+                    "{:<32} {:>10} {:<12} {:<10} {}{}",
+                    model.name,
+                    size,
+                    model.family.as_deref().unwrap_or("-"),
+                    model.parameter_size.as_deref().unwrap_or("-"),
+                    model.quantization.as_deref().unwrap_or("-"),
+                    marker,
+                );
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Formato de la lista de procesos en modo sistema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    /// Tabla plana (por defecto)
+    Table,
+    /// Jerarquía padre/hijo con conectores
+    Tree,
+}
+
+/// Monitorear actividad del sistema
+pub async fn monitor_system(
     watch: bool,
     duration: u64,
     suspicious_only: bool,
+    format: ListFormat,
+    quiet: bool,
     config: &AppConfig,
 ) -> Result<()> {
     info!("Monitoreando sistema durante {} segundos", duration);
@@ -326,7 +1786,470 @@ pub async fn monitor_system(
         info!("Mostrando solo actividad sospechosa");
     }
     
-    info!("Esta función está parcialmente implementada");
+    let mut process_monitor = ProcessMonitor::new();
+    process_monitor.set_max_cmd_args(config.max_cmd_args);
+    process_monitor.set_refresh_throttle(Duration::from_millis(config.refresh_throttle_ms));
+    process_monitor.set_include_self(config.include_self);
+    let start = std::time::Instant::now();
+    // Solo tiene sentido mientras se observa el sistema (--watch)
+    if config.serve.is_some() && !watch {
+        println!("ℹ️ --serve solo publica el estado en vivo con --watch");
+    }
+    let live_server = config.serve
+        .filter(|_| watch)
+        .map(|addr| LiveServer::start(addr, LiveMode::System, config.redactor.clone()))
+        .transpose()?;
+    
+    // Horario de `[schedule]`: solo se aplica a la observación continua
+    let schedule = watch.then_some(&config.schedule);
+    let mut paused = false;
+    // Con --watch los detectores se repiten en cada muestra: cada hallazgo se muestra una vez
+    let mut findings = SystemFindings::new(schedule);
+    
+    loop {
+        // Fuera de `active_hours` no se muestrea hasta que empiece la siguiente franja
+        if schedule.filter(|schedule| !schedule.is_active()).is_some() {
+            if !paused {
+                paused = true;
+                let resume = config.schedule.next_active_start(Local::now().time())
+                    .map(|start| format!("; se reanuda a las {}", start.format("%H:%M")))
+                    .unwrap_or_default();
+                info!("Fuera del horario de monitoreo, observación en pausa");
+                println!("⏸️ Fuera del horario de monitoreo ([schedule] active_hours){}", resume);
+            }
+        } else {
+            if paused {
+                paused = false;
+                info!("Dentro del horario de monitoreo, se reanuda la observación");
+                println!("▶️ Dentro del horario de monitoreo: se reanuda la observación");
+            }
+            match format {
+                ListFormat::Table => print_process_table(&mut process_monitor),
+                ListFormat::Tree => print_process_tree(&mut process_monitor),
+            }
+            if let Some(live_server) = &live_server {
+                live_server.publish(process_monitor.get_all_processes(), Vec::new(), 0, 0);
+            }
+            
+            // Destinos y archivos compartidos por varios procesos
+            print_correlations(&mut process_monitor, quiet, &mut findings);
+            // Procesos cuyo nombre o ruta no encaja con su ejecutable
+            print_masquerades(&mut process_monitor, &mut findings);
+            // Cadenas de procesos propias de un ataque (servidor web → shell...)
+            print_suspicious_spawns(&mut process_monitor, config, &mut findings);
+        }
+        
+        if !watch || start.elapsed().as_secs() >= duration {
+            break;
+        }
+        if config.runtime_exceeded() {
+            warn!("Se alcanzó --max-runtime: se detiene el monitoreo del sistema");
+            println!("⏱️ Se alcanzó el tiempo máximo de ejecución (--max-runtime)");
+            break;
+        }
+        
+        time::sleep(Duration::from_secs(2)).await;
+        if !paused {
+            println!();
+        }
+    }
+    
+    let silenced = findings.silenced_count();
+    if silenced > 0 {
+        info!("{} hallazgos silenciados por las horas de silencio", silenced);
+        println!("\n🔕 {} hallazgos silenciados ([schedule] quiet_hours)", silenced);
+    }
     
     Ok(())
-} 
+}
+
+/// Hallazgos de `system` ya mostrados o silenciados. Decide si se muestra cada hallazgo
+/// en el momento en que aparece, con las horas de silencio del horario
+struct SystemFindings<'a> {
+    schedule: Option<&'a Schedule>,
+    shown: HashSet<String>,
+    silenced: HashSet<String>,
+}
+
+impl<'a> SystemFindings<'a> {
+    fn new(schedule: Option<&'a Schedule>) -> Self {
+        Self {
+            schedule,
+            shown: HashSet::new(),
+            silenced: HashSet::new(),
+        }
+    }
+
+    /// Indica si el hallazgo se muestra: es nuevo y las horas de silencio de esa hora
+    /// permiten su severidad. Uno silenciado se muestra si sigue presente al acabar el silencio
+    fn admit(&mut self, description: &str, severity: SeverityLevel, time: NaiveTime) -> bool {
+        if self.shown.contains(description) {
+            return false;
+        }
+        if self.schedule.filter(|schedule| !schedule.allows_at(severity, time)).is_some() {
+            if self.silenced.insert(description.to_string()) {
+                info!("Hallazgo silenciado por las horas de silencio: {}", description);
+            }
+            return false;
+        }
+        self.shown.insert(description.to_string())
+    }
+
+    /// Hallazgos silenciados que no llegaron a mostrarse
+    fn silenced_count(&self) -> usize {
+        self.silenced.difference(&self.shown).count()
+    }
+}
+
+/// Correlacionar las conexiones y archivos abiertos de todos los procesos y mostrar los
+/// hallazgos nuevos que admite `findings`
+fn print_correlations(process_monitor: &mut ProcessMonitor, quiet: bool, findings: &mut SystemFindings) {
+    let mut report = Report::new("Correlación entre procesos");
+    let processes = process_monitor.get_all_processes();
+    let mut progress = ScanProgress::new("Correlacionando procesos", processes.len(), quiet);
+    for process in processes {
+        progress.advance(process.pid);
+        for event in enumerate_connections(process.pid) {
+            report.add_network_event(event);
+        }
+        for path in enumerate_open_files(process.pid) {
+            report.add_file_activity(FileActivity {
+                path: PathBuf::from(path),
+                operation: FileOperation::Open,
+                process_id: Some(process.pid),
+                timestamp: Utc::now(),
+                size: None,
+                entropy: None,
+            });
+        }
+    }
+    progress.finish();
+    
+    report.correlate();
+    let lines: Vec<String> = report.findings
+        .iter()
+        .map(|finding| (finding.severity, format!(
+            "[{}] {} ({})", finding.severity.marker(), finding.description, finding.affected_resources[1..].join(", ")
+        )))
+        .filter(|(severity, line)| findings.admit(line, *severity, Local::now().time()))
+        .map(|(_, line)| line)
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    
+    println!("\nActividad compartida entre procesos:");
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+/// Intervalo mínimo entre dos actualizaciones de la línea de progreso
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Línea de progreso en stderr para recorridos largos de procesos
+/// (procesados / total, PID actual, tiempo transcurrido y estimación restante).
+/// Solo se dibuja en una terminal y sin `--quiet`, para no ensuciar tuberías ni logs.
+struct ScanProgress {
+    label: &'static str,
+    total: usize,
+    done: usize,
+    start: std::time::Instant,
+    last_draw: Option<std::time::Instant>,
+    enabled: bool,
+}
+
+impl ScanProgress {
+    fn new(label: &'static str, total: usize, quiet: bool) -> Self {
+        Self {
+            label,
+            total,
+            done: 0,
+            start: std::time::Instant::now(),
+            last_draw: None,
+            enabled: !quiet && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Registrar que se empieza a procesar `pid`
+    fn advance(&mut self, pid: u32) {
+        self.done += 1;
+        if !self.enabled || self.last_draw.is_some_and(|t| t.elapsed() < PROGRESS_REDRAW_INTERVAL) {
+            return;
+        }
+        self.last_draw = Some(std::time::Instant::now());
+        let elapsed = self.start.elapsed();
+        // La estimación se basa en el ritmo medio de los procesos ya completados
+        let completed = self.done.saturating_sub(1);
+        let eta = if completed > 0 {
+            let remaining = (self.total - completed) as f64;
+            format!("{:.0} s", elapsed.as_secs_f64() / completed as f64 * remaining)
+        } else {
+            "--".to_string()
+        };
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{} {}/{} · PID {} · {:.0} s transcurridos · restante {}",
+            self.label, self.done, self.total, pid, elapsed.as_secs_f64(), eta
+        );
+        let _ = stderr.flush();
+    }
+
+    /// Borrar la línea de progreso
+    fn finish(&mut self) {
+        if self.enabled && self.last_draw.is_some() {
+            let mut stderr = std::io::stderr();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
+/// Imprimir los procesos que parecen hacerse pasar por otros o cuyo ejecutable se borró
+fn print_masquerades(process_monitor: &mut ProcessMonitor, findings: &mut SystemFindings) {
+    let mut suspects: Vec<(u32, String)> = process_monitor
+        .get_all_processes()
+        .into_iter()
+        .filter_map(|process| {
+            let description = process.masquerade().or_else(|| process.has_deleted_exe().then(|| format!(
+                "{} (PID: {}) se ejecuta desde un binario borrado del disco", process.name, process.pid
+            )))?;
+            Some((process.pid, description))
+        })
+        .collect();
+    suspects.sort();
+    suspects.retain(|(_, description)| findings.admit(description, SeverityLevel::Critical, Local::now().time()));
+    if suspects.is_empty() {
+        return;
+    }
+    
+    println!("\nPosibles suplantaciones y ejecutables borrados:");
+    for (_, description) in &suspects {
+        println!("⚠️ {}", description);
+    }
+}
+
+/// Imprimir las relaciones padre → hijo sospechosas de todo el sistema
+fn print_suspicious_spawns(process_monitor: &mut ProcessMonitor, config: &AppConfig, findings: &mut SystemFindings) {
+    let tree = process_monitor.build_process_tree();
+    let mut pids: Vec<u32> = tree.names.keys().copied().collect();
+    pids.sort_unstable();
+    let descriptions: Vec<String> = tree
+        .suspicious_spawns(&pids, &spawn_rules(&config.spawn_rules))
+        .iter()
+        .map(SuspiciousSpawn::description)
+        .filter(|description| findings.admit(description, SeverityLevel::Critical, Local::now().time()))
+        .collect();
+    if descriptions.is_empty() {
+        return;
+    }
+    
+    println!("\nRelaciones padre-hijo sospechosas:");
+    for description in &descriptions {
+        println!("⚠️ {}", description);
+    }
+}
+
+/// Imprimir la lista plana de procesos (el propio ShadowTrace se omite o se etiqueta)
+fn print_process_table(process_monitor: &mut ProcessMonitor) {
+    let mut processes = process_monitor.get_all_processes();
+    processes.sort_by_key(|p| p.pid);
+    
+    println!("{:<8} {:>7} {:>12}  NOMBRE", "PID", "CPU %", "MEMORIA");
+    for p in &processes {
+        let label = if p.pid == own_pid() { " [shadowtrace]" } else { "" };
+        println!("{:<8} {:>7.1} {:>12}  {}{}", p.pid, p.cpu_usage, format_memory(p.memory_usage), p.name, label);
+    }
+}
+
+/// Imprimir la jerarquía de procesos enraizada en PID 1
+fn print_process_tree(process_monitor: &mut ProcessMonitor) {
+    let tree = process_monitor.build_process_tree();
+    for line in tree.render_lines() {
+        println!("{}", line);
+    }
+}
+
+/// Procesos que muestra el resumen sin terminal
+const STATUS_SUMMARY_PROCESSES: usize = 5;
+
+/// Resumen breve del estado para cuando no hay terminal interactiva (tuberías, CI)
+pub fn print_status_summary(config: &AppConfig) {
+    println!("ShadowTrace {}", env!("CARGO_PKG_VERSION"));
+    if config.no_llm {
+        println!("LLM: desactivado");
+    } else {
+        println!("LLM: {} ({})", config.llm.model, config.llm.api_url);
+    }
+    
+    let mut process_monitor = ProcessMonitor::new();
+    process_monitor.set_include_self(config.include_self);
+    let mut processes = process_monitor.get_all_processes();
+    println!("Procesos visibles: {}", processes.len());
+    
+    // La CPU necesita dos lecturas para ser fiable: ordenar por memoria
+    processes.sort_by_key(|p| Reverse(p.memory_usage));
+    println!("\n{:<8} {:>12}  NOMBRE", "PID", "MEMORIA");
+    for p in processes.iter().take(STATUS_SUMMARY_PROCESSES) {
+        println!("{:<8} {:>12}  {}", p.pid, format_memory(p.memory_usage), p.name);
+    }
+}
+
+/// Mostrar la configuración efectiva con el origen de cada valor
+pub fn show_config(effective: &EffectiveConfig) {
+    let values = toml::Value::try_from(&effective.values)
+        .ok()
+        .and_then(|v| v.as_table().cloned())
+        .unwrap_or_default();
+    
+    for (key, source) in &effective.sources {
+        let value = match values.get(*key) {
+            // La clave de API no se muestra nunca
+            Some(_) if *key == "api_key" => format!("\"{}\"", REDACTED),
+            Some(v) => v.to_string(),
+            None => "(sin definir)".to_string(),
+        };
+        println!("{:<20} = {:<40} # {}", key, value, source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Alimentar el detector con una muestra por segundo
+    fn feed(series: &[f32], min_secs: u64) -> Vec<Finding> {
+        let mut report = Report::new("CPU");
+        let mut cpu = SustainedCpu::new(80.0);
+        let mut reported = None;
+        let start = Instant::now();
+        for (second, usage) in series.iter().enumerate() {
+            cpu.record(start + Duration::from_secs(second as u64), *usage);
+            detect_sustained_cpu(&mut report, &cpu, min_secs, &mut reported);
+        }
+        report.findings
+    }
+
+    #[test]
+    fn deleted_executable_is_reported_once_as_critical() {
+        let mut report = Report::new("Ejecutable borrado");
+        let mut process = ProcessInfo {
+            pid: 4242,
+            name: "updater".to_string(),
+            path: Some("/tmp/.cache/updater".to_string()),
+            cmd_line: None,
+            cmd_truncated: false,
+            user: None,
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            start_time: Utc::now(),
+            children: Vec::new(),
+            thread_count: None,
+            status: ProcessStatus::Running,
+            deleted_exe: Some(false),
+            environ: None,
+        };
+        let mut reported = false;
+
+        detect_deleted_exe(&mut report, &process, &mut reported);
+        assert!(report.findings.is_empty());
+
+        process.deleted_exe = Some(true);
+        detect_deleted_exe(&mut report, &process, &mut reported);
+        detect_deleted_exe(&mut report, &process, &mut reported);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].title, "deleted_exe");
+        assert_eq!(report.findings[0].severity, SeverityLevel::Critical);
+    }
+
+    #[test]
+    fn system_findings_consult_quiet_hours_when_they_appear() {
+        let schedule: Schedule = toml::from_str("[[quiet_hours]]\nhours = \"22:00-06:00\"\nmin_severity = \"critical\"").unwrap();
+        let mut findings = SystemFindings::new(Some(&schedule));
+        let night = NaiveTime::from_hms_opt(23, 30, 0).unwrap();
+        let morning = NaiveTime::from_hms_opt(6, 30, 0).unwrap();
+
+        // De noche solo pasan los críticos
+        assert!(!findings.admit("destino compartido", SeverityLevel::Error, night));
+        assert!(findings.admit("nginx → sh", SeverityLevel::Critical, night));
+        assert_eq!(findings.silenced_count(), 1);
+
+        // Cada hallazgo se muestra una vez; uno silenciado que sigue presente sale al terminar el silencio
+        assert!(!findings.admit("nginx → sh", SeverityLevel::Critical, morning));
+        assert!(!findings.admit("destino compartido", SeverityLevel::Error, night));
+        assert!(findings.admit("destino compartido", SeverityLevel::Error, morning));
+        assert_eq!(findings.silenced_count(), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn system_findings_never_reference_shadowtrace_unless_included() {
+        // Regla que marca a ShadowTrace lanzando un hijo, para que el hallazgo exista
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let mut process_monitor = ProcessMonitor::new();
+        process_monitor.set_include_self(true);
+        let own = process_monitor.get_process_by_pid(own_pid()).unwrap().unwrap();
+        let config = AppConfig {
+            spawn_rules: vec![SpawnRule {
+                parents: vec![own.name.clone()],
+                children: vec!["sleep".to_string()],
+                reason: "prueba".to_string(),
+            }],
+            ..AppConfig::default()
+        };
+        let references_self = |findings: &SystemFindings| {
+            findings.shown.iter().any(|description| description.contains(&format!("(PID: {})", own_pid())))
+        };
+
+        let mut findings = SystemFindings::new(None);
+        print_suspicious_spawns(&mut process_monitor, &config, &mut findings);
+        print_masquerades(&mut process_monitor, &mut findings);
+        assert!(references_self(&findings));
+
+        process_monitor.set_include_self(false);
+        let mut findings = SystemFindings::new(None);
+        print_suspicious_spawns(&mut process_monitor, &config, &mut findings);
+        print_masquerades(&mut process_monitor, &mut findings);
+        print_correlations(&mut process_monitor, true, &mut findings);
+        assert!(!references_self(&findings));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn without_a_schedule_every_new_finding_is_shown() {
+        let mut findings = SystemFindings::new(None);
+        let night = NaiveTime::from_hms_opt(23, 30, 0).unwrap();
+        assert!(findings.admit("destino compartido", SeverityLevel::Info, night));
+        assert!(!findings.admit("destino compartido", SeverityLevel::Info, night));
+        assert_eq!(findings.silenced_count(), 0);
+    }
+
+    #[test]
+    fn sustained_high_cpu_escalates_with_duration() {
+        let findings = feed(&[95.0; 31], 3);
+        let severities: Vec<SeverityLevel> = findings.iter().map(|finding| finding.severity).collect();
+        assert_eq!(severities, vec![SeverityLevel::Warning, SeverityLevel::Error, SeverityLevel::Critical]);
+        assert_eq!(findings[0].description, "CPU sostenido > 80% durante 3 s (media 95.0%)");
+        assert!(findings[2].description.contains("durante 30 s"));
+    }
+
+    #[test]
+    fn isolated_spikes_are_not_sustained() {
+        let spikes: Vec<f32> = (0..60).map(|second| if second % 3 == 0 { 99.0 } else { 10.0 }).collect();
+        assert!(feed(&spikes, 3).is_empty());
+    }
+
+    #[test]
+    fn a_broken_run_is_reported_again() {
+        let mut series = vec![95.0; 4];
+        series.push(20.0);
+        series.extend([95.0; 4]);
+        let findings = feed(&series, 3);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|finding| finding.severity == SeverityLevel::Warning));
+    }
+}
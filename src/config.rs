@@ -1,24 +1,544 @@
 use anyhow::Result;
-use crate::llm::{LlmClient, LlmConfig, LlmProvider};
+use crate::llm::{build_http_client, AnalysisDepth, ApiKeyPlacement, LlmClient, LlmConfig, LlmProvider};
+use crate::error::AppError;
+use crate::capture::CaptureBackend;
+use crate::redaction::Redactor;
+use crate::profile::{MonitorProfile, MonitorSettings};
+use crate::prompt_template::PromptTemplate;
+use crate::network::{ExpectedPorts, SensitiveEndpoint, SensitivePort, DEFAULT_CLOSED_CONNECTIONS_HISTORY};
+use crate::process::SpawnRule;
+use crate::schedule::Schedule;
+use crate::encoding::ReportEncoding;
+use crate::integrations::live::parse_serve_addr;
+use crate::integrations::syslog::SyslogTarget;
+use crate::process::{DEFAULT_MAX_CMD_ARGS, DEFAULT_REFRESH_THROTTLE_MS};
+use crate::reports::{ExportFormat, SeverityLevel};
+use crate::system_logs::DEFAULT_LOG_LINES;
+use crate::ui::app::{DEFAULT_TUI_MONITOR_DURATION, DEFAULT_WARMUP_SAMPLES};
+use crate::ui::widgets::{ChartStyle, ColorThresholds};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tracing::info;
 
-/// Configuración de la aplicación
-pub struct AppConfig {
+/// Temperatura por defecto del modelo
+pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// Longitud máxima de salida por defecto en modo CLI
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// Modelo por defecto
+pub const DEFAULT_MODEL: &str = "llama2";
+
+/// URL de la API por defecto (Ollama local)
+pub const DEFAULT_API_URL: &str = "http://localhost:11434/api";
+
+/// Timeout por defecto de las peticiones al LLM en segundos
+pub const DEFAULT_LLM_TIMEOUT: u64 = 120;
+
+/// Conexiones inactivas por host que se conservan para reutilizarlas en las peticiones al LLM
+pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 4;
+
+/// Segundos que se conserva una conexión inactiva con el LLM antes de cerrarla
+pub const DEFAULT_POOL_IDLE_TIMEOUT: u64 = 90;
+
+/// Opciones configurables desde archivo (`config.toml`) o desde la CLI.
+/// Cada campo ausente se resuelve con la siguiente fuente en precedencia.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
     /// Modelo LLM a utilizar
-    pub model: String,
+    pub model: Option<String>,
     /// URL de la API de LLM
-    pub api_url: String,
+    pub api_url: Option<String>,
+    /// Proveedor de LLM (ollama, openai o gemini)
+    pub provider: Option<LlmProvider>,
+    /// Host del servidor LLM, con el puerto y la ruta por defecto del proveedor
+    pub host: Option<String>,
+    /// Desactivar integración LLM
+    pub no_llm: Option<bool>,
+    /// Analizar sin LLM, con reglas heurísticas sobre los datos recopilados
+    pub offline: Option<bool>,
+    /// Temperatura del modelo
+    pub temperature: Option<f32>,
+    /// Longitud máxima de salida del LLM
+    pub max_tokens: Option<u32>,
+    /// Ventana de contexto del modelo en tokens
+    pub num_ctx: Option<u32>,
+    /// Conexiones inactivas por host que se conservan para el LLM (0 = no reutilizar)
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Segundos que se conserva una conexión inactiva con el LLM
+    pub pool_idle_timeout: Option<u64>,
+    /// Profundidad del análisis LLM (shallow, normal, deep)
+    pub depth: Option<AnalysisDepth>,
+    /// No repetir con solo estadísticas el análisis que excede la ventana de contexto del modelo
+    pub no_context_retry: Option<bool>,
+    /// Registrar las peticiones y respuestas del LLM (redactadas)
+    pub debug_llm: Option<bool>,
+    /// Modelos de respaldo si el principal no está disponible
+    pub fallback_models: Option<Vec<String>>,
+    /// Plantilla del prompt de análisis (ruta de un archivo o nombre incorporado)
+    pub template: Option<String>,
+    /// Clave de API del proveedor (Gemini). Sin ella se usa GEMINI_API_KEY o GOOGLE_API_KEY;
+    /// no hay opción de línea de comandos para que no quede en la lista de procesos
+    pub api_key: Option<String>,
+    /// Dónde se envía la clave de API de Gemini (header o query)
+    pub api_key_placement: Option<ApiKeyPlacement>,
+    /// Notas del analista que acompañan al análisis del LLM y se guardan en el reporte
+    pub notes: Option<Vec<String>>,
+    /// Desactivar la redacción de secretos
+    pub no_redact: Option<bool>,
+    /// Patrones adicionales a redactar
+    pub redact_patterns: Option<Vec<String>>,
+    /// Claves sensibles adicionales (pares clave=valor, flags y variables de entorno)
+    pub redact_keys: Option<Vec<String>>,
+    /// Seudonimizar las direcciones IP en reportes y prompts
+    pub redact_network: Option<bool>,
+    /// Máximo de eventos por tipo en un reporte
+    pub max_report_entries: Option<usize>,
+    /// Argumentos de la línea de comandos que se conservan por proceso (0 = todos)
+    pub max_cmd_args: Option<usize>,
+    /// Milisegundos durante los que las búsquedas por nombre reutilizan el último escaneo (0 = siempre escanear)
+    pub refresh_throttle_ms: Option<u64>,
+    /// Conexiones cerradas que se conservan para los reportes y la pantalla de red
+    pub closed_connections_history: Option<usize>,
+    /// Muestras que se recogen en la TUI antes de analizar un proceso sin historial
+    pub warmup_samples: Option<usize>,
+    /// Duración en segundos del monitoreo que inicia la tecla `m` de la TUI (0 = indefinido)
+    pub tui_monitor_duration: Option<u64>,
+    /// Tiempo máximo en segundos de cualquier sesión (CLI o TUI) antes de cerrarla
+    pub max_runtime: Option<u64>,
+    /// Incluir el propio proceso de ShadowTrace
+    pub include_self: Option<bool>,
+    /// Desactivar la persistencia de la sesión
+    pub no_session: Option<bool>,
+    /// Ocultar el consumo propio de ShadowTrace en la TUI
+    pub no_watchdog: Option<bool>,
+    /// Webhook para hallazgos críticos
+    pub alert_webhook: Option<String>,
+    /// Registrar los hallazgos en syslog o en el journal de systemd
+    pub syslog: Option<SyslogTarget>,
+    /// Exponer el estado en vivo en JSON por HTTP (`:PUERTO` o `HOST:PUERTO`)
+    pub serve: Option<String>,
+    /// Ubicaciones de persistencia adicionales a vigilar
+    pub persistence_paths: Option<Vec<String>>,
+    /// Puertos sensibles adicionales (`[[sensitive_ports]]`)
+    pub sensitive_ports: Option<Vec<SensitivePort>>,
+    /// Puertos habituales por familia de procesos (`[[expected_ports]]`)
+    pub expected_ports: Option<Vec<ExpectedPorts>>,
+    /// Destinos sensibles adicionales (`[[sensitive_endpoints]]`)
+    pub sensitive_endpoints: Option<Vec<SensitiveEndpoint>>,
+    /// Relaciones padre → hijo sospechosas adicionales (`[[spawn_rules]]`)
+    pub spawn_rules: Option<Vec<SpawnRule>>,
+    /// Horario de `system --watch`: franjas activas y horas de silencio (`[schedule]`)
+    pub schedule: Option<Schedule>,
+    /// Exportar reportes solo en ASCII
+    pub ascii: Option<bool>,
+    /// Escribir la marca BOM de UTF-8 en los reportes
+    pub utf8_bom: Option<bool>,
+    /// Formatos de exportación adicionales
+    pub export: Option<Vec<ExportFormat>>,
+    /// Archivo de métricas para el textfile collector de node_exporter
+    pub prometheus_textfile: Option<String>,
+    /// Registro de auditoría de acciones (JSONL)
+    pub audit_log: Option<String>,
+    /// Paquete zip con todos los artefactos del reporte
+    pub export_bundle: Option<String>,
+    /// Base de datos IP → ASN (TSV de iptoasn.com) para agrupar la salida de red por organización
+    pub asn_db: Option<String>,
+    /// Registro de resoluciones DNS (dnsmasq con log-queries o formato hosts) para detectar
+    /// conexiones directas a IP
+    pub dns_log: Option<String>,
+    /// Severidad de las conexiones directas a IP sin resolución DNS (por defecto, informativa)
+    pub raw_ip_severity: Option<SeverityLevel>,
+    /// Incluir las líneas recientes de los registros del sistema que mencionan el proceso
+    pub with_logs: Option<bool>,
+    /// Líneas de registro del sistema a incluir con `with_logs`
+    pub log_lines: Option<usize>,
+    /// Estilo de los gráficos de la TUI (braille, ascii)
+    pub charts: Option<ChartStyle>,
+    /// Umbrales de color de CPU y memoria de la TUI (`[colors]`)
+    pub colors: Option<ColorThresholds>,
+    /// Acompañar los colores de nivel de la TUI con símbolos y texto
+    pub accessible: Option<bool>,
+    /// Usar siempre el diseño compacto del monitor de procesos
+    pub compact: Option<bool>,
+    /// Método de captura de archivos y conexiones (auto, procfs, fanotify, ebpf)
+    pub capture_backend: Option<CaptureBackend>,
+    /// Perfil de monitoreo a aplicar
+    pub profile: Option<String>,
+    /// Perfiles de monitoreo personalizados (`[profiles.<nombre>]`)
+    pub profiles: Option<HashMap<String, MonitorProfile>>,
+}
+
+impl ConfigFile {
+    /// Ruta del archivo de configuración del usuario (~/.shadowtrace/config.toml)
+    pub fn default_path() -> Option<PathBuf> {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("config.toml"))
+    }
+
+    /// Cargar y validar un archivo de configuración
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| AppError::ConfigurationError(
+            format!("No se pudo leer {}: {}", path.display(), e)
+        ))?;
+        let file: Self = toml::from_str(&content).map_err(|e| AppError::ConfigurationError(
+            format!("Archivo de configuración inválido {}: {}", path.display(), e)
+        ))?;
+        Ok(file)
+    }
+
+    /// Valores por defecto incorporados
+    pub fn defaults() -> Self {
+        Self {
+            model: Some(DEFAULT_MODEL.to_string()),
+            // Sin URL explícita se construye a partir del proveedor y del host
+            api_url: None,
+            provider: None,
+            host: None,
+            no_llm: Some(false),
+            offline: Some(false),
+            temperature: Some(DEFAULT_TEMPERATURE),
+            max_tokens: None,
+            num_ctx: None,
+            pool_max_idle_per_host: Some(DEFAULT_POOL_MAX_IDLE_PER_HOST),
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            depth: Some(AnalysisDepth::default()),
+            no_context_retry: Some(false),
+            debug_llm: Some(false),
+            fallback_models: Some(Vec::new()),
+            template: Some("default".to_string()),
+            api_key: None,
+            api_key_placement: Some(ApiKeyPlacement::default()),
+            notes: Some(Vec::new()),
+            no_redact: Some(false),
+            redact_patterns: Some(Vec::new()),
+            redact_keys: Some(Vec::new()),
+            redact_network: Some(false),
+            max_report_entries: None,
+            max_cmd_args: Some(DEFAULT_MAX_CMD_ARGS),
+            refresh_throttle_ms: Some(DEFAULT_REFRESH_THROTTLE_MS),
+            closed_connections_history: Some(DEFAULT_CLOSED_CONNECTIONS_HISTORY),
+            warmup_samples: Some(DEFAULT_WARMUP_SAMPLES),
+            tui_monitor_duration: Some(DEFAULT_TUI_MONITOR_DURATION),
+            max_runtime: None,
+            include_self: Some(false),
+            no_session: Some(false),
+            no_watchdog: Some(false),
+            alert_webhook: None,
+            syslog: None,
+            serve: None,
+            persistence_paths: Some(Vec::new()),
+            sensitive_ports: Some(Vec::new()),
+            expected_ports: Some(Vec::new()),
+            sensitive_endpoints: Some(Vec::new()),
+            spawn_rules: Some(Vec::new()),
+            schedule: Some(Schedule::default()),
+            ascii: Some(false),
+            utf8_bom: Some(false),
+            export: Some(Vec::new()),
+            prometheus_textfile: None,
+            audit_log: None,
+            export_bundle: None,
+            asn_db: None,
+            dns_log: None,
+            raw_ip_severity: Some(SeverityLevel::Info),
+            with_logs: Some(false),
+            log_lines: Some(DEFAULT_LOG_LINES),
+            charts: None,
+            colors: Some(ColorThresholds::default()),
+            accessible: Some(false),
+            compact: Some(false),
+            capture_backend: Some(CaptureBackend::Auto),
+            profile: None,
+            profiles: None,
+        }
+    }
+}
+
+/// Origen de un valor de configuración
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Valor incorporado
+    Default,
+    /// ~/.shadowtrace/config.toml
+    UserFile(PathBuf),
+    /// Archivo indicado con --config
+    ExplicitFile(PathBuf),
+    /// Flag explícito de la CLI
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "por defecto"),
+            ConfigSource::UserFile(path) => write!(f, "{}", path.display()),
+            ConfigSource::ExplicitFile(path) => write!(f, "--config {}", path.display()),
+            ConfigSource::Cli => write!(f, "CLI"),
+        }
+    }
+}
+
+/// Configuración efectiva tras aplicar la precedencia, con el origen de cada valor
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    /// Valores resueltos
+    pub values: ConfigFile,
+    /// Origen de cada opción, en el orden de `ConfigFile`
+    pub sources: Vec<(&'static str, ConfigSource)>,
+}
+
+impl EffectiveConfig {
+    /// Resolver la configuración: CLI > --config > ~/.shadowtrace/config.toml > por defecto
+    pub fn resolve(cli: ConfigFile, explicit_path: Option<&Path>) -> Result<Self> {
+        let mut layers = vec![(ConfigSource::Cli, cli)];
+        
+        if let Some(path) = explicit_path {
+            layers.push((ConfigSource::ExplicitFile(path.to_path_buf()), ConfigFile::load(path)?));
+        }
+        if let Some(path) = ConfigFile::default_path() {
+            if path.exists() {
+                layers.push((ConfigSource::UserFile(path.clone()), ConfigFile::load(&path)?));
+            }
+        }
+        layers.push((ConfigSource::Default, ConfigFile::defaults()));
+        
+        let mut sources = Vec::new();
+        let values = ConfigFile {
+            model: pick(&layers, "model", |c| c.model.clone(), &mut sources),
+            api_url: pick(&layers, "api_url", |c| c.api_url.clone(), &mut sources),
+            provider: pick(&layers, "provider", |c| c.provider, &mut sources),
+            host: pick(&layers, "host", |c| c.host.clone(), &mut sources),
+            no_llm: pick(&layers, "no_llm", |c| c.no_llm, &mut sources),
+            offline: pick(&layers, "offline", |c| c.offline, &mut sources),
+            temperature: pick(&layers, "temperature", |c| c.temperature, &mut sources),
+            max_tokens: pick(&layers, "max_tokens", |c| c.max_tokens, &mut sources),
+            num_ctx: pick(&layers, "num_ctx", |c| c.num_ctx, &mut sources),
+            pool_max_idle_per_host: pick(&layers, "pool_max_idle_per_host", |c| c.pool_max_idle_per_host, &mut sources),
+            pool_idle_timeout: pick(&layers, "pool_idle_timeout", |c| c.pool_idle_timeout, &mut sources),
+            depth: pick(&layers, "depth", |c| c.depth, &mut sources),
+            no_context_retry: pick(&layers, "no_context_retry", |c| c.no_context_retry, &mut sources),
+            debug_llm: pick(&layers, "debug_llm", |c| c.debug_llm, &mut sources),
+            fallback_models: pick(&layers, "fallback_models", |c| c.fallback_models.clone(), &mut sources),
+            template: pick(&layers, "template", |c| c.template.clone(), &mut sources),
+            api_key: pick(&layers, "api_key", |c| c.api_key.clone(), &mut sources),
+            api_key_placement: pick(&layers, "api_key_placement", |c| c.api_key_placement, &mut sources),
+            notes: pick(&layers, "notes", |c| c.notes.clone(), &mut sources),
+            no_redact: pick(&layers, "no_redact", |c| c.no_redact, &mut sources),
+            redact_patterns: pick(&layers, "redact_patterns", |c| c.redact_patterns.clone(), &mut sources),
+            redact_keys: pick(&layers, "redact_keys", |c| c.redact_keys.clone(), &mut sources),
+            redact_network: pick(&layers, "redact_network", |c| c.redact_network, &mut sources),
+            max_report_entries: pick(&layers, "max_report_entries", |c| c.max_report_entries, &mut sources),
+            max_cmd_args: pick(&layers, "max_cmd_args", |c| c.max_cmd_args, &mut sources),
+            refresh_throttle_ms: pick(&layers, "refresh_throttle_ms", |c| c.refresh_throttle_ms, &mut sources),
+            closed_connections_history: pick(&layers, "closed_connections_history", |c| c.closed_connections_history, &mut sources),
+            warmup_samples: pick(&layers, "warmup_samples", |c| c.warmup_samples, &mut sources),
+            tui_monitor_duration: pick(&layers, "tui_monitor_duration", |c| c.tui_monitor_duration, &mut sources),
+            max_runtime: pick(&layers, "max_runtime", |c| c.max_runtime, &mut sources),
+            include_self: pick(&layers, "include_self", |c| c.include_self, &mut sources),
+            no_session: pick(&layers, "no_session", |c| c.no_session, &mut sources),
+            no_watchdog: pick(&layers, "no_watchdog", |c| c.no_watchdog, &mut sources),
+            alert_webhook: pick(&layers, "alert_webhook", |c| c.alert_webhook.clone(), &mut sources),
+            syslog: pick(&layers, "syslog", |c| c.syslog, &mut sources),
+            serve: pick(&layers, "serve", |c| c.serve.clone(), &mut sources),
+            persistence_paths: pick(&layers, "persistence_paths", |c| c.persistence_paths.clone(), &mut sources),
+            sensitive_ports: pick(&layers, "sensitive_ports", |c| c.sensitive_ports.clone(), &mut sources),
+            expected_ports: pick(&layers, "expected_ports", |c| c.expected_ports.clone(), &mut sources),
+            sensitive_endpoints: pick(&layers, "sensitive_endpoints", |c| c.sensitive_endpoints.clone(), &mut sources),
+            spawn_rules: pick(&layers, "spawn_rules", |c| c.spawn_rules.clone(), &mut sources),
+            schedule: pick(&layers, "schedule", |c| c.schedule.clone(), &mut sources),
+            ascii: pick(&layers, "ascii", |c| c.ascii, &mut sources),
+            utf8_bom: pick(&layers, "utf8_bom", |c| c.utf8_bom, &mut sources),
+            export: pick(&layers, "export", |c| c.export.clone(), &mut sources),
+            prometheus_textfile: pick(&layers, "prometheus_textfile", |c| c.prometheus_textfile.clone(), &mut sources),
+            audit_log: pick(&layers, "audit_log", |c| c.audit_log.clone(), &mut sources),
+            export_bundle: pick(&layers, "export_bundle", |c| c.export_bundle.clone(), &mut sources),
+            asn_db: pick(&layers, "asn_db", |c| c.asn_db.clone(), &mut sources),
+            dns_log: pick(&layers, "dns_log", |c| c.dns_log.clone(), &mut sources),
+            raw_ip_severity: pick(&layers, "raw_ip_severity", |c| c.raw_ip_severity, &mut sources),
+            with_logs: pick(&layers, "with_logs", |c| c.with_logs, &mut sources),
+            log_lines: pick(&layers, "log_lines", |c| c.log_lines, &mut sources),
+            charts: pick(&layers, "charts", |c| c.charts, &mut sources),
+            colors: pick(&layers, "colors", |c| c.colors, &mut sources),
+            accessible: pick(&layers, "accessible", |c| c.accessible, &mut sources),
+            compact: pick(&layers, "compact", |c| c.compact, &mut sources),
+            capture_backend: pick(&layers, "capture_backend", |c| c.capture_backend, &mut sources),
+            profile: pick(&layers, "profile", |c| c.profile.clone(), &mut sources),
+            profiles: pick(&layers, "profiles", |c| c.profiles.clone(), &mut sources),
+        };
+        
+        Ok(Self { values, sources })
+    }
+
+    /// Configuración del LLM resuelta, compartida por la CLI y la TUI
+    pub fn llm_config(&self) -> Result<LlmConfig> {
+        let values = &self.values;
+        // Una URL explícita manda; si no, el proveedor y el host dan la URL de costumbre
+        let provider = values.provider
+            .or_else(|| values.api_url.as_deref().map(LlmProvider::for_api_url))
+            .unwrap_or_default();
+        let api_url = match &values.api_url {
+            Some(api_url) => {
+                if values.host.is_some() {
+                    tracing::warn!("Se indicó api_url ({}); se ignora host", api_url);
+                }
+                api_url.clone()
+            }
+            None => provider.endpoint(values.host.as_deref()),
+        };
+        // La clave del archivo manda; para Gemini también valen sus variables de entorno
+        let api_key = values.api_key.clone().or_else(|| match provider {
+            LlmProvider::Gemini => ["GEMINI_API_KEY", "GOOGLE_API_KEY"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|key| !key.trim().is_empty()),
+            _ => None,
+        });
+        Ok(LlmConfig {
+            provider,
+            api_url,
+            model: values.model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            temperature: values.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            timeout_seconds: DEFAULT_LLM_TIMEOUT,
+            max_tokens: values.max_tokens,
+            num_ctx: values.num_ctx,
+            pool_max_idle_per_host: values.pool_max_idle_per_host.unwrap_or(DEFAULT_POOL_MAX_IDLE_PER_HOST),
+            pool_idle_timeout_seconds: values.pool_idle_timeout.unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT),
+            debug_requests: values.debug_llm.unwrap_or(false),
+            retry_reduced_context: !values.no_context_retry.unwrap_or(false),
+            fallback_models: values.fallback_models.clone().unwrap_or_default(),
+            prompt_template: match &values.template {
+                Some(template) => PromptTemplate::find(template)?,
+                None => PromptTemplate::default(),
+            },
+            api_key,
+            api_key_placement: values.api_key_placement.unwrap_or_default(),
+            analyst_notes: values.notes
+                .iter()
+                .flatten()
+                .map(|note| note.trim().to_string())
+                .filter(|note| !note.is_empty())
+                .collect(),
+        })
+    }
+}
+
+/// Tomar el primer valor definido según la precedencia de las capas
+fn pick<T>(
+    layers: &[(ConfigSource, ConfigFile)],
+    key: &'static str,
+    get: impl Fn(&ConfigFile) -> Option<T>,
+    sources: &mut Vec<(&'static str, ConfigSource)>,
+) -> Option<T> {
+    for (source, layer) in layers {
+        if let Some(value) = get(layer) {
+            sources.push((key, source.clone()));
+            return Some(value);
+        }
+    }
+    sources.push((key, ConfigSource::Default));
+    None
+}
+
+/// Configuración de la aplicación
+pub struct AppConfig {
+    /// Configuración del LLM, la misma para la CLI y la TUI
+    pub llm: LlmConfig,
     /// Nivel de verbosidad
     pub verbose: u8,
     /// Cliente LLM configurado
     pub llm_client: Option<LlmClient>,
+    /// Cliente HTTP del LLM: se crea una vez y lo comparten la CLI y todos los análisis
+    /// de la TUI, que así reutilizan las conexiones (y sesiones TLS) abiertas
+    pub http_client: Option<reqwest::Client>,
     /// Indica si la integración con LLM está desactivada
     pub no_llm: bool,
+    /// Generar el análisis heurístico sin IA en lugar del análisis con LLM (`--offline`)
+    pub offline: bool,
+    /// Redactor de secretos (None si la redacción está desactivada)
+    pub redactor: Option<Redactor>,
+    /// Seudonimizar las direcciones IP en reportes y prompts (`--redact-network`)
+    pub redact_network: bool,
+    /// Máximo de eventos por tipo que conserva un reporte (None = sin límite)
+    pub max_report_entries: Option<usize>,
+    /// Argumentos de la línea de comandos que se conservan por proceso (0 = todos)
+    pub max_cmd_args: usize,
+    /// Milisegundos durante los que las búsquedas por nombre reutilizan el último escaneo
+    pub refresh_throttle_ms: u64,
+    /// Conexiones cerradas que se conservan para los reportes y la pantalla de red
+    pub closed_connections_history: usize,
+    /// Muestras que se recogen en la TUI antes de analizar un proceso sin historial (0 = ninguna)
+    pub warmup_samples: usize,
+    /// Duración en segundos del monitoreo que inicia la tecla `m` de la TUI (0 = indefinido)
+    pub tui_monitor_duration: u64,
+    /// Momento en que vence `--max-runtime` (None = sin límite)
+    pub runtime_deadline: Option<Instant>,
+    /// Cuánto de los eventos en bruto se envía al LLM
+    pub analysis_depth: AnalysisDepth,
+    /// Incluir el propio proceso de ShadowTrace en listados y detectores
+    pub include_self: bool,
+    /// Desactivar la persistencia de la sesión de la TUI
+    pub no_session: bool,
+    /// Ocultar el consumo propio de ShadowTrace en la TUI
+    pub no_watchdog: bool,
+    /// Webhook a notificar cuando aparece un hallazgo crítico
+    pub alert_webhook: Option<String>,
+    /// Destino de los hallazgos en el registro del sistema (`--syslog`)
+    pub syslog: Option<SyslogTarget>,
+    /// Dirección del endpoint JSON en vivo (`--serve`)
+    pub serve: Option<SocketAddr>,
+    /// Ubicaciones de persistencia adicionales a las del sistema operativo
+    pub persistence_paths: Vec<String>,
+    /// Puertos sensibles adicionales a los incorporados
+    pub sensitive_ports: Vec<SensitivePort>,
+    /// Familias de procesos con puertos habituales adicionales a las incorporadas
+    pub expected_ports: Vec<ExpectedPorts>,
+    /// Destinos sensibles adicionales a los incorporados
+    pub sensitive_endpoints: Vec<SensitiveEndpoint>,
+    /// Relaciones padre → hijo sospechosas adicionales a las incorporadas
+    pub spawn_rules: Vec<SpawnRule>,
+    /// Horario de la observación continua del sistema (por defecto, siempre y sin silencio)
+    pub schedule: Schedule,
+    /// Codificación de los reportes exportados
+    pub report_encoding: ReportEncoding,
+    /// Formatos de exportación adicionales a JSON y Markdown
+    pub exports: Vec<ExportFormat>,
+    /// Archivo de métricas de Prometheus (None = ~/.shadowtrace/reports/shadowtrace.prom)
+    pub prometheus_textfile: Option<PathBuf>,
+    /// Paquete zip con todos los artefactos del reporte (`--export-bundle`)
+    pub export_bundle: Option<PathBuf>,
+    /// Base de datos IP → ASN para agrupar la salida de red por organización
+    pub asn_db: Option<PathBuf>,
+    /// Registro de resoluciones DNS para detectar conexiones directas a IP
+    pub dns_log: Option<PathBuf>,
+    /// Severidad de las conexiones directas a IP sin resolución DNS
+    pub raw_ip_severity: SeverityLevel,
+    /// Líneas de los registros del sistema a incluir por proceso (None = sin `--with-logs`)
+    pub with_logs: Option<usize>,
+    /// Estilo de los gráficos de la TUI
+    pub chart_style: ChartStyle,
+    /// Umbrales de color de CPU y memoria de la TUI
+    pub color_thresholds: ColorThresholds,
+    /// Mostrar el nivel de CPU, memoria y diferencias también con símbolos y texto (`--accessible`)
+    pub accessible: bool,
+    /// Diseño compacto del monitor de procesos aunque la terminal sea alta (`--compact`)
+    pub compact: bool,
+    /// Método de captura ya resuelto (`auto` se convierte en el primero disponible)
+    pub capture_backend: CaptureBackend,
+    /// Perfil de monitoreo seleccionado con --profile
+    pub profile: Option<MonitorProfile>,
 }
 
 impl AppConfig {
-    /// Crear una nueva configuración desde los parámetros de la CLI
-    pub fn new(model: String, api_url: String, verbose: u8, no_llm: bool) -> Result<Self> {
+    /// Crear una nueva configuración desde los parámetros de la CLI, con la
+    /// configuración del LLM por defecto (ver `with_llm_config`)
+    pub fn new(verbose: u8, no_llm: bool) -> Self {
         // Configurar nivel de verbosidad
         match verbose {
             0 => println!("Modo normal"),
@@ -26,18 +546,47 @@ impl AppConfig {
             _ => println!("Modo debug"),
         }
         
+        Self { verbose, no_llm, ..Self::default() }
+    }
+
+    /// Usar la configuración del LLM indicada (ya resuelta a partir de la CLI y del
+    /// archivo de configuración) y crear con ella el cliente, salvo con `--no-llm`.
+    /// La TUI la clona en lugar de construir la suya, y comparte el cliente HTTP.
+    pub fn with_llm_config(mut self, llm: LlmConfig) -> Result<Self> {
+        // Validar parámetros del modelo
+        if !(0.0..=2.0).contains(&llm.temperature) {
+            return Err(AppError::ConfigurationError(format!(
+                "La temperatura debe estar entre 0.0 y 2.0 (recibido: {})", llm.temperature
+            )).into());
+        }
+        if llm.max_tokens == Some(0) {
+            return Err(AppError::ConfigurationError(
+                "El número máximo de tokens debe ser mayor que 0".to_string()
+            ).into());
+        }
+        if llm.num_ctx == Some(0) {
+            return Err(AppError::ConfigurationError(
+                "La ventana de contexto (num_ctx) debe ser mayor que 0".to_string()
+            ).into());
+        }
+        if llm.pool_idle_timeout_seconds == 0 {
+            return Err(AppError::ConfigurationError(
+                "El tiempo de conservación de las conexiones (pool_idle_timeout) debe ser mayor que 0".to_string()
+            ).into());
+        }
+        
         // Configurar cliente LLM si no está desactivado
-        let llm_client = if !no_llm {
-            match LlmClient::new(LlmConfig {
-                provider: LlmProvider::Ollama,
-                api_url: api_url.clone(),
-                model: model.clone(),
-                temperature: 0.7,
-                timeout_seconds: 30,
-                max_tokens: Some(1024),
-            }) {
+        self.llm_client = if !self.no_llm {
+            let client = build_http_client(&llm).and_then(|http_client| {
+                self.http_client = Some(http_client.clone());
+                LlmClient::with_http_client(LlmConfig {
+                    max_tokens: Some(llm.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+                    ..llm.clone()
+                }, http_client)
+            });
+            match client {
                 Ok(client) => {
-                    info!("Cliente LLM inicializado con modelo {}", model);
+                    info!("Cliente LLM inicializado con modelo {}", llm.model);
                     Some(client)
                 },
                 Err(e) => {
@@ -49,25 +598,91 @@ impl AppConfig {
             info!("Integración con LLM desactivada");
             None
         };
+        self.llm = llm;
         
-        Ok(Self {
-            model,
-            api_url,
-            verbose,
-            llm_client,
-            no_llm,
-        })
+        Ok(self)
     }
 
-    /// Crear una configuración con valores por defecto
-    pub fn default() -> Self {
-        Self {
-            model: "llama2".to_string(),
-            api_url: "http://localhost:11434/api".to_string(),
-            verbose: 0,
-            no_llm: false,
-            llm_client: None,
-        }
+    /// Crear la configuración a partir de la configuración efectiva resuelta
+    pub fn from_effective(effective: &EffectiveConfig, verbose: u8) -> Result<Self> {
+        let values = &effective.values;
+        // --offline no contacta con el LLM
+        let offline = values.offline.unwrap_or(false);
+        let mut config = Self::new(verbose, values.no_llm.unwrap_or(false) || offline)
+            .with_llm_config(effective.llm_config()?)?;
+        config.offline = offline;
+        
+        // Configurar redacción de secretos
+        config.redactor = if values.no_redact.unwrap_or(false) {
+            None
+        } else {
+            Some(Redactor::new(
+                values.redact_keys.as_deref().unwrap_or(&[]),
+                values.redact_patterns.as_deref().unwrap_or(&[]),
+            )?)
+        };
+        config.redact_network = values.redact_network.unwrap_or(false);
+        config.max_report_entries = values.max_report_entries;
+        config.max_cmd_args = values.max_cmd_args.unwrap_or(DEFAULT_MAX_CMD_ARGS);
+        config.refresh_throttle_ms = values.refresh_throttle_ms.unwrap_or(DEFAULT_REFRESH_THROTTLE_MS);
+        config.closed_connections_history = values.closed_connections_history.unwrap_or(DEFAULT_CLOSED_CONNECTIONS_HISTORY);
+        config.warmup_samples = values.warmup_samples.unwrap_or(DEFAULT_WARMUP_SAMPLES);
+        config.tui_monitor_duration = values.tui_monitor_duration.unwrap_or(DEFAULT_TUI_MONITOR_DURATION);
+        // Se cuenta desde el arranque: la configuración se resuelve antes de cualquier sesión
+        config.runtime_deadline = values.max_runtime.map(|secs| Instant::now() + Duration::from_secs(secs));
+        config.analysis_depth = values.depth.unwrap_or_default();
+        config.include_self = values.include_self.unwrap_or(false);
+        config.no_session = values.no_session.unwrap_or(false);
+        config.no_watchdog = values.no_watchdog.unwrap_or(false);
+        config.alert_webhook = values.alert_webhook.clone();
+        config.syslog = values.syslog;
+        config.serve = values.serve.as_deref().map(parse_serve_addr).transpose()?;
+        config.persistence_paths = values.persistence_paths.clone().unwrap_or_default();
+        config.sensitive_ports = values.sensitive_ports.clone().unwrap_or_default();
+        config.expected_ports = values.expected_ports.clone().unwrap_or_default();
+        config.sensitive_endpoints = values.sensitive_endpoints.clone().unwrap_or_default();
+        config.spawn_rules = values.spawn_rules.clone().unwrap_or_default();
+        config.schedule = values.schedule.clone().unwrap_or_default();
+        config.report_encoding = ReportEncoding {
+            ascii: values.ascii.unwrap_or(false),
+            bom: values.utf8_bom.unwrap_or(false),
+        };
+        config.exports = values.export.clone().unwrap_or_default();
+        config.prometheus_textfile = values.prometheus_textfile.as_ref().map(PathBuf::from);
+        config.export_bundle = values.export_bundle.as_ref().map(PathBuf::from);
+        config.asn_db = values.asn_db.as_ref().map(PathBuf::from);
+        config.dns_log = values.dns_log.as_ref().map(PathBuf::from);
+        config.raw_ip_severity = values.raw_ip_severity.unwrap_or(SeverityLevel::Info);
+        config.with_logs = values.with_logs
+            .unwrap_or(false)
+            .then(|| values.log_lines.unwrap_or(DEFAULT_LOG_LINES));
+        // Sin valor explícito se elige según lo que soporte la terminal
+        config.chart_style = values.charts.unwrap_or_else(ChartStyle::detect);
+        config.color_thresholds = values.colors.unwrap_or_default();
+        config.accessible = values.accessible.unwrap_or(false);
+        config.compact = values.compact.unwrap_or(false);
+        config.capture_backend = values.capture_backend.unwrap_or_default().resolve()?;
+        config.profile = match &values.profile {
+            Some(name) => Some(MonitorProfile::find(name, values.profiles.as_ref())?),
+            None => None,
+        };
+        
+        Ok(config)
+    }
+
+    /// Tiempo que queda hasta `--max-runtime` (None = sin límite)
+    pub fn remaining_runtime(&self) -> Option<Duration> {
+        self.runtime_deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Indica si se superó `--max-runtime`
+    pub fn runtime_exceeded(&self) -> bool {
+        self.remaining_runtime().is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Parámetros de monitoreo: flags explícitos > perfil > valores por defecto
+    pub fn monitor_settings(&self, flags: &MonitorProfile) -> MonitorSettings {
+        MonitorSettings::resolve(self.profile.as_ref(), flags)
     }
 }
 
@@ -75,11 +690,48 @@ impl AppConfig {
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            model: "llama2".to_string(),
-            api_url: "http://localhost:11434/api".to_string(),
+            llm: LlmConfig::default(),
             verbose: 0,
             no_llm: false,
+            offline: false,
             llm_client: None,
+            http_client: None,
+            redactor: Some(Redactor::default()),
+            redact_network: false,
+            max_report_entries: None,
+            max_cmd_args: DEFAULT_MAX_CMD_ARGS,
+            refresh_throttle_ms: DEFAULT_REFRESH_THROTTLE_MS,
+            closed_connections_history: DEFAULT_CLOSED_CONNECTIONS_HISTORY,
+            warmup_samples: DEFAULT_WARMUP_SAMPLES,
+            tui_monitor_duration: DEFAULT_TUI_MONITOR_DURATION,
+            runtime_deadline: None,
+            analysis_depth: AnalysisDepth::default(),
+            include_self: false,
+            no_session: false,
+            no_watchdog: false,
+            alert_webhook: None,
+            syslog: None,
+            serve: None,
+            persistence_paths: Vec::new(),
+            sensitive_ports: Vec::new(),
+            expected_ports: Vec::new(),
+            sensitive_endpoints: Vec::new(),
+            spawn_rules: Vec::new(),
+            schedule: Schedule::default(),
+            report_encoding: ReportEncoding::default(),
+            exports: Vec::new(),
+            prometheus_textfile: None,
+            export_bundle: None,
+            asn_db: None,
+            dns_log: None,
+            raw_ip_severity: SeverityLevel::Info,
+            with_logs: None,
+            chart_style: ChartStyle::detect(),
+            color_thresholds: ColorThresholds::default(),
+            accessible: false,
+            compact: false,
+            capture_backend: CaptureBackend::Procfs,
+            profile: None,
         }
     }
 } 
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Comandos de portapapeles que se prueban en orden: programa, argumentos y variable
+/// de entorno que indica que hay una sesión gráfica donde usarlo
+#[cfg(target_os = "macos")]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str], Option<&str>)] = &[("pbcopy", &[], None)];
+
+#[cfg(windows)]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str], Option<&str>)] = &[("clip", &[], None)];
+
+#[cfg(not(any(target_os = "macos", windows)))]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str], Option<&str>)] = &[
+    ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+    ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+    ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+];
+
+/// Copiar un texto al portapapeles y devolver el método usado.
+/// Se usa la herramienta del sistema (pbcopy, clip, wl-copy, xclip o xsel); si no hay
+/// ninguna, se envía a la terminal la secuencia OSC 52, que también funciona por SSH
+/// en las terminales que la admiten.
+pub fn copy_to_clipboard(text: &str) -> Result<&'static str> {
+    for &(program, args, session_var) in CLIPBOARD_COMMANDS {
+        if session_var.is_some_and(|var| std::env::var_os(var).is_none()) {
+            continue;
+        }
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(program);
+        }
+    }
+
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+    stdout.flush()?;
+    Ok("OSC 52")
+}
+
+/// Mostrar un archivo en el explorador de archivos del sistema: Finder lo selecciona
+/// (`open -R`), el Explorador de Windows también (`explorer /select,`) y en el resto
+/// se abre la carpeta que lo contiene con `xdg-open`
+pub fn reveal_in_file_manager(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        let mut command = Command::new("explorer");
+        command.arg(select);
+        command
+    } else {
+        if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            anyhow::bail!("No hay una sesión gráfica en la que abrir la carpeta");
+        }
+        let folder = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(path);
+        let mut command = Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+
+    let opener = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("No se pudo ejecutar {}", opener))?;
+    // El explorador puede tardar en volver: se espera en segundo plano para no dejar un zombi
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Codificar en base64 estándar (con relleno) para OSC 52
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((triple >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
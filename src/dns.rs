@@ -0,0 +1,162 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use crate::asn::is_public;
+use crate::error::AppError;
+use crate::network::{Direction, NetworkEvent};
+use crate::reports::Detection;
+
+/// Resoluciones DNS observadas en el equipo (IP → nombres), leídas de un registro local:
+/// el de dnsmasq con `log-queries` (`reply ejemplo.com is 93.184.216.34`) o un archivo
+/// con el formato de /etc/hosts (`93.184.216.34 ejemplo.com`)
+#[derive(Debug, Default)]
+pub struct DnsLog {
+    /// Nombres resueltos a cada dirección
+    resolutions: HashMap<IpAddr, BTreeSet<String>>,
+    /// Archivo del registro
+    path: Option<PathBuf>,
+}
+
+impl DnsLog {
+    /// Cargar el registro de resoluciones
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| AppError::ConfigurationError(format!(
+            "No se pudo leer el registro DNS {}: {} (usa el registro de dnsmasq con log-queries o un archivo IP nombre)",
+            path.display(), e
+        )))?;
+        let mut log = Self::parse(&content);
+        log.path = Some(path.to_path_buf());
+        Ok(log)
+    }
+
+    /// Volver a leer el archivo para incluir las resoluciones hechas durante el monitoreo;
+    /// si ya no se puede leer se conservan las cargadas
+    pub fn reload(&mut self) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        match Self::load(&path) {
+            Ok(log) => *self = log,
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+
+    /// Interpretar el registro; las líneas que no son resoluciones se omiten
+    pub fn parse(content: &str) -> Self {
+        let mut log = Self::default();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // dnsmasq: "... reply <nombre> is <ip>" (también cached, config y /etc/hosts)
+            let resolution = match fields.iter().rposition(|field| *field == "is") {
+                Some(index) if index >= 2 && index + 1 < fields.len() => Some((fields[index - 1], fields[index + 1])),
+                // hosts: "<ip> <nombre> [alias...]"
+                _ => fields.first().zip(fields.get(1)).map(|(ip, name)| (*name, *ip)),
+            };
+            let Some((name, ip)) = resolution else {
+                continue;
+            };
+            if let Ok(ip) = ip.parse::<IpAddr>() {
+                log.resolutions.entry(ip.to_canonical()).or_default().insert(name.trim_end_matches('.').to_lowercase());
+            }
+        }
+        log
+    }
+
+    /// Nombres resueltos a una dirección
+    pub fn hostnames(&self, ip: IpAddr) -> Option<&BTreeSet<String>> {
+        self.resolutions.get(&ip.to_canonical())
+    }
+
+    /// Conexiones salientes a direcciones públicas que ningún nombre del registro resuelve
+    /// (posible C2 con la IP incrustada). Una detección por dirección, con la hora de la
+    /// primera conexión.
+    pub fn detect_raw_ip_connections<'a>(&self, events: impl IntoIterator<Item = &'a NetworkEvent>) -> Vec<Detection> {
+        let mut first_seen: BTreeMap<IpAddr, &NetworkEvent> = BTreeMap::new();
+        for event in events {
+            let Some(remote) = event.remote_addr.filter(|_| event.direction == Direction::Outbound) else {
+                continue;
+            };
+            let ip = remote.ip().to_canonical();
+            if !is_public(ip) || self.hostnames(ip).is_some() {
+                continue;
+            }
+            first_seen
+                .entry(ip)
+                .and_modify(|first| if event.timestamp < first.timestamp { *first = event })
+                .or_insert(event);
+        }
+        first_seen
+            .into_values()
+            .filter_map(|event| {
+                let remote = event.remote_addr?;
+                Some(Detection {
+                    message: format!("Conexión directa a IP sin resolución DNS: {}", remote),
+                    timestamp: event.timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{ConnectionState, Protocol};
+    use chrono::{DateTime, Duration, Utc};
+
+    fn outbound(remote: &str, timestamp: DateTime<Utc>) -> NetworkEvent {
+        NetworkEvent {
+            pid: 3,
+            local_addr: "10.0.0.2:40000".parse().unwrap(),
+            remote_addr: Some(remote.parse().unwrap()),
+            protocol: Protocol::TCP,
+            direction: Direction::Outbound,
+            state: ConnectionState::Established,
+            timestamp,
+            bytes_sent: None,
+            bytes_received: None,
+        }
+    }
+
+    #[test]
+    fn parses_dnsmasq_and_hosts_formats() {
+        let log = DnsLog::parse(
+            "Oct 16 10:00:01 dnsmasq[812]: query[A] Ejemplo.com from 10.0.0.2\n\
+             Oct 16 10:00:01 dnsmasq[812]: forwarded ejemplo.com to 1.1.1.1\n\
+             Oct 16 10:00:01 dnsmasq[812]: reply Ejemplo.com. is 93.184.216.34\n\
+             Oct 16 10:00:02 dnsmasq[812]: cached cdn.ejemplo.com is 2606:2800:220:1::1\n\
+             Oct 16 10:00:02 dnsmasq[812]: reply www.ejemplo.com is <CNAME>\n\
+             # comentario\n\
+             203.0.113.9 api.interna otro-nombre\n",
+        );
+        assert_eq!(log.hostnames("93.184.216.34".parse().unwrap()).unwrap().iter().collect::<Vec<_>>(), vec!["ejemplo.com"]);
+        assert!(log.hostnames("2606:2800:220:1::1".parse().unwrap()).is_some());
+        assert!(log.hostnames("::ffff:203.0.113.9".parse().unwrap()).unwrap().contains("api.interna"));
+        // El resolvedor al que se reenvía la consulta no es una resolución
+        assert!(log.hostnames("1.1.1.1".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn connections_to_unresolved_public_ips_are_flagged_once() {
+        let log = DnsLog::parse("Oct 16 10:00:01 dnsmasq[812]: reply ejemplo.com is 93.184.216.34\n");
+        let now = Utc::now();
+        let events = [
+            outbound("93.184.216.34:443", now),
+            outbound("45.33.32.156:8443", now + Duration::seconds(5)),
+            outbound("45.33.32.156:8443", now + Duration::seconds(1)),
+            // Privadas y loopback no salen a Internet
+            outbound("10.0.0.9:22", now),
+            outbound("127.0.0.1:5432", now),
+        ];
+
+        let detections = log.detect_raw_ip_connections(&events);
+        assert_eq!(detections, vec![Detection {
+            message: "Conexión directa a IP sin resolución DNS: 45.33.32.156:8443".to_string(),
+            timestamp: now + Duration::seconds(1),
+        }]);
+    }
+}
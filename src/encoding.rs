@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+/// Marca de orden de bytes UTF-8 (algunos editores de Windows la necesitan)
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Glifos decorativos de los reportes y su sustituto ASCII.
+/// Es la única tabla que consulta `--ascii`: cualquier glifo nuevo se añade aquí.
+pub const GLYPHS: &[(&str, &str)] = &[
+    ("🔴 CRÍTICO", "[CRITICAL]"),
+    ("🟠 ERROR", "[ERROR]"),
+    ("⚠️ WARN", "[WARN]"),
+    ("🔴", "[CRITICAL]"),
+    ("🟠", "[ERROR]"),
+    ("🟡", "[WARN]"),
+    ("🟢", "[OK]"),
+    ("⚠️", "[WARN]"),
+    ("⚠", "[WARN]"),
+    ("🚨", "[ALERT]"),
+    ("✅", "[OK]"),
+    ("❌", "[FAIL]"),
+    ("ℹ️", "[INFO]"),
+    ("⏳", "[...]"),
+    ("🔍", "[SCAN]"),
+    ("→", "->"),
+    ("←", "<-"),
+    ("↑", "^"),
+    ("↓", "v"),
+    ("•", "-"),
+    ("—", "-"),
+    ("–", "-"),
+    ("…", "..."),
+    ("“", "\""),
+    ("”", "\""),
+    ("‘", "'"),
+    ("’", "'"),
+];
+
+/// Codificación de los reportes exportados
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportEncoding {
+    /// Sustituir emojis y caracteres no ASCII (`--ascii`)
+    pub ascii: bool,
+    /// Escribir la marca BOM de UTF-8 al inicio del archivo (`--utf8-bom`)
+    pub bom: bool,
+}
+
+impl ReportEncoding {
+    /// Codificar un texto (Markdown) para escribirlo a disco
+    pub fn encode_text(&self, text: &str) -> Vec<u8> {
+        let body = if self.ascii { to_ascii(text) } else { text.to_string() };
+        self.with_bom(body)
+    }
+
+    /// Codificar JSON para escribirlo a disco; en modo ASCII los caracteres
+    /// se escapan (`\uXXXX`) para no perder información
+    pub fn encode_json(&self, json: &str) -> Vec<u8> {
+        let body = if self.ascii { escape_json_ascii(json) } else { json.to_string() };
+        self.with_bom(body)
+    }
+
+    fn with_bom(&self, body: String) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(body.len() + UTF8_BOM.len());
+        if self.bom {
+            bytes.extend_from_slice(UTF8_BOM);
+        }
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+}
+
+/// Convertir un texto a ASCII: primero los glifos de `GLYPHS`, después se quitan
+/// los acentos y cualquier otro carácter se sustituye por `?`
+pub fn to_ascii(text: &str) -> String {
+    let mut text = text.to_string();
+    for (glyph, replacement) in GLYPHS {
+        if text.contains(glyph) {
+            text = text.replace(glyph, replacement);
+        }
+    }
+
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+            continue;
+        }
+        match c {
+            'á' | 'à' | 'ä' | 'â' | 'ã' => ascii.push('a'),
+            'é' | 'è' | 'ë' | 'ê' => ascii.push('e'),
+            'í' | 'ì' | 'ï' | 'î' => ascii.push('i'),
+            'ó' | 'ò' | 'ö' | 'ô' | 'õ' => ascii.push('o'),
+            'ú' | 'ù' | 'ü' | 'û' => ascii.push('u'),
+            'Á' | 'À' | 'Ä' | 'Â' | 'Ã' => ascii.push('A'),
+            'É' | 'È' | 'Ë' | 'Ê' => ascii.push('E'),
+            'Í' | 'Ì' | 'Ï' | 'Î' => ascii.push('I'),
+            'Ó' | 'Ò' | 'Ö' | 'Ô' | 'Õ' => ascii.push('O'),
+            'Ú' | 'Ù' | 'Ü' | 'Û' => ascii.push('U'),
+            'ñ' => ascii.push('n'),
+            'Ñ' => ascii.push('N'),
+            'ç' => ascii.push('c'),
+            'Ç' => ascii.push('C'),
+            // Signos de apertura, selectores de variante y uniones de emoji
+            '¿' | '¡' | '\u{FE0F}' | '\u{200D}' => {}
+            _ => ascii.push('?'),
+        }
+    }
+    ascii
+}
+
+/// Escapar como `\uXXXX` los caracteres no ASCII de un JSON ya serializado
+/// (solo pueden aparecer dentro de cadenas, así que el resultado sigue siendo válido)
+fn escape_json_ascii(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                escaped.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    escaped
+}
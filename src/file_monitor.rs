@@ -1,8 +1,48 @@
+use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use directories::BaseDirs;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::{SystemTime, Duration};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::reports::Detection;
+use crate::static_analysis::shannon_entropy;
+
+/// Tamaño mínimo de escritura para calcular su entropía
+pub const ENTROPY_MIN_SIZE: u64 = 4096;
+
+/// Tamaño de cada ventana muestreada al calcular la entropía
+const ENTROPY_SAMPLE_SIZE: usize = 64 * 1024;
+
+/// Entropía a partir de la cual una escritura se considera cifrada o comprimida
+pub const HIGH_ENTROPY_THRESHOLD: f64 = 7.5;
+
+/// Ubicaciones de arranque/persistencia vigiladas por defecto (`~` = directorio del usuario)
+#[cfg(target_os = "linux")]
+pub const DEFAULT_PERSISTENCE_LOCATIONS: &[&str] = &[
+    "~/.config/autostart", "~/.config/systemd/user", "~/.bashrc", "~/.profile",
+    "/etc/xdg/autostart", "/etc/systemd/system", "/lib/systemd/system", "/usr/lib/systemd/system",
+    "/etc/init.d", "/etc/rc.local", "/etc/profile.d", "/etc/crontab",
+    "/etc/cron.d", "/etc/cron.hourly", "/etc/cron.daily", "/var/spool/cron",
+];
+
+/// Ubicaciones de arranque/persistencia vigiladas por defecto (`~` = directorio del usuario)
+#[cfg(target_os = "macos")]
+pub const DEFAULT_PERSISTENCE_LOCATIONS: &[&str] = &[
+    "~/Library/LaunchAgents", "~/.zshrc", "~/.bash_profile",
+    "/Library/LaunchAgents", "/Library/LaunchDaemons", "/Library/StartupItems",
+    "/usr/lib/cron/tabs", "/etc/periodic",
+];
+
+/// Ubicaciones de arranque/persistencia vigiladas por defecto (`~` = directorio del usuario)
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub const DEFAULT_PERSISTENCE_LOCATIONS: &[&str] = &[
+    "~/AppData/Roaming/Microsoft/Windows/Start Menu/Programs/Startup",
+    "C:/ProgramData/Microsoft/Windows/Start Menu/Programs/StartUp",
+    "C:/Windows/System32/Tasks",
+];
 
 /// Tipo de operación de archivo
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -27,6 +67,23 @@ pub enum FileOperation {
     Unknown,
 }
 
+impl FileOperation {
+    /// Nombre legible de la operación
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileOperation::Read => "lectura",
+            FileOperation::Write => "escritura",
+            FileOperation::Create => "creación",
+            FileOperation::Delete => "eliminación",
+            FileOperation::Open => "apertura",
+            FileOperation::Close => "cierre",
+            FileOperation::Rename { .. } => "renombrado",
+            FileOperation::ChangePermissions => "cambio de permisos",
+            FileOperation::Unknown => "desconocida",
+        }
+    }
+}
+
 /// Registro de una operación de archivo
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEvent {
@@ -42,6 +99,9 @@ pub struct FileEvent {
     pub size: Option<u64>,
     /// Indica si la operación tuvo éxito
     pub success: bool,
+    /// Entropía del archivo escrito (bits por byte, si se calculó)
+    #[serde(default)]
+    pub entropy: Option<f64>,
 }
 
 /// Información sobre un archivo monitoreado
@@ -54,9 +114,151 @@ pub struct FileActivity {
     /// Proceso que realizó la operación (PID)
     pub process_id: Option<u32>,
     /// Tiempo de la operación
-    pub timestamp: SystemTime,
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub timestamp: DateTime<Utc>,
     /// Tamaño del archivo (si es conocido)
     pub size: Option<u64>,
+    /// Entropía del archivo (bits por byte, si se calculó)
+    #[serde(default)]
+    pub entropy: Option<f64>,
+}
+
+/// Eventos repetidos de un proceso sobre una misma ruta con la misma operación,
+/// resumidos en una sola entrada para los reportes y el LLM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAccessSummary {
+    /// Ruta del archivo
+    pub path: String,
+    /// Tipo de operación
+    pub operation: FileOperation,
+    /// Proceso que realizó las operaciones (PID)
+    pub pid: Option<u32>,
+    /// Veces que se repitió la operación
+    pub count: u64,
+    /// Primera vez
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub first_seen: DateTime<Utc>,
+    /// Última vez
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub last_seen: DateTime<Utc>,
+    /// Suma de los tamaños de datos transferidos
+    pub total_bytes: u64,
+    /// Mayor entropía calculada (bits por byte)
+    #[serde(default)]
+    pub max_entropy: Option<f64>,
+    /// Operaciones que fallaron
+    #[serde(default)]
+    pub failures: u64,
+}
+
+/// Clave de agrupación de `FileAccessSummary`
+pub type FileAccessKey = (Option<u32>, String, FileOperation);
+
+impl FileAccessSummary {
+    /// Resumen de un primer evento
+    pub fn new(
+        pid: Option<u32>,
+        path: &str,
+        operation: &FileOperation,
+        timestamp: DateTime<Utc>,
+        size: Option<u64>,
+        entropy: Option<f64>,
+        success: bool,
+    ) -> Self {
+        Self {
+            path: path.to_string(),
+            operation: operation.clone(),
+            pid,
+            count: 1,
+            first_seen: timestamp,
+            last_seen: timestamp,
+            total_bytes: size.unwrap_or(0),
+            max_entropy: entropy,
+            failures: u64::from(!success),
+        }
+    }
+
+    /// Clave de agrupación del resumen
+    pub fn key(&self) -> FileAccessKey {
+        (self.pid, self.path.clone(), self.operation.clone())
+    }
+
+    /// Sumar otro resumen del mismo grupo
+    pub fn merge(&mut self, other: FileAccessSummary) {
+        self.count += other.count;
+        self.first_seen = self.first_seen.min(other.first_seen);
+        self.last_seen = self.last_seen.max(other.last_seen);
+        self.total_bytes += other.total_bytes;
+        self.max_entropy = match (self.max_entropy, other.max_entropy) {
+            (Some(current), Some(entropy)) => Some(current.max(entropy)),
+            (current, entropy) => current.or(entropy),
+        };
+        self.failures += other.failures;
+    }
+
+    /// Resumen de un evento del monitor
+    pub fn from_event(event: &FileEvent) -> Self {
+        Self::new(
+            Some(event.pid),
+            &event.path,
+            &event.operation,
+            event.timestamp,
+            event.size,
+            event.entropy,
+            event.success,
+        )
+    }
+
+    /// Resumen de una actividad de un reporte (las actividades no registran fallos)
+    pub fn from_activity(activity: &FileActivity) -> Self {
+        Self::new(
+            activity.process_id,
+            &activity.path.to_string_lossy(),
+            &activity.operation,
+            activity.timestamp,
+            activity.size,
+            activity.entropy,
+            true,
+        )
+    }
+}
+
+/// Añadir un resumen a una lista agrupada: se suma a su grupo si ya existe (`index`
+/// guarda la posición de cada grupo) y si no, se añade al final
+pub fn merge_file_access(
+    summaries: &mut Vec<FileAccessSummary>,
+    index: &mut HashMap<FileAccessKey, usize>,
+    summary: FileAccessSummary,
+) {
+    let key = summary.key();
+    match index.get(&key) {
+        Some(&position) => summaries[position].merge(summary),
+        None => {
+            index.insert(key, summaries.len());
+            summaries.push(summary);
+        }
+    }
+}
+
+/// Agrupar eventos de archivo por proceso, ruta y operación, en el orden en que
+/// aparece cada grupo por primera vez
+pub fn aggregate_file_events<'a>(events: impl IntoIterator<Item = &'a FileEvent>) -> Vec<FileAccessSummary> {
+    let mut summaries = Vec::new();
+    let mut index = HashMap::new();
+    for event in events {
+        merge_file_access(&mut summaries, &mut index, FileAccessSummary::from_event(event));
+    }
+    summaries
+}
+
+/// Agrupar las actividades de archivo de un reporte, como `aggregate_file_events`
+pub fn aggregate_file_activities<'a>(activities: impl IntoIterator<Item = &'a FileActivity>) -> Vec<FileAccessSummary> {
+    let mut summaries = Vec::new();
+    let mut index = HashMap::new();
+    for activity in activities {
+        merge_file_access(&mut summaries, &mut index, FileAccessSummary::from_activity(activity));
+    }
+    summaries
 }
 
 /// Monitor de operaciones de archivo
@@ -65,12 +267,25 @@ pub struct FileMonitor {
     events: Vec<FileEvent>,
     /// Mapa de archivos abiertos por PID
     open_files: HashMap<u32, Vec<String>>,
-    /// Rutas bajo monitoreo
-    paths: Vec<PathBuf>,
-    /// Actividades registradas
-    activities: Vec<FileActivity>,
-    /// Filtrar por PID
-    filter_pid: Option<u32>,
+    /// Ubicaciones de arranque/persistencia vigiladas
+    persistence_locations: Vec<PathBuf>,
+}
+
+/// Archivo dejado por un proceso en una ubicación de persistencia o hecho ejecutable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistenceDrop {
+    /// Ruta del archivo
+    pub path: String,
+    /// Motivo de la detección
+    pub reason: String,
+    /// Momento del evento que disparó la detección
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Default for FileMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FileMonitor {
@@ -79,23 +294,49 @@ impl FileMonitor {
         Self {
             events: Vec::new(),
             open_files: HashMap::new(),
-            paths: Vec::new(),
-            activities: Vec::new(),
-            filter_pid: None,
+            persistence_locations: DEFAULT_PERSISTENCE_LOCATIONS.iter().map(|l| expand_home(l)).collect(),
         }
     }
 
+    /// Vigilar ubicaciones de persistencia adicionales a las del sistema operativo
+    pub fn add_persistence_locations(&mut self, locations: &[String]) {
+        self.persistence_locations.extend(locations.iter().map(|l| expand_home(l)));
+    }
+
     /// Registrar un evento de archivo
-    pub fn record_event(&mut self, event: FileEvent) {
-        // Actualizar el mapa de archivos abiertos
+    pub fn record_event(&mut self, mut event: FileEvent) {
+        // Calcular la entropía de escrituras suficientemente grandes
+        let is_write = matches!(event.operation, FileOperation::Write | FileOperation::Create);
+        if is_write && event.success && event.entropy.is_none()
+            && event.size.unwrap_or(0) >= ENTROPY_MIN_SIZE
+        {
+            event.entropy = Self::file_entropy(Path::new(&event.path)).ok();
+        }
+        
+        self.track_open_files(&event);
+        self.events.push(event);
+    }
+
+    /// Cargar eventos grabados (`monitor --sample-file`), en orden. A diferencia de
+    /// `record_event`, no se lee el disco: las rutas son de otro equipo o momento,
+    /// así que la entropía es la que traiga cada evento.
+    pub fn load_events(&mut self, events: Vec<FileEvent>) -> usize {
+        let count = events.len();
+        for event in events {
+            self.track_open_files(&event);
+            self.events.push(event);
+        }
+        count
+    }
+
+    /// Actualizar el mapa de archivos abiertos con un evento
+    fn track_open_files(&mut self, event: &FileEvent) {
         match event.operation {
-            FileOperation::Open | FileOperation::Create => {
-                if event.success {
-                    self.open_files
-                        .entry(event.pid)
-                        .or_insert_with(Vec::new)
-                        .push(event.path.clone());
-                }
+            FileOperation::Open | FileOperation::Create if event.success => {
+                self.open_files
+                    .entry(event.pid)
+                    .or_default()
+                    .push(event.path.clone());
             }
             FileOperation::Close => {
                 if let Some(files) = self.open_files.get_mut(&event.pid) {
@@ -106,8 +347,6 @@ impl FileMonitor {
             }
             _ => {}
         }
-
-        self.events.push(event);
     }
 
     /// Obtener todos los eventos registrados
@@ -120,6 +359,12 @@ impl FileMonitor {
         self.events.iter().filter(|e| e.pid == pid).collect()
     }
 
+    /// Eventos de un proceso agrupados por ruta y operación (ver `FileAccessSummary`);
+    /// los eventos en bruto siguen disponibles con `get_events_for_pid`
+    pub fn aggregate_events(&self, pid: u32) -> Vec<FileAccessSummary> {
+        aggregate_file_events(self.events.iter().filter(|event| event.pid == pid))
+    }
+
     /// Obtener archivos actualmente abiertos por un proceso
     pub fn get_open_files_for_pid(&self, pid: u32) -> Vec<&String> {
         match self.open_files.get(&pid) {
@@ -128,33 +373,78 @@ impl FileMonitor {
         }
     }
 
-    /// Analizar el patrón de acceso a archivos de un proceso
-    pub fn analyze_file_pattern(&self, pid: u32) -> Vec<(String, usize)> {
-        let events = self.get_events_for_pid(pid);
+    /// Calcular la entropía de Shannon de un archivo muestreando su inicio, mitad y final
+    pub fn file_entropy(path: &Path) -> Result<f64> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let window = ENTROPY_SAMPLE_SIZE as u64;
         
-        let mut access_count: HashMap<String, usize> = HashMap::new();
-        
-        for event in events {
-            *access_count.entry(event.path.clone()).or_insert(0) += 1;
+        let mut sample = Vec::new();
+        if len <= window * 3 {
+            file.read_to_end(&mut sample)?;
+        } else {
+            for offset in [0, len / 2 - window / 2, len - window] {
+                let mut buf = vec![0u8; ENTROPY_SAMPLE_SIZE];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut buf)?;
+                sample.extend_from_slice(&buf);
+            }
         }
         
-        // Convertir a vector y ordenar por frecuencia (de mayor a menor)
-        let mut result: Vec<(String, usize)> = access_count.into_iter().collect();
-        result.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        result
+        Ok(shannon_entropy(&sample))
     }
 
-    /// Limpiar eventos antiguos (mantener solo los últimos N eventos)
-    pub fn clean_old_events(&mut self, keep_count: usize) {
-        if self.events.len() > keep_count {
-            let to_remove = self.events.len() - keep_count;
-            self.events.drain(0..to_remove);
+    /// Obtener las escrituras de alta entropía de un proceso
+    pub fn high_entropy_writes(&self, pid: u32) -> Vec<&FileEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.pid == pid)
+            .filter(|e| e.entropy.is_some_and(|h| h >= HIGH_ENTROPY_THRESHOLD))
+            .collect()
+    }
+
+    /// Detectar archivos escritos en ubicaciones de arranque/persistencia o
+    /// escritos y después hechos ejecutables (patrón típico de un dropper)
+    pub fn detect_persistence_drops(&self, pid: u32) -> Vec<PersistenceDrop> {
+        let mut drops: Vec<PersistenceDrop> = Vec::new();
+        let mut written: Vec<&str> = Vec::new();
+        
+        for event in self.get_events_for_pid(pid) {
+            if !event.success {
+                continue;
+            }
+            let reason = match event.operation {
+                FileOperation::Write | FileOperation::Create => {
+                    written.push(&event.path);
+                    self.persistence_location_of(&event.path)
+                        .map(|location| format!("Escritura en ubicación de persistencia ({})", location.display()))
+                }
+                FileOperation::ChangePermissions if written.contains(&event.path.as_str()) => {
+                    // Si el archivo sigue ahí y no es ejecutable, el cambio de permisos no es un dropper
+                    (is_executable(Path::new(&event.path)) != Some(false))
+                        .then(|| "Archivo escrito por el proceso y hecho ejecutable".to_string())
+                }
+                _ => None,
+            };
+            
+            if let Some(reason) = reason {
+                if !drops.iter().any(|d| d.path == event.path && d.reason == reason) {
+                    drops.push(PersistenceDrop { path: event.path.clone(), reason, timestamp: event.timestamp });
+                }
+            }
         }
+        
+        drops
     }
-    
+
+    /// Ubicación de persistencia que contiene una ruta, si la hay
+    fn persistence_location_of(&self, path: &str) -> Option<&PathBuf> {
+        let path = Path::new(path);
+        self.persistence_locations.iter().find(|location| path.starts_with(location))
+    }
+
     /// Detectar patrones sospechosos de acceso a archivos
-    pub fn detect_suspicious_patterns(&self, pid: u32) -> Vec<String> {
+    pub fn detect_suspicious_patterns(&self, pid: u32) -> Vec<Detection> {
         let events = self.get_events_for_pid(pid);
         let mut suspicious = Vec::new();
         
@@ -178,80 +468,146 @@ impl FileMonitor {
         for event in &events {
             for path in &sensitive_paths {
                 if event.path.contains(path) {
-                    suspicious.push(format!("Acceso a archivo sensible: {}", event.path));
+                    suspicious.push(Detection {
+                        message: format!("Acceso a archivo sensible: {}", event.path),
+                        timestamp: event.timestamp,
+                    });
                     break;
                 }
             }
         }
         
         // Detector de escritura masiva
-        let mut write_count = 0;
-        for event in &events {
-            if event.operation == FileOperation::Write {
-                write_count += 1;
-            }
-        }
+        let writes: Vec<&&FileEvent> = events
+            .iter()
+            .filter(|event| event.operation == FileOperation::Write)
+            .collect();
         
-        if write_count > 100 {
-            suspicious.push(format!("Escritura masiva detectada: {} archivos", write_count));
+        if writes.len() > 100 {
+            // La detección corresponde a la escritura más reciente
+            let timestamp = writes.last().map_or_else(Utc::now, |event| event.timestamp);
+            suspicious.push(Detection {
+                message: format!("Escritura masiva detectada: {} archivos", writes.len()),
+                timestamp,
+            });
         }
         
         suspicious
     }
+}
 
-    /// Añadir una ruta para monitorear
-    pub fn add_path(&mut self, path: PathBuf) {
-        self.paths.push(path);
-    }
+/// Archivos que un proceso tiene abiertos en este momento (vía /proc/<pid>/fd)
+#[cfg(target_os = "linux")]
+pub fn enumerate_open_files(pid: u32) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        // Descartar sockets, pipes y descriptores anónimos
+        .filter(|target| target.is_absolute())
+        .map(|target| target.to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
 
-    /// Establecer filtro por PID
-    pub fn set_pid_filter(&mut self, pid: Option<u32>) {
-        self.filter_pid = pid;
+/// Archivos que un proceso tiene abiertos en este momento (no disponible en esta plataforma)
+#[cfg(not(target_os = "linux"))]
+pub fn enumerate_open_files(_pid: u32) -> Vec<String> {
+    Vec::new()
+}
+
+/// Expandir `~` al directorio del usuario
+fn expand_home(location: &str) -> PathBuf {
+    match (location.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(location),
     }
+}
 
-    /// Obtener las actividades registradas
-    pub fn get_activities(&self) -> &[FileActivity] {
-        &self.activities
+/// Indica si un archivo tiene permiso de ejecución (None si no se puede consultar)
+#[cfg(unix)]
+fn is_executable(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).ok().map(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+/// Indica si un archivo tiene permiso de ejecución (None si no se puede consultar)
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(pid: u32, path: &str, operation: FileOperation, success: bool) -> FileEvent {
+        FileEvent {
+            pid,
+            path: path.to_string(),
+            operation,
+            timestamp: Utc::now(),
+            size: None,
+            success,
+            entropy: None,
+        }
     }
-    
-    /// Añadir una actividad manualmente (para simulación)
-    pub fn add_activity(&mut self, activity: FileActivity) {
-        self.activities.push(activity);
+
+    #[test]
+    fn write_to_persistence_location_is_a_drop() {
+        let mut monitor = FileMonitor::new();
+        monitor.add_persistence_locations(&["/opt/autorun".to_string()]);
+        monitor.load_events(vec![
+            event(10, "/opt/autorun/agent.desktop", FileOperation::Create, true),
+            event(10, "/opt/autorun/agent.desktop", FileOperation::Write, true),
+            event(10, "/opt/autorunner/notes.txt", FileOperation::Write, true),
+            event(10, "/opt/autorun/failed", FileOperation::Write, false),
+            event(10, "/opt/autorun/read-only", FileOperation::Read, true),
+            event(11, "/opt/autorun/other-pid", FileOperation::Write, true),
+        ]);
+
+        let drops = monitor.detect_persistence_drops(10);
+        assert_eq!(drops.len(), 1, "{:?}", drops);
+        assert_eq!(drops[0].path, "/opt/autorun/agent.desktop");
+        assert_eq!(drops[0].reason, "Escritura en ubicación de persistencia (/opt/autorun)");
     }
-    
-    /// Simular una detección para pruebas
-    pub fn simulate_activity(&mut self) {
-        // Simulamos algunas operaciones de archivos para probar la UI
-        if self.paths.is_empty() {
-            // Añadir una ruta por defecto si no hay ninguna
-            self.paths.push(PathBuf::from("/tmp"));
-        }
-        
-        let operations = [
-            FileOperation::Read,
-            FileOperation::Write,
-            FileOperation::Create,
-        ];
-        
-        // Simular una actividad con la primera ruta
-        if let Some(path) = self.paths.first() {
-            let op_index = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or(Duration::from_secs(0))
-                .as_secs() as usize % operations.len();
-                
-            self.activities.push(FileActivity {
-                path: path.join(format!("file_{}.txt", self.activities.len())),
-                operation: operations[op_index].clone(),
-                process_id: self.filter_pid,
-                timestamp: SystemTime::now(),
-                size: Some(1024 * (self.activities.len() as u64 % 10 + 1)),
-            });
-            
-            // Limitar a 100 registros para no consumir demasiada memoria
-            if self.activities.len() > 100 {
-                self.activities.remove(0);
-            }
-        }
+
+    #[cfg(unix)]
+    #[test]
+    fn written_file_made_executable_is_a_drop() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("shadowtrace-drop-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let payload = dir.join("payload");
+        let data = dir.join("data");
+        std::fs::write(&payload, b"#!/bin/sh\n").unwrap();
+        std::fs::write(&data, b"x").unwrap();
+        std::fs::set_permissions(&payload, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(&data, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let (payload, data) = (payload.to_string_lossy().to_string(), data.to_string_lossy().to_string());
+
+        let mut monitor = FileMonitor::new();
+        monitor.load_events(vec![
+            event(20, &payload, FileOperation::Write, true),
+            event(20, &payload, FileOperation::ChangePermissions, true),
+            // Un chmod que deja el archivo sin permiso de ejecución no es un dropper
+            event(20, &data, FileOperation::Write, true),
+            event(20, &data, FileOperation::ChangePermissions, true),
+            // Ni un chmod sobre un archivo que el proceso no escribió
+            event(20, "/usr/bin/env", FileOperation::ChangePermissions, true),
+        ]);
+        let drops = monitor.detect_persistence_drops(20);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            drops.iter().map(|d| (d.path.as_str(), d.reason.as_str())).collect::<Vec<_>>(),
+            vec![(payload.as_str(), "Archivo escrito por el proceso y hecho ejecutable")]
+        );
     }
-} 
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tiny_http::{Header, Method, Response, Server};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::process::ProcessInfo;
+use crate::redaction::Redactor;
+use crate::reports::{Finding, SeverityLevel};
+
+/// Interfaz en la que escucha `--serve :PUERTO` si no se indica otra
+pub const DEFAULT_SERVE_HOST: &str = "127.0.0.1";
+
+/// Interpretar el valor de `--serve`: `:PUERTO` o `PUERTO` escuchan solo en local,
+/// `HOST:PUERTO` en la interfaz indicada
+pub fn parse_serve_addr(value: &str) -> Result<SocketAddr, AppError> {
+    let value = value.trim();
+    let address = match value.strip_prefix(':') {
+        Some(port) => format!("{}:{}", DEFAULT_SERVE_HOST, port),
+        None if value.parse::<u16>().is_ok() => format!("{}:{}", DEFAULT_SERVE_HOST, value),
+        None => value.to_string(),
+    };
+    address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| AppError::ConfigurationError(format!(
+            "Dirección de --serve no válida: '{}' (usa :PUERTO o HOST:PUERTO)", value
+        )))
+}
+
+/// Modo de ShadowTrace que publica la instantánea
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveMode {
+    /// `monitor`
+    Monitor,
+    /// `audit`
+    Audit,
+    /// `system --watch`
+    System,
+    /// Interfaz de terminal
+    Tui,
+}
+
+/// Hallazgos por severidad
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FindingCounts {
+    pub critical: usize,
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+/// Métricas agregadas de la instantánea
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LiveMetrics {
+    /// Segundos desde que se inició el endpoint
+    pub uptime_secs: u64,
+    /// Procesos incluidos en la instantánea
+    pub processes: usize,
+    /// Eventos de archivo registrados
+    pub file_events: usize,
+    /// Eventos de red registrados
+    pub network_events: usize,
+    /// Hallazgos por severidad
+    pub findings: FindingCounts,
+}
+
+/// Estado actual del monitoreo que devuelve el endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSnapshot {
+    /// Versión de ShadowTrace
+    pub version: &'static str,
+    /// Modo que publica la instantánea
+    pub mode: LiveMode,
+    /// Última actualización
+    pub updated_at: DateTime<Utc>,
+    /// Procesos monitoreados (o todos, en `system` y en la TUI)
+    pub processes: Vec<ProcessInfo>,
+    /// Hallazgos hasta el momento
+    pub findings: Vec<Finding>,
+    /// Métricas agregadas
+    pub metrics: LiveMetrics,
+}
+
+/// Endpoint HTTP local de solo lectura con la última instantánea en JSON (`--serve`).
+/// Atiende las peticiones en un hilo propio; se detiene al soltarse.
+pub struct LiveServer {
+    server: Arc<Server>,
+    snapshot: Arc<Mutex<serde_json::Value>>,
+    mode: LiveMode,
+    started: Instant,
+    /// Redacción de secretos aplicada antes de publicar
+    redactor: Option<Redactor>,
+}
+
+impl LiveServer {
+    /// Escuchar en la dirección indicada y empezar a atender peticiones
+    pub fn start(addr: SocketAddr, mode: LiveMode, redactor: Option<Redactor>) -> Result<Self> {
+        let server = Server::http(addr)
+            .map_err(|e| anyhow::anyhow!("No se pudo escuchar en {} (--serve): {}", addr, e))?;
+        if !addr.ip().is_loopback() {
+            warn!("El endpoint --serve escucha en {}, accesible desde la red", addr);
+            println!("⚠️ El endpoint JSON escucha en {}: cualquiera con acceso a la red puede leer el estado", addr);
+        }
+        info!("Endpoint JSON en vivo en http://{}", addr);
+        println!("Estado en vivo: http://{}/snapshot", addr);
+
+        let server = Arc::new(server);
+        let live = Self {
+            server: Arc::clone(&server),
+            snapshot: Arc::new(Mutex::new(serde_json::Value::Null)),
+            mode,
+            started: Instant::now(),
+            redactor,
+        };
+        live.publish(Vec::new(), Vec::new(), 0, 0);
+
+        let snapshot = Arc::clone(&live.snapshot);
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let path = request.url().split('?').next().unwrap_or_default();
+                let (status, body) = match (request.method(), path) {
+                    (Method::Get, "/" | "/snapshot") => {
+                        let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+                        (200, snapshot.to_string())
+                    }
+                    (Method::Get, "/health") => (200, r#"{"status":"ok"}"#.to_string()),
+                    (Method::Get, _) => (404, r#"{"error":"Ruta no encontrada; usa /snapshot"}"#.to_string()),
+                    _ => (405, r#"{"error":"Endpoint de solo lectura: usa GET"}"#.to_string()),
+                };
+                let response = Response::from_string(body)
+                    .with_status_code(status)
+                    .with_header(header("Content-Type", "application/json; charset=utf-8"))
+                    .with_header(header("Cache-Control", "no-store"));
+                if let Err(e) = request.respond(response) {
+                    warn!("No se pudo responder a una petición de --serve: {}", e);
+                }
+            }
+        });
+
+        Ok(live)
+    }
+
+    /// Reemplazar la instantánea publicada
+    pub fn publish(&self, processes: Vec<ProcessInfo>, findings: Vec<Finding>, file_events: usize, network_events: usize) {
+        let mut counts = FindingCounts::default();
+        for finding in &findings {
+            match finding.severity {
+                SeverityLevel::Critical => counts.critical += 1,
+                SeverityLevel::Error => counts.error += 1,
+                SeverityLevel::Warning => counts.warning += 1,
+                SeverityLevel::Info => counts.info += 1,
+            }
+        }
+        let snapshot = LiveSnapshot {
+            version: env!("CARGO_PKG_VERSION"),
+            mode: self.mode,
+            updated_at: Utc::now(),
+            metrics: LiveMetrics {
+                uptime_secs: self.started.elapsed().as_secs(),
+                processes: processes.len(),
+                file_events,
+                network_events,
+                findings: counts,
+            },
+            processes,
+            findings,
+        };
+
+        let mut value = match serde_json::to_value(&snapshot) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("No se pudo serializar la instantánea de --serve: {}", e);
+                return;
+            }
+        };
+        if let Some(redactor) = &self.redactor {
+            redactor.redact_value(&mut value);
+        }
+        *self.snapshot.lock().unwrap_or_else(|e| e.into_inner()) = value;
+    }
+}
+
+impl Drop for LiveServer {
+    fn drop(&mut self) {
+        // Despierta al hilo de `incoming_requests` para que termine
+        self.server.unblock();
+    }
+}
+
+/// Cabecera HTTP de la respuesta
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("cabecera HTTP válida")
+}
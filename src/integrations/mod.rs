@@ -0,0 +1,4 @@
+// Integraciones con servicios externos
+pub mod live;
+pub mod syslog;
+pub mod webhook;
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+use ::syslog::{Facility, Formatter5424, LoggerBackend};
+
+use crate::reports::{Finding, SeverityLevel};
+
+/// Identificador de los datos estructurados RFC 5424 (PEN 32473, reservado para documentación)
+const STRUCTURED_DATA_ID: &str = "shadowtrace@32473";
+
+/// Socket nativo del journal de systemd
+#[cfg(unix)]
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Destino de los hallazgos en el registro del sistema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogTarget {
+    /// Syslog local (/dev/log) en formato RFC 5424 con datos estructurados
+    Syslog,
+    /// Journal de systemd con campos propios (SHADOWTRACE_*)
+    Journald,
+}
+
+enum Backend {
+    Syslog(::syslog::Logger<LoggerBackend, Formatter5424>),
+    #[cfg(unix)]
+    Journald(std::os::unix::net::UnixDatagram),
+}
+
+/// Envía cada hallazgo al registro del sistema una sola vez
+pub struct FindingSink {
+    backend: Backend,
+    /// Hallazgos ya registrados (título + descripción)
+    sent: HashSet<String>,
+}
+
+impl FindingSink {
+    /// Conectar con el destino indicado
+    pub fn connect(target: SyslogTarget) -> Result<Self> {
+        let backend = match target {
+            SyslogTarget::Syslog => {
+                let formatter = Formatter5424 {
+                    facility: Facility::LOG_DAEMON,
+                    hostname: None,
+                    process: env!("CARGO_PKG_NAME").to_string(),
+                    pid: std::process::id(),
+                };
+                let logger = ::syslog::unix(formatter)
+                    .map_err(|e| anyhow::anyhow!("No se pudo conectar con syslog: {}", e))?;
+                Backend::Syslog(logger)
+            }
+            #[cfg(unix)]
+            SyslogTarget::Journald => {
+                let socket = std::os::unix::net::UnixDatagram::unbound()?;
+                socket.connect(JOURNALD_SOCKET).map_err(|e| anyhow::anyhow!(
+                    "No se pudo conectar con el journal de systemd ({}): {}", JOURNALD_SOCKET, e
+                ))?;
+                Backend::Journald(socket)
+            }
+            #[cfg(not(unix))]
+            SyslogTarget::Journald => {
+                return Err(anyhow::anyhow!("El journal de systemd no está disponible en esta plataforma"));
+            }
+        };
+        Ok(Self { backend, sent: HashSet::new() })
+    }
+
+    /// Registrar los hallazgos aún no enviados del proceso `pid`.
+    /// Un fallo al escribir se avisa en el log sin interrumpir el monitoreo.
+    pub fn notify(&mut self, findings: &[Finding], pid: u32) {
+        for finding in findings {
+            let key = format!("{}\u{0}{}", finding.title, finding.description);
+            if !self.sent.insert(key) {
+                continue;
+            }
+            if let Err(e) = self.send(finding, pid) {
+                warn!("No se pudo registrar el hallazgo en el registro del sistema: {}", e);
+            }
+        }
+    }
+
+    fn send(&mut self, finding: &Finding, pid: u32) -> Result<()> {
+        match &mut self.backend {
+            Backend::Syslog(logger) => {
+                let params = HashMap::from([
+                    ("severity".to_string(), escape_param(severity_name(finding.severity))),
+                    ("category".to_string(), escape_param(&finding.title)),
+                    ("pid".to_string(), pid.to_string()),
+                ]);
+                let data = HashMap::from([(STRUCTURED_DATA_ID.to_string(), params)]);
+                let message = (0, data, finding.description.clone());
+                let result = match finding.severity {
+                    SeverityLevel::Critical => logger.crit(message),
+                    SeverityLevel::Error => logger.err(message),
+                    SeverityLevel::Warning => logger.warning(message),
+                    SeverityLevel::Info => logger.info(message),
+                };
+                result.map_err(|e| anyhow::anyhow!("{}", e))
+            }
+            #[cfg(unix)]
+            Backend::Journald(socket) => {
+                let mut datagram = Vec::new();
+                let mut field = |name: &str, value: &str| journal_field(&mut datagram, name, value);
+                field("MESSAGE", &format!("[{}] {}", finding.title, finding.description));
+                field("PRIORITY", &syslog_priority(finding.severity).to_string());
+                field("SYSLOG_IDENTIFIER", env!("CARGO_PKG_NAME"));
+                field("SHADOWTRACE_SEVERITY", severity_name(finding.severity));
+                field("SHADOWTRACE_CATEGORY", &finding.title);
+                field("SHADOWTRACE_PID", &pid.to_string());
+                if let Some(recommendation) = &finding.recommendation {
+                    field("SHADOWTRACE_RECOMMENDATION", recommendation);
+                }
+                socket.send(&datagram)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Nombre de la severidad en los campos estructurados
+fn severity_name(severity: SeverityLevel) -> &'static str {
+    match severity {
+        SeverityLevel::Info => "info",
+        SeverityLevel::Warning => "warning",
+        SeverityLevel::Error => "error",
+        SeverityLevel::Critical => "critical",
+    }
+}
+
+/// Prioridad de syslog (crit, err, warning, info) equivalente a la severidad
+#[cfg(unix)]
+fn syslog_priority(severity: SeverityLevel) -> u8 {
+    match severity {
+        SeverityLevel::Critical => 2,
+        SeverityLevel::Error => 3,
+        SeverityLevel::Warning => 4,
+        SeverityLevel::Info => 6,
+    }
+}
+
+/// Escapar un valor de los datos estructurados RFC 5424 (`"`, `\` y `]`)
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Añadir un campo al datagrama del journal en el formato binario
+/// (nombre, salto de línea, longitud u64 little-endian y valor), válido aunque
+/// el valor contenga saltos de línea
+#[cfg(unix)]
+fn journal_field(datagram: &mut Vec<u8>, name: &str, value: &str) {
+    datagram.extend_from_slice(name.as_bytes());
+    datagram.push(b'\n');
+    datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    datagram.extend_from_slice(value.as_bytes());
+    datagram.push(b'\n');
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::reports::{Finding, SeverityLevel};
+
+/// Tiempo máximo de espera al enviar una alerta
+const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+/// Enviar un hallazgo a un webhook (payload compatible con Slack)
+pub async fn post_finding(client: &reqwest::Client, url: &str, finding: &Finding) -> Result<()> {
+    let payload = json!({
+        // Campo `text` que muestran Slack y servicios compatibles
+        "text": format!("🔴 ShadowTrace [{:?}] {}: {}", finding.severity, finding.title, finding.description),
+        "title": finding.title,
+        "description": finding.description,
+        "severity": finding.severity,
+        "recommendation": finding.recommendation,
+        "affected_resources": finding.affected_resources,
+    });
+
+    let response = client.post(url).json(&payload).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("El webhook respondió con estado {}", response.status()));
+    }
+
+    Ok(())
+}
+
+/// Despachador de alertas que evita reenviar el mismo hallazgo
+pub struct AlertDispatcher {
+    /// URL del webhook
+    url: String,
+    /// Cliente HTTP compartido por todos los envíos
+    client: reqwest::Client,
+    /// Hallazgos ya notificados (título + descripción)
+    sent: HashSet<String>,
+    /// Envíos en curso
+    pending: Vec<JoinHandle<()>>,
+}
+
+impl AlertDispatcher {
+    /// Crear un despachador para la URL indicada
+    pub fn new(url: String) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            url,
+            client,
+            sent: HashSet::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Notificar los hallazgos críticos aún no enviados, sin bloquear al llamador
+    pub fn notify_critical(&mut self, findings: &[Finding]) {
+        for finding in findings.iter().filter(|f| f.severity == SeverityLevel::Critical) {
+            let key = format!("{}\u{0}{}", finding.title, finding.description);
+            if !self.sent.insert(key) {
+                continue;
+            }
+
+            let client = self.client.clone();
+            let url = self.url.clone();
+            let finding = finding.clone();
+            self.pending.push(tokio::spawn(async move {
+                if let Err(e) = post_finding(&client, &url, &finding).await {
+                    warn!("No se pudo enviar la alerta al webhook: {}", e);
+                }
+            }));
+        }
+        self.pending.retain(|handle| !handle.is_finished());
+    }
+
+    /// Esperar a que terminen los envíos pendientes (acotado por el timeout de cada envío)
+    pub async fn flush(&mut self) {
+        for handle in self.pending.drain(..) {
+            let _ = handle.await;
+        }
+    }
+}
@@ -7,9 +7,30 @@ pub mod reports;
 pub mod app;
 pub mod cli;
 pub mod config;
+pub mod desktop;
 pub mod commands;
 pub mod error;
 pub mod llm;
+pub mod prompt_template;
+pub mod redaction;
+pub mod static_analysis;
+pub mod syscalls;
+pub mod system_logs;
+pub mod offline_analysis;
+pub mod sample_events;
+pub mod timeline;
+pub mod timestamp;
+pub mod asn;
+pub mod dns;
+pub mod audit_log;
+pub mod bundle;
+pub mod session;
+pub mod integrations;
+pub mod profile;
+pub mod encoding;
+pub mod capture;
+pub mod sampler;
+pub mod schedule;
 
 // Reexportaciones útiles para los usuarios de la biblioteca
 pub use app::App;
@@ -1,16 +1,153 @@
 use anyhow::{Result, Context};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 
+use crate::config::{
+    DEFAULT_API_URL, DEFAULT_LLM_TIMEOUT, DEFAULT_MODEL, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_POOL_MAX_IDLE_PER_HOST,
+    DEFAULT_TEMPERATURE,
+};
+use crate::error::AppError;
+use crate::prompt_template::PromptTemplate;
+use crate::redaction::{is_sensitive_key, Redactor, DEFAULT_SENSITIVE_KEYS, REDACTED};
+use crate::reports::Finding;
+
 /// Proveedor de LLM
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 pub enum LlmProvider {
     /// Ollama (https://ollama.ai)
+    #[default]
+    #[serde(rename = "ollama")]
     Ollama,
-    /// Directo a la API OpenAI compatible
+    /// Directo a la API OpenAI compatible (vLLM, llama.cpp, LM Studio, ...)
+    #[serde(rename = "openai", alias = "openai-compatible")]
+    #[value(name = "openai", alias = "openai-compatible")]
     OpenAiCompatible,
+    /// Google Gemini (https://ai.google.dev), con clave de API
+    #[serde(rename = "gemini")]
+    Gemini,
+}
+
+/// Host de la API de Gemini
+pub const GEMINI_HOST: &str = "generativelanguage.googleapis.com";
+
+/// Dónde se envía la clave de API de Gemini (`api_key_placement`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyPlacement {
+    /// Cabecera `x-goog-api-key` (no queda en las URLs)
+    #[default]
+    Header,
+    /// Parámetro `?key=` de la URL
+    Query,
+}
+
+impl LlmProvider {
+    /// Proveedor que corresponde a una URL de API: el host de Google es Gemini, las
+    /// rutas `/v1/...` son de una API OpenAI compatible y el resto, de la API nativa de Ollama
+    pub fn for_api_url(api_url: &str) -> Self {
+        if api_url.contains(GEMINI_HOST) || api_url.contains(":generateContent") {
+            LlmProvider::Gemini
+        } else if api_url.contains("/v1") {
+            LlmProvider::OpenAiCompatible
+        } else {
+            LlmProvider::Ollama
+        }
+    }
+
+    /// Puerto en el que escucha el servidor por convención
+    pub fn default_port(&self) -> u16 {
+        match self {
+            LlmProvider::Ollama => 11434,
+            LlmProvider::OpenAiCompatible => 8000,
+            LlmProvider::Gemini => 443,
+        }
+    }
+
+    /// Ruta de la API por convención
+    pub fn api_path(&self) -> &'static str {
+        match self {
+            LlmProvider::Ollama => "/api",
+            LlmProvider::OpenAiCompatible => "/v1/chat/completions",
+            LlmProvider::Gemini => "/v1beta",
+        }
+    }
+
+    /// URL de la API a partir de un host (`--host`), con el puerto y la ruta de
+    /// costumbre del proveedor. El host puede llevar puerto (`10.0.0.5:8080`) o
+    /// esquema (`https://gpu.lan`), y entonces se respeta tal cual; sin host, localhost
+    /// (la API de Google para Gemini, siempre por https).
+    pub fn endpoint(&self, host: Option<&str>) -> String {
+        let host = host
+            .map(|h| h.trim().trim_end_matches('/'))
+            .filter(|h| !h.is_empty())
+            .unwrap_or(if *self == LlmProvider::Gemini { GEMINI_HOST } else { "localhost" });
+        let has_port = host
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+        let base = if host.contains("://") {
+            host.to_string()
+        } else if *self == LlmProvider::Gemini {
+            format!("https://{}", host)
+        } else if host.parse::<std::net::Ipv6Addr>().is_ok() {
+            format!("http://[{}]:{}", host, self.default_port())
+        } else if has_port {
+            format!("http://{}", host)
+        } else {
+            format!("http://{}:{}", host, self.default_port())
+        };
+        format!("{}{}", base, self.api_path())
+    }
+
+    /// URL a la que se envían las peticiones de generación. En Gemini cada modelo tiene
+    /// la suya: `<base>/models/<modelo>:generateContent` (ver `gemini_model_url`)
+    pub fn generate_url(&self, api_url: &str) -> String {
+        let api_url = api_url.trim_end_matches('/');
+        match self {
+            LlmProvider::Gemini => format!("{}/models", gemini_base_url(api_url)),
+            LlmProvider::Ollama if api_url.ends_with("/generate") => api_url.to_string(),
+            LlmProvider::Ollama => format!("{}/generate", api_url),
+            LlmProvider::OpenAiCompatible if api_url.ends_with("/chat/completions") => api_url.to_string(),
+            LlmProvider::OpenAiCompatible if api_url.ends_with("/v1") => format!("{}/chat/completions", api_url),
+            LlmProvider::OpenAiCompatible => format!("{}/v1/chat/completions", api_url),
+        }
+    }
+
+    /// URL con la lista de modelos disponibles
+    pub fn models_url(&self, api_url: &str) -> String {
+        let api_url = api_url.trim_end_matches('/');
+        match self {
+            LlmProvider::Ollama => format!("{}/tags", api_url.trim_end_matches("/generate")),
+            LlmProvider::Gemini => format!("{}/models", gemini_base_url(api_url)),
+            LlmProvider::OpenAiCompatible => match api_url.find("/v1") {
+                Some(index) => format!("{}/v1/models", &api_url[..index]),
+                None => format!("{}/v1/models", api_url),
+            },
+        }
+    }
+}
+
+/// Base de la API de Gemini (`https://.../v1beta`), aunque la URL configurada ya
+/// apunte a un modelo (`.../models/gemini-1.5-pro:generateContent`)
+fn gemini_base_url(api_url: &str) -> &str {
+    let api_url = api_url.trim_end_matches('/');
+    match api_url.find("/models") {
+        Some(index) => &api_url[..index],
+        None => api_url,
+    }
+}
+
+/// URL de un método de un modelo de Gemini (`generateContent`, `streamGenerateContent`)
+pub fn gemini_model_url(api_url: &str, model: &str, method: &str) -> String {
+    let model = model.trim_start_matches("models/");
+    format!("{}/models/{}:{}", gemini_base_url(api_url), model, method)
 }
 
 /// Configuración para el cliente LLM
@@ -26,71 +163,972 @@ pub struct LlmConfig {
     pub temperature: f32,
     /// Timeout en segundos
     pub timeout_seconds: u64,
-    /// Longitud máxima de salida
+    /// Longitud máxima de salida (None = la del modo: CLI o TUI)
     pub max_tokens: Option<u32>,
+    /// Tamaño de la ventana de contexto del modelo en tokens (`num_ctx` de Ollama)
+    pub num_ctx: Option<u32>,
+    /// Conexiones inactivas por host que se conservan para reutilizarlas (0 = ninguna)
+    pub pool_max_idle_per_host: usize,
+    /// Segundos que se conserva una conexión inactiva antes de cerrarla
+    pub pool_idle_timeout_seconds: u64,
+    /// Registrar URL, cabeceras y cuerpos de cada petición y respuesta (redactados)
+    pub debug_requests: bool,
+    /// Repetir una vez el análisis completo solo con estadísticas si el modelo rechaza
+    /// el prompt por no caber en su ventana de contexto
+    pub retry_reduced_context: bool,
+    /// Modelos a probar en orden si el principal no está disponible
+    pub fallback_models: Vec<String>,
+    /// Plantilla del prompt de análisis completo (`--template`)
+    pub prompt_template: PromptTemplate,
+    /// Notas del analista (`--note`, tecla `n` de la TUI) que se añaden a los prompts de
+    /// análisis y de las preguntas de seguimiento
+    pub analyst_notes: Vec<String>,
+    /// Clave de API de los proveedores que la piden (Gemini)
+    pub api_key: Option<String>,
+    /// Dónde se envía la clave de API
+    pub api_key_placement: ApiKeyPlacement,
 }
 
 impl Default for LlmConfig {
     fn default() -> Self {
         Self {
-            provider: LlmProvider::Ollama,
-            api_url: "http://localhost:11434/api".to_string(),
-            model: "llama2".to_string(),
-            temperature: 0.5,
-            timeout_seconds: 30,
-            max_tokens: Some(512),
+            provider: LlmProvider::for_api_url(DEFAULT_API_URL),
+            api_url: DEFAULT_API_URL.to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            temperature: DEFAULT_TEMPERATURE,
+            timeout_seconds: DEFAULT_LLM_TIMEOUT,
+            max_tokens: None,
+            num_ctx: None,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout_seconds: DEFAULT_POOL_IDLE_TIMEOUT,
+            debug_requests: false,
+            retry_reduced_context: true,
+            fallback_models: Vec::new(),
+            prompt_template: PromptTemplate::default(),
+            analyst_notes: Vec::new(),
+            api_key: None,
+            api_key_placement: ApiKeyPlacement::default(),
+        }
+    }
+}
+
+impl LlmConfig {
+    /// Comprobar que la configuración es utilizable antes de crear el cliente,
+    /// con mensajes que indican qué corregir
+    pub fn validate(&self) -> std::result::Result<(), AppError> {
+        let url = reqwest::Url::parse(&self.api_url).map_err(|e| AppError::ConfigurationError(format!(
+            "La URL de la API LLM '{}' no es válida ({}). Usa una URL completa, por ejemplo http://localhost:11434/api",
+            self.api_url, e
+        )))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(AppError::ConfigurationError(format!(
+                "La URL de la API LLM '{}' debe usar http o https (recibido: {})",
+                self.api_url, url.scheme()
+            )));
+        }
+        if self.model.trim().is_empty() {
+            return Err(AppError::ConfigurationError(
+                "No se ha indicado ningún modelo LLM. Usa --model o la clave 'model' del archivo de configuración".to_string()
+            ));
+        }
+        if self.timeout_seconds == 0 {
+            return Err(AppError::ConfigurationError(
+                "El timeout del LLM debe ser mayor que 0 segundos".to_string()
+            ));
+        }
+        if !(0.0..=2.0).contains(&self.temperature) {
+            return Err(AppError::ConfigurationError(format!(
+                "La temperatura debe estar entre 0.0 y 2.0 (recibido: {})", self.temperature
+            )));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(AppError::ConfigurationError(
+                "max_tokens debe ser mayor que 0; omítelo para usar el valor por defecto".to_string()
+            ));
+        }
+        if self.num_ctx == Some(0) {
+            return Err(AppError::ConfigurationError(
+                "num_ctx debe ser mayor que 0; omítelo para usar el tamaño de contexto del modelo".to_string()
+            ));
+        }
+        if self.provider == LlmProvider::Gemini && self.api_key.as_deref().filter(|key| !key.trim().is_empty()).is_none() {
+            return Err(AppError::ConfigurationError(
+                "Gemini necesita una clave de API: define GEMINI_API_KEY (o GOOGLE_API_KEY) o la clave 'api_key' del archivo de configuración".to_string()
+            ));
+        }
+        if self.pool_idle_timeout_seconds == 0 {
+            return Err(AppError::ConfigurationError(
+                "pool_idle_timeout debe ser mayor que 0 segundos; usa pool_max_idle_per_host = 0 para no reutilizar conexiones".to_string()
+            ));
+        }
+
+        // Combinaciones sospechosas de proveedor y URL: se avisa pero no se rechazan
+        let path = url.path();
+        match self.provider {
+            LlmProvider::Ollama if path.contains("/v1/chat/completions") => tracing::warn!(
+                "El proveedor es Ollama pero la URL '{}' parece de una API OpenAI compatible; \
+                 ¿querías usar --provider openai o la URL http://localhost:11434/api?",
+                self.api_url
+            ),
+            LlmProvider::OpenAiCompatible if path.contains("/api/generate") || path.contains("/api/chat") => tracing::warn!(
+                "El proveedor es openai pero la URL '{}' parece de la API nativa de Ollama; \
+                 ¿querías usar .../v1/chat/completions?",
+                self.api_url
+            ),
+            LlmProvider::Ollama | LlmProvider::OpenAiCompatible if url.host_str() == Some(GEMINI_HOST) => tracing::warn!(
+                "La URL '{}' es de la API de Gemini; ¿querías usar --provider gemini?",
+                self.api_url
+            ),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Cuánto de los eventos en bruto se incluye en el prompt frente a estadísticas agregadas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisDepth {
+    /// Solo estadísticas (modelos con poco contexto)
+    Shallow,
+    /// Estadísticas y una muestra de los eventos más recientes (por defecto)
+    #[default]
+    Normal,
+    /// Todos los eventos en bruto
+    Deep,
+}
+
+impl AnalysisDepth {
+    /// Eventos recientes que se envían en profundidad normal
+    pub const NORMAL_SAMPLE: usize = 50;
+
+    /// Preparar una lista de eventos para el prompt según la profundidad.
+    /// `group_by` es el campo por el que se cuentan los eventos en el resumen.
+    pub fn shape_events(&self, events: Value, group_by: &str) -> Value {
+        let Value::Array(events) = events else {
+            return events;
+        };
+        if *self == AnalysisDepth::Deep {
+            return Value::Array(events);
+        }
+        
+        // Las entradas agrupadas (`FileAccessSummary`) cuentan tantas veces como eventos resumen
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        let mut total_events = 0;
+        for event in &events {
+            let weight = event.get("count").and_then(Value::as_u64).unwrap_or(1);
+            total_events += weight;
+            let key = match event.get(group_by) {
+                Some(Value::String(value)) => value.clone(),
+                // Variantes con datos (p. ej. `{"Rename": {...}}`): usar el nombre de la variante
+                Some(Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+                Some(Value::Null) | None => "desconocido".to_string(),
+                Some(other) => other.to_string(),
+            };
+            *counts.entry(key).or_insert(0) += weight;
+        }
+        
+        let mut summary = serde_json::Map::new();
+        summary.insert("total_events".to_string(), serde_json::json!(total_events));
+        summary.insert(format!("by_{}", group_by), serde_json::json!(counts));
+        if *self == AnalysisDepth::Normal {
+            let skip = events.len().saturating_sub(Self::NORMAL_SAMPLE);
+            summary.insert("recent_events".to_string(), Value::Array(events.into_iter().skip(skip).collect()));
+        }
+        Value::Object(summary)
+    }
+
+    /// Reducir a solo estadísticas, como en shallow, unos eventos ya preparados con
+    /// cualquier profundidad
+    pub fn reduce_to_shallow(events: Value, group_by: &str) -> Value {
+        match events {
+            Value::Array(_) => AnalysisDepth::Shallow.shape_events(events, group_by),
+            Value::Object(mut summary) => {
+                summary.remove("recent_events");
+                Value::Object(summary)
+            }
+            other => other,
         }
     }
 }
 
+/// Respuesta HTTP de error de un endpoint LLM
+#[derive(Debug, thiserror::Error)]
+#[error("El endpoint LLM {url} devolvió un error ({status}): {}", .body.chars().take(200).collect::<String>().trim())]
+pub struct LlmHttpError {
+    /// URL de la petición
+    pub url: String,
+    /// Código de estado
+    pub status: reqwest::StatusCode,
+    /// Cuerpo de la respuesta
+    pub body: String,
+}
+
+impl LlmHttpError {
+    /// Indica si el error se debe al modelo (no encontrado, no cargado) o al servidor,
+    /// de modo que otro modelo podría responder
+    pub fn is_model_unavailable(&self) -> bool {
+        if self.is_context_overflow() {
+            return false;
+        }
+        if self.status.is_server_error() {
+            return true;
+        }
+        let body = self.body.to_lowercase();
+        let mentions_model = body.contains("model");
+        mentions_model
+            && (self.status == reqwest::StatusCode::NOT_FOUND
+                || body.contains("not found")
+                || body.contains("does not exist")
+                || body.contains("not loaded"))
+    }
+
+    /// Indica si el servidor rechazó el prompt por no caber en la ventana de contexto
+    /// del modelo (llama.cpp, vLLM, LM Studio y la API de OpenAI lo informan así)
+    pub fn is_context_overflow(&self) -> bool {
+        let body = self.body.to_lowercase();
+        CONTEXT_OVERFLOW_MARKERS.iter().any(|marker| body.contains(marker))
+    }
+}
+
+/// Textos con los que los servidores informan de que el prompt excede el contexto
+const CONTEXT_OVERFLOW_MARKERS: &[&str] = &[
+    "context length", "context_length", "context window", "context size", "maximum context",
+    "exceeds the context", "too many tokens", "prompt is too long", "input length exceeds",
+    "exceeds the maximum number of tokens",
+];
+
+/// Indica si un error del LLM se debe a que el prompt no cabe en la ventana de contexto
+pub fn is_context_overflow(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<LlmHttpError>())
+        .any(LlmHttpError::is_context_overflow)
+}
+
+/// Destino de los registros de `--debug-llm`; el filtro por defecto lo deja pasar
+/// porque solo se escribe cuando el flag está activo
+pub const DEBUG_LOG_TARGET: &str = "shadowtrace::llm::debug";
+
+/// Máximo de bytes de cada cuerpo registrado con `--debug-llm`
+const DEBUG_BODY_LIMIT: usize = 16 * 1024;
+
+/// Estimación aproximada de los tokens de un texto (unos 4 caracteres por token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
 /// Solicitud a Ollama
 #[derive(Debug, Serialize)]
 struct OllamaRequest {
     model: String,
     prompt: String,
+    /// Ollama responde por fragmentos salvo que se pida lo contrario
+    stream: bool,
     temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<Value>,
 }
 
-/// Respuesta de Ollama u OpenAI
+/// Respuesta de Ollama (completa, o cada línea de una respuesta por fragmentos)
 #[derive(Debug, Deserialize)]
 struct OllamaResponse {
-    #[serde(default)]
-    model: String,
     #[serde(default)]
     response: String,
     #[serde(default)]
-    choices: Vec<Choice>,
+    error: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: Option<Message>,
-    #[serde(default)]
-    index: i32,
+/// Solicitud a una API compatible con OpenAI
+#[derive(Debug, Serialize)]
+struct OpenAiRequest {
+    model: String,
+    messages: Vec<OpenAiMessage>,
+    temperature: f32,
+    max_tokens: Option<u32>,
+    stream: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct Message {
+/// Mensaje de una conversación de la API compatible con OpenAI
+#[derive(Debug, Serialize)]
+struct OpenAiMessage {
+    role: String,
     content: String,
 }
 
+/// Solicitud a `generateContent` de Gemini
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    system_instruction: GeminiContent,
+    generation_config: GeminiGenerationConfig,
+}
+
+/// Contenido de Gemini: un turno de la conversación dividido en partes
+#[derive(Debug, Serialize)]
+struct GeminiContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    parts: Vec<GeminiPart>,
+}
+
+/// Parte de texto de un contenido de Gemini
+#[derive(Debug, Serialize)]
+struct GeminiPart {
+    text: String,
+}
+
+/// Parámetros de generación de Gemini
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+}
+
+/// Instrucciones de sistema para los proveedores con API de chat
+const SYSTEM_PROMPT: &str = "Eres un asistente de seguridad informática con amplio conocimiento en análisis de comportamiento de procesos y detección de amenazas.";
+
+/// Modelo disponible en el endpoint configurado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Nombre a usar con --model
+    pub name: String,
+    /// Tamaño en bytes (si la API lo informa)
+    pub size: Option<u64>,
+    /// Familia del modelo (llama, mistral, ...)
+    pub family: Option<String>,
+    /// Número de parámetros (p. ej. "7B")
+    pub parameter_size: Option<String>,
+    /// Nivel de cuantización (p. ej. "Q4_0")
+    pub quantization: Option<String>,
+}
+
+/// Crear el cliente HTTP para el LLM con el timeout y el pool de conexiones de la configuración
+pub fn build_http_client(config: &LlmConfig) -> Result<Client> {
+    config.validate()?;
+    Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+        .build()
+        .context("Error creando cliente HTTP")
+}
+
+/// Direcciones a las que resuelve el endpoint del LLM, para no atribuir al proceso
+/// monitoreado el tráfico que genera ShadowTrace (vacío si la URL no se puede resolver)
+pub async fn endpoint_addrs(api_url: &str) -> Vec<SocketAddr> {
+    let Ok(url) = reqwest::Url::parse(api_url) else {
+        return Vec::new();
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Vec::new();
+    };
+    // host_str conserva los corchetes de las direcciones IPv6
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let resolved = tokio::net::lookup_host((host, port)).await;
+    match resolved {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => {
+            tracing::debug!("No se pudo resolver el endpoint LLM {}: {}", host, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Futuro que devuelven los backends de LLM
+pub type LlmFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Transporte HTTP común a los backends: envía las peticiones, las registra con
+/// `--debug-llm` y convierte las respuestas de error en `LlmHttpError`
+#[derive(Clone)]
+pub struct LlmHttp {
+    client: Client,
+    debug_requests: bool,
+}
+
+impl LlmHttp {
+    /// Transporte sobre un cliente HTTP (ver `build_http_client`)
+    pub fn new(client: Client, debug_requests: bool) -> Self {
+        Self { client, debug_requests }
+    }
+
+    /// Cliente HTTP para construir las peticiones
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Realizar una petición GET y decodificar la respuesta JSON
+    pub async fn get_json(&self, url: &str) -> Result<Value> {
+        self.send_json(self.client.get(url)).await
+    }
+
+    /// Enviar una petición y decodificar la respuesta JSON.
+    /// Con `debug_requests` registra la petición y la respuesta en bruto, redactadas y recortadas.
+    pub async fn send_json(&self, request: RequestBuilder) -> Result<Value> {
+        let (url, response) = self.send(request).await?;
+        let status = response.status();
+        let body = response.text()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("Respuesta inválida de {}", url))?;
+        self.log_response(status, &url, &body);
+        
+        if !status.is_success() {
+            return Err(LlmHttpError { url, status, body }.into());
+        }
+        serde_json::from_str(&body).with_context(|| format!("Respuesta inválida de {}", url))
+    }
+
+    /// Enviar una petición cuya respuesta llega por fragmentos y pasar a `on_line` cada
+    /// línea no vacía según llega (JSON por línea de Ollama, eventos `data:` de OpenAI)
+    pub async fn send_stream(
+        &self,
+        request: RequestBuilder,
+        mut on_line: impl FnMut(&str) -> Result<()>,
+    ) -> Result<()> {
+        let (url, mut response) = self.send(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            self.log_response(status, &url, &body);
+            return Err(LlmHttpError { url, status, body }.into());
+        }
+        if self.debug_requests {
+            tracing::debug!(target: DEBUG_LOG_TARGET, "<- {} {} (por fragmentos)", status, url);
+        }
+        
+        // Las líneas se separan sobre los bytes para no partir caracteres entre fragmentos
+        let mut pending = Vec::new();
+        while let Some(chunk) = response.chunk()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("Respuesta inválida de {}", url))?
+        {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if !line.trim().is_empty() {
+                    on_line(line.trim())?;
+                }
+            }
+        }
+        let rest = String::from_utf8_lossy(&pending);
+        if !rest.trim().is_empty() {
+            on_line(rest.trim())?;
+        }
+        Ok(())
+    }
+
+    /// Construir, registrar y enviar una petición. La URL que se devuelve para los
+    /// mensajes no lleva la clave de API (`?key=` de Gemini)
+    async fn send(&self, request: RequestBuilder) -> Result<(String, reqwest::Response)> {
+        let request = request.build().context("Error construyendo la petición al LLM")?;
+        let url = display_url(request.url());
+        if self.debug_requests {
+            log_request(&request);
+        }
+        
+        let response = self.client.execute(request)
+            .await
+            .map_err(reqwest::Error::without_url)
+            .with_context(|| format!("No se pudo conectar con el endpoint LLM {}", url))?;
+        Ok((url, response))
+    }
+
+    /// Registrar una respuesta completa con `--debug-llm`
+    fn log_response(&self, status: reqwest::StatusCode, url: &str, body: &str) {
+        if self.debug_requests {
+            tracing::debug!(
+                target: DEBUG_LOG_TARGET,
+                "<- {} {}\n{}",
+                status,
+                url,
+                truncate_for_log(&debug_redact_body(body.as_bytes()))
+            );
+        }
+    }
+}
+
+/// URL para mensajes y registros, con el valor del parámetro `key` redactado
+fn display_url(url: &reqwest::Url) -> String {
+    if !url.query_pairs().any(|(name, _)| name == "key") {
+        return url.to_string();
+    }
+    let mut display = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "key" { REDACTED.to_string() } else { value.to_string() };
+            (name.to_string(), value)
+        })
+        .collect();
+    display.query_pairs_mut().clear().extend_pairs(pairs);
+    display.to_string()
+}
+
+/// API de un proveedor de LLM. `LlmClient` construye los prompts, prueba los modelos de
+/// respaldo y repite con el contexto reducido; el backend solo habla con el servidor.
+/// Un proveedor nuevo implementa este trait y se elige en `LlmProvider::backend`
+pub trait LlmBackend: Send + Sync {
+    /// Nombre del proveedor para los registros
+    fn name(&self) -> &'static str;
+
+    /// Generar la respuesta completa de `model` a un prompt
+    fn generate<'a>(&'a self, model: &'a str, prompt: &'a str) -> LlmFuture<'a, String>;
+
+    /// Generar la respuesta pasando a `on_chunk` cada fragmento según llega; devuelve el
+    /// texto completo. Por defecto espera a la respuesta completa y la pasa de una vez
+    fn generate_stream<'a>(
+        &'a self,
+        model: &'a str,
+        prompt: &'a str,
+        on_chunk: &'a mut (dyn FnMut(&str) + Send),
+    ) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let response = self.generate(model, prompt).await?;
+            on_chunk(&response);
+            Ok(response)
+        })
+    }
+
+    /// Listar los modelos disponibles en el endpoint
+    fn list_models(&self) -> LlmFuture<'_, Vec<ModelInfo>>;
+}
+
+impl LlmProvider {
+    /// Backend que implementa la API del proveedor
+    pub fn backend(&self, config: &LlmConfig, client: Client) -> Box<dyn LlmBackend> {
+        let http = LlmHttp::new(client, config.debug_requests);
+        match self {
+            LlmProvider::Ollama => Box::new(OllamaBackend {
+                http,
+                api_url: config.api_url.clone(),
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+                num_ctx: config.num_ctx,
+            }),
+            LlmProvider::OpenAiCompatible => Box::new(OpenAiBackend {
+                http,
+                api_url: config.api_url.clone(),
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+            }),
+            LlmProvider::Gemini => Box::new(GeminiBackend {
+                http,
+                api_url: config.api_url.clone(),
+                api_key: config.api_key.clone().unwrap_or_default(),
+                api_key_placement: config.api_key_placement,
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+            }),
+        }
+    }
+}
+
+/// API nativa de Ollama (`/api/generate`, `/api/tags`)
+pub struct OllamaBackend {
+    http: LlmHttp,
+    api_url: String,
+    temperature: f32,
+    max_tokens: Option<u32>,
+    num_ctx: Option<u32>,
+}
+
+impl OllamaBackend {
+    /// Solicitud de generación
+    fn request(&self, model: &str, prompt: &str, stream: bool) -> OllamaRequest {
+        OllamaRequest {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            stream,
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            options: self.num_ctx.map(|num_ctx| serde_json::json!({ "num_ctx": num_ctx })),
+        }
+    }
+}
+
+impl LlmBackend for OllamaBackend {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn generate<'a>(&'a self, model: &'a str, prompt: &'a str) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = LlmProvider::Ollama.generate_url(&self.api_url);
+            let request = self.request(model, prompt, false);
+            let response: OllamaResponse = serde_json::from_value(
+                self.http.send_json(self.http.client().post(&url).json(&request)).await?
+            )?;
+            
+            if response.response.is_empty() {
+                return Err(anyhow::anyhow!("No se pudo obtener respuesta del LLM"));
+            }
+            Ok(response.response)
+        })
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        model: &'a str,
+        prompt: &'a str,
+        on_chunk: &'a mut (dyn FnMut(&str) + Send),
+    ) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = LlmProvider::Ollama.generate_url(&self.api_url);
+            let request = self.request(model, prompt, true);
+            let mut text = String::new();
+            self.http.send_stream(self.http.client().post(&url).json(&request), |line| {
+                let response: OllamaResponse = serde_json::from_str(line)
+                    .with_context(|| format!("Respuesta inválida de {}", url))?;
+                if let Some(error) = response.error {
+                    return Err(anyhow::anyhow!("El LLM devolvió un error: {}", error));
+                }
+                if !response.response.is_empty() {
+                    on_chunk(&response.response);
+                    text.push_str(&response.response);
+                }
+                Ok(())
+            }).await?;
+            
+            if text.is_empty() {
+                return Err(anyhow::anyhow!("No se pudo obtener respuesta del LLM"));
+            }
+            Ok(text)
+        })
+    }
+
+    fn list_models(&self) -> LlmFuture<'_, Vec<ModelInfo>> {
+        Box::pin(async move {
+            // GET /api/tags
+            let url = LlmProvider::Ollama.models_url(&self.api_url);
+            let response = self.http.get_json(&url).await?;
+            let models = response["models"]
+                .as_array()
+                .context("Respuesta inesperada al listar modelos")?
+                .iter()
+                .filter_map(|model| {
+                    let details = &model["details"];
+                    Some(ModelInfo {
+                        name: model["name"].as_str()?.to_string(),
+                        size: model["size"].as_u64(),
+                        family: details["family"].as_str().map(String::from),
+                        parameter_size: details["parameter_size"].as_str().map(String::from),
+                        quantization: details["quantization_level"].as_str().map(String::from),
+                    })
+                })
+                .collect();
+            Ok(models)
+        })
+    }
+}
+
+/// API compatible con OpenAI (`/v1/chat/completions`, `/v1/models`): vLLM, llama.cpp,
+/// LM Studio...
+pub struct OpenAiBackend {
+    http: LlmHttp,
+    api_url: String,
+    temperature: f32,
+    max_tokens: Option<u32>,
+}
+
+impl OpenAiBackend {
+    /// Solicitud de chat con las instrucciones de sistema y el prompt
+    fn request(&self, model: &str, prompt: &str, stream: bool) -> OpenAiRequest {
+        OpenAiRequest {
+            model: model.to_string(),
+            messages: vec![
+                OpenAiMessage {
+                    role: "system".to_string(),
+                    content: SYSTEM_PROMPT.to_string(),
+                },
+                OpenAiMessage {
+                    role: "user".to_string(),
+                    content: prompt.to_string(),
+                },
+            ],
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            stream,
+        }
+    }
+}
+
+impl LlmBackend for OpenAiBackend {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn generate<'a>(&'a self, model: &'a str, prompt: &'a str) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = LlmProvider::OpenAiCompatible.generate_url(&self.api_url);
+            let request = self.request(model, prompt, false);
+            let response = self.http.send_json(self.http.client().post(&url).json(&request)).await?;
+            
+            // Extraer el texto de la respuesta (estructura típica de una API OpenAI)
+            let content = response["choices"][0]["message"]["content"]
+                .as_str()
+                .context("No se pudo extraer el contenido de la respuesta")?;
+            Ok(content.to_string())
+        })
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        model: &'a str,
+        prompt: &'a str,
+        on_chunk: &'a mut (dyn FnMut(&str) + Send),
+    ) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = LlmProvider::OpenAiCompatible.generate_url(&self.api_url);
+            let request = self.request(model, prompt, true);
+            let mut text = String::new();
+            // Eventos "data: {...}" con el texto nuevo en choices[0].delta.content
+            self.http.send_stream(self.http.client().post(&url).json(&request), |line| {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    return Ok(());
+                };
+                if data == "[DONE]" {
+                    return Ok(());
+                }
+                let event: Value = serde_json::from_str(data)
+                    .with_context(|| format!("Respuesta inválida de {}", url))?;
+                if let Some(error) = event["error"]["message"].as_str() {
+                    return Err(anyhow::anyhow!("El LLM devolvió un error: {}", error));
+                }
+                if let Some(piece) = event["choices"][0]["delta"]["content"].as_str().filter(|piece| !piece.is_empty()) {
+                    on_chunk(piece);
+                    text.push_str(piece);
+                }
+                Ok(())
+            }).await?;
+            
+            if text.is_empty() {
+                return Err(anyhow::anyhow!("No se pudo extraer el contenido de la respuesta"));
+            }
+            Ok(text)
+        })
+    }
+
+    fn list_models(&self) -> LlmFuture<'_, Vec<ModelInfo>> {
+        Box::pin(async move {
+            // GET /v1/models
+            let url = LlmProvider::OpenAiCompatible.models_url(&self.api_url);
+            let response = self.http.get_json(&url).await?;
+            let models = response["data"]
+                .as_array()
+                .context("Respuesta inesperada al listar modelos")?
+                .iter()
+                .filter_map(|model| model["id"].as_str())
+                .map(|id| ModelInfo {
+                    name: id.to_string(),
+                    size: None,
+                    family: None,
+                    parameter_size: None,
+                    quantization: None,
+                })
+                .collect();
+            Ok(models)
+        })
+    }
+}
+
+/// API de Google Gemini (`models/{modelo}:generateContent`, `models`)
+pub struct GeminiBackend {
+    http: LlmHttp,
+    api_url: String,
+    api_key: String,
+    api_key_placement: ApiKeyPlacement,
+    temperature: f32,
+    max_tokens: Option<u32>,
+}
+
+impl GeminiBackend {
+    /// Solicitud con el prompt como único turno del usuario
+    fn request(&self, prompt: &str) -> GeminiRequest {
+        GeminiRequest {
+            contents: vec![GeminiContent {
+                role: Some("user".to_string()),
+                parts: vec![GeminiPart { text: prompt.to_string() }],
+            }],
+            system_instruction: GeminiContent {
+                role: None,
+                parts: vec![GeminiPart { text: SYSTEM_PROMPT.to_string() }],
+            },
+            generation_config: GeminiGenerationConfig {
+                temperature: self.temperature,
+                max_output_tokens: self.max_tokens,
+            },
+        }
+    }
+
+    /// Añadir la clave de API en la cabecera `x-goog-api-key` o en el parámetro `key`
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.api_key_placement {
+            ApiKeyPlacement::Header => request.header("x-goog-api-key", &self.api_key),
+            ApiKeyPlacement::Query => request.query(&[("key", &self.api_key)]),
+        }
+    }
+}
+
+/// Texto de una respuesta de Gemini: las partes del primer candidato. `None` si la
+/// respuesta no trae texto (por ejemplo, el último evento de un stream)
+fn gemini_text(response: &Value) -> Result<Option<String>> {
+    if let Some(error) = response["error"]["message"].as_str() {
+        return Err(anyhow::anyhow!("El LLM devolvió un error: {}", error));
+    }
+    if let Some(reason) = response["promptFeedback"]["blockReason"].as_str() {
+        return Err(anyhow::anyhow!("Gemini bloqueó el prompt: {}", reason));
+    }
+    let text: String = response["candidates"][0]["content"]["parts"]
+        .as_array()
+        .map(|parts| parts.iter().filter_map(|part| part["text"].as_str()).collect())
+        .unwrap_or_default();
+    Ok(Some(text).filter(|text| !text.is_empty()))
+}
+
+impl LlmBackend for GeminiBackend {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn generate<'a>(&'a self, model: &'a str, prompt: &'a str) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = gemini_model_url(&self.api_url, model, "generateContent");
+            let request = self.authorize(self.http.client().post(&url)).json(&self.request(prompt));
+            let response = self.http.send_json(request).await?;
+            
+            match gemini_text(&response)? {
+                Some(text) => Ok(text),
+                None => Err(anyhow::anyhow!(
+                    "No se pudo extraer el contenido de la respuesta (finishReason: {})",
+                    response["candidates"][0]["finishReason"].as_str().unwrap_or("desconocido")
+                )),
+            }
+        })
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        model: &'a str,
+        prompt: &'a str,
+        on_chunk: &'a mut (dyn FnMut(&str) + Send),
+    ) -> LlmFuture<'a, String> {
+        Box::pin(async move {
+            let url = gemini_model_url(&self.api_url, model, "streamGenerateContent");
+            let request = self.authorize(self.http.client().post(&url))
+                .query(&[("alt", "sse")])
+                .json(&self.request(prompt));
+            let mut text = String::new();
+            // Eventos "data: {...}", cada uno con una respuesta parcial completa
+            self.http.send_stream(request, |line| {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    return Ok(());
+                };
+                let event: Value = serde_json::from_str(data)
+                    .with_context(|| format!("Respuesta inválida de {}", url))?;
+                if let Some(piece) = gemini_text(&event)? {
+                    on_chunk(&piece);
+                    text.push_str(&piece);
+                }
+                Ok(())
+            }).await?;
+            
+            if text.is_empty() {
+                return Err(anyhow::anyhow!("No se pudo extraer el contenido de la respuesta"));
+            }
+            Ok(text)
+        })
+    }
+
+    fn list_models(&self) -> LlmFuture<'_, Vec<ModelInfo>> {
+        Box::pin(async move {
+            // GET /v1beta/models, solo los que admiten generateContent
+            let url = LlmProvider::Gemini.models_url(&self.api_url);
+            let response = self.http.send_json(self.authorize(self.http.client().get(&url))).await?;
+            let models = response["models"]
+                .as_array()
+                .context("Respuesta inesperada al listar modelos")?
+                .iter()
+                .filter(|model| {
+                    model["supportedGenerationMethods"]
+                        .as_array()
+                        .filter(|methods| !methods.iter().any(|method| method == "generateContent"))
+                        .is_none()
+                })
+                .filter_map(|model| {
+                    Some(ModelInfo {
+                        name: model["name"].as_str()?.trim_start_matches("models/").to_string(),
+                        size: None,
+                        family: None,
+                        parameter_size: None,
+                        quantization: None,
+                    })
+                })
+                .collect();
+            Ok(models)
+        })
+    }
+}
+
 /// Cliente para interactuar con el LLM
 pub struct LlmClient {
     config: LlmConfig,
-    client: Client,
+    /// API del proveedor configurado
+    backend: Box<dyn LlmBackend>,
+    /// Modelo que respondió la última petición (el principal o uno de respaldo)
+    answered_by: Mutex<Option<String>>,
+    /// El último análisis completo se repitió con el contexto reducido
+    reduced_context: AtomicBool,
 }
 
 impl LlmClient {
     /// Crear un nuevo cliente LLM con la configuración especificada
     pub fn new(config: LlmConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds))
-            .build()
-            .context("Error creando cliente HTTP")?;
-        
-        Ok(Self { config, client })
+        let client = build_http_client(&config)?;
+        Self::with_http_client(config, client)
+    }
+    
+    /// Crear un cliente LLM sobre un cliente HTTP ya creado (ver `build_http_client`).
+    /// Los clones de `reqwest::Client` comparten el pool de conexiones, así que varios
+    /// clientes LLM pueden reutilizar las mismas conexiones con el endpoint.
+    pub fn with_http_client(config: LlmConfig, client: Client) -> Result<Self> {
+        config.validate()?;
+        let backend = config.provider.backend(&config, client);
+        Ok(Self {
+            config,
+            backend,
+            answered_by: Mutex::new(None),
+            reduced_context: AtomicBool::new(false),
+        })
+    }
+    
+    /// Modelo que respondió la última petición
+    pub fn answered_by(&self) -> Option<String> {
+        self.answered_by.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
+    /// Indica si la última petición la respondió un modelo de respaldo
+    pub fn used_fallback(&self) -> bool {
+        self.answered_by().is_some_and(|model| model != self.config.model)
+    }
+    
+    /// Indica si el último análisis completo se hizo solo con estadísticas porque
+    /// el prompt no cabía en la ventana de contexto del modelo
+    pub fn used_reduced_context(&self) -> bool {
+        self.reduced_context.load(Ordering::Relaxed)
+    }
+    
+    /// Listar los modelos disponibles en el endpoint configurado
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        self.backend.list_models().await
     }
     
     /// Analizar un proceso basado en información recopilada
@@ -111,7 +1149,8 @@ impl LlmClient {
         let prompt = format!(
             "Actúa como un analista de seguridad. Revisa los siguientes eventos de acceso \
             a archivos de un proceso y determina patrones, intenciones, y posibles comportamientos \
-            maliciosos. Señala cualquier actividad que parezca inusual o sospechosa.\n\n\
+            maliciosos. Señala cualquier actividad que parezca inusual o sospechosa. El campo \
+            `entropy` (0-8 bits por byte) cercano a 8 indica datos cifrados o comprimidos.\n\n\
             Eventos de archivo:\n{}", 
             serde_json::to_string_pretty(&file_events)?
         );
@@ -133,150 +1172,652 @@ impl LlmClient {
         self.generate_response(&prompt).await
     }
     
-    /// Provee un análisis completo de un proceso
-    pub async fn comprehensive_analysis(
-        &self, 
-        process_info: serde_json::Value,
-        file_events: serde_json::Value,
-        network_events: serde_json::Value
+    /// Analiza el resultado del análisis estático de un binario
+    pub async fn analyze_static_binary(&self, analysis: serde_json::Value) -> Result<String> {
+        let prompt = format!(
+            "Actúa como un analista de malware. Examina el siguiente análisis estático de un binario \
+            (bibliotecas importadas, símbolos, secciones con su entropía y cadenas embebidas) sin que \
+            haya sido ejecutado. Indica qué capacidades parece tener, si hay señales de empaquetado, \
+            ofuscación, inyección de código o comunicación con servidores externos, y si conviene \
+            ejecutarlo bajo monitoreo.\n\n\
+            {}\
+            Análisis estático:\n{}",
+            analyst_notes_section(&self.config.analyst_notes),
+            serde_json::to_string_pretty(&analysis)?
+        );
+        
+        self.generate_response(&prompt).await
+    }
+    
+    /// Explica un único hallazgo y cómo remediarlo. El prompt lleva solo el hallazgo y
+    /// un contexto mínimo (proceso y riesgo del reporte), mucho más pequeño que el del
+    /// análisis completo
+    pub async fn explain_finding(&self, finding: &Finding, context: serde_json::Value) -> Result<String> {
+        let prompt = format!(
+            "Actúa como un analista de seguridad con experiencia. ShadowTrace detectó el \
+            siguiente hallazgo al monitorear un proceso. Explica en pocas frases qué significa, \
+            por qué puede ser peligroso y en qué casos sería un falso positivo. Después indica \
+            los pasos concretos para investigarlo y remediarlo, de más a menos urgente. \
+            Responde en Markdown breve, sin repetir el hallazgo.\n\n\
+            Hallazgo:\n{}\n\n\
+            Contexto:\n{}",
+            serde_json::to_string_pretty(finding)?,
+            serde_json::to_string_pretty(&context)?
+        );
+        
+        self.generate_response(&prompt).await
+    }
+    
+    /// Analiza la ejecución de un binario a partir de sus llamadas al sistema
+    pub async fn analyze_syscalls(&self, syscalls: serde_json::Value) -> Result<String> {
+        let prompt = format!(
+            "Actúa como un analista de malware. Examina el resumen de llamadas al sistema de un binario \
+            ejecutado bajo strace (recuentos por llamada y por categoría: archivos, red, procesos, IPC, \
+            memoria). Indica qué hace el programa, si crea procesos, se comunica por red o usa llamadas \
+            propias de inyección de código, depuración o rootkits, y si su comportamiento es sospechoso.\n\n\
+            {}\
+            Llamadas al sistema:\n{}",
+            analyst_notes_section(&self.config.analyst_notes),
+            serde_json::to_string_pretty(&syscalls)?
+        );
+        
+        self.generate_response(&prompt).await
+    }
+    
+    /// Responde una pregunta de seguimiento sobre un análisis previo
+    pub async fn follow_up(
+        &self,
+        context: serde_json::Value,
+        previous_analysis: &str,
+        conversation: &[(String, String)],
+        question: &str,
     ) -> Result<String> {
-        // Combinar toda la información en un solo objeto JSON
-        let mut combined = serde_json::Map::new();
-        combined.insert("process".to_string(), process_info);
-        combined.insert("file_activity".to_string(), file_events);
-        combined.insert("network_activity".to_string(), network_events);
+        let mut history = String::new();
+        for (previous_question, answer) in conversation {
+            history.push_str(&format!("Pregunta: {}\nRespuesta: {}\n\n", previous_question, answer));
+        }
         
         let prompt = format!(
-            "Eres un modelo de lenguaje. No necesitas capacidades de ejecución para hacer análisis útil.
-
-A continuación te paso un reporte generado por un sistema llamado ShadowTrace. El reporte contiene texto estructurado con información de procesos, archivos accedidos y conexiones de red. No necesitas analizar archivos ni ejecutar código, solo interpretar lo que ya está escrito.
-
-Tu tarea es:
-	• Leer el reporte y explicar en lenguaje humano técnico qué está haciendo el proceso monitoreado.
-	• Comentar si el comportamiento es típico o sospechoso.
-	• Hacer hipótesis razonables sobre el propósito del proceso basándote en los datos observados (ej. si parece un navegador, si hay persistencia, si hay tráfico de red anómalo, etc.).
-	• Puedes mencionar si algo parece malicioso, sospechoso o simplemente automatizado.
-	• Tu análisis puede ser especulativo, pero bien fundamentado.
-	• NO digas que no puedes hacer el análisis. NO digas que eres solo un modelo de lenguaje. Ya lo sabemos. Estás aquí para interpretar texto. Hazlo como si fueras un analista de seguridad con experiencia.
-
-Aquí está el reporte:\n{}", 
-            serde_json::to_string_pretty(&Value::Object(combined))?
+            "Actúa como un analista de seguridad con experiencia. Ya analizaste un proceso monitoreado \
+            por ShadowTrace; a continuación tienes los datos originales, tu análisis y las preguntas de \
+            seguimiento anteriores. Responde la nueva pregunta centrándote en lo que se pide y \
+            apoyándote en los datos observados.\n\n\
+            Datos observados:\n{}\n\n\
+            Análisis previo:\n{}\n\n\
+            {}\
+            {}\
+            Nueva pregunta: {}",
+            serde_json::to_string_pretty(&context)?,
+            previous_analysis,
+            analyst_notes_section(&self.config.analyst_notes),
+            history,
+            question
         );
         
         self.generate_response(&prompt).await
     }
     
-    /// Generar una respuesta basada en el prompt
-    pub async fn generate_response(&self, prompt: &str) -> Result<String> {
-        match self.config.provider {
-            LlmProvider::Ollama => self.generate_ollama_response(prompt).await,
-            LlmProvider::OpenAiCompatible => self.generate_openai_compatible_response(prompt).await,
+    /// Provee un análisis completo de un proceso.
+    ///
+    /// Si el modelo rechaza el prompt por exceder su ventana de contexto, se repite una
+    /// vez solo con estadísticas (como `--depth shallow`); `used_reduced_context()` lo indica.
+    pub async fn comprehensive_analysis(
+        &self, 
+        process_info: serde_json::Value,
+        file_events: serde_json::Value,
+        network_events: serde_json::Value
+    ) -> Result<String> {
+        self.reduced_context.store(false, Ordering::Relaxed);
+        let prompt = comprehensive_prompt(&self.config, process_info.clone(), file_events.clone(), network_events.clone())?;
+        match self.generate_response(&prompt).await {
+            Err(e) if self.config.retry_reduced_context && is_context_overflow(&e) => {
+                tracing::warn!("El prompt no cabe en la ventana de contexto del modelo ({}); reintentando solo con estadísticas", e);
+                let prompt = comprehensive_prompt(
+                    &self.config,
+                    reduce_process_info(process_info),
+                    AnalysisDepth::reduce_to_shallow(file_events, "operation"),
+                    AnalysisDepth::reduce_to_shallow(network_events, "remote_addr"),
+                )?;
+                let analysis = self.generate_response(&prompt)
+                    .await
+                    .context("El análisis con el contexto reducido también falló")?;
+                self.reduced_context.store(true, Ordering::Relaxed);
+                Ok(analysis)
+            }
+            result => result,
         }
     }
     
-    /// Generar una respuesta utilizando Ollama
-    async fn generate_ollama_response(&self, prompt: &str) -> Result<String> {
-        let request = if self.config.api_url.contains("/v1/chat/completions") {
-            // Formato compatible con OpenAI
-            let openai_request = serde_json::json!({
-                "model": self.config.model.clone(),
-                "messages": [
-                    {
-                        "role": "system",
-                        "content": "Eres un asistente de seguridad informática con amplio conocimiento en análisis de comportamiento de procesos y detección de amenazas."
-                    },
-                    {
-                        "role": "user",
-                        "content": prompt
-                    }
-                ],
-                "temperature": self.config.temperature,
-                "max_tokens": self.config.max_tokens,
-            });
-            openai_request
-        } else {
-            // Formato de Ollama
-            let ollama_request = OllamaRequest {
-                model: self.config.model.clone(),
-                prompt: prompt.to_string(),
-                temperature: self.config.temperature,
-                max_tokens: self.config.max_tokens,
-            };
-            serde_json::to_value(ollama_request)?
-        };
-        
-        // Determinar la URL correcta: si ya contiene un endpoint específico, usarla directamente
-        let url = if self.config.api_url.contains("/v1/chat/completions") 
-                  || self.config.api_url.contains("/generate") {
-            self.config.api_url.clone()
-        } else {
-            format!("{}/generate", self.config.api_url)
+    /// Tokens estimados del análisis completo, si superan la ventana de contexto configurada
+    pub fn comprehensive_context_overflow(
+        &self,
+        process_info: &serde_json::Value,
+        file_events: &serde_json::Value,
+        network_events: &serde_json::Value,
+    ) -> Result<Option<usize>> {
+        let Some(num_ctx) = self.config.num_ctx else {
+            return Ok(None);
         };
+        let prompt = comprehensive_prompt(&self.config, process_info.clone(), file_events.clone(), network_events.clone())?;
+        let estimated = estimate_tokens(&prompt);
+        Ok((estimated > num_ctx as usize).then_some(estimated))
+    }
+    
+    /// Generar una respuesta basada en el prompt.
+    ///
+    /// Si el modelo principal no está disponible (no encontrado, no cargado o error 5xx)
+    /// prueba los modelos de respaldo en orden; `answered_by()` indica cuál respondió.
+    pub async fn generate_response(&self, prompt: &str) -> Result<String> {
+        self.generate_with_fallback(prompt, None).await
+    }
+    
+    /// Igual que `generate_response`, pero pasa a `on_chunk` cada fragmento de la
+    /// respuesta según llega del servidor
+    pub async fn generate_response_stream(
+        &self,
+        prompt: &str,
+        on_chunk: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String> {
+        self.generate_with_fallback(prompt, Some(on_chunk)).await
+    }
+    
+    /// Probar el modelo principal y después los de respaldo hasta que uno responda
+    async fn generate_with_fallback(
+        &self,
+        prompt: &str,
+        mut on_chunk: Option<&mut (dyn FnMut(&str) + Send)>,
+    ) -> Result<String> {
+        let models: Vec<&str> = std::iter::once(self.config.model.as_str())
+            .chain(self.config.fallback_models.iter().map(String::as_str))
+            .collect();
         
-        let response = self.client.post(&url)
-            .json(&request)
-            .send()
-            .await?
-            .json::<OllamaResponse>()
-            .await?;
-            
-        // Obtener la respuesta del formato correcto
-        if !response.response.is_empty() {
-            // Es una respuesta de Ollama
-            Ok(response.response)
-        } else if !response.choices.is_empty() && response.choices[0].message.is_some() {
-            // Es una respuesta de OpenAI
-            Ok(response.choices[0].message.as_ref().unwrap().content.clone())
-        } else {
-            Err(anyhow::anyhow!("No se pudo obtener respuesta del LLM"))
+        *self.answered_by.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let mut last_error = None;
+        for (index, model) in models.iter().enumerate() {
+            let result = match on_chunk.as_deref_mut() {
+                Some(on_chunk) => self.backend.generate_stream(model, prompt, on_chunk).await,
+                None => self.backend.generate(model, prompt).await,
+            };
+            match result {
+                Ok(response) => {
+                    if index > 0 {
+                        tracing::info!("Respondió el modelo de respaldo {}", model);
+                    } else {
+                        tracing::debug!("Respondió el modelo {} ({})", model, self.backend.name());
+                    }
+                    *self.answered_by.lock().unwrap_or_else(|e| e.into_inner()) = Some(model.to_string());
+                    return Ok(response);
+                }
+                Err(e) => {
+                    let unavailable = e
+                        .downcast_ref::<LlmHttpError>()
+                        .is_some_and(LlmHttpError::is_model_unavailable);
+                    if !unavailable {
+                        return Err(e);
+                    }
+                    if let Some(next) = models.get(index + 1) {
+                        tracing::warn!("Modelo {} no disponible ({}); probando {}", model, e, next);
+                    }
+                    last_error = Some(e);
+                }
+            }
         }
+        
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("No hay modelos configurados"))
+            .context(format!("Ningún modelo disponible ({})", models.join(", "))))
     }
+}
+
+/// Registrar método, URL, cabeceras y cuerpo de una petición al LLM
+fn log_request(request: &reqwest::Request) {
+    let redactor = Redactor::default();
+    let headers: Vec<String> = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive_header(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                redactor.redact(&String::from_utf8_lossy(value.as_bytes()))
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(debug_redact_body)
+        .unwrap_or_default();
     
-    /// Generar una respuesta utilizando una API compatible con OpenAI
-    async fn generate_openai_compatible_response(&self, prompt: &str) -> Result<String> {
-        // Estructura para API compatible con OpenAI
-        #[derive(Serialize)]
-        struct OpenAiRequest {
-            model: String,
-            messages: Vec<Message>,
-            temperature: f32,
-            max_tokens: Option<u32>,
+    tracing::debug!(
+        target: DEBUG_LOG_TARGET,
+        "-> {} {}\n{}\n{}",
+        request.method(),
+        redactor.redact(&display_url(request.url())),
+        headers.join("\n"),
+        truncate_for_log(&body)
+    );
+}
+
+/// Cabeceras de autenticación cuyo valor nunca se registra
+fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "authorization"
+        || name == "proxy-authorization"
+        || name == "cookie"
+        || is_sensitive_key(&name, DEFAULT_SENSITIVE_KEYS)
+}
+
+/// Redactar un cuerpo para el registro de depuración. Si es JSON, además de los secretos
+/// en los textos oculta los valores de claves sensibles (`"api_key": "..."`)
+fn debug_redact_body(body: &[u8]) -> String {
+    let redactor = Redactor::default();
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_sensitive_fields(&mut value);
+            redactor.redact_value(&mut value);
+            value.to_string()
         }
-        
-        #[derive(Serialize)]
-        struct Message {
-            role: String,
-            content: String,
+        Err(_) => redactor.redact(&String::from_utf8_lossy(body)),
+    }
+}
+
+/// Sustituir los valores de texto de las claves sensibles de un JSON
+fn redact_sensitive_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if field.is_string() && is_sensitive_key(key, DEFAULT_SENSITIVE_KEYS) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_sensitive_fields(field);
+                }
+            }
         }
-        
-        let request = OpenAiRequest {
-            model: self.config.model.clone(),
-            messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: "Eres un asistente de seguridad informática con amplio conocimiento en análisis de comportamiento de procesos y detección de amenazas.".to_string(),
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                },
-            ],
-            temperature: self.config.temperature,
-            max_tokens: self.config.max_tokens,
+        Value::Array(items) => items.iter_mut().for_each(redact_sensitive_fields),
+        _ => {}
+    }
+}
+
+/// Recortar un texto a `DEBUG_BODY_LIMIT` bytes sin partir caracteres
+fn truncate_for_log(text: &str) -> String {
+    if text.len() <= DEBUG_BODY_LIMIT {
+        return text.to_string();
+    }
+    let mut end = DEBUG_BODY_LIMIT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… ({} bytes más)", &text[..end], text.len() - end)
+}
+
+/// Quitar de los datos del proceso lo que ocupa más contexto y no es agregado:
+/// las líneas de los registros del sistema (se conservan los recuentos)
+fn reduce_process_info(mut process_info: serde_json::Value) -> serde_json::Value {
+    if let Some(logs) = process_info.get_mut("system_logs").and_then(Value::as_object_mut) {
+        logs.remove("lines");
+    }
+    process_info
+}
+
+/// Prompt del análisis completo de un proceso a partir de la plantilla configurada
+fn comprehensive_prompt(
+    config: &LlmConfig,
+    process_info: serde_json::Value,
+    file_events: serde_json::Value,
+    network_events: serde_json::Value,
+) -> Result<String> {
+    let prompt = config.prompt_template.render(
+        &serde_json::to_string_pretty(&process_info)?,
+        &serde_json::to_string_pretty(&file_events)?,
+        &serde_json::to_string_pretty(&network_events)?,
+    );
+    Ok(match analyst_notes_section(&config.analyst_notes) {
+        notes if notes.is_empty() => prompt,
+        notes => format!("{}\n\n{}", prompt.trim_end(), notes.trim_end()),
+    })
+}
+
+/// Sección del prompt con las notas del analista (vacía si no hay). Se presentan como
+/// contexto externo: el modelo debe tenerlas en cuenta sin darlas por confirmadas
+fn analyst_notes_section(notes: &[String]) -> String {
+    if notes.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from(
+        "Notas del analista (contexto que ShadowTrace no observa; tenlo en cuenta al razonar, \
+        relaciónalo con los datos cuando proceda y no lo des por confirmado):\n"
+    );
+    for note in notes {
+        section.push_str(&format!("- {}\n", note));
+    }
+    section.push('\n');
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    #[test]
+    fn endpoints_use_each_providers_port_and_path() {
+        assert_eq!(LlmProvider::Ollama.endpoint(None), "http://localhost:11434/api");
+        assert_eq!(LlmProvider::Ollama.endpoint(Some("10.0.0.5")), "http://10.0.0.5:11434/api");
+        assert_eq!(LlmProvider::OpenAiCompatible.endpoint(Some("10.0.0.5")), "http://10.0.0.5:8000/v1/chat/completions");
+        assert_eq!(LlmProvider::OpenAiCompatible.endpoint(Some("gpu.lan:8080/")), "http://gpu.lan:8080/v1/chat/completions");
+        assert_eq!(LlmProvider::Ollama.endpoint(Some("https://gpu.lan")), "https://gpu.lan/api");
+        assert_eq!(LlmProvider::Ollama.endpoint(Some("::1")), "http://[::1]:11434/api");
+        assert_eq!(LlmProvider::Gemini.endpoint(None), "https://generativelanguage.googleapis.com/v1beta");
+        assert_eq!(LlmProvider::Gemini.endpoint(Some("  ")), "https://generativelanguage.googleapis.com/v1beta");
+    }
+
+    #[test]
+    fn generate_and_models_urls_follow_the_provider() {
+        let ollama = LlmProvider::Ollama;
+        assert_eq!(ollama.generate_url("http://localhost:11434/api"), "http://localhost:11434/api/generate");
+        assert_eq!(ollama.generate_url("http://localhost:11434/api/generate"), "http://localhost:11434/api/generate");
+        assert_eq!(ollama.models_url("http://localhost:11434/api/generate"), "http://localhost:11434/api/tags");
+
+        let openai = LlmProvider::OpenAiCompatible;
+        assert_eq!(openai.generate_url("http://h:8000"), "http://h:8000/v1/chat/completions");
+        assert_eq!(openai.generate_url("http://h:8000/v1/"), "http://h:8000/v1/chat/completions");
+        assert_eq!(openai.generate_url("http://h:8000/v1/chat/completions"), "http://h:8000/v1/chat/completions");
+        assert_eq!(openai.models_url("http://h:8000/v1/chat/completions"), "http://h:8000/v1/models");
+
+        let gemini = LlmProvider::Gemini;
+        let base = gemini.endpoint(None);
+        assert_eq!(gemini.generate_url(&base), format!("{}/models", base));
+        assert_eq!(gemini.models_url(&format!("{}/models/gemini-1.5-pro:generateContent", base)), format!("{}/models", base));
+        assert_eq!(
+            gemini_model_url(&base, "models/gemini-1.5-pro", "generateContent"),
+            format!("{}/models/gemini-1.5-pro:generateContent", base)
+        );
+    }
+
+    #[test]
+    fn provider_is_inferred_from_the_api_url() {
+        assert_eq!(LlmProvider::for_api_url("http://localhost:11434/api"), LlmProvider::Ollama);
+        assert_eq!(LlmProvider::for_api_url("http://h:8000/v1/chat/completions"), LlmProvider::OpenAiCompatible);
+        assert_eq!(LlmProvider::for_api_url(&LlmProvider::Gemini.endpoint(None)), LlmProvider::Gemini);
+    }
+
+    #[tokio::test]
+    async fn endpoint_addrs_resolve_host_and_default_port() {
+        assert_eq!(
+            endpoint_addrs("http://127.0.0.1:11434/api/generate").await,
+            vec!["127.0.0.1:11434".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            endpoint_addrs("https://[::1]/v1/chat/completions").await,
+            vec!["[::1]:443".parse::<SocketAddr>().unwrap()]
+        );
+        assert!(endpoint_addrs("no es una url").await.is_empty());
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected_with_specific_messages() {
+        let error = |config: LlmConfig| match config.validate() {
+            Err(AppError::ConfigurationError(message)) => message,
+            other => panic!("se esperaba un error de configuración: {:?}", other),
         };
-        
-        let response = self.client.post(&self.config.api_url)
-            .json(&request)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
-            
-        // Extraer el texto de la respuesta (estructura típica de una API OpenAI)
-        let content = response["choices"][0]["message"]["content"]
-            .as_str()
-            .context("No se pudo extraer el contenido de la respuesta")?;
-            
-        Ok(content.to_string())
+
+        assert!(LlmConfig::default().validate().is_ok());
+        assert!(error(LlmConfig { api_url: "localhost:11434".to_string(), ..LlmConfig::default() })
+            .contains("debe usar http o https"));
+        assert!(error(LlmConfig { api_url: "no es una url".to_string(), ..LlmConfig::default() })
+            .contains("no es válida"));
+        assert!(error(LlmConfig { model: " ".to_string(), ..LlmConfig::default() })
+            .contains("ningún modelo"));
+        assert!(error(LlmConfig { timeout_seconds: 0, ..LlmConfig::default() })
+            .contains("timeout del LLM"));
+        assert!(error(LlmConfig { temperature: 2.5, ..LlmConfig::default() })
+            .contains("temperatura"));
+        assert!(error(LlmConfig { temperature: -0.1, ..LlmConfig::default() })
+            .contains("temperatura"));
+        assert!(error(LlmConfig { max_tokens: Some(0), ..LlmConfig::default() })
+            .starts_with("max_tokens"));
+        assert!(error(LlmConfig { num_ctx: Some(0), ..LlmConfig::default() })
+            .starts_with("num_ctx"));
+        assert!(error(LlmConfig { pool_idle_timeout_seconds: 0, ..LlmConfig::default() })
+            .starts_with("pool_idle_timeout"));
+        assert!(error(LlmConfig { provider: LlmProvider::Gemini, api_key: Some(String::new()), ..LlmConfig::default() })
+            .contains("clave de API"));
+
+        // Proveedor y URL que no casan solo generan un aviso
+        let mismatched = LlmConfig { api_url: "http://localhost:11434/v1/chat/completions".to_string(), ..LlmConfig::default() };
+        assert_eq!(mismatched.provider, LlmProvider::Ollama);
+        assert!(mismatched.validate().is_ok());
     }
-} 
+
+    /// Peticiones que recibió el servidor simulado: (ruta, cuerpo JSON)
+    /// Petición recibida por `mock_server`
+    struct MockRequest {
+        /// Ruta con los parámetros
+        path: String,
+        /// Cabeceras (nombre en minúsculas, valor)
+        headers: Vec<(String, String)>,
+        /// Cuerpo JSON (Null si no lo había)
+        body: serde_json::Value,
+    }
+
+    type Received = Arc<Mutex<Vec<MockRequest>>>;
+
+    /// Servidor HTTP local que responde a cada petición con `respond(ruta, cuerpo)`.
+    /// Devuelve la URL base y las peticiones recibidas.
+    fn mock_server<F>(respond: F) -> (String, Received)
+    where
+        F: Fn(&str, &serde_json::Value) -> (u16, serde_json::Value) + Send + 'static,
+    {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let received: Received = Arc::default();
+        let log = Arc::clone(&received);
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                let (status, reply) = respond(request.url(), &body);
+                log.lock().unwrap().push(MockRequest {
+                    path: request.url().to_string(),
+                    headers: request
+                        .headers()
+                        .iter()
+                        .map(|header| (header.field.as_str().as_str().to_lowercase(), header.value.to_string()))
+                        .collect(),
+                    body,
+                });
+                let header = tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+                let response = tiny_http::Response::from_string(reply.to_string())
+                    .with_status_code(status)
+                    .with_header(header);
+                let _ = request.respond(response);
+            }
+        });
+        (url, received)
+    }
+
+    #[tokio::test]
+    async fn unavailable_model_falls_back_to_the_next_one() {
+        let (url, received) = mock_server(|_, body| match body["model"].as_str() {
+            Some("principal") => (404, serde_json::json!({ "error": "model 'principal' not found" })),
+            Some("caido") => (503, serde_json::json!({ "error": "overloaded" })),
+            model => (200, serde_json::json!({ "response": format!("análisis de {}", model.unwrap_or("?")) })),
+        });
+        let config = LlmConfig {
+            provider: LlmProvider::Ollama,
+            api_url: format!("{}/api/generate", url),
+            model: "principal".to_string(),
+            fallback_models: vec!["caido".to_string(), "respaldo".to_string(), "sin-usar".to_string()],
+            ..LlmConfig::default()
+        };
+        let client = LlmClient::new(config).unwrap();
+
+        assert_eq!(client.generate_response("hola").await.unwrap(), "análisis de respaldo");
+        assert_eq!(client.answered_by().as_deref(), Some("respaldo"));
+        assert!(client.used_fallback());
+
+        let models: Vec<String> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| format!("{} {}", request.path, request.body["model"].as_str().unwrap()))
+            .collect();
+        assert_eq!(models, vec!["/api/generate principal", "/api/generate caido", "/api/generate respaldo"]);
+    }
+
+    #[tokio::test]
+    async fn other_errors_do_not_fall_back() {
+        let (url, received) = mock_server(|_, _| (400, serde_json::json!({ "error": "invalid temperature" })));
+        let config = LlmConfig {
+            provider: LlmProvider::Ollama,
+            api_url: url,
+            model: "principal".to_string(),
+            fallback_models: vec!["respaldo".to_string()],
+            ..LlmConfig::default()
+        };
+        let client = LlmClient::new(config).unwrap();
+
+        assert!(client.generate_response("hola").await.is_err());
+        assert_eq!(client.answered_by(), None);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    fn gemini_config(api_url: String, api_key_placement: ApiKeyPlacement) -> LlmConfig {
+        LlmConfig {
+            provider: LlmProvider::Gemini,
+            api_url,
+            model: "gemini-1.5-flash".to_string(),
+            api_key: Some("clave-de-prueba".to_string()),
+            api_key_placement,
+            temperature: 0.2,
+            max_tokens: Some(512),
+            ..LlmConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn gemini_request_and_response_follow_the_generate_content_shape() {
+        let (url, received) = mock_server(|_, _| (200, serde_json::json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Sin " }, { "text": "indicios" }] },
+                "finishReason": "STOP"
+            }]
+        })));
+        let client = LlmClient::new(gemini_config(format!("{}/v1beta", url), ApiKeyPlacement::Header)).unwrap();
+
+        assert_eq!(client.generate_response("analiza esto").await.unwrap(), "Sin indicios");
+
+        let received = received.lock().unwrap();
+        let request = &received[0];
+        assert_eq!(request.path, "/v1beta/models/gemini-1.5-flash:generateContent");
+        assert!(request.headers.contains(&("x-goog-api-key".to_string(), "clave-de-prueba".to_string())));
+        assert_eq!(request.body["contents"], serde_json::json!([{ "role": "user", "parts": [{ "text": "analiza esto" }] }]));
+        assert_eq!(request.body["systemInstruction"]["parts"][0]["text"], SYSTEM_PROMPT);
+        assert_eq!(request.body["generationConfig"]["maxOutputTokens"], 512);
+        assert!((request.body["generationConfig"]["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn gemini_key_can_go_in_the_query_and_errors_are_surfaced() {
+        let (url, received) = mock_server(|_, _| (200, serde_json::json!({ "promptFeedback": { "blockReason": "SAFETY" } })));
+        let client = LlmClient::new(gemini_config(format!("{}/v1beta", url), ApiKeyPlacement::Query)).unwrap();
+
+        let error = client.generate_response("analiza esto").await.unwrap_err();
+        assert!(error.to_string().contains("Gemini bloqueó el prompt: SAFETY"), "{}", error);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0].path, "/v1beta/models/gemini-1.5-flash:generateContent?key=clave-de-prueba");
+        assert!(received[0].headers.iter().all(|(name, _)| name != "x-goog-api-key"));
+    }
+
+    /// Servidor HTTP con keep-alive que tarda `setup` en aceptar cada conexión nueva,
+    /// como un handshake TLS. Devuelve la URL base y el número de conexiones aceptadas.
+    fn keep_alive_server(setup: Duration) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(setup);
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut content_length = 0;
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                            if let Some((name, value)) = line.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap_or(0);
+                                }
+                            }
+                        }
+                        let mut body = vec![0; content_length];
+                        reader.read_exact(&mut body).unwrap();
+                        let reply = serde_json::json!({ "response": "ok" }).to_string();
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                            reply.len(), reply
+                        );
+                        if stream.write_all(response.as_bytes()).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    #[tokio::test]
+    async fn shared_http_client_reuses_connections() {
+        const REQUESTS: usize = 10;
+        let (url, connections) = keep_alive_server(Duration::from_millis(50));
+        let config = LlmConfig {
+            provider: LlmProvider::Ollama,
+            api_url: format!("{}/api", url),
+            model: "modelo".to_string(),
+            ..LlmConfig::default()
+        };
+
+        // Un cliente HTTP compartido por todos los clientes LLM
+        let shared = build_http_client(&config).unwrap();
+        let start = std::time::Instant::now();
+        for _ in 0..REQUESTS {
+            let client = LlmClient::with_http_client(config.clone(), shared.clone()).unwrap();
+            assert_eq!(client.generate_response("hola").await.unwrap(), "ok");
+        }
+        let pooled = start.elapsed();
+        assert_eq!(connections.swap(0, Ordering::SeqCst), 1);
+
+        // Un cliente HTTP nuevo por petición
+        let start = std::time::Instant::now();
+        for _ in 0..REQUESTS {
+            let client = LlmClient::new(config.clone()).unwrap();
+            assert_eq!(client.generate_response("hola").await.unwrap(), "ok");
+        }
+        let unpooled = start.elapsed();
+        assert_eq!(connections.load(Ordering::SeqCst), REQUESTS);
+        assert!(
+            pooled * 2 < unpooled,
+            "el cliente compartido tardó {:?} y uno por petición {:?}", pooled, unpooled
+        );
+    }
+}
@@ -1,22 +1,24 @@
 use std::path::PathBuf;
 use std::error::Error;
+use std::io::IsTerminal;
+use std::time::Duration;
 use clap::{Parser, Subcommand};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
-use crate::config::AppConfig;
-use crate::commands::{monitor_process, audit_binary, monitor_system};
-use crate::ui::{App, Tui};
-
-mod ui;
-mod app;
-mod process;
-mod file_monitor;
-mod network;
-mod reports;
-mod config;
-mod commands;
-mod error;
-mod llm;
+use shadowtrace::config::{AppConfig, ConfigFile, EffectiveConfig};
+use shadowtrace::profile::MonitorProfile;
+use shadowtrace::session::Session;
+use shadowtrace::audit_log::{audit_log, set_audit_log_path, AuditEvent};
+use shadowtrace::llm::{self, AnalysisDepth, LlmProvider};
+use shadowtrace::redaction::IpPseudonymizer;
+use shadowtrace::asn::AsnDatabase;
+use shadowtrace::integrations::live::{LiveMode, LiveServer};
+use shadowtrace::integrations::syslog::SyslogTarget;
+use shadowtrace::reports::ExportFormat;
+use shadowtrace::ui::widgets::ChartStyle;
+use shadowtrace::capture::CaptureBackend;
+use shadowtrace::commands::{monitor_process, audit_binary, monitor_system, show_config, list_models, diff_reports, print_status_summary, with_max_runtime, ListFormat, OutputFormat};
+use shadowtrace::ui::{App, Tui};
 
 // CLI principal
 #[derive(Parser)]
@@ -30,31 +32,294 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Nivel de verbosidad
+    /// Nivel de verbosidad (`-vv` añade los eventos en bruto al reporte Markdown)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
-    /// Modelo LLM a utilizar (llama2, mistral, orca, etc)
-    #[arg(short, long, default_value = "llama2")]
-    model: String,
+    /// Archivo de configuración (TOML); tiene precedencia sobre ~/.shadowtrace/config.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 
-    /// URL de la API de Ollama
-    #[arg(long, default_value = "http://localhost:11434/api")]
-    api_url: String,
+    /// Modelo LLM a utilizar (llama2, mistral, orca, etc) [por defecto: llama2]
+    #[arg(short, long)]
+    model: Option<String>,
+
+    /// URL de la API del LLM; tiene precedencia sobre --provider y --host [por defecto: http://localhost:11434/api]
+    #[arg(long)]
+    api_url: Option<String>,
+
+    /// Proveedor de LLM; sin --api-url, fija el puerto y la ruta de la API [por defecto: ollama]
+    #[arg(long, value_enum, value_name = "PROVIDER")]
+    provider: Option<LlmProvider>,
+
+    /// Host del servidor LLM (p. ej. 10.0.0.5, 10.0.0.5:8080 o https://gpu.lan) [por defecto: localhost]
+    #[arg(long, value_name = "HOST")]
+    host: Option<String>,
 
     /// Desactivar integración LLM
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    no_llm: Option<bool>,
+
+    /// Analizar sin LLM: genera un análisis heurístico (sin IA) a partir de los datos recopilados
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    offline: Option<bool>,
+
+    /// Temperatura del modelo LLM (0.0 - 2.0) [por defecto: 0.7]
     #[arg(long)]
-    no_llm: bool,
+    temperature: Option<f32>,
+
+    /// Longitud máxima de la respuesta del LLM en tokens
+    #[arg(long, value_name = "N")]
+    max_tokens: Option<u32>,
+
+    /// Ventana de contexto del modelo en tokens (num_ctx de Ollama)
+    #[arg(long, value_name = "N")]
+    num_ctx: Option<u32>,
+
+    /// Conexiones inactivas por host que se conservan para reutilizarlas con el LLM (0 = no reutilizar)
+    #[arg(long, value_name = "N")]
+    pool_max_idle_per_host: Option<usize>,
+
+    /// Segundos que se conserva una conexión inactiva con el LLM
+    #[arg(long, value_name = "SECONDS")]
+    pool_idle_timeout: Option<u64>,
+
+    /// Profundidad del análisis LLM: shallow (solo estadísticas), normal o deep (todos los eventos)
+    #[arg(long, value_enum)]
+    depth: Option<AnalysisDepth>,
+
+    /// No repetir el análisis solo con estadísticas cuando el modelo rechaza el prompt por exceder su ventana de contexto
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    no_context_retry: Option<bool>,
+
+    /// Registrar URL, cabeceras y cuerpos de cada petición y respuesta del LLM (redactados)
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    debug_llm: Option<bool>,
+
+    /// Modelo de respaldo si el principal no está disponible; puede repetirse (se prueban en orden)
+    #[arg(long, value_name = "MODEL")]
+    fallback_model: Vec<String>,
+
+    /// Plantilla del prompt de análisis: archivo con {process}, {files} y {network} (y opcionalmente {instructions}) o una incorporada (default, pci, malware)
+    #[arg(long, value_name = "PATH")]
+    template: Option<String>,
+
+    /// Nota del analista para el LLM (p. ej. "clic en un phishing a las 14:02"); se guarda en el reporte y puede repetirse
+    #[arg(long, value_name = "TEXT")]
+    note: Vec<String>,
 
     /// Iniciar en modo TUI
     #[arg(long)]
     tui: bool,
+
+    /// Desactivar la redacción de secretos en reportes y prompts LLM
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    no_redact: Option<bool>,
+
+    /// Patrón (regex) adicional a redactar; puede repetirse
+    #[arg(long, value_name = "REGEX")]
+    redact_pattern: Vec<String>,
+
+    /// Clave sensible adicional cuyo valor se redacta (clave=valor, flags y variables
+    /// de entorno); puede repetirse
+    #[arg(long, value_name = "KEY")]
+    redact_key: Vec<String>,
+
+    /// Seudonimizar las direcciones IP (10.0.0.5 → ip_7f3a) en reportes y prompts LLM;
+    /// el mapeo se guarda en ~/.shadowtrace/ip_pseudonyms.json
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    redact_network: Option<bool>,
+
+    /// Máximo de eventos por tipo que conserva un reporte; por encima se muestrea
+    #[arg(long, value_name = "N")]
+    max_report_entries: Option<usize>,
+
+    /// Argumentos de la línea de comandos que se conservan por proceso (0 = todos) [por defecto: 5]
+    #[arg(long, value_name = "N")]
+    max_cmd_args: Option<usize>,
+
+    /// Milisegundos durante los que las búsquedas por nombre reutilizan el último escaneo del sistema (0 = escanear siempre) [por defecto: 1000]
+    #[arg(long, value_name = "MS")]
+    refresh_throttle_ms: Option<u64>,
+
+    /// Conexiones cerradas que se conservan para los reportes y la pantalla de red [por defecto: 50]
+    #[arg(long, value_name = "N")]
+    closed_connections_history: Option<usize>,
+
+    /// Muestras de CPU y memoria que recoge la TUI antes de analizar un proceso sin historial (0 = ninguna) [por defecto: 4]
+    #[arg(long, value_name = "N")]
+    warmup_samples: Option<usize>,
+
+    /// Duración en segundos del monitoreo que inicia la tecla 'm' en la TUI (0 = indefinido) [por defecto: 30]
+    #[arg(long, value_name = "SECONDS")]
+    tui_monitor_duration: Option<u64>,
+
+    /// Cerrar cualquier sesión (CLI o TUI) tras este tiempo en segundos, guardando lo recopilado
+    #[arg(long, value_name = "SECONDS")]
+    max_runtime: Option<u64>,
+
+    /// Incluir el propio proceso de ShadowTrace en listados y detectores
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    include_self: Option<bool>,
+
+    /// No restaurar ni guardar la sesión de la TUI (~/.shadowtrace/session.json)
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    no_session: Option<bool>,
+
+    /// No mostrar el consumo de CPU y memoria de ShadowTrace en la TUI
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    no_watchdog: Option<bool>,
+
+    /// URL de webhook (compatible con Slack) a notificar ante hallazgos críticos
+    #[arg(long, value_name = "URL")]
+    alert_webhook: Option<String>,
+
+    /// Registrar los hallazgos en syslog (por defecto) o en el journal de systemd (--syslog=journald)
+    #[arg(long, value_enum, value_name = "TARGET", num_args = 0..=1, require_equals = true, default_missing_value = "syslog")]
+    syslog: Option<SyslogTarget>,
+
+    /// Exponer el estado en vivo (procesos, hallazgos, métricas) en JSON por HTTP; :PUERTO escucha solo en 127.0.0.1
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<String>,
+
+    /// Ubicación de arranque/persistencia adicional a vigilar; puede repetirse
+    #[arg(long, value_name = "PATH")]
+    persistence_path: Vec<String>,
+
+    /// Exportar reportes solo con caracteres ASCII (sin emojis ni acentos)
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    ascii: Option<bool>,
+
+    /// Escribir la marca BOM de UTF-8 en los reportes (compatibilidad con Windows)
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    utf8_bom: Option<bool>,
+
+    /// Exportar también en este formato (prometheus, summary, csv); puede repetirse
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    export: Vec<ExportFormat>,
+
+    /// Archivo de métricas para --export prometheus [por defecto: ~/.shadowtrace/reports/shadowtrace.prom]
+    #[arg(long, value_name = "PATH")]
+    prometheus_textfile: Option<String>,
+
+    /// Registro de auditoría de acciones (JSONL, solo se añaden líneas) [por defecto: ~/.shadowtrace/audit.log]
+    #[arg(long, value_name = "PATH")]
+    audit_log: Option<String>,
+
+    /// Empaquetar en un zip los reportes generados (y los formatos de --export) con un manifiesto y sumas SHA-256
+    #[arg(long, value_name = "PATH")]
+    export_bundle: Option<String>,
+
+    /// Base de datos IP → ASN (TSV de iptoasn.com) para agrupar las conexiones salientes por organización
+    #[arg(long, value_name = "PATH")]
+    asn_db: Option<String>,
+
+    /// Registro de resoluciones DNS (dnsmasq con log-queries o formato hosts) para detectar conexiones directas a IP
+    #[arg(long, value_name = "PATH")]
+    dns_log: Option<String>,
+
+    /// Incluir en el reporte y en el análisis las líneas recientes de auth.log/syslog o journald que mencionan el proceso
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    with_logs: Option<bool>,
+
+    /// Líneas de registro del sistema a incluir con --with-logs [por defecto: 200]
+    #[arg(long, value_name = "N")]
+    log_lines: Option<usize>,
+
+    /// Estilo de los gráficos de la TUI (braille, ascii) [por defecto: según la terminal]
+    #[arg(long, value_enum, value_name = "STYLE")]
+    charts: Option<ChartStyle>,
+
+    /// Acompañar los colores de nivel de la TUI con símbolos y texto (▲ [ALTO], ■ [CRIT]) para no depender solo del color
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    accessible: Option<bool>,
+
+    /// Diseño compacto del monitor de procesos (sin gráficos) aunque la terminal sea alta; con menos de 30 filas se activa solo
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true", value_name = "BOOL")]
+    compact: Option<bool>,
+
+    /// Método de captura de archivos y conexiones: auto elige el más fiel disponible (ebpf, fanotify con root, procfs) [por defecto: auto]
+    #[arg(long, value_enum, value_name = "BACKEND")]
+    capture_backend: Option<CaptureBackend>,
+
+    /// Perfil de monitoreo (quick-triage, deep-audit, leak-hunt, malware o uno propio del archivo de configuración)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+impl Cli {
+    /// Opciones indicadas explícitamente en la línea de comandos
+    fn overrides(&self) -> ConfigFile {
+        ConfigFile {
+            model: self.model.clone(),
+            api_url: self.api_url.clone(),
+            provider: self.provider,
+            host: self.host.clone(),
+            no_llm: self.no_llm,
+            offline: self.offline,
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            num_ctx: self.num_ctx,
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+            pool_idle_timeout: self.pool_idle_timeout,
+            depth: self.depth,
+            no_context_retry: self.no_context_retry,
+            debug_llm: self.debug_llm,
+            fallback_models: (!self.fallback_model.is_empty()).then(|| self.fallback_model.clone()),
+            template: self.template.clone(),
+            // Solo desde el archivo de configuración o el entorno
+            api_key: None,
+            api_key_placement: None,
+            notes: (!self.note.is_empty()).then(|| self.note.clone()),
+            no_redact: self.no_redact,
+            redact_patterns: (!self.redact_pattern.is_empty()).then(|| self.redact_pattern.clone()),
+            redact_keys: (!self.redact_key.is_empty()).then(|| self.redact_key.clone()),
+            redact_network: self.redact_network,
+            max_report_entries: self.max_report_entries,
+            max_cmd_args: self.max_cmd_args,
+            refresh_throttle_ms: self.refresh_throttle_ms,
+            closed_connections_history: self.closed_connections_history,
+            warmup_samples: self.warmup_samples,
+            tui_monitor_duration: self.tui_monitor_duration,
+            max_runtime: self.max_runtime,
+            include_self: self.include_self,
+            no_session: self.no_session,
+            no_watchdog: self.no_watchdog,
+            alert_webhook: self.alert_webhook.clone(),
+            syslog: self.syslog,
+            serve: self.serve.clone(),
+            persistence_paths: (!self.persistence_path.is_empty()).then(|| self.persistence_path.clone()),
+            sensitive_ports: None,
+            expected_ports: None,
+            sensitive_endpoints: None,
+            spawn_rules: None,
+            schedule: None,
+            ascii: self.ascii,
+            utf8_bom: self.utf8_bom,
+            export: (!self.export.is_empty()).then(|| self.export.clone()),
+            prometheus_textfile: self.prometheus_textfile.clone(),
+            audit_log: self.audit_log.clone(),
+            export_bundle: self.export_bundle.clone(),
+            asn_db: self.asn_db.clone(),
+            dns_log: self.dns_log.clone(),
+            raw_ip_severity: None,
+            with_logs: self.with_logs,
+            log_lines: self.log_lines,
+            charts: self.charts,
+            colors: None,
+            accessible: self.accessible,
+            compact: self.compact,
+            capture_backend: self.capture_backend,
+            profile: self.profile.clone(),
+            profiles: None,
+        }
+    }
 }
 
 // Comandos CLI disponibles
 #[derive(Subcommand)]
 enum Commands {
-    /// Monitorear un proceso específico
+    /// Monitorear un proceso específico (sin --pid ni --name, se elige de una lista)
     Monitor {
         /// ID del proceso a monitorear
         #[arg(short, long)]
@@ -64,13 +329,37 @@ enum Commands {
         #[arg(short, long)]
         name: Option<String>,
         
-        /// Duración del monitoreo en segundos (0 = indefinido)
-        #[arg(short, long, default_value = "0")]
-        duration: u64,
+        /// Duración del monitoreo en segundos (0 = indefinido) [por defecto: 0 o el del perfil]
+        #[arg(short, long)]
+        duration: Option<u64>,
+        
+        /// Intervalo de muestreo en segundos; 0 = lo más rápido posible (cada 100 ms) [por defecto: 1 o el del perfil]
+        #[arg(short, long)]
+        interval: Option<u64>,
+        
+        /// Adaptar el intervalo a la actividad: muestrear más rápido cuando la CPU, la memoria o la E/S cambian deprisa y espaciar las muestras en reposo
+        #[arg(long)]
+        interval_adaptive: bool,
+        
+        /// Intervalo mínimo del muestreo adaptativo en milisegundos [por defecto: 250 o el del perfil]
+        #[arg(long, value_name = "MS")]
+        min_interval_ms: Option<u64>,
+        
+        /// Intervalo máximo del muestreo adaptativo en milisegundos [por defecto: 5000 o el del perfil]
+        #[arg(long, value_name = "MS")]
+        max_interval_ms: Option<u64>,
+        
+        /// Uso de CPU (%) que, sostenido, genera un hallazgo [por defecto: 80 o el del perfil]
+        #[arg(long, value_name = "PERCENT")]
+        cpu_threshold: Option<f32>,
         
-        /// Intervalo de muestreo en segundos
-        #[arg(short, long, default_value = "1")]
-        interval: u64,
+        /// Segundos seguidos por encima de --cpu-threshold para generar el hallazgo [por defecto: 60 o el del perfil]
+        #[arg(long, value_name = "SECONDS")]
+        cpu_sustained: Option<u64>,
+        
+        /// Analizar eventos de archivo y red grabados en un JSON en lugar de un proceso en vivo (demos, pruebas, capturas compartidas)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["pid", "name"])]
+        sample_file: Option<PathBuf>,
     },
     
     /// Auditar un binario
@@ -86,11 +375,23 @@ enum Commands {
         /// Tiempo máximo de ejecución en segundos
         #[arg(short, long, default_value = "60")]
         timeout: u64,
+        
+        /// Analizar el binario sin ejecutarlo (ELF/PE/Mach-O)
+        #[arg(long = "static")]
+        static_mode: bool,
+        
+        /// Ejecutar el binario bajo strace y resumir sus llamadas al sistema por categoría (Linux)
+        #[arg(long, conflicts_with = "static_mode")]
+        trace_syscalls: bool,
+        
+        /// Monitorear también los procesos que crea el binario (hijos, nietos...) a medida que aparecen
+        #[arg(long, conflicts_with = "static_mode")]
+        follow_children: bool,
     },
     
     /// Monitorear actividad del sistema
     System {
-        /// Monitorear en tiempo real
+        /// Monitorear en tiempo real (respeta el horario de `[schedule]` del archivo de configuración)
         #[arg(short, long)]
         watch: bool,
         
@@ -101,18 +402,105 @@ enum Commands {
         /// Solo mostrar actividad sospechosa
         #[arg(short, long)]
         suspicious_only: bool,
+        
+        /// Formato de la lista de procesos (table, tree)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: ListFormat,
+        
+        /// No mostrar la línea de progreso del recorrido de procesos
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    
+    /// Listar los modelos disponibles en el endpoint LLM configurado
+    Models {
+        /// Formato de salida (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+    
+    /// Comparar dos reportes JSON (antes → después)
+    Diff {
+        /// Reporte anterior
+        before: PathBuf,
+        
+        /// Reporte posterior
+        after: PathBuf,
+        
+        /// Salida sin colores (también fuera de una terminal o con NO_COLOR)
+        #[arg(long)]
+        plain: bool,
+    },
+    
+    /// Inspeccionar la configuración
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
 }
 
+// Acciones sobre la configuración
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Mostrar la configuración efectiva y el origen de cada valor
+    Show,
+}
+
 /// Función para ejecutar la interfaz de usuario de terminal (TUI)
-fn run_tui_mode(config: &AppConfig) -> Result<(), Box<dyn Error>> {
+async fn run_tui_mode(config: &AppConfig) -> Result<(), Box<dyn Error>> {
     // Crear una instancia de la aplicación TUI
     let mut app = App::new();
     
     // Configurar app con AppConfig
-    if let Some(client) = &config.llm_client {
+    if config.llm_client.is_some() {
         app.status_message = Some("Cliente LLM conectado".to_string());
     }
+    app.redactor = config.redactor.clone();
+    app.offline = config.offline;
+    if config.redact_network {
+        app.ip_pseudonymizer = Some(IpPseudonymizer::load_default()?);
+    }
+    if let Some(path) = &config.asn_db {
+        app.asn_database = Some(AsnDatabase::load(path)?);
+    }
+    app.network_monitor().add_sensitive_ports(&config.sensitive_ports);
+    app.network_monitor().add_expected_ports(&config.expected_ports);
+    app.network_monitor().add_sensitive_endpoints(&config.sensitive_endpoints);
+    app.network_monitor().set_closed_history_len(config.closed_connections_history);
+    // El tráfico hacia el LLM lo genera ShadowTrace: se etiqueta y no cuenta en los detectores
+    if !config.include_self {
+        for addr in llm::endpoint_addrs(&config.llm.api_url).await {
+            app.network_monitor().ignore_remote(addr);
+        }
+    }
+    app.process_monitor().set_max_cmd_args(config.max_cmd_args);
+    app.process_monitor().set_refresh_throttle(Duration::from_millis(config.refresh_throttle_ms));
+    app.process_monitor().set_include_self(config.include_self);
+    app.llm_config = config.llm.clone();
+    app.analyst_notes = config.llm.analyst_notes.clone();
+    app.http_client = config.http_client.clone();
+    app.analysis_depth = config.analysis_depth;
+    app.chart_style = config.chart_style;
+    app.accessible = config.accessible;
+    app.compact = config.compact;
+    app.verbose = config.verbose;
+    app.set_capture(config.capture_backend.open()?);
+    app.color_thresholds = config.color_thresholds;
+    app.self_watchdog = !config.no_watchdog;
+    app.warmup_samples = config.warmup_samples;
+    app.monitor_duration = config.tui_monitor_duration;
+    app.runtime_deadline = config.runtime_deadline;
+    app.live_server = config.serve
+        .map(|addr| LiveServer::start(addr, LiveMode::Tui, config.redactor.clone()))
+        .transpose()?;
+    app.refresh_processes();
+    
+    // Restaurar la última sesión
+    if !config.no_session {
+        if let Some(session) = Session::load() {
+            app.restore_session(&session);
+        }
+    }
     
     // Crear e inicializar la terminal TUI
     let mut tui = Tui::new()?;
@@ -121,146 +509,23 @@ fn run_tui_mode(config: &AppConfig) -> Result<(), Box<dyn Error>> {
     // Ejecutar el loop principal de la UI
     let result = tui.run(&mut app);
     
+    // Guardar la sesión para continuar donde se dejó
+    if !config.no_session {
+        if let Err(e) = app.to_session().save() {
+            tracing::warn!("No se pudo guardar la sesión: {}", e);
+        }
+    }
+    
     // Restaurar terminal
     if let Err(e) = tui.exit() {
         eprintln!("Error al restaurar terminal: {}", e);
     }
+    if config.runtime_exceeded() {
+        tracing::warn!("Se alcanzó --max-runtime: la TUI se cerró y se guardó la sesión");
+    }
     
     // Propagar el resultado del loop principal
-    result.map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn Error>)
-}
-
-// Manejar eventos de teclado
-fn handle_key_events(key_event: KeyEvent, app: &mut App) -> bool {
-    match app.current_state {
-        AppState::Dashboard => handle_dashboard_keys(key_event, app),
-        AppState::ProcessMonitor => handle_process_monitor_keys(key_event, app),
-        AppState::FileMonitor => handle_file_monitor_keys(key_event, app),
-        AppState::NetworkMonitor => handle_network_monitor_keys(key_event, app),
-        AppState::Reports => handle_reports_keys(key_event, app),
-        AppState::Help => handle_help_keys(key_event, app),
-    }
-}
-
-// Manejar eventos de teclado en la pantalla de monitor de procesos
-fn handle_process_monitor_keys(key_event: KeyEvent, app: &mut App) -> bool {
-    match key_event.code {
-        // Teclas de navegación general
-        KeyCode::Esc => {
-            app.current_state = AppState::Dashboard;
-            app.selected_pid = None;
-            app.status_message = None;
-            app.cpu_history.clear();
-            app.memory_history.clear();
-            if app.is_monitoring_active {
-                app.stop_monitoring();
-            }
-            true
-        },
-        KeyCode::Tab => {
-            // Alternar entre pestañas
-            if app.process_monitor_tab == 0 {
-                app.process_monitor_tab = 1;
-            } else {
-                app.process_monitor_tab = 0;
-            }
-            true
-        },
-        // Navegación en la lista de procesos
-        KeyCode::Up => {
-            // Si estamos en la pestaña de análisis LLM y hay análisis, desplazar el texto
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::Up);
-            } else {
-                // Si no, navegar por la lista de procesos
-                let processes = &app.processes;
-                if !processes.is_empty() {
-                    let i = match app.list_state.selected() {
-                        Some(i) => {
-                            if i == 0 {
-                                processes.len() - 1
-                            } else {
-                                i - 1
-                            }
-                        }
-                        None => 0,
-                    };
-                    app.list_state.select(Some(i));
-                    
-                    // Actualizar selección
-                    let selected_process = &processes[i];
-                    app.selected_pid = Some(selected_process.pid);
-                    app.status_message = Some(format!("Proceso seleccionado: {} (PID: {})", 
-                        selected_process.name, selected_process.pid));
-                }
-            }
-            true
-        },
-        KeyCode::Down => {
-            // Si estamos en la pestaña de análisis LLM y hay análisis, desplazar el texto
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::Down);
-            } else {
-                // Si no, navegar por la lista de procesos
-                let processes = &app.processes;
-                if !processes.is_empty() {
-                    let i = match app.list_state.selected() {
-                        Some(i) => {
-                            if i >= processes.len() - 1 {
-                                0
-                            } else {
-                                i + 1
-                            }
-                        }
-                        None => 0,
-                    };
-                    app.list_state.select(Some(i));
-                    
-                    // Actualizar selección
-                    let selected_process = &processes[i];
-                    app.selected_pid = Some(selected_process.pid);
-                    app.status_message = Some(format!("Proceso seleccionado: {} (PID: {})", 
-                        selected_process.name, selected_process.pid));
-                }
-            }
-            true
-        },
-        // Teclas adicionales para navegación de scroll en análisis LLM
-        KeyCode::PageUp => {
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::PageUp);
-                true
-            } else {
-                false
-            }
-        },
-        KeyCode::PageDown => {
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::PageDown);
-                true
-            } else {
-                false
-            }
-        },
-        KeyCode::Home => {
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::Home);
-                true
-            } else {
-                false
-            }
-        },
-        KeyCode::End => {
-            if app.process_monitor_tab == 1 && app.process_llm_analysis.is_some() {
-                app.handle_llm_text_scroll(KeyCode::End);
-                true
-            } else {
-                false
-            }
-        },
-        // Otras teclas...
-        // ... resto del código existente ...
-    }
+    result.map_err(|e| Box::new(std::io::Error::other(e.to_string())) as Box<dyn Error>)
 }
 
 #[tokio::main]
@@ -270,17 +535,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Ok(val) => EnvFilter::new(val),
         Err(_) => {
             // Si RUST_LOG no está definido, usar valores basados en verbose
-            EnvFilter::new(format!("shadowtrace={}", match Cli::parse().verbose {
+            // El registro de --debug-llm se escribe en debug, pero solo cuando está activo
+            EnvFilter::new(format!("shadowtrace={},{}=debug", match Cli::parse().verbose {
                 0 => "info",
                 1 => "debug",
                 _ => "trace",
-            }))
+            }, llm::DEBUG_LOG_TARGET))
         }
     };
     
     tracing_subscriber::registry()
         .with(filter)
-        .with(tracing_subscriber::fmt::layer().with_target(true))
+        .with(tracing_subscriber::fmt::layer().with_target(true).with_writer(std::io::stderr))
         .init();
     
     // Log inicial para verificar que está funcionando
@@ -290,40 +556,99 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Parsear argumentos CLI
     let cli = Cli::parse();
     
+    // Resolver la configuración: CLI > --config > ~/.shadowtrace/config.toml > por defecto
+    let effective = EffectiveConfig::resolve(cli.overrides(), cli.config.as_deref())?;
+    if let Some(path) = &effective.values.audit_log {
+        set_audit_log_path(PathBuf::from(path));
+    }
+    
+    if let Some(Commands::Config { action: ConfigAction::Show }) = &cli.command {
+        show_config(&effective);
+        return Ok(());
+    }
+    
+    // Solo lee reportes guardados: no hace falta crear el cliente de análisis
+    if let Some(Commands::Diff { before, after, plain }) = &cli.command {
+        diff_reports(before, after, *plain)?;
+        return Ok(());
+    }
+    
+    // Solo consulta el endpoint: no hace falta crear el cliente de análisis
+    if let Some(Commands::Models { format }) = &cli.command {
+        list_models(*format, &effective).await?;
+        return Ok(());
+    }
+    
+    // Con --verbose, de dónde sale cada valor (a stderr, para no mezclarse con la salida)
+    if cli.verbose > 0 {
+        for (key, source) in &effective.sources {
+            eprintln!("Configuración: {} <- {}", key, source);
+        }
+    }
+    
     // Crear configuración global
-    let config = AppConfig::new(
-        cli.model.clone(), 
-        cli.api_url.clone(), 
-        cli.verbose, 
-        cli.no_llm
-    )?;
+    let config = AppConfig::from_effective(&effective, cli.verbose)?;
     
-    // Determinar si se debe ejecutar en modo TUI
-    let use_tui = cli.tui || cli.command.is_none();
+    // Determinar si se debe ejecutar en modo TUI; sin terminal (tubería, CI) el modo
+    // raw fallaría o ensuciaría la salida
+    let interactive = std::io::stdout().is_terminal() && std::io::stdin().is_terminal();
+    let use_tui = (cli.tui || cli.command.is_none()) && interactive;
+    
+    if !interactive && (cli.tui || cli.command.is_none()) {
+        eprintln!(
+            "La interfaz TUI necesita una terminal interactiva. Usa un subcomando \
+            (monitor, audit, system, models, config) para el modo no interactivo."
+        );
+    }
     
     if use_tui {
         // Ejecutar en modo TUI
-        run_tui_mode(&config)?;
+        run_tui_mode(&config).await?;
         return Ok(());
     }
     
     // Modo CLI normal
     match cli.command {
-        Some(Commands::Monitor { pid, name, duration, interval }) => {
-            // Ejecutar monitoreo
-            monitor_process(&pid, &name, duration, interval, &config).await?;
+        Some(Commands::Monitor { pid, name, duration, interval, interval_adaptive, min_interval_ms, max_interval_ms, cpu_threshold, cpu_sustained, sample_file }) => {
+            // Ejecutar monitoreo (los flags explícitos prevalecen sobre el perfil)
+            let settings = config.monitor_settings(&MonitorProfile {
+                interval,
+                interval_adaptive: interval_adaptive.then_some(true),
+                min_interval_ms,
+                max_interval_ms,
+                duration,
+                cpu_threshold,
+                cpu_sustained_secs: cpu_sustained,
+                ..MonitorProfile::default()
+            });
+            let target = sample_file
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .or_else(|| pid.map(|pid| format!("PID {}", pid)))
+                .or_else(|| name.clone());
+            audit_log(AuditEvent::new("monitor", target.clone(), "iniciado"));
+            let result = with_max_runtime(&config, monitor_process(&pid, &name, &sample_file, &settings, &config)).await;
+            audit_log(AuditEvent::from_result("monitor", target, &result));
+            result?;
         },
-        Some(Commands::Audit { binary, args, timeout }) => {
+        Some(Commands::Audit { binary, args, timeout, static_mode, trace_syscalls, follow_children }) => {
             // Ejecutar auditoría
-            audit_binary(&binary, &args, timeout, &config).await?;
+            let target = Some(binary.display().to_string());
+            audit_log(AuditEvent::new("audit", target.clone(), "iniciado"));
+            let result = with_max_runtime(&config, audit_binary(&binary, &args, timeout, static_mode, trace_syscalls, follow_children, &config)).await;
+            audit_log(AuditEvent::from_result("audit", target, &result));
+            result?;
         },
-        Some(Commands::System { watch, duration, suspicious_only }) => {
+        Some(Commands::System { watch, duration, suspicious_only, format, quiet }) => {
             // Ejecutar monitoreo de sistema
-            monitor_system(watch, duration, suspicious_only, &config).await?;
+            with_max_runtime(&config, monitor_system(watch, duration, suspicious_only, format, quiet, &config)).await?;
+        },
+        Some(Commands::Config { .. }) | Some(Commands::Models { .. }) | Some(Commands::Diff { .. }) => {
+            // Ya gestionado antes de crear la configuración
         },
         None => {
-            // No debería llegar aquí si use_tui es true cuando command es None
-            println!("Modo TUI no implementado todavía");
+            // Sin terminal interactiva: mostrar un resumen en lugar de la TUI
+            print_status_summary(&config);
         }
     }
     
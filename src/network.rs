@@ -1,8 +1,10 @@
-use chrono::{DateTime, Utc, TimeZone};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::net::SocketAddr;
-use std::time::SystemTime;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, SystemTime};
+
+use crate::reports::Detection;
 
 /// Tipo de protocolo
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -43,6 +45,20 @@ pub enum ConnectionState {
     Other,
 }
 
+impl ConnectionState {
+    /// Nombre del estado tal como se muestra en las conexiones ("ESTABLISHED", "CLOSED"...)
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionState::Established => "ESTABLISHED",
+            ConnectionState::Connecting => "CONNECTING",
+            ConnectionState::Listening => "LISTEN",
+            ConnectionState::Closing => "CLOSING",
+            ConnectionState::Closed => "CLOSED",
+            ConnectionState::Other => "OTHER",
+        }
+    }
+}
+
 /// Evento de conexión de red
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkEvent {
@@ -89,14 +105,316 @@ pub struct Connection {
     pub bytes_received: u64,
 }
 
+impl Connection {
+    /// Indica si la conexión corresponde a un evento (mismas direcciones local y remota)
+    pub fn matches(&self, event: &NetworkEvent) -> bool {
+        self.local_addr == event.local_addr && event.remote_addr == Some(self.remote_addr)
+    }
+}
+
+/// Conexión ya cerrada, con sus estadísticas finales
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedConnection {
+    /// Conexión con los bytes transferidos hasta el cierre
+    pub connection: Connection,
+    /// Momento del cierre
+    pub closed_at: SystemTime,
+}
+
+impl ClosedConnection {
+    /// Tiempo que estuvo abierta la conexión
+    pub fn duration(&self) -> Duration {
+        self.closed_at
+            .duration_since(self.connection.first_seen)
+            .unwrap_or_default()
+    }
+}
+
+/// Conexiones cerradas que se conservan por defecto
+pub const DEFAULT_CLOSED_CONNECTIONS_HISTORY: usize = 50;
+
+/// Número de muestras de throughput que se conservan por conexión
+pub const BANDWIDTH_HISTORY_LEN: usize = 60;
+
+/// Factor sobre la media reciente a partir del cual el throughput se considera un pico
+pub const BANDWIDTH_SPIKE_FACTOR: f64 = 3.0;
+
+/// Clave de una conexión (dirección local, dirección remota)
+pub type ConnectionKey = (SocketAddr, SocketAddr);
+
+/// Historial de throughput de una conexión (bytes transferidos entre muestras)
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthHistory {
+    /// Bytes transferidos en cada intervalo de muestreo
+    pub samples: Vec<f64>,
+    /// Total acumulado en la última muestra
+    last_total: Option<u64>,
+}
+
+impl BandwidthHistory {
+    /// Registrar el total acumulado de bytes y guardar la diferencia con la muestra anterior
+    pub fn push_total(&mut self, total: u64) {
+        if let Some(last) = self.last_total {
+            self.samples.push(total.saturating_sub(last) as f64);
+            if self.samples.len() > BANDWIDTH_HISTORY_LEN {
+                self.samples.remove(0);
+            }
+        }
+        self.last_total = Some(total);
+    }
+
+    /// Throughput de la última muestra
+    pub fn current(&self) -> f64 {
+        self.samples.last().copied().unwrap_or(0.0)
+    }
+
+    /// Indica si la última muestra supera claramente la media de las anteriores
+    pub fn is_spiking(&self) -> bool {
+        let Some((last, previous)) = self.samples.split_last() else {
+            return false;
+        };
+        if previous.is_empty() || *last <= 0.0 {
+            return false;
+        }
+        let average = previous.iter().sum::<f64>() / previous.len() as f64;
+        *last > average * BANDWIDTH_SPIKE_FACTOR
+    }
+}
+
+/// Categoría de un puerto sensible
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PortCategory {
+    /// Acceso remoto (SSH, Telnet, RDP, VNC...)
+    RemoteAccess,
+    /// Compartición de archivos de Windows (SMB, NetBIOS, RPC)
+    Smb,
+    /// Bases de datos
+    Database,
+    /// Correo
+    Mail,
+    /// Otros puertos marcados como sensibles
+    #[default]
+    Other,
+}
+
+impl PortCategory {
+    /// Descripción para los hallazgos ("puerto de acceso remoto", ...)
+    pub fn label(&self) -> &'static str {
+        match self {
+            PortCategory::RemoteAccess => "puerto de acceso remoto",
+            PortCategory::Smb => "puerto SMB",
+            PortCategory::Database => "puerto de base de datos",
+            PortCategory::Mail => "puerto de correo",
+            PortCategory::Other => "puerto sensible",
+        }
+    }
+}
+
+/// Puerto cuya conexión se considera sensible
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensitivePort {
+    /// Número de puerto
+    pub port: u16,
+    /// Nombre del servicio (RDP, MySQL...)
+    #[serde(default)]
+    pub service: String,
+    /// Categoría del puerto
+    #[serde(default)]
+    pub category: PortCategory,
+}
+
+impl SensitivePort {
+    /// Crear un puerto sensible
+    pub fn new(port: u16, service: &str, category: PortCategory) -> Self {
+        Self { port, service: service.to_string(), category }
+    }
+
+    /// Describir una conexión a este puerto, p. ej. "Conexión a puerto de acceso remoto (3389/RDP)"
+    pub fn describe(&self) -> String {
+        if self.service.is_empty() {
+            format!("Conexión a {} ({})", self.category.label(), self.port)
+        } else {
+            format!("Conexión a {} ({}/{})", self.category.label(), self.port, self.service)
+        }
+    }
+}
+
+/// Puertos sensibles por defecto: (puerto, servicio, categoría)
+pub const DEFAULT_SENSITIVE_PORTS: &[(u16, &str, PortCategory)] = &[
+    (22, "SSH", PortCategory::RemoteAccess),
+    (23, "Telnet", PortCategory::RemoteAccess),
+    (3389, "RDP", PortCategory::RemoteAccess),
+    (5900, "VNC", PortCategory::RemoteAccess),
+    (5985, "WinRM", PortCategory::RemoteAccess),
+    (135, "MSRPC", PortCategory::Smb),
+    (139, "NetBIOS", PortCategory::Smb),
+    (445, "SMB", PortCategory::Smb),
+    (1433, "MSSQL", PortCategory::Database),
+    (3306, "MySQL", PortCategory::Database),
+    (5432, "PostgreSQL", PortCategory::Database),
+    (6379, "Redis", PortCategory::Database),
+    (27017, "MongoDB", PortCategory::Database),
+    (25, "SMTP", PortCategory::Mail),
+    (110, "POP3", PortCategory::Mail),
+    (143, "IMAP", PortCategory::Mail),
+    (587, "SMTP", PortCategory::Mail),
+];
+
+/// Categoría de un destino que merece atención por sí mismo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndpointCategory {
+    /// Servicio de metadata de instancias en la nube (robo de credenciales, SSRF)
+    CloudMetadata,
+    /// Red Tor (autoridades de directorio, proxy SOCKS local)
+    Tor,
+    /// Proxies y VPN de anonimato
+    Proxy,
+    /// Otros destinos marcados como sensibles
+    #[default]
+    Other,
+}
+
+impl EndpointCategory {
+    /// Descripción para los hallazgos ("Acceso a metadata de nube", ...)
+    pub fn label(&self) -> &'static str {
+        match self {
+            EndpointCategory::CloudMetadata => "Acceso a metadata de nube",
+            EndpointCategory::Tor => "Conexión a la red Tor",
+            EndpointCategory::Proxy => "Conexión a un proxy de anonimato",
+            EndpointCategory::Other => "Conexión a un destino sensible",
+        }
+    }
+}
+
+/// Destino (IP y, opcionalmente, puerto) cuya conexión se reporta por sí misma
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensitiveEndpoint {
+    /// Dirección IP del destino
+    pub ip: IpAddr,
+    /// Puerto remoto; sin él, cualquier puerto de la IP
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Nombre del destino (AWS IMDS, moria1...)
+    #[serde(default)]
+    pub name: String,
+    /// Categoría del destino
+    #[serde(default)]
+    pub category: EndpointCategory,
+}
+
+impl SensitiveEndpoint {
+    /// Crear un destino sensible
+    pub fn new(ip: IpAddr, port: Option<u16>, name: &str, category: EndpointCategory) -> Self {
+        Self { ip, port, name: name.to_string(), category }
+    }
+
+    /// Indica si una dirección remota es este destino. Las IPv4 mapeadas en IPv6
+    /// (`::ffff:169.254.169.254`, sockets tcp6) cuentan como la IPv4
+    pub fn matches(&self, addr: SocketAddr) -> bool {
+        addr.ip().to_canonical() == self.ip.to_canonical() && self.port.is_none_or(|port| port == addr.port())
+    }
+
+    /// Describir una conexión a este destino, p. ej.
+    /// "Acceso a metadata de nube: 169.254.169.254:80 (AWS/GCP/Azure IMDS)"
+    pub fn describe(&self, addr: SocketAddr) -> String {
+        if self.name.is_empty() {
+            format!("{}: {}", self.category.label(), addr)
+        } else {
+            format!("{}: {} ({})", self.category.label(), addr, self.name)
+        }
+    }
+}
+
+/// Destinos sensibles por defecto: (IP, puerto, nombre, categoría). Las autoridades de
+/// directorio de Tor son las de la configuración de Tor; conviene añadir las que cambien
+pub const DEFAULT_SENSITIVE_ENDPOINTS: &[(&str, Option<u16>, &str, EndpointCategory)] = &[
+    ("169.254.169.254", None, "AWS/GCP/Azure/OpenStack IMDS", EndpointCategory::CloudMetadata),
+    ("fd00:ec2::254", None, "AWS IMDS (IPv6)", EndpointCategory::CloudMetadata),
+    ("169.254.170.2", None, "AWS ECS task metadata", EndpointCategory::CloudMetadata),
+    ("100.100.100.200", None, "Alibaba Cloud metadata", EndpointCategory::CloudMetadata),
+    ("128.31.0.39", None, "autoridad de directorio moria1", EndpointCategory::Tor),
+    ("86.59.21.38", None, "autoridad de directorio tor26", EndpointCategory::Tor),
+    ("45.66.33.45", None, "autoridad de directorio dizum", EndpointCategory::Tor),
+    ("131.188.40.189", None, "autoridad de directorio gabelmoo", EndpointCategory::Tor),
+    ("193.23.244.244", None, "autoridad de directorio dannenberg", EndpointCategory::Tor),
+    ("171.25.193.9", None, "autoridad de directorio maatuska", EndpointCategory::Tor),
+    ("199.58.81.140", None, "autoridad de directorio longclaw", EndpointCategory::Tor),
+    ("204.13.164.118", None, "autoridad de directorio bastet", EndpointCategory::Tor),
+    ("127.0.0.1", Some(9050), "proxy SOCKS de Tor", EndpointCategory::Tor),
+    ("127.0.0.1", Some(9150), "proxy SOCKS de Tor Browser", EndpointCategory::Tor),
+];
+
+/// Puertos remotos habituales de una familia de procesos (navegadores, resolvedores DNS...).
+/// Una conexión saliente de esa familia a otro puerto se reporta como inusual.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedPorts {
+    /// Nombres de proceso de la familia (se compara sin distinguir mayúsculas)
+    pub processes: Vec<String>,
+    /// Puertos remotos habituales
+    pub ports: Vec<u16>,
+}
+
+impl ExpectedPorts {
+    /// Indica si un nombre de proceso pertenece a la familia
+    pub fn matches(&self, process_name: &str) -> bool {
+        let name = process_name.to_lowercase();
+        self.processes.iter().any(|p| name.contains(&p.to_lowercase()))
+    }
+}
+
+/// Familias de procesos con puertos conocidos: (nombres, puertos).
+/// Solo se incluyen clientes con un conjunto de puertos estable para evitar falsos positivos.
+pub const DEFAULT_EXPECTED_PORTS: &[(&[&str], &[u16])] = &[
+    // Navegadores: HTTP(S), alternativos habituales, QUIC y STUN de WebRTC
+    (
+        &["firefox", "chrome", "chromium", "msedge", "safari", "brave", "opera", "vivaldi"],
+        &[80, 443, 8080, 8443, 3478, 5228, 19302],
+    ),
+    // Resolvedores DNS
+    (&["systemd-resolve", "dnsmasq", "unbound", "mdnsresponder"], &[53, 853, 5353]),
+    // Sincronización de hora
+    (&["chronyd", "ntpd", "timesyncd"], &[123]),
+    // Gestores de paquetes
+    (&["apt", "dnf", "yum", "pacman", "brew"], &[80, 443]),
+];
+
+/// Primer puerto del rango efímero (IANA)
+pub const EPHEMERAL_PORT_START: u16 = 49152;
+
+/// Conexiones a un mismo puerto efímero remoto a partir de las que se considera inusual
+pub const EPHEMERAL_REPEAT_THRESHOLD: usize = 5;
+
 /// Monitor de red
 pub struct NetworkMonitor {
     /// Conexiones activas
     connections: Vec<Connection>,
+    /// Conexiones cerradas recientemente, de la más antigua a la más reciente
+    closed_connections: Vec<ClosedConnection>,
+    /// Conexiones cerradas que se conservan (0 = ninguna)
+    closed_history_len: usize,
     /// Historial de eventos
     events: Vec<NetworkEvent>,
-    /// Filtrar por PID
-    filter_pid: Option<u32>,
+    /// Historial de throughput por conexión
+    bandwidth: HashMap<ConnectionKey, BandwidthHistory>,
+    /// Destinos ignorados por los detectores (p. ej. el endpoint del LLM)
+    ignored_remotes: Vec<SocketAddr>,
+    /// Puertos cuya conexión se reporta como sensible
+    sensitive_ports: Vec<SensitivePort>,
+    /// Puertos habituales por familia de procesos
+    expected_ports: Vec<ExpectedPorts>,
+    /// Destinos cuya conexión se reporta por sí misma (metadata de nube, Tor...)
+    sensitive_endpoints: Vec<SensitiveEndpoint>,
+}
+
+impl Default for NetworkMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NetworkMonitor {
@@ -104,14 +422,127 @@ impl NetworkMonitor {
     pub fn new() -> Self {
         Self {
             connections: Vec::new(),
+            closed_connections: Vec::new(),
+            closed_history_len: DEFAULT_CLOSED_CONNECTIONS_HISTORY,
             events: Vec::new(),
-            filter_pid: None,
+            bandwidth: HashMap::new(),
+            ignored_remotes: Vec::new(),
+            sensitive_ports: DEFAULT_SENSITIVE_PORTS
+                .iter()
+                .map(|(port, service, category)| SensitivePort::new(*port, service, *category))
+                .collect(),
+            expected_ports: DEFAULT_EXPECTED_PORTS
+                .iter()
+                .map(|(processes, ports)| ExpectedPorts {
+                    processes: processes.iter().map(|p| p.to_string()).collect(),
+                    ports: ports.to_vec(),
+                })
+                .collect(),
+            sensitive_endpoints: DEFAULT_SENSITIVE_ENDPOINTS
+                .iter()
+                .filter_map(|(ip, port, name, category)| {
+                    Some(SensitiveEndpoint::new(ip.parse().ok()?, *port, name, *category))
+                })
+                .collect(),
+        }
+    }
+
+    /// Añadir puertos sensibles; un puerto ya conocido se reemplaza por la nueva definición
+    pub fn add_sensitive_ports(&mut self, ports: &[SensitivePort]) {
+        for port in ports {
+            self.sensitive_ports.retain(|p| p.port != port.port);
+            self.sensitive_ports.push(port.clone());
+        }
+    }
+
+    /// Añadir destinos sensibles; uno ya conocido (misma IP y puerto) se reemplaza por la nueva definición
+    pub fn add_sensitive_endpoints(&mut self, endpoints: &[SensitiveEndpoint]) {
+        for endpoint in endpoints {
+            self.sensitive_endpoints.retain(|e| e.ip != endpoint.ip || e.port != endpoint.port);
+            self.sensitive_endpoints.push(endpoint.clone());
+        }
+    }
+
+    /// Destino sensible al que corresponde una dirección remota; uno con puerto tiene
+    /// prioridad sobre el de la misma IP sin puerto
+    pub fn classify_endpoint(&self, addr: SocketAddr) -> Option<&SensitiveEndpoint> {
+        self.sensitive_endpoints
+            .iter()
+            .filter(|endpoint| endpoint.matches(addr))
+            .max_by_key(|endpoint| endpoint.port.is_some())
+    }
+
+    /// Añadir familias de procesos con sus puertos habituales; tienen prioridad sobre las incorporadas
+    pub fn add_expected_ports(&mut self, families: &[ExpectedPorts]) {
+        for family in families.iter().rev() {
+            self.expected_ports.insert(0, family.clone());
+        }
+    }
+
+    /// Detectar conexiones inusuales para el proceso: puertos fuera de los habituales de
+    /// su familia o conexiones repetidas a un mismo puerto efímero de un host
+    pub fn detect_unusual_connections(&self, pid: u32, process_name: &str) -> Vec<Detection> {
+        let events: Vec<&NetworkEvent> = self.get_events_for_pid(pid)
+            .into_iter()
+            .filter(|e| e.direction == Direction::Outbound)
+            .filter(|e| e.remote_addr.is_some_and(|addr| !self.ignored_remotes.contains(&addr)))
+            .collect();
+        let mut unusual: Vec<Detection> = Vec::new();
+        let mut push = |addr: SocketAddr, detail: Option<String>, timestamp: DateTime<Utc>| {
+            let message = match detail {
+                Some(detail) => format!("Conexión inusual: {} → {} ({})", process_name, addr, detail),
+                None => format!("Conexión inusual: {} → {}", process_name, addr),
+            };
+            if !unusual.iter().any(|d| d.message == message) {
+                unusual.push(Detection { message, timestamp });
+            }
+        };
+        
+        // La primera familia que coincide decide (las configuradas van delante)
+        if let Some(family) = self.expected_ports.iter().find(|f| f.matches(process_name)) {
+            for event in &events {
+                let Some(addr) = event.remote_addr else { continue };
+                // Los puertos sensibles ya tienen su propio hallazgo
+                if !family.ports.contains(&addr.port()) && self.classify_port(addr.port()).is_none() {
+                    push(addr, None, event.timestamp);
+                }
+            }
+        }
+        
+        // Conexiones repetidas al mismo puerto efímero de un host (canal de control improvisado)
+        let mut repeated: HashMap<SocketAddr, Vec<&NetworkEvent>> = HashMap::new();
+        for event in &events {
+            if let Some(addr) = event.remote_addr.filter(|addr| addr.port() >= EPHEMERAL_PORT_START) {
+                repeated.entry(addr).or_default().push(event);
+            }
+        }
+        let mut repeated: Vec<_> = repeated.into_iter()
+            .filter(|(_, events)| events.len() >= EPHEMERAL_REPEAT_THRESHOLD)
+            .collect();
+        repeated.sort_by_key(|(addr, _)| *addr);
+        for (addr, events) in repeated {
+            let last = events.iter().map(|e| e.timestamp).max().unwrap_or_else(Utc::now);
+            push(addr, Some(format!("{} conexiones a un puerto efímero", events.len())), last);
+        }
+        
+        unusual
+    }
+
+    /// Clasificar un puerto (None si no es sensible)
+    pub fn classify_port(&self, port: u16) -> Option<&SensitivePort> {
+        self.sensitive_ports.iter().find(|p| p.port == port)
+    }
+
+    /// Ignorar un destino en los detectores (tráfico generado por la propia herramienta)
+    pub fn ignore_remote(&mut self, addr: SocketAddr) {
+        if !self.ignored_remotes.contains(&addr) {
+            self.ignored_remotes.push(addr);
         }
     }
 
-    /// Establecer filtro por PID
-    pub fn set_pid_filter(&mut self, pid: Option<u32>) {
-        self.filter_pid = pid;
+    /// Destinos ignorados por los detectores
+    pub fn ignored_remotes(&self) -> &[SocketAddr] {
+        &self.ignored_remotes
     }
 
     /// Obtener las conexiones activas
@@ -119,90 +550,111 @@ impl NetworkMonitor {
         &self.connections
     }
 
+    /// Obtener las conexiones cerradas recientemente
+    pub fn get_closed_connections(&self) -> &[ClosedConnection] {
+        &self.closed_connections
+    }
+
+    /// Conexiones cerradas recientemente de un proceso
+    pub fn get_closed_connections_for_pid(&self, pid: u32) -> Vec<&ClosedConnection> {
+        self.closed_connections
+            .iter()
+            .filter(|closed| closed.connection.pid == Some(pid))
+            .collect()
+    }
+
+    /// Cambiar cuántas conexiones cerradas se conservan, descartando las más antiguas
+    pub fn set_closed_history_len(&mut self, len: usize) {
+        self.closed_history_len = len;
+        self.trim_closed_connections();
+    }
+
+    /// Descartar las conexiones cerradas más antiguas que exceden el historial
+    fn trim_closed_connections(&mut self) {
+        if self.closed_connections.len() > self.closed_history_len {
+            let to_remove = self.closed_connections.len() - self.closed_history_len;
+            self.closed_connections.drain(0..to_remove);
+        }
+    }
+
     /// Obtener los eventos registrados
     pub fn get_events(&self) -> &[NetworkEvent] {
         &self.events
     }
-    
-    /// Simular detección de actividad de red para pruebas
-    pub fn simulate_activity(&mut self) {
-        // Generar una conexión simulada
-        let remote_ports = [80, 443, 8080, 22, 25, 53];
-        let protocols = [Protocol::TCP, Protocol::UDP];
-        
-        let timestamp = SystemTime::now();
-        let remote_port = remote_ports[self.events.len() % remote_ports.len()];
-        let protocol = protocols[self.events.len() % protocols.len()];
-        
-        // Crear un evento simulado
-        let event = NetworkEvent {
-            timestamp: Utc.timestamp_opt(
-                timestamp.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() as i64,
-                0
-            ).unwrap(),
-            protocol,
-            direction: Direction::Outbound,
-            local_addr: "127.0.0.1:12345".parse().unwrap(),
-            remote_addr: Some(format!("93.184.216.34:{}", remote_port).parse().unwrap()),
-            state: ConnectionState::Established,
-            pid: self.filter_pid.unwrap_or(0),
-            bytes_sent: Some((1024 * ((self.events.len() % 10) + 1)) as u64),
-            bytes_received: Some((2048 * ((self.events.len() % 10) + 1)) as u64),
-        };
-        
-        self.events.push(event);
-        
-        // Limitar el historial a 100 eventos
-        if self.events.len() > 100 {
-            self.events.remove(0);
+
+    /// Tomar una muestra del throughput de cada conexión activa
+    pub fn sample_bandwidth(&mut self) {
+        for conn in &self.connections {
+            self.bandwidth
+                .entry((conn.local_addr, conn.remote_addr))
+                .or_default()
+                .push_total(conn.bytes_sent + conn.bytes_received);
         }
         
-        // Actualizar o crear conexiones
-        if self.connections.len() < 5 {
-            // Crear nuevas conexiones simuladas
-            let connection = Connection {
-                protocol,
-                local_addr: "127.0.0.1:12345".parse().unwrap(),
-                remote_addr: format!("93.184.216.34:{}", remote_port).parse().unwrap(),
-                state: Some("ESTABLISHED".to_string()),
-                pid: self.filter_pid,
-                first_seen: timestamp,
-                last_seen: timestamp,
-                bytes_sent: (1024 * ((self.connections.len() % 10) + 1)) as u64,
-                bytes_received: (2048 * ((self.connections.len() % 10) + 1)) as u64,
-            };
-            
-            self.connections.push(connection);
-        } else {
-            // Actualizar una conexión existente
-            if let Some(conn) = self.connections.iter_mut().next() {
-                conn.last_seen = timestamp;
-                conn.bytes_sent += 512;
-                conn.bytes_received += 1024;
-            }
-        }
+        // Descartar el historial de conexiones que ya no existen
+        let connections = &self.connections;
+        self.bandwidth.retain(|(local, remote), _| {
+            connections.iter().any(|c| c.local_addr == *local && c.remote_addr == *remote)
+        });
     }
 
-    /// Registrar un evento de red
+    /// Obtener el historial de throughput de una conexión
+    pub fn get_bandwidth_history(&self, conn: &Connection) -> Option<&BandwidthHistory> {
+        self.bandwidth.get(&(conn.local_addr, conn.remote_addr))
+    }
+    
+    /// Registrar un evento de red.
+    ///
+    /// Cada evento lleva la conexión a su nuevo estado: la primera vez que se ve una
+    /// conexión se añade a las activas, y al cerrarse pasa al historial de cerradas con
+    /// sus bytes y duración finales, para que las conexiones breves no se pierdan.
     pub fn record_event(&mut self, event: NetworkEvent) {
+        let now = SystemTime::now();
+        let known = self.connections.iter().any(|conn| conn.matches(&event));
+        
         // Actualizar las conexiones activas
         match event.state {
-            ConnectionState::Established | ConnectionState::Connecting | ConnectionState::Listening => {
+            ConnectionState::Closed => {
+                let (closed, open): (Vec<Connection>, Vec<Connection>) = std::mem::take(&mut self.connections)
+                    .into_iter()
+                    .partition(|conn| conn.matches(&event));
+                self.connections = open;
+                
+                for mut connection in closed {
+                    connection.state = Some(event.state.label().to_string());
+                    connection.last_seen = now;
+                    connection.bytes_sent += event.bytes_sent.unwrap_or(0);
+                    connection.bytes_received += event.bytes_received.unwrap_or(0);
+                    self.closed_connections.push(ClosedConnection { connection, closed_at: now });
+                }
+                self.trim_closed_connections();
+            }
+            _ if known => {
                 self.connections
                     .iter_mut()
-                    .filter(|conn| conn.local_addr == event.local_addr && 
-                           event.remote_addr.as_ref().map_or(false, |addr| &conn.remote_addr == addr))
+                    .filter(|conn| conn.matches(&event))
                     .for_each(|conn| {
-                        conn.last_seen = SystemTime::now();
+                        conn.state = Some(event.state.label().to_string());
+                        conn.last_seen = now;
                         conn.bytes_sent += event.bytes_sent.unwrap_or(0);
                         conn.bytes_received += event.bytes_received.unwrap_or(0);
                     });
             }
-            ConnectionState::Closed => {
-                self.connections.retain(|conn| {
-                    conn.local_addr != event.local_addr || 
-                    event.remote_addr.as_ref().map_or(true, |addr| conn.remote_addr != *addr)
-                });
+            ConnectionState::Established | ConnectionState::Connecting => {
+                // Solo se siguen las conexiones con extremo remoto (no los sockets en escucha)
+                if let Some(remote_addr) = event.remote_addr {
+                    self.connections.push(Connection {
+                        protocol: event.protocol,
+                        local_addr: event.local_addr,
+                        remote_addr,
+                        state: Some(event.state.label().to_string()),
+                        pid: Some(event.pid),
+                        first_seen: event.timestamp.into(),
+                        last_seen: now,
+                        bytes_sent: event.bytes_sent.unwrap_or(0),
+                        bytes_received: event.bytes_received.unwrap_or(0),
+                    });
+                }
             }
             _ => {}
         }
@@ -210,6 +662,54 @@ impl NetworkMonitor {
         self.events.push(event);
     }
 
+    /// Cargar eventos grabados (`monitor --sample-file`), en orden, reconstruyendo
+    /// las conexiones activas y cerradas como si se hubieran observado en vivo
+    pub fn load_events(&mut self, events: Vec<NetworkEvent>) -> usize {
+        let count = events.len();
+        for event in events {
+            self.record_event(event);
+        }
+        count
+    }
+
+    /// Sincronizar las conexiones de un proceso con una enumeración reciente:
+    /// registra las conexiones nuevas y los cambios de estado, y da por cerradas
+    /// las conocidas que ya no aparecen
+    pub fn sync_connections(&mut self, pid: u32, current: Vec<NetworkEvent>) {
+        // Último estado conocido de cada conexión del proceso
+        let mut last_events: HashMap<(SocketAddr, Option<SocketAddr>), &NetworkEvent> = HashMap::new();
+        for event in self.events.iter().filter(|e| e.pid == pid) {
+            last_events.insert((event.local_addr, event.remote_addr), event);
+        }
+        
+        let disappeared: Vec<NetworkEvent> = last_events
+            .values()
+            .filter(|last| last.state != ConnectionState::Closed)
+            .filter(|last| {
+                !current.iter().any(|e| e.local_addr == last.local_addr && e.remote_addr == last.remote_addr)
+            })
+            .map(|last| NetworkEvent {
+                state: ConnectionState::Closed,
+                timestamp: Utc::now(),
+                bytes_sent: None,
+                bytes_received: None,
+                ..(*last).clone()
+            })
+            .collect();
+        let changed: Vec<NetworkEvent> = current
+            .into_iter()
+            .filter(|event| {
+                last_events
+                    .get(&(event.local_addr, event.remote_addr))
+                    .is_none_or(|last| last.state != event.state)
+            })
+            .collect();
+        
+        for event in disappeared.into_iter().chain(changed) {
+            self.record_event(event);
+        }
+    }
+
     /// Obtener eventos para un proceso específico
     pub fn get_events_for_pid(&self, pid: u32) -> Vec<&NetworkEvent> {
         self.events.iter().filter(|e| e.pid == pid).collect()
@@ -224,42 +724,396 @@ impl NetworkMonitor {
     }
 
     /// Detectar patrones sospechosos de red
-    pub fn detect_suspicious_patterns(&self, pid: u32) -> Vec<String> {
-        let events = self.get_events_for_pid(pid);
+    pub fn detect_suspicious_patterns(&self, pid: u32) -> Vec<Detection> {
+        let events: Vec<&NetworkEvent> = self.get_events_for_pid(pid)
+            .into_iter()
+            .filter(|e| e.remote_addr.is_none_or(|addr| !self.ignored_remotes.contains(&addr)))
+            .collect();
         let mut suspicious = Vec::new();
         
         // Detector de muchas conexiones en poco tiempo
-        let mut connection_count_by_minute: HashMap<i64, usize> = HashMap::new();
-        
-        for event in &events {
-            if event.state == ConnectionState::Established {
-                let minute = event.timestamp.timestamp() / 60;
-                *connection_count_by_minute.entry(minute).or_insert(0) += 1;
-            }
-        }
-        
-        for (_minute, count) in connection_count_by_minute {
-            if count > 10 {
-                suspicious.push(format!("Alta tasa de conexiones: {} en un minuto", count));
-            }
+        let established: Vec<DateTime<Utc>> = events
+            .iter()
+            .filter(|event| event.state == ConnectionState::Established)
+            .map(|event| event.timestamp)
+            .collect();
+        for (count, last) in connection_bursts(established) {
+            suspicious.push(Detection {
+                message: format!("Alta tasa de conexiones: {} en un minuto", count),
+                timestamp: last,
+            });
         }
         
         // Detector de puertos sensibles
-        let sensitive_ports = [22, 23, 3389, 445, 135, 139];
-        
         for event in &events {
             if let Some(addr) = event.remote_addr {
-                for port in &sensitive_ports {
-                    if addr.port() == *port {
-                        suspicious.push(format!("Conexión a puerto sensible: {}", addr));
-                    }
+                if let Some(port) = self.classify_port(addr.port()) {
+                    suspicious.push(Detection {
+                        message: format!("{}: {}", port.describe(), addr),
+                        timestamp: event.timestamp,
+                    });
                 }
             }
         }
         
-        // Detector de IPs sospechosas
-        // En una implementación real, se verificaría contra listas de IPs maliciosas
+        // Detector de destinos sensibles (metadata de nube, Tor, proxies): una vez por destino
+        for event in &events {
+            let Some(addr) = event.remote_addr else { continue };
+            if let Some(endpoint) = self.classify_endpoint(addr) {
+                let message = endpoint.describe(addr);
+                if !suspicious.iter().any(|d: &Detection| d.message == message) {
+                    suspicious.push(Detection { message, timestamp: event.timestamp });
+                }
+            }
+        }
         
         suspicious
     }
-} 
+}
+
+/// Ventana de tiempo del detector de ráfagas de conexiones
+pub const CONNECTION_BURST_WINDOW: Duration = Duration::from_secs(60);
+
+/// Conexiones dentro de la ventana a partir de las cuales hay una ráfaga
+pub const CONNECTION_BURST_THRESHOLD: usize = 10;
+
+/// Ráfagas de conexiones: más de `CONNECTION_BURST_THRESHOLD` en cualquier ventana
+/// deslizante de `CONNECTION_BURST_WINDOW`, no en minutos fijos del reloj, para que una
+/// ráfaga que cruza el cambio de minuto no se reparta entre dos. Devuelve, por ráfaga,
+/// el máximo de conexiones en una ventana y la hora de la última conexión.
+pub fn connection_bursts(mut times: Vec<DateTime<Utc>>) -> Vec<(usize, DateTime<Utc>)> {
+    times.sort_unstable();
+    let window = chrono::Duration::from_std(CONNECTION_BURST_WINDOW).unwrap_or(chrono::Duration::MAX);
+    let mut bursts = Vec::new();
+    let mut current: Option<(usize, DateTime<Utc>)> = None;
+    let mut start = 0;
+    for end in 0..times.len() {
+        while times[end] - times[start] >= window {
+            start += 1;
+        }
+        let count = end - start + 1;
+        if count > CONNECTION_BURST_THRESHOLD {
+            // La ráfaga continúa mientras alguna ventana supere el umbral
+            let peak = current.map_or(count, |(peak, _)| peak.max(count));
+            current = Some((peak, times[end]));
+        } else if let Some(burst) = current.take() {
+            bursts.push(burst);
+        }
+    }
+    bursts.extend(current);
+    bursts
+}
+
+/// Conexiones abiertas por un proceso en este momento.
+///
+/// Cruza los sockets de /proc/<pid>/fd con las tablas de /proc/<pid>/net/{tcp,udp}.
+#[cfg(target_os = "linux")]
+pub fn enumerate_connections(pid: u32) -> Vec<NetworkEvent> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    
+    // Inodos de los sockets del proceso ("socket:[12345]")
+    let inodes: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy();
+            target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .map(String::from)
+        })
+        .collect();
+    if inodes.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut events = Vec::new();
+    for (table, protocol) in [("tcp", Protocol::TCP), ("tcp6", Protocol::TCP), ("udp", Protocol::UDP), ("udp6", Protocol::UDP)] {
+        let Ok(content) = std::fs::read_to_string(format!("/proc/{}/net/{}", pid, table)) else {
+            continue;
+        };
+        
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || !inodes.iter().any(|inode| inode == fields[9]) {
+                continue;
+            }
+            let (Some(local_addr), Some(remote_addr)) = (parse_proc_addr(fields[1]), parse_proc_addr(fields[2])) else {
+                continue;
+            };
+            
+            let state = match (protocol, fields[3]) {
+                (Protocol::TCP, "01") => ConnectionState::Established,
+                (Protocol::TCP, "02") | (Protocol::TCP, "03") => ConnectionState::Connecting,
+                (Protocol::TCP, "0A") => ConnectionState::Listening,
+                (Protocol::TCP, "07") => ConnectionState::Closed,
+                (Protocol::TCP, _) => ConnectionState::Closing,
+                _ => ConnectionState::Other,
+            };
+            
+            events.push(NetworkEvent {
+                pid,
+                local_addr,
+                remote_addr: (remote_addr.port() != 0).then_some(remote_addr),
+                protocol,
+                direction: if state == ConnectionState::Listening { Direction::Inbound } else { Direction::Outbound },
+                state,
+                timestamp: Utc::now(),
+                bytes_sent: None,
+                bytes_received: None,
+            });
+        }
+    }
+    
+    // Las conexiones sobre un puerto en escucha del propio proceso fueron aceptadas, no iniciadas
+    let listening: Vec<u16> = events
+        .iter()
+        .filter(|e| e.state == ConnectionState::Listening)
+        .map(|e| e.local_addr.port())
+        .collect();
+    for event in &mut events {
+        if listening.contains(&event.local_addr.port()) {
+            event.direction = Direction::Inbound;
+        }
+    }
+    events
+}
+
+/// Conexiones abiertas por un proceso en este momento (no disponible en esta plataforma)
+#[cfg(not(target_os = "linux"))]
+pub fn enumerate_connections(_pid: u32) -> Vec<NetworkEvent> {
+    Vec::new()
+}
+
+/// Decodificar una dirección de /proc/net ("0100007F:0016"; cada palabra de 32 bits en orden del host)
+#[cfg(target_os = "linux")]
+fn parse_proc_addr(field: &str) -> Option<SocketAddr> {
+    let (ip_hex, port_hex) = field.split_once(':')?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+    
+    let mut words = Vec::new();
+    for chunk in ip_hex.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        words.extend_from_slice(&word.to_ne_bytes());
+    }
+    
+    let ip = match words.len() {
+        4 => std::net::IpAddr::from(<[u8; 4]>::try_from(words.as_slice()).ok()?),
+        16 => std::net::IpAddr::from(<[u8; 16]>::try_from(words.as_slice()).ok()?),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outbound(pid: u32, remote: &str, timestamp: DateTime<Utc>) -> NetworkEvent {
+        NetworkEvent {
+            pid,
+            local_addr: "10.0.0.2:40000".parse().unwrap(),
+            remote_addr: Some(remote.parse().unwrap()),
+            protocol: Protocol::TCP,
+            direction: Direction::Outbound,
+            state: ConnectionState::Established,
+            timestamp,
+            bytes_sent: None,
+            bytes_received: None,
+        }
+    }
+
+    #[test]
+    fn configured_sensitive_ports_extend_and_override_defaults() {
+        let config: crate::config::ConfigFile = toml::from_str(
+            r#"
+            [[sensitive_ports]]
+            port = 8443
+            service = "panel"
+            category = "remote-access"
+
+            [[sensitive_ports]]
+            port = 22
+            service = "SFTP"
+
+            [[sensitive_ports]]
+            port = 4444
+            "#,
+        )
+        .unwrap();
+        let mut monitor = NetworkMonitor::new();
+        monitor.add_sensitive_ports(&config.sensitive_ports.unwrap());
+
+        let describe = |port: u16| monitor.classify_port(port).map(|p| p.describe());
+        assert_eq!(describe(3389).as_deref(), Some("Conexión a puerto de acceso remoto (3389/RDP)"));
+        assert_eq!(describe(8443).as_deref(), Some("Conexión a puerto de acceso remoto (8443/panel)"));
+        assert_eq!(describe(22).as_deref(), Some("Conexión a puerto sensible (22/SFTP)"));
+        assert_eq!(describe(4444).as_deref(), Some("Conexión a puerto sensible (4444)"));
+        assert_eq!(describe(443), None);
+    }
+
+    #[test]
+    fn sensitive_port_connections_are_detected() {
+        let mut monitor = NetworkMonitor::new();
+        monitor.add_sensitive_ports(&[SensitivePort::new(4444, "shell", PortCategory::Other)]);
+        monitor.load_events(vec![
+            outbound(5, "203.0.113.7:4444", Utc::now()),
+            outbound(5, "203.0.113.7:443", Utc::now()),
+        ]);
+        let messages: Vec<String> = monitor.detect_suspicious_patterns(5).into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["Conexión a puerto sensible (4444/shell): 203.0.113.7:4444".to_string()]);
+    }
+
+    #[test]
+    fn ignored_remotes_do_not_trigger_detectors() {
+        let mut monitor = NetworkMonitor::new();
+        let now = Utc::now();
+        monitor.load_events(
+            (0..EPHEMERAL_REPEAT_THRESHOLD as i64)
+                .map(|i| outbound(7, "10.0.0.9:50000", now + chrono::Duration::seconds(i)))
+                .collect(),
+        );
+        assert_eq!(monitor.detect_unusual_connections(7, "worker").len(), 1);
+
+        // El endpoint del LLM lo usa ShadowTrace, no el proceso
+        monitor.ignore_remote("10.0.0.9:50000".parse().unwrap());
+        assert!(monitor.detect_unusual_connections(7, "worker").is_empty());
+    }
+
+    #[test]
+    fn browser_on_unexpected_port_is_unusual() {
+        let mut monitor = NetworkMonitor::new();
+        monitor.load_events(vec![
+            outbound(8, "198.51.100.4:443", Utc::now()),
+            outbound(8, "198.51.100.4:4444", Utc::now()),
+        ]);
+        let messages: Vec<String> = monitor.detect_unusual_connections(8, "firefox-bin").into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["Conexión inusual: firefox-bin → 198.51.100.4:4444".to_string()]);
+
+        // Un proceso sin familia conocida no tiene puertos esperados
+        assert!(monitor.detect_unusual_connections(8, "worker").is_empty());
+    }
+
+    #[test]
+    fn configured_family_takes_precedence_over_defaults() {
+        let mut monitor = NetworkMonitor::new();
+        monitor.add_expected_ports(&[ExpectedPorts { processes: vec!["Firefox".to_string()], ports: vec![4444] }]);
+        monitor.load_events(vec![
+            outbound(8, "198.51.100.4:4444", Utc::now()),
+            outbound(8, "198.51.100.4:443", Utc::now()),
+        ]);
+        let messages: Vec<String> = monitor.detect_unusual_connections(8, "firefox").into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["Conexión inusual: firefox → 198.51.100.4:443".to_string()]);
+    }
+
+    #[test]
+    fn repeated_connections_to_an_ephemeral_port_are_unusual() {
+        let mut monitor = NetworkMonitor::new();
+        let now = Utc::now();
+        let mut events: Vec<NetworkEvent> = (0..EPHEMERAL_REPEAT_THRESHOLD as i64)
+            .map(|i| outbound(9, "192.0.2.10:51515", now + chrono::Duration::seconds(i)))
+            .collect();
+        // Por debajo del umbral en otro host
+        events.push(outbound(9, "192.0.2.11:51515", now));
+        monitor.load_events(events);
+
+        let detections = monitor.detect_unusual_connections(9, "worker");
+        assert_eq!(detections.len(), 1);
+        assert_eq!(
+            detections[0].message,
+            format!("Conexión inusual: worker → 192.0.2.10:51515 ({} conexiones a un puerto efímero)", EPHEMERAL_REPEAT_THRESHOLD),
+        );
+        assert_eq!(detections[0].timestamp, now + chrono::Duration::seconds(EPHEMERAL_REPEAT_THRESHOLD as i64 - 1));
+    }
+
+    #[test]
+    fn cloud_metadata_access_is_reported_once() {
+        let mut monitor = NetworkMonitor::new();
+        let now = Utc::now();
+        monitor.load_events(vec![
+            outbound(11, "169.254.169.254:80", now),
+            outbound(11, "169.254.169.254:80", now + chrono::Duration::seconds(2)),
+            outbound(11, "8.8.8.8:443", now),
+        ]);
+
+        let detections = monitor.detect_suspicious_patterns(11);
+        assert_eq!(detections, vec![Detection {
+            message: "Acceso a metadata de nube: 169.254.169.254:80 (AWS/GCP/Azure/OpenStack IMDS)".to_string(),
+            timestamp: now,
+        }]);
+    }
+
+    #[test]
+    fn tor_endpoints_are_reported_with_their_category() {
+        let mut monitor = NetworkMonitor::new();
+        let now = Utc::now();
+        monitor.load_events(vec![
+            outbound(12, "[::ffff:128.31.0.39]:9131", now),
+            outbound(12, "127.0.0.1:9050", now),
+            outbound(12, "127.0.0.1:8080", now),
+        ]);
+
+        let messages: Vec<String> = monitor.detect_suspicious_patterns(12).into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec![
+            "Conexión a la red Tor: [::ffff:128.31.0.39]:9131 (autoridad de directorio moria1)".to_string(),
+            "Conexión a la red Tor: 127.0.0.1:9050 (proxy SOCKS de Tor)".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn configured_endpoints_replace_builtin_ones_with_the_same_address() {
+        let mut monitor = NetworkMonitor::new();
+        monitor.add_sensitive_endpoints(&[
+            SensitiveEndpoint::new("127.0.0.1".parse().unwrap(), Some(9050), "proxy corporativo", EndpointCategory::Proxy),
+            SensitiveEndpoint::new("198.51.100.7".parse().unwrap(), None, "", EndpointCategory::Other),
+        ]);
+
+        let proxy = monitor.classify_endpoint("127.0.0.1:9050".parse().unwrap()).unwrap();
+        assert_eq!(proxy.describe("127.0.0.1:9050".parse().unwrap()), "Conexión a un proxy de anonimato: 127.0.0.1:9050 (proxy corporativo)");
+        let other = monitor.classify_endpoint("198.51.100.7:22".parse().unwrap()).unwrap();
+        assert_eq!(other.describe("198.51.100.7:22".parse().unwrap()), "Conexión a un destino sensible: 198.51.100.7:22");
+        assert!(monitor.classify_endpoint("127.0.0.1:9051".parse().unwrap()).is_none());
+    }
+
+    fn at(base: DateTime<Utc>, seconds: &[i64]) -> Vec<DateTime<Utc>> {
+        seconds.iter().map(|s| base + chrono::Duration::seconds(*s)).collect()
+    }
+
+    #[test]
+    fn burst_across_a_minute_boundary_is_flagged() {
+        // 15 conexiones entre hh:00:52 y hh:01:06: ningún minuto fijo pasa de 10
+        let base: DateTime<Utc> = "2026-10-16T10:00:52Z".parse().unwrap();
+        let times = at(base, &(0..15).collect::<Vec<_>>());
+        assert_eq!(connection_bursts(times), vec![(15, base + chrono::Duration::seconds(14))]);
+    }
+
+    #[test]
+    fn threshold_connections_are_not_a_burst() {
+        let base = Utc::now();
+        assert!(connection_bursts(at(base, &(0..CONNECTION_BURST_THRESHOLD as i64).collect::<Vec<_>>())).is_empty());
+    }
+
+    #[test]
+    fn steady_rate_below_the_threshold_is_not_a_burst() {
+        // Una conexión cada 7 s durante diez minutos: 9 por ventana de 60 s
+        let base = Utc::now();
+        let times = at(base, &(0..90).map(|i| i * 7).collect::<Vec<_>>());
+        assert!(connection_bursts(times).is_empty());
+    }
+
+    #[test]
+    fn separate_bursts_are_reported_separately() {
+        let base: DateTime<Utc> = "2026-10-16T10:00:00Z".parse().unwrap();
+        let mut seconds: Vec<i64> = (0..12).collect();
+        seconds.extend(600..620);
+        // El orden de llegada no importa
+        seconds.reverse();
+        let bursts = connection_bursts(at(base, &seconds));
+        assert_eq!(bursts, vec![
+            (12, base + chrono::Duration::seconds(11)),
+            (20, base + chrono::Duration::seconds(619)),
+        ]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::network::Direction;
+use crate::process::format_memory;
+use crate::reports::{Finding, Report, RiskLevel, SeverityLevel};
+
+/// Rutas y destinos más frecuentes que se listan en el análisis
+const TOP_ENTRIES: usize = 5;
+
+/// Hallazgos que describen la propia sesión y no el comportamiento del proceso
+const SESSION_FINDINGS: &[&str] = &["llm_analysis", "offline_analysis", "llm_reduced_context", "monitoring", "system_logs"];
+
+/// Indicador del análisis: un hallazgo de los detectores
+#[derive(Debug, Clone, PartialEq)]
+pub struct Indicator {
+    /// Severidad
+    pub severity: SeverityLevel,
+    /// Detector que lo generó
+    pub title: String,
+    /// Descripción
+    pub description: String,
+}
+
+/// Resultado estructurado del análisis heurístico: lo que la TUI dibuja con un diseño
+/// fijo (indicador de riesgo, indicadores y recomendaciones) en lugar de Markdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisResult {
+    /// Nivel de riesgo
+    pub risk: RiskLevel,
+    /// Puntuación de riesgo (0-100)
+    pub risk_score: u32,
+    /// Resumen de la actividad registrada
+    pub summary: String,
+    /// Hallazgos de los detectores, de más a menos graves
+    pub indicators: Vec<Indicator>,
+    /// Recomendaciones de los hallazgos, sin repetir, y una general según el riesgo
+    pub recommendations: Vec<String>,
+}
+
+impl AnalysisResult {
+    /// Construir el resultado a partir de los datos del reporte
+    pub fn from_report(report: &Report) -> Self {
+        let mut indicators: Vec<&Finding> = report.findings
+            .iter()
+            .filter(|finding| finding.severity != SeverityLevel::Info)
+            .filter(|finding| !SESSION_FINDINGS.contains(&finding.title.as_str()))
+            .collect();
+        indicators.sort_by_key(|finding| std::cmp::Reverse(finding.severity.rank()));
+        let risk = report.risk_level();
+
+        let summary = format!(
+            "Durante {} s se registraron {} operaciones de archivo y {} eventos de red. {}",
+            report.duration.as_secs(),
+            report.file_activities.len(),
+            report.network_events.len(),
+            match indicators.len() {
+                0 => "Los detectores no encontraron indicios sospechosos.".to_string(),
+                1 => "Los detectores encontraron 1 indicio que conviene revisar.".to_string(),
+                n => format!("Los detectores encontraron {} indicios que conviene revisar.", n),
+            },
+        );
+
+        let mut recommendations: Vec<String> = indicators
+            .iter()
+            .filter_map(|finding| finding.recommendation.clone())
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+        recommendations.push(match risk {
+            RiskLevel::Low => "Sin acciones inmediatas; un monitoreo más largo daría más contexto",
+            RiskLevel::Medium => "Revisar los indicadores y confirmar que la actividad es la esperada",
+            RiskLevel::High => "Investigar el proceso cuanto antes y restringir su acceso a red y archivos sensibles",
+            RiskLevel::Critical => "Aislar el equipo o detener el proceso y conservar el reporte como evidencia",
+        }.to_string());
+
+        Self {
+            risk,
+            risk_score: report.risk_score(),
+            summary,
+            indicators: indicators
+                .into_iter()
+                .map(|finding| Indicator {
+                    severity: finding.severity,
+                    title: finding.title.clone(),
+                    description: finding.description.clone(),
+                })
+                .collect(),
+            recommendations,
+        }
+    }
+}
+
+/// Análisis de comportamiento sin LLM (`--offline`, tecla `d` de la TUI), construido
+/// con reglas a partir de los datos reales del reporte: recursos, actividad de archivos
+/// y de red, hallazgos de los detectores y sus recomendaciones. Se devuelve en Markdown
+/// y se etiqueta como generado sin IA.
+pub fn heuristic_analysis(report: &Report, cpu_history: &[f32]) -> String {
+    let mut md = String::from("## Análisis Heurístico (sin IA)\n\n");
+    let process = report.processes.first();
+    if let Some(process) = process {
+        md.push_str(&format!("**Proceso:** {} (PID: {})\n\n", process.name, process.pid));
+    }
+
+    let result = AnalysisResult::from_report(report);
+    md.push_str(&format!("**Evaluación de riesgo:** {} ({}/100)\n\n", result.risk.label(), result.risk_score));
+    md.push_str(&format!("**Resumen:** {}\n\n", result.summary));
+
+    // Recursos
+    md.push_str("### Recursos\n\n");
+    if let Some(process) = process {
+        if cpu_history.is_empty() {
+            md.push_str(&format!("- CPU: {:.2}%\n", process.cpu_usage));
+        } else {
+            let average = cpu_history.iter().sum::<f32>() / cpu_history.len() as f32;
+            let max = cpu_history.iter().copied().fold(0.0f32, f32::max);
+            md.push_str(&format!(
+                "- CPU: media {:.2}%, máxima {:.2}% ({} muestras)\n",
+                average, max, cpu_history.len()
+            ));
+        }
+        md.push_str(&format!("- Memoria: {}\n", format_memory(process.memory_usage)));
+        if let Some(threads) = process.thread_count {
+            md.push_str(&format!("- Hilos: {}\n", threads));
+        }
+    }
+    md.push('\n');
+
+    // Actividad de archivos
+    md.push_str("### Actividad de Archivos\n\n");
+    if report.file_activities.is_empty() {
+        md.push_str("- No se registró actividad de archivos\n");
+    } else {
+        let mut operations: HashMap<&str, usize> = HashMap::new();
+        let mut paths: HashMap<String, usize> = HashMap::new();
+        for activity in &report.file_activities {
+            *operations.entry(activity.operation.label()).or_insert(0) += 1;
+            *paths.entry(activity.path.display().to_string()).or_insert(0) += 1;
+        }
+        md.push_str(&format!("- Operaciones: {}\n", join_counts(operations)));
+        md.push_str(&format!("- Archivos distintos: {}\n", paths.len()));
+        for (path, count) in top(paths) {
+            md.push_str(&format!("  - `{}` ({})\n", path, count));
+        }
+    }
+    md.push('\n');
+
+    // Actividad de red
+    md.push_str("### Actividad de Red\n\n");
+    if report.network_events.is_empty() && report.closed_connections.is_empty() {
+        md.push_str("- No se registró actividad de red\n");
+    } else {
+        let outbound = report.network_events
+            .iter()
+            .filter(|event| matches!(event.direction, Direction::Outbound))
+            .count();
+        md.push_str(&format!(
+            "- Conexiones salientes: {}, entrantes: {}\n",
+            outbound,
+            report.network_events.len() - outbound
+        ));
+        let mut destinations: HashMap<String, usize> = HashMap::new();
+        for addr in report.network_events.iter().filter_map(|event| event.remote_addr) {
+            *destinations.entry(addr.ip().to_string()).or_insert(0) += 1;
+        }
+        md.push_str(&format!("- Destinos distintos: {}\n", destinations.len()));
+        for (destination, count) in top(destinations) {
+            md.push_str(&format!("  - `{}` ({})\n", destination, count));
+        }
+        if !report.closed_connections.is_empty() {
+            md.push_str(&format!("- Conexiones ya cerradas: {}\n", report.closed_connections.len()));
+        }
+    }
+    md.push('\n');
+
+    // Indicadores
+    md.push_str("### Indicadores\n\n");
+    if result.indicators.is_empty() {
+        md.push_str("- Ninguno: el comportamiento observado no activó las reglas de ShadowTrace\n");
+    }
+    for indicator in &result.indicators {
+        md.push_str(&format!("- **{}** {}: {}\n", indicator.severity.marker(), indicator.title, indicator.description));
+    }
+    md.push('\n');
+
+    // Recomendaciones: las de los hallazgos y una general según el riesgo
+    md.push_str("### Recomendaciones\n\n");
+    for recommendation in &result.recommendations {
+        md.push_str(&format!("- {}\n", recommendation));
+    }
+    md.push('\n');
+
+    md.push_str(
+        "*Análisis heurístico generado sin IA a partir de los datos recopilados por ShadowTrace; \
+         no interpreta el propósito del proceso como lo haría un modelo.*"
+    );
+    md
+}
+
+/// Recuentos "nombre N" de mayor a menor
+fn join_counts(counts: HashMap<&str, usize>) -> String {
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|(name, count)| format!("{} {}", name, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Las `TOP_ENTRIES` entradas más frecuentes
+fn top(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(TOP_ENTRIES);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn finding(title: &str, severity: SeverityLevel, recommendation: Option<&str>) -> Finding {
+        Finding {
+            title: title.to_string(),
+            description: format!("{} detectado", title),
+            severity,
+            recommendation: recommendation.map(str::to_string),
+            affected_resources: Vec::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn structured_result_orders_indicators_and_dedups_recommendations() {
+        let mut report = Report::new("Análisis");
+        report.add_finding(finding("network", SeverityLevel::Warning, Some("Revisar el destino")));
+        report.add_finding(finding("masquerade", SeverityLevel::Critical, Some("Revisar el destino")));
+        report.add_finding(finding("offline_analysis", SeverityLevel::Warning, None));
+
+        let result = AnalysisResult::from_report(&report);
+        let titles: Vec<&str> = result.indicators.iter().map(|indicator| indicator.title.as_str()).collect();
+        assert_eq!(titles, vec!["masquerade", "network"]);
+        assert_eq!(result.risk, report.risk_level());
+        assert_eq!(result.recommendations.len(), 2);
+        assert_eq!(result.recommendations[0], "Revisar el destino");
+        assert!(result.summary.ends_with("Los detectores encontraron 2 indicios que conviene revisar."));
+
+        // El Markdown se construye con los mismos datos
+        let markdown = heuristic_analysis(&report, &[]);
+        assert!(markdown.contains(&format!("**Resumen:** {}", result.summary)));
+        assert!(markdown.contains("- Revisar el destino\n"));
+    }
+}
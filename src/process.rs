@@ -1,7 +1,13 @@
+use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
 use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
 
+use crate::error::AppError;
+
 /// Estructura que representa un proceso monitorizado
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
@@ -13,21 +19,951 @@ pub struct ProcessInfo {
     pub path: Option<String>,
     /// Argumentos de línea de comandos
     pub cmd_line: Option<Vec<String>>,
+    /// Indica si `cmd_line` se recortó (ver `max_cmd_args`)
+    #[serde(default)]
+    pub cmd_truncated: bool,
     /// Usuario que ejecuta el proceso
     pub user: Option<String>,
     /// Uso de CPU
     pub cpu_usage: f32,
-    /// Uso de memoria (KB)
+    /// Memoria residente en bytes (ver `format_memory`)
     pub memory_usage: u64,
     /// Tiempo de inicio
     pub start_time: DateTime<Utc>,
     /// Procesos hijos
     pub children: Vec<u32>,
+    /// Número de hilos (solo disponible en Linux)
+    #[serde(default)]
+    pub thread_count: Option<usize>,
+    /// Estado de planificación del proceso
+    #[serde(default)]
+    pub status: ProcessStatus,
+    /// El ejecutable se borró del disco mientras el proceso sigue en marcha
+    /// (solo disponible en Linux)
+    #[serde(default)]
+    pub deleted_exe: Option<bool>,
+    /// Variables de entorno (`CLAVE=valor`); solo al consultar un proceso concreto y
+    /// redactadas antes de guardarlas o enviarlas (ver `Redactor::redact_env`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environ: Option<Vec<String>>,
+}
+
+/// Identidad estable de un proceso. El sistema reutiliza los PIDs, pero no la
+/// combinación de PID, hora de inicio y ejecutable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProcessIdentity {
+    /// ID del proceso
+    pub pid: u32,
+    /// Hora de inicio
+    pub start_time: DateTime<Utc>,
+    /// Ruta del ejecutable, si se pudo leer
+    pub path: Option<String>,
+}
+
+impl ProcessIdentity {
+    /// Indica si `process` es el mismo proceso. Una ruta que no se pudo leer en
+    /// alguna de las dos lecturas no cuenta como diferencia.
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        let other = process.identity();
+        self.pid == other.pid
+            && self.start_time == other.start_time
+            && (self.path.is_none() || other.path.is_none() || self.path == other.path)
+    }
+
+    /// Indica si `process` es otro proceso que ha reutilizado el mismo PID
+    pub fn is_reused_by(&self, process: &ProcessInfo) -> bool {
+        process.pid == self.pid && !self.matches(process)
+    }
+}
+
+/// Argumentos de la línea de comandos que se conservan por proceso por defecto
+pub const DEFAULT_MAX_CMD_ARGS: usize = 5;
+
+/// Milisegundos durante los que las búsquedas por nombre reutilizan el último escaneo
+/// completo del sistema por defecto
+pub const DEFAULT_REFRESH_THROTTLE_MS: u64 = 1000;
+
+/// Rutas esperadas de binarios conocidos del sistema, por nombre de proceso
+pub const EXPECTED_SYSTEM_PATHS: &[(&str, &[&str])] = &[
+    ("sshd", &["/usr/sbin/sshd", "/usr/bin/sshd", "/sbin/sshd"]),
+    ("systemd", &["/usr/lib/systemd/systemd", "/lib/systemd/systemd", "/usr/bin/systemd", "/sbin/init"]),
+    ("init", &["/sbin/init", "/usr/sbin/init", "/usr/lib/systemd/systemd", "/lib/systemd/systemd"]),
+    ("cron", &["/usr/sbin/cron", "/usr/bin/cron"]),
+    ("crond", &["/usr/sbin/crond", "/usr/bin/crond"]),
+    ("sudo", &["/usr/bin/sudo", "/bin/sudo"]),
+    ("rsyslogd", &["/usr/sbin/rsyslogd"]),
+    ("dbus-daemon", &["/usr/bin/dbus-daemon", "/bin/dbus-daemon"]),
+    ("launchd", &["/sbin/launchd"]),
+    ("svchost.exe", &["c:\\windows\\system32\\svchost.exe", "c:\\windows\\syswow64\\svchost.exe"]),
+    ("lsass.exe", &["c:\\windows\\system32\\lsass.exe"]),
+    ("csrss.exe", &["c:\\windows\\system32\\csrss.exe"]),
+    ("winlogon.exe", &["c:\\windows\\system32\\winlogon.exe"]),
+    ("services.exe", &["c:\\windows\\system32\\services.exe"]),
+    ("explorer.exe", &["c:\\windows\\explorer.exe"]),
+];
+
+/// Intérpretes cuyo proceso toma el nombre del script que ejecutan
+const SCRIPT_INTERPRETERS: &[&str] = &["sh", "bash", "dash", "zsh", "ksh", "fish", "python", "perl", "ruby", "node", "php"];
+
+/// Ejecutables que renombran legítimamente sus procesos (p. ej. "Isolated Web Co" de Firefox)
+const SELF_RENAMING_EXECUTABLES: &[&str] = &["firefox", "firefox-esr", "thunderbird", "systemd"];
+
+/// Nombre de archivo de una ruta, con separadores de Unix o de Windows
+fn path_basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Longitud a la que Linux recorta los nombres de proceso
+const COMM_NAME_LEN: usize = 15;
+
+/// Indica si un nombre de proceso corresponde a un ejecutable. Linux recorta los
+/// nombres a 15 caracteres y los intérpretes añaden la versión (`python3` → `python3.11`)
+fn name_matches_executable(name: &str, executable: &str) -> bool {
+    let name = name.to_lowercase();
+    let executable = executable.to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+    if name == executable || (name.chars().count() == COMM_NAME_LEN && executable.starts_with(&name)) {
+        return true;
+    }
+    executable
+        .strip_prefix(&name)
+        .is_some_and(|version| version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+/// Indica si un ejecutable es un intérprete de scripts, con o sin versión
+fn is_script_interpreter(executable: &str) -> bool {
+    let executable = executable.to_lowercase();
+    let unversioned = executable.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    SCRIPT_INTERPRETERS.contains(&unversioned)
+}
+
+/// Diferencia de CPU (en puntos porcentuales) a partir de la cual se reporta
+const COMPARE_CPU_DELTA: f32 = 10.0;
+
+/// Proporción entre memorias a partir de la cual se reporta la diferencia
+const COMPARE_MEMORY_RATIO: f64 = 2.0;
+
+impl ProcessInfo {
+    /// Ruta del ejecutable, si se pudo leer
+    fn known_path(&self) -> Option<&str> {
+        self.path.as_deref().filter(|path| !path.is_empty())
+    }
+
+    /// Identidad estable del proceso (PID, hora de inicio y ejecutable)
+    pub fn identity(&self) -> ProcessIdentity {
+        ProcessIdentity {
+            pid: self.pid,
+            start_time: self.start_time,
+            path: self.known_path().map(str::to_string),
+        }
+    }
+
+    /// El ejecutable se borró del disco y el proceso sigue en marcha
+    pub fn has_deleted_exe(&self) -> bool {
+        self.deleted_exe == Some(true)
+    }
+
+    /// Detectar si el proceso se hace pasar por otro: el nombre no coincide con su
+    /// ejecutable, o un binario conocido del sistema se ejecuta desde otra ruta.
+    /// Devuelve la descripción del problema, o None si no hay indicios
+    pub fn masquerade(&self) -> Option<String> {
+        // Sin ruta (hilos del kernel, procesos de otros usuarios) no hay nada que comparar
+        let path = self.known_path()?;
+        // Un binario actualizado o borrado sigue ejecutándose con el sufijo " (deleted)"
+        let path = path.strip_suffix(DELETED_SUFFIX).unwrap_or(path);
+        let executable = path_basename(path);
+
+        if let Some((_, expected)) = EXPECTED_SYSTEM_PATHS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.name))
+        {
+            if !expected.iter().any(|expected| expected.eq_ignore_ascii_case(path)) {
+                return Some(format!(
+                    "{} (PID {}) se ejecuta desde {}, fuera de su ubicación habitual ({})",
+                    self.name, self.pid, path, expected.join(", ")
+                ));
+            }
+        }
+
+        let renames_itself = SELF_RENAMING_EXECUTABLES
+            .iter()
+            .any(|renaming| renaming.eq_ignore_ascii_case(executable));
+        if renames_itself || name_matches_executable(&self.name, executable) {
+            return None;
+        }
+        // Un script toma el nombre de su archivo, no el del intérprete
+        let interpreted = is_script_interpreter(executable);
+        let script = self.cmd_line.as_ref().and_then(|cmd| cmd.get(1)).map(|arg| path_basename(arg));
+        if interpreted && script.is_some_and(|script| name_matches_executable(&self.name, script)) {
+            return None;
+        }
+
+        Some(format!(
+            "El proceso {} (PID {}) no coincide con su ejecutable {}",
+            self.name, self.pid, path
+        ))
+    }
+
+    /// Comparar con otro proceso (p. ej. un binario legítimo y uno que se hace pasar por él)
+    pub fn compare(&self, other: &ProcessInfo) -> ProcessComparison {
+        let mut comparison = ProcessComparison::default();
+        let same_name = self.name == other.name;
+
+        if !same_name {
+            comparison.push("Nombre", &self.name, &other.name, false);
+        }
+
+        let left_path = self.known_path();
+        let right_path = other.known_path();
+        if left_path != right_path {
+            // El mismo nombre desde otra ruta es la señal clásica de suplantación;
+            // un ejecutable borrado del disco también lo es
+            let deleted = [left_path, right_path]
+                .iter()
+                .any(|path| path.is_some_and(|path| path.ends_with(DELETED_SUFFIX)));
+            let relocated = same_name && left_path.is_some() && right_path.is_some();
+            comparison.push(
+                "Ruta",
+                left_path.unwrap_or("-"),
+                right_path.unwrap_or("-"),
+                relocated || deleted,
+            );
+        }
+
+        let left_cmd = self.cmd_line.as_ref().map(|cmd| cmd.join(" ")).unwrap_or_default();
+        let right_cmd = other.cmd_line.as_ref().map(|cmd| cmd.join(" ")).unwrap_or_default();
+        if left_cmd != right_cmd {
+            comparison.push("Comandos", &left_cmd, &right_cmd, false);
+        }
+
+        if self.user != other.user {
+            comparison.push(
+                "Usuario",
+                self.user.as_deref().unwrap_or("-"),
+                other.user.as_deref().unwrap_or("-"),
+                false,
+            );
+        }
+
+        if self.status != other.status {
+            comparison.push("Estado", self.status.label(), other.status.label(), false);
+        }
+
+        if self.thread_count != other.thread_count {
+            let threads = |count: Option<usize>| count.map_or("-".to_string(), |count| count.to_string());
+            comparison.push("Hilos", &threads(self.thread_count), &threads(other.thread_count), false);
+        }
+
+        if (self.cpu_usage - other.cpu_usage).abs() >= COMPARE_CPU_DELTA {
+            comparison.push(
+                "CPU",
+                &format!("{:.1}%", self.cpu_usage),
+                &format!("{:.1}%", other.cpu_usage),
+                false,
+            );
+        }
+
+        let (smaller, larger) = if self.memory_usage < other.memory_usage {
+            (self.memory_usage, other.memory_usage)
+        } else {
+            (other.memory_usage, self.memory_usage)
+        };
+        if larger as f64 >= smaller.max(1) as f64 * COMPARE_MEMORY_RATIO {
+            comparison.push(
+                "Memoria",
+                &format_memory(self.memory_usage),
+                &format_memory(other.memory_usage),
+                false,
+            );
+        }
+
+        comparison
+    }
+}
+
+/// Campo en el que difieren dos procesos comparados
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDifference {
+    /// Nombre del campo
+    pub field: &'static str,
+    /// Valor en el primer proceso
+    pub left: String,
+    /// Valor en el segundo proceso
+    pub right: String,
+    /// Indica si la diferencia sugiere suplantación
+    pub suspicious: bool,
+}
+
+/// Resultado de comparar dos procesos con `ProcessInfo::compare`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessComparison {
+    /// Campos que difieren, en orden de presentación
+    pub differences: Vec<FieldDifference>,
+}
+
+impl ProcessComparison {
+    fn push(&mut self, field: &'static str, left: &str, right: &str, suspicious: bool) {
+        self.differences.push(FieldDifference {
+            field,
+            left: left.to_string(),
+            right: right.to_string(),
+            suspicious,
+        });
+    }
+
+    /// Diferencia en un campo concreto
+    pub fn difference(&self, field: &str) -> Option<&FieldDifference> {
+        self.differences.iter().find(|difference| difference.field == field)
+    }
+
+    /// Indica si alguna diferencia sugiere que un proceso se hace pasar por el otro
+    pub fn is_masquerade(&self) -> bool {
+        self.differences.iter().any(|difference| difference.suspicious)
+    }
+
+    /// Resumen legible de las diferencias
+    pub fn summary(&self) -> String {
+        if self.differences.is_empty() {
+            return "Sin diferencias relevantes".to_string();
+        }
+
+        let mut summary = String::new();
+        if self.is_masquerade() {
+            summary.push_str("⚠️ Posible suplantación de proceso\n");
+        }
+        for difference in &self.differences {
+            summary.push_str(&format!(
+                "{}{}: {} ≠ {}\n",
+                if difference.suspicious { "⚠️ " } else { "" },
+                difference.field, difference.left, difference.right
+            ));
+        }
+        summary
+    }
+}
+
+/// Estado de un proceso, independiente de la versión de sysinfo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessStatus {
+    /// En ejecución o listo para ejecutarse
+    Running,
+    /// Durmiendo a la espera de un evento
+    Sleeping,
+    /// Esperando E/S de disco sin poder interrumpirse
+    DiskSleep,
+    /// Hilo del kernel inactivo
+    Idle,
+    /// Detenido por una señal (SIGSTOP, SIGTSTP...)
+    Stopped,
+    /// Detenido por un depurador
+    Tracing,
+    /// Terminado pero sin recoger por su padre
+    Zombie,
+    /// Terminado
+    Dead,
+    /// Estado no reconocido
+    #[default]
+    Unknown,
+}
+
+impl ProcessStatus {
+    /// Nombre legible del estado
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProcessStatus::Running => "en ejecución",
+            ProcessStatus::Sleeping => "durmiendo",
+            ProcessStatus::DiskSleep => "esperando disco",
+            ProcessStatus::Idle => "inactivo",
+            ProcessStatus::Stopped => "detenido",
+            ProcessStatus::Tracing => "depurándose",
+            ProcessStatus::Zombie => "zombi",
+            ProcessStatus::Dead => "terminado",
+            ProcessStatus::Unknown => "desconocido",
+        }
+    }
+
+    /// Indica si el proceso ya terminó (aunque siga en la tabla de procesos)
+    pub fn is_terminated(&self) -> bool {
+        matches!(self, ProcessStatus::Zombie | ProcessStatus::Dead)
+    }
+}
+
+impl From<sysinfo::ProcessStatus> for ProcessStatus {
+    fn from(status: sysinfo::ProcessStatus) -> Self {
+        use sysinfo::ProcessStatus as Sys;
+        match status {
+            Sys::Run | Sys::Waking | Sys::Wakekill => ProcessStatus::Running,
+            Sys::Sleep | Sys::Parked | Sys::LockBlocked => ProcessStatus::Sleeping,
+            Sys::UninterruptibleDiskSleep => ProcessStatus::DiskSleep,
+            Sys::Idle => ProcessStatus::Idle,
+            Sys::Stop => ProcessStatus::Stopped,
+            Sys::Tracing => ProcessStatus::Tracing,
+            Sys::Zombie => ProcessStatus::Zombie,
+            Sys::Dead => ProcessStatus::Dead,
+            Sys::Unknown(_) => ProcessStatus::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Sufijo que añade el kernel a los enlaces de /proc cuando el archivo ya no existe
+pub const DELETED_SUFFIX: &str = " (deleted)";
+
+/// Indica si el ejecutable de un proceso se borró del disco (/proc/<pid>/exe termina
+/// en " (deleted)"). None si no se puede leer el enlace
+#[cfg(target_os = "linux")]
+pub fn deleted_exe(pid: u32) -> Option<bool> {
+    exe_link_deleted(Path::new(&format!("/proc/{}/exe", pid)))
+}
+
+/// Indica si el ejecutable de un proceso se borró del disco (no disponible en esta plataforma)
+#[cfg(not(target_os = "linux"))]
+pub fn deleted_exe(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Indica si un enlace al estilo de /proc/<pid>/exe apunta a un archivo borrado
+pub fn exe_link_deleted(link: &Path) -> Option<bool> {
+    std::fs::read_link(link)
+        .ok()
+        .map(|target| target.to_string_lossy().ends_with(DELETED_SUFFIX))
+}
+
+/// Número de hilos de un proceso (entradas de /proc/<pid>/task)
+#[cfg(target_os = "linux")]
+pub fn thread_count(pid: u32) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/task", pid))
+        .ok()
+        .map(|entries| entries.count())
+}
+
+/// Número de hilos de un proceso (no disponible en esta plataforma)
+#[cfg(not(target_os = "linux"))]
+pub fn thread_count(_pid: u32) -> Option<usize> {
+    None
+}
+
+/// Bytes leídos y escritos por un proceso desde que arrancó (`rchar` + `wchar` de
+/// /proc/<pid>/io, que incluyen tuberías y sockets). Sin permisos sobre el proceso no se
+/// puede leer
+#[cfg(target_os = "linux")]
+pub fn io_bytes(pid: u32) -> Option<u64> {
+    let content = std::fs::read_to_string(format!("/proc/{}/io", pid)).ok()?;
+    let field = |name: &str| {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    Some(field("rchar:")? + field("wchar:")?)
+}
+
+/// Bytes leídos y escritos por un proceso (no disponible en esta plataforma)
+#[cfg(not(target_os = "linux"))]
+pub fn io_bytes(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Tramo actual de muestras de CPU por encima de un umbral, con la hora de cada una.
+/// Distingue un uso sostenido (p. ej. un minero) de un pico breve.
+#[derive(Debug, Clone)]
+pub struct SustainedCpu {
+    /// Uso de CPU (%) que hay que superar
+    threshold: f32,
+    /// Muestras (hora, CPU %) desde que se superó el umbral por última vez
+    run: Vec<(Instant, f32)>,
+}
+
+impl SustainedCpu {
+    /// Seguir el uso de CPU por encima de `threshold`
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold, run: Vec::new() }
+    }
+
+    /// Registrar una muestra; una muestra por debajo del umbral cierra el tramo
+    pub fn record(&mut self, at: Instant, cpu_usage: f32) {
+        if cpu_usage > self.threshold {
+            self.run.push((at, cpu_usage));
+        } else {
+            self.run.clear();
+        }
+    }
+
+    /// Tiempo seguido por encima del umbral: de la primera a la última muestra del tramo
+    pub fn duration(&self) -> Duration {
+        match (self.run.first(), self.run.last()) {
+            (Some((first, _)), Some((last, _))) => last.saturating_duration_since(*first),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Uso medio de CPU (%) del tramo
+    pub fn average(&self) -> f32 {
+        if self.run.is_empty() {
+            return 0.0;
+        }
+        self.run.iter().map(|(_, cpu)| cpu).sum::<f32>() / self.run.len() as f32
+    }
+
+    /// Uso de CPU (%) que hay que superar
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+}
+
+/// Relación padre → hijo sospechosa (`[[spawn_rules]]`): cualquiera de los
+/// procesos `parents` lanzando cualquiera de los `children`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnRule {
+    /// Nombres de los procesos padre (sin distinguir mayúsculas)
+    pub parents: Vec<String>,
+    /// Nombres de los procesos hijo que no deberían lanzar
+    pub children: Vec<String>,
+    /// Motivo que se incluye en el hallazgo
+    pub reason: String,
+}
+
+impl SpawnRule {
+    /// Indica si la relación padre → hijo coincide con la regla. Se tienen en cuenta
+    /// el recorte de nombres de Linux y las versiones (`php-fpm8.2`, `python3.11`)
+    pub fn matches(&self, parent_name: &str, child_name: &str) -> bool {
+        let matches = |names: &[String], name: &str| {
+            names.iter().any(|n| name_matches_executable(name, n) || name_matches_executable(n, name))
+        };
+        matches(&self.parents, parent_name) && matches(&self.children, child_name)
+    }
+}
+
+/// Cadenas de procesos clásicas de un ataque: (padres, hijos, motivo).
+/// Solo se incluyen relaciones poco habituales en sistemas sanos para evitar falsos positivos.
+pub const DEFAULT_SPAWN_RULES: &[(&[&str], &[&str], &str)] = &[
+    // Macros maliciosas en documentos
+    (
+        &["winword.exe", "excel.exe", "powerpnt.exe", "outlook.exe", "msaccess.exe", "acrord32.exe", "soffice.bin"],
+        &["cmd.exe", "powershell.exe", "pwsh.exe", "wscript.exe", "cscript.exe", "mshta.exe", "rundll32.exe", "regsvr32.exe", "sh", "bash", "dash", "python", "perl"],
+        "Una aplicación ofimática lanzando una shell o un intérprete (posible macro maliciosa)",
+    ),
+    // Web shells y ejecución remota de código
+    (
+        &["nginx", "apache2", "httpd", "lighttpd", "php-fpm", "w3wp.exe", "tomcat"],
+        &["sh", "bash", "dash", "zsh", "ksh", "cmd.exe", "powershell.exe", "pwsh.exe", "nc", "ncat", "netcat", "socat"],
+        "Un servidor web lanzando una shell (posible web shell o ejecución remota de código)",
+    ),
+    // Bases de datos abusadas para ejecutar comandos
+    (
+        &["mysqld", "mariadbd", "postgres", "mongod", "redis-server", "sqlservr.exe"],
+        &["sh", "bash", "dash", "cmd.exe", "powershell.exe", "pwsh.exe", "nc", "ncat", "netcat", "socat"],
+        "Una base de datos lanzando una shell (posible inyección o abuso de funciones del servidor)",
+    ),
+    // Navegadores explotados (en Linux abren enlaces con sh/xdg-open, por eso no se incluyen)
+    (
+        &["chrome.exe", "firefox.exe", "msedge.exe", "iexplore.exe"],
+        &["cmd.exe", "powershell.exe", "pwsh.exe", "wscript.exe", "cscript.exe", "mshta.exe"],
+        "Un navegador lanzando una shell o un intérprete de scripts (posible explotación)",
+    ),
+    // Servicios de persistencia que ejecutan herramientas de red o intérpretes poco habituales
+    (
+        &["systemd", "init", "launchd"],
+        &["nc", "ncat", "netcat", "socat", "perl", "php", "ruby"],
+        "El gestor de servicios lanzando directamente una herramienta de red o un intérprete inesperado (posible persistencia)",
+    ),
+];
+
+/// Reglas de relaciones sospechosas: las configuradas seguidas de las incorporadas
+pub fn spawn_rules(configured: &[SpawnRule]) -> Vec<SpawnRule> {
+    configured
+        .iter()
+        .cloned()
+        .chain(DEFAULT_SPAWN_RULES.iter().map(|(parents, children, reason)| SpawnRule {
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            children: children.iter().map(|c| c.to_string()).collect(),
+            reason: reason.to_string(),
+        }))
+        .collect()
+}
+
+/// Relación padre → hijo que coincide con una regla
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SuspiciousSpawn {
+    /// PID del proceso padre
+    pub parent_pid: u32,
+    /// Nombre del proceso padre
+    pub parent_name: String,
+    /// PID del proceso hijo
+    pub pid: u32,
+    /// Nombre del proceso hijo
+    pub name: String,
+    /// Cadena completa de ancestros hasta el hijo
+    pub chain: String,
+    /// Motivo de la regla que coincide
+    pub reason: String,
+}
+
+impl SuspiciousSpawn {
+    /// Descripción del hallazgo
+    pub fn description(&self) -> String {
+        format!("{} (PID: {}) lanzó {} (PID: {}): {}. Cadena: {}",
+            self.parent_name, self.parent_pid, self.name, self.pid, self.reason, self.chain)
+    }
+}
+
+/// Árbol de procesos construido a partir de las relaciones padre/hijo
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTree {
+    /// Nombre de cada proceso por PID
+    pub names: HashMap<u32, String>,
+    /// PIDs hijos de cada proceso padre
+    pub children: HashMap<u32, Vec<u32>>,
+    /// Procesos raíz (sin padre conocido)
+    pub roots: Vec<u32>,
+    /// PID padre de cada proceso que no es raíz
+    pub parents: HashMap<u32, u32>,
+}
+
+impl ProcessTree {
+    /// Construir un árbol a partir de tuplas (pid, pid padre, nombre)
+    pub fn from_entries(entries: &[(u32, Option<u32>, String)]) -> Self {
+        let mut tree = ProcessTree::default();
+
+        for (pid, _, name) in entries {
+            tree.names.insert(*pid, name.clone());
+        }
+
+        for (pid, parent, _) in entries {
+            match parent {
+                Some(ppid) if tree.names.contains_key(ppid) && ppid != pid => {
+                    tree.children.entry(*ppid).or_insert_with(Vec::new).push(*pid);
+                    tree.parents.insert(*pid, *ppid);
+                }
+                _ => tree.roots.push(*pid),
+            }
+        }
+
+        for children in tree.children.values_mut() {
+            children.sort_unstable();
+        }
+
+        // El árbol se enraíza en PID 1 (init/launchd) cuando está presente
+        tree.roots.sort_unstable_by_key(|pid| (*pid != 1, *pid));
+
+        tree
+    }
+
+    /// Descendientes de un proceso (hijos, nietos...) como pares (pid, pid padre)
+    pub fn descendants(&self, pid: u32) -> Vec<(u32, u32)> {
+        let mut descendants = Vec::new();
+        let mut pending = vec![pid];
+        while let Some(parent) = pending.pop() {
+            for child in self.children.get(&parent).into_iter().flatten() {
+                descendants.push((*child, parent));
+                pending.push(*child);
+            }
+        }
+        descendants
+    }
+
+    /// Cadena de ancestros de un proceso hasta él mismo ("systemd (1) → nginx (80) → sh (95)")
+    pub fn chain(&self, pid: u32) -> String {
+        let mut ancestry = vec![pid];
+        let mut current = pid;
+        while let Some(parent) = self.parents.get(&current) {
+            // Protección frente a ciclos si los PIDs se reutilizaron entre lecturas
+            if ancestry.contains(parent) {
+                break;
+            }
+            ancestry.push(*parent);
+            current = *parent;
+        }
+        ancestry
+            .iter()
+            .rev()
+            .map(|pid| self.format_node(*pid))
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    /// Relaciones padre → hijo de los procesos indicados que coinciden con alguna regla
+    pub fn suspicious_spawns(&self, pids: &[u32], rules: &[SpawnRule]) -> Vec<SuspiciousSpawn> {
+        let mut spawns = Vec::new();
+        for pid in pids {
+            let Some(parent) = self.parents.get(pid) else {
+                continue;
+            };
+            let (Some(parent_name), Some(child_name)) = (self.names.get(parent), self.names.get(pid)) else {
+                continue;
+            };
+            if let Some(rule) = rules.iter().find(|rule| rule.matches(parent_name, child_name)) {
+                spawns.push(SuspiciousSpawn {
+                    parent_pid: *parent,
+                    parent_name: parent_name.clone(),
+                    pid: *pid,
+                    name: child_name.clone(),
+                    chain: self.chain(*pid),
+                    reason: rule.reason.clone(),
+                });
+            }
+        }
+        spawns
+    }
+
+    /// Renderizar el árbol con conectores ├─/└─, una línea por proceso
+    pub fn render_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for root in &self.roots {
+            lines.push(self.format_node(*root));
+            self.render_children(*root, "", &mut lines);
+        }
+
+        lines
+    }
+
+    fn render_children(&self, pid: u32, prefix: &str, lines: &mut Vec<String>) {
+        let children = match self.children.get(&pid) {
+            Some(children) => children,
+            None => return,
+        };
+
+        for (i, child) in children.iter().enumerate() {
+            let is_last = i == children.len() - 1;
+            let connector = if is_last { "└─ " } else { "├─ " };
+            lines.push(format!("{}{}{}", prefix, connector, self.format_node(*child)));
+
+            let child_prefix = format!("{}{}", prefix, if is_last { "   " } else { "│  " });
+            self.render_children(*child, &child_prefix, lines);
+        }
+    }
+
+    fn format_node(&self, pid: u32) -> String {
+        let name = self.names.get(&pid).map(|n| n.as_str()).unwrap_or("?");
+        let label = if pid == own_pid() { " [shadowtrace]" } else { "" };
+        format!("{}{} ({})", name, label, pid)
+    }
+}
+
+/// Hora de inicio de un proceso (sysinfo la da en segundos desde la época Unix)
+fn process_start_time(process: &sysinfo::Process) -> DateTime<Utc> {
+    DateTime::from_timestamp(process.start_time() as i64, 0).unwrap_or_else(Utc::now)
+}
+
+/// PID del propio proceso de ShadowTrace
+pub fn own_pid() -> u32 {
+    std::process::id()
+}
+
+/// Estado de un PID según el sistema operativo, con independencia de lo que sysinfo pudo leer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidState {
+    /// El proceso existe y se tiene permiso para señalizarlo
+    Alive,
+    /// El proceso existe pero pertenece a otro usuario (EPERM)
+    AccessDenied,
+    /// No existe ningún proceso con ese PID (ESRCH)
+    Gone,
+    /// No se puede comprobar en esta plataforma
+    Unknown,
+}
+
+/// Comprobar si un PID existe enviándole la señal nula (`kill(pid, 0)`), que
+/// distingue un proceso terminado de uno sin permisos aunque /proc esté oculto
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn probe_pid(pid: u32) -> PidState {
+    // 0 y los valores negativos se refieren a grupos de procesos
+    let Ok(pid) = i32::try_from(pid) else {
+        return PidState::Gone;
+    };
+    if pid == 0 {
+        return PidState::Gone;
+    }
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return PidState::Alive;
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EPERM) => PidState::AccessDenied,
+        Some(libc::ESRCH) => PidState::Gone,
+        _ => PidState::Unknown,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn probe_pid(_pid: u32) -> PidState {
+    PidState::Unknown
+}
+
+/// Bytes en un MiB.
+///
+/// `ProcessInfo::memory_usage` guarda lo que devuelve `ProcessExt::memory()`, que desde
+/// sysinfo 0.26 (aquí 0.29) está en bytes; las versiones anteriores devolvían KB.
+/// Cualquier conversión de memoria debe pasar por `memory_mib` o `format_memory`.
+pub const BYTES_PER_MIB: u64 = 1024 * 1024;
+
+/// Memoria en MiB a partir de bytes
+pub fn memory_mib(bytes: u64) -> f64 {
+    bytes as f64 / BYTES_PER_MIB as f64
+}
+
+/// Formatear una cantidad de memoria en bytes con la unidad binaria más adecuada
+pub fn format_memory(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const GIB: u64 = BYTES_PER_MIB * 1024;
+    if bytes >= GIB {
+        format!("{:.2} GiB", bytes as f64 / GIB as f64)
+    } else if bytes >= BYTES_PER_MIB {
+        format!("{:.1} MiB", memory_mib(bytes))
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Región de memoria de un proceso (una línea de `/proc/<pid>/maps`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemRegion {
+    /// Dirección de inicio
+    pub start: u64,
+    /// Dirección de fin (exclusiva)
+    pub end: u64,
+    /// Permisos tal como aparecen en maps ("r-xp", "rw-s"...)
+    pub perms: String,
+    /// Desplazamiento dentro del archivo mapeado
+    pub offset: u64,
+    /// Archivo mapeado o pseudo-ruta ("[heap]", "[stack]"); None si es anónima
+    pub path: Option<String>,
+}
+
+impl MemRegion {
+    /// Tamaño de la región en bytes
+    pub fn size(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Indica si la región se puede escribir
+    pub fn is_writable(&self) -> bool {
+        self.perms.as_bytes().get(1) == Some(&b'w')
+    }
+
+    /// Indica si la región es ejecutable
+    pub fn is_executable(&self) -> bool {
+        self.perms.as_bytes().get(2) == Some(&b'x')
+    }
+
+    /// Escribible y ejecutable a la vez: el código inyectado necesita ambas cosas
+    pub fn is_rwx(&self) -> bool {
+        self.is_writable() && self.is_executable()
+    }
+
+    /// Indica si la región no está respaldada por un archivo (incluye "[heap]", "[stack]", "[anon:...]")
+    pub fn is_anonymous(&self) -> bool {
+        match &self.path {
+            None => true,
+            Some(path) => path.starts_with('['),
+        }
+    }
+
+    /// Indica si el archivo mapeado fue borrado del disco después de cargarse
+    pub fn is_deleted(&self) -> bool {
+        self.path.as_deref().is_some_and(|path| path.ends_with(DELETED_SUFFIX))
+    }
+
+    /// Dirección en el formato de maps ("7f3a1c000000-7f3a1c021000")
+    pub fn range(&self) -> String {
+        format!("{:x}-{:x}", self.start, self.end)
+    }
+}
+
+/// Analizar el contenido de `/proc/<pid>/maps`; las líneas mal formadas se ignoran
+pub fn parse_memory_maps(content: &str) -> Vec<MemRegion> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // dirección permisos desplazamiento dispositivo inodo [ruta]
+            let mut fields = line.splitn(6, char::is_whitespace);
+            let (start, end) = fields.next()?.split_once('-')?;
+            let perms = fields.next()?.to_string();
+            let offset = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let path = fields
+                .nth(2)
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(String::from);
+            Some(MemRegion {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                perms,
+                offset,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Bibliotecas compartidas cargadas (archivos `.so` mapeados), sin repetir y ordenadas
+pub fn loaded_libraries(regions: &[MemRegion]) -> Vec<String> {
+    let mut libraries: Vec<String> = regions
+        .iter()
+        .filter_map(|region| region.path.as_deref())
+        .filter(|path| path.starts_with('/') && (path.ends_with(".so") || path.contains(".so.") || path.contains(".so ")))
+        .map(String::from)
+        .collect();
+    libraries.sort();
+    libraries.dedup();
+    libraries
+}
+
+/// Resumen del mapa de memoria para el prompt del LLM: recuentos, regiones RWX, bibliotecas
+/// cargadas y archivos ejecutables mapeados que ya no están en disco
+pub fn memory_map_summary(regions: &[MemRegion]) -> serde_json::Value {
+    let rwx: Vec<String> = regions
+        .iter()
+        .filter(|region| region.is_rwx())
+        .map(|region| format!("{} {}", region.range(), region.path.as_deref().unwrap_or("[anónima]")))
+        .collect();
+    let mut deleted: Vec<&str> = regions
+        .iter()
+        .filter(|region| region.is_executable() && region.is_deleted())
+        .filter_map(|region| region.path.as_deref())
+        .collect();
+    deleted.dedup();
+    serde_json::json!({
+        "regions": regions.len(),
+        "executable_regions": regions.iter().filter(|region| region.is_executable()).count(),
+        "writable_regions": regions.iter().filter(|region| region.is_writable()).count(),
+        "rwx_regions": rwx,
+        "loaded_libraries": loaded_libraries(regions),
+        "deleted_mappings": deleted,
+    })
 }
 
 /// Estructura para monitorizar procesos
 pub struct ProcessMonitor {
     system: System,
+    /// Argumentos de la línea de comandos que se conservan por proceso (0 = todos)
+    max_cmd_args: usize,
+    /// Momento del último escaneo completo del sistema
+    last_full_refresh: Instant,
+    /// Tiempo durante el que las búsquedas por nombre reutilizan el último escaneo completo
+    refresh_throttle: Duration,
+    /// PIDs cuyo último refresco individual falló. sysinfo no los quita de su lista
+    /// hasta el siguiente escaneo completo, así que se ocultan hasta entonces
+    stale: HashSet<Pid>,
+    /// Incluir el propio proceso de ShadowTrace en listados, búsquedas y árboles
+    include_self: bool,
+}
+
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ProcessMonitor {
@@ -36,82 +972,643 @@ impl ProcessMonitor {
         let mut system = System::new_all();
         system.refresh_all();
         
-        Self { system }
+        Self {
+            system,
+            max_cmd_args: DEFAULT_MAX_CMD_ARGS,
+            last_full_refresh: Instant::now(),
+            refresh_throttle: Duration::from_millis(DEFAULT_REFRESH_THROTTLE_MS),
+            stale: HashSet::new(),
+            include_self: false,
+        }
+    }
+
+    /// Incluir el propio ShadowTrace en los listados, las búsquedas por nombre y el árbol
+    /// de procesos (`--include-self`); por defecto se omite
+    pub fn set_include_self(&mut self, include_self: bool) {
+        self.include_self = include_self;
+    }
+
+    /// Indica si el proceso aparece en los listados: todos salvo el propio ShadowTrace,
+    /// a menos que se haya pedido incluirlo
+    pub fn lists(&self, pid: u32) -> bool {
+        self.include_self || pid != own_pid()
+    }
+
+    /// Limitar los argumentos de la línea de comandos que se conservan (0 = todos)
+    pub fn set_max_cmd_args(&mut self, max_cmd_args: usize) {
+        self.max_cmd_args = max_cmd_args;
+    }
+
+    /// Tiempo durante el que las búsquedas por nombre reutilizan el último escaneo
+    /// completo en lugar de volver a recorrer todo el sistema (cero = escanear siempre)
+    pub fn set_refresh_throttle(&mut self, throttle: Duration) {
+        self.refresh_throttle = throttle;
+    }
+
+    /// Línea de comandos de un proceso, recortada según `max_cmd_args`,
+    /// e indicación de si se recortó
+    fn cmd_line(&self, process: &sysinfo::Process) -> (Vec<String>, bool) {
+        let limit = if self.max_cmd_args == 0 { usize::MAX } else { self.max_cmd_args };
+        let cmd = process.cmd();
+        (cmd.iter().take(limit).cloned().collect(), cmd.len() > limit)
+    }
+
+    /// Línea de comandos completa de un proceso, sin recortar
+    pub fn full_cmd_line(&mut self, pid: u32) -> Option<Vec<String>> {
+        self.refresh_specific(&[pid]);
+        self.cached(Pid::from_u32(pid)).map(|process| process.cmd().to_vec())
+    }
+
+    /// Proceso ya cargado, salvo que su último refresco indicara que terminó
+    fn cached(&self, pid: Pid) -> Option<&sysinfo::Process> {
+        if self.stale.contains(&pid) {
+            return None;
+        }
+        self.system.process(pid)
     }
 
     /// Refrescar la información del sistema
     pub fn refresh(&mut self) {
         self.system.refresh_all();
+        self.stale.clear();
+        self.last_full_refresh = Instant::now();
+    }
+
+    /// Re-escanear la lista de procesos; elimina de verdad los que terminaron
+    fn refresh_processes(&mut self) {
+        self.system.refresh_processes();
+        self.stale.clear();
+    }
+
+    /// Refrescar todo el sistema salvo que el último escaneo completo sea más reciente
+    /// que `refresh_throttle`. Devuelve si se refrescó.
+    ///
+    /// A cambio de no re-escanear en cada búsqueda, un proceso que arranca dentro de la
+    /// ventana no aparece hasta que vence, y CPU y memoria son las de ese escaneo.
+    pub fn refresh_throttled(&mut self) -> bool {
+        if self.last_full_refresh.elapsed() < self.refresh_throttle {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+
+    /// Refrescar solo los procesos indicados, sin re-escanear todo el sistema.
+    ///
+    /// Es el refresco a usar en los bucles de monitoreo: con ~60 procesos en el
+    /// sistema, `refresh_processes` tarda ~1 ms frente a ~20 µs por PID, y el coste
+    /// del refresco completo crece linealmente con el número de procesos.
+    /// Los PIDs que no se pueden refrescar (ya terminaron) dejan de devolverse hasta
+    /// que vuelvan a refrescarse con éxito o se haga un escaneo completo.
+    pub fn refresh_specific(&mut self, pids: &[u32]) {
+        for pid in pids.iter().map(|pid| Pid::from_u32(*pid)) {
+            if self.system.refresh_process(pid) {
+                self.stale.remove(&pid);
+            } else {
+                self.stale.insert(pid);
+            }
+        }
+    }
+
+    /// Obtener información sobre un proceso específico por PID.
+    /// `Ok(None)` si el proceso no existe (o ya terminó); `Err` si existe pero no se puede leer
+    pub fn get_process_by_pid(&mut self, pid: u32) -> Result<Option<ProcessInfo>, AppError> {
+        self.refresh_specific(&[pid]);
+        self.lookup_process(pid)
     }
 
-    /// Obtener información sobre un proceso específico por PID
-    pub fn get_process_by_pid(&mut self, pid: u32) -> Option<ProcessInfo> {
+    /// Como `get_process_by_pid`, pero sobre la información ya cargada, sin refrescarla.
+    /// Se consulta primero al sistema: un PID que ya no existe da Ok(None) aunque siga
+    /// en la información cargada
+    pub fn lookup_process(&self, pid: u32) -> Result<Option<ProcessInfo>, AppError> {
+        self.lookup_process_with(pid, probe_pid)
+    }
+
+    /// `lookup_process` con la comprobación del PID indicada
+    fn lookup_process_with(&self, pid: u32, probe: impl Fn(u32) -> PidState) -> Result<Option<ProcessInfo>, AppError> {
+        let state = probe(pid);
+        if state == PidState::Gone {
+            return Ok(None);
+        }
+        if let Some(info) = self.process_info(pid) {
+            return Ok(Some(info));
+        }
+        match state {
+            PidState::Gone | PidState::Unknown => Ok(None),
+            PidState::AccessDenied => Err(AppError::ProcessAccessError(format!(
+                "Sin permisos para leer el proceso {} (pertenece a otro usuario o /proc está oculto); prueba a ejecutar ShadowTrace con más privilegios",
+                pid
+            ))),
+            PidState::Alive => Err(AppError::ProcessAccessError(format!(
+                "El proceso {} existe pero no se pudo leer su información",
+                pid
+            ))),
+        }
+    }
+
+    /// Obtener la información ya cargada de un proceso, sin refrescarla
+    pub fn process_info(&self, pid: u32) -> Option<ProcessInfo> {
         let pid = Pid::from_u32(pid);
         
-        self.system.refresh_process(pid);
-        
-        self.system.process(pid).map(|process| {
-            ProcessInfo {
-                pid: pid.as_u32(),
-                name: process.name().to_string(),
-                path: Some(process.exe().to_string_lossy().to_string()),
-                cmd_line: Some(process.cmd().iter().take(5).map(|s| s.to_string()).collect()),
-                user: None, // No disponible directamente en sysinfo
-                cpu_usage: process.cpu_usage(),
-                memory_usage: process.memory(),
-                start_time: chrono::DateTime::from_timestamp(process.start_time() as i64, 0)
-                    .unwrap_or_else(|| Utc::now()),
-                children: Vec::new(),
-            }
+        self.cached(pid).map(|process| ProcessInfo {
+            environ: Some(process.environ().to_vec()),
+            ..self.info_from(pid, process)
         })
     }
 
+    /// Información de un proceso a partir de la lectura de sysinfo
+    fn info_from(&self, pid: Pid, process: &sysinfo::Process) -> ProcessInfo {
+        let (cmd_line, cmd_truncated) = self.cmd_line(process);
+        ProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            path: Some(process.exe().to_string_lossy().to_string()),
+            cmd_line: Some(cmd_line),
+            cmd_truncated,
+            user: None, // No disponible directamente en sysinfo
+            cpu_usage: process.cpu_usage(),
+            memory_usage: process.memory(),
+            start_time: process_start_time(process),
+            children: Vec::new(),
+            thread_count: thread_count(pid.as_u32()),
+            status: process.status().into(),
+            deleted_exe: deleted_exe(pid.as_u32()),
+            environ: None,
+        }
+    }
+
+    /// Regiones de memoria mapeadas de un proceso (vía /proc/<pid>/maps)
+    #[cfg(target_os = "linux")]
+    pub fn memory_maps(pid: u32) -> Result<Vec<MemRegion>> {
+        let path = format!("/proc/{}/maps", pid);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| AppError::ProcessAccessError(format!("No se pudo leer {}: {}", path, e)))?;
+        Ok(parse_memory_maps(&content))
+    }
+
+    /// Regiones de memoria mapeadas de un proceso (no disponible en esta plataforma)
+    #[cfg(not(target_os = "linux"))]
+    pub fn memory_maps(_pid: u32) -> Result<Vec<MemRegion>> {
+        Err(AppError::ProcessAccessError(
+            "El mapa de memoria solo está disponible en Linux".to_string()
+        ).into())
+    }
+
     /// Obtener todos los procesos activos
     pub fn get_all_processes(&mut self) -> Vec<ProcessInfo> {
-        self.system.refresh_processes();
+        self.refresh_processes();
         
         self.system
             .processes()
             .iter()
+            .filter(|(pid, _)| self.lists(pid.as_u32()))
             .take(100)
-            .map(|(pid, process)| {
-                ProcessInfo {
-                    pid: pid.as_u32(),
-                    name: process.name().to_string(),
-                    path: None,
-                    cmd_line: None,
-                    user: None,
-                    cpu_usage: process.cpu_usage(),
-                    memory_usage: process.memory(),
-                    start_time: Utc::now(),
-                    children: Vec::new(),
-                }
-            })
+            .map(|(pid, process)| self.info_from(*pid, process))
             .collect()
     }
 
-    /// Buscar procesos por nombre
+    /// Buscar procesos por nombre. Las búsquedas seguidas reutilizan el último escaneo
+    /// completo durante `refresh_throttle` (ver `refresh_throttled`)
     pub fn find_process_by_name(&mut self, name: &str) -> Vec<ProcessInfo> {
-        self.system.refresh_all();
+        self.refresh_throttled();
         
         self.system
             .processes()
             .iter()
-            .filter(|(_, process)| process.name().to_lowercase().contains(&name.to_lowercase()))
-            .map(|(pid, process)| {
-                ProcessInfo {
-                    pid: pid.as_u32(),
-                    name: process.name().to_string(),
-                    path: Some(process.exe().to_string_lossy().to_string()),
-                    cmd_line: Some(process.cmd().iter().map(|s| s.to_string()).collect()),
-                    user: None,
-                    cpu_usage: process.cpu_usage(),
-                    memory_usage: process.memory(),
-                    start_time: chrono::DateTime::from_timestamp(process.start_time() as i64, 0)
-                        .unwrap_or_else(|| Utc::now()),
-                    children: Vec::new(),
-                }
-            })
+            .filter(|(pid, process)| self.lists(pid.as_u32()) && process.name().to_lowercase().contains(&name.to_lowercase()))
+            .map(|(pid, process)| self.info_from(*pid, process))
             .collect()
     }
+
+    /// Hijos del proceso que terminaron sin que el padre los recogiera (zombis)
+    pub fn zombie_children(&mut self, pid: u32) -> Vec<u32> {
+        self.refresh_processes();
+        let parent = Pid::from_u32(pid);
+
+        let mut zombies: Vec<u32> = self.system
+            .processes()
+            .iter()
+            .filter(|(_, process)| process.parent() == Some(parent))
+            .filter(|(_, process)| ProcessStatus::from(process.status()) == ProcessStatus::Zombie)
+            .map(|(pid, _)| pid.as_u32())
+            .collect();
+        zombies.sort_unstable();
+        zombies
+    }
+
+    /// Terminar un proceso (SIGKILL en Unix); false si no existe o no se pudo
+    pub fn kill_process(&self, pid: u32) -> bool {
+        self.cached(Pid::from_u32(pid)).is_some_and(|process| process.kill())
+    }
+
+    /// Construir el árbol de procesos del sistema (relaciones padre/hijo); el propio
+    /// ShadowTrace solo aparece, etiquetado, con `set_include_self`
+    pub fn build_process_tree(&mut self) -> ProcessTree {
+        self.refresh_processes();
+
+        let entries: Vec<(u32, Option<u32>, String)> = self.system
+            .processes()
+            .iter()
+            .filter(|(pid, _)| self.lists(pid.as_u32()))
+            .map(|(pid, process)| (pid.as_u32(), process.parent().map(|p| p.as_u32()), process.name().to_string()))
+            .collect();
+
+        ProcessTree::from_entries(&entries)
+    }
 } 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, parent: Option<u32>, name: &str) -> (u32, Option<u32>, String) {
+        (pid, parent, name.to_string())
+    }
+
+    fn process(pid: u32, name: &str, path: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            path: path.map(str::to_string),
+            cmd_line: None,
+            cmd_truncated: false,
+            user: None,
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            start_time: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            children: Vec::new(),
+            thread_count: None,
+            status: ProcessStatus::Running,
+            deleted_exe: None,
+            environ: None,
+        }
+    }
+
+    #[test]
+    fn tree_renders_hierarchy_rooted_at_init() {
+        let tree = ProcessTree::from_entries(&[
+            entry(812, Some(1), "nginx"),
+            entry(1, None, "systemd"),
+            entry(4410, Some(812), "sh"),
+            entry(900, Some(1), "sshd"),
+            entry(813, Some(812), "nginx"),
+            // El padre no está en la lista: se muestra como otra raíz
+            entry(50, Some(49), "kthreadd"),
+        ]);
+
+        assert_eq!(tree.render_lines(), vec![
+            "systemd (1)",
+            "├─ nginx (812)",
+            "│  ├─ nginx (813)",
+            "│  └─ sh (4410)",
+            "└─ sshd (900)",
+            "kthreadd (50)",
+        ]);
+        assert_eq!(tree.chain(4410), "systemd (1) → nginx (812) → sh (4410)");
+    }
+
+    #[test]
+    fn tree_survives_self_parented_and_cyclic_entries() {
+        // PID 0 y PIDs reutilizados pueden aparecer como su propio padre o en ciclo
+        let tree = ProcessTree::from_entries(&[
+            entry(1, Some(1), "init"),
+            entry(10, Some(11), "a"),
+            entry(11, Some(10), "b"),
+        ]);
+
+        assert_eq!(tree.roots, vec![1]);
+        assert_eq!(tree.chain(10), "b (11) → a (10)");
+    }
+
+    #[test]
+    fn default_spawn_rules_flag_attack_chains() {
+        let tree = ProcessTree::from_entries(&[
+            entry(1, None, "systemd"),
+            entry(812, Some(1), "nginx"),
+            entry(4410, Some(812), "sh"),
+            entry(2000, Some(1), "soffice.bin"),
+            entry(2001, Some(2000), "python3.11"),
+            entry(3000, Some(1), "php-fpm8.2"),
+            entry(3001, Some(3000), "bash"),
+            entry(4000, Some(1), "ncat"),
+        ]);
+        let spawns = tree.suspicious_spawns(&[4410, 2001, 3001, 4000], &spawn_rules(&[]));
+
+        let pairs: Vec<(&str, &str)> = spawns.iter().map(|spawn| (spawn.parent_name.as_str(), spawn.name.as_str())).collect();
+        assert_eq!(pairs, vec![("nginx", "sh"), ("soffice.bin", "python3.11"), ("php-fpm8.2", "bash"), ("systemd", "ncat")]);
+        assert_eq!(spawns[0].chain, "systemd (1) → nginx (812) → sh (4410)");
+        assert!(spawns[0].reason.contains("servidor web"));
+        assert!(spawns[0].description().contains(&spawns[0].chain));
+    }
+
+    #[test]
+    fn ordinary_chains_are_not_flagged() {
+        let tree = ProcessTree::from_entries(&[
+            entry(1, None, "systemd"),
+            entry(900, Some(1), "sshd"),
+            entry(901, Some(900), "bash"),
+            entry(902, Some(901), "python3"),
+            entry(1500, Some(1), "nginx"),
+            entry(1501, Some(1500), "nginx"),
+            entry(1600, Some(1), "firefox"),
+            entry(1601, Some(1600), "sh"),
+            // Sin padre conocido no hay relación que evaluar
+            entry(7000, None, "bash"),
+        ]);
+        assert!(tree.suspicious_spawns(&[900, 901, 902, 1501, 1601, 7000], &spawn_rules(&[])).is_empty());
+    }
+
+    #[test]
+    fn configured_spawn_rules_come_first() {
+        let configured = SpawnRule {
+            parents: vec!["Cron".to_string()],
+            children: vec!["curl".to_string()],
+            reason: "Descarga programada".to_string(),
+        };
+        let rules = spawn_rules(std::slice::from_ref(&configured));
+        assert_eq!(rules[0], configured);
+        assert_eq!(rules.len(), DEFAULT_SPAWN_RULES.len() + 1);
+
+        let tree = ProcessTree::from_entries(&[entry(300, None, "cron"), entry(301, Some(300), "curl")]);
+        let spawns = tree.suspicious_spawns(&[301], &rules);
+        assert_eq!(spawns.len(), 1);
+        assert_eq!(spawns[0].reason, "Descarga programada");
+        assert!(tree.suspicious_spawns(&[301], &spawn_rules(&[])).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exe_links_to_deleted_files_are_detected() {
+        let dir = std::env::temp_dir().join(format!("shadowtrace-exe-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Enlaces con la forma de /proc/<pid>/exe
+        let intact = dir.join("intact");
+        std::os::unix::fs::symlink("/usr/bin/updater", &intact).unwrap();
+        let deleted = dir.join("deleted");
+        std::os::unix::fs::symlink("/tmp/.cache/updater (deleted)", &deleted).unwrap();
+        assert_eq!(exe_link_deleted(&intact), Some(false));
+        assert_eq!(exe_link_deleted(&deleted), Some(true));
+        assert_eq!(exe_link_deleted(&dir.join("missing")), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!process(1, "updater", None).has_deleted_exe());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn running_process_with_removed_binary_has_deleted_exe() {
+        let dir = std::env::temp_dir().join(format!("shadowtrace-deleted-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("sleeper");
+        std::fs::copy("/bin/sleep", &binary).unwrap();
+        let mut child = std::process::Command::new(&binary).arg("30").spawn().unwrap();
+        let pid = child.id();
+        assert_eq!(deleted_exe(pid), Some(false));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        let deleted = deleted_exe(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(deleted, Some(true));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn name_lookups_reuse_the_scan_within_the_throttle_window() {
+        let dir = std::env::temp_dir().join(format!("shadowtrace-throttle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Nombre corto para que el kernel no lo recorte (15 caracteres)
+        let name = format!("st{}", std::process::id());
+        let binary = dir.join(&name);
+        std::fs::copy("/bin/sleep", &binary).unwrap();
+
+        let mut monitor = ProcessMonitor::new();
+        monitor.set_refresh_throttle(Duration::from_secs(60));
+        monitor.refresh();
+        assert!(!monitor.refresh_throttled());
+
+        // Arranca dentro de la ventana: la búsqueda reutiliza el escaneo anterior
+        let mut child = std::process::Command::new(&binary).arg("30").spawn().unwrap();
+        let within_window = monitor.find_process_by_name(&name);
+
+        monitor.set_refresh_throttle(Duration::ZERO);
+        assert!(monitor.refresh_throttled());
+        let after_window = monitor.find_process_by_name(&name);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(within_window.is_empty());
+        assert_eq!(after_window.iter().map(|info| info.pid).collect::<Vec<_>>(), vec![child.id()]);
+    }
+
+    #[test]
+    fn identity_detects_pid_reuse() {
+        let original = process(4242, "backup", Some("/usr/bin/backup"));
+        let identity = original.identity();
+        assert!(identity.matches(&original));
+        assert!(!identity.is_reused_by(&original));
+
+        // Mismo PID, otro proceso arrancado más tarde
+        let mut reused = process(4242, "miner", Some("/tmp/miner"));
+        reused.start_time = original.start_time + chrono::Duration::seconds(90);
+        assert!(identity.is_reused_by(&reused));
+
+        // Mismo PID y hora de inicio, pero otro ejecutable (exec sobre el PID)
+        let execed = process(4242, "miner", Some("/tmp/miner"));
+        assert!(identity.is_reused_by(&execed));
+
+        // Una ruta que no se pudo leer no cuenta como diferencia
+        let unreadable = process(4242, "backup", None);
+        assert!(identity.matches(&unreadable));
+
+        // Otro PID no es una reutilización
+        assert!(!identity.is_reused_by(&process(4243, "backup", Some("/usr/bin/backup"))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refresh_specific_hides_exited_processes() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut monitor = ProcessMonitor::new();
+        monitor.refresh_specific(&[pid]);
+        assert!(monitor.process_info(pid).is_some());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        monitor.refresh_specific(&[pid]);
+        assert!(monitor.process_info(pid).is_none());
+        assert!(monitor.full_cmd_line(pid).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn long_command_lines_are_truncated_and_flagged() {
+        // GNU sleep suma sus argumentos: 12 × 5 s
+        let mut child = std::process::Command::new("sleep").args(["5"; 12]).spawn().unwrap();
+        let pid = child.id();
+        let mut monitor = ProcessMonitor::new();
+        monitor.set_max_cmd_args(5);
+        monitor.refresh_specific(&[pid]);
+
+        let info = monitor.process_info(pid).unwrap();
+        assert_eq!(info.cmd_line.as_ref().map(Vec::len), Some(5));
+        assert!(info.cmd_truncated);
+        assert_eq!(monitor.full_cmd_line(pid).map(|cmd| cmd.len()), Some(13));
+
+        // Sin límite no se recorta nada
+        monitor.set_max_cmd_args(0);
+        let info = monitor.process_info(pid).unwrap();
+        assert_eq!(info.cmd_line.as_ref().map(Vec::len), Some(13));
+        assert!(!info.cmd_truncated);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn lookup_of_missing_pid_is_none() {
+        let mut monitor = ProcessMonitor::new();
+        assert!(matches!(monitor.get_process_by_pid(u32::MAX), Ok(None)));
+        assert!(matches!(monitor.lookup_process(u32::MAX), Ok(None)));
+    }
+
+    #[test]
+    fn lookup_without_permission_is_an_error() {
+        let monitor = ProcessMonitor::new();
+        let lookup = monitor.lookup_process_with(u32::MAX, |_| PidState::AccessDenied);
+        assert!(matches!(lookup, Err(AppError::ProcessAccessError(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lookup_without_refresh_reports_exited_process_as_none() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut monitor = ProcessMonitor::new();
+        monitor.refresh_specific(&[pid]);
+        assert!(matches!(monitor.lookup_process(pid), Ok(Some(_))));
+
+        // Sin volver a refrescar: la información cargada sigue teniendo el proceso
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(matches!(monitor.lookup_process(pid), Ok(None)));
+    }
+
+    #[test]
+    fn listings_exclude_own_process_unless_included() {
+        let mut monitor = ProcessMonitor::new();
+        let own = monitor.get_process_by_pid(own_pid()).unwrap().expect("el propio proceso existe");
+        assert!(!monitor.build_process_tree().names.contains_key(&own_pid()));
+        assert!(monitor.find_process_by_name(&own.name).iter().all(|p| p.pid != own_pid()));
+        assert!(monitor.get_all_processes().iter().all(|p| p.pid != own_pid()));
+
+        monitor.set_include_self(true);
+        let tree = monitor.build_process_tree();
+        assert_eq!(tree.names[&own_pid()], own.name);
+        assert!(tree.render_lines().iter().any(|line| line.contains(&format!(" [shadowtrace] ({})", own_pid()))));
+        assert!(monitor.find_process_by_name(&own.name).iter().any(|p| p.pid == own_pid()));
+    }
+
+    #[test]
+    fn listing_and_lookup_agree_on_identity() {
+        // La lista de procesos y la búsqueda por PID deben dar la misma identidad;
+        // si no, la TUI da por reutilizado el PID en cada muestra
+        let mut monitor = ProcessMonitor::new();
+        monitor.set_include_self(true);
+        let own = monitor.get_process_by_pid(own_pid()).unwrap().expect("el propio proceso existe");
+        let listed = monitor
+            .find_process_by_name(&own.name)
+            .into_iter()
+            .find(|p| p.pid == own.pid)
+            .expect("el propio proceso aparece al buscarlo por nombre");
+        assert!(own.identity().matches(&listed));
+        assert!(own.start_time <= Utc::now());
+    }
+
+    #[test]
+    fn matching_processes_are_not_masquerades() {
+        assert_eq!(process(1, "sshd", Some("/usr/sbin/sshd")).masquerade(), None);
+        assert_eq!(process(1, "nginx", Some("/usr/sbin/nginx")).masquerade(), None);
+        // Nombre recortado a 15 caracteres e intérprete con versión
+        assert_eq!(process(1, "gnome-shell-cal", Some("/usr/libexec/gnome-shell-calendar-server")).masquerade(), None);
+        assert_eq!(process(1, "python3", Some("/usr/bin/python3.11")).masquerade(), None);
+        // Un ejecutable actualizado sigue ejecutándose con el sufijo " (deleted)"
+        assert_eq!(process(1, "sshd", Some("/usr/sbin/sshd (deleted)")).masquerade(), None);
+        // Un script toma el nombre de su archivo
+        let mut script = process(1, "backup.py", Some("/usr/bin/python3"));
+        script.cmd_line = Some(vec!["python3".to_string(), "/opt/backup.py".to_string()]);
+        assert_eq!(script.masquerade(), None);
+        // Sin ruta no hay nada que comparar
+        assert_eq!(process(1, "sshd", None).masquerade(), None);
+    }
+
+    #[test]
+    fn mismatching_processes_are_masquerades() {
+        assert_eq!(
+            process(77, "sshd", Some("/tmp/sshd")).masquerade().as_deref(),
+            Some("sshd (PID 77) se ejecuta desde /tmp/sshd, fuera de su ubicación habitual (/usr/sbin/sshd, /usr/bin/sshd, /sbin/sshd)")
+        );
+        assert_eq!(
+            process(78, "kworker", Some("/home/user/.cache/miner")).masquerade().as_deref(),
+            Some("El proceso kworker (PID 78) no coincide con su ejecutable /home/user/.cache/miner")
+        );
+        // Un intérprete cuyo script no coincide con el nombre
+        let mut script = process(79, "cron", Some("/usr/bin/python3"));
+        script.cmd_line = Some(vec!["python3".to_string(), "/tmp/x.py".to_string()]);
+        assert!(script.masquerade().is_some());
+    }
+
+    #[test]
+    fn memory_map_summary_flags_rwx_and_deleted_mappings() {
+        let regions = parse_memory_maps(
+            "55d0c0000000-55d0c0021000 r-xp 00000000 08:01 131 /usr/bin/worker\n\
+             7f3a1c000000-7f3a1c021000 rwxp 00000000 00:00 0 \n\
+             7f3a1d000000-7f3a1d100000 r-xp 00000000 08:01 922 /tmp/libhook.so (deleted)\n\
+             7f3a1e000000-7f3a1e100000 r--p 00000000 08:01 923 /tmp/data.bin (deleted)\n\
+             7f3a1f000000-7f3a1f100000 r-xp 00000000 08:01 412 /usr/lib/libc.so.6\n\
+             linea mal formada\n",
+        );
+        assert_eq!(regions.len(), 5);
+        assert!(regions[1].is_rwx() && regions[1].is_anonymous());
+        assert!(regions[2].is_deleted() && !regions[4].is_deleted());
+
+        let summary = memory_map_summary(&regions);
+        assert_eq!(summary["executable_regions"], 4);
+        assert_eq!(summary["rwx_regions"], serde_json::json!(["7f3a1c000000-7f3a1c021000 [anónima]"]));
+        assert_eq!(summary["deleted_mappings"], serde_json::json!(["/tmp/libhook.so (deleted)"]));
+        assert_eq!(
+            summary["loaded_libraries"],
+            serde_json::json!(["/tmp/libhook.so (deleted)", "/usr/lib/libc.so.6"])
+        );
+    }
+
+    #[test]
+    fn memory_is_formatted_in_binary_units() {
+        assert_eq!(format_memory(512), "512 B");
+        assert_eq!(format_memory(1536), "1.5 KiB");
+        assert_eq!(format_memory(BYTES_PER_MIB), "1.0 MiB");
+        assert_eq!(format_memory(3 * BYTES_PER_MIB * 1024 / 2), "1.50 GiB");
+        assert_eq!(memory_mib(5 * BYTES_PER_MIB), 5.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn memory_usage_is_in_bytes() {
+        // VmRSS viene en kB: el valor de sysinfo debe rondar esa cifra por 1024, no por 1
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        let rss_kib: u64 = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|value| value.parse().ok())
+            .unwrap();
+
+        let mut monitor = ProcessMonitor::new();
+        let own = monitor.get_process_by_pid(own_pid()).unwrap().unwrap();
+        let ratio = own.memory_usage as f64 / (rss_kib * 1024) as f64;
+        assert!((0.5..2.0).contains(&ratio), "memoria {} B frente a VmRSS {} kB", own.memory_usage, rss_kib);
+    }
+}
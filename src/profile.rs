@@ -0,0 +1,254 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::sampler::{DEFAULT_MAX_ADAPTIVE_INTERVAL_MS, DEFAULT_MIN_ADAPTIVE_INTERVAL_MS};
+
+/// Perfiles de monitoreo incorporados
+pub const BUILTIN_PROFILES: [&str; 4] = ["quick-triage", "deep-audit", "leak-hunt", "malware"];
+
+/// Intervalo mínimo entre muestras: un intervalo de 0 significa "tan rápido como se permita"
+pub const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Detectores que puede activar un perfil
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Detector {
+    /// Accesos sospechosos a archivos
+    File,
+    /// Conexiones sospechosas
+    Network,
+    /// Escrituras sostenidas de alta entropía
+    Entropy,
+    /// Ejecutables dejados en ubicaciones de arranque/persistencia
+    Persistence,
+    /// Regiones de memoria escribibles y ejecutables (/proc/<pid>/maps)
+    Memory,
+    /// Nombre que no coincide con el ejecutable, binario del sistema fuera de su ruta
+    /// o ejecutable borrado del disco
+    Masquerade,
+    /// Relaciones padre → hijo sospechosas (p. ej. servidor web → shell)
+    Spawn,
+}
+
+/// Preajuste de monitoreo (intervalo, duración, umbrales, detectores y análisis).
+/// Los campos ausentes toman el valor por defecto de `MonitorSettings`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorProfile {
+    /// Intervalo de muestreo en segundos (0 = lo más rápido posible, ver `MIN_SAMPLE_INTERVAL`)
+    pub interval: Option<u64>,
+    /// Adaptar el intervalo a la actividad del proceso, entre `min_interval_ms` y `max_interval_ms`
+    pub interval_adaptive: Option<bool>,
+    /// Intervalo mínimo del muestreo adaptativo en milisegundos
+    pub min_interval_ms: Option<u64>,
+    /// Intervalo máximo del muestreo adaptativo en milisegundos
+    pub max_interval_ms: Option<u64>,
+    /// Duración del monitoreo en segundos (0 = indefinido)
+    pub duration: Option<u64>,
+    /// Uso de CPU (%) que, sostenido, genera un hallazgo
+    pub cpu_threshold: Option<f32>,
+    /// Segundos seguidos por encima de `cpu_threshold` para generar el hallazgo
+    pub cpu_sustained_secs: Option<u64>,
+    /// Escrituras de alta entropía necesarias para generar un hallazgo
+    pub entropy_write_count: Option<usize>,
+    /// Detectores activos
+    pub detectors: Option<Vec<Detector>>,
+    /// Analizar el comportamiento con el LLM al terminar
+    pub llm_analysis: Option<bool>,
+}
+
+impl MonitorProfile {
+    /// Perfil incorporado con el nombre indicado
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            // Vistazo rápido: poco tiempo, solo lo más ruidoso y sin esperar al LLM
+            "quick-triage" => Some(Self {
+                interval: Some(1),
+                interval_adaptive: None,
+                min_interval_ms: None,
+                max_interval_ms: None,
+                duration: Some(30),
+                cpu_threshold: Some(90.0),
+                cpu_sustained_secs: Some(10),
+                entropy_write_count: None,
+                detectors: Some(vec![Detector::File, Detector::Network, Detector::Persistence, Detector::Masquerade, Detector::Spawn]),
+                llm_analysis: Some(false),
+            }),
+            // Auditoría completa: sesión larga con todos los detectores y análisis
+            "deep-audit" => Some(Self {
+                interval: Some(1),
+                interval_adaptive: None,
+                min_interval_ms: None,
+                max_interval_ms: None,
+                duration: Some(600),
+                cpu_threshold: Some(80.0),
+                cpu_sustained_secs: Some(60),
+                entropy_write_count: Some(5),
+                detectors: Some(vec![Detector::File, Detector::Network, Detector::Entropy, Detector::Persistence, Detector::Memory, Detector::Masquerade, Detector::Spawn]),
+                llm_analysis: Some(true),
+            }),
+            // Fugas de recursos: muestreo espaciado durante mucho tiempo
+            "leak-hunt" => Some(Self {
+                interval: Some(5),
+                interval_adaptive: None,
+                min_interval_ms: None,
+                max_interval_ms: None,
+                duration: Some(1800),
+                cpu_threshold: Some(70.0),
+                cpu_sustained_secs: Some(300),
+                entropy_write_count: None,
+                detectors: Some(vec![Detector::File]),
+                llm_analysis: Some(true),
+            }),
+            // Malware: umbrales agresivos para cifrado y exfiltración
+            "malware" => Some(Self {
+                interval: Some(1),
+                interval_adaptive: None,
+                min_interval_ms: None,
+                max_interval_ms: None,
+                duration: Some(300),
+                cpu_threshold: Some(60.0),
+                cpu_sustained_secs: Some(30),
+                entropy_write_count: Some(3),
+                detectors: Some(vec![Detector::File, Detector::Network, Detector::Entropy, Detector::Persistence, Detector::Memory, Detector::Masquerade, Detector::Spawn]),
+                llm_analysis: Some(true),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Buscar un perfil: los definidos en la configuración tienen prioridad sobre los incorporados
+    pub fn find(name: &str, custom: Option<&HashMap<String, MonitorProfile>>) -> Result<Self> {
+        if let Some(profile) = custom.and_then(|profiles| profiles.get(name)) {
+            return Ok(profile.clone());
+        }
+        Self::builtin(name).ok_or_else(|| {
+            let mut available: Vec<&str> = BUILTIN_PROFILES.to_vec();
+            if let Some(profiles) = custom {
+                available.extend(profiles.keys().map(String::as_str));
+            }
+            AppError::ConfigurationError(format!(
+                "Perfil desconocido: {} (disponibles: {})", name, available.join(", ")
+            )).into()
+        })
+    }
+}
+
+/// Parámetros concretos de una sesión de monitoreo
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorSettings {
+    /// Intervalo de muestreo en segundos (0 = lo más rápido posible, ver `MIN_SAMPLE_INTERVAL`)
+    pub interval: u64,
+    /// Adaptar el intervalo a la actividad del proceso (ver `AdaptiveSampler`)
+    pub interval_adaptive: bool,
+    /// Intervalo mínimo del muestreo adaptativo en milisegundos
+    pub min_interval_ms: u64,
+    /// Intervalo máximo del muestreo adaptativo en milisegundos
+    pub max_interval_ms: u64,
+    /// Duración del monitoreo en segundos (0 = indefinido)
+    pub duration: u64,
+    /// Uso de CPU (%) que, sostenido, genera un hallazgo
+    pub cpu_threshold: f32,
+    /// Segundos seguidos por encima de `cpu_threshold` para generar el hallazgo (mínimo 1)
+    pub cpu_sustained_secs: u64,
+    /// Escrituras de alta entropía necesarias para generar un hallazgo
+    pub entropy_write_count: usize,
+    /// Detectores activos
+    pub detectors: Vec<Detector>,
+    /// Analizar el comportamiento con el LLM al terminar
+    pub llm_analysis: bool,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            interval_adaptive: false,
+            min_interval_ms: DEFAULT_MIN_ADAPTIVE_INTERVAL_MS,
+            max_interval_ms: DEFAULT_MAX_ADAPTIVE_INTERVAL_MS,
+            duration: 0,
+            cpu_threshold: 80.0,
+            cpu_sustained_secs: 60,
+            entropy_write_count: 5,
+            detectors: vec![Detector::File, Detector::Network, Detector::Entropy, Detector::Persistence, Detector::Memory, Detector::Masquerade, Detector::Spawn],
+            llm_analysis: true,
+        }
+    }
+}
+
+impl MonitorSettings {
+    /// Resolver los parámetros: flags explícitos > perfil > valores por defecto.
+    /// `flags` recoge los valores indicados en la línea de comandos.
+    pub fn resolve(profile: Option<&MonitorProfile>, flags: &MonitorProfile) -> Self {
+        let defaults = Self::default();
+        let profile = profile.cloned().unwrap_or_default();
+        Self {
+            interval: flags.interval.or(profile.interval).unwrap_or(defaults.interval),
+            interval_adaptive: flags.interval_adaptive.or(profile.interval_adaptive).unwrap_or(defaults.interval_adaptive),
+            min_interval_ms: flags.min_interval_ms.or(profile.min_interval_ms).unwrap_or(defaults.min_interval_ms),
+            max_interval_ms: flags.max_interval_ms.or(profile.max_interval_ms).unwrap_or(defaults.max_interval_ms),
+            duration: flags.duration.or(profile.duration).unwrap_or(defaults.duration),
+            cpu_threshold: flags.cpu_threshold.or(profile.cpu_threshold).unwrap_or(defaults.cpu_threshold),
+            cpu_sustained_secs: flags.cpu_sustained_secs
+                .or(profile.cpu_sustained_secs)
+                .unwrap_or(defaults.cpu_sustained_secs)
+                .max(1),
+            entropy_write_count: profile.entropy_write_count.unwrap_or(defaults.entropy_write_count),
+            detectors: profile.detectors.unwrap_or(defaults.detectors),
+            llm_analysis: profile.llm_analysis.unwrap_or(defaults.llm_analysis),
+        }
+    }
+
+    /// Tiempo entre muestras, nunca inferior a `MIN_SAMPLE_INTERVAL`
+    pub fn sample_interval(&self) -> Duration {
+        Duration::from_secs(self.interval).max(MIN_SAMPLE_INTERVAL)
+    }
+
+    /// Límites del muestreo adaptativo (mínimo, máximo). El mínimo nunca es inferior a
+    /// `MIN_SAMPLE_INTERVAL` y el máximo nunca es inferior al mínimo
+    pub fn adaptive_bounds(&self) -> (Duration, Duration) {
+        let min = Duration::from_millis(self.min_interval_ms).max(MIN_SAMPLE_INTERVAL);
+        let max = Duration::from_millis(self.max_interval_ms).max(min);
+        (min, max)
+    }
+
+    /// Tiempo máximo de monitoreo (None = indefinido). Se mide en tiempo real,
+    /// así que no depende del intervalo
+    pub fn time_limit(&self) -> Option<Duration> {
+        (self.duration > 0).then(|| Duration::from_secs(self.duration))
+    }
+
+    /// Indica si un detector está activo
+    pub fn detects(&self, detector: Detector) -> bool {
+        self.detectors.contains(&detector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(interval: u64, duration: u64) -> MonitorSettings {
+        MonitorSettings { interval, duration, ..MonitorSettings::default() }
+    }
+
+    #[test]
+    fn zero_interval_is_raised_to_the_floor() {
+        assert_eq!(settings(0, 0).sample_interval(), MIN_SAMPLE_INTERVAL);
+        assert_eq!(settings(1, 0).sample_interval(), Duration::from_secs(1));
+        assert_eq!(settings(30, 0).sample_interval(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn duration_bounds_the_run_regardless_of_interval() {
+        assert_eq!(settings(1, 0).time_limit(), None);
+        assert_eq!(settings(0, 0).time_limit(), None);
+        // El límite es tiempo real, no un número de muestras: ni 0 ni un intervalo
+        // mayor que la duración lo cambian
+        assert_eq!(settings(0, 5).time_limit(), Some(Duration::from_secs(5)));
+        assert_eq!(settings(10, 5).time_limit(), Some(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// Plantillas de prompt incorporadas (`--template <NOMBRE>`)
+pub const BUILTIN_TEMPLATES: [&str; 3] = ["default", "pci", "malware"];
+
+/// Marcadores que toda plantilla debe contener
+pub const REQUIRED_PLACEHOLDERS: [&str; 3] = ["{process}", "{files}", "{network}"];
+
+/// Instrucciones del analista por defecto; las plantillas las incluyen con `{instructions}`
+const DEFAULT_INSTRUCTIONS: &str = "Tu tarea es:
+	• Leer el reporte y explicar en lenguaje humano técnico qué está haciendo el proceso monitoreado.
+	• Comentar si el comportamiento es típico o sospechoso.
+	• Hacer hipótesis razonables sobre el propósito del proceso basándote en los datos observados (ej. si parece un navegador, si hay persistencia, si hay tráfico de red anómalo, etc.).
+	• Puedes mencionar si algo parece malicioso, sospechoso o simplemente automatizado.
+	• Los eventos de archivo repetidos vienen agrupados por ruta y operación: `count` veces entre `first_seen` y `last_seen`, con `total_bytes` y `failures`. Pueden incluir `max_entropy` (0-8 bits por byte): escrituras sostenidas cercanas a 8 sugieren cifrado (ransomware), empaquetado o exfiltración.
+	• Tu análisis puede ser especulativo, pero bien fundamentado.
+	• NO digas que no puedes hacer el análisis. NO digas que eres solo un modelo de lenguaje. Ya lo sabemos. Estás aquí para interpretar texto. Hazlo como si fueras un analista de seguridad con experiencia.";
+
+/// Prompt del analista de seguridad (el comportamiento sin `--template`)
+const DEFAULT_TEMPLATE: &str = "Eres un modelo de lenguaje. No necesitas capacidades de ejecución para hacer análisis útil.
+
+A continuación te paso un reporte generado por un sistema llamado ShadowTrace. El reporte contiene texto estructurado con información de procesos, archivos accedidos y conexiones de red. No necesitas analizar archivos ni ejecutar código, solo interpretar lo que ya está escrito.
+
+{instructions}
+
+Aquí está el reporte:
+
+Proceso:
+{process}
+
+Actividad de archivos:
+{files}
+
+Actividad de red:
+{network}";
+
+/// Alcance PCI DSS: datos de titulares de tarjeta, segmentación y accesos
+const PCI_TEMPLATE: &str = "Eres un auditor de seguridad que evalúa un proceso dentro del alcance de PCI DSS. A continuación tienes un reporte de ShadowTrace con información del proceso, los archivos que accedió y sus conexiones de red.
+
+{instructions}
+
+Además, céntrate en el cumplimiento:
+	• Accesos a archivos que puedan contener datos de titulares de tarjeta (PAN), volcados de bases de datos, claves de cifrado o registros de transacciones.
+	• Conexiones que crucen la segmentación del entorno de datos de tarjetas (CDE) o salgan a destinos no autorizados, y protocolos sin cifrar.
+	• Uso de credenciales, cambios de configuración o herramientas de administración que deban quedar registrados.
+	• Termina con una lista de requisitos de PCI DSS potencialmente afectados y la evidencia de cada uno.
+
+Proceso:
+{process}
+
+Actividad de archivos:
+{files}
+
+Actividad de red:
+{network}";
+
+/// Caza de malware: persistencia, C2, cifrado y exfiltración
+const MALWARE_TEMPLATE: &str = "Eres un analista de malware. A continuación tienes un reporte de ShadowTrace con información de un proceso sospechoso, los archivos que accedió y sus conexiones de red.
+
+{instructions}
+
+Además, busca indicadores concretos de familias de malware:
+	• Persistencia (servicios, cron, autostart, claves de arranque) y escalada de privilegios.
+	• Comunicación con C2: balizas periódicas, puertos no estándar, dominios o IPs poco habituales.
+	• Cifrado masivo de archivos (ransomware), robo de credenciales o de carteras y exfiltración.
+	• Si el comportamiento encaja con una familia o técnica conocida, nómbrala (con su ID de MITRE ATT&CK si es posible) y explica por qué.
+
+Proceso:
+{process}
+
+Actividad de archivos:
+{files}
+
+Actividad de red:
+{network}";
+
+/// Plantilla del prompt de análisis completo, con los marcadores `{process}`, `{files}`,
+/// `{network}` y, opcionalmente, `{instructions}` (las instrucciones del analista por defecto)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    /// Nombre de la plantilla incorporada o ruta del archivo
+    pub name: String,
+    /// Texto con los marcadores
+    pub text: String,
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::builtin("default").expect("la plantilla por defecto está incorporada")
+    }
+}
+
+impl PromptTemplate {
+    /// Plantilla incorporada con el nombre indicado
+    pub fn builtin(name: &str) -> Option<Self> {
+        let text = match name {
+            "default" => DEFAULT_TEMPLATE,
+            "pci" => PCI_TEMPLATE,
+            "malware" => MALWARE_TEMPLATE,
+            _ => return None,
+        };
+        Some(Self { name: name.to_string(), text: text.to_string() })
+    }
+
+    /// Crear una plantilla comprobando que contiene los marcadores obligatorios
+    pub fn new(name: &str, text: String) -> std::result::Result<Self, AppError> {
+        let missing: Vec<&str> = REQUIRED_PLACEHOLDERS
+            .into_iter()
+            .filter(|placeholder| !text.contains(placeholder))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::ConfigurationError(format!(
+                "A la plantilla {} le faltan los marcadores {} (obligatorios: {})",
+                name, missing.join(", "), REQUIRED_PLACEHOLDERS.join(", ")
+            )));
+        }
+        Ok(Self { name: name.to_string(), text })
+    }
+
+    /// Cargar una plantilla desde un archivo
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| AppError::ConfigurationError(format!(
+            "No se pudo leer la plantilla {}: {}", path.display(), e
+        )))?;
+        Ok(Self::new(&path.display().to_string(), text)?)
+    }
+
+    /// Buscar una plantilla: un archivo existente tiene prioridad sobre las incorporadas
+    pub fn find(name_or_path: &str) -> Result<Self> {
+        let path = Path::new(name_or_path);
+        if path.exists() {
+            return Self::load(path);
+        }
+        Self::builtin(name_or_path).ok_or_else(|| AppError::ConfigurationError(format!(
+            "Plantilla desconocida: {} (no existe el archivo; incorporadas: {})",
+            name_or_path, BUILTIN_TEMPLATES.join(", ")
+        )).into())
+    }
+
+    /// Sustituir los marcadores en una sola pasada, de modo que los datos insertados
+    /// nunca se interpretan como marcadores
+    pub fn render(&self, process: &str, files: &str, network: &str) -> String {
+        let mut prompt = String::with_capacity(self.text.len() + process.len() + files.len() + network.len());
+        let mut rest = self.text.as_str();
+        while let Some(start) = rest.find('{') {
+            prompt.push_str(&rest[..start]);
+            let candidate = &rest[start..];
+            let value = [
+                ("{process}", process),
+                ("{files}", files),
+                ("{network}", network),
+                ("{instructions}", DEFAULT_INSTRUCTIONS),
+            ]
+            .into_iter()
+            .find(|(placeholder, _)| candidate.starts_with(placeholder));
+            match value {
+                Some((placeholder, value)) => {
+                    prompt.push_str(value);
+                    rest = &candidate[placeholder.len()..];
+                }
+                None => {
+                    prompt.push('{');
+                    rest = &candidate[1..];
+                }
+            }
+        }
+        prompt.push_str(rest);
+        prompt
+    }
+}
@@ -2,17 +2,26 @@ use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use directories::BaseDirs;
-use std::time::{SystemTime, Duration};
+use rand::Rng;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
-use crate::file_monitor::FileEvent;
-use crate::network::NetworkEvent;
-use crate::process::ProcessInfo;
-use crate::file_monitor::FileActivity;
+use crate::network::{ClosedConnection, Direction, NetworkEvent};
+use crate::process::{format_memory, ProcessInfo, ProcessStatus};
+use crate::file_monitor::{aggregate_file_activities, merge_file_access, FileAccessKey, FileAccessSummary, FileActivity, FileOperation};
+use crate::redaction::{IpPseudonymizer, Redactor};
+use crate::syscalls::SyscallSummary;
+use crate::system_logs::LogContext;
+use crate::asn::AsnEgress;
+use crate::encoding::ReportEncoding;
+use crate::sampler::ResourceSample;
+use crate::timeline::{self, TimelineEntry, TimelineFilter};
 
 /// Estado de un reporte
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -31,15 +40,121 @@ pub enum ReportStatus {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SeverityLevel {
     /// Informativo
+    #[serde(alias = "info")]
     Info,
     /// Advertencia
+    #[serde(alias = "warning")]
     Warning,
     /// Error
+    #[serde(alias = "error")]
     Error,
     /// Crítico
+    #[serde(alias = "critical")]
     Critical,
 }
 
+impl SeverityLevel {
+    /// Orden de las severidades, de menor a mayor
+    pub fn rank(&self) -> u8 {
+        match self {
+            SeverityLevel::Info => 0,
+            SeverityLevel::Warning => 1,
+            SeverityLevel::Error => 2,
+            SeverityLevel::Critical => 3,
+        }
+    }
+    
+    /// Marca de la severidad en los reportes Markdown
+    pub fn marker(&self) -> &'static str {
+        match self {
+            SeverityLevel::Info => "INFO",
+            SeverityLevel::Warning => "⚠️ WARN",
+            SeverityLevel::Error => "🟠 ERROR",
+            SeverityLevel::Critical => "🔴 CRÍTICO",
+        }
+    }
+}
+
+/// Nivel de riesgo global derivado de los hallazgos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskLevel {
+    /// Bajo
+    Low,
+    /// Medio
+    Medium,
+    /// Alto
+    High,
+    /// Crítico
+    Critical,
+}
+
+impl RiskLevel {
+    /// Nivel correspondiente a una puntuación de 0 a 100
+    pub fn from_score(score: u32) -> Self {
+        match score {
+            0..=19 => RiskLevel::Low,
+            20..=49 => RiskLevel::Medium,
+            50..=79 => RiskLevel::High,
+            _ => RiskLevel::Critical,
+        }
+    }
+
+    /// Etiqueta legible del nivel
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "Bajo",
+            RiskLevel::Medium => "Medio",
+            RiskLevel::High => "Alto",
+            RiskLevel::Critical => "Crítico",
+        }
+    }
+}
+
+/// Conteo de hallazgos por severidad
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityCounts {
+    pub critical: usize,
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    /// Resumen legible, p. ej. "2 críticos, 1 advertencia"
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            (self.critical, "crítico", "críticos"),
+            (self.error, "error", "errores"),
+            (self.warning, "advertencia", "advertencias"),
+            (self.info, "informativo", "informativos"),
+        ]
+        .iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, singular, plural)| {
+            format!("{} {}", count, if *count == 1 { singular } else { plural })
+        })
+        .collect();
+
+        if parts.is_empty() {
+            "sin hallazgos".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Formatos de exportación adicionales a JSON y Markdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Archivo de texto para el textfile collector de node_exporter
+    Prometheus,
+    /// Resumen en Markdown solo con el veredicto y los hallazgos, sin eventos en bruto
+    Summary,
+    /// Actividad de archivos en bruto, un evento por fila, sin agrupar
+    Csv,
+}
+
 /// Entrada de reporte
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportEntry {
@@ -55,6 +170,15 @@ pub struct ReportEntry {
     pub data: Option<Value>,
 }
 
+/// Patrón sospechoso encontrado por un detector, con la hora del evento que lo originó
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// Mensaje descriptivo
+    pub message: String,
+    /// Momento del evento que disparó la detección
+    pub timestamp: DateTime<Utc>,
+}
+
 /// Hallazgo o anomalía detectada
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Finding {
@@ -69,7 +193,215 @@ pub struct Finding {
     /// Recursos afectados
     pub affected_resources: Vec<String>,
     /// Timestamp
-    pub timestamp: SystemTime,
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Límite de tamaño del reporte y estadísticas del muestreo aplicado
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSampling {
+    /// Máximo de elementos por colección (None = sin límite)
+    pub max_entries: Option<usize>,
+    /// Actividades de archivo observadas
+    pub file_activities_seen: u64,
+    /// Actividades de archivo descartadas por muestreo
+    pub file_activities_dropped: u64,
+    /// Eventos de red observados
+    pub network_events_seen: u64,
+    /// Eventos de red descartados por muestreo
+    pub network_events_dropped: u64,
+    /// Hallazgos informativos observados
+    pub info_findings_seen: u64,
+    /// Hallazgos informativos descartados por muestreo
+    pub info_findings_dropped: u64,
+}
+
+impl ReportSampling {
+    /// Total de elementos descartados por muestreo
+    pub fn total_dropped(&self) -> u64 {
+        self.file_activities_dropped + self.network_events_dropped + self.info_findings_dropped
+    }
+}
+
+/// Hallazgos informativos que nunca se descartan al muestrear
+const UNSAMPLED_FINDINGS: &[&str] = &["llm_analysis", "offline_analysis", "static_analysis", "syscall_summary", "system_logs"];
+
+/// Llamadas al sistema más frecuentes que se listan en el reporte Markdown
+const MARKDOWN_TOP_SYSCALLS: usize = 15;
+
+/// Cierre de una tabla de eventos en bruto, con una nota si se recortó
+fn raw_events_truncated(total: usize, limit: usize) -> String {
+    if total > limit {
+        format!(
+            "\n*… y {} eventos más (se muestran los {} primeros; `-vvv` los lista todos)*\n\n",
+            total - limit,
+            limit
+        )
+    } else {
+        "\n".to_string()
+    }
+}
+
+/// Campo CSV, entre comillas si contiene separadores, comillas o saltos de línea
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Verbosidad (`-vv`) a partir de la que el Markdown lista los eventos en bruto
+pub const RAW_EVENTS_VERBOSITY: u8 = 2;
+
+/// Verbosidad (`-vvv`) a partir de la que la lista de eventos en bruto no tiene límite
+const RAW_EVENTS_ALL_VERBOSITY: u8 = 3;
+
+/// Eventos de cada tipo que lista el apéndice "Eventos Detallados" con `-vv`
+const MARKDOWN_RAW_EVENTS: usize = 1000;
+
+/// Patrones de acceso a archivos que se listan en el reporte Markdown (los más frecuentes)
+const MARKDOWN_FILE_PATTERNS: usize = 20;
+
+/// Líneas de los registros del sistema que se listan en el reporte Markdown (las más recientes)
+const MARKDOWN_LOG_LINES: usize = 50;
+
+/// Reportes creados por este proceso, para distinguir los creados en el mismo segundo
+static REPORT_SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// ID de un reporte nuevo: segundo de creación, PID de ShadowTrace y número de
+/// secuencia, de modo que ni ejecuciones rápidas ni simultáneas comparten ID
+fn report_id(created_at: DateTime<Utc>) -> String {
+    let secs = created_at.timestamp();
+    let sequence = REPORT_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!("report_{}_{}_{}", secs, std::process::id(), sequence)
+}
+
+/// Número mínimo de procesos distintos que deben compartir un recurso para correlacionarlo
+pub const CORRELATION_MIN_PROCESSES: usize = 2;
+
+/// Rutas que muchos procesos comparten legítimamente y no se correlacionan
+const CORRELATION_IGNORED_PATHS: &[&str] = &[
+    "/dev/", "/proc/", "/sys/", "/usr/lib", "/usr/share", "/lib", "/etc/ld.so",
+    "C:/Windows/System32/",
+];
+
+/// Títulos de los hallazgos generados por `Report::correlate`
+const CORRELATION_FINDINGS: &[&str] = &["correlated_destination", "correlated_file"];
+
+/// PIDs distintos que comparten un recurso, si alcanzan el mínimo para correlacionarlo
+fn distinct_pids(mut pids: Vec<u32>) -> Option<Vec<u32>> {
+    pids.sort_unstable();
+    pids.dedup();
+    (pids.len() >= CORRELATION_MIN_PROCESSES).then_some(pids)
+}
+
+/// Recursos afectados de una correlación: el recurso compartido y los procesos implicados
+fn correlated_resources(resource: String, pids: &[u32]) -> Vec<String> {
+    std::iter::once(resource)
+        .chain(pids.iter().map(|pid| format!("PID {}", pid)))
+        .collect()
+}
+
+/// Insertar en una colección acotada usando muestreo de reservorio.
+/// Devuelve `true` si algún elemento quedó fuera de la muestra.
+fn reservoir_push<T>(items: &mut Vec<T>, item: T, seen: &mut u64, max_entries: Option<usize>) -> bool {
+    *seen += 1;
+    match max_entries {
+        Some(max) if items.len() >= max => {
+            let j = rand::thread_rng().gen_range(0..*seen);
+            if (j as usize) < max {
+                items[j as usize] = item;
+            }
+            true
+        }
+        _ => {
+            items.push(item);
+            false
+        }
+    }
+}
+
+/// Diferencias de una categoría entre dos reportes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffSection {
+    /// Categoría (hallazgos, destinos, archivos, recursos)
+    pub category: String,
+    /// Elementos que solo aparecen en el reporte posterior
+    pub added: Vec<String>,
+    /// Elementos que solo aparecían en el reporte anterior
+    pub removed: Vec<String>,
+    /// Valores que cambiaron ("CPU: 2.0% → 35.0% (+33.0)")
+    pub changed: Vec<String>,
+}
+
+impl DiffSection {
+    fn new(category: &str) -> Self {
+        Self { category: category.to_string(), ..Self::default() }
+    }
+
+    /// Indica si la categoría no tiene diferencias
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Comparación entre dos reportes (antes → después), ver `Report::diff`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportDiff {
+    /// Título del reporte anterior
+    pub before: String,
+    /// Título del reporte posterior
+    pub after: String,
+    /// Diferencias por categoría (solo las que tienen cambios)
+    pub sections: Vec<DiffSection>,
+}
+
+impl ReportDiff {
+    /// Indica si los reportes son equivalentes
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+/// Elementos de `after` que no están en `before` y viceversa, sin duplicados y en orden
+fn set_difference(before: Vec<String>, after: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let before: std::collections::BTreeSet<String> = before.into_iter().collect();
+    let after: std::collections::BTreeSet<String> = after.into_iter().collect();
+    (
+        after.difference(&before).cloned().collect(),
+        before.difference(&after).cloned().collect(),
+    )
+}
+
+/// Proceso del árbol auditado (`audit --follow-children`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditedProcess {
+    /// ID del proceso
+    pub pid: u32,
+    /// Proceso padre (None para el binario auditado)
+    pub parent_pid: Option<u32>,
+    /// Nombre del proceso
+    pub name: String,
+    /// Línea de comandos
+    pub cmd_line: Option<Vec<String>>,
+    /// Momento en que se detectó
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub first_seen: DateTime<Utc>,
+    /// Momento en que terminó (None si seguía en ejecución al acabar la auditoría)
+    #[serde(default, deserialize_with = "crate::timestamp::deserialize_option")]
+    pub exited_at: Option<DateTime<Utc>>,
+}
+
+/// Archivo guardado en el directorio de reportes
+#[derive(Debug, Clone)]
+pub struct SavedReportFile {
+    /// Ruta del archivo
+    pub path: PathBuf,
+    /// Tamaño en bytes
+    pub size: u64,
+    /// Última modificación
+    pub modified: Option<DateTime<Local>>,
 }
 
 /// Reporte de análisis
@@ -80,7 +412,8 @@ pub struct Report {
     /// Título
     pub title: String,
     /// Timestamp de creación
-    pub created_at: SystemTime,
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub created_at: DateTime<Utc>,
     /// Estado actual
     pub status: ReportStatus,
     /// Duración del análisis
@@ -91,18 +424,64 @@ pub struct Report {
     pub file_activities: Vec<FileActivity>,
     /// Eventos de red
     pub network_events: Vec<NetworkEvent>,
+    /// Actividad de archivos agrupada por proceso, ruta y operación. Se actualiza con cada
+    /// actividad, así que cuenta también las que el muestreo descarta de `file_activities`
+    #[serde(default)]
+    pub file_access: Vec<FileAccessSummary>,
+    /// Posición en `file_access` de cada grupo
+    #[serde(skip)]
+    file_access_index: HashMap<FileAccessKey, usize>,
+    /// Conexiones cerradas durante el análisis, con sus estadísticas finales
+    #[serde(default)]
+    pub closed_connections: Vec<ClosedConnection>,
+    /// Procesos del árbol auditado, empezando por el binario
+    #[serde(default)]
+    pub audited_processes: Vec<AuditedProcess>,
+    /// Llamadas al sistema por categoría (`audit --trace-syscalls`)
+    #[serde(default)]
+    pub syscalls: Option<SyscallSummary>,
+    /// Conexiones salientes agrupadas por organización (`--asn-db`)
+    #[serde(default)]
+    pub egress_by_asn: Vec<AsnEgress>,
+    /// Líneas de los registros del sistema relacionadas con el proceso (`--with-logs`)
+    #[serde(default)]
+    pub system_logs: Option<LogContext>,
     /// Hallazgos detectados
     pub findings: Vec<Finding>,
     /// Resumen
     pub summary: String,
+    /// Modelo LLM que generó el análisis (puede ser uno de respaldo)
+    #[serde(default)]
+    pub llm_model: Option<String>,
+    /// Análisis heurístico generado sin IA (`--offline`, tecla `d` de la TUI), en Markdown
+    #[serde(default)]
+    pub offline_analysis: Option<String>,
+    /// Notas del analista que acompañaron al análisis (`--note`, tecla `n` de la TUI)
+    #[serde(default)]
+    pub analyst_notes: Vec<String>,
+    /// Límite de tamaño y muestreo de eventos
+    #[serde(default)]
+    pub sampling: ReportSampling,
+    /// Muestras de CPU, memoria y E/S con el intervalo aplicado (`--interval-adaptive`)
+    #[serde(default)]
+    pub resource_samples: Vec<ResourceSample>,
+    /// Codificación con la que se exporta (no forma parte del reporte)
+    #[serde(skip)]
+    pub encoding: ReportEncoding,
+    /// Nivel de `--verbose` con el que se genera el Markdown (no forma parte del reporte)
+    #[serde(skip)]
+    pub verbosity: u8,
+    /// Seudónimos de las direcciones IP aplicados al exportar (`--redact-network`)
+    #[serde(skip)]
+    pub ip_pseudonyms: Option<IpPseudonymizer>,
 }
 
 impl Report {
     /// Crear un nuevo reporte
     pub fn new(title: &str) -> Self {
-        let now = SystemTime::now();
+        let now = Utc::now();
         Self {
-            id: format!("report_{}", now.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()),
+            id: report_id(now),
             title: title.to_string(),
             created_at: now,
             status: ReportStatus::InProgress,
@@ -110,8 +489,23 @@ impl Report {
             processes: Vec::new(),
             file_activities: Vec::new(),
             network_events: Vec::new(),
+            file_access: Vec::new(),
+            file_access_index: HashMap::new(),
+            closed_connections: Vec::new(),
+            audited_processes: Vec::new(),
+            syscalls: None,
+            egress_by_asn: Vec::new(),
+            system_logs: None,
             findings: Vec::new(),
             summary: String::new(),
+            llm_model: None,
+            offline_analysis: None,
+            analyst_notes: Vec::new(),
+            sampling: ReportSampling::default(),
+            resource_samples: Vec::new(),
+            encoding: ReportEncoding::default(),
+            verbosity: 0,
+            ip_pseudonyms: None,
         }
     }
 
@@ -120,26 +514,153 @@ impl Report {
         self.processes.push(process);
     }
 
+    /// Limitar el número de eventos y hallazgos informativos que conserva el reporte
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.sampling.max_entries = max_entries;
+    }
+
+    /// Registrar el modelo LLM que generó el análisis
+    pub fn set_llm_model(&mut self, model: Option<String>) {
+        self.llm_model = model;
+    }
+
+    /// Añadir el análisis heurístico generado sin IA
+    pub fn set_offline_analysis(&mut self, analysis: String) {
+        self.add_info("offline_analysis", "Análisis heurístico generado sin IA a partir de los datos recopilados", None);
+        self.offline_analysis = Some(analysis);
+    }
+
+    /// Elegir la codificación de los archivos exportados (ASCII, BOM)
+    pub fn set_encoding(&mut self, encoding: ReportEncoding) {
+        self.encoding = encoding;
+    }
+
+    /// Nivel de detalle del Markdown: con `-vv` (`RAW_EVENTS_VERBOSITY`) se añade el
+    /// apéndice "Eventos Detallados"
+    pub fn set_verbosity(&mut self, verbosity: u8) {
+        self.verbosity = verbosity;
+    }
+
     /// Añadir actividad de archivo
     pub fn add_file_activity(&mut self, activity: FileActivity) {
-        self.file_activities.push(activity);
+        self.record_file_access(&activity);
+        let sampling = &mut self.sampling;
+        if reservoir_push(&mut self.file_activities, activity, &mut sampling.file_activities_seen, sampling.max_entries) {
+            sampling.file_activities_dropped += 1;
+        }
+    }
+
+    /// Sumar una actividad a su grupo de `file_access`
+    fn record_file_access(&mut self, activity: &FileActivity) {
+        // Un reporte cargado de JSON o redactado no tiene el índice al día
+        if self.file_access_index.len() != self.file_access.len() {
+            self.file_access_index = self.file_access
+                .iter()
+                .enumerate()
+                .map(|(position, summary)| (summary.key(), position))
+                .collect();
+        }
+        merge_file_access(&mut self.file_access, &mut self.file_access_index, FileAccessSummary::from_activity(activity));
+    }
+
+    /// Patrones de acceso a archivos; los reportes anteriores a `file_access` se
+    /// agrupan a partir de sus actividades
+    pub fn file_access_patterns(&self) -> Vec<FileAccessSummary> {
+        if self.file_access.is_empty() {
+            aggregate_file_activities(&self.file_activities)
+        } else {
+            self.file_access.clone()
+        }
     }
 
     /// Añadir evento de red
     pub fn add_network_event(&mut self, event: NetworkEvent) {
-        self.network_events.push(event);
+        let sampling = &mut self.sampling;
+        if reservoir_push(&mut self.network_events, event, &mut sampling.network_events_seen, sampling.max_entries) {
+            sampling.network_events_dropped += 1;
+        }
+    }
+
+    /// Añadir una muestra de recursos con el intervalo aplicado
+    pub fn add_resource_sample(&mut self, sample: ResourceSample) {
+        self.resource_samples.push(sample);
     }
 
-    /// Añadir un hallazgo
+    /// Añadir una conexión ya cerrada
+    pub fn add_closed_connection(&mut self, closed: ClosedConnection) {
+        self.closed_connections.push(closed);
+    }
+
+    /// Añadir la salida de red por organización, con un hallazgo por cada ASN no visto antes
+    pub fn set_egress_by_asn(&mut self, egress: Vec<AsnEgress>) {
+        for group in egress.iter().filter(|group| group.unseen) {
+            self.add_finding(Finding {
+                title: format!("Conexión a un ASN no visto antes: {}", group.label()),
+                description: format!(
+                    "{} conexiones a {} hosts de una organización a la que el proceso no se había conectado en ejecuciones anteriores",
+                    group.connections, group.hosts
+                ),
+                severity: SeverityLevel::Warning,
+                recommendation: Some("Comprueba si el proceso tiene motivos para contactar con esta organización".to_string()),
+                affected_resources: Vec::new(),
+                timestamp: Utc::now(),
+            });
+        }
+        self.egress_by_asn = egress;
+    }
+
+    /// Añadir el resumen de llamadas al sistema y sus hallazgos
+    pub fn set_syscalls(&mut self, summary: SyscallSummary) {
+        for finding in summary.findings() {
+            self.add_finding(finding);
+        }
+        self.syscalls = Some(summary);
+    }
+
+    /// Añadir el contexto de los registros del sistema y sus hallazgos
+    pub fn set_system_logs(&mut self, context: LogContext) {
+        for finding in context.findings() {
+            self.add_finding(finding);
+        }
+        self.system_logs = Some(context);
+    }
+
+    /// Añadir un hallazgo (los informativos se muestrean al superar el límite;
+    /// el resto se conserva siempre)
     pub fn add_finding(&mut self, finding: Finding) {
-        self.findings.push(finding);
+        if finding.severity != SeverityLevel::Info || UNSAMPLED_FINDINGS.contains(&finding.title.as_str()) {
+            self.findings.push(finding);
+            return;
+        }
+        
+        let sampling = &mut self.sampling;
+        sampling.info_findings_seen += 1;
+        
+        // Muestrear solo entre los hallazgos informativos
+        let info_indices: Vec<usize> = self.findings
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.severity == SeverityLevel::Info && !UNSAMPLED_FINDINGS.contains(&f.title.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+        
+        match sampling.max_entries {
+            Some(max) if info_indices.len() >= max => {
+                sampling.info_findings_dropped += 1;
+                let j = rand::thread_rng().gen_range(0..sampling.info_findings_seen) as usize;
+                if j < max {
+                    self.findings[info_indices[j]] = finding;
+                }
+            }
+            _ => self.findings.push(finding),
+        }
     }
 
     /// Completar el reporte
     pub fn complete(&mut self, summary: &str) {
         self.status = ReportStatus::Completed;
-        self.duration = SystemTime::now()
-            .duration_since(self.created_at)
+        self.duration = (Utc::now() - self.created_at)
+            .to_std()
             .unwrap_or(Duration::from_secs(0));
         self.summary = summary.to_string();
     }
@@ -154,9 +675,9 @@ impl Report {
         let now = Utc::now();
         
         Self {
-            id: format!("report_{}", now.timestamp()),
+            id: report_id(now),
             title: format!("Análisis de {}", process_name),
-            created_at: now.into(),
+            created_at: now,
             status: ReportStatus::InProgress,
             duration: Duration::from_secs(0),
             processes: vec![ProcessInfo {
@@ -164,23 +685,133 @@ impl Report {
                 name: process_name,
                 path: None,
                 cmd_line: None,
+                cmd_truncated: false,
                 cpu_usage: 0.0,
                 memory_usage: 0,
-                start_time: now.into(),
+                start_time: now,
                 children: Vec::new(),
                 user: None,
+                thread_count: None,
+                status: ProcessStatus::Unknown,
+                deleted_exe: None,
+                environ: None,
             }],
             file_activities: Vec::new(),
             network_events: Vec::new(),
+            file_access: Vec::new(),
+            file_access_index: HashMap::new(),
+            closed_connections: Vec::new(),
+            audited_processes: Vec::new(),
+            syscalls: None,
+            egress_by_asn: Vec::new(),
+            system_logs: None,
             findings: Vec::new(),
             summary: String::new(),
+            llm_model: None,
+            offline_analysis: None,
+            analyst_notes: Vec::new(),
+            sampling: ReportSampling::default(),
+            resource_samples: Vec::new(),
+            encoding: ReportEncoding::default(),
+            verbosity: 0,
+            ip_pseudonyms: None,
         }
     }
     
+    /// Correlacionar la actividad de varios procesos: un mismo destino remoto o
+    /// archivo poco común compartido por varios PIDs sugiere actividad coordinada.
+    ///
+    /// Se puede llamar varias veces; los hallazgos de correlaciones anteriores se reemplazan.
+    pub fn correlate(&mut self) {
+        self.findings.retain(|f| !CORRELATION_FINDINGS.contains(&f.title.as_str()));
+        
+        // Destinos remotos (por IP, sin contar loopback)
+        let mut hosts: HashMap<std::net::IpAddr, Vec<u32>> = HashMap::new();
+        for event in &self.network_events {
+            if let Some(remote) = event.remote_addr {
+                if !remote.ip().is_loopback() && !remote.ip().is_unspecified() {
+                    hosts.entry(remote.ip()).or_default().push(event.pid);
+                }
+            }
+        }
+        
+        // Archivos (sin contar bibliotecas y pseudo-sistemas de archivos)
+        let mut files: HashMap<String, Vec<u32>> = HashMap::new();
+        for activity in &self.file_activities {
+            let path = activity.path.to_string_lossy().to_string();
+            if CORRELATION_IGNORED_PATHS.iter().any(|prefix| path.starts_with(prefix)) {
+                continue;
+            }
+            if let Some(pid) = activity.process_id {
+                files.entry(path).or_default().push(pid);
+            }
+        }
+        
+        let mut correlated = Vec::new();
+        for (host, pids) in hosts {
+            if let Some(pids) = distinct_pids(pids) {
+                correlated.push(Finding {
+                    title: "correlated_destination".to_string(),
+                    description: format!("{} procesos contactaron {}", pids.len(), host),
+                    severity: SeverityLevel::Error,
+                    recommendation: Some("Verificar si los procesos pertenecen a la misma aplicación o si el destino es conocido".to_string()),
+                    affected_resources: correlated_resources(host.to_string(), &pids),
+                    timestamp: Utc::now(),
+                });
+            }
+        }
+        for (path, pids) in files {
+            if let Some(pids) = distinct_pids(pids) {
+                correlated.push(Finding {
+                    title: "correlated_file".to_string(),
+                    description: format!("{} procesos accedieron a {}", pids.len(), path),
+                    severity: SeverityLevel::Error,
+                    recommendation: Some("Revisar por qué varios procesos comparten este archivo".to_string()),
+                    affected_resources: correlated_resources(path, &pids),
+                    timestamp: Utc::now(),
+                });
+            }
+        }
+        
+        // Orden estable: primero los recursos compartidos por más procesos
+        correlated.sort_by(|a, b| {
+            b.affected_resources.len().cmp(&a.affected_resources.len()).then_with(|| a.description.cmp(&b.description))
+        });
+        for finding in correlated {
+            self.add_finding(finding);
+        }
+    }
+
+    /// Contar los hallazgos por severidad
+    pub fn severity_counts(&self) -> SeverityCounts {
+        let mut counts = SeverityCounts::default();
+        for finding in &self.findings {
+            match finding.severity {
+                SeverityLevel::Critical => counts.critical += 1,
+                SeverityLevel::Error => counts.error += 1,
+                SeverityLevel::Warning => counts.warning += 1,
+                SeverityLevel::Info => counts.info += 1,
+            }
+        }
+        counts
+    }
+
+    /// Puntuación de riesgo (0-100) ponderando los hallazgos por severidad
+    pub fn risk_score(&self) -> u32 {
+        let counts = self.severity_counts();
+        let score = counts.critical * 40 + counts.error * 25 + counts.warning * 10;
+        score.min(100) as u32
+    }
+
+    /// Nivel de riesgo derivado de la puntuación
+    pub fn risk_level(&self) -> RiskLevel {
+        RiskLevel::from_score(self.risk_score())
+    }
+
     /// Actualizar el momento de fin del análisis
     pub fn update_end_time(&mut self) {
-        self.duration = SystemTime::now()
-            .duration_since(self.created_at)
+        self.duration = (Utc::now() - self.created_at)
+            .to_std()
             .unwrap_or(Duration::from_secs(0));
     }
     
@@ -191,20 +822,20 @@ impl Report {
     
     /// Agregar una entrada al reporte
     pub fn add_entry(&mut self, entry: ReportEntry) {
-        self.findings.push(Finding {
+        self.add_finding(Finding {
             title: entry.category.clone(),
             description: entry.message.clone(),
             severity: entry.severity,
             recommendation: None,
             affected_resources: Vec::new(),
-            timestamp: SystemTime::now(),
+            timestamp: entry.timestamp,
         });
     }
     
     /// Agregar una entrada informativa
     pub fn add_info(&mut self, category: &str, message: &str, data: Option<Value>) {
         self.add_entry(ReportEntry {
-            timestamp: Utc::now().into(),
+            timestamp: Utc::now(),
             severity: SeverityLevel::Info,
             category: category.to_string(),
             message: message.to_string(),
@@ -215,7 +846,7 @@ impl Report {
     /// Agregar una entrada de advertencia
     pub fn add_warning(&mut self, category: &str, message: &str, data: Option<Value>) {
         self.add_entry(ReportEntry {
-            timestamp: Utc::now().into(),
+            timestamp: Utc::now(),
             severity: SeverityLevel::Warning,
             category: category.to_string(),
             message: message.to_string(),
@@ -226,7 +857,7 @@ impl Report {
     /// Agregar una entrada de alerta
     pub fn add_alert(&mut self, category: &str, message: &str, data: Option<Value>) {
         self.add_entry(ReportEntry {
-            timestamp: Utc::now().into(),
+            timestamp: Utc::now(),
             severity: SeverityLevel::Critical,
             category: category.to_string(),
             message: message.to_string(),
@@ -234,10 +865,21 @@ impl Report {
         });
     }
     
+    /// Agregar un patrón detectado, conservando la hora del evento
+    pub fn add_detection(&mut self, category: &str, severity: SeverityLevel, detection: &Detection) {
+        self.add_entry(ReportEntry {
+            timestamp: detection.timestamp,
+            severity,
+            category: category.to_string(),
+            message: detection.message.clone(),
+            data: None,
+        });
+    }
+    
     /// Agregar una entrada crítica
     pub fn add_critical(&mut self, category: &str, message: &str, data: Option<Value>) {
         self.add_entry(ReportEntry {
-            timestamp: Utc::now().into(),
+            timestamp: Utc::now(),
             severity: SeverityLevel::Critical,
             category: category.to_string(),
             message: message.to_string(),
@@ -245,14 +887,153 @@ impl Report {
         });
     }
     
+    /// Redactar secretos en líneas de comandos, variables de entorno, rutas y hallazgos del reporte
+    pub fn redact(&mut self, redactor: &Redactor) {
+        for process in &mut self.processes {
+            if let Some(path) = &process.path {
+                process.path = Some(redactor.redact(path));
+            }
+            if let Some(cmd) = &process.cmd_line {
+                process.cmd_line = Some(redactor.redact_args(cmd));
+            }
+            if let Some(environ) = &process.environ {
+                process.environ = Some(redactor.redact_env(environ));
+            }
+        }
+        for process in &mut self.audited_processes {
+            if let Some(cmd) = &process.cmd_line {
+                process.cmd_line = Some(redactor.redact_args(cmd));
+            }
+        }
+        
+        for activity in &mut self.file_activities {
+            let path = redactor.redact(&activity.path.to_string_lossy());
+            activity.path = PathBuf::from(path);
+        }
+        for summary in &mut self.file_access {
+            summary.path = redactor.redact(&summary.path);
+        }
+        self.file_access_index.clear();
+        
+        if let Some(logs) = &mut self.system_logs {
+            for line in &mut logs.lines {
+                line.text = redactor.redact(&line.text);
+            }
+        }
+        
+        for finding in &mut self.findings {
+            finding.title = redactor.redact(&finding.title);
+            finding.description = redactor.redact(&finding.description);
+            for resource in &mut finding.affected_resources {
+                *resource = redactor.redact(resource);
+            }
+        }
+        
+        self.summary = redactor.redact(&self.summary);
+        if let Some(analysis) = &self.offline_analysis {
+            self.offline_analysis = Some(redactor.redact(analysis));
+        }
+        for note in &mut self.analyst_notes {
+            *note = redactor.redact(note);
+        }
+    }
+    
+    /// Seudonimizar las direcciones IP de todos los archivos exportados (sección de red,
+    /// hallazgos, registro y textos). Las direcciones nuevas se registran en `pseudonymizer`,
+    /// que hay que guardar después para poder deshacer el mapeo.
+    pub fn pseudonymize_ips(&mut self, pseudonymizer: &mut IpPseudonymizer) {
+        // Registrar antes todas las direcciones para que cada formato use los mismos seudónimos
+        self.ip_pseudonyms = None;
+        if let Ok(json) = serde_json::to_string(&self) {
+            pseudonymizer.pseudonymize_text(&json);
+        }
+        pseudonymizer.pseudonymize_text(&self.generate_markdown());
+        self.ip_pseudonyms = Some(pseudonymizer.clone());
+    }
+    
+    /// Aplicar los seudónimos de IP, si están activos, a un texto exportado
+    fn export_text(&self, text: String) -> String {
+        match &self.ip_pseudonyms {
+            Some(pseudonymizer) => pseudonymizer.replace_known(&text),
+            None => text,
+        }
+    }
+    
     /// Guardar el reporte en formato JSON
     pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self)?;
+        let json = self.export_text(serde_json::to_string_pretty(&self)?);
         let mut file = File::create(path)?;
-        file.write_all(json.as_bytes())?;
+        file.write_all(&self.encoding.encode_json(&json))?;
         Ok(())
     }
     
+    /// Cargar un reporte guardado con `save_json` (admite la marca BOM de `--utf8-bom`)
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(text.trim_start_matches('\u{feff}'))?)
+    }
+    
+    /// Comparar este reporte (anterior) con otro posterior: hallazgos, destinos de red,
+    /// archivos y variación de riesgo y recursos
+    pub fn diff(&self, after: &Report) -> ReportDiff {
+        let mut sections = Vec::new();
+        
+        let findings = |report: &Report| -> Vec<String> {
+            report.findings
+                .iter()
+                .filter(|f| f.title != "llm_analysis")
+                .map(|f| format!("[{}] {}: {}", f.severity.marker(), f.title, f.description))
+                .collect()
+        };
+        let mut section = DiffSection::new("Hallazgos");
+        (section.added, section.removed) = set_difference(findings(self), findings(after));
+        sections.push(section);
+        
+        let destinations = |report: &Report| -> Vec<String> {
+            report.network_events
+                .iter()
+                .filter_map(|e| e.remote_addr)
+                .chain(report.closed_connections.iter().map(|c| c.connection.remote_addr))
+                .map(|addr| addr.to_string())
+                .collect()
+        };
+        let mut section = DiffSection::new("Destinos");
+        (section.added, section.removed) = set_difference(destinations(self), destinations(after));
+        sections.push(section);
+        
+        let files = |report: &Report| -> Vec<String> {
+            report.file_activities.iter().map(|a| a.path.display().to_string()).collect()
+        };
+        let mut section = DiffSection::new("Archivos");
+        (section.added, section.removed) = set_difference(files(self), files(after));
+        sections.push(section);
+        
+        let mut section = DiffSection::new("Recursos");
+        let (risk_before, risk_after) = (self.risk_score(), after.risk_score());
+        if risk_before != risk_after {
+            section.changed.push(format!("Riesgo: {} → {} ({:+})", risk_before, risk_after, risk_after as i64 - risk_before as i64));
+        }
+        if let (Some(before), Some(after)) = (self.processes.first(), after.processes.first()) {
+            if (before.cpu_usage - after.cpu_usage).abs() >= 0.1 {
+                section.changed.push(format!("CPU: {:.1}% → {:.1}% ({:+.1})",
+                    before.cpu_usage, after.cpu_usage, after.cpu_usage - before.cpu_usage));
+            }
+            if before.memory_usage != after.memory_usage {
+                let (sign, delta) = if after.memory_usage >= before.memory_usage {
+                    ('+', after.memory_usage - before.memory_usage)
+                } else {
+                    ('-', before.memory_usage - after.memory_usage)
+                };
+                section.changed.push(format!("Memoria: {} → {} ({}{})",
+                    format_memory(before.memory_usage), format_memory(after.memory_usage), sign, format_memory(delta)));
+            }
+        }
+        sections.push(section);
+        
+        sections.retain(|section| !section.is_empty());
+        ReportDiff { before: self.title.clone(), after: after.title.clone(), sections }
+    }
+    
     /// Generar un reporte en formato Markdown
     pub fn generate_markdown(&self) -> String {
         let mut md = String::new();
@@ -263,45 +1044,107 @@ impl Report {
         
         // Resumen
         md.push_str("## Resumen\n\n");
-        md.push_str(&format!("- **Proceso:** {}\n", self.processes[0].name));
+        if let Some(process) = self.processes.first() {
+            md.push_str(&format!("- **Proceso:** {}\n", process.name));
+        }
         md.push_str(&format!("- **Análisis iniciado:** {}\n", 
             DateTime::<Local>::from(self.created_at).format("%Y-%m-%d %H:%M:%S")));
         md.push_str(&format!("- **Análisis finalizado:** {}\n", 
             DateTime::<Local>::from(self.created_at + self.duration).format("%Y-%m-%d %H:%M:%S")));
         md.push_str(&format!("- **Duración:** {} segundos\n", self.duration.as_secs()));
-        md.push_str(&format!("- **Hallazgos detectados:** {}\n\n", self.findings.len()));
+        md.push_str(&format!("- **Hallazgos detectados:** {} ({})\n",
+            self.findings.len(), self.severity_counts().summary()));
+        md.push_str(&format!("- **Riesgo:** {} ({}/100)\n", self.risk_level().label(), self.risk_score()));
+        if let Some(model) = &self.llm_model {
+            md.push_str(&format!("- **Modelo LLM:** {}\n", model));
+        }
+        if self.sampling.total_dropped() > 0 {
+            md.push_str(&format!("- **Eventos descartados por muestreo:** {} (límite de {} por tipo)\n",
+                self.sampling.total_dropped(),
+                self.sampling.max_entries.unwrap_or(0)));
+        }
+        if !self.resource_samples.is_empty() {
+            let intervals = self.resource_samples.iter().map(|sample| sample.interval_ms);
+            md.push_str(&format!("- **Muestreo adaptativo:** {} muestras, intervalo de {} a {} ms (media {} ms)\n",
+                self.resource_samples.len(),
+                intervals.clone().min().unwrap_or(0),
+                intervals.clone().max().unwrap_or(0),
+                intervals.sum::<u64>() / self.resource_samples.len() as u64));
+        }
+        md.push('\n');
         
-        // Información del proceso
-        md.push_str("## Información del Proceso\n\n");
-        md.push_str(&format!("- **Nombre:** {}\n", self.processes[0].name));
-        if let Some(path) = &self.processes[0].path {
-            md.push_str(&format!("- **Ruta ejecutable:** {}\n", path));
+        // Contexto aportado por el analista
+        if !self.analyst_notes.is_empty() {
+            md.push_str("## Notas del Analista\n\n");
+            for note in &self.analyst_notes {
+                md.push_str(&format!("- {}\n", note));
+            }
+            md.push('\n');
         }
-        if let Some(cmd) = &self.processes[0].cmd_line {
-            md.push_str(&format!("- **Línea de comandos:** {}\n", cmd.join(" ")));
+        
+        // Información del proceso (los análisis estáticos no tienen proceso)
+        if let Some(process) = self.processes.first() {
+            md.push_str("## Información del Proceso\n\n");
+            md.push_str(&format!("- **Nombre:** {}\n", process.name));
+            if let Some(path) = &process.path {
+                md.push_str(&format!("- **Ruta ejecutable:** {}\n", path));
+            }
+            if let Some(cmd) = &process.cmd_line {
+                md.push_str(&format!("- **Línea de comandos:** {}\n", cmd.join(" ")));
+                if process.cmd_truncated {
+                    md.push_str(&format!(
+                        "  - *Recortada a los {} primeros argumentos (ver `--max-cmd-args`)*\n", cmd.len()
+                    ));
+                }
+            }
+            md.push_str(&format!("- **Uso de CPU:** {:.2}%\n", process.cpu_usage));
+            md.push_str(&format!("- **Uso de memoria:** {}\n", format_memory(process.memory_usage)));
+            if process.status != ProcessStatus::Unknown {
+                md.push_str(&format!("- **Estado:** {}\n", process.status));
+            }
+            if let Some(threads) = process.thread_count {
+                md.push_str(&format!("- **Hilos:** {}\n", threads));
+            }
+            md.push_str(&format!("- **Tiempo de inicio:** {}\n", 
+                DateTime::<Local>::from(process.start_time).format("%Y-%m-%d %H:%M:%S")));
+            if !process.children.is_empty() {
+                md.push_str(&format!("- **Procesos hijos:** {}\n", process.children.len()));
+                for child_pid in &process.children {
+                    md.push_str(&format!("  - PID: {}\n", child_pid));
+                }
+            }
+            md.push('\n');
         }
-        md.push_str(&format!("- **Uso de CPU:** {:.2}%\n", self.processes[0].cpu_usage));
-        md.push_str(&format!("- **Uso de memoria:** {} KB\n", self.processes[0].memory_usage));
-        md.push_str(&format!("- **Tiempo de inicio:** {}\n", 
-            DateTime::<Local>::from(self.processes[0].start_time).format("%Y-%m-%d %H:%M:%S")));
-        if !self.processes[0].children.is_empty() {
-            md.push_str(&format!("- **Procesos hijos:** {}\n", self.processes[0].children.len()));
-            for child_pid in &self.processes[0].children {
-                md.push_str(&format!("  - PID: {}\n", child_pid));
+        
+        // Actividad de cada proceso del árbol auditado
+        if self.audited_processes.len() > 1 {
+            md.push_str("## Árbol de Procesos Auditado\n\n");
+            md.push_str("| PID | Padre | Nombre | Activo | Archivos | Conexiones | Línea de comandos |\n");
+            md.push_str("|-----|-------|--------|--------|----------|------------|-------------------|\n");
+            for process in &self.audited_processes {
+                let lifetime = process.exited_at
+                    .and_then(|exited| (exited - process.first_seen).to_std().ok())
+                    .map(|lifetime| format!("{:.1} s", lifetime.as_secs_f64()))
+                    .unwrap_or_else(|| "hasta el final".to_string());
+                md.push_str(&format!("| {} | {} | {} | {} | {} | {} | {} |\n",
+                    process.pid,
+                    process.parent_pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+                    process.name,
+                    lifetime,
+                    self.file_activities.iter().filter(|a| a.process_id == Some(process.pid)).count(),
+                    self.network_events.iter().filter(|e| e.pid == process.pid).count(),
+                    process.cmd_line.as_ref().map(|cmd| cmd.join(" ")).unwrap_or_default(),
+                ));
             }
+            md.push('\n');
         }
-        md.push_str("\n");
         
         // Hallazgos detectados
         if !self.findings.is_empty() {
             md.push_str("## Hallazgos Detectados\n\n");
             for finding in &self.findings {
-                let severity_marker = match finding.severity {
-                    SeverityLevel::Critical => "🔴 CRÍTICO",
-                    SeverityLevel::Error => "🟠 ERROR",
-                    _ => "",
-                };
-                md.push_str(&format!("### {} - {}\n\n", severity_marker, finding.title));
+                // La severidad va siempre en texto, no solo en el color del emoji
+                md.push_str(&format!("### {} - {}\n\n", finding.severity.marker(), finding.title));
                 md.push_str(&format!("{}\n\n", finding.description));
                 if let Some(recommendation) = &finding.recommendation {
                     md.push_str(&format!("**Recomendación:** {}\n\n", recommendation));
@@ -311,7 +1154,7 @@ impl Report {
                     for resource in &finding.affected_resources {
                         md.push_str(&format!("- {}\n", resource));
                     }
-                    md.push_str("\n");
+                    md.push('\n');
                 }
             }
         }
@@ -330,23 +1173,47 @@ impl Report {
             for (op, count) in operations {
                 md.push_str(&format!("- {}: {} operaciones\n", op, count));
             }
-            md.push_str("\n");
+            md.push('\n');
             
-            // Top 10 archivos más accedidos
-            let mut file_access: HashMap<String, usize> = HashMap::new();
-            for activity in &self.file_activities {
-                let path_str = activity.path.to_string_lossy().to_string();
-                *file_access.entry(path_str).or_insert(0) += 1;
+            // Accesos repetidos agrupados por ruta y operación, los más frecuentes primero
+            let mut patterns = self.file_access_patterns();
+            patterns.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.first_seen.cmp(&b.first_seen)));
+            md.push_str("### Patrones de acceso\n\n");
+            md.push_str("| Archivo | Operación | Veces | Primera | Última | Bytes |\n");
+            md.push_str("|---------|-----------|-------|---------|--------|-------|\n");
+            for pattern in patterns.iter().take(MARKDOWN_FILE_PATTERNS) {
+                md.push_str(&format!(
+                    "| `{}` | {} | {} | {} | {} | {} |\n",
+                    pattern.path.replace('|', "\\|"),
+                    pattern.operation.label(),
+                    pattern.count,
+                    DateTime::<Local>::from(pattern.first_seen).format("%H:%M:%S"),
+                    DateTime::<Local>::from(pattern.last_seen).format("%H:%M:%S"),
+                    format_memory(pattern.total_bytes),
+                ));
             }
+            if patterns.len() > MARKDOWN_FILE_PATTERNS {
+                md.push_str(&format!(
+                    "\n*… y {} patrones más (se muestran los {} más frecuentes)*\n",
+                    patterns.len() - MARKDOWN_FILE_PATTERNS,
+                    MARKDOWN_FILE_PATTERNS
+                ));
+            }
+            md.push('\n');
             
-            let mut file_access_vec: Vec<(String, usize)> = file_access.into_iter().collect();
-            file_access_vec.sort_by(|a, b| b.1.cmp(&a.1));
-            
-            md.push_str("### Top archivos accedidos\n\n");
-            for (i, (path, count)) in file_access_vec.iter().take(10).enumerate() {
-                md.push_str(&format!("{}. `{}` - {} accesos\n", i+1, path, count));
+            // Escrituras con entropía calculada
+            let with_entropy: Vec<&FileActivity> = self.file_activities
+                .iter()
+                .filter(|a| a.entropy.is_some())
+                .collect();
+            if !with_entropy.is_empty() {
+                md.push_str("### Entropía de escrituras\n\n");
+                for activity in with_entropy {
+                    md.push_str(&format!("- `{}` - {:.2} bits/byte\n",
+                        activity.path.display(), activity.entropy.unwrap_or(0.0)));
+                }
+                md.push('\n');
             }
-            md.push_str("\n");
         }
         
         // Resumen de conexiones de red
@@ -375,15 +1242,109 @@ impl Report {
             }
             
             let mut dest_vec: Vec<(String, usize)> = destinations.into_iter().collect();
-            dest_vec.sort_by(|a, b| b.1.cmp(&a.1));
+            dest_vec.sort_by_key(|(_, count)| Reverse(*count));
             
             if !dest_vec.is_empty() {
                 md.push_str("### Top destinos de conexión\n\n");
                 for (i, (addr, count)) in dest_vec.iter().take(10).enumerate() {
                     md.push_str(&format!("{}. `{}` - {} conexiones\n", i+1, addr, count));
                 }
-                md.push_str("\n");
+                md.push('\n');
+            }
+        }
+        
+        // Destinos agrupados por la organización propietaria de cada dirección
+        if !self.egress_by_asn.is_empty() {
+            md.push_str("### Salida por organización (ASN)\n\n");
+            md.push_str("| Organización | Conexiones | Hosts | Línea base |\n");
+            md.push_str("|--------------|------------|-------|------------|\n");
+            for group in &self.egress_by_asn {
+                md.push_str(&format!("| {} | {} | {} | {} |\n",
+                    group.label(),
+                    group.connections,
+                    group.hosts,
+                    if group.unseen { "⚠️ nuevo" } else { "" },
+                ));
             }
+            md.push('\n');
+        }
+        
+        // Conexiones breves que ya no aparecen entre las activas
+        if !self.closed_connections.is_empty() {
+            md.push_str("## Conexiones Cerradas Recientes\n\n");
+            md.push_str("| Protocolo | Local | Remota | Duración | Enviados | Recibidos |\n");
+            md.push_str("|-----------|-------|--------|----------|----------|-----------|\n");
+            for closed in self.closed_connections.iter().rev() {
+                let conn = &closed.connection;
+                md.push_str(&format!("| {:?} | {} | {} | {:.1} s | {} B | {} B |\n",
+                    conn.protocol,
+                    conn.local_addr,
+                    conn.remote_addr,
+                    closed.duration().as_secs_f64(),
+                    conn.bytes_sent,
+                    conn.bytes_received,
+                ));
+            }
+            md.push('\n');
+        }
+        
+        // Llamadas al sistema por categoría y las más frecuentes
+        if let Some(syscalls) = &self.syscalls {
+            md.push_str("## Llamadas al Sistema\n\n");
+            if syscalls.timed_out {
+                md.push_str("El binario se detuvo al alcanzar el tiempo máximo.\n\n");
+            } else if let Some(code) = syscalls.exit_code {
+                md.push_str(&format!("El binario terminó con código {}.\n\n", code));
+            }
+            let total = syscalls.total_calls().max(1);
+            md.push_str("| Categoría | Llamadas | % |\n");
+            md.push_str("|-----------|----------|---|\n");
+            for (category, calls) in syscalls.category_totals() {
+                md.push_str(&format!("| {} | {} | {:.1} |\n",
+                    category.label(), calls, calls as f64 * 100.0 / total as f64));
+            }
+            md.push_str("\n| Llamada | Categoría | Llamadas | Errores |\n");
+            md.push_str("|---------|-----------|----------|---------|\n");
+            for syscall in syscalls.syscalls.iter().take(MARKDOWN_TOP_SYSCALLS) {
+                md.push_str(&format!("| {} | {} | {} | {} |\n",
+                    syscall.name, syscall.category.label(), syscall.calls, syscall.errors));
+            }
+            md.push('\n');
+        }
+        
+        // Análisis generado con reglas, sin LLM (ya incluye su encabezado)
+        if let Some(analysis) = &self.offline_analysis {
+            md.push_str(analysis);
+            md.push_str("\n\n");
+        }
+        
+        // Líneas de los registros del sistema relacionadas con el proceso
+        if let Some(logs) = &self.system_logs {
+            md.push_str("## Registros del Sistema\n\n");
+            md.push_str(&format!("{}.\n\n", logs.summary()));
+            for reason in &logs.unavailable {
+                md.push_str(&format!("- No disponible: {}\n", reason));
+            }
+            if !logs.unavailable.is_empty() {
+                md.push('\n');
+            }
+            if !logs.lines.is_empty() {
+                let skipped = logs.lines.len().saturating_sub(MARKDOWN_LOG_LINES);
+                if skipped > 0 {
+                    md.push_str(&format!("Últimas {} líneas (el JSON incluye las {}):\n\n", MARKDOWN_LOG_LINES, logs.lines.len()));
+                }
+                md.push_str("```\n");
+                for line in &logs.lines[skipped..] {
+                    md.push_str(&line.text);
+                    md.push('\n');
+                }
+                md.push_str("```\n\n");
+            }
+        }
+        
+        // Eventos de archivo y red intercalados en el tiempo
+        if !self.file_activities.is_empty() || !self.network_events.is_empty() {
+            md.push_str(&self.generate_timeline_markdown(TimelineFilter::All));
         }
         
         // Registro cronológico de eventos
@@ -391,14 +1352,12 @@ impl Report {
         md.push_str("| Tiempo | Severidad | Categoría | Mensaje |\n");
         md.push_str("|--------|-----------|-----------|--------|\n");
         
-        for finding in &self.findings {
+        // Ordenar por la hora del evento, no por el orden en que se agregaron
+        let mut chronological: Vec<&Finding> = self.findings.iter().collect();
+        chronological.sort_by_key(|finding| finding.timestamp);
+        for finding in chronological {
             let local_time = DateTime::<Local>::from(finding.timestamp);
-            let severity = match finding.severity {
-                SeverityLevel::Info => "INFO",
-                SeverityLevel::Warning => "⚠️ WARN",
-                SeverityLevel::Error => "🟠 ERROR",
-                SeverityLevel::Critical => "🔴 CRÍTICO",
-            };
+            let severity = finding.severity.marker();
             
             let message = finding.title.replace("|", "\\|");  // Escapar caracteres pipe para Markdown
             
@@ -410,20 +1369,296 @@ impl Report {
             ));
         }
         
+        // Apéndice con los eventos en bruto, solo con `-vv` o más
+        if self.verbosity >= RAW_EVENTS_VERBOSITY {
+            md.push('\n');
+            md.push_str(&self.generate_raw_events_markdown());
+        }
+        
+        md
+    }
+    
+    /// Apéndice "Eventos Detallados": cada actividad de archivo y evento de red, en orden
+    /// cronológico. Con `-vv` se listan los `MARKDOWN_RAW_EVENTS` primeros de cada tipo y
+    /// con `-vvv`, todos
+    pub fn generate_raw_events_markdown(&self) -> String {
+        let limit = if self.verbosity >= RAW_EVENTS_ALL_VERBOSITY { usize::MAX } else { MARKDOWN_RAW_EVENTS };
+        let mut md = String::from("## Eventos Detallados\n\n");
+        if self.sampling.file_activities_dropped + self.sampling.network_events_dropped > 0 {
+            md.push_str(&format!(
+                "*El reporte conserva una muestra de los eventos (límite de {} por tipo, ver `--max-report-entries`).*\n\n",
+                self.sampling.max_entries.unwrap_or(0)
+            ));
+        }
+        
+        let mut files: Vec<&FileActivity> = self.file_activities.iter().collect();
+        files.sort_by_key(|activity| activity.timestamp);
+        md.push_str(&format!("### Archivos ({})\n\n", files.len()));
+        if files.is_empty() {
+            md.push_str("No se registraron eventos de archivo.\n\n");
+        } else {
+            md.push_str("| Hora | PID | Operación | Archivo | Tamaño | Entropía |\n");
+            md.push_str("|------|-----|-----------|---------|--------|----------|\n");
+            for activity in files.iter().take(limit) {
+                let path = match &activity.operation {
+                    FileOperation::Rename { old_path } => format!("{} → {}", old_path.display(), activity.path.display()),
+                    _ => activity.path.display().to_string(),
+                };
+                md.push_str(&format!(
+                    "| {} | {} | {} | `{}` | {} | {} |\n",
+                    DateTime::<Local>::from(activity.timestamp).format("%H:%M:%S%.3f"),
+                    activity.process_id.map_or("-".to_string(), |pid| pid.to_string()),
+                    activity.operation.label(),
+                    path.replace('|', "\\|"),
+                    activity.size.map_or(String::new(), format_memory),
+                    activity.entropy.map_or(String::new(), |entropy| format!("{:.2}", entropy)),
+                ));
+            }
+            md.push_str(&raw_events_truncated(files.len(), limit));
+        }
+        
+        let mut network: Vec<&NetworkEvent> = self.network_events.iter().collect();
+        network.sort_by_key(|event| event.timestamp);
+        md.push_str(&format!("### Red ({})\n\n", network.len()));
+        if network.is_empty() {
+            md.push_str("No se registraron eventos de red.\n\n");
+        } else {
+            md.push_str("| Hora | PID | Protocolo | Sentido | Local | Remoto | Estado | Enviados | Recibidos |\n");
+            md.push_str("|------|-----|-----------|---------|-------|--------|--------|----------|-----------|\n");
+            for event in network.iter().take(limit) {
+                md.push_str(&format!(
+                    "| {} | {} | {:?} | {} | {} | {} | {} | {} | {} |\n",
+                    DateTime::<Local>::from(event.timestamp).format("%H:%M:%S%.3f"),
+                    event.pid,
+                    event.protocol,
+                    match event.direction {
+                        Direction::Outbound => "saliente",
+                        Direction::Inbound => "entrante",
+                    },
+                    event.local_addr,
+                    event.remote_addr.map_or("-".to_string(), |addr| addr.to_string()),
+                    event.state.label(),
+                    event.bytes_sent.map_or(String::new(), format_memory),
+                    event.bytes_received.map_or(String::new(), format_memory),
+                ));
+            }
+            md.push_str(&raw_events_truncated(network.len(), limit));
+        }
         md
     }
     
+    /// Actividad de archivos y eventos de red del reporte en un único orden cronológico
+    pub fn timeline(&self) -> Vec<TimelineEntry> {
+        timeline::merge(
+            self.file_activities.iter().map(TimelineEntry::from_file_activity).collect(),
+            self.network_events.iter().map(TimelineEntry::from_network_event).collect(),
+        )
+    }
+    
+    /// Sección "Línea de Tiempo" en Markdown con los eventos que deja pasar el filtro
+    pub fn generate_timeline_markdown(&self, filter: TimelineFilter) -> String {
+        timeline::markdown(&self.timeline(), filter)
+    }
+    
     /// Guardar el reporte en formato Markdown
     pub fn save_markdown<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let markdown = self.generate_markdown();
+        let markdown = self.export_text(self.generate_markdown());
+        let mut file = File::create(path)?;
+        file.write_all(&self.encoding.encode_text(&markdown))?;
+        Ok(())
+    }
+    
+    /// Generar un resumen en Markdown para compartir el veredicto (p. ej. en un ticket):
+    /// identidad del proceso, riesgo, hallazgos y conclusión del LLM, sin las tablas de
+    /// archivos y red ni el registro cronológico
+    pub fn generate_summary_markdown(&self) -> String {
+        let mut md = String::new();
+        
+        md.push_str(&format!("# Resumen ShadowTrace: {} (ID: {})\n\n", self.title, self.id));
+        md.push_str(&format!("**Generado el:** {}\n\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+        
+        // Identidad del proceso (los análisis estáticos no tienen proceso)
+        if let Some(process) = self.processes.first() {
+            md.push_str("## Proceso\n\n");
+            md.push_str(&format!("- **Nombre:** {} (PID: {})\n", process.name, process.pid));
+            if let Some(path) = &process.path {
+                md.push_str(&format!("- **Ruta ejecutable:** {}\n", path));
+            }
+            if let Some(user) = &process.user {
+                md.push_str(&format!("- **Usuario:** {}\n", user));
+            }
+            md.push('\n');
+        }
+        
+        md.push_str("## Veredicto\n\n");
+        md.push_str(&format!("- **Riesgo:** {} ({}/100)\n", self.risk_level().label(), self.risk_score()));
+        md.push_str(&format!("- **Hallazgos detectados:** {} ({})\n",
+            self.findings.len(), self.severity_counts().summary()));
+        md.push_str(&format!("- **Duración:** {} segundos\n\n", self.duration.as_secs()));
+        
+        // Los hallazgos informativos son datos del monitoreo, no conclusiones
+        md.push_str("## Hallazgos\n\n");
+        let relevant: Vec<&Finding> = self.findings
+            .iter()
+            .filter(|finding| finding.severity != SeverityLevel::Info)
+            .collect();
+        if relevant.is_empty() {
+            md.push_str("Sin hallazgos de advertencia o superiores.\n");
+        }
+        for finding in relevant {
+            md.push_str(&format!("- **{}** {}: {}\n", finding.severity.marker(), finding.title, finding.description));
+            if let Some(recommendation) = &finding.recommendation {
+                md.push_str(&format!("  - *Recomendación:* {}\n", recommendation));
+            }
+        }
+        md.push('\n');
+        
+        // Conclusión del LLM: el resumen del reporte o, si no lo hay, el hallazgo del análisis
+        let llm_summary = Some(self.summary.as_str())
+            .filter(|summary| !summary.is_empty())
+            .or_else(|| {
+                self.findings
+                    .iter()
+                    .find(|finding| finding.title == "llm_analysis")
+                    .map(|finding| finding.description.as_str())
+            });
+        if let Some(summary) = llm_summary {
+            md.push_str("## Análisis LLM\n\n");
+            md.push_str(&format!("{}\n\n", summary));
+            if let Some(model) = &self.llm_model {
+                md.push_str(&format!("*Modelo: {}*\n", model));
+            }
+        }
+        if let Some(analysis) = &self.offline_analysis {
+            md.push_str(analysis);
+            md.push('\n');
+        }
+        
+        md
+    }
+    
+    /// Guardar el resumen en formato Markdown
+    pub fn save_summary_markdown<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let markdown = self.export_text(self.generate_summary_markdown());
+        let mut file = File::create(path)?;
+        file.write_all(&self.encoding.encode_text(&markdown))?;
+        Ok(())
+    }
+    
+    /// Generar la actividad de archivos en bruto en CSV (los reportes JSON y Markdown
+    /// también la resumen agrupada en `file_access`)
+    pub fn generate_file_activity_csv(&self) -> String {
+        let mut csv = String::from("timestamp,pid,operation,path,old_path,size,entropy\n");
+        for activity in &self.file_activities {
+            let operation = serde_json::to_value(&activity.operation).unwrap_or_default();
+            let (operation, old_path) = match &activity.operation {
+                FileOperation::Rename { old_path } => ("Rename".to_string(), old_path.to_string_lossy().to_string()),
+                _ => (operation.as_str().unwrap_or_default().to_string(), String::new()),
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                activity.timestamp.to_rfc3339(),
+                activity.process_id.map(|pid| pid.to_string()).unwrap_or_default(),
+                operation,
+                csv_field(&activity.path.to_string_lossy()),
+                csv_field(&old_path),
+                activity.size.map(|size| size.to_string()).unwrap_or_default(),
+                activity.entropy.map(|entropy| format!("{:.4}", entropy)).unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+    
+    /// Guardar la actividad de archivos en bruto en CSV
+    pub fn save_file_activity_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let csv = self.export_text(self.generate_file_activity_csv());
         let mut file = File::create(path)?;
-        file.write_all(markdown.as_bytes())?;
+        file.write_all(&self.encoding.encode_text(&csv))?;
         Ok(())
     }
     
+    /// Guardar el CSV de actividad de archivos junto a los reportes completos
+    pub fn save_file_activity_csv_to_default_dir(&self) -> Result<PathBuf> {
+        let path = Self::default_dir()?.join(self.generate_filename("files.csv"));
+        self.save_file_activity_csv(&path)?;
+        Ok(path)
+    }
+    
+    /// Generar las métricas del reporte en el formato de texto de Prometheus
+    pub fn generate_prometheus(&self) -> String {
+        let mut out = String::new();
+        let counts = self.severity_counts();
+        
+        out.push_str("# HELP shadowtrace_findings_total Hallazgos del último reporte por severidad\n");
+        out.push_str("# TYPE shadowtrace_findings_total gauge\n");
+        for (severity, count) in [
+            ("critical", counts.critical),
+            ("error", counts.error),
+            ("warning", counts.warning),
+            ("info", counts.info),
+        ] {
+            out.push_str(&format!("shadowtrace_findings_total{{severity=\"{}\"}} {}\n", severity, count));
+        }
+        
+        out.push_str("# HELP shadowtrace_risk_score Puntuación de riesgo del último reporte (0-100)\n");
+        out.push_str("# TYPE shadowtrace_risk_score gauge\n");
+        out.push_str(&format!("shadowtrace_risk_score {}\n", self.risk_score()));
+        
+        out.push_str("# HELP shadowtrace_report_timestamp_seconds Momento de creación del último reporte\n");
+        out.push_str("# TYPE shadowtrace_report_timestamp_seconds gauge\n");
+        out.push_str(&format!("shadowtrace_report_timestamp_seconds {}\n", self.created_at.timestamp()));
+        
+        out.push_str("# HELP shadowtrace_report_duration_seconds Duración del análisis\n");
+        out.push_str("# TYPE shadowtrace_report_duration_seconds gauge\n");
+        out.push_str(&format!("shadowtrace_report_duration_seconds {}\n", self.duration.as_secs_f64()));
+        
+        let processes = &self.processes;
+        if !processes.is_empty() {
+            out.push_str("# HELP shadowtrace_process_cpu_usage_percent Uso de CPU del proceso analizado\n");
+            out.push_str("# TYPE shadowtrace_process_cpu_usage_percent gauge\n");
+            for process in processes {
+                out.push_str(&format!(
+                    "shadowtrace_process_cpu_usage_percent{{pid=\"{}\",name=\"{}\"}} {}\n",
+                    process.pid, prometheus_label(&process.name), process.cpu_usage
+                ));
+            }
+            out.push_str("# HELP shadowtrace_process_memory_bytes Memoria residente del proceso analizado\n");
+            out.push_str("# TYPE shadowtrace_process_memory_bytes gauge\n");
+            for process in processes {
+                out.push_str(&format!(
+                    "shadowtrace_process_memory_bytes{{pid=\"{}\",name=\"{}\"}} {}\n",
+                    process.pid, prometheus_label(&process.name), process.memory_usage
+                ));
+            }
+        }
+        
+        out
+    }
+    
+    /// Exportar las métricas a un archivo para el textfile collector de node_exporter.
+    /// Se escribe en un temporal y se renombra para que nunca se lea un archivo a medias.
+    pub fn export_prometheus(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        
+        fs::write(&tmp, self.export_text(self.generate_prometheus()))?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+    
+    /// Archivo de métricas por defecto (~/.shadowtrace/reports/shadowtrace.prom)
+    pub fn default_prometheus_path() -> Option<PathBuf> {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("reports").join("shadowtrace.prom"))
+    }
+    
     /// Generar nombre de archivo para el reporte basado en tiempo y proceso
     pub fn generate_filename(&self, extension: &str) -> String {
-        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        // La hora de creación (y no la actual) para que todos los archivos del reporte compartan nombre
+        let timestamp = DateTime::<Local>::from(self.created_at).format("%Y%m%d_%H%M%S");
         format!("shadowtrace_{}_id{}_{}.{}", 
             self.title.replace(" ", "_"), 
             self.id, 
@@ -431,9 +1666,13 @@ impl Report {
             extension)
     }
     
-    /// Guardar en directorio por defecto
-    pub fn save_to_default_dir(&self) -> Result<(PathBuf, PathBuf)> {
-        // Crear directorio de reportes si no existe
+    /// Ruta del directorio por defecto de los reportes (~/.shadowtrace/reports), sin crearlo
+    pub fn default_dir_path() -> Option<PathBuf> {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("reports"))
+    }
+
+    /// Directorio de reportes por defecto (~/.shadowtrace/reports), creado si no existe
+    fn default_dir() -> Result<PathBuf> {
         let base_dir = if let Some(base_dirs) = BaseDirs::new() {
             let home_dir = base_dirs.home_dir();
             home_dir.join(".shadowtrace").join("reports")
@@ -442,6 +1681,13 @@ impl Report {
         };
             
         fs::create_dir_all(&base_dir)?;
+        Ok(base_dir)
+    }
+    
+    /// Guardar en directorio por defecto
+    pub fn save_to_default_dir(&self) -> Result<(PathBuf, PathBuf)> {
+        // Crear directorio de reportes si no existe
+        let base_dir = Self::default_dir()?;
         
         // Generar nombres de archivo
         let json_filename = self.generate_filename("json");
@@ -456,15 +1702,39 @@ impl Report {
         
         Ok((json_path, md_path))
     }
+    
+    /// Guardar el resumen junto a los reportes completos en el directorio por defecto
+    pub fn save_summary_to_default_dir(&self) -> Result<PathBuf> {
+        let path = Self::default_dir()?.join(self.generate_filename("summary.md"));
+        self.save_summary_markdown(&path)?;
+        Ok(path)
+    }
+
+    /// Archivos guardados en el directorio por defecto, del más reciente al más antiguo
+    pub fn list_saved() -> Result<Vec<SavedReportFile>> {
+        let mut files: Vec<SavedReportFile> = fs::read_dir(Self::default_dir()?)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+                Some(SavedReportFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok().map(DateTime::<Local>::from),
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.path.cmp(&b.path)));
+        Ok(files)
+    }
 
     /// Generar un reporte de ejemplo para propósitos de demo
     pub fn demo() -> Self {
         let now = Utc::now();
         
         Self {
-            id: format!("report_{}", now.timestamp()),
+            id: report_id(now),
             title: String::from("Reporte de demostración"),
-            created_at: now.into(),
+            created_at: now,
             status: ReportStatus::Completed,
             duration: Duration::from_secs(60),
             processes: vec![ProcessInfo {
@@ -472,16 +1742,164 @@ impl Report {
                 name: String::from("demo_process"),
                 path: Some(String::from("/usr/bin/demo_process")),
                 cmd_line: Some(vec![String::from("/usr/bin/demo_process"), String::from("--arg1"), String::from("--arg2")]),
+                cmd_truncated: false,
                 cpu_usage: 5.2,
                 memory_usage: 128,
-                start_time: now.into(),
+                start_time: now,
                 children: Vec::new(),
                 user: Some(String::from("usuario")),
+                thread_count: Some(4),
+                status: ProcessStatus::Sleeping,
+                deleted_exe: Some(false),
+                environ: None,
             }],
             file_activities: Vec::new(),
             network_events: Vec::new(),
+            file_access: Vec::new(),
+            file_access_index: HashMap::new(),
+            closed_connections: Vec::new(),
+            audited_processes: Vec::new(),
+            syscalls: None,
+            egress_by_asn: Vec::new(),
+            system_logs: None,
             findings: Vec::new(),
             summary: String::from("Este es un reporte de demostración generado automáticamente."),
+            llm_model: None,
+            offline_analysis: None,
+            analyst_notes: Vec::new(),
+            sampling: ReportSampling::default(),
+            resource_samples: Vec::new(),
+            encoding: ReportEncoding::default(),
+            verbosity: 0,
+            ip_pseudonyms: None,
+        }
+    }
+}
+
+/// Escapar el valor de una etiqueta de Prometheus (barra invertida, comillas y saltos de línea)
+fn prometheus_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_reports_contain_only_ascii_after_the_bom() {
+        let dir = std::env::temp_dir().join(format!("shadowtrace-ascii-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let description = "⚠️ Conexión rápida → 10.0.0.1 ñ 日本 👨‍💻";
+        let mut report = Report::demo();
+        report.add_finding(Finding {
+            title: "Glifos".to_string(),
+            description: description.to_string(),
+            severity: SeverityLevel::Warning,
+            recommendation: Some("Revisar ✅".to_string()),
+            affected_resources: vec!["/tmp/café".to_string()],
+            timestamp: Utc::now(),
+        });
+        report.set_encoding(ReportEncoding { ascii: true, bom: true });
+
+        let markdown = dir.join("report.md");
+        let summary = dir.join("summary.md");
+        let json = dir.join("report.json");
+        report.save_markdown(&markdown).unwrap();
+        report.save_summary_markdown(&summary).unwrap();
+        report.save_json(&json).unwrap();
+
+        for path in [&markdown, &summary, &json] {
+            let bytes = fs::read(path).unwrap();
+            assert_eq!(&bytes[..3], &[0xEF, 0xBB, 0xBF], "{} sin BOM", path.display());
+            assert!(bytes[3..].is_ascii(), "{} contiene bytes no ASCII", path.display());
         }
+        let markdown = fs::read_to_string(&markdown).unwrap();
+        assert!(markdown.contains("Conexion rapida"));
+
+        // El JSON escapa los caracteres en lugar de sustituirlos, así que se recuperan al cargarlo
+        let loaded = Report::load_json(&json).unwrap();
+        assert!(loaded.findings.iter().any(|finding| finding.description == description));
+
+        fs::remove_dir_all(&dir).unwrap();
     }
-} 
+
+    #[test]
+    fn saved_reports_round_trip_timestamps() {
+        let dir = std::env::temp_dir().join(format!("shadowtrace-roundtrip-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Con nanosegundos, que el formato anterior de SystemTime también conservaba
+        let event_time: DateTime<Utc> = "2026-10-16T08:30:15.123456789Z".parse().unwrap();
+        let mut report = Report::new("Ida y vuelta");
+        report.add_finding(Finding {
+            title: "Hallazgo".to_string(),
+            description: "con hora del evento".to_string(),
+            severity: SeverityLevel::Warning,
+            recommendation: None,
+            affected_resources: Vec::new(),
+            timestamp: event_time,
+        });
+        report.add_file_activity(FileActivity {
+            path: PathBuf::from("/tmp/archivo"),
+            operation: FileOperation::Write,
+            process_id: Some(7),
+            timestamp: event_time + chrono::Duration::seconds(1),
+            size: None,
+            entropy: None,
+        });
+
+        let path = dir.join("report.json");
+        report.save_json(&path).unwrap();
+        let loaded = Report::load_json(&path).unwrap();
+        assert_eq!(loaded.created_at, report.created_at);
+        assert_eq!(loaded.findings[0].timestamp, event_time);
+        assert_eq!(loaded.file_activities[0].timestamp, report.file_activities[0].timestamp);
+
+        // Los reportes de versiones anteriores guardaban las fechas como SystemTime
+        let mut legacy: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        legacy["findings"][0]["timestamp"] = serde_json::json!({
+            "secs_since_epoch": event_time.timestamp(),
+            "nanos_since_epoch": event_time.timestamp_subsec_nanos(),
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+        assert_eq!(Report::load_json(&path).unwrap().findings[0].timestamp, event_time);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn back_to_back_reports_get_distinct_ids_and_filenames() {
+        let first = Report::new("Rápido");
+        let second = Report::new("Rápido");
+        let third = Report::new_for_process(42, "worker".to_string());
+        assert_ne!(first.id, second.id);
+        assert_ne!(second.id, third.id);
+        assert_ne!(first.generate_filename("md"), second.generate_filename("md"));
+        assert!(first.generate_filename("json").contains(&first.id));
+    }
+
+    #[test]
+    fn findings_keep_event_time_and_log_is_ordered_by_it() {
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let detection = |minutes: i64| Detection {
+            message: format!("evento +{}m", minutes),
+            timestamp: base + chrono::Duration::minutes(minutes),
+        };
+
+        // Se agregan en orden inverso al de los eventos
+        let mut report = Report::new("Orden");
+        report.add_detection("Tercero", SeverityLevel::Warning, &detection(30));
+        report.add_detection("Primero", SeverityLevel::Warning, &detection(10));
+        report.add_detection("Segundo", SeverityLevel::Warning, &detection(20));
+
+        let timestamps: Vec<_> = report.findings.iter().map(|finding| finding.timestamp).collect();
+        assert_eq!(timestamps, vec![detection(30).timestamp, detection(10).timestamp, detection(20).timestamp]);
+
+        let markdown = report.generate_markdown();
+        let log = &markdown[markdown.find("## Registro Cronológico").unwrap()..];
+        let position = |title: &str| log.find(&format!("| {} |", title)).unwrap();
+        assert!(position("Primero") < position("Segundo"));
+        assert!(position("Segundo") < position("Tercero"));
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::file_monitor::FileEvent;
+use crate::network::NetworkEvent;
+use crate::process::{ProcessInfo, ProcessStatus};
+
+/// Nombre del proceso cuando la captura no lo indica
+const UNNAMED_PROCESS: &str = "muestra";
+
+/// Proceso al que pertenecen los eventos de una captura
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleProcess {
+    /// ID del proceso en la captura
+    pub pid: u32,
+    /// Nombre del proceso
+    pub name: String,
+    /// Ruta ejecutable
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Argumentos de línea de comandos
+    #[serde(default)]
+    pub cmd_line: Option<Vec<String>>,
+    /// Usuario que ejecutaba el proceso
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// Eventos de archivo y red grabados (`monitor --sample-file`): se cargan en los
+/// monitores en lugar de observar un proceso en vivo, y pasan por los mismos
+/// detectores, reporte y análisis. Los eventos usan el mismo formato que
+/// `FileEvent` y `NetworkEvent` en los reportes JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleCapture {
+    /// Proceso analizado; si falta, se toma el único PID de los eventos
+    #[serde(default)]
+    pub process: Option<SampleProcess>,
+    /// Operaciones de archivo
+    #[serde(default)]
+    pub file_events: Vec<FileEvent>,
+    /// Eventos de red
+    #[serde(default)]
+    pub network_events: Vec<NetworkEvent>,
+}
+
+impl SampleCapture {
+    /// Leer y validar una captura: JSON con el esquema de `SampleCapture`, al menos un
+    /// evento y un proceso identificable con eventos propios
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| AppError::ConfigurationError(format!(
+            "No se pudo leer el archivo de muestra {}: {}", path.display(), e
+        )))?;
+        let mut capture: Self = serde_json::from_str(&content).map_err(|e| AppError::ConfigurationError(format!(
+            "El archivo de muestra {} no es válido: {} (se espera un objeto con \"process\", \"file_events\" y \"network_events\")",
+            path.display(), e
+        )))?;
+        capture.validate().map_err(|reason| AppError::ConfigurationError(format!(
+            "El archivo de muestra {} no es válido: {}", path.display(), reason
+        )))?;
+        capture.file_events.sort_by_key(|event| event.timestamp);
+        capture.network_events.sort_by_key(|event| event.timestamp);
+        Ok(capture)
+    }
+
+    /// Comprobaciones que el esquema no expresa
+    fn validate(&self) -> Result<(), String> {
+        if self.file_events.is_empty() && self.network_events.is_empty() {
+            return Err("no contiene eventos de archivo ni de red".to_string());
+        }
+        if let Some(event) = self.file_events.iter().find(|event| event.path.is_empty()) {
+            return Err(format!("un evento de archivo del PID {} no tiene ruta", event.pid));
+        }
+        let pids = self.pids();
+        match &self.process {
+            Some(process) if process.name.trim().is_empty() => {
+                Err("\"process.name\" está vacío".to_string())
+            }
+            Some(process) if !pids.contains(&process.pid) => Err(format!(
+                "ningún evento pertenece al PID {} de \"process\" (PIDs en los eventos: {})",
+                process.pid, join_pids(&pids)
+            )),
+            None if pids.len() > 1 => Err(format!(
+                "los eventos son de varios procesos ({}); indica cuál analizar en \"process\"",
+                join_pids(&pids)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// PIDs que aparecen en los eventos
+    fn pids(&self) -> BTreeSet<u32> {
+        self.file_events
+            .iter()
+            .map(|event| event.pid)
+            .chain(self.network_events.iter().map(|event| event.pid))
+            .collect()
+    }
+
+    /// PID del proceso analizado
+    pub fn pid(&self) -> u32 {
+        match &self.process {
+            Some(process) => process.pid,
+            None => self.pids().into_iter().next().unwrap_or_default(),
+        }
+    }
+
+    /// Momentos de todos los eventos
+    fn timestamps(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        self.file_events
+            .iter()
+            .map(|event| event.timestamp)
+            .chain(self.network_events.iter().map(|event| event.timestamp))
+    }
+
+    /// Tiempo entre el primer y el último evento, que hace de duración del monitoreo
+    pub fn span(&self) -> Duration {
+        match (self.timestamps().min(), self.timestamps().max()) {
+            (Some(first), Some(last)) => (last - first).to_std().unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Información del proceso para el reporte; el inicio es el primer evento y los
+    /// recursos quedan a cero porque la captura no los incluye
+    pub fn process_info(&self) -> ProcessInfo {
+        let first_event = self.timestamps().min().unwrap_or_else(Utc::now);
+        let process = self.process.clone();
+        ProcessInfo {
+            pid: self.pid(),
+            name: process.as_ref().map_or(UNNAMED_PROCESS.to_string(), |process| process.name.clone()),
+            path: process.as_ref().and_then(|process| process.path.clone()),
+            cmd_line: process.as_ref().and_then(|process| process.cmd_line.clone()),
+            cmd_truncated: false,
+            user: process.and_then(|process| process.user),
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            start_time: first_event,
+            children: Vec::new(),
+            thread_count: None,
+            status: ProcessStatus::default(),
+            deleted_exe: None,
+            environ: None,
+        }
+    }
+}
+
+/// PIDs separados por comas
+fn join_pids(pids: &BTreeSet<u32>) -> String {
+    pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_monitor::FileMonitor;
+    use crate::network::NetworkMonitor;
+
+    /// Escribir una captura en un archivo temporal y cargarla
+    fn load(name: &str, json: &str) -> Result<SampleCapture> {
+        let path = std::env::temp_dir().join(format!("shadowtrace-sample-{}-{}.json", name, std::process::id()));
+        fs::write(&path, json).unwrap();
+        let capture = SampleCapture::load(&path);
+        fs::remove_file(&path).unwrap();
+        capture
+    }
+
+    const CAPTURE: &str = r#"{
+        "file_events": [
+            { "pid": 4242, "path": "/home/ana/a.pdf", "operation": "Write", "timestamp": "2026-10-16T10:00:09Z", "size": 90000, "success": true, "entropy": 7.98 },
+            { "pid": 4242, "path": "/etc/shadow", "operation": "Read", "timestamp": "2026-10-16T10:00:01Z", "size": 1200, "success": true }
+        ],
+        "network_events": [
+            { "pid": 4242, "local_addr": "10.0.0.5:50123", "remote_addr": "203.0.113.7:4444", "protocol": "TCP", "direction": "Outbound",
+              "state": "Established", "timestamp": "2026-10-16T10:00:05Z", "bytes_sent": 5000, "bytes_received": 100 }
+        ]
+    }"#;
+
+    #[test]
+    fn valid_capture_feeds_the_monitors() {
+        let capture = load("valid", CAPTURE).unwrap();
+        assert_eq!(capture.pid(), 4242);
+        assert_eq!(capture.span(), Duration::from_secs(8));
+        assert_eq!(capture.file_events[0].path, "/etc/shadow");
+
+        let info = capture.process_info();
+        assert_eq!(info.name, UNNAMED_PROCESS);
+        assert_eq!(info.start_time, capture.file_events[0].timestamp);
+
+        let mut file_monitor = FileMonitor::new();
+        let mut network_monitor = NetworkMonitor::new();
+        assert_eq!(file_monitor.load_events(capture.file_events.clone()), 2);
+        assert_eq!(network_monitor.load_events(capture.network_events.clone()), 1);
+        assert_eq!(network_monitor.get_events().len(), 1);
+    }
+
+    #[test]
+    fn invalid_captures_are_rejected_with_the_reason() {
+        let error = |name: &str, json: &str| load(name, json).unwrap_err().to_string();
+
+        assert!(error("empty", r#"{ "file_events": [] }"#).contains("no contiene eventos"));
+        assert!(error("unknown", r#"{ "file_event": [] }"#).contains("unknown field `file_event`"));
+        assert!(error("type", &CAPTURE.replace("\"size\": 1200", "\"size\": \"mucho\"")).contains("line 4"));
+        assert!(error("mixed", &CAPTURE.replace("\"pid\": 4242, \"path\": \"/etc", "\"pid\": 7, \"path\": \"/etc"))
+            .contains("varios procesos (7, 4242)"));
+        let other_process = CAPTURE.replacen('{', r#"{ "process": { "pid": 1, "name": "otro" },"#, 1);
+        assert!(error("process", &other_process).contains("ningún evento pertenece al PID 1"));
+        assert!(error("path", &CAPTURE.replace("/etc/shadow", "")).contains("no tiene ruta"));
+    }
+}
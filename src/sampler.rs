@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Intervalo mínimo por defecto del muestreo adaptativo. sysinfo no recalcula el uso de
+/// CPU más a menudo que cada 200 ms, así que muestrear más rápido no aporta datos nuevos
+pub const DEFAULT_MIN_ADAPTIVE_INTERVAL_MS: u64 = 250;
+
+/// Intervalo máximo por defecto del muestreo adaptativo
+pub const DEFAULT_MAX_ADAPTIVE_INTERVAL_MS: u64 = 5000;
+
+/// Cambio de CPU (en puntos porcentuales) entre dos muestras que se considera actividad
+const CPU_CHANGE_POINTS: f32 = 10.0;
+
+/// Cambio relativo de la memoria residente entre dos muestras que se considera actividad
+const MEMORY_CHANGE_RATIO: f64 = 0.05;
+
+/// Bytes por segundo de E/S a partir de los que el proceso se considera activo
+const IO_ACTIVE_BYTES_PER_SEC: f64 = 256.0 * 1024.0;
+
+/// Factor con el que crece el intervalo en cada muestra sin actividad
+const BACKOFF_FACTOR: f64 = 1.5;
+
+/// Lectura de recursos de un proceso en una muestra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceReading {
+    /// Uso de CPU (%)
+    pub cpu_usage: f32,
+    /// Memoria residente en bytes
+    pub memory_usage: u64,
+    /// Bytes leídos y escritos desde que arrancó el proceso, si se pueden leer
+    pub io_bytes: Option<u64>,
+}
+
+/// Muestra de recursos registrada en el reporte con el intervalo que se aplicó después
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSample {
+    /// Momento de la muestra
+    #[serde(deserialize_with = "crate::timestamp::deserialize")]
+    pub timestamp: DateTime<Utc>,
+    /// Uso de CPU (%)
+    pub cpu_usage: f32,
+    /// Memoria residente en bytes
+    pub memory_usage: u64,
+    /// Bytes leídos y escritos desde que arrancó el proceso (solo disponible en Linux)
+    pub io_bytes: Option<u64>,
+    /// Milisegundos hasta la muestra siguiente
+    pub interval_ms: u64,
+}
+
+/// Muestreo adaptativo (`--interval-adaptive`): vuelve al intervalo mínimo en cuanto la
+/// CPU, la memoria o la E/S cambian deprisa y lo alarga poco a poco, hasta el máximo,
+/// mientras el proceso está en reposo. Así se capturan las ráfagas sin sondear siempre
+/// a alta frecuencia.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSampler {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    previous: Option<ResourceReading>,
+}
+
+impl AdaptiveSampler {
+    /// Muestreo entre `min` y `max`, empezando en `initial` (ajustado a esos límites)
+    pub fn new(min: Duration, max: Duration, initial: Duration) -> Self {
+        let max = max.max(min);
+        Self {
+            min,
+            max,
+            current: initial.clamp(min, max),
+            previous: None,
+        }
+    }
+
+    /// Intervalo actual
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Registrar una lectura y devolver el intervalo hasta la siguiente
+    pub fn observe(&mut self, reading: ResourceReading) -> Duration {
+        if let Some(previous) = self.previous {
+            self.current = if self.is_active(&previous, &reading) {
+                self.min
+            } else {
+                self.current.mul_f64(BACKOFF_FACTOR).min(self.max)
+            };
+        }
+        self.previous = Some(reading);
+        self.current
+    }
+
+    /// Indica si entre dos lecturas hubo un cambio rápido de CPU, memoria o E/S
+    fn is_active(&self, previous: &ResourceReading, reading: &ResourceReading) -> bool {
+        let cpu_change = (reading.cpu_usage - previous.cpu_usage).abs();
+        let memory_change = reading.memory_usage.abs_diff(previous.memory_usage) as f64
+            / previous.memory_usage.max(1) as f64;
+        // La E/S se compara por segundo: el intervalo entre las dos lecturas es el actual
+        let io_rate = match (previous.io_bytes, reading.io_bytes) {
+            (Some(before), Some(after)) => after.saturating_sub(before) as f64 / self.current.as_secs_f64(),
+            _ => 0.0,
+        };
+        cpu_change >= CPU_CHANGE_POINTS || memory_change >= MEMORY_CHANGE_RATIO || io_rate >= IO_ACTIVE_BYTES_PER_SEC
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn reading(cpu_usage: f32, memory_usage: u64, io_bytes: u64) -> ResourceReading {
+        ResourceReading { cpu_usage, memory_usage, io_bytes: Some(io_bytes) }
+    }
+
+    fn default_sampler() -> AdaptiveSampler {
+        AdaptiveSampler::new(Duration::from_millis(250), Duration::from_secs(5), Duration::from_secs(1))
+    }
+
+    #[test]
+    fn quiet_process_backs_off_to_the_maximum() {
+        let mut sampler = default_sampler();
+        let intervals: Vec<u128> = (0..10).map(|_| sampler.observe(reading(2.0, 100 * MIB, 0)).as_millis()).collect();
+        assert_eq!(intervals[..4], [1000, 1500, 2250, 3375]);
+        assert_eq!(intervals[9], 5000);
+    }
+
+    #[test]
+    fn interval_shrinks_under_a_spike() {
+        let mut sampler = default_sampler();
+        for _ in 0..10 {
+            sampler.observe(reading(2.0, 100 * MIB, 0));
+        }
+        assert_eq!(sampler.current(), Duration::from_secs(5));
+
+        // Pico de CPU: vuelve al mínimo y se queda ahí mientras dure la actividad
+        assert_eq!(sampler.observe(reading(95.0, 100 * MIB, 0)), Duration::from_millis(250));
+        assert_eq!(sampler.observe(reading(40.0, 100 * MIB, 0)), Duration::from_millis(250));
+        // Al calmarse, se alarga de nuevo
+        assert_eq!(sampler.observe(reading(41.0, 100 * MIB, 0)), Duration::from_millis(375));
+    }
+
+    #[test]
+    fn memory_growth_and_io_bursts_count_as_activity() {
+        let mut sampler = default_sampler();
+        sampler.observe(reading(1.0, 100 * MIB, 0));
+        sampler.observe(reading(1.0, 100 * MIB, 0));
+        assert_eq!(sampler.observe(reading(1.0, 120 * MIB, 0)), Duration::from_millis(250));
+
+        let mut sampler = default_sampler();
+        sampler.observe(reading(1.0, 100 * MIB, 0));
+        sampler.observe(reading(1.0, 100 * MIB, 0));
+        // 1 MiB en un intervalo de 1,5 s supera los 256 KiB/s
+        assert_eq!(sampler.observe(reading(1.0, 100 * MIB, MIB)), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn bounds_are_normalized() {
+        let sampler = AdaptiveSampler::new(Duration::from_secs(2), Duration::from_secs(1), Duration::from_millis(10));
+        assert_eq!(sampler.current(), Duration::from_secs(2));
+        let sampler = AdaptiveSampler::new(Duration::from_millis(250), Duration::from_secs(5), Duration::from_secs(60));
+        assert_eq!(sampler.current(), Duration::from_secs(5));
+    }
+}
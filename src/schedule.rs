@@ -0,0 +1,130 @@
+use chrono::{Local, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::reports::SeverityLevel;
+
+/// Segundos de un día
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Franja horaria diaria en hora local ("22:00-06:00"). Si termina antes de empezar
+/// cruza la medianoche; si empieza y termina a la misma hora cubre el día entero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeWindow {
+    /// Inicio (incluido)
+    pub start: NaiveTime,
+    /// Fin (excluido)
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    /// Indica si la hora cae dentro de la franja
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Franja horaria no válida '{}': se esperaba HH:MM-HH:MM (p. ej. 22:00-06:00)", s);
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+impl TryFrom<String> for TimeWindow {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TimeWindow> for String {
+    fn from(window: TimeWindow) -> Self {
+        window.to_string()
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Horas de silencio (`[[schedule.quiet_hours]]`): dentro de la franja solo se emiten
+/// los hallazgos de `min_severity` o más
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuietWindow {
+    /// Franja en la que se aplica
+    pub hours: TimeWindow,
+    /// Severidad mínima de los hallazgos que se siguen emitiendo (por defecto, solo críticos)
+    #[serde(default = "default_quiet_severity")]
+    pub min_severity: SeverityLevel,
+}
+
+fn default_quiet_severity() -> SeverityLevel {
+    SeverityLevel::Critical
+}
+
+/// Horario de la observación continua (`[schedule]`). Por defecto no tiene franjas:
+/// se observa siempre y no se silencia nada.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    /// Franjas en las que se observa el sistema (vacío = siempre)
+    pub active_hours: Vec<TimeWindow>,
+    /// Franjas en las que se silencian los hallazgos menos graves
+    pub quiet_hours: Vec<QuietWindow>,
+}
+
+impl Schedule {
+    /// Indica si la hora cae dentro de alguna franja de `active_hours`
+    pub fn is_active_at(&self, time: NaiveTime) -> bool {
+        self.active_hours.is_empty() || self.active_hours.iter().any(|window| window.contains(time))
+    }
+
+    /// Severidad mínima que se emite a esa hora; si coinciden varias horas de silencio,
+    /// la más alta (None = sin silencio)
+    pub fn severity_floor_at(&self, time: NaiveTime) -> Option<SeverityLevel> {
+        self.quiet_hours
+            .iter()
+            .filter(|quiet| quiet.hours.contains(time))
+            .map(|quiet| quiet.min_severity)
+            .max_by_key(|severity| severity.rank())
+    }
+
+    /// Indica si un hallazgo de esa severidad se emite a esa hora
+    pub fn allows_at(&self, severity: SeverityLevel, time: NaiveTime) -> bool {
+        self.severity_floor_at(time)
+            .filter(|floor| severity.rank() < floor.rank())
+            .is_none()
+    }
+
+    /// Inicio de la siguiente franja de `active_hours` a partir de esa hora
+    pub fn next_active_start(&self, time: NaiveTime) -> Option<NaiveTime> {
+        let now = time.num_seconds_from_midnight();
+        self.active_hours
+            .iter()
+            .map(|window| window.start)
+            .min_by_key(|start| (start.num_seconds_from_midnight() + SECONDS_PER_DAY - now) % SECONDS_PER_DAY)
+    }
+
+    /// `is_active_at` con la hora local actual
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Local::now().time())
+    }
+}
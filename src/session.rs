@@ -0,0 +1,57 @@
+use anyhow::Result;
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Estado ligero de la última sesión de la TUI.
+/// Se guarda aparte de la configuración y puede borrarse sin consecuencias.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// Última pantalla activa
+    #[serde(default)]
+    pub screen: Option<String>,
+    /// Nombre del último proceso seleccionado
+    #[serde(default)]
+    pub selected_process: Option<String>,
+    /// Pestaña activa del monitor de procesos
+    #[serde(default)]
+    pub process_monitor_tab: usize,
+    /// Refresco en vivo de la pantalla de red
+    #[serde(default)]
+    pub network_watch: Option<bool>,
+    /// Filtro de procesos sospechosos del monitor de procesos
+    #[serde(default)]
+    pub suspicious_only: Option<bool>,
+}
+
+impl Session {
+    /// Ruta del archivo de sesión (~/.shadowtrace/session.json)
+    pub fn path() -> Option<PathBuf> {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join(".shadowtrace").join("session.json"))
+    }
+
+    /// Cargar la última sesión; un archivo ausente o corrupto se ignora
+    pub fn load() -> Option<Self> {
+        let path = Self::path()?;
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                tracing::warn!("Ignorando sesión corrupta en {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Guardar la sesión actual
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| anyhow::anyhow!("No se pudo determinar el directorio home"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
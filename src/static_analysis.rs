@@ -0,0 +1,356 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use goblin::Object;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::reports::{Finding, SeverityLevel};
+
+/// Entropía a partir de la cual una sección se considera empaquetada o cifrada
+pub const HIGH_ENTROPY_THRESHOLD: f64 = 7.2;
+
+/// Longitud mínima de una cadena imprimible extraída del binario
+const MIN_STRING_LEN: usize = 6;
+
+/// Número máximo de cadenas de interés a conservar
+const MAX_STRINGS_OF_INTEREST: usize = 50;
+
+/// Nombres de API frecuentemente usados por malware (inyección, evasión, descarga).
+/// Se comparan con el símbolo completo: APIs habituales en cualquier binario
+/// (`execve`, `dlopen`, `mprotect`, `prctl`) no indican nada por sí solas.
+pub const SUSPICIOUS_APIS: &[&str] = &[
+    "VirtualAlloc", "VirtualAllocEx", "VirtualProtect", "VirtualProtectEx",
+    "WriteProcessMemory", "CreateRemoteThread", "LoadLibrary", "GetProcAddress",
+    "IsDebuggerPresent", "URLDownloadToFile", "WinExec", "ShellExecute",
+    "InternetOpen", "SetWindowsHookEx", "ptrace", "memfd_create",
+];
+
+/// Indica si un símbolo es una de las APIs sospechosas. Se ignoran las decoraciones
+/// habituales del enlazador (`_` inicial, `@12` de stdcall, `@GLIBC_2.2.5`) y el sufijo
+/// ANSI/Unicode de Windows (`LoadLibraryA`, `ShellExecuteW`).
+pub fn is_suspicious_api(symbol: &str) -> bool {
+    let name = symbol.trim_start_matches('_');
+    let name = name.split('@').next().unwrap_or(name);
+    let undecorated = name.strip_suffix(['A', 'W']);
+    SUSPICIOUS_APIS
+        .iter()
+        .any(|api| *api == name || Some(*api) == undecorated)
+}
+
+/// Información de una sección del binario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionInfo {
+    /// Nombre de la sección
+    pub name: String,
+    /// Tamaño en bytes
+    pub size: u64,
+    /// Entropía de Shannon (0.0 - 8.0)
+    pub entropy: f64,
+}
+
+/// Resultado del análisis estático de un binario (sin ejecutarlo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticAnalysis {
+    /// Ruta del binario
+    pub path: PathBuf,
+    /// Formato detectado (ELF, PE, Mach-O, ...)
+    pub format: String,
+    /// Tamaño del archivo en bytes
+    pub size: u64,
+    /// Entropía de todo el archivo
+    pub entropy: f64,
+    /// Bibliotecas importadas
+    pub libraries: Vec<String>,
+    /// Símbolos importados
+    pub imports: Vec<String>,
+    /// Secciones con su entropía
+    pub sections: Vec<SectionInfo>,
+    /// Cadenas de interés embebidas (URLs, IPs, APIs sospechosas)
+    pub strings_of_interest: Vec<String>,
+}
+
+/// Calcular la entropía de Shannon de un bloque de bytes (bits por byte)
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Analizar un binario leyendo sus cabeceras, sin ejecutarlo
+pub fn analyze_binary(path: &Path) -> Result<StaticAnalysis> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("No se pudo leer el binario: {}", path.display()))?;
+
+    let mut analysis = StaticAnalysis {
+        path: path.to_path_buf(),
+        format: "Desconocido".to_string(),
+        size: bytes.len() as u64,
+        entropy: shannon_entropy(&bytes),
+        libraries: Vec::new(),
+        imports: Vec::new(),
+        sections: Vec::new(),
+        strings_of_interest: extract_strings_of_interest(&bytes),
+    };
+
+    match Object::parse(&bytes) {
+        Ok(Object::Elf(elf)) => {
+            analysis.format = if elf.is_64 { "ELF64" } else { "ELF32" }.to_string();
+            analysis.libraries = elf.libraries.iter().map(|l| l.to_string()).collect();
+            analysis.imports = elf.dynsyms
+                .iter()
+                .filter(|sym| sym.is_import())
+                .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+                .collect();
+            for header in &elf.section_headers {
+                let name = elf.shdr_strtab.get_at(header.sh_name).unwrap_or("").to_string();
+                if name.is_empty() {
+                    continue;
+                }
+                // Las secciones NOBITS (.bss) no ocupan espacio en el archivo
+                let data = if header.sh_type == goblin::elf::section_header::SHT_NOBITS {
+                    &[][..]
+                } else {
+                    section_slice(&bytes, header.sh_offset, header.sh_size)
+                };
+                analysis.sections.push(SectionInfo {
+                    name,
+                    size: header.sh_size,
+                    entropy: shannon_entropy(data),
+                });
+            }
+        }
+        Ok(Object::PE(pe)) => {
+            analysis.format = if pe.is_64 { "PE32+" } else { "PE32" }.to_string();
+            analysis.libraries = pe.libraries.iter().map(|l| l.to_string()).collect();
+            analysis.imports = pe.imports
+                .iter()
+                .map(|import| format!("{}!{}", import.dll, import.name))
+                .collect();
+            for section in &pe.sections {
+                let data = section_slice(
+                    &bytes,
+                    section.pointer_to_raw_data as u64,
+                    section.size_of_raw_data as u64,
+                );
+                analysis.sections.push(SectionInfo {
+                    name: section.name().unwrap_or("?").to_string(),
+                    size: section.size_of_raw_data as u64,
+                    entropy: shannon_entropy(data),
+                });
+            }
+        }
+        Ok(Object::Mach(goblin::mach::Mach::Binary(macho))) => {
+            analysis.format = if macho.is_64 { "Mach-O 64" } else { "Mach-O 32" }.to_string();
+            analysis.libraries = macho.libs
+                .iter()
+                .filter(|l| **l != "self")
+                .map(|l| l.to_string())
+                .collect();
+            if let Ok(imports) = macho.imports() {
+                analysis.imports = imports.iter().map(|i| i.name.to_string()).collect();
+            }
+            for segment in macho.segments.iter() {
+                if let Ok(sections) = segment.sections() {
+                    for (section, data) in sections {
+                        analysis.sections.push(SectionInfo {
+                            name: section.name().unwrap_or("?").to_string(),
+                            size: section.size,
+                            entropy: shannon_entropy(data),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(Object::Mach(goblin::mach::Mach::Fat(_))) => {
+            analysis.format = "Mach-O universal".to_string();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::warn!("No se pudieron interpretar las cabeceras de {}: {}", path.display(), e);
+        }
+    }
+
+    analysis.imports.sort();
+    analysis.imports.dedup();
+
+    Ok(analysis)
+}
+
+impl StaticAnalysis {
+    /// Secciones con entropía alta (indicador de empaquetado o cifrado)
+    pub fn high_entropy_sections(&self) -> Vec<&SectionInfo> {
+        self.sections
+            .iter()
+            .filter(|s| s.size > 0 && s.entropy >= HIGH_ENTROPY_THRESHOLD)
+            .collect()
+    }
+
+    /// Importaciones que coinciden con APIs sospechosas
+    pub fn suspicious_imports(&self) -> Vec<&String> {
+        self.imports
+            .iter()
+            .filter(|import| is_suspicious_api(import))
+            .collect()
+    }
+
+    /// Convertir el análisis en hallazgos para el reporte
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let now = Utc::now();
+
+        findings.push(Finding {
+            title: "static_analysis".to_string(),
+            description: format!(
+                "Binario {} de {} bytes, entropía global {:.2}, {} bibliotecas y {} importaciones",
+                self.format, self.size, self.entropy, self.libraries.len(), self.imports.len()
+            ),
+            severity: SeverityLevel::Info,
+            recommendation: None,
+            affected_resources: self.sections
+                .iter()
+                .map(|s| format!("{} ({} bytes, entropía {:.2})", s.name, s.size, s.entropy))
+                .collect(),
+            timestamp: now,
+        });
+
+        let packed = self.high_entropy_sections();
+        if !packed.is_empty() {
+            findings.push(Finding {
+                title: "packing".to_string(),
+                description: format!(
+                    "{} secciones con entropía superior a {:.1}: posible binario empaquetado o cifrado",
+                    packed.len(), HIGH_ENTROPY_THRESHOLD
+                ),
+                severity: SeverityLevel::Warning,
+                recommendation: Some("Verificar si el binario usa un empaquetador (UPX, etc.) antes de ejecutarlo".to_string()),
+                affected_resources: packed.iter().map(|s| s.name.clone()).collect(),
+                timestamp: now,
+            });
+        }
+
+        let suspicious = self.suspicious_imports();
+        if !suspicious.is_empty() {
+            findings.push(Finding {
+                title: "suspicious_imports".to_string(),
+                description: format!("{} importaciones de APIs sospechosas", suspicious.len()),
+                severity: SeverityLevel::Warning,
+                recommendation: None,
+                affected_resources: suspicious.iter().map(|s| s.to_string()).collect(),
+                timestamp: now,
+            });
+        }
+
+        if !self.strings_of_interest.is_empty() {
+            findings.push(Finding {
+                title: "embedded_strings".to_string(),
+                description: format!("{} cadenas de interés embebidas (URLs, IPs, APIs)", self.strings_of_interest.len()),
+                severity: SeverityLevel::Info,
+                recommendation: None,
+                affected_resources: self.strings_of_interest.clone(),
+                timestamp: now,
+            });
+        }
+
+        findings
+    }
+}
+
+/// Obtener el contenido de una sección acotado al tamaño del archivo
+fn section_slice(bytes: &[u8], offset: u64, size: u64) -> &[u8] {
+    let start = (offset as usize).min(bytes.len());
+    let end = start.saturating_add(size as usize).min(bytes.len());
+    &bytes[start..end]
+}
+
+/// Extraer cadenas imprimibles que parezcan URLs, IPs o nombres de API sospechosos
+fn extract_strings_of_interest(bytes: &[u8]) -> Vec<String> {
+    let url_re = Regex::new(r"https?://[A-Za-z0-9._~:/?#@!$&'()*+,;=%-]+").unwrap();
+    let ip_re = Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap();
+
+    let mut result: Vec<String> = Vec::new();
+    let mut current = Vec::new();
+
+    for &byte in bytes.iter().chain(std::iter::once(&0u8)) {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            current.push(byte);
+            continue;
+        }
+
+        if current.len() >= MIN_STRING_LEN {
+            let s = String::from_utf8_lossy(&current);
+            let mut matches: Vec<String> = url_re.find_iter(&s).map(|m| m.as_str().to_string()).collect();
+            matches.extend(ip_re.find_iter(&s).map(|m| m.as_str().to_string()));
+            if s.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '@')).any(is_suspicious_api) {
+                matches.push(s.trim().to_string());
+            }
+            for m in matches {
+                if !result.contains(&m) {
+                    result.push(m);
+                }
+            }
+        }
+        current.clear();
+
+        if result.len() >= MAX_STRINGS_OF_INTEREST {
+            result.truncate(MAX_STRINGS_OF_INTEREST);
+            break;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspicious_api_matches_whole_symbols() {
+        for symbol in ["VirtualAlloc", "LoadLibraryA", "ShellExecuteW", "_WriteProcessMemory@20", "ptrace@GLIBC_2.2.5", "memfd_create"] {
+            assert!(is_suspicious_api(symbol), "{} debería ser sospechoso", symbol);
+        }
+        for symbol in ["execve", "dlopen", "mprotect", "prctl", "ptrace_scope", "MyLoadLibraryHelper", "GetProcAddressCache"] {
+            assert!(!is_suspicious_api(symbol), "{} no debería ser sospechoso", symbol);
+        }
+    }
+
+    #[test]
+    fn strings_of_interest_use_whole_api_names() {
+        let bytes = b"\0calls VirtualProtect here\0no_ptrace_scope_allowed\0see https://evil.example/x\0peer 10.1.2.3 up\0";
+        assert_eq!(
+            extract_strings_of_interest(bytes),
+            vec![
+                "calls VirtualProtect here".to_string(),
+                "https://evil.example/x".to_string(),
+                "10.1.2.3".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn entropy_bounds() {
+        assert_eq!(shannon_entropy(&[]), 0.0);
+        assert_eq!(shannon_entropy(&[7; 64]), 0.0);
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert!((shannon_entropy(&all_bytes) - 8.0).abs() < 1e-9);
+    }
+}
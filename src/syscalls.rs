@@ -0,0 +1,336 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::reports::{Finding, SeverityLevel};
+
+/// Tiempo que se espera a que strace escriba el resumen tras interrumpirlo
+const STRACE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Llamadas al sistema poco habituales en programas corrientes (inyección, evasión, rootkits)
+pub const SENSITIVE_SYSCALLS: &[&str] = &[
+    "ptrace", "process_vm_writev", "process_vm_readv", "memfd_create", "init_module",
+    "finit_module", "delete_module", "kexec_load", "kexec_file_load", "bpf", "perf_event_open",
+];
+
+/// Categoría de una llamada al sistema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyscallCategory {
+    /// Archivos y sistema de archivos
+    File,
+    /// Sockets y red
+    Network,
+    /// Creación, ejecución y control de procesos
+    Process,
+    /// Comunicación entre procesos (tuberías, memoria compartida, colas, semáforos)
+    Ipc,
+    /// Gestión de memoria
+    Memory,
+    /// Resto (tiempo, señales, información del sistema...)
+    Other,
+}
+
+impl SyscallCategory {
+    /// Nombre legible de la categoría
+    pub fn label(&self) -> &'static str {
+        match self {
+            SyscallCategory::File => "Archivos",
+            SyscallCategory::Network => "Red",
+            SyscallCategory::Process => "Procesos",
+            SyscallCategory::Ipc => "IPC",
+            SyscallCategory::Memory => "Memoria",
+            SyscallCategory::Other => "Otras",
+        }
+    }
+
+    /// Clasificar una llamada al sistema por su nombre
+    pub fn of(syscall: &str) -> Self {
+        const NETWORK: &[&str] = &[
+            "socket", "connect", "bind", "listen", "accept", "accept4", "sendto", "recvfrom",
+            "sendmsg", "recvmsg", "sendmmsg", "recvmmsg", "setsockopt", "getsockopt",
+            "getsockname", "getpeername", "shutdown",
+        ];
+        const PROCESS: &[&str] = &[
+            "clone", "clone3", "fork", "vfork", "execve", "execveat", "exit", "exit_group",
+            "wait4", "waitid", "kill", "tkill", "tgkill", "ptrace", "prctl", "arch_prctl",
+            "set_tid_address", "setsid", "setpgid", "getpid", "getppid", "gettid", "getpgrp",
+            "process_vm_readv", "process_vm_writev", "pidfd_open", "pidfd_send_signal",
+            "unshare", "setns", "seccomp", "capset", "capget", "prlimit64", "setrlimit",
+            "getrlimit", "sched_setaffinity", "sched_getaffinity", "sched_yield",
+        ];
+        const IPC: &[&str] = &[
+            "pipe", "pipe2", "socketpair", "eventfd", "eventfd2", "futex", "signalfd",
+            "signalfd4", "shmget", "shmat", "shmdt", "shmctl", "msgget", "msgsnd", "msgrcv",
+            "msgctl", "semget", "semop", "semtimedop", "semctl",
+        ];
+        const MEMORY: &[&str] = &[
+            "mmap", "munmap", "mprotect", "mremap", "brk", "madvise", "mlock", "munlock",
+            "mlockall", "munlockall", "msync", "mincore", "membarrier", "pkey_mprotect",
+        ];
+        const FILE: &[&str] = &[
+            "open", "openat", "openat2", "creat", "close", "close_range", "read", "write",
+            "pread64", "pwrite64", "readv", "writev", "lseek", "stat", "fstat", "lstat",
+            "newfstatat", "statx", "statfs", "fstatfs", "access", "faccessat", "faccessat2",
+            "readlink", "readlinkat", "getdents", "getdents64", "unlink", "unlinkat", "rename",
+            "renameat", "renameat2", "mkdir", "mkdirat", "rmdir", "chmod", "fchmod", "fchmodat",
+            "chown", "fchown", "lchown", "fchownat", "truncate", "ftruncate", "fcntl", "ioctl",
+            "dup", "dup2", "dup3", "fsync", "fdatasync", "chdir", "fchdir", "getcwd", "link",
+            "linkat", "symlink", "symlinkat", "utimensat", "sendfile", "copy_file_range",
+            "inotify_init1", "inotify_add_watch", "fanotify_init", "mount", "umount2",
+            "memfd_create", "flock", "fallocate", "getxattr", "lgetxattr", "fgetxattr",
+            "setxattr", "lsetxattr", "fsetxattr", "listxattr", "llistxattr",
+        ];
+
+        if NETWORK.contains(&syscall) {
+            SyscallCategory::Network
+        } else if PROCESS.contains(&syscall) || (syscall.starts_with("set") && syscall.ends_with("id")) {
+            SyscallCategory::Process
+        } else if IPC.contains(&syscall) || syscall.starts_with("mq_") {
+            SyscallCategory::Ipc
+        } else if MEMORY.contains(&syscall) {
+            SyscallCategory::Memory
+        } else if FILE.contains(&syscall) {
+            SyscallCategory::File
+        } else {
+            SyscallCategory::Other
+        }
+    }
+}
+
+/// Recuento de una llamada al sistema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyscallCount {
+    /// Nombre de la llamada
+    pub name: String,
+    /// Categoría a la que pertenece
+    pub category: SyscallCategory,
+    /// Número de llamadas
+    pub calls: u64,
+    /// Llamadas que devolvieron error
+    pub errors: u64,
+}
+
+/// Resumen de las llamadas al sistema del árbol de procesos auditado
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyscallSummary {
+    /// Llamadas ordenadas de más a menos frecuente
+    pub syscalls: Vec<SyscallCount>,
+    /// Se alcanzó el tiempo máximo y se detuvo el binario
+    pub timed_out: bool,
+    /// Código de salida del binario, si terminó por sí mismo
+    pub exit_code: Option<i32>,
+}
+
+impl SyscallSummary {
+    /// Interpretar la tabla que genera `strace -c`
+    pub fn parse_strace(output: &str) -> Self {
+        let mut syscalls: Vec<SyscallCount> = Vec::new();
+
+        for line in output.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // % time, seconds, usecs/call, calls, [errors], syscall
+            if fields.len() < 5 || fields.len() > 6 || fields[0].parse::<f64>().is_err() {
+                continue;
+            }
+            let name = fields[fields.len() - 1];
+            let Ok(calls) = fields[3].parse::<u64>() else {
+                continue;
+            };
+            if name == "total" {
+                continue;
+            }
+            let errors = if fields.len() == 6 { fields[4].parse().unwrap_or(0) } else { 0 };
+
+            // Con varias personalidades (32 y 64 bits) strace repite la tabla
+            match syscalls.iter_mut().find(|s| s.name == name) {
+                Some(existing) => {
+                    existing.calls += calls;
+                    existing.errors += errors;
+                }
+                None => syscalls.push(SyscallCount {
+                    name: name.to_string(),
+                    category: SyscallCategory::of(name),
+                    calls,
+                    errors,
+                }),
+            }
+        }
+
+        syscalls.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+        Self { syscalls, timed_out: false, exit_code: None }
+    }
+
+    /// Total de llamadas registradas
+    pub fn total_calls(&self) -> u64 {
+        self.syscalls.iter().map(|s| s.calls).sum()
+    }
+
+    /// Llamadas agrupadas por categoría
+    pub fn category_totals(&self) -> BTreeMap<SyscallCategory, u64> {
+        let mut totals = BTreeMap::new();
+        for syscall in &self.syscalls {
+            *totals.entry(syscall.category).or_insert(0) += syscall.calls;
+        }
+        totals
+    }
+
+    /// Llamadas sensibles que aparecieron en la traza
+    pub fn sensitive_syscalls(&self) -> Vec<&SyscallCount> {
+        self.syscalls
+            .iter()
+            .filter(|s| SENSITIVE_SYSCALLS.contains(&s.name.as_str()))
+            .collect()
+    }
+
+    /// Hallazgos derivados del resumen
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let now = Utc::now();
+
+        let totals = self.category_totals();
+        findings.push(Finding {
+            title: "syscall_summary".to_string(),
+            description: format!(
+                "{} llamadas al sistema: {}",
+                self.total_calls(),
+                totals
+                    .iter()
+                    .map(|(category, calls)| format!("{} {}", category.label(), calls))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            severity: SeverityLevel::Info,
+            recommendation: None,
+            affected_resources: Vec::new(),
+            timestamp: now,
+        });
+
+        let sensitive = self.sensitive_syscalls();
+        if !sensitive.is_empty() {
+            findings.push(Finding {
+                title: "sensitive_syscalls".to_string(),
+                description: format!(
+                    "El binario usó {} llamadas al sistema sensibles: {}",
+                    sensitive.len(),
+                    sensitive.iter().map(|s| s.name.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+                severity: SeverityLevel::Warning,
+                recommendation: Some("Revisar si el programa necesita depurar, inyectar código o cargar módulos del kernel".to_string()),
+                affected_resources: sensitive.iter().map(|s| format!("{} ({} llamadas)", s.name, s.calls)).collect(),
+                timestamp: now,
+            });
+        }
+
+        let spawned = self.syscalls
+            .iter()
+            .filter(|s| s.name == "execve" || s.name == "execveat")
+            .map(|s| s.calls)
+            .sum::<u64>();
+        if spawned > 1 {
+            findings.push(Finding {
+                title: "child_processes".to_string(),
+                description: format!("El binario ejecutó {} programas adicionales", spawned - 1),
+                severity: SeverityLevel::Warning,
+                recommendation: None,
+                affected_resources: Vec::new(),
+                timestamp: now,
+            });
+        }
+
+        findings
+    }
+}
+
+/// Ejecutar el binario bajo `strace -f -c` y resumir sus llamadas al sistema.
+/// Al agotarse el tiempo se interrumpe strace (que escribe el resumen) y se
+/// matan los procesos que queden en su grupo.
+#[cfg(target_os = "linux")]
+pub async fn trace_binary(binary: &Path, args: &[String], timeout: Duration) -> Result<SyscallSummary> {
+    use std::process::Stdio;
+    use tokio::io::AsyncReadExt;
+    use crate::audit_log::{audit_log, AuditEvent};
+
+    let output_path = std::env::temp_dir().join(format!("shadowtrace-strace-{}.txt", std::process::id()));
+    let mut child = match tokio::process::Command::new("strace")
+        .args(["-f", "-c", "-o"])
+        .arg(&output_path)
+        .arg("--")
+        .arg(binary)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(AppError::ConfigurationError(
+                "strace no está instalado; instálalo (p. ej. `apt install strace`) para usar --trace-syscalls".to_string()
+            ).into());
+        }
+        Err(e) => return Err(AppError::GenericError(format!("No se pudo ejecutar strace: {}", e)).into()),
+    };
+
+    // Leer stderr en paralelo para que el binario no se bloquee si escribe mucho
+    let mut stderr = child.stderr.take().expect("stderr capturado");
+    let stderr_reader = tokio::spawn(async move {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text).await;
+        text
+    });
+
+    let pgid = child.id().map(|id| id as i32);
+    let mut timed_out = false;
+    let status = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => Some(status?),
+        Err(_) => {
+            timed_out = true;
+            if let Some(pgid) = pgid {
+                unsafe { libc::kill(pgid, libc::SIGINT) };
+            }
+            let status = tokio::time::timeout(STRACE_GRACE_PERIOD, child.wait()).await.ok().and_then(|s| s.ok());
+            if let Some(pgid) = pgid {
+                unsafe { libc::kill(-pgid, libc::SIGKILL) };
+                audit_log(AuditEvent::new("kill", Some(format!("grupo {} ({})", pgid, binary.display())), "ok"));
+            }
+            if status.is_none() {
+                let _ = child.kill().await;
+            }
+            status
+        }
+    };
+    let stderr_text = stderr_reader.await.unwrap_or_default();
+
+    let strace_output = std::fs::read_to_string(&output_path).unwrap_or_default();
+    let _ = std::fs::remove_file(&output_path);
+
+    let mut summary = SyscallSummary::parse_strace(&strace_output);
+    if summary.syscalls.is_empty() {
+        let strace_errors: Vec<&str> = stderr_text.lines().filter(|l| l.starts_with("strace:")).collect();
+        if strace_errors.iter().any(|l| l.contains("Operation not permitted")) {
+            return Err(AppError::ProcessAccessError(
+                "ptrace no está permitido en este sistema. Comprueba /proc/sys/kernel/yama/ptrace_scope \
+                 (debe ser menor que 3) o, en un contenedor, ejecútalo con la capacidad CAP_SYS_PTRACE".to_string()
+            ).into());
+        }
+        return Err(AppError::GenericError(format!(
+            "strace no produjo ningún resumen{}",
+            if strace_errors.is_empty() { String::new() } else { format!(": {}", strace_errors.join("; ")) }
+        )).into());
+    }
+
+    summary.timed_out = timed_out;
+    summary.exit_code = if timed_out { None } else { status.and_then(|s| s.code()) };
+    Ok(summary)
+}
+
+/// El trazado de llamadas al sistema depende de ptrace y solo está disponible en Linux
+#[cfg(not(target_os = "linux"))]
+pub async fn trace_binary(_binary: &Path, _args: &[String], _timeout: Duration) -> Result<SyscallSummary> {
+    Err(AppError::ConfigurationError("--trace-syscalls solo está disponible en Linux".to_string()).into())
+}
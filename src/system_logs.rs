@@ -0,0 +1,218 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::process::Command;
+
+use crate::reports::{Finding, SeverityLevel};
+
+/// Líneas de registro por proceso que se incluyen con `--with-logs` si no se indica otro número
+pub const DEFAULT_LOG_LINES: usize = 200;
+
+/// Archivos de registro que se consultan, en orden: autenticación (Debian/Ubuntu y
+/// RHEL/Fedora) y registro general
+pub const LOG_FILES: &[&str] = &["/var/log/auth.log", "/var/log/secure", "/var/log/syslog", "/var/log/messages"];
+
+/// Bytes del final de cada archivo que se revisan; los registros rotan, y leerlos
+/// enteros en un servidor con mucha actividad sería demasiado lento
+const TAIL_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Longitud máxima de `_COMM` en journald (el nombre del proceso se trunca a 15 caracteres)
+const JOURNAL_COMM_LEN: usize = 15;
+
+/// Textos que indican un fallo de autenticación
+const AUTH_FAILURE_MARKERS: &[&str] = &[
+    "authentication failure", "failed password", "invalid user", "incorrect password",
+    "auth could not identify", "failed publickey", "maximum authentication attempts",
+];
+
+/// Línea de un registro del sistema relacionada con el proceso
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    /// Archivo o `journald`
+    pub source: String,
+    /// Texto de la línea tal cual aparece en el registro
+    pub text: String,
+}
+
+impl LogLine {
+    /// Es un fallo de autenticación
+    pub fn is_auth_failure(&self) -> bool {
+        let text = self.text.to_lowercase();
+        AUTH_FAILURE_MARKERS.iter().any(|marker| text.contains(marker))
+    }
+
+    /// Es un uso de sudo
+    pub fn is_sudo(&self) -> bool {
+        self.text.contains("sudo:") || self.text.contains("sudo[")
+    }
+}
+
+/// Contexto de los registros del sistema para un proceso (`--with-logs`): las últimas
+/// líneas que lo mencionan y un recuento de fallos de autenticación y usos de sudo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogContext {
+    /// Fuentes consultadas
+    pub sources: Vec<String>,
+    /// Líneas relacionadas con el proceso, de la más antigua a la más reciente
+    pub lines: Vec<LogLine>,
+    /// Fallos de autenticación entre esas líneas
+    pub auth_failures: usize,
+    /// Usos de sudo entre esas líneas
+    pub sudo_events: usize,
+    /// Fuentes que no se pudieron leer y el motivo
+    pub unavailable: Vec<String>,
+}
+
+impl LogContext {
+    /// Reunir las últimas `max_lines` líneas que mencionan el proceso (por nombre, PID o
+    /// ruta del ejecutable). Se leen los archivos de `LOG_FILES` y, si no hay ninguno
+    /// legible, journald. Las fuentes ausentes o sin permiso se anotan en `unavailable`.
+    pub fn collect(pid: u32, name: &str, path: Option<&str>, max_lines: usize) -> Self {
+        let mut context = Self::default();
+        let needles = process_needles(pid, name, path);
+        let mut lines: VecDeque<LogLine> = VecDeque::new();
+
+        for file in LOG_FILES {
+            match read_tail(file) {
+                Ok(content) => {
+                    context.sources.push(file.to_string());
+                    for text in content.lines().filter(|line| needles.iter().any(|n| line.contains(n.as_str()))) {
+                        push_bounded(&mut lines, LogLine { source: file.to_string(), text: text.to_string() }, max_lines);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) if e.kind() == ErrorKind::PermissionDenied => context.unavailable.push(format!(
+                    "{}: sin permiso de lectura (ejecuta como root o con un usuario del grupo adm)", file
+                )),
+                Err(e) => context.unavailable.push(format!("{}: {}", file, e)),
+            }
+        }
+
+        if context.sources.is_empty() {
+            match read_journal(pid, name, max_lines) {
+                Ok(journal) => {
+                    context.sources.push("journald".to_string());
+                    for text in journal.lines().filter(|line| !line.trim().is_empty()) {
+                        push_bounded(&mut lines, LogLine { source: "journald".to_string(), text: text.to_string() }, max_lines);
+                    }
+                }
+                Err(reason) => context.unavailable.push(format!("journald: {}", reason)),
+            }
+        }
+
+        context.lines = lines.into();
+        context.auth_failures = context.lines.iter().filter(|line| line.is_auth_failure()).count();
+        context.sudo_events = context.lines.iter().filter(|line| line.is_sudo()).count();
+        context
+    }
+
+    /// Resumen en una línea
+    pub fn summary(&self) -> String {
+        if self.sources.is_empty() {
+            return "No se pudo leer ningún registro del sistema".to_string();
+        }
+        format!(
+            "{} líneas relacionadas en {} ({} fallos de autenticación, {} usos de sudo)",
+            self.lines.len(),
+            self.sources.join(", "),
+            self.auth_failures,
+            self.sudo_events,
+        )
+    }
+
+    /// Hallazgos derivados del contexto: el resumen y, si los hay, los fallos de autenticación
+    pub fn findings(&self) -> Vec<Finding> {
+        let now = Utc::now();
+        let mut findings = vec![Finding {
+            title: "system_logs".to_string(),
+            description: self.summary(),
+            severity: SeverityLevel::Info,
+            recommendation: None,
+            affected_resources: self.unavailable.clone(),
+            timestamp: now,
+        }];
+        if self.auth_failures > 0 {
+            findings.push(Finding {
+                title: "auth_failures".to_string(),
+                description: format!(
+                    "{} fallos de autenticación recientes relacionados con el proceso en los registros del sistema",
+                    self.auth_failures
+                ),
+                severity: SeverityLevel::Warning,
+                recommendation: Some("Revisar el origen de los intentos en la sección de registros del sistema del reporte".to_string()),
+                affected_resources: self.lines
+                    .iter()
+                    .filter(|line| line.is_auth_failure())
+                    .map(|line| line.text.clone())
+                    .collect(),
+                timestamp: now,
+            });
+        }
+        findings
+    }
+}
+
+/// Textos que identifican al proceso en una línea de registro: `nombre[pid]`, `nombre:`,
+/// `[pid]` y la ruta del ejecutable (p. ej. en `COMMAND=` de sudo)
+fn process_needles(pid: u32, name: &str, path: Option<&str>) -> Vec<String> {
+    let mut needles = vec![format!("[{}]", pid)];
+    if !name.is_empty() {
+        needles.push(format!("{}[", name));
+        needles.push(format!("{}:", name));
+    }
+    if let Some(path) = path.filter(|path| path.starts_with('/')) {
+        needles.push(path.to_string());
+    }
+    needles
+}
+
+/// Añadir una línea conservando solo las `max` más recientes
+fn push_bounded(lines: &mut VecDeque<LogLine>, line: LogLine, max: usize) {
+    if max == 0 {
+        return;
+    }
+    if lines.len() == max {
+        lines.pop_front();
+    }
+    lines.push_back(line);
+}
+
+/// Leer los últimos `TAIL_BYTES` de un archivo, descartando la primera línea si quedó cortada
+fn read_tail(path: &str) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let content = String::from_utf8_lossy(&bytes).into_owned();
+    if start > 0 {
+        if let Some((_, rest)) = content.split_once('\n') {
+            return Ok(rest.to_string());
+        }
+    }
+    Ok(content)
+}
+
+/// Consultar journald con `journalctl` filtrando por PID o nombre del proceso
+fn read_journal(pid: u32, name: &str, max_lines: usize) -> Result<String, String> {
+    let comm: String = name.chars().take(JOURNAL_COMM_LEN).collect();
+    let output = Command::new("journalctl")
+        .args(["--no-pager", "--quiet", "--output", "short-iso", "--lines"])
+        .arg(max_lines.to_string())
+        .arg(format!("_PID={}", pid))
+        .arg("+")
+        .arg(format!("_COMM={}", comm))
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => "journalctl no está disponible en este sistema".to_string(),
+            _ => format!("no se pudo ejecutar journalctl ({})", e),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("journalctl falló: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,254 @@
+use chrono::{DateTime, Local, Utc};
+
+use crate::file_monitor::{FileActivity, FileEvent, FileOperation};
+use crate::network::{Direction, NetworkEvent};
+use crate::process::format_memory;
+
+/// Filas que incluye como máximo la línea de tiempo de un reporte Markdown
+pub const MAX_TIMELINE_MARKDOWN_ROWS: usize = 200;
+
+/// Origen de un evento de la línea de tiempo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineKind {
+    /// Operación de archivo
+    File,
+    /// Evento de red
+    Network,
+}
+
+/// Categoría de un evento, para distinguirlos de un vistazo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineCategory {
+    /// Lectura, apertura o cierre de un archivo
+    FileAccess,
+    /// Escritura, creación o renombrado
+    FileWrite,
+    /// Borrado o cambio de permisos
+    FileDestructive,
+    /// Conexión saliente
+    Outbound,
+    /// Conexión entrante
+    Inbound,
+}
+
+/// Eventos que muestra la línea de tiempo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimelineFilter {
+    /// Archivos y red
+    #[default]
+    All,
+    /// Solo operaciones de archivo
+    File,
+    /// Solo eventos de red
+    Network,
+}
+
+impl TimelineFilter {
+    /// Siguiente filtro (Todos → Archivos → Red → Todos)
+    pub fn next(self) -> Self {
+        match self {
+            TimelineFilter::All => TimelineFilter::File,
+            TimelineFilter::File => TimelineFilter::Network,
+            TimelineFilter::Network => TimelineFilter::All,
+        }
+    }
+
+    /// Nombre del filtro
+    pub fn label(self) -> &'static str {
+        match self {
+            TimelineFilter::All => "Todos",
+            TimelineFilter::File => "Archivos",
+            TimelineFilter::Network => "Red",
+        }
+    }
+
+    /// Indica si el filtro deja pasar un tipo de evento
+    pub fn includes(self, kind: TimelineKind) -> bool {
+        match self {
+            TimelineFilter::All => true,
+            TimelineFilter::File => kind == TimelineKind::File,
+            TimelineFilter::Network => kind == TimelineKind::Network,
+        }
+    }
+}
+
+/// Evento de archivo o de red en la línea de tiempo
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    /// Momento del evento
+    pub timestamp: DateTime<Utc>,
+    /// Proceso que lo generó, si se conoce
+    pub pid: Option<u32>,
+    /// Origen del evento
+    pub kind: TimelineKind,
+    /// Categoría del evento
+    pub category: TimelineCategory,
+    /// Qué ocurrió ("escritura", "conexión saliente TCP"...)
+    pub action: String,
+    /// Archivo o dirección afectada
+    pub target: String,
+    /// Tamaño, entropía, estado de la conexión... si los hay
+    pub detail: Option<String>,
+}
+
+impl TimelineEntry {
+    /// Entrada de una operación de archivo registrada por el monitor
+    pub fn from_file_event(event: &FileEvent) -> Self {
+        let mut entry = Self::file(event.timestamp, Some(event.pid), &event.operation, &event.path, event.size, event.entropy);
+        if !event.success {
+            entry.detail = Some(join_details(entry.detail.take(), "fallida".to_string()));
+        }
+        entry
+    }
+
+    /// Entrada de una actividad de archivo de un reporte
+    pub fn from_file_activity(activity: &FileActivity) -> Self {
+        Self::file(
+            activity.timestamp,
+            activity.process_id,
+            &activity.operation,
+            &activity.path.to_string_lossy(),
+            activity.size,
+            activity.entropy,
+        )
+    }
+
+    /// Entrada de un evento de red
+    pub fn from_network_event(event: &NetworkEvent) -> Self {
+        let (direction, category) = match event.direction {
+            Direction::Outbound => ("saliente", TimelineCategory::Outbound),
+            Direction::Inbound => ("entrante", TimelineCategory::Inbound),
+        };
+        let target = match event.remote_addr {
+            Some(remote) => remote.to_string(),
+            None => format!("{} (local)", event.local_addr),
+        };
+        let mut detail = event.state.label().to_string();
+        if let Some(sent) = event.bytes_sent.filter(|bytes| *bytes > 0) {
+            detail.push_str(&format!(", enviados {}", format_memory(sent)));
+        }
+        if let Some(received) = event.bytes_received.filter(|bytes| *bytes > 0) {
+            detail.push_str(&format!(", recibidos {}", format_memory(received)));
+        }
+        Self {
+            timestamp: event.timestamp,
+            pid: Some(event.pid),
+            kind: TimelineKind::Network,
+            category,
+            action: format!("conexión {} {:?}", direction, event.protocol),
+            target,
+            detail: Some(detail),
+        }
+    }
+
+    /// Entrada común de las operaciones de archivo
+    fn file(
+        timestamp: DateTime<Utc>,
+        pid: Option<u32>,
+        operation: &FileOperation,
+        path: &str,
+        size: Option<u64>,
+        entropy: Option<f64>,
+    ) -> Self {
+        let category = match operation {
+            FileOperation::Write | FileOperation::Create | FileOperation::Rename { .. } => TimelineCategory::FileWrite,
+            FileOperation::Delete | FileOperation::ChangePermissions => TimelineCategory::FileDestructive,
+            _ => TimelineCategory::FileAccess,
+        };
+        let target = match operation {
+            FileOperation::Rename { old_path } => format!("{} → {}", old_path.display(), path),
+            _ => path.to_string(),
+        };
+        let mut detail = size.filter(|size| *size > 0).map(format_memory);
+        if let Some(entropy) = entropy {
+            detail = Some(join_details(detail, format!("entropía {:.2}", entropy)));
+        }
+        Self {
+            timestamp,
+            pid,
+            kind: TimelineKind::File,
+            category,
+            action: operation.label().to_string(),
+            target,
+            detail,
+        }
+    }
+}
+
+/// Añadir un dato al detalle de una entrada
+fn join_details(detail: Option<String>, extra: String) -> String {
+    match detail {
+        Some(detail) => format!("{}, {}", detail, extra),
+        None => extra,
+    }
+}
+
+/// Línea de tiempo de los eventos de archivo y de red de un proceso, del más antiguo al
+/// más reciente
+pub fn from_events<'a>(
+    file_events: impl IntoIterator<Item = &'a FileEvent>,
+    network_events: impl IntoIterator<Item = &'a NetworkEvent>,
+) -> Vec<TimelineEntry> {
+    merge(
+        file_events.into_iter().map(TimelineEntry::from_file_event).collect(),
+        network_events.into_iter().map(TimelineEntry::from_network_event).collect(),
+    )
+}
+
+/// Mezclar las entradas de archivo y de red en orden cronológico. Cada lista se ordena
+/// primero (de forma estable) y después se intercalan; a igual momento va antes el archivo,
+/// que suele ser la causa (escribir un script y después conectarse)
+pub fn merge(mut files: Vec<TimelineEntry>, mut network: Vec<TimelineEntry>) -> Vec<TimelineEntry> {
+    files.sort_by_key(|entry| entry.timestamp);
+    network.sort_by_key(|entry| entry.timestamp);
+
+    let mut merged = Vec::with_capacity(files.len() + network.len());
+    let mut files = files.into_iter().peekable();
+    let mut network = network.into_iter().peekable();
+    loop {
+        let next = match (files.peek(), network.peek()) {
+            (Some(file), Some(event)) if event.timestamp < file.timestamp => network.next(),
+            (Some(_), _) => files.next(),
+            (None, Some(_)) => network.next(),
+            (None, None) => break,
+        };
+        merged.extend(next);
+    }
+    merged
+}
+
+/// Línea de tiempo en Markdown: una tabla con los eventos que deja pasar el filtro,
+/// limitada a `MAX_TIMELINE_MARKDOWN_ROWS` filas
+pub fn markdown(entries: &[TimelineEntry], filter: TimelineFilter) -> String {
+    let entries: Vec<&TimelineEntry> = entries.iter().filter(|entry| filter.includes(entry.kind)).collect();
+    let mut md = String::from("## Línea de Tiempo\n\n");
+    if filter != TimelineFilter::All {
+        md.push_str(&format!("Eventos: {}\n\n", filter.label()));
+    }
+    if entries.is_empty() {
+        md.push_str("No se registraron eventos.\n\n");
+        return md;
+    }
+
+    md.push_str("| Hora | PID | Evento | Objetivo | Detalle |\n");
+    md.push_str("|------|-----|--------|----------|---------|\n");
+    for entry in entries.iter().take(MAX_TIMELINE_MARKDOWN_ROWS) {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            DateTime::<Local>::from(entry.timestamp).format("%H:%M:%S%.3f"),
+            entry.pid.map_or("-".to_string(), |pid| pid.to_string()),
+            entry.action,
+            entry.target.replace('|', "\\|"),
+            entry.detail.as_deref().unwrap_or("").replace('|', "\\|"),
+        ));
+    }
+    if entries.len() > MAX_TIMELINE_MARKDOWN_ROWS {
+        md.push_str(&format!(
+            "\n*… y {} eventos más (se muestran los {} primeros)*\n",
+            entries.len() - MAX_TIMELINE_MARKDOWN_ROWS,
+            MAX_TIMELINE_MARKDOWN_ROWS
+        ));
+    }
+    md.push('\n');
+    md
+}
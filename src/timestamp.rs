@@ -0,0 +1,50 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer};
+
+/// Marca de tiempo en el formato de `std::time::SystemTime` con el que se
+/// guardaban los reportes de versiones anteriores
+#[derive(Deserialize)]
+struct LegacySystemTime {
+    secs_since_epoch: i64,
+    nanos_since_epoch: u32,
+}
+
+/// Marca de tiempo leída de un reporte guardado
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTimestamp {
+    /// RFC 3339, el formato actual
+    Rfc3339(DateTime<Utc>),
+    /// `{ "secs_since_epoch": ..., "nanos_since_epoch": ... }`
+    Legacy(LegacySystemTime),
+}
+
+impl StoredTimestamp {
+    fn into_utc<E: serde::de::Error>(self) -> Result<DateTime<Utc>, E> {
+        match self {
+            StoredTimestamp::Rfc3339(timestamp) => Ok(timestamp),
+            StoredTimestamp::Legacy(legacy) => Utc
+                .timestamp_opt(legacy.secs_since_epoch, legacy.nanos_since_epoch)
+                .single()
+                .ok_or_else(|| E::custom(format!(
+                    "marca de tiempo fuera de rango: {}.{:09}",
+                    legacy.secs_since_epoch, legacy.nanos_since_epoch
+                ))),
+        }
+    }
+}
+
+/// Deserializar una marca de tiempo en RFC 3339 o en el formato anterior de
+/// `SystemTime`, para que los reportes antiguos se sigan pudiendo cargar
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    StoredTimestamp::deserialize(deserializer)?.into_utc()
+}
+
+/// Como [`deserialize`], para campos opcionales
+pub fn deserialize_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    Option::<StoredTimestamp>::deserialize(deserializer)?
+        .map(StoredTimestamp::into_utc)
+        .transpose()
+}
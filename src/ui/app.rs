@@ -1,19 +1,71 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
+use chrono::{DateTime, Utc};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::widgets::ListState;
-use ratatui::text::{Span, Line};
-use ratatui::style::{Color, Style, Modifier};
-use ratatui::widgets::{Block, Borders, Paragraph};
 use serde_json;
 use std::thread;
 use std::sync::mpsc;
 
-use crate::process::ProcessMonitor;
-use crate::file_monitor::FileMonitor;
+use crate::process::{format_memory, memory_map_summary, own_pid, ProcessIdentity, ProcessInfo, ProcessMonitor};
+use crate::file_monitor::{FileActivity, FileMonitor};
 use crate::network::NetworkMonitor;
-use crate::reports::Report;
-use crate::llm::{LlmClient, LlmConfig, LlmProvider};
+use crate::capture::{CaptureSource, ProcfsCapture, SharedCapture};
+use crate::reports::{Finding, Report, ReportDiff, SavedReportFile, SeverityLevel};
+use crate::llm::{AnalysisDepth, LlmClient, LlmConfig};
+use crate::offline_analysis::{heuristic_analysis, AnalysisResult};
+use crate::timeline::{self, TimelineEntry, TimelineFilter};
+use crate::redaction::{IpPseudonymizer, Redactor};
+use crate::asn::AsnDatabase;
+use crate::session::Session;
+use crate::audit_log::{audit_log, AuditEvent};
+use crate::error::AppError;
+use crate::desktop::{copy_to_clipboard, reveal_in_file_manager};
+use crate::integrations::live::LiveServer;
+use super::widgets::{ChartStyle, ColorThresholds, ScrollableTextState};
+use super::workers::MonitoringWorkers;
+
+/// Muestras que se recogen antes de analizar un proceso sin historial (`--warmup-samples`)
+pub const DEFAULT_WARMUP_SAMPLES: usize = 4;
+
+/// Duración en segundos del monitoreo que inicia la tecla `m` (`--tui-monitor-duration`, 0 = indefinido)
+pub const DEFAULT_TUI_MONITOR_DURATION: u64 = 30;
+
+/// Segundos que suman o restan `+`/`-` a la duración del monitoreo
+const MONITOR_DURATION_STEP: u64 = 10;
+
+/// Duración máxima que se alcanza con `+` (una hora)
+const MAX_TUI_MONITOR_DURATION: u64 = 3600;
+
+/// Longitud máxima de salida de los análisis de la TUI si no se indica `--max-tokens`
+const TUI_DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Nota que se añade al análisis repetido solo con estadísticas por exceder el contexto
+const REDUCED_CONTEXT_NOTE: &str = "*El prompt no cabía en la ventana de contexto del modelo: análisis hecho solo con estadísticas*";
+
+/// Ticks entre dos muestras del calentamiento (aproximadamente medio segundo)
+const WARMUP_SAMPLE_TICKS: u64 = 5;
+
+/// Puntos de historial de CPU y memoria que se conservan por proceso
+const MAX_HISTORY: usize = 100;
+
+/// Eventos que avanzan PageUp/PageDown en la línea de tiempo
+const TIMELINE_PAGE: usize = 10;
+
+/// Procesos que se pueden fijar como pestañas (teclas 1-9)
+pub const MAX_PINNED: usize = 9;
+
+/// Uso de CPU (% de un núcleo) de ShadowTrace a partir del cual se avisa
+pub const SELF_CPU_THRESHOLD: f32 = 25.0;
+
+/// Memoria residente de ShadowTrace a partir de la cual se avisa
+pub const SELF_MEMORY_THRESHOLD: u64 = 256 * crate::process::BYTES_PER_MIB;
+
+/// Filas de la terminal por debajo de las cuales el monitor de procesos pasa solo al
+/// diseño compacto
+pub const COMPACT_HEIGHT_THRESHOLD: u16 = 30;
 
 /// Estados posibles de la aplicación
 pub enum AppState {
@@ -23,6 +75,204 @@ pub enum AppState {
     NetworkMonitor,
     Reports,
     Help,
+    /// Comparación lado a lado de dos procesos
+    ProcessCompare,
+    /// Eventos de archivo y red del proceso seleccionado en orden cronológico
+    Timeline,
+}
+
+impl AppState {
+    /// Nombre estable de la pantalla (usado al persistir la sesión)
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppState::Dashboard => "dashboard",
+            AppState::ProcessMonitor => "process_monitor",
+            AppState::FileMonitor => "file_monitor",
+            AppState::NetworkMonitor => "network_monitor",
+            AppState::Reports => "reports",
+            AppState::Help => "help",
+            AppState::ProcessCompare => "process_compare",
+            AppState::Timeline => "timeline",
+        }
+    }
+
+    /// Pantalla correspondiente a un nombre persistido
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dashboard" => Some(AppState::Dashboard),
+            "process_monitor" => Some(AppState::ProcessMonitor),
+            "file_monitor" => Some(AppState::FileMonitor),
+            "network_monitor" => Some(AppState::NetworkMonitor),
+            "reports" => Some(AppState::Reports),
+            "help" => Some(AppState::Help),
+            // La pareja comparada no se persiste: se vuelve al monitor de procesos
+            "process_compare" => Some(AppState::ProcessMonitor),
+            // La línea de tiempo depende del proceso seleccionado: se vuelve a su monitor
+            "timeline" => Some(AppState::ProcessMonitor),
+            _ => None,
+        }
+    }
+}
+
+/// Reporte guardado abierto en la pantalla de reportes para revisar sus hallazgos
+#[derive(Debug, Clone)]
+pub struct OpenedReport {
+    /// Archivo del que se cargó
+    pub path: PathBuf,
+    /// Contenido del reporte
+    pub report: Report,
+    /// Selección en la lista de hallazgos
+    pub findings_state: ListState,
+}
+
+impl OpenedReport {
+    /// Hallazgo seleccionado
+    pub fn selected_finding(&self) -> Option<&Finding> {
+        self.findings_state.selected().and_then(|i| self.report.findings.get(i))
+    }
+}
+
+/// Comparación de dos reportes guardados abierta en la pantalla de reportes
+#[derive(Debug, Clone)]
+pub struct OpenedDiff {
+    /// Diferencias entre el reporte anterior y el posterior
+    pub diff: ReportDiff,
+    /// Desplazamiento de la comparación
+    pub scroll: ScrollableTextState,
+}
+
+/// Identifica un hallazgo en la caché de explicaciones: título, descripción y momento
+pub type FindingKey = (String, String, DateTime<Utc>);
+
+/// Clave de un hallazgo en la caché de explicaciones
+pub fn finding_key(finding: &Finding) -> FindingKey {
+    (finding.title.clone(), finding.description.clone(), finding.timestamp)
+}
+
+/// Conversación con el LLM sobre el proceso analizado
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisConversation {
+    /// Datos enviados en el análisis inicial (ya redactados)
+    pub context: serde_json::Value,
+    /// Respuesta del análisis inicial
+    pub analysis: String,
+    /// Preguntas de seguimiento y sus respuestas
+    pub follow_ups: Vec<(String, String)>,
+}
+
+/// Duración de monitoreo legible ("30 s", "indefinido")
+pub fn format_monitor_duration(seconds: u64) -> String {
+    if seconds == 0 {
+        "indefinido".to_string()
+    } else {
+        format!("{} s", seconds)
+    }
+}
+
+/// Esperar una petición al LLM desde un hilo propio: en el runtime de la aplicación si
+/// lo hay, para que las conexiones abiertas sigan disponibles para el siguiente análisis
+fn block_on_llm<F: std::future::Future>(runtime: Option<tokio::runtime::Handle>, future: F) -> F::Output {
+    match runtime {
+        Some(handle) => handle.block_on(future),
+        None => tokio::runtime::Runtime::new().unwrap().block_on(future),
+    }
+}
+
+/// Texto del panel de análisis mientras se recogen las muestras previas
+fn warm_up_progress(collected: usize, target: usize) -> String {
+    format!(
+        "## Preparando el Análisis\n\n\
+        Recogiendo muestras de CPU y memoria para que el análisis parta de datos reales.\n\n\
+        **{}{}** {}/{} muestras\n\n\
+        *Ajusta el número de muestras con --warmup-samples (0 analiza de inmediato).*",
+        "▰".repeat(collected),
+        "▱".repeat(target.saturating_sub(collected)),
+        collected,
+        target
+    )
+}
+
+/// Estado de monitoreo y análisis de un proceso (el seleccionado o uno fijado)
+pub struct MonitoredProcess {
+    /// PID del proceso
+    pub pid: u32,
+    /// Nombre del proceso (para las pestañas)
+    pub name: String,
+    /// Identidad del proceso al seleccionarlo, para detectar que su PID lo reutiliza otro
+    pub identity: Option<ProcessIdentity>,
+    /// Historial de lecturas de CPU
+    pub cpu_history: Vec<f32>,
+    /// Historial de lecturas de memoria
+    pub memory_history: Vec<u64>,
+    /// Análisis LLM del proceso
+    pub llm_analysis: Option<String>,
+    /// Desplazamiento del texto del análisis LLM (sigue el final mientras crece)
+    pub llm_scroll: ScrollableTextState,
+    /// Reporte construido durante el último análisis (hallazgos y riesgo)
+    pub analysis_report: Option<Report>,
+    /// Resultado estructurado del análisis heurístico; si existe se dibuja en lugar de `llm_analysis`
+    pub structured_analysis: Option<AnalysisResult>,
+    /// Contexto del último análisis LLM para preguntas de seguimiento
+    pub conversation: Option<AnalysisConversation>,
+    /// Duración del monitoreo en segundos (0 = indefinido)
+    pub monitoring_duration: u64,
+    /// Tiempo de inicio del monitoreo actual
+    pub monitoring_start_time: Option<Instant>,
+    /// Tiempo desde el inicio del monitoreo
+    pub monitoring_time: Duration,
+    /// Indica si se está monitoreando activamente
+    pub is_monitoring_active: bool,
+    /// Muestras que faltan para terminar el calentamiento y lanzar el análisis
+    pub warm_up_remaining: Option<usize>,
+    /// Hilos que registran archivos y conexiones durante el monitoreo activo
+    workers: Option<MonitoringWorkers>,
+    /// Receptor para el resultado del análisis LLM (None si no hay análisis en curso)
+    llm_analysis_rx: Option<mpsc::Receiver<Result<String, anyhow::Error>>>,
+    /// Pregunta de seguimiento en curso
+    pending_follow_up: Option<String>,
+}
+
+impl MonitoredProcess {
+    /// Estado vacío para un proceso
+    pub fn new(pid: u32, name: String, identity: Option<ProcessIdentity>) -> Self {
+        Self {
+            pid,
+            name,
+            identity,
+            cpu_history: Vec::new(),
+            memory_history: Vec::new(),
+            llm_analysis: None,
+            llm_scroll: ScrollableTextState::following(),
+            analysis_report: None,
+            structured_analysis: None,
+            conversation: None,
+            monitoring_duration: 0,
+            monitoring_start_time: None,
+            monitoring_time: Duration::from_secs(0),
+            is_monitoring_active: false,
+            warm_up_remaining: None,
+            workers: None,
+            llm_analysis_rx: None,
+            pending_follow_up: None,
+        }
+    }
+
+    /// Indica si hay un análisis (o su calentamiento) o una pregunta de seguimiento en curso
+    pub fn is_analyzing(&self) -> bool {
+        self.llm_analysis_rx.is_some() || self.warm_up_remaining.is_some()
+    }
+
+    /// Añadir una lectura al historial, descartando las más antiguas
+    fn record_sample(&mut self, cpu_usage: f32, memory_usage: u64) {
+        self.cpu_history.push(cpu_usage);
+        self.memory_history.push(memory_usage);
+        if self.cpu_history.len() > MAX_HISTORY {
+            self.cpu_history.remove(0);
+        }
+        if self.memory_history.len() > MAX_HISTORY {
+            self.memory_history.remove(0);
+        }
+    }
 }
 
 /// Estructura principal de la aplicación para la UI
@@ -35,46 +285,119 @@ pub struct App {
     pub tick_count: u64,
     /// Última vez que se actualizó
     pub last_tick: Instant,
-    /// Monitor de procesos
-    pub process_monitor: ProcessMonitor,
-    /// Monitor de archivos
-    pub file_monitor: FileMonitor,
-    /// Monitor de red
-    pub network_monitor: NetworkMonitor,
+    /// Monitor de procesos (compartido con los hilos de muestreo)
+    process_monitor: Arc<Mutex<ProcessMonitor>>,
+    /// Monitor de archivos (compartido con los hilos de muestreo)
+    file_monitor: Arc<Mutex<FileMonitor>>,
+    /// Monitor de red (compartido con los hilos de muestreo)
+    network_monitor: Arc<Mutex<NetworkMonitor>>,
+    /// Fuente de captura de archivos y conexiones (`--capture-backend`)
+    capture: SharedCapture,
     /// Reportes generados
     pub reports: Vec<Report>,
+    /// Archivos del directorio de reportes (pantalla de reportes)
+    pub saved_reports: Vec<SavedReportFile>,
+    /// Selección en la lista de archivos de reportes
+    pub saved_reports_state: ListState,
+    /// Tipos de evento que muestra la línea de tiempo
+    pub timeline_filter: TimelineFilter,
+    /// Selección en la línea de tiempo
+    pub timeline_state: ListState,
+    /// Reporte cuyos hallazgos se están revisando (Enter sobre un reporte JSON)
+    pub opened_report: Option<OpenedReport>,
+    /// Reporte JSON marcado con `M` para compararlo con otro
+    pub diff_mark: Option<PathBuf>,
+    /// Comparación entre el reporte marcado y el seleccionado (tecla `D`)
+    pub opened_diff: Option<OpenedDiff>,
+    /// Explicaciones del LLM ya recibidas, por hallazgo (tecla `e` en los hallazgos)
+    pub finding_explanations: HashMap<FindingKey, String>,
+    /// Explicación en curso: hallazgo y receptor del resultado
+    pub finding_explanation_rx: Option<(FindingKey, mpsc::Receiver<Result<String, anyhow::Error>>)>,
     /// Estado de selección para listas
     pub list_state: ListState,
     /// PID del proceso actualmente seleccionado
     pub selected_pid: Option<u32>,
     /// Mensajes de estado
     pub status_message: Option<String>,
-    /// Tiempo desde el inicio del monitoreo
-    pub monitoring_time: Duration,
     /// Intervalo de actualización en milisegundos
     pub update_interval: u64,
     /// Lista de procesos actualmente en pantalla
     pub processes: Vec<crate::process::ProcessInfo>,
+    /// Mostrar solo los procesos con indicios de suplantación (tecla `!`)
+    pub suspicious_only: bool,
+    /// Resultado de las heurísticas por PID, con el inicio del proceso para
+    /// no heredarlo si el PID se reutiliza
+    suspicion_cache: HashMap<u32, (DateTime<Utc>, bool)>,
     /// Tab actual en el monitor de procesos (0: Detalles, 1: Análisis LLM)
     pub process_monitor_tab: usize,
-    /// Análisis LLM para el proceso seleccionado
-    pub process_llm_analysis: Option<String>,
-    /// Duración del monitoreo en segundos (0 = indefinido)
-    pub monitoring_duration: u64,
-    /// Tiempo de inicio del monitoreo actual
-    pub monitoring_start_time: Option<Instant>,
-    /// Indica si se está monitoreando activamente
-    pub is_monitoring_active: bool,
-    /// Historial de lecturas de CPU
-    pub cpu_history: Vec<f32>,
-    /// Historial de lecturas de memoria
-    pub memory_history: Vec<u64>,
-    /// Receptor para el resultado del análisis LLM (None si no hay análisis en curso)
-    llm_analysis_rx: Option<mpsc::Receiver<Result<String, anyhow::Error>>>,
-    /// Animación del indicador de carga
-    loading_tick: u64,
-    /// Índice de desplazamiento para el texto LLM
-    pub llm_text_scroll_index: Option<usize>,
+    /// Estado de monitoreo de cada proceso (el seleccionado y los fijados), por PID
+    pub monitored: HashMap<u32, MonitoredProcess>,
+    /// Procesos fijados como pestañas, en el orden de las teclas 1-9
+    pub pinned: Vec<u32>,
+    /// Desplazamiento de la pantalla de ayuda
+    pub help_scroll: ScrollableTextState,
+    /// Redactor de secretos aplicado a los datos enviados al LLM
+    pub redactor: Option<Redactor>,
+    /// Usar el análisis heurístico sin IA en lugar del LLM (`--offline`)
+    pub offline: bool,
+    /// Seudónimos de IP aplicados a los datos enviados al LLM (`--redact-network`)
+    pub ip_pseudonymizer: Option<IpPseudonymizer>,
+    /// Base de datos IP → ASN para agrupar las conexiones por organización (`--asn-db`)
+    pub asn_database: Option<AsnDatabase>,
+    /// Indica si la pantalla de red se refresca en vivo
+    pub network_watch: bool,
+    /// Texto de la pregunta de seguimiento en edición (None si la entrada está cerrada)
+    pub follow_up_input: Option<String>,
+    /// Notas del analista que acompañan a los análisis (`--note` y tecla `n`)
+    pub analyst_notes: Vec<String>,
+    /// Texto de la nota en edición (None si la entrada está cerrada)
+    pub note_input: Option<String>,
+    /// PID cuyo reinicio espera confirmación porque se está monitoreando
+    pub reset_confirmation: Option<u32>,
+    /// Configuración del LLM para los análisis (la de `AppConfig`)
+    pub llm_config: LlmConfig,
+    /// Cliente HTTP del LLM compartido con `AppConfig` (None = uno nuevo por análisis)
+    pub http_client: Option<reqwest::Client>,
+    /// Runtime en el que se ejecutan los análisis. Las conexiones del pool viven en el
+    /// runtime que las abrió, así que un runtime por análisis impediría reutilizarlas
+    pub llm_runtime: Option<tokio::runtime::Handle>,
+    /// Cuánto de los eventos en bruto se envía al LLM
+    pub analysis_depth: AnalysisDepth,
+    /// Dibujar gráficos y animaciones con braille o con ASCII
+    pub chart_style: ChartStyle,
+    /// Umbrales de color de CPU y memoria (`[colors]`)
+    pub color_thresholds: ColorThresholds,
+    /// Acompañar los colores de nivel con símbolos y texto (`--accessible`)
+    pub accessible: bool,
+    /// Diseño compacto del monitor de procesos (`--compact`, tecla `v`): sin gráficos,
+    /// con más espacio para la lista y el análisis
+    pub compact: bool,
+    /// Usar el diseño compacto en terminales bajas; la tecla `v` lo desactiva
+    pub auto_compact: bool,
+    /// Nivel de `--verbose`: con `-vv` los reportes guardados incluyen los eventos en bruto
+    pub verbose: u8,
+    /// Momento en que vence `--max-runtime`: la TUI se cierra y guarda la sesión
+    pub runtime_deadline: Option<Instant>,
+    /// Mostrar el consumo propio de ShadowTrace en una esquina (F2 lo alterna)
+    pub self_watchdog: bool,
+    /// Muestras de CPU y memoria que se recogen antes de analizar un proceso sin historial
+    pub warmup_samples: usize,
+    /// Duración en segundos del monitoreo que inicia la tecla `m` (0 = indefinido)
+    pub monitor_duration: u64,
+    /// Última lectura del consumo propio (CPU %, memoria en bytes)
+    pub self_usage: Option<(f32, u64)>,
+    /// Línea de comandos completa pedida con 'l' (PID, argumentos)
+    pub full_cmd_line: Option<(u32, Vec<String>)>,
+    /// Proceso marcado como primer lado de una comparación
+    pub compare_candidate: Option<u32>,
+    /// Procesos comparados lado a lado (izquierda, derecha)
+    pub compare_pids: Option<(u32, u32)>,
+    /// Historial de CPU de cada lado de la comparación
+    pub compare_cpu_history: [Vec<f32>; 2],
+    /// Historial de memoria de cada lado de la comparación
+    pub compare_memory_history: [Vec<u64>; 2],
+    /// Endpoint JSON en vivo (`--serve`); se actualiza con cada refresco de procesos
+    pub live_server: Option<LiveServer>,
 }
 
 impl Default for App {
@@ -84,26 +407,61 @@ impl Default for App {
             running: true,
             tick_count: 0,
             last_tick: Instant::now(),
-            process_monitor: ProcessMonitor::new(),
-            file_monitor: FileMonitor::new(),
-            network_monitor: NetworkMonitor::new(),
+            process_monitor: Arc::new(Mutex::new(ProcessMonitor::new())),
+            file_monitor: Arc::new(Mutex::new(FileMonitor::new())),
+            network_monitor: Arc::new(Mutex::new(NetworkMonitor::new())),
+            capture: Arc::new(Mutex::new(Box::new(ProcfsCapture))),
             reports: Vec::new(),
+            saved_reports: Vec::new(),
+            saved_reports_state: ListState::default(),
+            timeline_filter: TimelineFilter::default(),
+            timeline_state: ListState::default(),
+            opened_report: None,
+            diff_mark: None,
+            opened_diff: None,
+            finding_explanations: HashMap::new(),
+            finding_explanation_rx: None,
             list_state: ListState::default(),
             selected_pid: None,
             status_message: None,
-            monitoring_time: Duration::from_secs(0),
             update_interval: 250,
             processes: Vec::new(),
+            suspicious_only: false,
+            suspicion_cache: HashMap::new(),
             process_monitor_tab: 0,
-            process_llm_analysis: None,
-            monitoring_duration: 0,
-            monitoring_start_time: None,
-            is_monitoring_active: false,
-            cpu_history: Vec::new(),
-            memory_history: Vec::new(),
-            llm_analysis_rx: None,
-            loading_tick: 0,
-            llm_text_scroll_index: None,
+            monitored: HashMap::new(),
+            pinned: Vec::new(),
+            help_scroll: ScrollableTextState::new(),
+            redactor: Some(Redactor::default()),
+            offline: false,
+            ip_pseudonymizer: None,
+            asn_database: None,
+            network_watch: true,
+            follow_up_input: None,
+            analyst_notes: Vec::new(),
+            note_input: None,
+            reset_confirmation: None,
+            llm_config: LlmConfig::default(),
+            http_client: None,
+            llm_runtime: tokio::runtime::Handle::try_current().ok(),
+            analysis_depth: AnalysisDepth::default(),
+            chart_style: ChartStyle::detect(),
+            accessible: false,
+            compact: false,
+            auto_compact: true,
+            verbose: 0,
+            color_thresholds: ColorThresholds::default(),
+            runtime_deadline: None,
+            self_watchdog: true,
+            warmup_samples: DEFAULT_WARMUP_SAMPLES,
+            monitor_duration: DEFAULT_TUI_MONITOR_DURATION,
+            self_usage: None,
+            full_cmd_line: None,
+            compare_candidate: None,
+            compare_pids: None,
+            compare_cpu_history: [Vec::new(), Vec::new()],
+            compare_memory_history: [Vec::new(), Vec::new()],
+            live_server: None,
         };
         // Cargar procesos iniciales
         app.refresh_processes();
@@ -122,105 +480,294 @@ impl App {
         self.tick_count = self.tick_count.wrapping_add(1);
         self.last_tick = Instant::now();
         
+        if self.runtime_deadline.is_some_and(|deadline| self.last_tick >= deadline) {
+            self.running = false;
+            return;
+        }
+        
         // Actualizar el indicador de carga si está activo
         self.update_loading_indicator();
         
-        // Verificar si hay resultados del análisis LLM
-        if let Some(rx) = &self.llm_analysis_rx {
-            if let Ok(analysis_result) = rx.try_recv() {
-                // Recibimos un resultado, procesarlo
-                match analysis_result {
-                    Ok(analysis) => {
-                        // Actualizar el análisis y el estado
-                        self.process_llm_analysis = Some(analysis);
-                        self.status_message = Some("Análisis completado con éxito".to_string());
-                    },
-                    Err(e) => {
-                        // Mostrar un mensaje de error y un análisis alternativo
-                        let error_msg = format!("Error al realizar análisis: {}", e);
-                        self.status_message = Some(error_msg.clone());
-                        
-                        if let Some(pid) = self.selected_pid {
-                            if let Some(process) = self.process_monitor.get_process_by_pid(pid) {
-                                // Generar análisis alternativo
-                                let fallback_analysis = format!(
-                                    "## Análisis de Comportamiento del Proceso\n\n\
-                                    **Proceso:** {} (PID: {})\n\n\
-                                    **⚠️ Error al conectar con el servicio LLM**\n\n\
-                                    {}.\n\n\
-                                    **Datos recopilados:**\n\
-                                    - CPU media: {:.2}%\n\
-                                    - Memoria: {} KB\n\
-                                    - Tiempo de monitoreo: {} segundos\n\
-                                    - Muestras recopiladas: {}\n\n\
-                                    **Recomendación:** Verifica que el servicio LLM esté activo en http://10.0.0.171:8000\n\n\
-                                    *Este es un análisis básico generado sin IA debido al error de conexión.*\
-                                    ",
-                                    process.name, 
-                                    process.pid,
-                                    error_msg,
-                                    process.cpu_usage,
-                                    process.memory_usage,
-                                    self.monitoring_time.as_secs(),
-                                    self.cpu_history.len()
-                                );
-                                
-                                self.process_llm_analysis = Some(fallback_analysis);
-                            }
-                        }
-                    }
-                }
-                
+        // Verificar si hay resultados de análisis LLM de cualquier proceso monitoreado
+        let results: Vec<_> = self.monitored
+            .values_mut()
+            .filter_map(|monitored| {
+                let result = monitored.llm_analysis_rx.as_ref()?.try_recv().ok()?;
                 // Ya no necesitamos el receptor
-                self.llm_analysis_rx = None;
+                monitored.llm_analysis_rx = None;
+                Some((monitored.pid, monitored.pending_follow_up.take(), result))
+            })
+            .collect();
+        for (pid, question, result) in results {
+            match question {
+                Some(question) => self.receive_follow_up(pid, question, result),
+                None => self.receive_analysis(pid, result),
             }
         }
+        self.receive_finding_explanation();
+        
+        // Re-muestrear el throughput de red cada 10 ticks mientras se ve la pantalla de red
+        if matches!(self.state, AppState::NetworkMonitor) && self.network_watch && self.tick_count.is_multiple_of(10) {
+            self.network_monitor().sample_bandwidth();
+        }
+        
+        // Muestrear el consumo propio cada 10 ticks (aproximadamente cada segundo)
+        if self.self_watchdog && self.tick_count.is_multiple_of(10) {
+            self.sample_self_usage();
+        }
+        
+        // Muestrear los dos procesos comparados cada 10 ticks mientras se ve la comparación
+        if matches!(self.state, AppState::ProcessCompare) && self.tick_count.is_multiple_of(10) {
+            self.sample_compared_processes();
+        }
         
         // Actualizar la lista de procesos cada 50 ticks (aproximadamente cada 5 segundos)
-        if self.tick_count % 50 == 0 {
+        if self.tick_count.is_multiple_of(50) {
             self.refresh_processes();
         }
         
-        // Actualizar tiempo de monitoreo si está activo
-        if self.is_monitoring_active {
-            if let Some(start_time) = self.monitoring_start_time {
-                self.monitoring_time = self.last_tick.duration_since(start_time);
+        // Actualizar el tiempo de monitoreo de cada proceso monitoreado
+        let now = self.last_tick;
+        let mut finished = Vec::new();
+        for monitored in self.monitored.values_mut().filter(|monitored| monitored.is_monitoring_active) {
+            if let Some(start_time) = monitored.monitoring_start_time {
+                monitored.monitoring_time = now.duration_since(start_time);
                 
                 // Verificar si se ha alcanzado la duración máxima
-                if self.monitoring_duration > 0 && 
-                   self.monitoring_time.as_secs() >= self.monitoring_duration {
-                    // Detener el monitoreo si se alcanzó el límite
-                    self.stop_monitoring();
-                    self.status_message = Some(format!(
-                        "Monitoreo finalizado después de {} segundos", 
-                        self.monitoring_duration
-                    ));
-                    
-                    // Generar reporte si no hay uno
-                    if self.process_llm_analysis.is_none() {
-                        self.generate_demo_analysis();
-                    }
-                    return;
+                if monitored.monitoring_duration > 0 &&
+                   monitored.monitoring_time.as_secs() >= monitored.monitoring_duration {
+                    finished.push((monitored.pid, monitored.monitoring_duration));
                 }
             }
+        }
+        for (pid, duration) in finished {
+            // Detener el monitoreo si se alcanzó el límite
+            self.stop_monitoring(pid);
             
-            // Actualizar información de proceso y almacenar historial cada 10 ticks
-            if self.tick_count % 10 == 0 {
-                if let Some(pid) = self.selected_pid {
-                    if let Some(process) = self.process_monitor.get_process_by_pid(pid) {
-                        // Almacenar historial de CPU y memoria
-                        self.cpu_history.push(process.cpu_usage);
-                        self.memory_history.push(process.memory_usage);
-                        
-                        // Limitar el tamaño del historial a 100 puntos
-                        if self.cpu_history.len() > 100 {
-                            self.cpu_history.remove(0);
+            // Generar el análisis heurístico si no hay uno
+            if self.monitored.get(&pid).is_some_and(|monitored| monitored.llm_analysis.is_none()) {
+                self.generate_offline_analysis(pid, false);
+                self.status_message = Some(format!(
+                    "Monitoreo del PID {} finalizado después de {} segundos; análisis heurístico (sin IA) listo, 'a' para analizar con IA",
+                    pid, duration
+                ));
+            } else {
+                self.status_message = Some(format!(
+                    "Monitoreo del PID {} finalizado después de {} segundos", 
+                    pid, duration
+                ));
+            }
+        }
+        
+        // Muestras de calentamiento de los análisis pendientes
+        if self.tick_count.is_multiple_of(WARMUP_SAMPLE_TICKS) {
+            self.advance_warm_ups();
+        }
+        
+        // Almacenar el historial de CPU y memoria de los procesos monitoreados cada 10 ticks
+        if self.tick_count.is_multiple_of(10) {
+            let active: Vec<u32> = self.monitored
+                .values()
+                .filter(|monitored| monitored.is_monitoring_active)
+                .map(|monitored| monitored.pid)
+                .collect();
+            for pid in active {
+                // Un proceso terminado o que deja de poder leerse detiene su monitoreo
+                let reason = match self.lookup_process(pid) {
+                    Ok(Some(process)) => {
+                        if self.reset_if_pid_reused(&process) {
+                            continue;
                         }
-                        if self.memory_history.len() > 100 {
-                            self.memory_history.remove(0);
+                        if let Some(monitored) = self.monitored.get_mut(&pid) {
+                            monitored.record_sample(process.cpu_usage, process.memory_usage);
                         }
+                        continue;
                     }
-                }
+                    Ok(None) => format!("El proceso {} ha terminado.", pid),
+                    Err(e) => format!("{}.", e),
+                };
+                self.stop_monitoring(pid);
+                self.status_message = Some(match self.status_message.take() {
+                    Some(message) => format!("{} {}", reason, message),
+                    None => reason,
+                });
+            }
+        }
+    }
+
+    /// Acceso exclusivo al monitor de procesos.
+    /// El guard debe soltarse antes de dibujar para no bloquear a los hilos de muestreo.
+    pub fn process_monitor(&self) -> MutexGuard<'_, ProcessMonitor> {
+        self.process_monitor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Acceso exclusivo al monitor de archivos
+    pub fn file_monitor(&self) -> MutexGuard<'_, FileMonitor> {
+        self.file_monitor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Acceso exclusivo al monitor de red
+    pub fn network_monitor(&self) -> MutexGuard<'_, NetworkMonitor> {
+        self.network_monitor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Referencia compartida al monitor de archivos para hilos en segundo plano
+    pub fn shared_file_monitor(&self) -> Arc<Mutex<FileMonitor>> {
+        Arc::clone(&self.file_monitor)
+    }
+
+    /// Referencia compartida al monitor de red para hilos en segundo plano
+    pub fn shared_network_monitor(&self) -> Arc<Mutex<NetworkMonitor>> {
+        Arc::clone(&self.network_monitor)
+    }
+
+    /// Usar otra fuente de captura (la que resolvió `--capture-backend`)
+    pub fn set_capture(&mut self, capture: Box<dyn CaptureSource>) {
+        self.capture = Arc::new(Mutex::new(capture));
+    }
+
+    /// Registrar lo que el proceso tiene abierto en este momento con la fuente de captura
+    fn record_activity(&self, pid: u32) {
+        let mut capture = self.capture.lock().unwrap_or_else(|e| e.into_inner());
+        capture.record_files(&mut self.file_monitor(), pid);
+        capture.record_connections(&mut self.network_monitor(), pid);
+    }
+
+    /// Obtener la información de un proceso sin retener el bloqueo del monitor
+    /// (None tanto si terminó como si no se puede leer; ver `lookup_process`)
+    pub fn get_process_by_pid(&self, pid: u32) -> Option<crate::process::ProcessInfo> {
+        self.lookup_process(pid).ok().flatten()
+    }
+
+    /// Distinguir un proceso terminado (`Ok(None)`) de uno que existe pero no se puede leer (`Err`)
+    pub fn lookup_process(&self, pid: u32) -> Result<Option<crate::process::ProcessInfo>, AppError> {
+        self.process_monitor().get_process_by_pid(pid)
+    }
+
+    /// Estado de monitoreo del proceso seleccionado
+    pub fn current(&self) -> Option<&MonitoredProcess> {
+        self.selected_pid.and_then(|pid| self.monitored.get(&pid))
+    }
+
+    /// Estado de monitoreo del proceso seleccionado, para modificarlo
+    pub fn current_mut(&mut self) -> Option<&mut MonitoredProcess> {
+        self.selected_pid.and_then(|pid| self.monitored.get_mut(&pid))
+    }
+
+    /// Seleccionar un proceso para monitorearlo.
+    /// El estado del proceso anterior se descarta (y su monitoreo se detiene) salvo que esté fijado.
+    pub fn select_process(&mut self, pid: u32) {
+        if let Some(previous) = self.selected_pid.filter(|&previous| previous != pid) {
+            if !self.pinned.contains(&previous) {
+                // Soltar el estado detiene sus hilos de monitoreo
+                self.monitored.remove(&previous);
+            }
+            self.full_cmd_line = None;
+        }
+        
+        let process = self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .cloned()
+            .or_else(|| self.get_process_by_pid(pid));
+        if let Some(process) = &process {
+            self.reset_if_pid_reused(process);
+        }
+        self.monitored.entry(pid).or_insert_with(|| {
+            let name = process.as_ref().map(|p| p.name.clone()).unwrap_or_default();
+            let identity = process.as_ref().map(ProcessInfo::identity);
+            MonitoredProcess::new(pid, name, identity)
+        });
+        
+        self.selected_pid = Some(pid);
+        if let Some(i) = self.processes.iter().position(|p| p.pid == pid) {
+            self.list_state.select(Some(i));
+        }
+    }
+
+    /// Si otro proceso ha reutilizado el PID de uno monitoreado, descartar el estado
+    /// anterior (historial, análisis y monitoreo) y empezar de cero con el nuevo.
+    /// Devuelve true si se detectó la reutilización.
+    fn reset_if_pid_reused(&mut self, process: &ProcessInfo) -> bool {
+        let reused = self.monitored
+            .get(&process.pid)
+            .and_then(|monitored| monitored.identity.as_ref())
+            .is_some_and(|identity| identity.is_reused_by(process));
+        if reused {
+            // Soltar el estado anterior detiene sus hilos de monitoreo
+            self.monitored.insert(
+                process.pid,
+                MonitoredProcess::new(process.pid, process.name.clone(), Some(process.identity())),
+            );
+            self.status_message = Some(format!(
+                "El PID {} ahora corresponde a otro proceso ({}): se reinició su historial",
+                process.pid, process.name
+            ));
+        }
+        reused
+    }
+
+    /// Fijar el proceso seleccionado como pestaña, o soltarlo si ya lo estaba
+    pub fn toggle_pin(&mut self) {
+        let Some(pid) = self.selected_pid else {
+            self.status_message = Some("Selecciona un proceso primero".to_string());
+            return;
+        };
+        
+        if let Some(i) = self.pinned.iter().position(|&pinned| pinned == pid) {
+            self.pinned.remove(i);
+            self.status_message = Some(format!("PID {} ya no está fijado", pid));
+        } else if self.pinned.len() >= MAX_PINNED {
+            self.status_message = Some(format!(
+                "Ya hay {} procesos fijados. Suelta uno con 'P' antes de fijar otro", MAX_PINNED
+            ));
+        } else {
+            self.pinned.push(pid);
+            self.status_message = Some(format!(
+                "PID {} fijado en la pestaña {}. Seguirá monitoreándose al cambiar de proceso",
+                pid, self.pinned.len()
+            ));
+        }
+    }
+
+    /// Capturar el estado de la sesión actual para persistirlo
+    pub fn to_session(&self) -> Session {
+        let selected_process = self.selected_pid
+            .and_then(|pid| self.processes.iter().find(|p| p.pid == pid))
+            .map(|p| p.name.clone());
+        
+        Session {
+            screen: Some(self.state.name().to_string()),
+            selected_process,
+            process_monitor_tab: self.process_monitor_tab,
+            network_watch: Some(self.network_watch),
+            suspicious_only: Some(self.suspicious_only),
+        }
+    }
+
+    /// Restaurar una sesión previa (el proceso se busca por nombre porque el PID cambia)
+    pub fn restore_session(&mut self, session: &Session) {
+        if let Some(state) = session.screen.as_deref().and_then(AppState::from_name) {
+            if matches!(state, AppState::Reports) {
+                self.refresh_saved_reports();
+            }
+            self.state = state;
+        }
+        self.process_monitor_tab = session.process_monitor_tab.min(1);
+        if let Some(network_watch) = session.network_watch {
+            self.network_watch = network_watch;
+        }
+        if let Some(suspicious_only) = session.suspicious_only.filter(|&on| on != self.suspicious_only) {
+            self.suspicious_only = suspicious_only;
+            self.refresh_processes();
+        }
+        
+        if let Some(name) = &session.selected_process {
+            if let Some(i) = self.processes.iter().position(|p| &p.name == name) {
+                self.select_process(self.processes[i].pid);
+                self.status_message = Some(format!(
+                    "Sesión restaurada: {} (PID {})", name, self.processes[i].pid
+                ));
             }
         }
     }
@@ -228,15 +775,30 @@ impl App {
     /// Refresca la lista de procesos
     pub fn refresh_processes(&mut self) {
         // Usar un enfoque más eficiente limitando la cantidad de datos
-        let procs = self.process_monitor.get_all_processes();
+        let mut procs = self.process_monitor().get_all_processes();
+        self.publish_live_snapshot(&procs);
+        // También los procesos seleccionados o fijados que no se están monitoreando
+        let tracked: Vec<ProcessInfo> = procs.iter().filter(|p| self.monitored.contains_key(&p.pid)).cloned().collect();
+        for process in &tracked {
+            self.reset_if_pid_reused(process);
+        }
+        if self.suspicious_only {
+            self.retain_suspicious(&mut procs);
+        }
         
         // Reemplazar la lista existente sin realocar si es posible
+        let highlighted = self.list_state.selected().and_then(|i| self.processes.get(i)).map(ProcessInfo::identity);
         self.processes.clear();
         self.processes.extend(procs);
         
-        // Asegurarse de que la selección sigue siendo válida
-        if let Some(i) = self.list_state.selected() {
-            if i >= self.processes.len() && !self.processes.is_empty() {
+        // Mantener resaltado el mismo proceso aunque cambie de posición (no otro que
+        // reutilice su PID); si ya no está, asegurarse de que la selección sigue siendo válida
+        if let Some(i) = highlighted.and_then(|identity| self.processes.iter().position(|p| identity.matches(p))) {
+            self.list_state.select(Some(i));
+        } else if let Some(i) = self.list_state.selected() {
+            if self.processes.is_empty() {
+                self.list_state.select(None);
+            } else if i >= self.processes.len() {
                 self.list_state.select(Some(self.processes.len() - 1));
             }
         } else if !self.processes.is_empty() {
@@ -245,8 +807,108 @@ impl App {
         }
     }
 
+    /// Publicar en `--serve` todos los procesos (sin el filtro de sospechosos) y los
+    /// hallazgos de los análisis de los procesos monitoreados
+    fn publish_live_snapshot(&self, procs: &[crate::process::ProcessInfo]) {
+        let Some(live_server) = &self.live_server else {
+            return;
+        };
+        let findings = self.monitored
+            .values()
+            .filter_map(|monitored| monitored.analysis_report.as_ref())
+            .flat_map(|report| report.findings.iter().cloned())
+            .collect();
+        let file_events = self.file_monitor().get_events().len();
+        let network_events = self.network_monitor().get_events().len();
+        live_server.publish(procs.to_vec(), findings, file_events, network_events);
+    }
+
+    /// Conservar solo los procesos en los que las heurísticas ligeras encuentran indicios.
+    /// Cada proceso se evalúa una vez y el resultado se reutiliza en los refrescos siguientes.
+    fn retain_suspicious(&mut self, procs: &mut Vec<crate::process::ProcessInfo>) {
+        let alive: HashSet<u32> = procs.iter().map(|p| p.pid).collect();
+        self.suspicion_cache.retain(|pid, _| alive.contains(pid));
+        
+        let cache = &mut self.suspicion_cache;
+        procs.retain(|p| match cache.get(&p.pid) {
+            Some(&(start_time, suspicious)) if start_time == p.start_time => suspicious,
+            _ => {
+                let suspicious = p.masquerade().is_some() || p.has_deleted_exe();
+                cache.insert(p.pid, (p.start_time, suspicious));
+                suspicious
+            }
+        });
+    }
+
+    /// Indica si el monitor de procesos usa el diseño compacto en una terminal de `height` filas
+    pub fn is_compact(&self, height: u16) -> bool {
+        self.compact || (self.auto_compact && height < COMPACT_HEIGHT_THRESHOLD)
+    }
+
+    /// Alternar el diseño compacto. A partir de aquí manda la elección del usuario y no
+    /// la altura de la terminal
+    pub fn toggle_compact(&mut self) {
+        let height = crossterm::terminal::size().map_or(u16::MAX, |(_, rows)| rows);
+        self.compact = !self.is_compact(height);
+        self.auto_compact = false;
+        self.status_message = Some(if self.compact {
+            "Vista compacta: sin gráficos, más espacio para la lista y el análisis".to_string()
+        } else {
+            "Vista normal".to_string()
+        });
+    }
+
+    /// Alternar entre la lista completa y solo los procesos sospechosos,
+    /// manteniendo resaltado el mismo proceso si sigue en la lista
+    pub fn toggle_suspicious_only(&mut self) {
+        self.suspicious_only = !self.suspicious_only;
+        // La lista refrescada mantiene resaltado el mismo proceso si sigue en ella
+        self.refresh_processes();
+        
+        self.status_message = Some(if !self.suspicious_only {
+            "Mostrando todos los procesos".to_string()
+        } else if self.processes.is_empty() {
+            "Ningún proceso muestra indicios de suplantación. Pulsa '!' para ver todos".to_string()
+        } else {
+            format!("Mostrando solo los {} procesos sospechosos. Pulsa '!' para ver todos", self.processes.len())
+        });
+    }
+
     /// Maneja eventos de teclado
     pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        // La entrada de seguimiento captura todas las teclas mientras está abierta
+        if self.follow_up_input.is_some() {
+            self.handle_follow_up_input_keys(key_event);
+            return;
+        }
+        if self.note_input.is_some() {
+            self.handle_note_input_keys(key_event);
+            return;
+        }
+        
+        // La confirmación de reinicio espera una respuesta antes de cualquier otra tecla
+        if let Some(pid) = self.reset_confirmation.take() {
+            if matches!(key_event.code, KeyCode::Char('s' | 'S' | 'y' | 'Y') | KeyCode::Enter) {
+                self.stop_monitoring(pid);
+                self.reset_analysis(pid);
+            } else {
+                self.status_message = Some("Reinicio cancelado".to_string());
+            }
+            return;
+        }
+        
+        // F2 alterna el indicador de consumo propio en cualquier pantalla
+        if key_event.code == KeyCode::F(2) {
+            self.self_watchdog = !self.self_watchdog;
+            self.self_usage = None;
+            self.status_message = Some(if self.self_watchdog {
+                "Indicador de consumo de ShadowTrace activado".to_string()
+            } else {
+                "Indicador de consumo de ShadowTrace desactivado".to_string()
+            });
+            return;
+        }
+        
         match self.state {
             AppState::Dashboard => self.handle_dashboard_keys(key_event),
             AppState::ProcessMonitor => self.handle_process_monitor_keys(key_event),
@@ -254,6 +916,8 @@ impl App {
             AppState::NetworkMonitor => self.handle_network_monitor_keys(key_event),
             AppState::Reports => self.handle_reports_keys(key_event),
             AppState::Help => self.handle_help_keys(key_event),
+            AppState::ProcessCompare => self.handle_process_compare_keys(key_event),
+            AppState::Timeline => self.handle_timeline_keys(key_event),
         }
     }
 
@@ -263,7 +927,10 @@ impl App {
             KeyCode::Char('p') => self.state = AppState::ProcessMonitor,
             KeyCode::Char('f') => self.state = AppState::FileMonitor,
             KeyCode::Char('n') => self.state = AppState::NetworkMonitor,
-            KeyCode::Char('r') => self.state = AppState::Reports,
+            KeyCode::Char('r') => {
+                self.state = AppState::Reports;
+                self.refresh_saved_reports();
+            }
             KeyCode::Char('h') => self.state = AppState::Help,
             _ => {}
         }
@@ -271,18 +938,42 @@ impl App {
 
     fn handle_process_monitor_keys(&mut self, key_event: KeyEvent) {
         match key_event.code {
-            KeyCode::Esc => self.state = AppState::Dashboard,
+            KeyCode::Esc => {
+                // Los procesos fijados siguen monitoreándose en segundo plano
+                if let Some(pid) = self.selected_pid.filter(|pid| !self.pinned.contains(pid)) {
+                    if self.is_monitoring(pid) {
+                        self.stop_monitoring(pid);
+                    }
+                }
+                self.state = AppState::Dashboard;
+            }
             KeyCode::Char('r') => self.refresh_processes(),
             KeyCode::Char('a') | KeyCode::Char('A') => {
                 // Generar análisis real del proceso seleccionado
                 if let Some(pid) = self.selected_pid {
-                    // Verificar si hay datos de monitoreo suficientes
-                    if self.cpu_history.len() < 5 && !self.is_monitoring_active {
-                        self.status_message = Some("Se recomienda monitorear primero (tecla 'M') para mejores resultados".to_string());
+                    // Cambiar a la pestaña de análisis LLM automáticamente
+                    self.process_monitor_tab = 1;
+                    
+                    // Sin historial suficiente, recoger unas muestras antes de analizar
+                    let samples = self.current().map_or(0, |monitored| monitored.cpu_history.len());
+                    if self.current().is_some_and(MonitoredProcess::is_analyzing) {
+                        self.status_message = Some("Espera a que termine el análisis en curso".to_string());
+                    } else if samples < self.warmup_samples && !self.is_monitoring(pid) {
+                        self.start_warm_up(pid);
+                    } else {
+                        self.generate_real_analysis(pid);
+                    }
+                } else {
+                    self.status_message = Some("Selecciona un proceso primero".to_string());
+                }
+            },
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                // Análisis heurístico sin IA con los datos recopilados hasta ahora
+                if let Some(pid) = self.selected_pid {
+                    if self.current().is_some_and(MonitoredProcess::is_analyzing) {
+                        self.status_message = Some("Espera a que termine el análisis en curso".to_string());
                     } else {
-                        // Cambiar a la pestaña de análisis LLM automáticamente
-                        self.process_monitor_tab = 1;
-                        self.generate_real_analysis();
+                        self.generate_offline_analysis(pid, true);
                     }
                 } else {
                     self.status_message = Some("Selecciona un proceso primero".to_string());
@@ -290,10 +981,9 @@ impl App {
             },
             KeyCode::Char('m') | KeyCode::Char('M') => {
                 // Iniciar monitoreo si hay un proceso seleccionado
-                if let Some(_) = self.selected_pid {
-                    if !self.is_monitoring_active {
-                        // Monitoreo por 30 segundos por defecto 
-                        self.start_monitoring(30);
+                if let Some(pid) = self.selected_pid {
+                    if !self.is_monitoring(pid) {
+                        self.start_monitoring(self.monitor_duration);
                     } else {
                         self.status_message = Some("Ya hay un monitoreo activo. Presiona 's' para detenerlo.".to_string());
                     }
@@ -303,14 +993,97 @@ impl App {
             },
             KeyCode::Char('s') | KeyCode::Char('S') => {
                 // Detener monitoreo activo
-                if self.is_monitoring_active {
-                    self.stop_monitoring();
+                if let Some(pid) = self.selected_pid.filter(|&pid| self.is_monitoring(pid)) {
+                    self.stop_monitoring(pid);
                     // Sugerir análisis después de detener monitoreo
                     self.status_message = Some("Monitoreo detenido. Presiona 'a' para analizar los datos recopilados.".to_string());
                 } else {
                     self.status_message = Some("No hay un monitoreo activo".to_string());
                 }
             },
+            KeyCode::Char('f') | KeyCode::Char('F') => {
+                // Abrir la entrada para una pregunta de seguimiento
+                if self.current().is_some_and(MonitoredProcess::is_analyzing) {
+                    self.status_message = Some("Espera a que termine el análisis en curso".to_string());
+                } else if self.current().is_some_and(|monitored| monitored.conversation.is_some()) {
+                    self.process_monitor_tab = 1;
+                    self.follow_up_input = Some(String::new());
+                    self.status_message = Some("Escribe la pregunta de seguimiento y presiona ENTER".to_string());
+                } else {
+                    self.status_message = Some("Realiza primero un análisis (tecla 'a')".to_string());
+                }
+            },
+            KeyCode::Char('v') | KeyCode::Char('V') => self.toggle_compact(),
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                // Abrir la entrada de notas del analista
+                self.note_input = Some(String::new());
+                self.status_message = Some(format!(
+                    "Escribe una nota para el análisis y presiona ENTER ({} notas)",
+                    self.analyst_notes.len()
+                ));
+            },
+            KeyCode::Char('l') | KeyCode::Char('L') => {
+                // Mostrar u ocultar la línea de comandos sin recortar
+                if let Some(pid) = self.selected_pid {
+                    if self.full_cmd_line.as_ref().is_some_and(|(full_pid, _)| *full_pid == pid) {
+                        self.full_cmd_line = None;
+                        self.status_message = Some("Mostrando la línea de comandos recortada".to_string());
+                    } else {
+                        let full = self.process_monitor().full_cmd_line(pid);
+                        self.status_message = Some(match &full {
+                            Some(args) => format!("Línea de comandos completa: {} argumentos", args.len()),
+                            None => format!("No se pudo leer la línea de comandos del PID {}", pid),
+                        });
+                        self.full_cmd_line = full.map(|args| (pid, args));
+                    }
+                } else {
+                    self.status_message = Some("Selecciona un proceso primero".to_string());
+                }
+            },
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                // Marcar el proceso resaltado para compararlo con otro
+                if let Some(pid) = self.list_state.selected().and_then(|i| self.processes.get(i)).map(|p| p.pid) {
+                    self.mark_for_comparison(pid);
+                }
+            },
+            KeyCode::Char('+') => self.adjust_monitor_duration(true),
+            KeyCode::Char('-') => self.adjust_monitor_duration(false),
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                // Empezar de cero con el proceso seleccionado (pide confirmación si se está monitoreando)
+                if let Some(pid) = self.selected_pid {
+                    if self.current().is_some_and(MonitoredProcess::is_analyzing) {
+                        self.status_message = Some("Espera a que termine el análisis en curso".to_string());
+                    } else if self.is_monitoring(pid) {
+                        self.reset_confirmation = Some(pid);
+                    } else {
+                        self.reset_analysis(pid);
+                    }
+                } else {
+                    self.status_message = Some("Selecciona un proceso primero".to_string());
+                }
+            },
+            KeyCode::Char('p') | KeyCode::Char('P') => self.toggle_pin(),
+            KeyCode::Char('!') => self.toggle_suspicious_only(),
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                // Línea de tiempo de los eventos del proceso seleccionado
+                if self.selected_pid.is_some() {
+                    self.timeline_state.select(Some(0));
+                    self.state = AppState::Timeline;
+                } else {
+                    self.status_message = Some("Selecciona un proceso primero".to_string());
+                }
+            },
+            KeyCode::Char(digit @ '1'..='9') => {
+                // Cambiar a la pestaña de un proceso fijado
+                let index = digit as usize - '1' as usize;
+                if let Some(&pid) = self.pinned.get(index) {
+                    self.select_process(pid);
+                    let name = self.current().map(|monitored| monitored.name.clone()).unwrap_or_default();
+                    self.status_message = Some(format!("Pestaña {}: {} (PID {})", index + 1, name, pid));
+                } else {
+                    self.status_message = Some(format!("No hay ningún proceso fijado en la pestaña {}", index + 1));
+                }
+            },
             KeyCode::Char('t') | KeyCode::Tab => {
                 // Alternar entre tabs
                 self.process_monitor_tab = (self.process_monitor_tab + 1) % 2;
@@ -322,6 +1095,12 @@ impl App {
                     }
                 );
             },
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End
+                if self.process_monitor_tab == 1 && self.current().is_some_and(|monitored| monitored.llm_analysis.is_some()) =>
+            {
+                // En la pestaña de análisis las flechas desplazan el texto
+                self.handle_llm_text_scroll(key_event.code);
+            }
             KeyCode::Down => {
                 // Mover selección hacia abajo
                 let len = self.processes.len();
@@ -361,18 +1140,12 @@ impl App {
                 if let Some(i) = self.list_state.selected() {
                     if i < self.processes.len() {
                         let pid = self.processes[i].pid;
-                        self.selected_pid = Some(pid);
+                        // El estado del proceso anterior se descarta salvo que esté fijado
+                        self.select_process(pid);
                         self.status_message = Some(format!(
-                            "Proceso seleccionado: PID {}. Presiona 'm' para iniciar monitoreo o 'a' para análisis.", 
+                            "Proceso seleccionado: PID {}. Presiona 'm' para iniciar monitoreo, 'a' para análisis o 'P' para fijarlo.", 
                             pid
                         ));
-                        
-                        // Limpiar análisis anterior si se selecciona un nuevo proceso
-                        self.process_llm_analysis = None;
-                        
-                        // Limpiar historial si se selecciona un nuevo proceso
-                        self.cpu_history.clear();
-                        self.memory_history.clear();
                     }
                 }
             }
@@ -380,328 +1153,1155 @@ impl App {
         }
     }
 
-    fn handle_file_monitor_keys(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Esc => self.state = AppState::Dashboard,
+    fn handle_follow_up_input_keys(&mut self, key_event: KeyEvent) {
+        let Some(input) = &mut self.follow_up_input else {
+            return;
+        };
+        
+        match key_event.code {
+            KeyCode::Esc => {
+                self.follow_up_input = None;
+                self.status_message = Some("Pregunta de seguimiento cancelada".to_string());
+            },
+            KeyCode::Enter => {
+                let question = input.trim().to_string();
+                self.follow_up_input = None;
+                if question.is_empty() {
+                    self.status_message = Some("Pregunta de seguimiento vacía".to_string());
+                } else {
+                    self.send_follow_up(question);
+                }
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            },
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    fn handle_note_input_keys(&mut self, key_event: KeyEvent) {
+        let Some(input) = &mut self.note_input else {
+            return;
+        };
+        
+        match key_event.code {
+            KeyCode::Esc => {
+                self.note_input = None;
+                self.status_message = Some("Nota cancelada".to_string());
+            },
+            KeyCode::Enter => {
+                let note = input.trim().to_string();
+                self.note_input = None;
+                if note.is_empty() {
+                    self.status_message = Some("Nota vacía".to_string());
+                } else {
+                    self.analyst_notes.push(note);
+                    self.status_message = Some(format!(
+                        "Nota añadida ({} en total); se incluirá en el próximo análisis",
+                        self.analyst_notes.len()
+                    ));
+                }
+            },
+            KeyCode::Delete => {
+                // Borrar la última nota sin cerrar la entrada
+                self.status_message = Some(match self.analyst_notes.pop() {
+                    Some(note) => format!("Nota borrada: {}", note),
+                    None => "No hay notas que borrar".to_string(),
+                });
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            },
+            KeyCode::Char(c) => input.push(c),
             _ => {}
         }
     }
 
+    fn handle_file_monitor_keys(&mut self, key_event: KeyEvent) {
+        if key_event.code == KeyCode::Esc {
+            self.state = AppState::Dashboard;
+        }
+    }
+
     fn handle_network_monitor_keys(&mut self, key_event: KeyEvent) {
         match key_event.code {
             KeyCode::Esc => self.state = AppState::Dashboard,
+            KeyCode::Char('w') => {
+                self.network_watch = !self.network_watch;
+                self.status_message = Some(if self.network_watch {
+                    "Refresco de red en vivo activado".to_string()
+                } else {
+                    "Refresco de red en vivo pausado".to_string()
+                });
+            },
             _ => {}
         }
     }
 
     fn handle_reports_keys(&mut self, key_event: KeyEvent) {
+        if self.opened_report.is_some() {
+            self.handle_report_findings_keys(key_event);
+            return;
+        }
+        if let Some(opened) = self.opened_diff.as_mut() {
+            if key_event.code == KeyCode::Esc {
+                self.opened_diff = None;
+            } else {
+                opened.scroll.handle_key(key_event.code);
+            }
+            return;
+        }
         match key_event.code {
             KeyCode::Esc => self.state = AppState::Dashboard,
+            KeyCode::Enter => self.open_selected_report(),
+            KeyCode::Char('m') | KeyCode::Char('M') => self.toggle_diff_mark(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.open_selected_diff(),
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                self.refresh_saved_reports();
+                self.status_message = Some(format!("{} archivos en el directorio de reportes", self.saved_reports.len()));
+            }
+            KeyCode::Down | KeyCode::Up => {
+                let len = self.saved_reports.len();
+                if len > 0 {
+                    let i = match (self.saved_reports_state.selected(), key_event.code) {
+                        (Some(i), KeyCode::Down) => (i + 1) % len,
+                        (Some(i), _) => (i + len - 1) % len,
+                        (None, _) => 0,
+                    };
+                    self.saved_reports_state.select(Some(i));
+                }
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                // Copiar la ruta del archivo seleccionado al portapapeles
+                if let Some(path) = self.selected_saved_report().map(|file| file.path.display().to_string()) {
+                    self.status_message = Some(match copy_to_clipboard(&path) {
+                        Ok(method) => format!("Ruta copiada al portapapeles ({}): {}", method, path),
+                        Err(e) => format!("No se pudo copiar la ruta: {}", e),
+                    });
+                } else {
+                    self.status_message = Some("No hay ningún reporte seleccionado".to_string());
+                }
+            }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                // Mostrar el archivo seleccionado (o la carpeta) en el explorador de archivos
+                let path = match self.selected_saved_report() {
+                    Some(file) => Some(file.path.clone()),
+                    None => Report::default_dir_path(),
+                };
+                self.status_message = Some(match path.map(|path| reveal_in_file_manager(&path)) {
+                    Some(Ok(())) => "Abriendo la carpeta de reportes en el explorador de archivos".to_string(),
+                    Some(Err(e)) => format!("No se pudo abrir la carpeta de reportes: {}", e),
+                    None => "No se pudo determinar el directorio de reportes".to_string(),
+                });
+            }
             _ => {}
         }
     }
 
-    fn handle_help_keys(&mut self, key_event: KeyEvent) {
+    /// Teclas de la lista de hallazgos de un reporte abierto
+    fn handle_report_findings_keys(&mut self, key_event: KeyEvent) {
+        let Some(opened) = self.opened_report.as_mut() else {
+            return;
+        };
         match key_event.code {
-            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => self.state = AppState::Dashboard,
+            KeyCode::Esc => self.opened_report = None,
+            KeyCode::Down | KeyCode::Up => {
+                let len = opened.report.findings.len();
+                if len > 0 {
+                    let i = match (opened.findings_state.selected(), key_event.code) {
+                        (Some(i), KeyCode::Down) => (i + 1) % len,
+                        (Some(i), _) => (i + len - 1) % len,
+                        (None, _) => 0,
+                    };
+                    opened.findings_state.select(Some(i));
+                }
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => self.explain_selected_finding(),
             _ => {}
         }
     }
 
-    /// Genera un análisis de demostración para el proceso seleccionado
-    fn generate_demo_analysis(&mut self) {
-        if let Some(pid) = self.selected_pid {
-            if let Some(process) = self.process_monitor.get_process_by_pid(pid) {
-                // Generar un análisis de ejemplo basado en el proceso
-                let analysis = format!(
-                    "## Análisis de Comportamiento del Proceso\n\n\
-                    **Proceso:** {} (PID: {})\n\n\
-                    **Resumen:** El proceso {} es un proceso del sistema que muestra un comportamiento normal para su tipo. \
-                    Está utilizando aproximadamente {:.2}% de CPU y {} KB de memoria.\n\n\
-                    **Actividad de Archivos:**\n\
-                    - El proceso está accediendo a archivos de configuración en ubicaciones estándar\n\
-                    - No se observa acceso a archivos sensibles del sistema\n\
-                    - La actividad de lectura/escritura es consistente con operaciones normales\n\n\
-                    **Actividad de Red:**\n\
-                    - No se detectan conexiones sospechosas\n\
-                    - El tráfico de red está dentro de los parámetros normales\n\
-                    - No hay intentos de conexión a direcciones IP externas desconocidas\n\n\
-                    **Evaluación de Riesgo:**\n\
-                    - **Nivel de Riesgo:** Bajo\n\
-                    - **Confianza en el Análisis:** Alta\n\
-                    - **Indicadores de Comportamiento Malicioso:** Ninguno detectado\n\n\
-                    **Recomendaciones:**\n\
-                    - Continuar con el monitoreo normal del sistema\n\
-                    - No se requieren acciones inmediatas\n\
-                    - Considerar incluir este proceso en la lista blanca si se monitorea frecuentemente\n\n\
-                    *Este análisis fue generado automáticamente por el módulo de Inteligencia Artificial de ShadowTrace.*\
-                    ",
-                    process.name, 
-                    process.pid,
-                    process.name,
-                    process.cpu_usage,
-                    process.memory_usage
-                );
+    /// Abrir el reporte JSON seleccionado para revisar sus hallazgos
+    fn open_selected_report(&mut self) {
+        let Some(path) = self.selected_saved_report().map(|file| file.path.clone()) else {
+            self.status_message = Some("No hay ningún reporte seleccionado".to_string());
+            return;
+        };
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            self.status_message = Some("Solo se pueden abrir los reportes JSON para revisar sus hallazgos".to_string());
+            return;
+        }
+        match Report::load_json(&path) {
+            Ok(report) => {
+                let mut findings_state = ListState::default();
+                if !report.findings.is_empty() {
+                    findings_state.select(Some(0));
+                }
+                self.status_message = Some(format!("{} hallazgos en {}", report.findings.len(), report.title));
+                self.opened_report = Some(OpenedReport { path, report, findings_state });
+            }
+            Err(e) => self.status_message = Some(format!("No se pudo abrir el reporte {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Marcar (o desmarcar) el reporte JSON seleccionado como uno de los dos a comparar
+    fn toggle_diff_mark(&mut self) {
+        let Some(path) = self.selected_saved_report().map(|file| file.path.clone()) else {
+            self.status_message = Some("No hay ningún reporte seleccionado".to_string());
+            return;
+        };
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            self.status_message = Some("Solo se pueden comparar los reportes JSON".to_string());
+            return;
+        }
+        if self.diff_mark.as_ref() == Some(&path) {
+            self.diff_mark = None;
+            self.status_message = Some("Marca de comparación quitada".to_string());
+        } else {
+            self.diff_mark = Some(path);
+            self.status_message = Some("Reporte marcado: selecciona otro reporte JSON y pulsa D para compararlos".to_string());
+        }
+    }
+
+    /// Comparar el reporte marcado con el seleccionado; el modificado antes es el anterior
+    fn open_selected_diff(&mut self) {
+        let Some(marked) = self.diff_mark.clone() else {
+            self.status_message = Some("Marca primero un reporte JSON con M".to_string());
+            return;
+        };
+        let Some(selected) = self.selected_saved_report().cloned() else {
+            self.status_message = Some("No hay ningún reporte seleccionado".to_string());
+            return;
+        };
+        if selected.path == marked {
+            self.status_message = Some("Selecciona un reporte distinto del marcado".to_string());
+            return;
+        }
+        if selected.path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            self.status_message = Some("Solo se pueden comparar los reportes JSON".to_string());
+            return;
+        }
+        let marked_modified = self.saved_reports
+            .iter()
+            .find(|file| file.path == marked)
+            .and_then(|file| file.modified);
+        let (before, after) = match (marked_modified, selected.modified) {
+            (Some(marked_modified), Some(selected_modified)) if selected_modified < marked_modified => (selected.path, marked),
+            _ => (marked, selected.path),
+        };
+        
+        let load = |path: &PathBuf| Report::load_json(path)
+            .map_err(|e| format!("No se pudo abrir el reporte {}: {}", path.display(), e));
+        match load(&before).and_then(|before| Ok(before.diff(&load(&after)?))) {
+            Ok(diff) => {
+                self.status_message = Some(if diff.is_empty() {
+                    "Sin diferencias entre los reportes".to_string()
+                } else {
+                    format!("{} categorías con diferencias", diff.sections.len())
+                });
+                self.opened_diff = Some(OpenedDiff { diff, scroll: ScrollableTextState::new() });
+            }
+            Err(e) => self.status_message = Some(e),
+        }
+    }
+
+    /// Pedir al LLM que explique el hallazgo seleccionado y cómo remediarlo. La respuesta
+    /// se guarda por hallazgo, así que volver a pedirla no repite la consulta
+    fn explain_selected_finding(&mut self) {
+        let Some(opened) = &self.opened_report else {
+            return;
+        };
+        let Some(finding) = opened.selected_finding() else {
+            self.status_message = Some("El reporte no tiene hallazgos".to_string());
+            return;
+        };
+        let key = finding_key(finding);
+        if self.finding_explanations.contains_key(&key) {
+            self.status_message = Some("Explicación ya disponible".to_string());
+            return;
+        }
+        if self.offline {
+            self.status_message = Some("Las explicaciones necesitan el LLM (se ejecuta con --offline)".to_string());
+            return;
+        }
+        if self.finding_explanation_rx.is_some() {
+            self.status_message = Some("Ya hay una explicación en curso; espera a que termine".to_string());
+            return;
+        }
+        
+        // Solo el hallazgo y lo mínimo del reporte, con los secretos redactados
+        let mut finding = finding.clone();
+        let process = opened.report.processes.first();
+        let mut context = serde_json::json!({
+            "report": opened.report.title,
+            "risk_level": opened.report.risk_level().label(),
+            "process": process.map(|process| serde_json::json!({
+                "pid": process.pid,
+                "name": process.name,
+                "path": process.path,
+                "user": process.user,
+            })),
+        });
+        if !opened.report.analyst_notes.is_empty() {
+            context["analyst_notes"] = serde_json::json!(opened.report.analyst_notes);
+        }
+        if let Some(redactor) = &self.redactor {
+            finding.description = redactor.redact(&finding.description);
+            finding.recommendation = finding.recommendation.map(|recommendation| redactor.redact(&recommendation));
+            finding.affected_resources = redactor.redact_args(&finding.affected_resources);
+            redactor.redact_value(&mut context);
+        }
+        
+        let client = self.analysis_client(self.analysis_llm_config());
+        let runtime = self.llm_runtime.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = block_on_llm(runtime, async {
+                let client = client.map_err(|e| anyhow::anyhow!("Error al crear cliente LLM: {}", e))?;
+                client.explain_finding(&finding, context).await
+            });
+            let _ = tx.send(result);
+        });
+        self.status_message = Some(format!("Consultando al LLM sobre el hallazgo {}...", key.0));
+        self.finding_explanation_rx = Some((key, rx));
+    }
+
+    /// Guardar la explicación de un hallazgo si ya llegó
+    fn receive_finding_explanation(&mut self) {
+        let Some((_, rx)) = &self.finding_explanation_rx else {
+            return;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return;
+        };
+        let Some((key, _)) = self.finding_explanation_rx.take() else {
+            return;
+        };
+        match result {
+            Ok(explanation) => {
+                self.status_message = Some(format!("Explicación del hallazgo {} lista", key.0));
+                self.finding_explanations.insert(key, explanation);
+            }
+            Err(e) => self.status_message = Some(format!("No se pudo explicar el hallazgo {}: {}", key.0, e)),
+        }
+    }
+
+    /// Teclas de la línea de tiempo
+    fn handle_timeline_keys(&mut self, key_event: KeyEvent) {
+        let len = self.selected_pid.map_or(0, |pid| self.timeline_entries(pid).len());
+        let selected = self.timeline_state.selected().unwrap_or(0);
+        let last = len.saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc => self.state = AppState::ProcessMonitor,
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.timeline_filter = self.timeline_filter.next();
+                self.timeline_state.select(Some(0));
+                self.status_message = Some(format!("Línea de tiempo: {}", self.timeline_filter.label()));
+            }
+            KeyCode::Down => self.timeline_state.select(Some((selected + 1).min(last))),
+            KeyCode::Up => self.timeline_state.select(Some(selected.saturating_sub(1))),
+            KeyCode::PageDown => self.timeline_state.select(Some((selected + TIMELINE_PAGE).min(last))),
+            KeyCode::PageUp => self.timeline_state.select(Some(selected.saturating_sub(TIMELINE_PAGE))),
+            KeyCode::Home => self.timeline_state.select(Some(0)),
+            KeyCode::End => self.timeline_state.select(Some(last)),
+            _ => {}
+        }
+    }
+
+    /// Eventos de archivo y red de un proceso en orden cronológico, con el filtro actual
+    pub fn timeline_entries(&self, pid: u32) -> Vec<TimelineEntry> {
+        let file_monitor = self.file_monitor();
+        let network_monitor = self.network_monitor();
+        let mut entries = timeline::from_events(
+            file_monitor.get_events_for_pid(pid),
+            network_monitor.get_events_for_pid(pid),
+        );
+        entries.retain(|entry| self.timeline_filter.includes(entry.kind));
+        entries
+    }
+
+    /// Volver a leer el directorio de reportes, conservando el archivo seleccionado
+    pub fn refresh_saved_reports(&mut self) {
+        let selected = self.selected_saved_report().map(|file| file.path.clone());
+        self.saved_reports = match Report::list_saved() {
+            Ok(files) => files,
+            Err(e) => {
+                self.status_message = Some(format!("No se pudo leer el directorio de reportes: {}", e));
+                Vec::new()
+            }
+        };
+        let index = selected
+            .and_then(|path| self.saved_reports.iter().position(|file| file.path == path))
+            .or(if self.saved_reports.is_empty() { None } else { Some(0) });
+        self.saved_reports_state.select(index);
+    }
+
+    /// Archivo de reporte seleccionado
+    pub fn selected_saved_report(&self) -> Option<&SavedReportFile> {
+        self.saved_reports_state.selected().and_then(|i| self.saved_reports.get(i))
+    }
+
+    fn handle_process_compare_keys(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.compare_pids = None;
+                self.state = AppState::ProcessMonitor;
+            }
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                // Intercambiar los lados conservando el historial de cada proceso
+                if let Some((left, right)) = self.compare_pids {
+                    self.compare_pids = Some((right, left));
+                    self.compare_cpu_history.swap(0, 1);
+                    self.compare_memory_history.swap(0, 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Leer el consumo de CPU y memoria del propio ShadowTrace
+    fn sample_self_usage(&mut self) {
+        let was_exceeded = self.self_usage_exceeded();
+        self.self_usage = self
+            .get_process_by_pid(own_pid())
+            .map(|process| (process.cpu_usage, process.memory_usage));
+        
+        // Registrar solo el momento en que se supera el umbral, no cada muestra
+        if let (false, true, Some((cpu, memory))) = (was_exceeded, self.self_usage_exceeded(), self.self_usage) {
+            tracing::warn!(
+                "ShadowTrace está consumiendo muchos recursos: {:.1}% de CPU, {}",
+                cpu, format_memory(memory)
+            );
+        }
+    }
+
+    /// Indica si el consumo propio supera los umbrales de aviso
+    pub fn self_usage_exceeded(&self) -> bool {
+        self.self_usage
+            .is_some_and(|(cpu, memory)| cpu > SELF_CPU_THRESHOLD || memory > SELF_MEMORY_THRESHOLD)
+    }
+
+    /// Marcar un proceso para compararlo; al marcar el segundo se abre la comparación
+    pub fn mark_for_comparison(&mut self, pid: u32) {
+        match self.compare_candidate {
+            None => {
+                self.compare_candidate = Some(pid);
+                self.status_message = Some(format!(
+                    "PID {} marcado para comparar. Selecciona otro proceso y presiona 'c'", pid
+                ));
+            }
+            Some(candidate) if candidate == pid => {
+                self.compare_candidate = None;
+                self.status_message = Some("Comparación cancelada".to_string());
+            }
+            Some(candidate) => {
+                self.compare_candidate = None;
+                self.compare_pids = Some((candidate, pid));
+                self.compare_cpu_history = [Vec::new(), Vec::new()];
+                self.compare_memory_history = [Vec::new(), Vec::new()];
+                self.sample_compared_processes();
+                self.state = AppState::ProcessCompare;
+                self.status_message = Some(format!("Comparando PID {} con PID {}", candidate, pid));
+            }
+        }
+    }
+
+    /// Registrar recursos, archivos abiertos y conexiones de los dos procesos comparados
+    fn sample_compared_processes(&mut self) {
+        let Some((left, right)) = self.compare_pids else {
+            return;
+        };
+        
+        for (side, pid) in [left, right].into_iter().enumerate() {
+            if let Some(process) = self.get_process_by_pid(pid) {
+                self.compare_cpu_history[side].push(process.cpu_usage);
+                self.compare_memory_history[side].push(process.memory_usage);
                 
-                self.process_llm_analysis = Some(analysis);
-                self.status_message = Some("Análisis LLM generado".to_string());
+                // Limitar el tamaño del historial a 100 puntos
+                if self.compare_cpu_history[side].len() > 100 {
+                    self.compare_cpu_history[side].remove(0);
+                    self.compare_memory_history[side].remove(0);
+                }
+            }
+            self.record_activity(pid);
+        }
+    }
+
+    fn handle_help_keys(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
+                self.help_scroll.reset();
+                self.state = AppState::Dashboard;
+            }
+            code => {
+                self.help_scroll.handle_key(code);
             }
         }
     }
 
-    /// Iniciar monitoreo de proceso
+    /// Subir o bajar la duración de los próximos monitoreos; por debajo del paso se pasa a indefinido
+    pub fn adjust_monitor_duration(&mut self, increase: bool) {
+        self.monitor_duration = if increase {
+            (self.monitor_duration + MONITOR_DURATION_STEP).min(MAX_TUI_MONITOR_DURATION)
+        } else {
+            self.monitor_duration.saturating_sub(MONITOR_DURATION_STEP)
+        };
+        self.status_message = Some(format!(
+            "Duración del monitoreo: {} (se aplica al pulsar 'm')",
+            format_monitor_duration(self.monitor_duration)
+        ));
+    }
+
+    /// Iniciar monitoreo del proceso seleccionado
     pub fn start_monitoring(&mut self, duration_secs: u64) {
-        self.monitoring_duration = duration_secs;
-        self.monitoring_start_time = Some(Instant::now());
-        self.monitoring_time = Duration::from_secs(0);
-        self.is_monitoring_active = true;
-        self.cpu_history.clear();
-        self.memory_history.clear();
+        let Some(pid) = self.selected_pid else {
+            return;
+        };
+        let file_monitor = self.shared_file_monitor();
+        let network_monitor = self.shared_network_monitor();
+        let capture = Arc::clone(&self.capture);
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        
+        monitored.monitoring_duration = duration_secs;
+        monitored.monitoring_start_time = Some(Instant::now());
+        monitored.monitoring_time = Duration::from_secs(0);
+        monitored.is_monitoring_active = true;
+        monitored.cpu_history.clear();
+        monitored.memory_history.clear();
+        
+        // Reemplazar los hilos de un monitoreo anterior antes de lanzar los nuevos
+        monitored.workers = None;
+        monitored.workers = Some(MonitoringWorkers::spawn(pid, capture, file_monitor, network_monitor));
+        audit_log(AuditEvent::new("monitor", Some(format!("PID {}", pid)), "iniciado"));
         
         // Cambiar mensaje de estado
-        if self.monitoring_duration > 0 {
+        if duration_secs > 0 {
             self.status_message = Some(format!(
                 "Monitoreando proceso por {} segundos", 
-                self.monitoring_duration
+                duration_secs
             ));
         } else {
             self.status_message = Some("Monitoreando proceso indefinidamente".to_string());
         }
     }
     
-    /// Detener monitoreo de proceso
-    pub fn stop_monitoring(&mut self) {
-        self.is_monitoring_active = false;
+    /// Indica si un proceso se está monitoreando activamente
+    pub fn is_monitoring(&self, pid: u32) -> bool {
+        self.monitored.get(&pid).is_some_and(|monitored| monitored.is_monitoring_active)
+    }
+    
+    /// Empezar a recoger muestras de un proceso sin historial; el análisis se lanza
+    /// desde `tick` al completarlas, para que el modelo no reciba un uso de CPU nulo
+    /// (sysinfo necesita dos lecturas separadas en el tiempo para calcularlo)
+    fn start_warm_up(&mut self, pid: u32) {
+        // Lectura inicial que sirve de referencia para la primera muestra
+        self.process_monitor().refresh_specific(&[pid]);
+        
+        let target = self.warmup_samples;
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        monitored.cpu_history.clear();
+        monitored.memory_history.clear();
+        monitored.monitoring_start_time = Some(Instant::now());
+        monitored.warm_up_remaining = Some(target);
+        monitored.llm_analysis = Some(warm_up_progress(0, target));
+        monitored.structured_analysis = None;
+        monitored.llm_scroll.follow_tail();
+        
+        self.status_message = Some(format!("Recogiendo {} muestras antes del análisis...", target));
+    }
+    
+    /// Tomar la siguiente muestra de los calentamientos en curso y lanzar el análisis
+    /// de los que terminan
+    fn advance_warm_ups(&mut self) {
+        let warming: Vec<u32> = self.monitored
+            .values()
+            .filter(|monitored| monitored.warm_up_remaining.is_some())
+            .map(|monitored| monitored.pid)
+            .collect();
+        
+        for pid in warming {
+            let process = self.get_process_by_pid(pid);
+            if process.as_ref().is_some_and(|process| self.reset_if_pid_reused(process)) {
+                continue;
+            }
+            let target = self.warmup_samples;
+            let Some(monitored) = self.monitored.get_mut(&pid) else {
+                continue;
+            };
+            let Some(process) = process else {
+                monitored.warm_up_remaining = None;
+                monitored.llm_analysis = None;
+                self.status_message = Some(format!("El proceso {} terminó antes de completar las muestras", pid));
+                continue;
+            };
+            
+            monitored.record_sample(process.cpu_usage, process.memory_usage);
+            let remaining = monitored.warm_up_remaining.unwrap_or(0).saturating_sub(1);
+            if let Some(start_time) = monitored.monitoring_start_time {
+                monitored.monitoring_time = start_time.elapsed();
+            }
+            if remaining > 0 {
+                monitored.warm_up_remaining = Some(remaining);
+                monitored.llm_analysis = Some(warm_up_progress(target - remaining, target));
+                continue;
+            }
+            monitored.warm_up_remaining = None;
+            
+            // Registrar lo que el proceso tiene abierto en este momento
+            self.record_activity(pid);
+            self.generate_real_analysis(pid);
+        }
+    }
+    
+    /// Detener el monitoreo de un proceso
+    /// Descartar el análisis, la conversación y el historial de CPU y memoria de un proceso
+    pub fn reset_analysis(&mut self, pid: u32) {
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        monitored.llm_analysis = None;
+        monitored.analysis_report = None;
+        monitored.structured_analysis = None;
+        monitored.conversation = None;
+        monitored.cpu_history.clear();
+        monitored.memory_history.clear();
+        monitored.llm_scroll = ScrollableTextState::following();
+        self.status_message = Some("Análisis e historial reiniciados".to_string());
+    }
+
+    pub fn stop_monitoring(&mut self, pid: u32) {
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        monitored.is_monitoring_active = false;
+        // Soltar los hilos los detiene y espera a que terminen
+        monitored.workers = None;
+        let samples = monitored.cpu_history.len();
         
         // Generar mensaje de estado basado en la cantidad de datos recopilados
-        if self.cpu_history.len() >= 5 {
+        if samples >= 5 {
             self.status_message = Some(format!(
                 "Monitoreo detenido. Se recopilaron {} muestras. Presiona 'A' para analizar.", 
-                self.cpu_history.len()
+                samples
             ));
             
             // Cambiar a la pestaña de análisis para guiar al usuario
-            if self.process_monitor_tab == 0 {
+            if self.process_monitor_tab == 0 && self.selected_pid == Some(pid) {
                 self.process_monitor_tab = 1;
             }
-        } else if !self.cpu_history.is_empty() {
+        } else if samples > 0 {
             self.status_message = Some(format!(
                 "Monitoreo detenido. Solo se recopilaron {} muestras. Considera monitorear por más tiempo.", 
-                self.cpu_history.len()
+                samples
             ));
         } else {
             self.status_message = Some("Monitoreo detenido sin recopilar datos.".to_string());
         }
     }
 
-    /// Genera un análisis real con LLM para el proceso seleccionado
-    fn generate_real_analysis(&mut self) {
-        if let Some(pid) = self.selected_pid {
-            if let Some(process) = self.process_monitor.get_process_by_pid(pid) {
-                // Si hay monitoreo activo, primero lo detenemos
-                if self.is_monitoring_active {
-                    self.stop_monitoring();
-                    self.status_message = Some("Monitoreo detenido. Preparando análisis...".to_string());
+    /// Reporte de un análisis con los datos recopilados del proceso: muestras de CPU,
+    /// actividad de archivos y de red y los patrones sospechosos detectados
+    fn build_analysis_report(&self, process: &ProcessInfo, cpu_history: &[f32], monitoring_time: u64) -> Report {
+        let pid = process.pid;
+        let mut report = Report::new_for_process(pid, process.name.clone());
+        report.set_process_info(process.clone());
+        report.analyst_notes = self.analyst_notes.clone();
+        report.set_verbosity(self.verbose);
+        
+        // Añadir datos de monitoreo al reporte
+        if !cpu_history.is_empty() {
+            let avg_cpu = cpu_history.iter().sum::<f32>() / cpu_history.len() as f32;
+            let max_cpu = cpu_history.iter().fold(0.0f32, |max, &val| if val > max { val } else { max });
+            
+            report.add_info(
+                "monitoring", 
+                &format!("Datos de monitoreo UI: CPU promedio {:.2}%, máxima {:.2}%, tiempo {} segundos", 
+                    avg_cpu, max_cpu, monitoring_time),
+                None
+            );
+        }
+        
+        for event in self.file_monitor().get_events_for_pid(pid) {
+            report.add_file_activity(FileActivity {
+                path: PathBuf::from(&event.path),
+                operation: event.operation.clone(),
+                process_id: Some(event.pid),
+                timestamp: event.timestamp,
+                size: event.size,
+                entropy: event.entropy,
+            });
+        }
+        for event in self.network_monitor().get_events_for_pid(pid) {
+            report.add_network_event(event.clone());
+        }
+        
+        // Añadir los patrones sospechosos detectados como hallazgos
+        for pattern in self.file_monitor().detect_suspicious_patterns(pid) {
+            report.add_detection("file_access", SeverityLevel::Critical, &pattern);
+        }
+        for pattern in self.network_monitor().detect_suspicious_patterns(pid) {
+            report.add_detection("network", SeverityLevel::Critical, &pattern);
+        }
+        for pattern in self.network_monitor().detect_unusual_connections(pid, &process.name) {
+            report.add_detection("unusual_connection", SeverityLevel::Warning, &pattern);
+        }
+        for closed in self.network_monitor().get_closed_connections_for_pid(pid) {
+            report.add_closed_connection(closed.clone());
+        }
+        report.update_end_time();
+        report
+    }
+
+    /// Genera el análisis heurístico sin IA (tecla 'd' o `--offline`) con los datos
+    /// recopilados; con `save`, lo guarda además como reporte
+    fn generate_offline_analysis(&mut self, pid: u32, save: bool) {
+        let Some(process) = self.get_process_by_pid(pid) else {
+            self.status_message = Some(format!("El proceso {} ya no existe", pid));
+            return;
+        };
+        if self.is_monitoring(pid) {
+            self.stop_monitoring(pid);
+        }
+        let Some(monitored) = self.monitored.get(&pid) else {
+            return;
+        };
+        let cpu_history = monitored.cpu_history.clone();
+        let monitoring_time = monitored.monitoring_time.as_secs();
+        
+        let mut report = self.build_analysis_report(&process, &cpu_history, monitoring_time);
+        let analysis = heuristic_analysis(&report, &cpu_history);
+        let structured = AnalysisResult::from_report(&report);
+        report.set_offline_analysis(analysis.clone());
+        
+        let saved = if save {
+            if let Some(redactor) = &self.redactor {
+                report.redact(redactor);
+            }
+            if let Some(pseudonymizer) = &mut self.ip_pseudonymizer {
+                report.pseudonymize_ips(pseudonymizer);
+                if let Err(e) = pseudonymizer.save() {
+                    tracing::warn!("No se pudo guardar el mapeo de IPs: {}", e);
                 }
+            }
+            Some(report.save_to_default_dir())
+        } else {
+            None
+        };
+        
+        self.process_monitor_tab = 1;
+        if let Some(monitored) = self.monitored.get_mut(&pid) {
+            monitored.llm_analysis = Some(analysis);
+            monitored.structured_analysis = Some(structured);
+            monitored.llm_scroll = Default::default();
+            // Sin respuesta de un modelo no hay conversación que continuar
+            monitored.conversation = None;
+            monitored.analysis_report = Some(report);
+        }
+        match saved {
+            Some(Ok((json_path, _))) => {
+                self.refresh_saved_reports();
+                self.status_message = Some(format!("Análisis heurístico (sin IA) guardado en {}", json_path.display()));
+            }
+            Some(Err(e)) => {
+                self.status_message = Some(format!("Análisis heurístico (sin IA) generado; no se pudo guardar el reporte: {}", e));
+            }
+            None => {}
+        }
+    }
 
-                // Actualizar mensaje de estado
-                self.status_message = Some("Conectando con servicio LLM...".to_string());
+    /// Genera un análisis real con LLM para un proceso
+    fn generate_real_analysis(&mut self, pid: u32) {
+        if self.offline {
+            self.generate_offline_analysis(pid, true);
+            return;
+        }
+        let Some(process) = self.get_process_by_pid(pid) else {
+            return;
+        };
+        
+        // Si hay monitoreo activo, primero lo detenemos
+        if self.is_monitoring(pid) {
+            self.stop_monitoring(pid);
+            self.status_message = Some("Monitoreo detenido. Preparando análisis...".to_string());
+        }
+        
+        // Historial recopilado para este proceso
+        let Some(monitored) = self.monitored.get(&pid) else {
+            return;
+        };
+        let cpu_history = monitored.cpu_history.clone();
+        let memory_history = monitored.memory_history.clone();
+        let monitoring_time = monitored.monitoring_time.as_secs();
+
+        // Actualizar mensaje de estado
+        self.status_message = Some("Conectando con servicio LLM...".to_string());
+        
+        // Convertir la información del proceso a formato JSON para el LLM
+        let mut process_json = serde_json::json!({
+            "pid": process.pid,
+            "name": process.name,
+            "path": process.path,
+            "cmd_line": process.cmd_line,
+            "cmd_truncated": process.cmd_truncated,
+            "cpu_usage": process.cpu_usage,
+            "memory_usage": process.memory_usage,
+            "status": process.status,
+            "thread_count": process.thread_count,
+            "deleted_exe": process.deleted_exe,
+            "cpu_history": cpu_history,
+            "memory_history": memory_history,
+            "monitoring_time": monitoring_time,
+        });
+        // Bibliotecas cargadas y regiones ejecutables/escribibles
+        if let Ok(regions) = ProcessMonitor::memory_maps(pid) {
+            process_json["memory_map"] = memory_map_summary(&regions);
+        }
+        
+        // Convertir eventos de archivo y red a formato JSON
+        let mut file_events_json = self.analysis_depth.shape_events(
+            serde_json::to_value(self.file_monitor().aggregate_events(pid))
+                .unwrap_or_else(|_| serde_json::json!([])),
+            "operation",
+        );
+        let mut network_events_json = self.analysis_depth.shape_events(
+            serde_json::to_value(self.network_monitor().get_events_for_pid(pid))
+                .unwrap_or_else(|_| serde_json::json!([])),
+            "remote_addr",
+        );
+        
+        // Redactar secretos antes de enviarlos al LLM
+        if let Some(redactor) = &self.redactor {
+            redactor.redact_value(&mut process_json);
+            redactor.redact_value(&mut file_events_json);
+        }
+        if let Some(pseudonymizer) = &mut self.ip_pseudonymizer {
+            pseudonymizer.pseudonymize_value(&mut process_json);
+            pseudonymizer.pseudonymize_value(&mut file_events_json);
+            pseudonymizer.pseudonymize_value(&mut network_events_json);
+            if let Err(e) = pseudonymizer.save() {
+                tracing::warn!("No se pudo guardar el mapeo de IPs: {}", e);
+            }
+        }
+        
+        // Crear un reporte para este análisis
+        let report = self.build_analysis_report(&process, &cpu_history, monitoring_time);
+        
+        // Mostrar un análisis en estado de carga con indicador animado
+        self.process_monitor_tab = 1; // Cambiar a la pestaña de análisis
+        
+        // Crear una cadena de texto con indicador de carga animado
+        let process_name = process.name.clone();
+        let process_pid = process.pid;
+        let process_cpu = process.cpu_usage;
+        let process_mem = format_memory(process.memory_usage);
+        let samples_count = cpu_history.len();
+        
+        // Mostrar análisis en estado de carga
+        let loading_analysis = format!(
+            "## Analizando Comportamiento del Proceso\n\n\
+            **Proceso:** {} (PID: {})\n\n\
+            **⏳ Conectando con el servicio de análisis...**\n\n\
+            Por favor espera mientras se procesa la información del proceso.\n\
+            Este análisis puede tardar unos segundos en completarse.\n\n\
+            **Datos que se están analizando:**\n\
+            - CPU media: {:.2}%\n\
+            - Memoria: {}\n\
+            - Tiempo de monitoreo: {} segundos\n\
+            - Muestras recopiladas: {}\n\n\
+            *La interfaz seguirá respondiendo mientras se realiza el análisis. \
+            El indicador de carga se actualizará automáticamente.*\
+            ",
+            process_name.clone(), 
+            process_pid,
+            process_cpu,
+            process_mem,
+            monitoring_time,
+            samples_count
+        );
+        
+        // Configurar cliente LLM para llamada local con endpoint específico
+        let llm_config = self.analysis_llm_config();
+        
+        let client = self.analysis_client(llm_config);
+        
+        // Avisar si el prompt no cabe en la ventana de contexto del modelo
+        let context_overflow = client
+            .as_ref()
+            .ok()
+            .and_then(|client| {
+                client.comprehensive_context_overflow(&process_json, &file_events_json, &network_events_json).ok()
+            })
+            .flatten();
+        
+        // Crear canal para recibir el resultado del análisis
+        let (tx, rx) = mpsc::channel();
+        
+        // Guardar el análisis en curso en el estado del proceso; el receptor se procesa en tick()
+        if let Some(monitored) = self.monitored.get_mut(&pid) {
+            monitored.analysis_report = Some(report);
+            monitored.structured_analysis = None;
+            monitored.llm_analysis = Some(loading_analysis);
+            monitored.llm_scroll.follow_tail();
+            
+            // Guardar los datos enviados para poder hacer preguntas de seguimiento
+            monitored.conversation = Some(AnalysisConversation {
+                context: serde_json::json!({
+                    "process": process_json.clone(),
+                    "file_activity": file_events_json.clone(),
+                    "network_activity": network_events_json.clone(),
+                }),
+                ..Default::default()
+            });
+            monitored.pending_follow_up = None;
+            monitored.llm_analysis_rx = Some(rx);
+        }
+        
+        // Clonar los datos necesarios para el hilo
+        let process_json_clone = process_json.clone();
+        let file_events_json_clone = file_events_json.clone();
+        let network_events_json_clone = network_events_json.clone();
+        let runtime = self.llm_runtime.clone();
+        
+        // Crear instrucciones de análisis personalizadas
+        let custom_instructions = r#"
+        Eres un modelo de lenguaje. No necesitas capacidades de ejecución para hacer análisis útil.
+
+        A continuación te paso un reporte generado por un sistema llamado ShadowTrace. El reporte contiene texto estructurado con información de procesos, archivos accedidos y conexiones de red. No necesitas analizar archivos ni ejecutar código, solo interpretar lo que ya está escrito.
+
+        Tu tarea es:
+            • Leer el reporte y explicar en lenguaje humano técnico qué está haciendo el proceso monitoreado.
+            • Comentar si el comportamiento es típico o sospechoso.
+            • Hacer hipótesis razonables sobre el propósito del proceso basándote en los datos observados (ej. si parece un navegador, si hay persistencia, si hay tráfico de red anómalo, etc.).
+            • Puedes mencionar si algo parece malicioso, sospechoso o simplemente automatizado.
+            • Tu análisis puede ser especulativo, pero bien fundamentado.
+            • NO digas que no puedes hacer el análisis. NO digas que eres solo un modelo de lenguaje. Ya lo sabemos. Estás aquí para interpretar texto. Hazlo como si fueras un analista de seguridad con experiencia.
+        "#;
+        
+        // Lanzar hilo para realizar el análisis sin bloquear
+        thread::spawn(move || {
+            let result = block_on_llm(runtime, async {
+                match client {
+                    Ok(client) => {
+                        // Añadir instrucciones personalizadas
+                        let mut enriched_data = process_json_clone.clone();
+                        enriched_data["instructions"] = serde_json::json!(custom_instructions);
+                        
+                        // Realizar análisis
+                        let analysis_result = client.comprehensive_analysis(
+                            enriched_data,
+                            file_events_json_clone,
+                            network_events_json_clone
+                        ).await;
+                        let analysis_result = analysis_result.map(|analysis| {
+                            if client.used_reduced_context() {
+                                format!("{}\n\n{}", analysis, REDUCED_CONTEXT_NOTE)
+                            } else {
+                                analysis
+                            }
+                        });
+                        
+                        // Indicar qué modelo respondió si no fue el principal
+                        match (analysis_result, client.answered_by()) {
+                            (Ok(analysis), Some(model)) if client.used_fallback() => Ok(format!(
+                                "{}\n\n*Análisis generado por el modelo de respaldo {}*",
+                                analysis, model
+                            )),
+                            (result, _) => result,
+                        }
+                    },
+                    Err(e) => {
+                        Err(anyhow::anyhow!("Error al crear cliente LLM: {}", e))
+                    }
+                }
+            });
+            
+            // Enviar resultado al hilo principal a través del canal
+            let _ = tx.send(result);
+        });
+        
+        // Actualizar estado pero no intentar procesar la respuesta aquí
+        self.status_message = Some(match context_overflow {
+            Some(estimated) => format!(
+                "Análisis en curso. ⚠️ El prompt (~{} tokens) supera num_ctx; el modelo podría truncarlo",
+                estimated
+            ),
+            None => "Análisis en curso. Por favor espera...".to_string(),
+        });
+        
+        // El resultado será procesado en el método tick()
+    }
+
+    /// Cliente LLM para un análisis, sobre el cliente HTTP compartido si lo hay
+    fn analysis_client(&self, llm_config: LlmConfig) -> anyhow::Result<LlmClient> {
+        match &self.http_client {
+            Some(http_client) => LlmClient::with_http_client(llm_config, http_client.clone()),
+            None => LlmClient::new(llm_config),
+        }
+    }
+
+    /// Configuración del LLM usado para los análisis de la TUI
+    fn analysis_llm_config(&self) -> LlmConfig {
+        LlmConfig {
+            max_tokens: Some(self.llm_config.max_tokens.unwrap_or(TUI_DEFAULT_MAX_TOKENS)),
+            analyst_notes: self.analyst_notes.clone(),
+            ..self.llm_config.clone()
+        }
+    }
+
+    /// Envía una pregunta de seguimiento al LLM con el contexto del análisis previo
+    fn send_follow_up(&mut self, question: String) {
+        let Some(conversation) = self.current().and_then(|monitored| monitored.conversation.clone()) else {
+            self.status_message = Some("No hay un análisis previo sobre el que preguntar".to_string());
+            return;
+        };
+        
+        let (tx, rx) = mpsc::channel();
+        if let Some(monitored) = self.current_mut() {
+            monitored.llm_analysis_rx = Some(rx);
+            monitored.pending_follow_up = Some(question.clone());
+        }
+        self.status_message = Some(format!("Consultando: {}...", question));
+        
+        let client = self.analysis_client(self.analysis_llm_config());
+        let runtime = self.llm_runtime.clone();
+        thread::spawn(move || {
+            let result = block_on_llm(runtime, async {
+                let client = client.map_err(|e| anyhow::anyhow!("Error al crear cliente LLM: {}", e))?;
+                client.follow_up(
+                    conversation.context,
+                    &conversation.analysis,
+                    &conversation.follow_ups,
+                    &question,
+                ).await
+            });
+            
+            let _ = tx.send(result);
+        });
+    }
+
+    /// Guarda el resultado del análisis inicial de un proceso, o un análisis básico si falló
+    fn receive_analysis(&mut self, pid: u32, result: Result<String, anyhow::Error>) {
+        let process = self.get_process_by_pid(pid);
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        
+        match result {
+            Ok(analysis) => {
+                // Guardar la respuesta como base de la conversación
+                if let Some(conversation) = &mut monitored.conversation {
+                    conversation.analysis = analysis.clone();
+                }
                 
-                // Convertir la información del proceso a formato JSON para el LLM
-                let process_json = serde_json::json!({
-                    "pid": process.pid,
-                    "name": process.name,
-                    "path": process.path,
-                    "cmd_line": process.cmd_line,
-                    "cpu_usage": process.cpu_usage,
-                    "memory_usage": process.memory_usage,
-                    "cpu_history": self.cpu_history,
-                    "memory_history": self.memory_history,
-                    "monitoring_time": self.monitoring_time.as_secs(),
+                // Actualizar el análisis y el estado
+                let reduced = analysis.contains(REDUCED_CONTEXT_NOTE);
+                monitored.llm_analysis = Some(analysis);
+                self.status_message = Some(if reduced {
+                    format!(
+                        "Análisis de {} (PID {}) completado solo con estadísticas: el prompt no cabía en la ventana de contexto del modelo",
+                        monitored.name, pid
+                    )
+                } else if self.selected_pid == Some(pid) {
+                    "Análisis completado con éxito. Presiona 'f' para una pregunta de seguimiento".to_string()
+                } else {
+                    format!("Análisis de {} (PID {}) completado", monitored.name, pid)
                 });
+            },
+            Err(e) => {
+                // Sin análisis inicial no hay conversación que continuar
+                monitored.conversation = None;
                 
-                // Convertir eventos de archivo y red a formato JSON
-                let file_events = self.file_monitor.get_events_for_pid(pid);
-                let network_events = self.network_monitor.get_events_for_pid(pid);
-                
-                let file_events_json = serde_json::to_value(&file_events).unwrap_or_else(|_| serde_json::json!([]));
-                let network_events_json = serde_json::to_value(&network_events).unwrap_or_else(|_| serde_json::json!([]));
-                
-                // Crear un reporte para este análisis
-                let mut report = crate::reports::Report::new_for_process(pid, process.name.clone());
-                report.set_process_info(process.clone());
+                // Mostrar un mensaje de error y un análisis alternativo
+                let error_msg = format!("Error al realizar análisis: {}", e);
+                self.status_message = Some(error_msg.clone());
                 
-                // Añadir datos de monitoreo al reporte
-                if !self.cpu_history.is_empty() {
-                    let avg_cpu = self.cpu_history.iter().sum::<f32>() / self.cpu_history.len() as f32;
-                    let max_cpu = self.cpu_history.iter().fold(0.0f32, |max, &val| if val > max { val } else { max });
-                    
-                    report.add_info(
-                        "monitoring", 
-                        &format!("Datos de monitoreo UI: CPU promedio {:.2}%, máxima {:.2}%, tiempo {} segundos", 
-                            avg_cpu, max_cpu, self.monitoring_time.as_secs()),
-                        None
+                if let Some(process) = process {
+                    // Generar análisis alternativo
+                    let fallback_analysis = format!(
+                        "## Análisis de Comportamiento del Proceso\n\n\
+                        **Proceso:** {} (PID: {})\n\n\
+                        **⚠️ Error al conectar con el servicio LLM**\n\n\
+                        {}.\n\n\
+                        **Datos recopilados:**\n\
+                        - CPU media: {:.2}%\n\
+                        - Memoria: {}\n\
+                        - Tiempo de monitoreo: {} segundos\n\
+                        - Muestras recopiladas: {}\n\n\
+                        **Recomendación:** Verifica que el servicio LLM esté activo en {} (modelo {})\n\n\
+                        *Este es un análisis básico generado sin IA debido al error de conexión.*\
+                        ",
+                        process.name, 
+                        process.pid,
+                        error_msg,
+                        process.cpu_usage,
+                        format_memory(process.memory_usage),
+                        monitored.monitoring_time.as_secs(),
+                        monitored.cpu_history.len(),
+                        self.llm_config.api_url,
+                        self.llm_config.model
                     );
-                }
-                
-                // Mostrar un análisis en estado de carga con indicador animado
-                self.process_monitor_tab = 1; // Cambiar a la pestaña de análisis
-                
-                // Crear una cadena de texto con indicador de carga animado
-                let process_name = process.name.clone();
-                let process_pid = process.pid;
-                let process_cpu = process.cpu_usage;
-                let process_mem = process.memory_usage;
-                let monitoring_time = self.monitoring_time.as_secs();
-                let samples_count = self.cpu_history.len();
-                
-                // Mostrar análisis en estado de carga
-                let loading_analysis = format!(
-                    "## Analizando Comportamiento del Proceso\n\n\
-                    **Proceso:** {} (PID: {})\n\n\
-                    **⏳ Conectando con el servicio de análisis...**\n\n\
-                    Por favor espera mientras se procesa la información del proceso.\n\
-                    Este análisis puede tardar unos segundos en completarse.\n\n\
-                    **Datos que se están analizando:**\n\
-                    - CPU media: {:.2}%\n\
-                    - Memoria: {} KB\n\
-                    - Tiempo de monitoreo: {} segundos\n\
-                    - Muestras recopiladas: {}\n\n\
-                    *La interfaz seguirá respondiendo mientras se realiza el análisis. \
-                    El indicador de carga se actualizará automáticamente.*\
-                    ",
-                    process_name.clone(), 
-                    process_pid,
-                    process_cpu,
-                    process_mem,
-                    monitoring_time,
-                    samples_count
-                );
-                
-                self.process_llm_analysis = Some(loading_analysis);
-                
-                // Configurar cliente LLM para llamada local con endpoint específico
-                let llm_config = LlmConfig {
-                    provider: LlmProvider::OpenAiCompatible,
-                    api_url: "http://10.0.0.171:8000/v1/chat/completions".to_string(),
-                    model: "gemma-3-27b-it".to_string(),
-                    temperature: 0.7,
-                    timeout_seconds: 120,
-                    max_tokens: Some(4096),
-                };
-                
-                // Crear canal para recibir el resultado del análisis
-                let (tx, rx) = mpsc::channel();
-                
-                // Guardar el receptor en la estructura para procesarlo en tick()
-                self.llm_analysis_rx = Some(rx);
-                
-                // Clonar los datos necesarios para el hilo
-                let process_json_clone = process_json.clone();
-                let file_events_json_clone = file_events_json.clone();
-                let network_events_json_clone = network_events_json.clone();
-                let llm_config_clone = llm_config.clone();
-                let process_name_clone = process_name.clone();
-                
-                // Crear instrucciones de análisis personalizadas
-                let custom_instructions = r#"
-                Eres un modelo de lenguaje. No necesitas capacidades de ejecución para hacer análisis útil.
-
-                A continuación te paso un reporte generado por un sistema llamado ShadowTrace. El reporte contiene texto estructurado con información de procesos, archivos accedidos y conexiones de red. No necesitas analizar archivos ni ejecutar código, solo interpretar lo que ya está escrito.
-
-                Tu tarea es:
-                    • Leer el reporte y explicar en lenguaje humano técnico qué está haciendo el proceso monitoreado.
-                    • Comentar si el comportamiento es típico o sospechoso.
-                    • Hacer hipótesis razonables sobre el propósito del proceso basándote en los datos observados (ej. si parece un navegador, si hay persistencia, si hay tráfico de red anómalo, etc.).
-                    • Puedes mencionar si algo parece malicioso, sospechoso o simplemente automatizado.
-                    • Tu análisis puede ser especulativo, pero bien fundamentado.
-                    • NO digas que no puedes hacer el análisis. NO digas que eres solo un modelo de lenguaje. Ya lo sabemos. Estás aquí para interpretar texto. Hazlo como si fueras un analista de seguridad con experiencia.
-                "#;
-                
-                // Lanzar hilo para realizar el análisis sin bloquear
-                thread::spawn(move || {
-                    // Esta parte se ejecuta en un hilo separado, por lo que es seguro crear un nuevo runtime aquí
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    let result = rt.block_on(async {
-                        // Crear cliente LLM
-                        match LlmClient::new(llm_config_clone) {
-                            Ok(client) => {
-                                // Añadir instrucciones personalizadas
-                                let mut enriched_data = process_json_clone.clone();
-                                enriched_data["instructions"] = serde_json::json!(custom_instructions);
-                                
-                                // Realizar análisis
-                                let analysis_result = client.comprehensive_analysis(
-                                    enriched_data,
-                                    file_events_json_clone,
-                                    network_events_json_clone
-                                ).await;
-                                
-                                analysis_result
-                            },
-                            Err(e) => {
-                                Err(anyhow::anyhow!("Error al crear cliente LLM: {}", e))
-                            }
-                        }
-                    });
                     
-                    // Enviar resultado al hilo principal a través del canal
-                    let _ = tx.send(result);
-                });
-                
-                // Actualizar estado pero no intentar procesar la respuesta aquí
-                self.status_message = Some("Análisis en curso. Por favor espera...".to_string());
-                
-                // El resultado será procesado en el método tick()
+                    monitored.llm_analysis = Some(fallback_analysis);
+                }
+            }
+        }
+    }
+
+    /// Añade la respuesta de una pregunta de seguimiento al análisis mostrado
+    fn receive_follow_up(&mut self, pid: u32, question: String, result: Result<String, anyhow::Error>) {
+        let Some(monitored) = self.monitored.get_mut(&pid) else {
+            return;
+        };
+        
+        match result {
+            Ok(answer) => {
+                let section = format!("\n\n---\n\n## Seguimiento: {}\n\n{}", question, answer);
+                monitored.llm_analysis
+                    .get_or_insert_with(String::new)
+                    .push_str(&section);
+                if let Some(conversation) = &mut monitored.conversation {
+                    conversation.follow_ups.push((question, answer));
+                }
+                self.status_message = Some("Respuesta de seguimiento añadida al análisis".to_string());
+            },
+            Err(e) => {
+                self.status_message = Some(format!("Error en la pregunta de seguimiento: {}", e));
             }
         }
     }
 
     // Añadir método para actualizar el indicador de carga
     fn update_loading_indicator(&mut self) {
-        if let Some(analysis) = &mut self.process_llm_analysis {
-            if analysis.contains("⏳ Conectando con el servicio de análisis...") {
-                // Actualizar el indicador de carga basado en el tick_count
-                let loading_chars = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-                let idx = (self.tick_count / 5) % loading_chars.len() as u64;
-                let loading_char = loading_chars[idx as usize];
-                
-                // Actualizar el texto con el nuevo indicador
-                *analysis = analysis.replace("⏳", loading_char);
+        // Actualizar el indicador de carga basado en el tick_count
+        let loading_chars = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+        let idx = (self.tick_count / 5) % loading_chars.len() as u64;
+        let loading_char = loading_chars[idx as usize];
+        
+        for monitored in self.monitored.values_mut() {
+            if let Some(analysis) = &mut monitored.llm_analysis {
+                if analysis.contains("⏳ Conectando con el servicio de análisis...") {
+                    // Actualizar el texto con el nuevo indicador
+                    *analysis = analysis.replace("⏳", loading_char);
+                }
             }
         }
     }
 
-    /// Maneja el desplazamiento del texto de análisis LLM
+    /// Maneja el desplazamiento del texto de análisis LLM del proceso seleccionado.
+    ///
+    /// Desplazarse hacia arriba deja de seguir el final del texto; volver al
+    /// final (End o bajando hasta la última página) lo reactiva.
     pub fn handle_llm_text_scroll(&mut self, key: KeyCode) {
-        if let Some(analysis) = &self.process_llm_analysis {
-            // Convertir el texto a líneas para calcular el tamaño total
-            let total_lines = analysis.lines().count();
-            
-            // Obtener el índice actual de scroll o inicializarlo
-            let current_index = self.llm_text_scroll_index.unwrap_or(0);
-            
-            // Calcular el nuevo índice según la tecla presionada
-            let new_index = match key {
-                KeyCode::Up => current_index.saturating_sub(1),
-                KeyCode::Down => if current_index + 1 < total_lines {
-                    current_index + 1
-                } else {
-                    current_index
-                },
-                KeyCode::PageUp => current_index.saturating_sub(10),
-                KeyCode::PageDown => if current_index + 10 < total_lines {
-                    current_index + 10
-                } else {
-                    total_lines.saturating_sub(1)
-                },
-                KeyCode::Home => 0,
-                KeyCode::End => total_lines.saturating_sub(1),
-                _ => current_index,
-            };
-            
-            // Actualizar el índice de scroll
-            self.llm_text_scroll_index = Some(new_index);
+        let scrolled = self
+            .current_mut()
+            .is_some_and(|monitored| monitored.llm_analysis.is_some() && monitored.llm_scroll.handle_key(key));
+        if scrolled && key == KeyCode::End {
+            self.status_message = Some("Siguiendo el final del análisis".to_string());
         }
     }
 } 
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use crate::ui::widgets::ChartStyle;
 
 // Simulación de la biblioteca rsille
 pub struct BrailleCanvas {
@@ -48,10 +48,8 @@ impl Canvas for BrailleCanvas {
         // Simplificado para este ejemplo
         let mut result = String::new();
         
-        for y in 0..(self.height / 4) {
-            for x in 0..(self.width / 2) {
-                // Determinar qué carácter braille usar
-                let char_code = 0x2800; // Carácter braille base
+        for _y in 0..(self.height / 4) {
+            for _x in 0..(self.width / 2) {
                 result.push('⠶'); // Carácter braille simple de ejemplo
             }
             result.push('\n');
@@ -61,6 +59,24 @@ impl Canvas for BrailleCanvas {
     }
 }
 
+impl BrailleCanvas {
+    /// Representación ASCII: cada celda de 2x4 puntos es `*` si tiene algún punto activo y espacio si no
+    pub fn to_ascii_string(&self) -> String {
+        let mut result = String::new();
+        
+        for row in 0..(self.height / 4) {
+            for col in 0..(self.width / 2) {
+                let lit = (col * 2..col * 2 + 2)
+                    .any(|x| (row * 4..row * 4 + 4).any(|y| self.data[x][y]));
+                result.push(if lit { '*' } else { ' ' });
+            }
+            result.push('\n');
+        }
+        
+        result
+    }
+}
+
 /// Tipos de animaciones disponibles
 #[derive(Debug, Clone, Copy)]
 pub enum AnimationType {
@@ -76,7 +92,6 @@ pub struct BrailleAnimator {
     canvas: BrailleCanvas,
     width: usize,
     height: usize,
-    start_time: Instant,
     animation_type: AnimationType,
     frame_count: u64,
 }
@@ -88,7 +103,6 @@ impl BrailleAnimator {
             canvas: BrailleCanvas::new(width, height),
             width,
             height,
-            start_time: Instant::now(),
             animation_type,
             frame_count: 0,
         }
@@ -102,6 +116,10 @@ impl BrailleAnimator {
             self.frame_count = self.frame_count.wrapping_add(1);
         }
         self.canvas.clear();
+        // Un área vacía (terminal reducida al mínimo) no tiene nada que dibujar
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
         
         match self.animation_type {
             AnimationType::Wave => self.draw_wave_animation(),
@@ -117,6 +135,14 @@ impl BrailleAnimator {
         self.canvas.to_string()
     }
     
+    /// Representación con el estilo de gráficos elegido
+    pub fn render_as(&self, style: ChartStyle) -> String {
+        match style {
+            ChartStyle::Braille => self.render(),
+            ChartStyle::Ascii => self.canvas.to_ascii_string(),
+        }
+    }
+    
     // Animación de onda 
     fn draw_wave_animation(&mut self) {
         // Implementación simplificada
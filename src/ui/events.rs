@@ -2,7 +2,7 @@ use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
-use crossterm::event::{self, Event as CEvent, KeyEvent, KeyEventKind};
+use crossterm::event::{self, Event as CEvent};
 use anyhow::Result;
 
 pub enum Event<I> {
@@ -4,6 +4,7 @@ pub mod widgets;
 pub mod screens;
 pub mod events;
 pub mod braille_art;
+pub mod workers;
 
 pub use app::App;
 pub use tui::Tui;
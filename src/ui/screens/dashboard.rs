@@ -1,7 +1,7 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Line, Text},
+    text::{Span, Line},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
@@ -97,7 +97,7 @@ fn draw_animation(frame: &mut Frame, app: &mut App, area: Rect) {
     animator.update(None);
     
     // Renderizar la animación como Paragraph
-    let animation_text = animator.render();
+    let animation_text = animator.render_as(app.chart_style);
     let animation_paragraph = Paragraph::new(animation_text);
     
     frame.render_widget(animation_paragraph, inner_area);
@@ -150,14 +150,13 @@ fn draw_main_menu(frame: &mut Frame, _app: &mut App, area: Rect) {
     
     // Dibujar cada opción de menú en su columna
     for (i, chunk) in horizontal_chunks.iter().enumerate() {
-        let items_per_column = (menu_items.len() + horizontal_chunks.len() - 1) / horizontal_chunks.len();
+        let items_per_column = menu_items.len().div_ceil(horizontal_chunks.len());
         let start_idx = i * items_per_column;
         let end_idx = (start_idx + items_per_column).min(menu_items.len());
         
         let mut column_items = Vec::new();
         
-        for j in start_idx..end_idx {
-            let (title, desc, color) = menu_items[j];
+        for (j, &(title, desc, color)) in menu_items.iter().enumerate().take(end_idx).skip(start_idx) {
             
             column_items.push(Line::from(vec![
                 Span::styled(format!("[{: >3}] ", j + 1), Style::default().fg(Color::White)),
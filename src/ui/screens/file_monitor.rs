@@ -1,8 +1,8 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Span, Line},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
@@ -48,7 +48,7 @@ pub fn draw_file_monitor(frame: &mut Frame, app: &mut App) {
     let mut animator = BrailleAnimator::new(braille_width, braille_height, AnimationType::Matrix);
     animator.update(None);
     
-    let animation_text = animator.render();
+    let animation_text = animator.render_as(app.chart_style);
     let animation_paragraph = Paragraph::new(animation_text);
     
     frame.render_widget(animation_paragraph, inner_area);
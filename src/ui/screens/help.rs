@@ -1,14 +1,15 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Span, Line},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
 use crate::ui::App;
+use crate::ui::widgets::ScrollableText;
 
-pub fn draw_help(frame: &mut Frame, _app: &mut App) {
+pub fn draw_help(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
     
     // Dividir la pantalla en secciones
@@ -91,10 +92,82 @@ pub fn draw_help(frame: &mut Frame, _app: &mut App) {
             Span::styled("  N", Style::default().fg(Color::LightCyan)),
             Span::raw(" - Ir a monitoreo de red")
         ]),
+        Line::from(vec![
+            Span::styled("  F", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Pregunta de seguimiento sobre el análisis (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  D", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Análisis heurístico sin IA con los datos recopilados; se guarda como reporte (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  L", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Ver la línea de comandos completa del proceso seleccionado (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  C", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Marcar dos procesos y compararlos lado a lado (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  N", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Añadir una nota del analista al próximo análisis; SUPR borra la última (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  E/T", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Línea de tiempo de archivos y red del proceso seleccionado / cambiar el filtro (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  V", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Alternar la vista compacta, sin gráficos (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  +/-", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Ajustar la duración del próximo monitoreo; por debajo de 10 s es indefinido (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  X", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Reiniciar el análisis y el historial del proceso seleccionado (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  P", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Fijar/soltar el proceso seleccionado como pestaña (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  !", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Mostrar solo los procesos sospechosos / todos (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  1-9", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Cambiar a la pestaña de un proceso fijado (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  End", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Volver a seguir el final del análisis (monitor de procesos)")
+        ]),
+        Line::from(vec![
+            Span::styled("  W", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Pausar/reanudar el tráfico en vivo (pantalla de red)")
+        ]),
         Line::from(vec![
             Span::styled("  R", Style::default().fg(Color::LightCyan)),
             Span::raw(" - Ir a reportes")
         ]),
+        Line::from(vec![
+            Span::styled("  C/O", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Copiar la ruta del reporte seleccionado / mostrarlo en el explorador de archivos (pantalla de reportes)")
+        ]),
+        Line::from(vec![
+            Span::styled("  ENTER/E", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Ver los hallazgos del reporte JSON seleccionado / explicar el hallazgo con IA (pantalla de reportes)")
+        ]),
+        Line::from(vec![
+            Span::styled("  M/D", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Marcar un reporte JSON / compararlo con el seleccionado (pantalla de reportes)")
+        ]),
+        Line::from(vec![
+            Span::styled("  F2", Style::default().fg(Color::LightCyan)),
+            Span::raw(" - Mostrar/ocultar el consumo de CPU y memoria de ShadowTrace")
+        ]),
         Line::from(vec![
             Span::styled("  H", Style::default().fg(Color::LightCyan)),
             Span::raw(" - Mostrar esta ayuda")
@@ -105,17 +178,16 @@ pub fn draw_help(frame: &mut Frame, _app: &mut App) {
         ]),
     ];
     
-    let help_paragraph = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL).title(" Instrucciones "))
-        .style(Style::default())
-        .wrap(Wrap { trim: true });
+    let help_widget = ScrollableText::new(help_text)
+        .title("Instrucciones")
+        .hint("↑/↓: Navegar | PgUp/PgDn: Saltar | Home/End: Inicio/Fin");
     
-    frame.render_widget(help_paragraph, chunks[1]);
+    frame.render_stateful_widget(help_widget, chunks[1], &mut app.help_scroll);
     
     // Barra de estado
     let status_bar = Paragraph::new(Line::from(vec![
         Span::styled(" ℹ️ ", Style::default().fg(Color::LightYellow)),
-        Span::raw("Presiona ESC o Q para volver al menú principal"),
+        Span::raw("↑/↓, PgUp/PgDn: Desplazar | ESC o Q: Volver al menú principal"),
     ]))
     .block(Block::default().borders(Borders::ALL))
     .style(Style::default());
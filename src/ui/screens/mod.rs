@@ -1,13 +1,19 @@
 mod dashboard;
 mod process_monitor;
+mod process_compare;
 mod file_monitor;
 mod network_monitor;
 mod reports;
 mod help;
+mod watchdog;
+mod timeline;
 
 pub use dashboard::draw_dashboard;
 pub use process_monitor::draw_process_monitor;
+pub use process_compare::draw_process_compare;
 pub use file_monitor::draw_file_monitor;
 pub use network_monitor::draw_network_monitor;
 pub use reports::draw_reports;
-pub use help::draw_help; 
+pub use help::draw_help;
+pub use watchdog::draw_self_usage;
+pub use timeline::draw_timeline;
@@ -2,16 +2,32 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Line},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph, Sparkline},
     Frame,
 };
 
+use std::net::SocketAddr;
+
+use crate::asn::AsnEgress;
+use crate::network::{BandwidthHistory, ClosedConnection, Connection};
 use crate::ui::App;
-use crate::ui::braille_art::{BrailleAnimator, AnimationType};
+use crate::ui::widgets::{ChartStyle, SparklineBraille};
+
+/// Altura (en filas) de cada conexión en la vista en vivo
+const CONNECTION_ROW_HEIGHT: u16 = 2;
+
+/// Conexiones cerradas que se muestran bajo las activas
+const CLOSED_ROWS: usize = 5;
+
+/// Ancho de la columna de salida por organización (`--asn-db`)
+const EGRESS_COLUMN_WIDTH: u16 = 44;
+
+/// Conexión activa con una copia de su historial de ancho de banda
+type ConnectionSnapshot = (Connection, Option<BandwidthHistory>);
 
 pub fn draw_network_monitor(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
-    
+
     // Dividir la pantalla en secciones
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -21,49 +37,251 @@ pub fn draw_network_monitor(frame: &mut Frame, app: &mut App) {
             Constraint::Length(3),   // Barra de estado
         ].as_ref())
         .split(size);
-    
+
     // Título
     let title = Paragraph::new(Line::from(vec![
-        Span::styled("Monitoreo de Red", 
+        Span::styled("Monitoreo de Red",
             Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD))
     ]))
     .alignment(ratatui::layout::Alignment::Center)
     .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Blue)));
-    
+
     frame.render_widget(title, chunks[0]);
+
+    // Copiar las conexiones y su historial para no retener el bloqueo durante el render
+    let (connections, closed, llm_endpoints): (Vec<ConnectionSnapshot>, Vec<ClosedConnection>, Vec<SocketAddr>) = {
+        let network_monitor = app.network_monitor();
+        let connections = network_monitor
+            .get_connections()
+            .iter()
+            .map(|conn| (conn.clone(), network_monitor.get_bandwidth_history(conn).cloned()))
+            .collect();
+        // Las más recientes primero
+        let closed = network_monitor
+            .get_closed_connections()
+            .iter()
+            .rev()
+            .take(CLOSED_ROWS)
+            .cloned()
+            .collect();
+        (connections, closed, network_monitor.ignored_remotes().to_vec())
+    };
     
-    // En construcción - Mostrar una animación
+    // Con --asn-db, una columna a la derecha agrupa los destinos por organización
+    let egress = app
+        .asn_database
+        .as_ref()
+        .map(|database| database.group_egress(connections.iter().map(|(conn, _)| conn.remote_addr)))
+        .unwrap_or_default();
+    let content_area = if egress.is_empty() {
+        chunks[1]
+    } else {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(40), Constraint::Length(EGRESS_COLUMN_WIDTH)].as_ref())
+            .split(chunks[1]);
+        draw_egress_by_asn(frame, &egress, columns[1]);
+        columns[0]
+    };
+    
+    // Reservar una franja inferior para las conexiones cerradas recientemente
+    let (connections_area, closed_area) = if closed.is_empty() {
+        (content_area, None)
+    } else {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(6),                          // Conexiones activas
+                Constraint::Length(closed.len() as u16 + 2), // Cerradas recientes
+            ].as_ref())
+            .split(content_area);
+        (areas[0], Some(areas[1]))
+    };
+    let block_title = format!(
+        " Conexiones activas: {} {} ",
+        connections.len(),
+        if app.network_watch { "[en vivo]" } else { "[pausado]" }
+    );
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(" Monitoreo de Red en Construcción ")
+        .title(block_title)
         .style(Style::default().fg(Color::Blue));
+
+    let inner_area = block.inner(connections_area);
+    frame.render_widget(block, connections_area);
+
+    if connections.is_empty() {
+        // Las conexiones solo se capturan de los procesos monitoreados
+        let empty = Paragraph::new(vec![
+            Line::from(Span::styled("Sin conexiones capturadas", Style::default().fg(Color::DarkGray))),
+            Line::from(Span::styled(
+                "Monitorea un proceso con 'm' para ver sus conexiones y su tráfico",
+                Style::default().fg(Color::DarkGray),
+            )),
+        ]);
+        frame.render_widget(empty, inner_area);
+    } else {
+        draw_connections(frame, &connections, &llm_endpoints, inner_area, app.chart_style);
+    }
     
-    let inner_area = block.inner(chunks[1]);
-    frame.render_widget(block, chunks[1]);
-    
-    // Animación
-    let braille_width = inner_area.width as usize * 2;
-    let braille_height = inner_area.height as usize * 4;
-    
-    let mut animator = BrailleAnimator::new(braille_width, braille_height, AnimationType::Scanner);
-    animator.update(None);
-    
-    let animation_text = animator.render();
-    let animation_paragraph = Paragraph::new(animation_text);
-    
-    frame.render_widget(animation_paragraph, inner_area);
-    
+    if let Some(area) = closed_area {
+        draw_closed_connections(frame, &closed, area);
+    }
+
     // Barra de estado
     let status_bar = Paragraph::new(Line::from(vec![
         Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
         Span::raw("ESC: Volver | "),
-        Span::styled("↑↓", Style::default().fg(Color::LightYellow)),
-        Span::raw(": Navegar | "),
-        Span::styled("ENTER", Style::default().fg(Color::LightYellow)),
-        Span::raw(": Seleccionar"),
+        Span::styled("w", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Pausar/reanudar refresco | "),
+        Span::styled("▲", Style::default().fg(Color::Red)),
+        Span::raw(": Pico de tráfico"),
     ]))
     .block(Block::default().borders(Borders::ALL))
     .style(Style::default());
-    
+
     frame.render_widget(status_bar, chunks[2]);
-} 
+}
+
+/// Dibujar cada conexión con su sparkline de throughput
+fn draw_connections(
+    frame: &mut Frame,
+    connections: &[(Connection, Option<BandwidthHistory>)],
+    llm_endpoints: &[SocketAddr],
+    area: Rect,
+    chart_style: ChartStyle,
+) {
+    let max_rows = (area.height / CONNECTION_ROW_HEIGHT) as usize;
+    let connections = &connections[..connections.len().min(max_rows)];
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            connections
+                .iter()
+                .map(|_| Constraint::Length(CONNECTION_ROW_HEIGHT))
+                .chain(std::iter::once(Constraint::Min(0)))
+                .collect::<Vec<_>>()
+        )
+        .split(area);
+
+    for ((conn, history), row) in connections.iter().zip(rows.iter()) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(64), Constraint::Min(10)].as_ref())
+            .split(*row);
+
+        let history = history.as_ref();
+        let spiking = history.is_some_and(|h| h.is_spiking());
+        let current = history.map_or(0.0, |h| h.current());
+        let color = if spiking { Color::Red } else { Color::Green };
+
+        let info = Paragraph::new(vec![
+            Line::from(vec![
+                Span::styled(
+                    if spiking { "▲ " } else { "  " },
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("{:?} ", conn.protocol), Style::default().fg(Color::Cyan)),
+                Span::raw(format!("{} → {}", conn.local_addr, conn.remote_addr)),
+                // El tráfico hacia el LLM configurado no cuenta en los detectores
+                Span::styled(
+                    if llm_endpoints.contains(&conn.remote_addr) { " [LLM de ShadowTrace]" } else { "" },
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw(format!(
+                    "  {} | ↑ {} ↓ {} | ",
+                    conn.state.as_deref().unwrap_or("-"),
+                    format_bytes(conn.bytes_sent as f64),
+                    format_bytes(conn.bytes_received as f64),
+                )),
+                Span::styled(format!("{}/muestra", format_bytes(current)), Style::default().fg(color)),
+            ]),
+        ]);
+        frame.render_widget(info, columns[0]);
+
+        if let Some(history) = history {
+            match chart_style {
+                ChartStyle::Braille => {
+                    let sparkline = SparklineBraille::new(&history.samples)
+                        .min(0.0)
+                        .style(Style::default().fg(color));
+                    frame.render_widget(sparkline, columns[1]);
+                }
+                ChartStyle::Ascii => {
+                    let samples: Vec<u64> = history.samples.iter().map(|&v| v.max(0.0) as u64).collect();
+                    let sparkline = Sparkline::default()
+                        .data(&samples)
+                        .style(Style::default().fg(color));
+                    frame.render_widget(sparkline, columns[1]);
+                }
+            }
+        }
+    }
+}
+
+/// Dibujar las conexiones cerradas recientemente con su duración y bytes finales
+fn draw_closed_connections(frame: &mut Frame, closed: &[ClosedConnection], area: Rect) {
+    let lines: Vec<Line> = closed
+        .iter()
+        .map(|closed| {
+            let conn = &closed.connection;
+            Line::from(vec![
+                Span::styled(format!("  {:?} ", conn.protocol), Style::default().fg(Color::DarkGray)),
+                Span::raw(format!("{} → {}", conn.local_addr, conn.remote_addr)),
+                Span::styled(
+                    format!(
+                        " | {:.1} s | ↑ {} ↓ {}",
+                        closed.duration().as_secs_f64(),
+                        format_bytes(conn.bytes_sent as f64),
+                        format_bytes(conn.bytes_received as f64),
+                    ),
+                    Style::default().fg(Color::Gray),
+                ),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(" Cerradas recientes ")
+            .style(Style::default().fg(Color::DarkGray)));
+    frame.render_widget(paragraph, area);
+}
+
+/// Dibujar las conexiones activas agrupadas por la organización propietaria del destino
+fn draw_egress_by_asn(frame: &mut Frame, egress: &[AsnEgress], area: Rect) {
+    let lines: Vec<Line> = egress
+        .iter()
+        .map(|group| {
+            Line::from(vec![
+                Span::styled(format!(" {:>3} ", group.connections), Style::default().fg(Color::LightYellow)),
+                Span::styled(
+                    group.label(),
+                    Style::default().fg(if group.asn.is_some() { Color::White } else { Color::Red }),
+                ),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(" Salida por organización ")
+            .style(Style::default().fg(Color::Blue)));
+    frame.render_widget(paragraph, area);
+}
+
+/// Formatear una cantidad de bytes en unidades legibles
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1} KB", bytes / 1024.0)
+    } else {
+        format!("{:.0} B", bytes)
+    }
+}
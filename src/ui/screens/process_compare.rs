@@ -0,0 +1,252 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Wrap},
+    text::{Span, Line},
+    Frame,
+};
+
+use crate::process::{format_memory, memory_mib, ProcessComparison, ProcessInfo};
+use crate::ui::App;
+use crate::ui::widgets::{ChartStyle, Level};
+
+pub fn draw_process_compare(frame: &mut Frame, app: &mut App) {
+    let size = frame.area();
+
+    // Dividir la pantalla en secciones
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),   // Título
+            Constraint::Min(10),     // Procesos lado a lado
+            Constraint::Length(8),   // Diferencias
+            Constraint::Length(3),   // Barra de estado
+        ].as_ref())
+        .split(size);
+
+    // Título
+    let title = Paragraph::new(Line::from(vec![
+        Span::styled("Comparación de Procesos",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    ]))
+    .alignment(ratatui::layout::Alignment::Center)
+    .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Blue)));
+
+    frame.render_widget(title, main_chunks[0]);
+
+    let processes = app.compare_pids.map(|(left, right)| {
+        (app.get_process_by_pid(left), app.get_process_by_pid(right))
+    });
+    let comparison = match &processes {
+        Some((Some(left), Some(right))) => Some(left.compare(right)),
+        _ => None,
+    };
+
+    // Un proceso a cada lado
+    let side_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ].as_ref())
+        .split(main_chunks[1]);
+
+    if let Some((left, right)) = &processes {
+        let pids = app.compare_pids.unwrap_or_default();
+        draw_side(frame, app, side_chunks[0], 0, pids.0, left.as_ref(), comparison.as_ref());
+        draw_side(frame, app, side_chunks[1], 1, pids.1, right.as_ref(), comparison.as_ref());
+    }
+
+    draw_differences(frame, app, comparison.as_ref(), main_chunks[2]);
+
+    // Barra de estado
+    let status = app.status_message.clone().unwrap_or_default();
+    let status_bar = Paragraph::new(Line::from(vec![
+        Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
+        Span::raw("ESC: Volver | "),
+        Span::styled("X", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
+        Span::raw(": Intercambiar lados | "),
+        Span::styled(" 📋 ", Style::default().fg(Color::LightYellow)),
+        Span::raw(format!(": {}", status)),
+    ]))
+    .block(Block::default().borders(Borders::ALL))
+    .style(Style::default());
+
+    frame.render_widget(status_bar, main_chunks[3]);
+}
+
+/// Dibujar los detalles, la actividad y los gráficos de uno de los procesos
+fn draw_side(
+    frame: &mut Frame,
+    app: &App,
+    area: Rect,
+    side: usize,
+    pid: u32,
+    process: Option<&ProcessInfo>,
+    comparison: Option<&ProcessComparison>,
+) {
+    let Some(process) = process else {
+        let paragraph = Paragraph::new(vec![
+            Line::from(Span::styled("Proceso no encontrado", Style::default().fg(Color::Red))),
+            Line::from(Span::raw(format!("El proceso con PID {} ya no está disponible.", pid))),
+        ])
+        .alignment(ratatui::layout::Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title(format!(" PID {} ", pid)));
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(12),      // Detalles y actividad
+            Constraint::Percentage(50),  // CPU
+            Constraint::Percentage(50),  // Memoria
+        ].as_ref())
+        .split(area);
+
+    // Resaltar los valores que difieren del otro proceso
+    let value = |field: &str, text: String| {
+        let level = comparison
+            .and_then(|comparison| comparison.difference(field))
+            .map(|difference| if difference.suspicious { Level::Critical } else { Level::Warning });
+        let style = match level {
+            Some(Level::Critical) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Some(_) => Style::default().fg(Color::Yellow),
+            None => Style::default(),
+        };
+        match level.filter(|_| app.accessible) {
+            Some(level) => Span::styled(format!("{} {}", level.symbol(app.chart_style), text), style),
+            None => Span::styled(text, style),
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::LightYellow));
+
+    let file_events = app.file_monitor().get_events_for_pid(pid).len();
+    let network_events = app.network_monitor().get_events_for_pid(pid).len();
+
+    let details = vec![
+        Line::from(vec![label("Nombre:    "), value("Nombre", process.name.clone())]),
+        Line::from(vec![
+            label("Ruta:      "),
+            value("Ruta", process.path.clone().filter(|path| !path.is_empty()).unwrap_or_else(|| "-".to_string())),
+        ]),
+        Line::from(vec![
+            label("Comandos:  "),
+            value("Comandos", process.cmd_line.clone().map_or("-".to_string(), |cmd| cmd.join(" "))),
+        ]),
+        Line::from(vec![label("Usuario:   "), value("Usuario", process.user.clone().unwrap_or_else(|| "-".to_string()))]),
+        Line::from(vec![label("Estado:    "), value("Estado", process.status.label().to_string())]),
+        Line::from(vec![
+            label("Hilos:     "),
+            value("Hilos", process.thread_count.map_or("-".to_string(), |threads| threads.to_string())),
+        ]),
+        Line::from(vec![label("CPU:       "), value("CPU", format!("{:.2}%", process.cpu_usage))]),
+        Line::from(vec![label("Memoria:   "), value("Memoria", format_memory(process.memory_usage))]),
+        Line::from(vec![label("Archivos:  "), Span::raw(format!("{} eventos", file_events))]),
+        Line::from(vec![label("Red:       "), Span::raw(format!("{} eventos", network_events))]),
+    ];
+
+    let details_paragraph = Paragraph::new(details)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" PID {} ", pid))
+            .style(Style::default().fg(Color::Blue)))
+        .style(Style::default())
+        .wrap(Wrap { trim: true });
+    frame.render_widget(details_paragraph, chunks[0]);
+
+    let marker = match app.chart_style {
+        ChartStyle::Braille => Marker::Braille,
+        ChartStyle::Ascii => Marker::Block,
+    };
+
+    let cpu_data: Vec<(f64, f64)> = app.compare_cpu_history[side]
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| (i as f64, value as f64))
+        .collect();
+    draw_history_chart(frame, chunks[1], " CPU % ", &cpu_data, 100.0, Color::Cyan, marker);
+
+    let mem_data: Vec<(f64, f64)> = app.compare_memory_history[side]
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| (i as f64, memory_mib(value)))
+        .collect();
+    // Mínimo 10 MiB para evitar gráficos planos
+    let max_mem = (mem_data.iter().map(|(_, value)| *value).fold(0.0, f64::max) * 1.2).max(10.0);
+    draw_history_chart(frame, chunks[2], " Memoria (MiB) ", &mem_data, max_mem, Color::Magenta, marker);
+}
+
+/// Dibujar el historial de una métrica con el eje Y entre 0 y `max`
+fn draw_history_chart(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    data: &[(f64, f64)],
+    max: f64,
+    color: Color,
+    marker: Marker,
+) {
+    let dataset = Dataset::default()
+        .marker(marker)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(data);
+
+    let chart = Chart::new(vec![dataset])
+        .block(Block::default().title(title.to_string()).borders(Borders::ALL))
+        .x_axis(Axis::default().bounds([0.0, (data.len() as f64).max(30.0)]))
+        .y_axis(Axis::default()
+            .bounds([0.0, max])
+            .labels(["0".to_string(), format!("{:.0}", max)]
+                .into_iter()
+                .map(Span::raw)
+                .collect::<Vec<_>>()));
+
+    frame.render_widget(chart, area);
+}
+
+/// Dibujar la lista de diferencias, con las señales de suplantación en rojo
+fn draw_differences(frame: &mut Frame, app: &App, comparison: Option<&ProcessComparison>, area: Rect) {
+    let mut lines = Vec::new();
+
+    match comparison {
+        None => lines.push(Line::from(Span::styled(
+            "Alguno de los procesos ya no está disponible",
+            Style::default().fg(Color::Red),
+        ))),
+        Some(comparison) if comparison.differences.is_empty() => lines.push(Line::from(Span::styled(
+            "Sin diferencias relevantes",
+            Style::default().fg(Color::Green),
+        ))),
+        Some(comparison) => {
+            if comparison.is_masquerade() {
+                lines.push(Line::from(Span::styled(
+                    "⚠️ Posible suplantación: mismo nombre con distinto ejecutable",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+            }
+            for difference in &comparison.differences {
+                let level = if difference.suspicious { Level::Critical } else { Level::Warning };
+                let style = Style::default().fg(level.color());
+                let marker = if app.accessible {
+                    format!("{} {} ", level.symbol(app.chart_style), level.token())
+                } else {
+                    String::new()
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{}{:<10}", marker, difference.field), style.add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{}  ≠  {}", difference.left, difference.right)),
+                ]));
+            }
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Diferencias "))
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
@@ -1,49 +1,77 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    symbols::{self, Marker},
+    symbols::Marker,
     widgets::{
-        Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph, 
-        Sparkline, Wrap
+        Axis, Block, Borders, Chart, Dataset, Gauge, GraphType, List, ListItem, Paragraph, Wrap
     },
     text::{Span, Line},
     Frame,
 };
 
+use crate::process::{format_memory, memory_mib, ProcessInfo, ProcessStatus};
+use crate::offline_analysis::AnalysisResult;
+use crate::reports::{Report, RiskLevel, SeverityLevel};
 use crate::ui::App;
+use crate::ui::app::format_monitor_duration;
 use crate::ui::braille_art::{BrailleAnimator, AnimationType};
+use crate::ui::widgets::{ChartStyle, Level, ScrollableText, ScrollableTextState};
 
 pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
-    let size = frame.size();
+    let size = frame.area();
+    
+    // En el diseño compacto el título y la barra de estado ocupan una sola línea sin
+    // bordes y los gráficos se ocultan para dejar sitio a la lista y al análisis
+    let compact = app.is_compact(size.height);
+    let (bar_height, bar_borders) = if compact { (1, Borders::NONE) } else { (3, Borders::ALL) };
     
     // Dividir la pantalla en secciones
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),   // Título
-            Constraint::Min(10),     // Contenido principal
-            Constraint::Length(3),   // Barra de estado
+            Constraint::Length(bar_height), // Título
+            Constraint::Min(5),             // Contenido principal
+            Constraint::Length(bar_height), // Barra de estado
         ].as_ref())
         .split(size);
     
     // Título
-    let title = Paragraph::new(Line::from(vec![
+    let mut title_spans = vec![
         Span::styled("Monitoreo de Procesos", 
             Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-    ]))
+    ];
+    if compact {
+        title_spans.push(Span::styled(" (vista compacta)", Style::default().fg(Color::DarkGray)));
+    }
+    let title = Paragraph::new(Line::from(title_spans))
     .alignment(ratatui::layout::Alignment::Center)
-    .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Blue)));
+    .block(Block::default().borders(bar_borders).style(Style::default().fg(Color::Blue)));
     
     frame.render_widget(title, main_chunks[0]);
     
+    // Pestañas de los procesos fijados sobre el contenido, solo si hay alguno
+    let content_area = if app.pinned.is_empty() {
+        main_chunks[1]
+    } else {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Procesos fijados
+                Constraint::Min(5),    // Contenido principal
+            ].as_ref())
+            .split(main_chunks[1]);
+        draw_pinned_tabs(frame, app, chunks[0]);
+        chunks[1]
+    };
+    
     // Contenido principal
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(30), // Lista de procesos
-            Constraint::Percentage(70), // Detalles/gráficos o análisis
+            Constraint::Percentage(if compact { 40 } else { 30 }), // Lista de procesos
+            Constraint::Percentage(if compact { 60 } else { 70 }), // Detalles/gráficos o análisis
         ].as_ref())
-        .split(main_chunks[1]);
+        .split(content_area);
     
     // Lista de procesos (siempre visible)
     draw_process_list(frame, app, content_chunks[0]);
@@ -68,14 +96,18 @@ pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
     let tabs_row = Line::from(tabs);
     let tabs_para = Paragraph::new(tabs_row)
         .alignment(ratatui::layout::Alignment::Center)
-        .block(Block::default().borders(Borders::TOP | Borders::LEFT | Borders::RIGHT));
+        .block(Block::default().borders(if compact {
+            Borders::NONE
+        } else {
+            Borders::TOP | Borders::LEFT | Borders::RIGHT
+        }));
     
     // Dividir el área derecha para mostrar las pestañas y el contenido
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Pestañas
-            Constraint::Min(5),    // Contenido de la pestaña
+            Constraint::Length(if compact { 1 } else { 3 }), // Pestañas
+            Constraint::Min(3),                              // Contenido de la pestaña
         ].as_ref())
         .split(content_chunks[1]);
     
@@ -83,6 +115,10 @@ pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
     
     // Mostrar el contenido según la pestaña seleccionada
     match app.process_monitor_tab {
+        0 if compact => {
+            // Sin gráficos: los detalles ocupan toda la pestaña
+            draw_process_details(frame, app, right_chunks[1]);
+        },
         0 => {
             // Vista de detalles y gráficos
             let details_chunks = Layout::default()
@@ -118,32 +154,118 @@ pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
         Span::raw(": Navegar | "),
         Span::styled("ENTER", Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
         Span::raw(": Seleccionar | "),
+        Span::styled("C", Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
+        Span::raw(if app.compare_candidate.is_some() { ": Comparar con el marcado | " } else { ": Comparar | " }),
     ];
     
+    let is_monitoring = app.current().is_some_and(|monitored| monitored.is_monitoring_active);
+    let samples = app.current().map_or(0, |monitored| monitored.cpu_history.len());
+    let has_conversation = app.current().is_some_and(|monitored| monitored.conversation.is_some());
+    
     // Añadir controles específicos basados en el estado actual
-    if app.is_monitoring_active {
+    if is_monitoring {
         status_spans.push(Span::styled("S", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
         status_spans.push(Span::raw(": Detener monitoreo | "));
     } else if app.selected_pid.is_some() {
         status_spans.push(Span::styled("M", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
-        status_spans.push(Span::raw(": Monitorear | "));
+        status_spans.push(Span::raw(format!(": Monitorear ({}, ", format_monitor_duration(app.monitor_duration))));
+        status_spans.push(Span::styled("+/-", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+        status_spans.push(Span::raw(") | "));
         
         // Destacar opción de analizar si hay suficientes datos
-        let analyze_style = if app.cpu_history.len() >= 5 {
+        let analyze_style = if samples >= 5 {
             Style::default().fg(Color::LightGreen).bg(Color::Black).add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)
             };
             
         status_spans.push(Span::styled("A", analyze_style));
-        if app.cpu_history.len() >= 5 {
+        if samples >= 5 {
             status_spans.push(Span::raw(": Analizar datos | "));
         } else {
             status_spans.push(Span::raw(": Analizar | "));
         }
+        status_spans.push(Span::styled("D", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+        status_spans.push(Span::raw(": Sin IA | "));
         
         status_spans.push(Span::styled("TAB", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
         status_spans.push(Span::raw(": Cambiar vista | "));
+        
+        status_spans.push(Span::styled("P", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+        status_spans.push(Span::raw(if app.selected_pid.is_some_and(|pid| app.pinned.contains(&pid)) {
+            ": Soltar | "
+        } else {
+            ": Fijar | "
+        }));
+        
+        if has_conversation {
+            status_spans.push(Span::styled("F", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+            status_spans.push(Span::raw(": Preguntar | "));
+        }
+        
+        status_spans.push(Span::styled("X", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+        status_spans.push(Span::raw(": Reiniciar | "));
+    }
+    if app.selected_pid.is_some() {
+        status_spans.push(Span::styled("E", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+        status_spans.push(Span::raw(": Línea de tiempo | "));
+    }
+    status_spans.push(Span::styled("N", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+    status_spans.push(Span::raw(match app.analyst_notes.len() {
+        0 => ": Nota | ".to_string(),
+        count => format!(": Notas ({}) | ", count),
+    }));
+    status_spans.push(Span::styled("V", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+    status_spans.push(Span::raw(if compact { ": Vista normal | " } else { ": Vista compacta | " }));
+    
+    // La confirmación de reinicio reemplaza la barra de estado hasta que se responda
+    if app.reset_confirmation.is_some() {
+        let confirm_bar = Paragraph::new(Line::from(vec![
+            Span::styled(" ⚠️ Hay un monitoreo activo. ", Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
+            Span::raw("¿Detenerlo y reiniciar el análisis y el historial? (S/ENTER: Sí | cualquier otra tecla: No)"),
+        ]))
+            .block(Block::default().borders(bar_borders))
+            .style(Style::default());
+        
+        frame.render_widget(confirm_bar, main_chunks[2]);
+        return;
+    }
+    
+    // Si la entrada de seguimiento está abierta, reemplaza la barra de estado
+    if let Some(input) = &app.follow_up_input {
+        status_spans = vec![
+            Span::styled(" ❓ Seguimiento > ", Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
+            Span::raw(input.clone()),
+            Span::styled("█", Style::default().fg(Color::LightYellow)),
+            Span::raw("  (ENTER: Enviar | ESC: Cancelar)"),
+        ];
+        
+        let input_bar = Paragraph::new(Line::from(status_spans))
+            .block(Block::default().borders(bar_borders))
+            .style(Style::default());
+        
+        frame.render_widget(input_bar, main_chunks[2]);
+        return;
+    }
+    
+    // Igual con la entrada de notas del analista
+    if let Some(input) = &app.note_input {
+        status_spans = vec![
+            Span::styled(" 📝 Nota > ", Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)),
+            Span::raw(input.clone()),
+            Span::styled("█", Style::default().fg(Color::LightYellow)),
+            Span::raw(format!(
+                "  (ENTER: Añadir | SUPR: Borrar la última | ESC: Cancelar) {} notas",
+                app.analyst_notes.len()
+            )),
+        ];
+        
+        let input_bar = Paragraph::new(Line::from(status_spans))
+            .block(Block::default().borders(bar_borders))
+            .style(Style::default());
+        
+        frame.render_widget(input_bar, main_chunks[2]);
+        return;
     }
     
     // Añadir mensaje de estado
@@ -151,7 +273,7 @@ pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
     status_spans.push(Span::raw(format!(": {}", status)));
     
     let status_bar = Paragraph::new(Line::from(status_spans))
-        .block(Block::default().borders(Borders::ALL))
+        .block(Block::default().borders(bar_borders))
         .style(Style::default());
     
     frame.render_widget(status_bar, main_chunks[2]);
@@ -160,6 +282,10 @@ pub fn draw_process_monitor(frame: &mut Frame, app: &mut App) {
 fn draw_process_list(frame: &mut Frame, app: &mut App, area: Rect) {
     // Crear lista de procesos
     let processes = &app.processes;
+    let compare_candidate = app.compare_candidate;
+    let pinned = &app.pinned;
+    let thresholds = app.color_thresholds;
+    let (accessible, chart_style) = (app.accessible, app.chart_style);
     
     let items: Vec<ListItem> = processes
         .iter()
@@ -167,29 +293,51 @@ fn draw_process_list(frame: &mut Frame, app: &mut App, area: Rect) {
             let name = p.name.clone();
             let pid = p.pid;
             let cpu = p.cpu_usage;
+            let cpu_level = thresholds.cpu_level(cpu);
             
             // Formato mejorado para mayor visibilidad
-            let content = Line::from(vec![
+            let mut spans = vec![
+                // Marca del proceso pendiente de comparar
+                if compare_candidate == Some(pid) {
+                    Span::styled("⇄ ", Style::default().fg(Color::LightMagenta).add_modifier(Modifier::BOLD))
+                } else if pinned.contains(&pid) {
+                    // Marca de los procesos fijados como pestaña
+                    Span::styled("● ", Style::default().fg(Color::LightCyan))
+                } else {
+                    Span::raw("  ")
+                },
                 Span::raw(format!("{:<8}", pid)),
                 Span::styled(
-                    format!("{:>6.1}% ", cpu),
+                    if accessible {
+                        format!("{:>6.1}% {} ", cpu, cpu_level.symbol(chart_style))
+                    } else {
+                        format!("{:>6.1}% ", cpu)
+                    },
                     Style::default()
-                        .fg(if cpu > 50.0 { Color::Red } 
-                            else if cpu > 20.0 { Color::Yellow } 
-                            else { Color::Green })
+                        .fg(cpu_level.color())
                         .add_modifier(Modifier::BOLD)
                 ),
                 Span::raw(name),
-            ]);
+            ];
+            // Ejecutable borrado del disco con el proceso aún en marcha
+            if p.has_deleted_exe() {
+                spans.push(Span::styled(" [BORRADO]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+            }
             
-            ListItem::new(content)
+            ListItem::new(Line::from(spans))
         })
         .collect();
     
+    let title = if app.suspicious_only {
+        format!(" Procesos sospechosos: {} ", processes.len())
+    } else {
+        " Procesos ".to_string()
+    };
+    
     let list = List::new(items)
         .block(Block::default()
             .borders(Borders::ALL)
-            .title(" Procesos ")
+            .title(title)
             .title_style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)))
         .highlight_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
         .highlight_symbol(" 👉 ");
@@ -209,7 +357,8 @@ fn draw_process_details(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(block, area);
     
     if let Some(pid) = selected_pid {
-        if let Some(process) = app.process_monitor.get_process_by_pid(pid) {
+        let lookup = app.lookup_process(pid);
+        if let Ok(Some(process)) = lookup {
             // Detalles del proceso
             let details = vec![
                 Line::from(vec![
@@ -223,27 +372,47 @@ fn draw_process_details(frame: &mut Frame, app: &mut App, area: Rect) {
                 Line::from(vec![
                     Span::styled("CPU:       ", Style::default().fg(Color::LightYellow)),
                     Span::styled(
-                        format!("{:.2}%", process.cpu_usage),
-                        Style::default().fg(
-                            if process.cpu_usage > 50.0 { Color::Red } 
-                            else if process.cpu_usage > 20.0 { Color::Yellow } 
-                            else { Color::Green }
-                        ),
+                        format!("{:.2}%{}", process.cpu_usage, level_suffix(app, app.color_thresholds.cpu_level(process.cpu_usage))),
+                        Style::default().fg(app.color_thresholds.cpu_color(process.cpu_usage)),
                     ),
                 ]),
                 Line::from(vec![
                     Span::styled("Memoria:   ", Style::default().fg(Color::LightYellow)),
-                    Span::raw(format!("{} KB", process.memory_usage)),
+                    Span::styled(
+                        format!(
+                            "{}{}",
+                            format_memory(process.memory_usage),
+                            level_suffix(app, app.color_thresholds.memory_level(memory_mib(process.memory_usage)))
+                        ),
+                        Style::default().fg(app.color_thresholds.memory_color(memory_mib(process.memory_usage))),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Estado:    ", Style::default().fg(Color::LightYellow)),
+                    Span::styled(
+                        process.status.label(),
+                        Style::default().fg(match process.status {
+                            ProcessStatus::Zombie | ProcessStatus::Dead => Color::Red,
+                            ProcessStatus::Stopped | ProcessStatus::Tracing => Color::Yellow,
+                            _ => Color::Green,
+                        }),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Hilos:     ", Style::default().fg(Color::LightYellow)),
+                    Span::raw(process.thread_count.map_or("-".to_string(), |threads| threads.to_string())),
                 ]),
                 Line::from(vec![Span::raw("")]),
                 Line::from(vec![
                     Span::styled("Ruta:      ", Style::default().fg(Color::LightYellow)),
                     Span::raw(process.path.clone().unwrap_or_else(|| "-".to_string())),
+                    if process.has_deleted_exe() {
+                        Span::styled("  [BORRADO DEL DISCO]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+                    } else {
+                        Span::raw("")
+                    },
                 ]),
-                Line::from(vec![
-                    Span::styled("Comandos:  ", Style::default().fg(Color::LightYellow)),
-                    Span::raw(process.cmd_line.clone().map_or("-".to_string(), |cmd| cmd.join(" "))),
-                ]),
+                command_line(&process, app.full_cmd_line.as_ref()),
             ];
             
             let details_paragraph = Paragraph::new(details)
@@ -252,13 +421,17 @@ fn draw_process_details(frame: &mut Frame, app: &mut App, area: Rect) {
             
             frame.render_widget(details_paragraph, inner_area);
         } else {
-            // No se encontró el proceso
+            // Terminado o sin permisos para leerlo
+            let (title, detail) = match lookup {
+                Err(e) => ("Proceso no accesible", e.to_string()),
+                _ => ("Proceso no encontrado", format!("El proceso con PID {} ha terminado.", pid)),
+            };
             let text = vec![
                 Line::from(vec![
-                    Span::styled("Proceso no encontrado", Style::default().fg(Color::Red)),
+                    Span::styled(title, Style::default().fg(Color::Red)),
                 ]),
                 Line::from(vec![
-                    Span::raw(format!("El proceso con PID {} ya no está disponible.", pid)),
+                    Span::raw(detail),
                 ]),
             ];
             
@@ -292,9 +465,31 @@ fn draw_process_details(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+/// Línea de detalles con los comandos, completos si se pidieron con 'l'
+/// o con una marca si se recortaron
+fn command_line<'a>(process: &ProcessInfo, full_cmd_line: Option<&(u32, Vec<String>)>) -> Line<'a> {
+    let mut spans = vec![Span::styled("Comandos:  ", Style::default().fg(Color::LightYellow))];
+    match full_cmd_line.filter(|(pid, _)| *pid == process.pid) {
+        Some((_, args)) => spans.push(Span::raw(args.join(" "))),
+        None => {
+            spans.push(Span::raw(process.cmd_line.clone().map_or("-".to_string(), |cmd| cmd.join(" "))));
+            if process.cmd_truncated {
+                spans.push(Span::styled(" … [L: ver completa]", Style::default().fg(Color::DarkGray)));
+            }
+        }
+    }
+    Line::from(spans)
+}
+
 fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
     let selected_pid = app.selected_pid;
     
+    // Historial recopilado del proceso seleccionado (vacío si aún no se monitoreó)
+    let monitored = app.current();
+    let is_monitoring = monitored.is_some_and(|monitored| monitored.is_monitoring_active);
+    let cpu_history = monitored.map_or(&[][..], |monitored| monitored.cpu_history.as_slice());
+    let memory_history = monitored.map_or(&[][..], |monitored| monitored.memory_history.as_slice());
+    
     // Dividir área para los gráficos
     let graphs_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -305,61 +500,73 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
         .split(area);
     
     if let Some(pid) = selected_pid {
-        if let Some(process) = app.process_monitor.get_process_by_pid(pid) {
+        if let Some(process) = app.get_process_by_pid(pid) {
             // Preparar datos para los gráficos
             let cpu_data: Vec<(f64, f64)>;
             let mem_data: Vec<(f64, f64)>;
             
             // Usar datos históricos reales si hay monitoreo activo
-            if app.is_monitoring_active && !app.cpu_history.is_empty() {
+            if is_monitoring && !cpu_history.is_empty() {
                 // Convertir historial a formato de datos para el gráfico
-                cpu_data = app.cpu_history.iter().enumerate()
+                cpu_data = cpu_history.iter().enumerate()
                     .map(|(i, &value)| (i as f64, value as f64))
                     .collect();
                 
-                mem_data = app.memory_history.iter().enumerate()
-                    .map(|(i, &value)| (i as f64, value as f64 / 1000.0)) // Convertir a MB
+                mem_data = memory_history.iter().enumerate()
+                    .map(|(i, &value)| (i as f64, memory_mib(value)))
                     .collect();
             } else {
                 // Usar datos simulados si no hay monitoreo activo
                 cpu_data = simulate_chart_data(app.tick_count, process.cpu_usage as f64);
-                mem_data = simulate_chart_data(app.tick_count, process.memory_usage as f64 / 1000.0); // Convertir a MB
+                mem_data = simulate_chart_data(app.tick_count, memory_mib(process.memory_usage));
             }
             
             // Añadir indicadores de monitoreo si está activo
             let mut cpu_title = " CPU % ".to_string();
-            let mut mem_title = " Memoria (MB) ".to_string();
+            let mut mem_title = " Memoria (MiB) ".to_string();
             
-            if app.is_monitoring_active {
-                let elapsed = app.monitoring_time.as_secs();
-                let duration_info = if app.monitoring_duration > 0 {
-                    format!("{}/{} seg", elapsed, app.monitoring_duration)
+            if let Some(monitored) = monitored.filter(|_| is_monitoring) {
+                let elapsed = monitored.monitoring_time.as_secs();
+                let duration_info = if monitored.monitoring_duration > 0 {
+                    format!("{}/{} seg", elapsed, monitored.monitoring_duration)
                 } else {
                     format!("{} seg", elapsed)
                 };
                 
                 cpu_title = format!(" CPU % [Monitoreo: {}] ", duration_info);
-                mem_title = format!(" Memoria (MB) [Muestras: {}] ", app.cpu_history.len());
-            } else if app.cpu_history.len() >= 5 {
+                mem_title = format!(" Memoria (MiB) [Muestras: {}] ", cpu_history.len());
+            } else if cpu_history.len() >= 5 {
                 // Mostrar indicador de datos listos para análisis
-                cpu_title = format!(" CPU % [Datos recopilados: {}] ", app.cpu_history.len());
-                mem_title = format!(" Memoria (MB) [Análisis disponible ✓] ");
+                cpu_title = format!(" CPU % [Datos recopilados: {}] ", cpu_history.len());
+                mem_title = " Memoria (MiB) [Análisis disponible ✓] ".to_string();
+            }
+            
+            // Las líneas toman el color del último valor real, igual que la lista y los detalles
+            let current_cpu = cpu_history.last().copied().filter(|_| is_monitoring).unwrap_or(process.cpu_usage);
+            let current_mem = memory_mib(memory_history.last().copied().filter(|_| is_monitoring).unwrap_or(process.memory_usage));
+            if app.accessible {
+                cpu_title = format!("{}{} ", cpu_title.trim_end(), level_suffix(app, app.color_thresholds.cpu_level(current_cpu)));
+                mem_title = format!("{}{} ", mem_title.trim_end(), level_suffix(app, app.color_thresholds.memory_level(current_mem)));
             }
             
             // Gráfico de CPU
+            let chart_marker = match app.chart_style {
+                ChartStyle::Braille => Marker::Braille,
+                ChartStyle::Ascii => Marker::Block,
+            };
             let cpu_dataset = Dataset::default()
                 .name("CPU %")
-                .marker(Marker::Braille)
+                .marker(chart_marker)
                 .graph_type(GraphType::Line)
-                .style(Style::default().fg(Color::Cyan))
+                .style(Style::default().fg(app.color_thresholds.cpu_color(current_cpu)))
                 .data(&cpu_data);
             
             let cpu_chart = Chart::new(vec![cpu_dataset])
                 .block(Block::default().title(cpu_title).borders(Borders::ALL))
                 .x_axis(Axis::default()
                     .title(Span::styled("Tiempo", Style::default().fg(Color::Gray)))
-                    .bounds([0.0, if app.is_monitoring_active && !app.cpu_history.is_empty() { 
-                        app.cpu_history.len() as f64 
+                    .bounds([0.0, if is_monitoring && !cpu_history.is_empty() { 
+                        cpu_history.len() as f64 
                     } else { 
                         30.0 
                     }])
@@ -379,20 +586,20 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
             
             // Gráfico de Memoria
             let mem_dataset = Dataset::default()
-                .name("Memoria (MB)")
-                .marker(Marker::Braille)
+                .name("Memoria (MiB)")
+                .marker(chart_marker)
                 .graph_type(GraphType::Line)
-                .style(Style::default().fg(Color::Magenta))
+                .style(Style::default().fg(app.color_thresholds.memory_color(current_mem)))
                 .data(&mem_data);
             
             // Calcular límite máximo para el eje Y de memoria
-            let max_mem = if app.is_monitoring_active && !app.memory_history.is_empty() {
+            let max_mem = if is_monitoring && !memory_history.is_empty() {
                 // Usar el valor máximo del historial multiplicado por 1.2 para dar espacio
-                let max_val = *app.memory_history.iter().max().unwrap_or(&process.memory_usage);
-                (max_val as f64 / 1000.0) * 1.2
+                let max_val = *memory_history.iter().max().unwrap_or(&process.memory_usage);
+                memory_mib(max_val) * 1.2
             } else {
-                (process.memory_usage as f64 / 1000.0) * 1.2
-            }.max(10.0); // Mínimo 10 MB para evitar gráficos planos
+                memory_mib(process.memory_usage) * 1.2
+            }.max(10.0); // Mínimo 10 MiB para evitar gráficos planos
             
             // Crear etiquetas para el eje Y como strings para evitar problemas de lifetime
             let label_0 = "0".to_string();
@@ -405,8 +612,8 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
                 .block(Block::default().title(mem_title).borders(Borders::ALL))
                 .x_axis(Axis::default()
                     .title(Span::styled("Tiempo", Style::default().fg(Color::Gray)))
-                    .bounds([0.0, if app.is_monitoring_active && !app.memory_history.is_empty() { 
-                        app.memory_history.len() as f64 
+                    .bounds([0.0, if is_monitoring && !memory_history.is_empty() { 
+                        memory_history.len() as f64 
                     } else { 
                         30.0 
                     }])
@@ -415,11 +622,11 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
                         .map(|&x| Span::raw(x))
                         .collect::<Vec<_>>()))
                 .y_axis(Axis::default()
-                    .title(Span::styled("MB", Style::default().fg(Color::Gray)))
+                    .title(Span::styled("MiB", Style::default().fg(Color::Gray)))
                     .bounds([0.0, max_mem])
                     .labels([&label_0, &label_1, &label_2, &label_3, &label_4]
                         .iter()
-                        .map(|x| Span::raw(x.clone()))
+                        .map(|x| Span::raw((*x).clone()))
                         .collect::<Vec<_>>()));
             
             frame.render_widget(mem_chart, graphs_chunks[1]);
@@ -431,7 +638,7 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
                 .style(Style::default().fg(Color::DarkGray));
             
             let mem_block = Block::default()
-                .title(" Memoria (MB) ")
+                .title(" Memoria (MiB) ")
                 .borders(Borders::ALL)
                 .style(Style::default().fg(Color::DarkGray));
             
@@ -455,13 +662,22 @@ fn draw_process_graphs(frame: &mut Frame, app: &mut App, area: Rect) {
         let mut animator = BrailleAnimator::new(braille_width, braille_height, AnimationType::Pulse);
         animator.update(None);
         
-        let animation_text = animator.render();
+        let animation_text = animator.render_as(app.chart_style);
         let animation_paragraph = Paragraph::new(animation_text);
         
         frame.render_widget(animation_paragraph, inner_area);
     }
 }
 
+/// Símbolo y texto del nivel que siguen a un valor coloreado en el modo accesible
+fn level_suffix(app: &App, level: Level) -> String {
+    if app.accessible {
+        format!(" {} {}", level.symbol(app.chart_style), level.token())
+    } else {
+        String::new()
+    }
+}
+
 // Función auxiliar para simular datos de gráfico
 fn simulate_chart_data(seed: u64, current_value: f64) -> Vec<(f64, f64)> {
     let mut data = Vec::new();
@@ -478,69 +694,148 @@ fn simulate_chart_data(seed: u64, current_value: f64) -> Vec<(f64, f64)> {
     data
 }
 
+/// Color de un nivel de riesgo
+fn risk_color(level: RiskLevel) -> Color {
+    match level {
+        RiskLevel::Low => Color::Green,
+        RiskLevel::Medium => Color::Yellow,
+        RiskLevel::High => Color::LightRed,
+        RiskLevel::Critical => Color::Red,
+    }
+}
+
+/// Franja con el veredicto de riesgo y el conteo de hallazgos del último análisis
+fn draw_risk_banner(frame: &mut Frame, report: &Report, area: Rect) {
+    let level = report.risk_level();
+    let color = risk_color(level);
+    
+    let banner = Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!(" RIESGO {} ", level.label().to_uppercase()),
+            Style::default().fg(Color::Black).bg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!("  {}/100", report.risk_score()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::raw(format!("  |  {}", report.severity_counts().summary())),
+    ]))
+    .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(color)));
+    
+    frame.render_widget(banner, area);
+}
+
+/// Dibujar un análisis estructurado: indicador de riesgo, resumen, indicadores y
+/// recomendaciones (desplazables, porque la lista puede ser larga)
+fn draw_structured_analysis(frame: &mut Frame, result: &AnalysisResult, scroll: &mut ScrollableTextState, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Percentage(45),
+            Constraint::Min(4),
+        ].as_ref())
+        .split(area);
+    
+    let color = risk_color(result.risk);
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(" Riesgo "))
+        .gauge_style(Style::default().fg(color))
+        .ratio(f64::from(result.risk_score.min(100)) / 100.0)
+        .label(Span::styled(
+            format!("{} {}/100", result.risk.label().to_uppercase(), result.risk_score),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    frame.render_widget(gauge, chunks[0]);
+    
+    let summary = Paragraph::new(result.summary.as_str())
+        .block(Block::default().borders(Borders::ALL).title(" Resumen (análisis heurístico, sin IA) "))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(summary, chunks[1]);
+    
+    let indicators: Vec<ListItem> = if result.indicators.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "Ninguno: el comportamiento observado no activó las reglas de ShadowTrace",
+            Style::default().fg(Color::Green),
+        ))]
+    } else {
+        result.indicators
+            .iter()
+            .map(|indicator| {
+                let color = match indicator.severity {
+                    SeverityLevel::Critical | SeverityLevel::Error => Color::Red,
+                    SeverityLevel::Warning => Color::Yellow,
+                    SeverityLevel::Info => Color::Gray,
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", indicator.severity.marker()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{}: ", indicator.title), Style::default().fg(Color::Cyan)),
+                    Span::raw(indicator.description.clone()),
+                ]))
+            })
+            .collect()
+    };
+    let indicators = List::new(indicators)
+        .block(Block::default().borders(Borders::ALL).title(format!(" Indicadores ({}) ", result.indicators.len())));
+    frame.render_widget(indicators, chunks[2]);
+    
+    let recommendations: Vec<Line> = result.recommendations
+        .iter()
+        .map(|recommendation| Line::from(format!("• {}", recommendation)))
+        .collect();
+    let recommendations = ScrollableText::new(recommendations)
+        .title("Recomendaciones")
+        .hint("↑/↓: Navegar | PgUp/PgDn: Saltar");
+    frame.render_stateful_widget(recommendations, chunks[3], scroll);
+}
+
 /// Dibujar panel de análisis LLM
 fn draw_llm_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
-    // Mostrar análisis LLM si hay uno disponible
-    if let Some(analysis) = &app.process_llm_analysis {
-        // Convertir el análisis markdown a texto formateado para la interfaz
-        let text = convert_markdown_to_spans(analysis);
-        
-        // Calcular si necesitamos scroll vertical
-        let total_lines = text.len();
-        let visible_lines = area.height as usize - 2; // Restamos 2 por los bordes
-        
-        // Inicializar o actualizar el scroll si es necesario
-        if app.llm_text_scroll_index.is_none() {
-            app.llm_text_scroll_index = Some(0);
-        }
-        
-        // Obtener el índice de scroll actual
-        let scroll_index = app.llm_text_scroll_index.unwrap_or(0);
+    let Some(monitored) = app.current_mut() else {
+        // Mensaje cuando no hay proceso seleccionado
+        let content = vec![
+            Line::from(vec![
+                Span::styled("Selecciona un proceso para analizar", 
+                    Style::default().fg(Color::Gray))
+            ]),
+        ];
         
-        // Mostrar indicadores de scroll solo si es necesario
-        let mut block = Block::default()
-            .borders(Borders::ALL)
-            .title(" Análisis LLM ");
-            
-        // Añadir indicadores de scroll en el título si hay más contenido
-        if total_lines > visible_lines {
-            let scroll_percentage = if total_lines > 0 {
-                (scroll_index as f64 / (total_lines - visible_lines.min(total_lines)) as f64 * 100.0) as u16
-            } else {
-                0
-            };
-            
-            let scroll_info = format!(" Análisis LLM [{}%] ↓↑ ", scroll_percentage);
-            block = block.title(scroll_info);
-        }
+        let paragraph = Paragraph::new(content)
+            .block(Block::default().borders(Borders::ALL).title(" Análisis LLM "))
+            .alignment(ratatui::layout::Alignment::Center)
+            .wrap(Wrap { trim: true });
         
-        let paragraph = Paragraph::new(text)
-            .block(block)
-            .wrap(Wrap { trim: true })
-            .scroll((scroll_index as u16, 0));
-            
         frame.render_widget(paragraph, area);
-        
-        // Instrucciones de navegación si hay scroll disponible
-        if total_lines > visible_lines {
-            let nav_text = "↑/↓: Navegar | PgUp/PgDn: Saltar";
-            let nav_style = Style::default().fg(Color::DarkGray);
-            
-            // Crear un pequeño widget para mostrar las instrucciones de navegación
-            let nav_width = nav_text.len() as u16 + 4;
-            let nav_height = 1;
-            let nav_x = area.x + area.width.saturating_sub(nav_width);
-            let nav_y = area.y + area.height.saturating_sub(1);
-            
-            if nav_x >= area.x && nav_y >= area.y {
-                let nav_area = Rect::new(nav_x, nav_y, nav_width, nav_height);
-                let nav_widget = Paragraph::new(Line::from(vec![Span::styled(nav_text, nav_style)]))
-                    .alignment(ratatui::layout::Alignment::Right);
-                
-                frame.render_widget(nav_widget, nav_area);
-            }
+        return;
+    };
+    
+    // El análisis heurístico tiene un diseño fijo; el del LLM es Markdown libre
+    if let Some(result) = &monitored.structured_analysis {
+        draw_structured_analysis(frame, result, &mut monitored.llm_scroll, area);
+        return;
+    }
+    
+    // Reservar una franja superior para el veredicto de riesgo si hay reporte
+    let area = match (&monitored.analysis_report, &monitored.llm_analysis) {
+        (Some(report), Some(_)) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(5)].as_ref())
+                .split(area);
+            draw_risk_banner(frame, report, chunks[0]);
+            chunks[1]
         }
-    } else if let Some(pid) = app.selected_pid {
+        _ => area,
+    };
+    
+    // Mostrar análisis LLM si hay uno disponible
+    if let Some(analysis) = &monitored.llm_analysis {
+        // Convertir el análisis markdown a texto formateado para la interfaz
+        let text = convert_markdown_to_spans(analysis);
+        
+        let text_widget = ScrollableText::new(text)
+            .title("Análisis LLM")
+            .hint("↑/↓: Navegar | PgUp/PgDn: Saltar | End: Seguir el final");
+        frame.render_stateful_widget(text_widget, area, &mut monitored.llm_scroll);
+    } else {
         // Mostrar un mensaje para iniciar análisis
         let mut content = vec![
             Line::from(vec![
@@ -550,10 +845,10 @@ fn draw_llm_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
             Line::from(""),
         ];
         
-        // Restablecer el índice de scroll cuando no hay análisis
-        app.llm_text_scroll_index = None;
+        // El próximo análisis empieza siguiendo el final
+        monitored.llm_scroll.follow_tail();
         
-        if app.is_monitoring_active {
+        if monitored.is_monitoring_active {
             content.push(Line::from(vec![
                 Span::raw("El monitoreo está activo. Presiona "),
                 Span::styled("S", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
@@ -561,7 +856,7 @@ fn draw_llm_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
                 Span::styled("A", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
                 Span::raw(" para realizar un análisis.")
             ]));
-        } else if app.cpu_history.is_empty() {
+        } else if monitored.cpu_history.is_empty() {
             content.push(Line::from(vec![
                 Span::raw("No hay datos de monitoreo. Presiona "),
                 Span::styled("M", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
@@ -571,24 +866,23 @@ fn draw_llm_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
             content.push(Line::from(vec![
                 Span::raw("Presiona "),
                 Span::styled("A", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-                Span::raw(" para realizar un análisis con los datos recopilados.")
+                Span::raw(" para realizar un análisis con los datos recopilados, o "),
+                Span::styled("D", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::raw(" para un análisis heurístico sin IA.")
             ]));
         }
         
-        let paragraph = Paragraph::new(content)
-            .block(Block::default().borders(Borders::ALL).title(" Análisis LLM "))
-            .alignment(ratatui::layout::Alignment::Center)
-            .wrap(Wrap { trim: true });
-        
-        frame.render_widget(paragraph, area);
-    } else {
-        // Mensaje cuando no hay proceso seleccionado
-        let content = vec![
-            Line::from(vec![
-                Span::styled("Selecciona un proceso para analizar", 
-                    Style::default().fg(Color::Gray))
-            ]),
-        ];
+        // Notas que acompañarán al próximo análisis
+        if !app.analyst_notes.is_empty() {
+            content.push(Line::from(""));
+            content.push(Line::from(Span::styled(
+                "Notas del analista para el análisis:",
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            )));
+            for note in &app.analyst_notes {
+                content.push(Line::from(Span::styled(format!("• {}", note), Style::default().fg(Color::Gray))));
+            }
+        }
         
         let paragraph = Paragraph::new(content)
             .block(Block::default().borders(Borders::ALL).title(" Análisis LLM "))
@@ -599,8 +893,38 @@ fn draw_llm_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+/// Pestañas de los procesos fijados (teclas 1-9) con el estado de cada uno
+fn draw_pinned_tabs(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![Span::styled(" Fijados: ", Style::default().fg(Color::LightYellow))];
+    
+    for (i, pid) in app.pinned.iter().enumerate() {
+        let Some(monitored) = app.monitored.get(pid) else {
+            continue;
+        };
+        // ⏳ analizando, ● monitoreando, ✓ con análisis
+        let marker = if monitored.is_analyzing() {
+            " ⏳"
+        } else if monitored.is_monitoring_active {
+            " ●"
+        } else if monitored.llm_analysis.is_some() {
+            " ✓"
+        } else {
+            ""
+        };
+        let style = if app.selected_pid == Some(*pid) {
+            Style::default().fg(Color::Black).bg(Color::LightCyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::LightCyan)
+        };
+        spans.push(Span::styled(format!(" [{}] {} ({}){} ", i + 1, monitored.name, pid, marker), style));
+        spans.push(Span::raw(" "));
+    }
+    
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
 // Función para convertir markdown simple a spans con formato
-fn convert_markdown_to_spans(markdown: &str) -> Vec<Line> {
+pub(super) fn convert_markdown_to_spans(markdown: &str) -> Vec<Line<'_>> {
     let mut lines = Vec::new();
     
     for line in markdown.lines() {
@@ -2,12 +2,19 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Line},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
+use crate::process::format_memory;
+use crate::reports::{Report, SeverityLevel};
+use crate::ui::app::{finding_key, OpenedReport};
+use crate::ui::widgets::ScrollableText;
 use crate::ui::App;
-use crate::ui::braille_art::{BrailleAnimator, AnimationType};
+use super::process_monitor::convert_markdown_to_spans;
+
+/// Recursos afectados que se listan en el detalle de un hallazgo
+const MAX_AFFECTED_RESOURCES: usize = 10;
 
 pub fn draw_reports(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
@@ -32,38 +39,281 @@ pub fn draw_reports(frame: &mut Frame, app: &mut App) {
     
     frame.render_widget(title, chunks[0]);
     
-    // En construcción - Mostrar una animación
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(" Visor de Reportes en Construcción ")
-        .style(Style::default().fg(Color::Magenta));
-    
-    let inner_area = block.inner(chunks[1]);
-    frame.render_widget(block, chunks[1]);
-    
-    // Animación
-    let braille_width = inner_area.width as usize * 2;
-    let braille_height = inner_area.height as usize * 4;
-    
-    let mut animator = BrailleAnimator::new(braille_width, braille_height, AnimationType::Spiral);
-    animator.update(None);
-    
-    let animation_text = animator.render();
-    let animation_paragraph = Paragraph::new(animation_text);
-    
-    frame.render_widget(animation_paragraph, inner_area);
+    if app.opened_report.is_some() {
+        draw_report_findings(frame, app, chunks[1]);
+    } else if app.opened_diff.is_some() {
+        draw_report_diff(frame, app, chunks[1]);
+    } else {
+        draw_saved_reports(frame, app, chunks[1]);
+    }
     
     // Barra de estado
-    let status_bar = Paragraph::new(Line::from(vec![
+    let mut status_spans = if app.opened_report.is_some() {
+        vec![
+            Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
+            Span::raw("ESC: Volver a los reportes | "),
+            Span::styled("↑↓", Style::default().fg(Color::LightYellow)),
+            Span::raw(": Navegar | "),
+            Span::styled("E", Style::default().fg(Color::LightYellow)),
+            Span::raw(": Explicar hallazgo con IA"),
+        ]
+    } else if app.opened_diff.is_some() {
+        vec![
+            Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
+            Span::raw("ESC: Volver a los reportes | "),
+            Span::styled("↑↓ PgUp/PgDn", Style::default().fg(Color::LightYellow)),
+            Span::raw(": Desplazar"),
+        ]
+    } else {
+        vec![
         Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
         Span::raw("ESC: Volver | "),
         Span::styled("↑↓", Style::default().fg(Color::LightYellow)),
         Span::raw(": Navegar | "),
+        Span::styled("C", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Copiar ruta | "),
+        Span::styled("O", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Abrir carpeta | "),
+        Span::styled("R", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Actualizar | "),
         Span::styled("ENTER", Style::default().fg(Color::LightYellow)),
-        Span::raw(": Ver reporte"),
-    ]))
+        Span::raw(": Ver hallazgos | "),
+        Span::styled("M", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Marcar | "),
+        Span::styled("D", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Comparar con el marcado"),
+        ]
+    };
+    if let Some(status) = &app.status_message {
+        status_spans.push(Span::styled(" 📋 ", Style::default().fg(Color::LightYellow)));
+        status_spans.push(Span::raw(status.clone()));
+    }
+    let status_bar = Paragraph::new(Line::from(status_spans))
     .block(Block::default().borders(Borders::ALL))
     .style(Style::default());
     
     frame.render_widget(status_bar, chunks[2]);
-} 
+}
+
+/// Lista de los archivos del directorio de reportes, del más reciente al más antiguo
+fn draw_saved_reports(frame: &mut Frame, app: &mut App, area: Rect) {
+    let folder = Report::default_dir_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "~/.shadowtrace/reports".to_string());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ({}) ", folder, app.saved_reports.len()))
+        .style(Style::default().fg(Color::Magenta));
+    
+    if app.saved_reports.is_empty() {
+        let empty = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled("No hay reportes guardados todavía.", Style::default().fg(Color::Gray))),
+            Line::from(Span::styled(
+                "Los monitoreos y auditorías de la CLI los guardan aquí al terminar.",
+                Style::default().fg(Color::DarkGray),
+            )),
+        ])
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
+    
+    let items: Vec<ListItem> = app.saved_reports
+        .iter()
+        .map(|file| {
+            let modified = file.modified
+                .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".repeat(16));
+            let name = file.path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let marked = app.diff_mark.as_ref() == Some(&file.path);
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}  ", modified), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:>10}  ", format_memory(file.size)), Style::default().fg(Color::Cyan)),
+                Span::styled(name, Style::default().fg(Color::White)),
+                if marked {
+                    Span::styled("  ◆ marcado", Style::default().fg(Color::LightYellow))
+                } else {
+                    Span::raw("")
+                },
+            ]))
+        })
+        .collect();
+    
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
+        .highlight_symbol(" 👉 ");
+    
+    frame.render_stateful_widget(list, area, &mut app.saved_reports_state);
+}
+
+/// Comparación entre dos reportes, agrupada por categoría como `shadowtrace diff`:
+/// añadidos en verde, eliminados en rojo y cambios de recursos en amarillo
+fn draw_report_diff(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(opened) = app.opened_diff.as_mut() else {
+        return;
+    };
+    let diff = &opened.diff;
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Anterior: ", Style::default().fg(Color::LightYellow)),
+            Span::raw(diff.before.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Posterior: ", Style::default().fg(Color::LightYellow)),
+            Span::raw(diff.after.clone()),
+        ]),
+    ];
+    if diff.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Sin diferencias entre los reportes", Style::default().fg(Color::Gray))));
+    }
+    for section in &diff.sections {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("== {} ==", section.category),
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )));
+        for (marker, items, color) in [
+            ('+', &section.added, Color::Green),
+            ('-', &section.removed, Color::Red),
+            ('~', &section.changed, Color::Yellow),
+        ] {
+            for item in items {
+                lines.push(Line::from(Span::styled(format!("{} {}", marker, item), Style::default().fg(color))));
+            }
+        }
+    }
+    
+    let widget = ScrollableText::new(lines).title("Comparación de reportes");
+    frame.render_stateful_widget(widget, area, &mut opened.scroll);
+}
+
+/// Color de la severidad de un hallazgo
+fn severity_color(severity: SeverityLevel) -> Color {
+    match severity {
+        SeverityLevel::Info => Color::Gray,
+        SeverityLevel::Warning => Color::Yellow,
+        SeverityLevel::Error => Color::LightRed,
+        SeverityLevel::Critical => Color::Red,
+    }
+}
+
+/// Hallazgos del reporte abierto: la lista a la izquierda y, a la derecha, el detalle del
+/// seleccionado con su explicación del LLM si ya se pidió
+fn draw_report_findings(frame: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(area);
+    let Some(opened) = app.opened_report.as_mut() else {
+        return;
+    };
+    let name = opened.path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ({} hallazgos) ", name, opened.report.findings.len()))
+        .style(Style::default().fg(Color::Magenta));
+    
+    if opened.report.findings.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled("El reporte no tiene hallazgos.", Style::default().fg(Color::Gray))))
+            .alignment(ratatui::layout::Alignment::Center)
+            .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
+    
+    let items: Vec<ListItem> = opened.report.findings
+        .iter()
+        .map(|finding| {
+            let explained = app.finding_explanations.contains_key(&finding_key(finding));
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", finding.severity.marker()),
+                    Style::default().fg(severity_color(finding.severity)).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(finding.title.clone(), Style::default().fg(Color::White)),
+                if explained {
+                    Span::styled("  ✓ explicado", Style::default().fg(Color::Green))
+                } else {
+                    Span::raw("")
+                },
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
+        .highlight_symbol(" 👉 ");
+    frame.render_stateful_widget(list, chunks[0], &mut opened.findings_state);
+    
+    let details = finding_details(app);
+    let paragraph = Paragraph::new(details)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(" Hallazgo ").style(Style::default().fg(Color::Blue)));
+    frame.render_widget(paragraph, chunks[1]);
+}
+
+/// Detalle del hallazgo seleccionado y su explicación
+fn finding_details(app: &App) -> Vec<Line<'_>> {
+    let Some(finding) = app.opened_report.as_ref().and_then(OpenedReport::selected_finding) else {
+        return Vec::new();
+    };
+    let label = |text: &str| Span::styled(text.to_string(), Style::default().fg(Color::LightYellow));
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("[{}] ", finding.severity.marker()),
+                Style::default().fg(severity_color(finding.severity)).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(finding.title.clone(), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![label("Momento: "), Span::raw(finding.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string())]),
+        Line::from(""),
+        Line::from(Span::raw(finding.description.clone())),
+    ];
+    if let Some(recommendation) = &finding.recommendation {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![label("Recomendación: "), Span::raw(recommendation.clone())]));
+    }
+    if !finding.affected_resources.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(label("Recursos afectados:")));
+        for resource in finding.affected_resources.iter().take(MAX_AFFECTED_RESOURCES) {
+            lines.push(Line::from(format!("  • {}", resource)));
+        }
+        if finding.affected_resources.len() > MAX_AFFECTED_RESOURCES {
+            lines.push(Line::from(Span::styled(
+                format!("  … y {} más", finding.affected_resources.len() - MAX_AFFECTED_RESOURCES),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+    
+    lines.push(Line::from(""));
+    let key = finding_key(finding);
+    match app.finding_explanations.get(&key) {
+        Some(explanation) => {
+            lines.push(Line::from(Span::styled("Explicación (IA)", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))));
+            lines.extend(convert_markdown_to_spans(explanation));
+        }
+        None if app.finding_explanation_rx.as_ref().is_some_and(|(pending, _)| *pending == key) => {
+            lines.push(Line::from(Span::styled("⏳ Consultando al LLM...", Style::default().fg(Color::Yellow))));
+        }
+        None => lines.push(Line::from(vec![
+            Span::styled("Pulsa ", Style::default().fg(Color::DarkGray)),
+            Span::styled("E", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled(" para que el LLM explique este hallazgo y cómo remediarlo", Style::default().fg(Color::DarkGray)),
+        ])),
+    }
+    lines
+}
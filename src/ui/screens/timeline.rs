@@ -0,0 +1,120 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Line},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::timeline::{TimelineCategory, TimelineEntry, TimelineKind};
+use crate::ui::App;
+use crate::ui::widgets::ChartStyle;
+
+/// Línea de tiempo del proceso seleccionado: sus eventos de archivo y de red intercalados
+/// en orden cronológico, con un icono por tipo y un color por categoría
+pub fn draw_timeline(frame: &mut Frame, app: &mut App) {
+    let size = frame.area();
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),   // Título
+            Constraint::Min(10),     // Eventos
+            Constraint::Length(3),   // Barra de estado
+        ].as_ref())
+        .split(size);
+    
+    let process = app.selected_pid
+        .map(|pid| match app.get_process_by_pid(pid) {
+            Some(process) => format!("{} (PID: {})", process.name, pid),
+            None => format!("PID {}", pid),
+        })
+        .unwrap_or_else(|| "sin proceso seleccionado".to_string());
+    let title = Paragraph::new(Line::from(vec![
+        Span::styled("Línea de Tiempo", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw(format!(" — {}", process)),
+    ]))
+    .alignment(ratatui::layout::Alignment::Center)
+    .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Blue)));
+    frame.render_widget(title, chunks[0]);
+    
+    let entries = app.selected_pid.map(|pid| app.timeline_entries(pid)).unwrap_or_default();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Eventos: {} ({}) ", app.timeline_filter.label(), entries.len()))
+        .style(Style::default().fg(Color::Cyan));
+    
+    if entries.is_empty() {
+        let empty = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled("No hay eventos registrados para este proceso.", Style::default().fg(Color::Gray))),
+            Line::from(Span::styled(
+                "Inicia un monitoreo (tecla 'm' en el monitor de procesos) para registrar su actividad.",
+                Style::default().fg(Color::DarkGray),
+            )),
+        ])
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(block);
+        frame.render_widget(empty, chunks[1]);
+    } else {
+        let chart_style = app.chart_style;
+        let items: Vec<ListItem> = entries.iter().map(|entry| timeline_item(entry, chart_style)).collect();
+        if app.timeline_state.selected().filter(|selected| *selected < entries.len()).is_none() {
+            app.timeline_state.select(Some(entries.len() - 1));
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, chunks[1], &mut app.timeline_state);
+    }
+    
+    let mut status_spans = vec![
+        Span::styled(" ⌨️ ", Style::default().fg(Color::LightYellow)),
+        Span::raw("ESC: Volver | "),
+        Span::styled("↑↓ PgUp/PgDn Inicio/Fin", Style::default().fg(Color::LightYellow)),
+        Span::raw(": Desplazarse | "),
+        Span::styled("T", Style::default().fg(Color::LightYellow)),
+        Span::raw(format!(": Filtro ({})", app.timeline_filter.next().label())),
+    ];
+    if let Some(status) = &app.status_message {
+        status_spans.push(Span::styled(" 📋 ", Style::default().fg(Color::LightYellow)));
+        status_spans.push(Span::raw(status.clone()));
+    }
+    let status_bar = Paragraph::new(Line::from(status_spans))
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default());
+    frame.render_widget(status_bar, chunks[2]);
+}
+
+/// Fila de un evento: hora, icono del tipo, acción coloreada por categoría, objetivo y detalle
+fn timeline_item(entry: &TimelineEntry, chart_style: ChartStyle) -> ListItem<'static> {
+    let icon = match (entry.kind, chart_style) {
+        (TimelineKind::File, ChartStyle::Braille) => "📄",
+        (TimelineKind::Network, ChartStyle::Braille) => "🌐",
+        (TimelineKind::File, ChartStyle::Ascii) => "F",
+        (TimelineKind::Network, ChartStyle::Ascii) => "N",
+    };
+    let color = match entry.category {
+        TimelineCategory::FileAccess => Color::Gray,
+        TimelineCategory::FileWrite => Color::Yellow,
+        TimelineCategory::FileDestructive => Color::LightRed,
+        TimelineCategory::Outbound => Color::LightMagenta,
+        TimelineCategory::Inbound => Color::LightGreen,
+    };
+    let mut spans = vec![
+        Span::styled(
+            format!("{} ", DateTime::<Local>::from(entry.timestamp).format("%H:%M:%S%.3f")),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::raw(format!("{} ", icon)),
+        Span::styled(format!("{:<22} ", entry.action), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::styled(entry.target.clone(), Style::default().fg(Color::White)),
+    ];
+    if let Some(detail) = &entry.detail {
+        spans.push(Span::styled(format!("  ({})", detail), Style::default().fg(Color::DarkGray)));
+    }
+    ListItem::new(Line::from(spans))
+}
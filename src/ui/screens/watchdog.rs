@@ -0,0 +1,38 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Line},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::process::format_memory;
+use crate::ui::App;
+use crate::ui::widgets::display_width;
+
+/// Dibujar el consumo propio de ShadowTrace sobre el borde superior derecho,
+/// en rojo si supera los umbrales de aviso
+pub fn draw_self_usage(frame: &mut Frame, app: &App) {
+    let Some((cpu, memory)) = app.self_usage.filter(|_| app.self_watchdog) else {
+        return;
+    };
+
+    let exceeded = app.self_usage_exceeded();
+    let text = format!(
+        " {}ShadowTrace: {:.1}% CPU · {} ",
+        if exceeded { "⚠️ " } else { "" },
+        cpu,
+        format_memory(memory)
+    );
+    let style = if exceeded {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+
+    // Sin pisar la esquina superior derecha del borde
+    let area = frame.area();
+    let width = (display_width(&text) as u16).min(area.width.saturating_sub(2));
+    let usage_area = Rect::new(area.x + area.width.saturating_sub(width + 1), area.y, width, 1);
+    frame.render_widget(Paragraph::new(Line::from(Span::styled(text, style))), usage_area);
+}
@@ -1,16 +1,16 @@
 use std::io;
 use std::time::{Duration, Instant};
-use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent},
+    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::CrosstermBackend,
-    Terminal, Frame,
+    layout::Rect,
+    Terminal,
 };
 
 use super::App;
@@ -65,7 +65,11 @@ impl Tui {
                 super::app::AppState::NetworkMonitor => screens::draw_network_monitor(frame, app),
                 super::app::AppState::Reports => screens::draw_reports(frame, app),
                 super::app::AppState::Help => screens::draw_help(frame, app),
+                super::app::AppState::ProcessCompare => screens::draw_process_compare(frame, app),
+                super::app::AppState::Timeline => screens::draw_timeline(frame, app),
             }
+            // Consumo propio de ShadowTrace sobre cualquier pantalla
+            screens::draw_self_usage(frame, app);
         })?;
         
         Ok(())
@@ -78,11 +82,18 @@ impl Tui {
                     if let KeyCode::Char(c) = key_event.code {
                         app.status_message = Some(format!("Tecla presionada: {}", c));
                     } else {
-                        app.status_message = Some(format!("Tecla especial presionada"));
+                        app.status_message = Some("Tecla especial presionada".to_string());
                     }
                     
                     app.handle_key_event(key_event);
                 }
+                Event::Resize(width, height) => {
+                    // Ajustar los búferes al nuevo tamaño (limpia la pantalla) y redibujar ya,
+                    // sin esperar a la siguiente vuelta. Los desplazamientos y la selección de
+                    // las listas se recalculan al dibujar con el área nueva.
+                    self.terminal.resize(Rect::new(0, 0, width, height))?;
+                    self.draw(app)?;
+                }
                 _ => {}
             }
         }
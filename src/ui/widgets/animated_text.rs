@@ -1,13 +1,14 @@
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
-    text::{Line, Span, Text},
-    widgets::{Block, StatefulWidget, Widget, Paragraph},
+    style::Style,
+    text::Text,
+    widgets::{Block, StatefulWidget, Widget},
 };
 use std::time::{Duration, Instant};
 
 use crate::ui::braille_art::{AnimationType, BrailleAnimator};
+use crate::ui::widgets::display_width;
 
 /// Tipos de animación para el texto
 pub enum AnimationStyle {
@@ -157,9 +158,9 @@ impl<'a> StatefulWidget for AnimatedText<'a> {
         let mut animator = BrailleAnimator::new(
             self.text.lines.iter().fold(0, |acc, line| {
                 acc + line.spans.iter().fold(0, |line_acc, span| {
-                    line_acc + span.content.chars().count()
+                    line_acc + display_width(&span.content)
                 })
-            }) * 2,  // El doble de caracteres en x para braille
+            }) * 2,  // El doble de celdas en x para braille
             self.text.lines.len() * 4,  // 4 veces en y para braille
             state.get_animation_type(),
         );
@@ -252,7 +253,7 @@ impl ScannerTextState {
             self.position = (self.position as isize + self.direction) as usize;
             
             // Cambiar dirección en los bordes
-            if self.position >= max_width - 3 {
+            if self.position >= max_width.saturating_sub(3) {
                 self.direction = -1;
             } else if self.position == 0 {
                 self.direction = 1;
@@ -331,16 +332,9 @@ impl<'a> StatefulWidget for ScannerText<'a> {
                 );
 
                 // Renderizar el efecto de escáner
-                if !line.is_empty() {
-                    let pos = state.position.min(line.len().saturating_sub(1));
-                    let scanner_width = 3.min(inner_area.width as usize - pos);
-                    
-                    // Extraer la parte a resaltar
-                    let highlight = &line[pos..pos + scanner_width.min(line.len() - pos)];
-                    
-                    // Aplicar el estilo del escáner
+                if let Some((x_offset, highlight)) = scanner_highlight(line, state.position, inner_area.width as usize) {
                     buf.set_string(
-                        inner_area.x + pos as u16,
+                        inner_area.x + x_offset,
                         inner_area.y + i as u16,
                         highlight,
                         self.scanner_style,
@@ -349,5 +343,66 @@ impl<'a> StatefulWidget for ScannerText<'a> {
             }
         }
     }
-} 
+}
+
+/// Calcular el tramo de `line` que resalta el escáner en la posición `position`.
+///
+/// La posición se mide en caracteres, no en bytes, para no cortar nunca un
+/// carácter multibyte (emojis, acentos). Devuelve la columna donde empieza el
+/// resaltado y el texto a resaltar (como máximo 3 caracteres), recortado para
+/// que no sobrepase `width` celdas aunque la línea contenga caracteres anchos.
+fn scanner_highlight(line: &str, position: usize, width: usize) -> Option<(u16, &str)> {
+    let char_count = line.chars().count();
+    if char_count == 0 {
+        return None;
+    }
+
+    let pos = position.min(char_count - 1);
+    let byte_index = |n: usize| line.char_indices().nth(n).map_or(line.len(), |(idx, _)| idx);
+    let start = byte_index(pos);
+
+    // La columna depende del ancho en pantalla de lo anterior, no del número de bytes
+    let x_offset = display_width(&line[..start]);
+    if x_offset >= width {
+        return None;
+    }
+
+    let mut end = start;
+    let mut used = 0;
+    for ch in line[start..].chars().take(3) {
+        let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
+        if x_offset + used + ch_width > width {
+            break;
+        }
+        used += ch_width;
+        end += ch.len_utf8();
+    }
+
+    if end == start {
+        return None;
+    }
+    Some((x_offset as u16, &line[start..end]))
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanner_highlight_slices_on_char_boundaries() {
+        assert_eq!(scanner_highlight("héllo", 1, 80), Some((1, "éll")));
+        assert_eq!(scanner_highlight("añ🔥ü", 1, 80), Some((1, "ñ🔥ü")));
+        // Una posición fuera de la línea se queda en el último carácter
+        assert_eq!(scanner_highlight("abc", 10, 80), Some((2, "c")));
+        assert_eq!(scanner_highlight("", 0, 80), None);
+    }
+
+    #[test]
+    fn scanner_highlight_uses_display_width() {
+        // El emoji ocupa dos celdas: lo que va detrás empieza en la columna 2
+        assert_eq!(scanner_highlight("🔥ab", 1, 80), Some((2, "ab")));
+        // El resaltado no sobrepasa el ancho disponible
+        assert_eq!(scanner_highlight("a🔥b", 0, 2), Some((0, "a")));
+        assert_eq!(scanner_highlight("abcdef", 4, 3), None);
+    }
+}
@@ -1,14 +1,15 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Size},
-    style::{Color, Style},
-    symbols,
+    layout::Rect,
+    style::Style,
     text::Span,
     widgets::{Block, Widget},
 };
 use crate::ui::braille_art::{BrailleCanvas, Canvas};
+use crate::ui::widgets::display_width;
 
 /// Un widget de gráfico de alta resolución usando caracteres braille
+#[derive(Default)]
 pub struct BrailleChart<'a> {
     /// Block para el widget
     block: Option<Block<'a>>,
@@ -101,18 +102,10 @@ impl<'a> Dataset<'a> {
         self.style = style;
         self
     }
-}
 
-impl<'a> Default for BrailleChart<'a> {
-    fn default() -> Self {
-        Self {
-            block: None,
-            datasets: Vec::new(),
-            style: Style::default(),
-            x_axis: Axis::default(),
-            y_axis: Axis::default(),
-            title: None,
-        }
+    /// Nombre del conjunto de datos
+    pub fn name(&self) -> &'a str {
+        self.name
     }
 }
 
@@ -246,7 +239,7 @@ impl<'a> BrailleChart<'a> {
                 if y_pos < chart_area.y + chart_area.height {
                     let label_x = chart_area.x;
                     buf.set_string(label_x, y_pos, "      ", self.style); // Limpiar espacio
-                    buf.set_span(label_x, y_pos, label, label.content.len().min(6) as u16);
+                    buf.set_span(label_x, y_pos, label, display_width(&label.content).min(6) as u16);
                     buf.set_string(y_axis_x, y_pos, "┤", self.style); // Marca en el eje
                 }
             }
@@ -261,8 +254,8 @@ impl<'a> BrailleChart<'a> {
                 
                 if x_pos < chart_area.x + chart_area.width {
                     let label_y = x_axis_y + 1;
-                    buf.set_span(x_pos.saturating_sub(label.content.len() as u16 / 2), 
-                                label_y, label, label.content.len() as u16);
+                    buf.set_span(x_pos.saturating_sub(display_width(&label.content) as u16 / 2), 
+                                label_y, label, display_width(&label.content) as u16);
                     buf.set_string(x_pos, x_axis_y, "┬", self.style); // Marca en el eje
                 }
             }
@@ -272,7 +265,7 @@ impl<'a> BrailleChart<'a> {
         if let Some(title) = &self.y_axis.title {
             let y_title_x = chart_area.x;
             let y_title_y = graph_area.y;
-            buf.set_span(y_title_x, y_title_y, title, title.content.len().min(6) as u16);
+            buf.set_span(y_title_x, y_title_y, title, display_width(&title.content).min(6) as u16);
         }
 
         // Dibujar título del eje X
@@ -280,15 +273,15 @@ impl<'a> BrailleChart<'a> {
             let x_title_x = graph_area.x + graph_area.width / 2;
             let x_title_y = chart_area.y + chart_area.height - 1;
             buf.set_span(
-                x_title_x.saturating_sub(title.content.len() as u16 / 2),
+                x_title_x.saturating_sub(display_width(&title.content) as u16 / 2),
                 x_title_y,
                 title,
-                title.content.len() as u16,
+                display_width(&title.content) as u16,
             );
         }
     }
 
-    fn draw_dataset(&self, dataset: &Dataset<'a>, canvas: &mut BrailleCanvas, area: &Rect) {
+    fn draw_dataset(&self, dataset: &Dataset<'a>, canvas: &mut BrailleCanvas, _area: &Rect) {
         if dataset.data.is_empty() {
             return;
         }
@@ -1,7 +1,207 @@
 pub mod animated_text;
 pub mod braille_chart;
+pub mod scrollable_text;
 pub mod sparkline_braille;
 
 pub use sparkline_braille::SparklineBraille;
 pub use animated_text::AnimatedText;
-pub use braille_chart::BrailleChart; 
+pub use braille_chart::BrailleChart;
+pub use scrollable_text::{ScrollableText, ScrollableTextState};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+/// Ancho en celdas de terminal de un texto.
+///
+/// A diferencia de `chars().count()` o `len()`, cuenta los caracteres anchos
+/// (CJK, la mayoría de emojis) como dos celdas.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Cómo se dibujan los gráficos y animaciones de la TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartStyle {
+    /// Puntos braille (más resolución; requiere una fuente con esos glifos)
+    Braille,
+    /// Bloques y caracteres ASCII (consolas y fuentes sin braille)
+    Ascii,
+}
+
+impl ChartStyle {
+    /// Estilo por defecto según la terminal: la consola de Linux, las terminales
+    /// "dumb"/VT y los locales que no son UTF-8 no pueden mostrar braille
+    pub fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        if term == "linux" || term == "dumb" || term.starts_with("vt") {
+            return ChartStyle::Ascii;
+        }
+        
+        // La primera variable de locale definida es la que aplica
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        match locale {
+            Some(locale) if !locale.to_uppercase().replace('-', "").contains("UTF8") => ChartStyle::Ascii,
+            _ => ChartStyle::Braille,
+        }
+    }
+}
+
+/// Umbrales de color del uso de CPU y memoria (`[colors]` en la configuración),
+/// compartidos por la lista de procesos, el panel de detalles y los gráficos
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorThresholds {
+    /// CPU (%) a partir de la cual el uso se muestra en amarillo
+    pub cpu_warning: f32,
+    /// CPU (%) a partir de la cual el uso se muestra en rojo
+    pub cpu_critical: f32,
+    /// Memoria (MiB) a partir de la cual el uso se muestra en amarillo
+    pub memory_warning_mib: f64,
+    /// Memoria (MiB) a partir de la cual el uso se muestra en rojo
+    pub memory_critical_mib: f64,
+}
+
+impl Default for ColorThresholds {
+    fn default() -> Self {
+        Self {
+            cpu_warning: 20.0,
+            cpu_critical: 50.0,
+            memory_warning_mib: 512.0,
+            memory_critical_mib: 2048.0,
+        }
+    }
+}
+
+impl ColorThresholds {
+    /// Nivel de un uso de CPU en porcentaje
+    pub fn cpu_level(&self, usage: f32) -> Level {
+        Level::from_thresholds(usage as f64, self.cpu_warning as f64, self.cpu_critical as f64)
+    }
+
+    /// Nivel de un uso de memoria en MiB
+    pub fn memory_level(&self, mib: f64) -> Level {
+        Level::from_thresholds(mib, self.memory_warning_mib, self.memory_critical_mib)
+    }
+
+    /// Color de un uso de CPU en porcentaje
+    pub fn cpu_color(&self, usage: f32) -> Color {
+        self.cpu_level(usage).color()
+    }
+
+    /// Color de un uso de memoria en MiB
+    pub fn memory_color(&self, mib: f64) -> Color {
+        self.memory_level(mib).color()
+    }
+}
+
+/// Nivel de gravedad de un valor en pantalla: uso de recursos o diferencias
+/// entre procesos. Además del color tiene un símbolo y un texto, que el modo
+/// accesible (`accessible`) muestra para no depender solo del color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl Level {
+    /// Nivel según los umbrales (estrictamente por encima de cada uno)
+    fn from_thresholds(value: f64, warning: f64, critical: f64) -> Self {
+        if value > critical {
+            Level::Critical
+        } else if value > warning {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+
+    /// Verde, amarillo o rojo
+    pub fn color(&self) -> Color {
+        match self {
+            Level::Normal => Color::Green,
+            Level::Warning => Color::Yellow,
+            Level::Critical => Color::Red,
+        }
+    }
+
+    /// Símbolo de forma distinta para cada nivel; en estilo ASCII, un carácter
+    /// que también se distingue en la consola de Linux
+    pub fn symbol(&self, style: ChartStyle) -> &'static str {
+        match (self, style) {
+            (Level::Normal, ChartStyle::Braille) => "●",
+            (Level::Warning, ChartStyle::Braille) => "▲",
+            (Level::Critical, ChartStyle::Braille) => "■",
+            (Level::Normal, ChartStyle::Ascii) => "o",
+            (Level::Warning, ChartStyle::Ascii) => "!",
+            (Level::Critical, ChartStyle::Ascii) => "X",
+        }
+    }
+
+    /// Texto corto del nivel, p. ej. `[CRIT]`
+    pub fn token(&self) -> &'static str {
+        match self {
+            Level::Normal => "[OK]",
+            Level::Warning => "[ALTO]",
+            Level::Critical => "[CRIT]",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    #[test]
+    fn display_width_counts_wide_glyphs_as_two_cells() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("añé"), 3);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("🔥 ok"), 5);
+    }
+
+    #[test]
+    fn default_thresholds_map_usage_to_colors() {
+        let thresholds = ColorThresholds::default();
+        assert_eq!(thresholds.cpu_color(5.0), Color::Green);
+        assert_eq!(thresholds.cpu_color(20.0), Color::Green);
+        assert_eq!(thresholds.cpu_color(20.5), Color::Yellow);
+        assert_eq!(thresholds.cpu_color(50.0), Color::Yellow);
+        assert_eq!(thresholds.cpu_color(97.0), Color::Red);
+        assert_eq!(thresholds.memory_color(100.0), Color::Green);
+        assert_eq!(thresholds.memory_color(600.0), Color::Yellow);
+        assert_eq!(thresholds.memory_color(4096.0), Color::Red);
+    }
+
+    #[test]
+    fn configured_thresholds_replace_the_defaults() {
+        let thresholds: ColorThresholds = toml::from_str("cpu_warning = 30.0\ncpu_critical = 80.0").unwrap();
+        assert_eq!(thresholds.memory_warning_mib, ColorThresholds::default().memory_warning_mib);
+        assert_eq!(thresholds.cpu_level(25.0), Level::Normal);
+        assert_eq!(thresholds.cpu_level(60.0), Level::Warning);
+        assert_eq!(thresholds.cpu_color(85.0), Color::Red);
+        assert!(toml::from_str::<ColorThresholds>("cpu_warn = 1.0").is_err());
+    }
+
+    #[test]
+    fn chart_centers_wide_axis_titles_by_display_width() {
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        BrailleChart::new(Vec::new())
+            .x_axis(braille_chart::Axis::default().title("🔥🔥🔥🔥"))
+            .render(area, &mut buf);
+
+        // El área del gráfico empieza tras 6 columnas de etiquetas y mide 34: el título
+        // (8 celdas, 16 bytes) se centra en la columna 23, así que empieza en la 19
+        assert_eq!(buf[(19, 9)].symbol(), "🔥");
+        assert_eq!(buf[(25, 9)].symbol(), "🔥");
+        assert_eq!(buf[(27, 9)].symbol(), " ");
+    }
+}
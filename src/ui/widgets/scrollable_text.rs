@@ -0,0 +1,158 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::ui::widgets::display_width;
+
+/// Líneas que avanza PageUp/PageDown
+const PAGE_LINES: usize = 10;
+
+/// Estado de desplazamiento de un texto más alto que su panel
+#[derive(Debug, Clone, Default)]
+pub struct ScrollableTextState {
+    /// Primera línea visible
+    offset: usize,
+    /// Último desplazamiento posible (lo actualiza el render)
+    max_offset: usize,
+    /// Mantener la vista en el final a medida que el texto crece (como `tail -f`)
+    follow_tail: bool,
+}
+
+impl ScrollableTextState {
+    /// Estado que empieza al principio del texto
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Estado que sigue el final del texto
+    pub fn following() -> Self {
+        Self { follow_tail: true, ..Self::default() }
+    }
+
+    /// Volver a seguir el final del texto
+    pub fn follow_tail(&mut self) {
+        self.follow_tail = true;
+        self.offset = self.max_offset;
+    }
+
+    /// Volver al principio sin seguir el final
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.follow_tail = false;
+    }
+
+    /// Desplazar con ↑/↓, PgUp/PgDn, Home/End; devuelve false si la tecla no es de desplazamiento
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        let current = self.offset.min(self.max_offset);
+        self.offset = match key {
+            KeyCode::Up => current.saturating_sub(1),
+            KeyCode::Down => (current + 1).min(self.max_offset),
+            KeyCode::PageUp => current.saturating_sub(PAGE_LINES),
+            KeyCode::PageDown => (current + PAGE_LINES).min(self.max_offset),
+            KeyCode::Home => 0,
+            KeyCode::End => self.max_offset,
+            _ => return false,
+        };
+        // Llegar al final (o pulsar End) vuelve a seguirlo; subir lo suelta
+        self.follow_tail = self.offset >= self.max_offset;
+        true
+    }
+
+    /// Porcentaje recorrido del texto
+    pub fn percentage(&self) -> u16 {
+        (self.offset.min(self.max_offset) * 100)
+            .checked_div(self.max_offset)
+            .map_or(100, |percentage| percentage as u16)
+    }
+}
+
+/// Texto con bordes, desplazamiento vertical e indicador de posición en el título
+pub struct ScrollableText<'a> {
+    /// Líneas a mostrar
+    lines: Vec<Line<'a>>,
+    /// Título del bloque
+    title: String,
+    /// Instrucciones de navegación mostradas sobre el borde inferior
+    hint: Option<&'a str>,
+}
+
+impl<'a> ScrollableText<'a> {
+    pub fn new(lines: Vec<Line<'a>>) -> Self {
+        Self {
+            lines,
+            title: String::new(),
+            hint: None,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn hint(mut self, hint: &'a str) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Filas que ocupa el texto al ajustarlo a un ancho (aproximación del ajuste por palabras)
+    fn wrapped_height(&self, width: u16) -> usize {
+        let width = width.max(1) as usize;
+        self.lines
+            .iter()
+            .map(|line| {
+                let line_width: usize = line.spans.iter().map(|span| display_width(&span.content)).sum();
+                line_width.div_ceil(width).max(1)
+            })
+            .sum()
+    }
+}
+
+impl<'a> StatefulWidget for ScrollableText<'a> {
+    type State = ScrollableTextState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let total_lines = self.wrapped_height(inner.width);
+        let visible_lines = inner.height as usize;
+
+        state.max_offset = total_lines.saturating_sub(visible_lines);
+        if state.follow_tail {
+            state.offset = state.max_offset;
+        }
+        state.offset = state.offset.min(state.max_offset);
+
+        let scrollable = total_lines > visible_lines;
+        let title = match (scrollable, state.follow_tail) {
+            (true, true) => format!(" {} [{}%] ↓↑ [siguiendo] ", self.title, state.percentage()),
+            (true, false) => format!(" {} [{}%] ↓↑ ", self.title, state.percentage()),
+            (false, _) => format!(" {} ", self.title),
+        };
+
+        Paragraph::new(self.lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: true })
+            .scroll((state.offset.min(u16::MAX as usize) as u16, 0))
+            .render(area, buf);
+
+        // Instrucciones de navegación sobre el borde inferior, solo si hay desplazamiento
+        if let (true, Some(hint)) = (scrollable, self.hint) {
+            // Sin pisar la esquina inferior derecha del borde
+            let hint_width = (display_width(hint) as u16 + 1).min(area.width.saturating_sub(2));
+            let hint_area = Rect::new(
+                area.x + area.width.saturating_sub(hint_width + 1),
+                area.y + area.height.saturating_sub(1),
+                hint_width,
+                1,
+            );
+            Paragraph::new(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))))
+                .alignment(Alignment::Right)
+                .render(hint_area, buf);
+        }
+    }
+}
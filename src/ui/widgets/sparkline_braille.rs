@@ -1,12 +1,13 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Size},
+    layout::Rect,
     style::Style,
-    widgets::{Block, StatefulWidget, Widget},
+    widgets::{Block, Widget},
 };
 use crate::ui::braille_art::{BrailleCanvas, Canvas};
 
 /// Un widget de gráfico tipo sparkline usando caracteres braille para mayor resolución
+#[derive(Default)]
 pub struct SparklineBraille<'a> {
     /// Título del gráfico
     title: Option<&'a str>,
@@ -22,19 +23,6 @@ pub struct SparklineBraille<'a> {
     min: Option<f64>,
 }
 
-impl<'a> Default for SparklineBraille<'a> {
-    fn default() -> Self {
-        Self {
-            title: None,
-            data: &[],
-            style: Style::default(),
-            block: None,
-            max: None,
-            min: None,
-        }
-    }
-}
-
 impl<'a> SparklineBraille<'a> {
     pub fn new(data: &'a [f64]) -> Self {
         Self {
@@ -89,13 +77,13 @@ impl<'a> Widget for SparklineBraille<'a> {
             return;
         }
 
-        // Calcular valores min/max
-        let max = self.max.unwrap_or_else(|| {
-            self.data.iter().fold(f64::MIN, |acc, &x| acc.max(x))
-        });
+        // Calcular valores min/max (un máximo menor que el mínimo se iguala a este)
         let min = self.min.unwrap_or_else(|| {
             self.data.iter().fold(f64::MAX, |acc, &x| acc.min(x))
         });
+        let max = self.max.unwrap_or_else(|| {
+            self.data.iter().fold(f64::MIN, |acc, &x| acc.max(x))
+        }).max(min);
 
         // Crear canvas braille (cada carácter braille tiene 2x4 puntos)
         let width = chart_area.width as usize * 2;
@@ -107,18 +95,18 @@ impl<'a> Widget for SparklineBraille<'a> {
         let x_scale = width as f64 / data_len.max(1) as f64;
         let y_scale = height as f64 / (max - min + 1.0);
 
+        // Con un solo punto no hay línea: se dibuja el punto
+        if data_len == 1 {
+            let y = Self::row(self.data[0], min, max, y_scale, height);
+            canvas.set(0, y, true);
+        }
+
         // Dibujar la línea
-        for i in 0..data_len - 1 {
-            let x1 = (i as f64 * x_scale) as usize;
-            let x2 = ((i + 1) as f64 * x_scale) as usize;
-            let y1 = height - ((self.data[i] - min) * y_scale) as usize;
-            let y2 = height - ((self.data[i + 1] - min) * y_scale) as usize;
-
-            // Limitar a los bordes del canvas
-            let y1 = y1.min(height - 1);
-            let y2 = y2.min(height - 1);
-            let x1 = x1.min(width - 1);
-            let x2 = x2.min(width - 1);
+        for i in 0..data_len.saturating_sub(1) {
+            let x1 = ((i as f64 * x_scale) as usize).min(width - 1);
+            let x2 = (((i + 1) as f64 * x_scale) as usize).min(width - 1);
+            let y1 = Self::row(self.data[i], min, max, y_scale, height);
+            let y2 = Self::row(self.data[i + 1], min, max, y_scale, height);
 
             // Dibujar línea entre puntos
             self.draw_line(&mut canvas, x1, y1, x2, y2);
@@ -140,6 +128,14 @@ impl<'a> Widget for SparklineBraille<'a> {
 }
 
 impl<'a> SparklineBraille<'a> {
+    /// Fila del canvas para un valor. Los valores fuera de [min, max] se dibujan en
+    /// el borde y los no finitos en la base, sin salirse nunca del canvas.
+    fn row(value: f64, min: f64, max: f64, y_scale: f64, height: usize) -> usize {
+        let value = if value.is_finite() { value.max(min).min(max) } else { min };
+        let offset = ((value - min) * y_scale) as usize;
+        height.saturating_sub(offset).min(height - 1)
+    }
+
     // Algoritmo de Bresenham para dibujar líneas
     fn draw_line(&self, canvas: &mut BrailleCanvas, x0: usize, y0: usize, x1: usize, y1: usize) {
         let mut x0 = x0 as isize;
@@ -181,3 +177,54 @@ impl<'a> SparklineBraille<'a> {
         }
     }
 } 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::Borders;
+
+    fn render(widget: SparklineBraille, width: u16, height: u16) -> Buffer {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        widget.render(area, &mut buf);
+        buf
+    }
+
+    fn is_braille(symbol: &str) -> bool {
+        symbol.chars().all(|c| ('\u{2800}'..='\u{28FF}').contains(&c))
+    }
+
+    #[test]
+    fn tiny_areas_do_not_panic() {
+        let data = [1.0, 5.0, 3.0];
+        // El borde se come todo el área: no queda espacio para el gráfico
+        let buf = render(SparklineBraille::new(&data).block(Block::default().borders(Borders::ALL)), 2, 2);
+        assert_eq!(buf[(0, 0)].symbol(), "┌");
+        render(SparklineBraille::new(&data), 0, 0);
+
+        let buf = render(SparklineBraille::new(&data), 1, 1);
+        assert!(is_braille(buf[(0, 0)].symbol()));
+        assert_ne!(buf[(0, 0)].symbol(), "\u{2800}");
+    }
+
+    #[test]
+    fn single_point_is_drawn() {
+        let buf = render(SparklineBraille::new(&[42.0]), 1, 1);
+        assert_ne!(buf[(0, 0)].symbol(), "\u{2800}");
+    }
+
+    #[test]
+    fn out_of_range_values_stay_inside_the_canvas() {
+        let data = [-50.0, 250.0, f64::NAN, f64::INFINITY, 10.0];
+        for (width, height) in [(1, 1), (3, 2), (10, 4)] {
+            let buf = render(SparklineBraille::new(&data).min(0.0).max(100.0), width, height);
+            for y in 0..height {
+                for x in 0..width {
+                    assert!(is_braille(buf[(x, y)].symbol()), "{}x{} en ({}, {})", width, height, x, y);
+                }
+            }
+        }
+        // Máximo menor que el mínimo
+        render(SparklineBraille::new(&data).min(10.0).max(-10.0), 4, 2);
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::capture::SharedCapture;
+use crate::file_monitor::FileMonitor;
+use crate::network::NetworkMonitor;
+
+/// Cada cuánto recorren los hilos los archivos y conexiones del proceso
+const WORKER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pausa máxima entre comprobaciones de la señal de parada
+const SHUTDOWN_POLL: Duration = Duration::from_millis(100);
+
+/// Hilos de monitoreo en segundo plano de un proceso.
+/// Se detienen con `stop()` o al soltar el valor, así que nunca sobreviven al `App`.
+pub struct MonitoringWorkers {
+    shutdown: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl MonitoringWorkers {
+    /// Lanzar los hilos que registran la actividad de archivos y las conexiones del
+    /// proceso con la fuente de captura elegida
+    pub fn spawn(
+        pid: u32,
+        capture: SharedCapture,
+        file_monitor: Arc<Mutex<FileMonitor>>,
+        network_monitor: Arc<Mutex<NetworkMonitor>>,
+    ) -> Self {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let mut handles = Vec::new();
+
+        let files_capture = Arc::clone(&capture);
+        handles.push(spawn_worker("shadowtrace-files", Arc::clone(&shutdown), move || {
+            let mut capture = files_capture.lock().unwrap_or_else(|e| e.into_inner());
+            let mut monitor = file_monitor.lock().unwrap_or_else(|e| e.into_inner());
+            capture.record_files(&mut monitor, pid);
+        }));
+        handles.push(spawn_worker("shadowtrace-network", Arc::clone(&shutdown), move || {
+            let mut capture = capture.lock().unwrap_or_else(|e| e.into_inner());
+            let mut monitor = network_monitor.lock().unwrap_or_else(|e| e.into_inner());
+            capture.record_connections(&mut monitor, pid);
+        }));
+
+        Self { shutdown, handles }
+    }
+
+    /// Número de hilos que siguen en marcha
+    pub fn active_count(&self) -> usize {
+        self.handles.iter().filter(|handle| !handle.is_finished()).count()
+    }
+
+    /// Señalar la parada y esperar a que terminen todos los hilos
+    pub fn stop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        for handle in self.handles.drain(..) {
+            if handle.join().is_err() {
+                tracing::warn!("Un hilo de monitoreo terminó con pánico");
+            }
+        }
+    }
+}
+
+impl Drop for MonitoringWorkers {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Lanzar un hilo que repite `work` hasta que se active la señal de parada.
+/// La espera se hace en tramos cortos para que la parada no tarde un intervalo entero.
+fn spawn_worker<F>(name: &str, shutdown: Arc<AtomicBool>, mut work: F) -> JoinHandle<()>
+where
+    F: FnMut() + Send + 'static,
+{
+    let body = move || {
+        while !shutdown.load(Ordering::SeqCst) {
+            work();
+
+            let mut waited = Duration::ZERO;
+            while waited < WORKER_INTERVAL && !shutdown.load(Ordering::SeqCst) {
+                thread::sleep(SHUTDOWN_POLL);
+                waited += SHUTDOWN_POLL;
+            }
+        }
+    };
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(body)
+        .expect("no se pudo lanzar el hilo de monitoreo")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{CaptureBackend, CaptureSource};
+    use std::sync::atomic::AtomicUsize;
+
+    /// Fuente de captura que solo cuenta las pasadas de los hilos
+    struct CountingCapture(Arc<AtomicUsize>);
+
+    impl CaptureSource for CountingCapture {
+        fn backend(&self) -> CaptureBackend {
+            CaptureBackend::Procfs
+        }
+
+        fn record_files(&mut self, _file_monitor: &mut FileMonitor, _pid: u32) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn record_connections(&mut self, _network_monitor: &mut NetworkMonitor, _pid: u32) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn spawn_counting(passes: &Arc<AtomicUsize>, file_monitor: &Arc<Mutex<FileMonitor>>) -> MonitoringWorkers {
+        let capture: SharedCapture = Arc::new(Mutex::new(Box::new(CountingCapture(Arc::clone(passes)))));
+        let network_monitor = Arc::new(Mutex::new(NetworkMonitor::new()));
+        MonitoringWorkers::spawn(1, capture, Arc::clone(file_monitor), network_monitor)
+    }
+
+    #[test]
+    fn repeated_start_and_stop_leaves_no_threads() {
+        let passes = Arc::new(AtomicUsize::new(0));
+        let file_monitor = Arc::new(Mutex::new(FileMonitor::new()));
+
+        for _ in 0..5 {
+            let mut workers = spawn_counting(&passes, &file_monitor);
+            assert_eq!(workers.active_count(), 2);
+            workers.stop();
+            assert_eq!(workers.active_count(), 0);
+            // Los hilos soltaron su referencia al monitor: ya no queda ninguno vivo
+            assert_eq!(Arc::strong_count(&file_monitor), 1);
+        }
+
+        let after_stop = passes.load(Ordering::SeqCst);
+        thread::sleep(SHUTDOWN_POLL * 3);
+        assert_eq!(passes.load(Ordering::SeqCst), after_stop);
+    }
+
+    #[test]
+    fn dropping_workers_stops_the_threads() {
+        let passes = Arc::new(AtomicUsize::new(0));
+        let file_monitor = Arc::new(Mutex::new(FileMonitor::new()));
+
+        let workers = spawn_counting(&passes, &file_monitor);
+        assert!(Arc::strong_count(&file_monitor) > 1);
+        drop(workers);
+
+        assert_eq!(Arc::strong_count(&file_monitor), 1);
+    }
+}